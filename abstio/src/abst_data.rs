@@ -16,8 +16,12 @@ pub struct Manifest {
 /// A single file
 #[derive(Serialize, Deserialize)]
 pub struct Entry {
-    /// md5sum of the file
+    /// md5sum of the file, used to notice changes
     pub checksum: String,
+    /// SHA-256 of the file, used to verify downloads. Manifests written before this was recorded
+    /// leave it empty.
+    #[serde(default)]
+    pub sha256: String,
     /// Uncompressed size in bytes. Because we have some massive files more than 2^32 bytes
     /// described by this, explicitly use u64 instead of usize, so wasm doesn't break.
     pub uncompressed_size_bytes: u64,
@@ -25,6 +29,53 @@ pub struct Entry {
     pub compressed_size_bytes: u64,
 }
 
+/// What a data file holds, so players can tell what an update changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DataKind {
+    MapGeometry,
+    Scenarios,
+    PrebakedResults,
+    /// Files only used by the map importer
+    Input,
+    /// City metadata, fonts, proposals, etc
+    Other,
+}
+
+impl DataKind {
+    /// Categorize a manifest path, starting with "data/"
+    pub fn from_path(path: &str) -> DataKind {
+        let parts = path.split('/').collect::<Vec<_>>();
+        if parts.get(1) == Some(&"input") {
+            return DataKind::Input;
+        }
+        if parts.len() > 4 {
+            match parts[4] {
+                "maps" => {
+                    return DataKind::MapGeometry;
+                }
+                "scenarios" => {
+                    return DataKind::Scenarios;
+                }
+                "prebaked_results" => {
+                    return DataKind::PrebakedResults;
+                }
+                _ => {}
+            }
+        }
+        DataKind::Other
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            DataKind::MapGeometry => "map geometry",
+            DataKind::Scenarios => "scenarios",
+            DataKind::PrebakedResults => "prebaked results",
+            DataKind::Input => "importer input",
+            DataKind::Other => "other data",
+        }
+    }
+}
+
 impl Manifest {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn load() -> Manifest {
@@ -137,6 +188,36 @@ impl Manifest {
         None
     }
 
+    /// Summarize a list of changed paths by the kind of data they hold, like "2 files with map
+    /// geometry (45 MB)". The sizes are what has to be downloaded.
+    pub fn describe_changes<'a, I: IntoIterator<Item = &'a String>>(
+        &self,
+        paths: I,
+    ) -> Vec<String> {
+        let mut per_kind: BTreeMap<DataKind, (usize, u64)> = BTreeMap::new();
+        for path in paths {
+            let pair = per_kind.entry(DataKind::from_path(path)).or_insert((0, 0));
+            pair.0 += 1;
+            pair.1 += self
+                .entries
+                .get(path)
+                .map(|entry| entry.compressed_size_bytes)
+                .unwrap_or(0);
+        }
+        per_kind
+            .into_iter()
+            .map(|(kind, (files, bytes))| {
+                format!(
+                    "{} file{} with {} ({})",
+                    files,
+                    if files == 1 { "" } else { "s" },
+                    kind.describe(),
+                    abstutil::prettyprint_bytes(bytes)
+                )
+            })
+            .collect()
+    }
+
     /// Look up an entry.
     pub fn get_entry(&self, path: &str) -> Option<&Entry> {
         let path = path.strip_prefix(&crate::path("")).unwrap_or(path);
@@ -332,6 +332,22 @@ pub fn path_prebaked_results(name: &MapName, scenario_name: &str) -> String {
     ))
 }
 
+/// Describes where a set of prebaked results came from
+pub fn path_prebaked_metadata(name: &MapName, scenario_name: &str) -> String {
+    path(format!(
+        "system/{}/{}/prebaked_results/{}/{}_metadata.json",
+        name.city.country, name.city.city, name.map, scenario_name
+    ))
+}
+
+/// Travel times from running a scenario with several random seeds
+pub fn path_prebaked_reliability(name: &MapName, scenario_name: &str) -> String {
+    path(format!(
+        "system/{}/{}/prebaked_results/{}/{}_reliability.bin",
+        name.city.country, name.city.city, name.map, scenario_name
+    ))
+}
+
 pub fn path_scenario(name: &MapName, scenario_name: &str) -> String {
     // TODO Getting complicated. Sometimes we're trying to load, so we should look for .bin, then
     // .json. But when we're writing a custom scenario, we actually want to write a .bin.
@@ -383,6 +399,25 @@ pub fn path_camera_state(name: &MapName) -> String {
     ))
 }
 
+pub fn path_ui_state(app_name: &str) -> String {
+    path(format!("player/ui_state/{}.json", app_name))
+}
+
+/// Pathfinding graphs prepared after applying edits. `key` identifies the map, edits, and routing
+/// params.
+pub fn path_pathfinder_cache(key: u64) -> String {
+    path(format!("player/pathfinder_cache/{:016x}.bin", key))
+}
+
+pub fn path_all_pathfinder_caches() -> String {
+    path_player("pathfinder_cache")
+}
+
+/// Pieces of large files downloaded so far, so failed downloads can resume.
+pub fn path_partial_downloads() -> String {
+    path_player("partial_downloads")
+}
+
 pub fn path_edits(name: &MapName, edits_name: &str) -> String {
     path(format!(
         "player/edits/{}/{}/{}/{}.json",
@@ -396,6 +431,14 @@ pub fn path_all_edits(name: &MapName) -> String {
     ))
 }
 
+/// A bike share system designed for a map
+pub fn path_bike_share(name: &MapName) -> String {
+    path(format!(
+        "player/bike_share/{}/{}/{}.json",
+        name.city.country, name.city.city, name.map
+    ))
+}
+
 pub fn path_ltn_proposals(name: &MapName, proposal_name: &str) -> String {
     path(format!(
         "player/ltn_proposals/{}/{}/{}/{}.bin",
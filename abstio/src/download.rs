@@ -1,10 +1,15 @@
-use std::io::Write;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use futures_channel::mpsc;
+use sha2::{Digest, Sha256};
 
 use abstutil::prettyprint_usize;
 
+use crate::Entry;
+
 /// Downloads bytes from a URL. This must be called with a tokio runtime somewhere. The caller
 /// creates an mpsc channel pair and provides the sender. Progress will be described through it.
 pub async fn download_bytes<I: AsRef<str>>(
@@ -55,6 +60,220 @@ pub async fn download_bytes<I: AsRef<str>>(
     Ok(bytes)
 }
 
+/// Large files are downloaded in pieces of this size, so a failure only loses one piece.
+const CHUNK_SIZE_BYTES: u64 = 8 * 1024 * 1024;
+/// How many pieces to download at the same time
+const MAX_PARALLEL_CHUNKS: usize = 4;
+const ATTEMPTS_PER_CHUNK: usize = 3;
+
+/// Downloads the compressed version of a file in the manifest. Large files are fetched in
+/// pieces, a few at a time, using HTTP range requests. Each piece is saved to disk once it's done,
+/// so if the download fails or the app is closed, calling this again only fetches the missing
+/// pieces. The pieces are deleted when the whole file is returned. This must be called with a
+/// tokio runtime somewhere.
+pub async fn download_bytes_resumable<I: AsRef<str>>(
+    url: I,
+    entry: &Entry,
+    progress: &mut mpsc::Sender<String>,
+) -> Result<Vec<u8>> {
+    let url = url.as_ref().to_string();
+    let total_size = entry.compressed_size_bytes;
+    if total_size <= CHUNK_SIZE_BYTES {
+        return download_bytes(url, None, progress).await;
+    }
+
+    // Pieces of an old version of the file at the same URL must not be mixed in, so key by the
+    // contents
+    let dir = format!("{}/{}", crate::path_partial_downloads(), entry.checksum);
+    fs_err::create_dir_all(&dir)?;
+    let num_chunks = ((total_size + CHUNK_SIZE_BYTES - 1) / CHUNK_SIZE_BYTES) as usize;
+    let chunk_path = |idx: usize| format!("{}/{}", dir, idx);
+    let chunk_range = |idx: usize| {
+        let start = (idx as u64) * CHUNK_SIZE_BYTES;
+        (start, (start + CHUNK_SIZE_BYTES).min(total_size))
+    };
+
+    let bytes_so_far = Arc::new(AtomicU64::new(0));
+    let mut missing = Vec::new();
+    for idx in 0..num_chunks {
+        let (start, end) = chunk_range(idx);
+        if fs_err::metadata(chunk_path(idx))
+            .map(|m| m.len() == end - start)
+            .unwrap_or(false)
+        {
+            bytes_so_far.fetch_add(end - start, Ordering::Relaxed);
+        } else {
+            missing.push(idx);
+        }
+    }
+    if missing.len() < num_chunks {
+        info!(
+            "Resuming {}, with {} of {} pieces left",
+            url,
+            missing.len(),
+            num_chunks
+        );
+    } else {
+        info!("Downloading {} in {} pieces", url, num_chunks);
+    }
+
+    for batch in missing.chunks(MAX_PARALLEL_CHUNKS) {
+        let mut tasks = Vec::new();
+        for idx in batch {
+            let (start, end) = chunk_range(*idx);
+            tasks.push(tokio::spawn(download_chunk(
+                url.clone(),
+                (start, end),
+                total_size,
+                chunk_path(*idx),
+                bytes_so_far.clone(),
+                progress.clone(),
+            )));
+        }
+        // Let the whole batch finish, so the pieces that worked are kept
+        let mut first_err = None;
+        for task in tasks {
+            if let Err(err) = task.await.map_err(anyhow::Error::from).and_then(|x| x) {
+                first_err.get_or_insert(err);
+            }
+        }
+        if let Some(err) = first_err {
+            return Err(err);
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(total_size as usize);
+    for idx in 0..num_chunks {
+        fs_err::File::open(chunk_path(idx))?.read_to_end(&mut bytes)?;
+    }
+    fs_err::remove_dir_all(&dir)?;
+    Ok(bytes)
+}
+
+async fn download_chunk(
+    url: String,
+    range: (u64, u64),
+    total_size: u64,
+    path: String,
+    bytes_so_far: Arc<AtomicU64>,
+    mut progress: mpsc::Sender<String>,
+) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match download_range(&url, range, total_size, &bytes_so_far, &mut progress).await {
+            Ok(bytes) => {
+                fs_err::write(&path, bytes)?;
+                return Ok(());
+            }
+            Err(err) => {
+                if attempt == ATTEMPTS_PER_CHUNK {
+                    return Err(err).with_context(|| format!("{} bytes {:?}", url, range));
+                }
+                warn!(
+                    "Retrying {} bytes {:?} after attempt {}: {}",
+                    url, range, attempt, err
+                );
+                attempt += 1;
+            }
+        }
+    }
+}
+
+async fn download_range(
+    url: &str,
+    (start, end): (u64, u64),
+    total_size: u64,
+    bytes_so_far: &AtomicU64,
+    progress: &mut mpsc::Sender<String>,
+) -> Result<Vec<u8>> {
+    let mut resp = reqwest::Client::new()
+        .get(url)
+        .header(
+            reqwest::header::RANGE,
+            format!("bytes={}-{}", start, end - 1),
+        )
+        .send()
+        .await?;
+    resp.error_for_status_ref()?;
+    if resp.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        bail!("the server doesn't support range requests");
+    }
+
+    let mut bytes = Vec::new();
+    loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => {
+                bytes.extend_from_slice(&chunk);
+                let n = bytes_so_far.fetch_add(chunk.len() as u64, Ordering::Relaxed)
+                    + chunk.len() as u64;
+                if let Err(err) = progress.try_send(format!(
+                    "{:.2}% ({} / {} bytes)",
+                    (n as f64) / (total_size as f64) * 100.0,
+                    prettyprint_usize(n as usize),
+                    prettyprint_usize(total_size as usize)
+                )) {
+                    warn!("Couldn't send download progress message: {}", err);
+                }
+            }
+            Ok(None) => break,
+            Err(err) => {
+                // This attempt's progress is lost
+                bytes_so_far.fetch_sub(bytes.len() as u64, Ordering::Relaxed);
+                return Err(err.into());
+            }
+        }
+    }
+    if bytes.len() as u64 != end - start {
+        bytes_so_far.fetch_sub(bytes.len() as u64, Ordering::Relaxed);
+        bail!("got {} bytes, but expected {}", bytes.len(), end - start);
+    }
+    Ok(bytes)
+}
+
+/// Calculates the md5sum of a file, like the manifest records. Large files are read in pieces.
+pub fn md5sum_file(path: &str) -> Result<String> {
+    let mut file = fs_err::File::open(path)?;
+    let mut buffer = [0_u8; 4096];
+    let mut context = md5::Context::new();
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        context.consume(&buffer[..n]);
+    }
+    Ok(format!("{:x}", context.compute()))
+}
+
+/// Calculates the SHA-256 of a file, like the manifest records. Large files are read in pieces.
+pub fn sha256sum_file(path: &str) -> Result<String> {
+    let mut file = fs_err::File::open(path)?;
+    let mut buffer = [0_u8; 4096];
+    let mut hasher = Sha256::new();
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Checks a downloaded file against its manifest entry. Older manifests without a SHA-256 can
+/// only be checked against the md5sum.
+pub fn verify_file(path: &str, entry: &Entry) -> Result<()> {
+    let (actual, expected) = if entry.sha256.is_empty() {
+        (md5sum_file(path)?, &entry.checksum)
+    } else {
+        (sha256sum_file(path)?, &entry.sha256)
+    };
+    if &actual != expected {
+        bail!("checksum is {}, but the manifest says {}", actual, expected);
+    }
+    Ok(())
+}
+
 /// Download a file from a URL. This must be called with a tokio runtime somewhere. Progress will
 /// be printed to STDOUT.
 pub async fn download_to_file<I1: AsRef<str>, I2: AsRef<str>>(
@@ -88,6 +88,14 @@ fn maybe_write_binary<T: Serialize>(path: &str, obj: &T) -> Result<()> {
     bincode::serialize_into(file, obj).map_err(|err| err.into())
 }
 
+/// Save a file the user asked to export, like GeoJSON or CSV. Returns the path where the file was
+/// written.
+pub fn write_file(path: String, contents: String) -> Result<String> {
+    let mut file = File::create(&path)?;
+    write!(file, "{}", contents)?;
+    Ok(path)
+}
+
 pub fn write_binary<T: Serialize>(path: String, obj: &T) {
     if let Err(err) = maybe_write_binary(&path, obj) {
         panic!("Can't write_binary({}): {}", path, err);
@@ -1,5 +1,6 @@
 //! Since the local filesystem can't be read from a web browser, instead bundle system data files in
-//! the WASM binary using include_dir. For now, no support for saving files.
+//! the WASM binary using include_dir. Player data is saved in local storage, and exported files
+//! are downloaded by the browser.
 
 use std::collections::BTreeSet;
 
@@ -121,6 +122,34 @@ pub fn write_json<T: Serialize>(path: String, obj: &T) {
     storage.set_item(&path, &abstutil::to_json(obj)).unwrap();
 }
 
+/// Save a file the user asked to export, like GeoJSON or CSV. The browser downloads it. Returns
+/// the filename.
+pub fn write_file(path: String, contents: String) -> Result<String> {
+    use wasm_bindgen::JsCast;
+
+    let data: String = js_sys::JsString::from("data:text/json;charset=utf-8,")
+        .concat(&js_sys::encode_uri_component(&contents))
+        .into();
+
+    let window = web_sys::window().ok_or_else(|| anyhow!("no window"))?;
+    let document = window.document().ok_or_else(|| anyhow!("no document"))?;
+    let node = document
+        .create_element("a")
+        .map_err(|err| anyhow!("couldn't create a link: {:?}", err))?
+        .dyn_into::<web_sys::HtmlElement>()
+        .map_err(|err| anyhow!("link isn't an HtmlElement: {:?}", err))?;
+    node.set_attribute("href", &data)
+        .and_then(|_| node.set_attribute("download", &path))
+        .map_err(|err| anyhow!("couldn't set up the link: {:?}", err))?;
+    let body = document.body().ok_or_else(|| anyhow!("no document body"))?;
+    body.append_child(&node)
+        .map_err(|err| anyhow!("couldn't add the link: {:?}", err))?;
+    node.click();
+    node.remove();
+
+    Ok(path)
+}
+
 pub fn write_binary<T: Serialize>(path: String, obj: &T) {
     // Only save for data/player, for now
     if !path.starts_with(&path_player("")) {
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 /// ## On native: uses env_log
 ///
 /// You can adjust the log level without recompiling with the RUST_LOG env variable.
@@ -47,3 +49,61 @@ pub fn setup() {
         Builder::from_env(Env::default().default_filter_or("info")).init();
     }
 }
+
+static JSON_LOGS: AtomicBool = AtomicBool::new(false);
+
+/// Like `setup`, but for tools run by other programs. Every log message, along with all progress
+/// that `Timer` would normally print, goes to STDOUT as one JSON object per line. Each object has
+/// a `timestamp` (seconds since the Unix epoch), a `category`, and a `message`. Log messages are
+/// in the "log" category and also have a `level` and `target`; `Timer` output is "progress".
+/// Callers can emit their own categories with `print_json_line`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn setup_json() {
+    use env_logger::{Builder, Env, Target};
+    use std::io::Write;
+
+    JSON_LOGS.store(true, Ordering::SeqCst);
+    Builder::from_env(Env::default().default_filter_or("info"))
+        .target(Target::Stdout)
+        .format(|buf, record| {
+            let mut fields = serde_json::Map::new();
+            fields.insert("level".to_string(), record.level().to_string().into());
+            fields.insert("target".to_string(), record.target().into());
+            writeln!(
+                buf,
+                "{}",
+                json_line("log", &record.args().to_string(), fields)
+            )
+        })
+        .init();
+}
+
+/// Is `setup_json` in effect?
+pub fn json_logs_enabled() -> bool {
+    JSON_LOGS.load(Ordering::SeqCst)
+}
+
+/// Print one JSON object to STDOUT, with the timestamp, category, and message filled out, plus any
+/// extra fields. Only call this when `json_logs_enabled`.
+pub fn print_json_line(
+    category: &str,
+    message: &str,
+    fields: serde_json::Map<String, serde_json::Value>,
+) {
+    println!("{}", json_line(category, message, fields));
+}
+
+fn json_line(
+    category: &str,
+    message: &str,
+    mut fields: serde_json::Map<String, serde_json::Value>,
+) -> String {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    fields.insert("timestamp".to_string(), timestamp.into());
+    fields.insert("category".to_string(), category.into());
+    fields.insert("message".to_string(), message.into());
+    serde_json::Value::Object(fields).to_string()
+}
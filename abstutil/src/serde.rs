@@ -1,9 +1,11 @@
 use std::cmp::Ord;
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::sync::Mutex;
 
 use anyhow::Result;
-use serde::de::DeserializeOwned;
+use once_cell::sync::OnceCell;
+use serde::de::{DeserializeOwned, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::MultiMap;
@@ -149,3 +151,125 @@ pub fn deserialize_usize<'de, D: Deserializer<'de>>(d: D) -> Result<usize, D::Er
     let x = <u32>::deserialize(d)?;
     Ok(x as usize)
 }
+
+/// Something that's expensive to deserialize, but might never be used. It stays in serialized
+/// (bincode) form until the first time it's needed. Changing the value discards the serialized
+/// copy, and serializing it again writes the current value.
+pub struct LazyDeserialize<T> {
+    // Emptied once the value is deserialized
+    bytes: Mutex<Vec<u8>>,
+    value: OnceCell<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> LazyDeserialize<T> {
+    pub fn new(value: T) -> LazyDeserialize<T> {
+        let cell = OnceCell::new();
+        let _ = cell.set(value);
+        LazyDeserialize {
+            bytes: Mutex::new(Vec::new()),
+            value: cell,
+        }
+    }
+
+    /// Deserializes the value if this is the first use. Fails if the bytes are corrupt or were
+    /// written by an incompatible version of `T`.
+    pub fn get(&self) -> Result<&T> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let mut bytes = self.bytes.lock().unwrap();
+        // Another thread may have deserialized it while we waited
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = from_binary(&bytes)?;
+        *bytes = Vec::new();
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    pub fn get_mut(&mut self) -> Result<&mut T> {
+        self.get()?;
+        Ok(self.value.get_mut().unwrap())
+    }
+
+    /// Like `get`, but if the bytes can't be deserialized, the value is built from scratch
+    /// instead.
+    pub fn get_or_rebuild<F: FnOnce() -> T>(&self, rebuild: F) -> &T {
+        match self.get() {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("Can't deserialize lazily, so rebuilding: {}", err);
+                *self.bytes.lock().unwrap() = Vec::new();
+                self.value.get_or_init(rebuild)
+            }
+        }
+    }
+
+    pub fn get_mut_or_rebuild<F: FnOnce() -> T>(&mut self, rebuild: F) -> &mut T {
+        self.get_or_rebuild(rebuild);
+        self.value.get_mut().unwrap()
+    }
+
+    /// Has the value been deserialized yet?
+    pub fn is_loaded(&self) -> bool {
+        self.value.get().is_some()
+    }
+}
+
+impl<T: Clone> Clone for LazyDeserialize<T> {
+    fn clone(&self) -> Self {
+        LazyDeserialize {
+            bytes: Mutex::new(self.bytes.lock().unwrap().clone()),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for LazyDeserialize<T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if let Some(value) = self.value.get() {
+            s.serialize_bytes(&to_binary(value))
+        } else {
+            s.serialize_bytes(&self.bytes.lock().unwrap())
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for LazyDeserialize<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let bytes = d.deserialize_byte_buf(BytesVisitor)?;
+        Ok(LazyDeserialize {
+            bytes: Mutex::new(bytes),
+            value: OnceCell::new(),
+        })
+    }
+}
+
+// Reading bytes all at once is much faster than going through Vec<u8>, which handles each byte
+// separately.
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "bytes")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    // Formats like JSON represent bytes as a list
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::new();
+        while let Some(x) = seq.next_element()? {
+            bytes.push(x);
+        }
+        Ok(bytes)
+    }
+}
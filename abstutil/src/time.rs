@@ -1,4 +1,5 @@
 use std::io::{stdout, BufReader, ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use anyhow::{Context, Result};
 use fs_err::File;
@@ -6,6 +7,24 @@ use instant::Instant;
 
 use crate::{prettyprint_usize, PROGRESS_FREQUENCY_SECONDS};
 
+/// If nonzero, caps the number of threads used by `Timer::parallelize`.
+static MAX_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Limit how many threads `Timer::parallelize` and `parallelize_polite` may use. Pass `None` to
+/// use all available CPUs again. This is mostly useful to check that results don't depend on the
+/// number of threads.
+pub fn set_max_threads(max: Option<usize>) {
+    MAX_THREADS.store(max.unwrap_or(0), Ordering::SeqCst);
+}
+
+fn num_threads(available: usize) -> u32 {
+    let available = available.max(1);
+    match MAX_THREADS.load(Ordering::SeqCst) {
+        0 => available as u32,
+        max => available.min(max) as u32,
+    }
+}
+
 pub fn elapsed_seconds(since: Instant) -> f64 {
     let dt = since.elapsed();
     (dt.as_secs() as f64) + (f64::from(dt.subsec_nanos()) * 1e-9)
@@ -58,8 +77,7 @@ impl Progress {
             if self.total_items == 1 {
                 temporary_println(maybe_sink, line.clone());
             } else {
-                clear_current_line();
-                println!("{}", line);
+                println_over_progress(&line);
                 if let Some(ref mut sink) = maybe_sink {
                     sink.reprintln(line.clone());
                 }
@@ -74,9 +92,7 @@ impl Progress {
                 prettyprint_usize(self.total_items),
                 prettyprint_time(elapsed_seconds(self.started_at))
             );
-            clear_current_line();
-            print!("{}", line);
-            stdout().flush().unwrap();
+            print_progress(&line);
 
             if let Some(ref mut sink) = maybe_sink {
                 if self.first_update {
@@ -300,7 +316,7 @@ impl<'a> Timer<'a> {
         O: Send,
         F: Send + Clone + Copy,
     {
-        self.inner_parallelize(timer_name, requests, cb, num_cpus::get().max(1) as u32)
+        self.inner_parallelize(timer_name, requests, cb, num_threads(num_cpus::get()))
     }
 
     /// Like `parallelize`, but leave one CPU free, to avoid thrashing the user's system.
@@ -315,12 +331,7 @@ impl<'a> Timer<'a> {
         O: Send,
         F: Send + Clone + Copy,
     {
-        self.inner_parallelize(
-            timer_name,
-            requests,
-            cb,
-            (num_cpus::get() - 1).max(1) as u32,
-        )
+        self.inner_parallelize(timer_name, requests, cb, num_threads(num_cpus::get() - 1))
     }
 
     fn inner_parallelize<I, O, F: Fn(I) -> O>(
@@ -518,8 +529,7 @@ impl<'a> Read for Timer<'a> {
                 if file.last_printed_at.is_none() {
                     self.temporary_println(line.clone());
                 } else {
-                    clear_current_line();
-                    println!("{}", line);
+                    println_over_progress(&line);
                     if let Some(ref mut sink) = self.sink {
                         sink.reprintln(line.clone());
                     }
@@ -538,10 +548,7 @@ impl<'a> Read for Timer<'a> {
                     prettyprint_usize(file.total_bytes / 1024 / 1024),
                     prettyprint_time(elapsed_seconds(file.started_at))
                 );
-                // TODO Refactor this pattern...
-                clear_current_line();
-                print!("{}", line);
-                stdout().flush().unwrap();
+                print_progress(&line);
 
                 if let Some(ref mut sink) = self.sink {
                     if file.last_printed_at.is_none() {
@@ -563,7 +570,11 @@ impl<'a> Read for Timer<'a> {
 fn temporary_println<'a>(maybe_sink: &mut Option<Box<dyn TimerSink + 'a>>, line: String) {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        println!("{}", line);
+        if crate::logger::json_logs_enabled() {
+            crate::logger::print_json_line("progress", &line, serde_json::Map::new());
+        } else {
+            println!("{}", line);
+        }
     }
     #[cfg(target_arch = "wasm32")]
     {
@@ -574,6 +585,27 @@ fn temporary_println<'a>(maybe_sink: &mut Option<Box<dyn TimerSink + 'a>>, line:
     }
 }
 
+// Print an in-progress update, to be overwritten by the next one.
+fn print_progress(line: &str) {
+    if crate::logger::json_logs_enabled() {
+        crate::logger::print_json_line("progress", line, serde_json::Map::new());
+        return;
+    }
+    clear_current_line();
+    print!("{}", line);
+    stdout().flush().unwrap();
+}
+
+// Replace the in-progress update with a final line.
+fn println_over_progress(line: &str) {
+    if crate::logger::json_logs_enabled() {
+        crate::logger::print_json_line("progress", line, serde_json::Map::new());
+        return;
+    }
+    clear_current_line();
+    println!("{}", line);
+}
+
 // Print info about a completed Timer. Always uses info logs, so works on native and web.
 fn finalized_println<'a>(maybe_sink: &mut Option<Box<dyn TimerSink + 'a>>, line: String) {
     info!("{}", line);
@@ -132,6 +132,16 @@ impl State<App> for BusExperiment {
             if x == "15-minute neighborhoods" {
                 return Transition::Pop;
             }
+            if x == "add a waypoint by address" {
+                return Transition::Push(InputWaypoints::search_address_state(
+                    ctx,
+                    |state: &mut BusExperiment, ctx, app: &mut App, at| {
+                        if state.waypoints.add(app, at) {
+                            state.recalculate_everything(ctx, app);
+                        }
+                    },
+                ));
+            }
         }
 
         let world_outcome = self.world.event(ctx);
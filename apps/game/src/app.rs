@@ -7,15 +7,17 @@ use structopt::StructOpt;
 
 use abstio::MapName;
 use abstutil::Timer;
-use geom::{Bounds, Circle, Distance, Duration, FindClosest, Polygon, Pt2D, Time};
+use geom::{Bounds, Circle, Distance, Duration, FindClosest, Polygon, Pt2D, Speed, Time};
 use map_gui::colors::ColorScheme;
 use map_gui::options::Options;
 use map_gui::render::{unzoomed_agent_radius, AgentCache, DrawMap, DrawOptions, Renderable};
-use map_gui::tools::CameraState;
+use map_gui::tools::{CameraState, GeoJsonOverlay, UiState};
 use map_gui::ID;
 use map_model::AreaType;
-use map_model::{BufferType, IntersectionID, LaneType, Map, Traversable};
-use sim::{AgentID, Analytics, Sim, SimCallback, SimFlags, VehicleType};
+use map_model::{BufferType, IntersectionID, LaneType, Map, StressThresholds, Traversable};
+#[cfg(not(target_arch = "wasm32"))]
+use sim::BackgroundSim;
+use sim::{AgentID, Analytics, SavestateHistory, Sim, SimCallback, SimFlags, VehicleType};
 use synthpop::Scenario;
 use widgetry::mapspace::ToggleZoomed;
 use widgetry::{Cached, Canvas, EventCtx, GfxCtx, Prerender, SharedAppState, State};
@@ -30,6 +32,9 @@ use crate::sandbox::{GameplayMode, TutorialState};
 // Convenient typedef
 pub type Transition = widgetry::Transition<App>;
 
+/// When rewinding is enabled, snapshots of the simulation use at most this many bytes
+const SAVESTATE_MEMORY_LIMIT: usize = 1 << 30;
+
 /// The top-level data that lasts through the entire game, no matter what state the game is in.
 pub struct App {
     /// State (like the simulation and drawing stuff) associated with the "primary" map.
@@ -48,6 +53,8 @@ pub struct App {
 
     /// Static data that lasts the entire session. Use sparingly.
     pub session: SessionState,
+    /// Remembered between sessions
+    pub ui_state: UiState,
 }
 
 impl App {
@@ -149,6 +156,9 @@ impl App {
                 g.redraw(&draw_map.draw_all_buildings);
                 g.redraw(&draw_map.draw_all_building_outlines);
             }
+            if layers.show_lanes {
+                draw_map.road_labels.draw(g, self);
+            }
 
             // Still show some shape selection when zoomed out.
             // TODO Refactor! Ideally use get_obj
@@ -466,6 +476,16 @@ impl App {
         let primary = std::mem::replace(&mut self.primary, secondary);
         self.secondary = Some(primary);
     }
+
+    /// Remember the current map and layer for the next session.
+    pub fn save_ui_state(&self) {
+        let mut ui_state = self.ui_state.clone();
+        // If the last layer hasn't been restored yet, keep it
+        if let Some(name) = self.primary.layer.as_ref().and_then(|l| l.name()) {
+            ui_state.last_layer = Some(name.to_string());
+        }
+        ui_state.save_with_map(self.primary.map.get_name());
+    }
 }
 
 impl App {
@@ -637,6 +657,10 @@ pub struct PerMap {
     pub layer: Option<Box<dyn Layer>>,
     /// Only filled out in edit mode. Stored here once to avoid lots of clones. Used for preview.
     pub suspended_sim: Option<Sim>,
+    /// While this is running, `sim` is just a snapshot of it. Changes to `sim` will be lost, so
+    /// call `stop_background_sim` first.
+    #[cfg(not(target_arch = "wasm32"))]
+    background_sim: Option<BackgroundSim>,
     /// Only exists in some gameplay modes. Must be carefully reset otherwise. Has the map and
     /// scenario name too.
     // TODO Embed that in Analytics directly instead.
@@ -650,6 +674,9 @@ pub struct PerMap {
 
     /// Is this the original "secondary" state, loaded via --diff?
     pub is_secondary: bool,
+
+    /// Snapshots of the current simulation run, for quickly rewinding
+    pub savestates: SavestateHistory,
 }
 
 impl PerMap {
@@ -686,9 +713,16 @@ impl PerMap {
             unedited_map: None,
             layer: None,
             suspended_sim: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            background_sim: None,
             prebaked: None,
             scenario: None,
             is_secondary: false,
+            savestates: SavestateHistory::new(
+                Duration::minutes(30),
+                Duration::minutes(1),
+                SAVESTATE_MEMORY_LIMIT,
+            ),
         }
     }
 
@@ -704,6 +738,7 @@ impl PerMap {
 
     /// Returns whatever was there
     pub fn clear_sim(&mut self) -> Sim {
+        self.stop_background_sim();
         self.dirty_from_edits = false;
         std::mem::replace(
             &mut self.sim,
@@ -711,6 +746,42 @@ impl PerMap {
         )
     }
 
+    /// Advance the simulation on a background thread at `speed` times realtime, starting the thread
+    /// if needed. Returns true if `sim` was replaced by a newer snapshot.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn step_in_background(&mut self, speed: f64) -> bool {
+        if let Some(ref bg) = self.background_sim {
+            bg.set_speed(speed);
+            if let Some(snapshot) = bg.take_snapshot() {
+                self.sim = snapshot;
+                return true;
+            }
+            return false;
+        }
+        self.background_sim = Some(BackgroundSim::start(
+            self.sim.clone(),
+            self.map.clone(),
+            speed,
+            Duration::seconds(0.1),
+        ));
+        false
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn step_in_background(&mut self, _: f64) -> bool {
+        unreachable!()
+    }
+
+    /// If the simulation is running on a background thread, stop it and take back control.
+    pub fn stop_background_sim(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(bg) = self.background_sim.take() {
+                self.sim = bg.stop();
+            }
+        }
+    }
+
     pub fn canonical_point(&self, id: ID) -> Option<Pt2D> {
         match id {
             ID::Road(id) => self.map.maybe_get_r(id).map(|r| r.center_pts.first_pt()),
@@ -742,6 +813,18 @@ pub struct SessionState {
     pub last_gmns_timing_csv: Option<String>,
     pub dash_tab: DashTab,
     pub buffer_lane_type: LaneType,
+    pub school_run_baseline: Option<crate::sandbox::dashboards::SchoolRunReport>,
+    /// Defines high-stress roads for cycling everywhere in the app
+    pub bike_stress: StressThresholds,
+    /// A GeoJSON file dropped onto the window, drawn as a reference layer
+    pub geojson_overlay: Cached<MapName, GeoJsonOverlay>,
+    /// Real-world collisions, loaded the first time they're needed for each map
+    pub collisions: Cached<MapName, Option<crate::layer::collisions::SnappedCollisions>>,
+    /// GPX traces imported by the user, matched to the map
+    pub gpx_traces: Cached<MapName, crate::layer::gpx_traces::MatchedTraces>,
+    /// Traffic signals should give people walking this slowly enough time to cross
+    pub slow_walking_speed: Speed,
+    pub trip_time_budgets: crate::sandbox::TripTimeBudgets,
 
     // Specific to the ungap tool
     pub elevation_contours: Cached<MapName, (FindClosest<Distance>, ToggleZoomed)>,
@@ -766,6 +849,13 @@ impl SessionState {
             last_gmns_timing_csv: None,
             dash_tab: DashTab::TripTable,
             buffer_lane_type: LaneType::Buffer(BufferType::Stripes),
+            school_run_baseline: None,
+            bike_stress: StressThresholds::default(),
+            geojson_overlay: Cached::new(),
+            collisions: Cached::new(),
+            gpx_traces: Cached::new(),
+            slow_walking_speed: crate::layer::crossing_times::DEFAULT_SLOW_WALKING_SPEED,
+            trip_time_budgets: crate::sandbox::TripTimeBudgets::default(),
 
             elevation_contours: Cached::new(),
             routing_preferences: crate::ungap::RoutingPreferences::default(),
@@ -829,6 +919,7 @@ impl SharedAppState for App {
             "********************************************************************************"
         );
         CameraState::save(canvas, self.primary.map.get_name());
+        self.save_ui_state();
         println!(
             "Crash! Please report to https://github.com/a-b-street/abstreet/issues/ and include \
              all output.txt; at least everything starting from the stack trace above!"
@@ -868,6 +959,7 @@ impl SharedAppState for App {
 
     fn before_quit(&self, canvas: &Canvas) {
         CameraState::save(canvas, self.primary.map.get_name());
+        self.save_ui_state();
     }
 
     fn free_memory(&mut self) {
@@ -0,0 +1,126 @@
+use geom::Polygon;
+use map_gui::render::DrawMap;
+use widgetry::tools::PopupMsg;
+use widgetry::{
+    Color, DrawBaselayer, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Line, Outcome, Panel,
+    ScreenPt, ScreenRectangle, State, TextExt, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+
+/// Shows two versions of the map side-by-side, sharing one camera. The left side is always the
+/// primary map. The right side is the secondary map if one is loaded, or the map before any edits.
+pub struct LinkedView {
+    panel: Panel,
+    // Only set when comparing against the unedited map, since a secondary map has its own DrawMap
+    unedited: Option<DrawMap>,
+}
+
+impl LinkedView {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let (unedited, right_label) = if let Some(ref secondary) = app.secondary {
+            let label = if app.store_unedited_map_in_secondary {
+                "Before edits".to_string()
+            } else {
+                secondary.map.get_name().describe()
+            };
+            (None, label)
+        } else if let Some(ref map) = app.primary.unedited_map {
+            let draw_map = ctx.loading_screen("render unedited map", |ctx, timer| {
+                DrawMap::new(ctx, map, &app.opts, &app.cs, timer)
+            });
+            (Some(draw_map), "Before edits".to_string())
+        } else {
+            return PopupMsg::new_state(
+                ctx,
+                "Nothing to compare",
+                vec![
+                    "Edit the map or load a second map with --diff_map first.",
+                    "The two versions will be shown side-by-side.",
+                ],
+            );
+        };
+
+        let left_label = if app.primary.map.get_edits().commands.is_empty() {
+            app.primary.map.get_name().describe()
+        } else {
+            format!("With edits \"{}\"", app.primary.map.get_edits().edits_name)
+        };
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Linked view").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            format!("Left: {}", left_label).text_widget(ctx),
+            format!("Right: {}", right_label).text_widget(ctx),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+        .build(ctx);
+
+        Box::new(LinkedView { panel, unedited })
+    }
+}
+
+impl State<App> for LinkedView {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        ctx.canvas_movement();
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.clear(app.cs.void_background);
+
+        let right = match self.unedited {
+            Some(ref draw_map) => draw_map,
+            None => &app.secondary.as_ref().unwrap().draw_map,
+        };
+        let half_width = g.canvas.window_width / 2.0;
+        let height = g.canvas.window_height;
+        let zoom = g.canvas.cam_zoom;
+        // Both halves are centered on whatever the full canvas is centered on
+        let top_left = g
+            .canvas
+            .center_to_map_pt()
+            .offset(-half_width / 2.0 / zoom, -height / 2.0 / zoom);
+
+        for (x1, draw_map) in [(0.0, &app.primary.draw_map), (half_width, right)] {
+            g.fork(top_left, ScreenPt::new(x1, 0.0), zoom, None);
+            g.enable_clipping(ScreenRectangle {
+                x1,
+                y1: 0.0,
+                x2: x1 + half_width,
+                y2: height,
+            });
+            g.redraw(&draw_map.boundary_polygon);
+            g.redraw(&draw_map.draw_all_areas);
+            g.redraw(&draw_map.draw_all_unzoomed_parking_lots);
+            g.redraw(&draw_map.draw_all_unzoomed_roads_and_intersections);
+            g.redraw(&draw_map.draw_all_buildings);
+            g.redraw(&draw_map.draw_all_building_outlines);
+            g.disable_clipping();
+            g.unfork();
+        }
+
+        g.fork_screenspace();
+        let divider = Polygon::rectangle(4.0, height).translate(half_width - 2.0, 0.0);
+        GeomBatch::from(vec![(Color::WHITE, divider)]).draw(g);
+        g.unfork();
+
+        self.panel.draw(g);
+    }
+}
@@ -8,6 +8,7 @@ use widgetry::{
     ScreenPt, ScreenRectangle, Text, TextSpan, VerticalAlignment, Widget,
 };
 
+pub use self::linked_view::LinkedView;
 pub use self::route_sketcher::RouteSketcher;
 pub use self::select::RoadSelector;
 pub use self::warp::{warp_to_id, Warping};
@@ -16,6 +17,7 @@ use crate::app::Transition;
 use crate::info::{ContextualActions, InfoPanel, Tab};
 use crate::sandbox::TimeWarpScreen;
 
+mod linked_view;
 mod route_sketcher;
 mod select;
 pub mod share;
@@ -91,6 +93,12 @@ impl CommonState {
     }
 
     pub fn draw(&self, g: &mut GfxCtx, app: &App) {
+        map_gui::tools::GeoJsonOverlay::draw_cached(
+            g,
+            &app.primary.map,
+            &app.session.geojson_overlay,
+        );
+
         let keys = if let Some(ref info) = self.info_panel {
             info.draw(g, app);
             info.active_keys()
@@ -297,7 +305,8 @@ impl CommonState {
         self.info_panel.as_ref().and_then(|i| i.active_id(app))
     }
 
-    /// Allow toggling of dev mode and warping to an object by ID.
+    /// Allow toggling of dev mode, warping to an object by ID, comparing two versions of the map,
+    /// and dropping in a GeoJSON overlay.
     pub fn debug_actions(ctx: &mut EventCtx, app: &mut App) -> Option<Transition> {
         if ctx.input.pressed(lctrl(Key::S)) {
             app.opts.dev = !app.opts.dev;
@@ -308,6 +317,18 @@ impl CommonState {
         if app.secondary.is_some() && ctx.input.pressed(lctrl(Key::Tab)) {
             app.swap_map();
         }
+        if let Some(state) = map_gui::tools::GeoJsonOverlay::handle_event(
+            ctx,
+            &app.primary.map,
+            &mut app.session.geojson_overlay,
+        ) {
+            return Some(Transition::Push(state));
+        }
+        if (app.secondary.is_some() || app.primary.unedited_map.is_some())
+            && ctx.input.pressed(lctrl(Key::K))
+        {
+            return Some(Transition::Push(LinkedView::new_state(ctx, app)));
+        }
         None
     }
 }
@@ -3,8 +3,9 @@ use std::collections::{BTreeMap, BTreeSet};
 use abstutil::Timer;
 use geom::Distance;
 use map_model::osm::RoadRank;
-use map_model::{Block, Perimeter};
+use map_model::{Block, BlockfindingFailure, Perimeter};
 use widgetry::mapspace::{ObjectID, World, WorldOutcome};
+use widgetry::tools::PopupMsg;
 use widgetry::{
     Color, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line, Outcome, Panel,
     SimpleState, State, Text, TextExt, VerticalAlignment, Widget,
@@ -23,6 +24,8 @@ const COLORS: [Color; 6] = [
 ];
 const MODIFIED: Color = Color::RED;
 const TO_MERGE: Color = Color::CYAN;
+const FAILURE: Color = Color::RED;
+const EXPECTED_FAILURE: Color = Color::grey(0.5);
 
 pub struct Blockfinder {
     panel: Panel,
@@ -34,6 +37,9 @@ pub struct Blockfinder {
     // Since we can't easily color adjacent groups of blocks differently when we classify but don't
     // merge, just remember the groups here
     partitions: Vec<Vec<Obj>>,
+
+    failures: Vec<BlockfindingFailure>,
+    draw_failures: Drawable,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -43,19 +49,24 @@ impl ObjectID for Obj {}
 impl Blockfinder {
     pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
         let mut state = Blockfinder {
-            panel: make_panel(ctx),
+            panel: Panel::empty(ctx),
             id_counter: 0,
             blocks: BTreeMap::new(),
             world: World::bounded(app.primary.map.get_bounds()),
             to_merge: BTreeSet::new(),
 
             partitions: Vec::new(),
+
+            failures: Vec::new(),
+            draw_failures: Drawable::empty(ctx),
         };
 
         ctx.loading_screen("calculate all blocks", |ctx, timer| {
             timer.start("find single blocks");
-            let perimeters = Perimeter::find_all_single_blocks(&app.primary.map);
+            let (perimeters, failures) =
+                Perimeter::find_all_single_blocks_with_failures(&app.primary.map);
             timer.stop("find single blocks");
+            state.failures = failures;
             state.add_blocks_with_coloring(ctx, app, perimeters, timer);
         });
         state.world.initialize_hover(ctx);
@@ -123,20 +134,28 @@ impl Blockfinder {
             // deadends. See https://github.com/a-b-street/abstreet/issues/841.
             let mut copy = perimeter.clone();
             copy.collapse_deadends();
-            if let Err(err) = copy.to_block(&app.primary.map) {
+            if let Err(err) = copy.clone().to_block(&app.primary.map) {
                 error!(
                     "A perimeter won't blockify after collapsing deadends: {}",
                     err
                 );
+                self.failures.push(BlockfindingFailure::new(
+                    &copy,
+                    format!("after collapsing dead-ends: {}", err),
+                ));
+                // We assigned a color, so don't let the indices get out of sync!
+                colors.remove(blocks.len());
                 continue;
             }
 
-            match perimeter.to_block(&app.primary.map) {
+            match perimeter.clone().to_block(&app.primary.map) {
                 Ok(block) => {
                     blocks.push(block);
                 }
                 Err(err) => {
                     warn!("Failed to make a block from a perimeter: {}", err);
+                    self.failures
+                        .push(BlockfindingFailure::new(&perimeter, err.to_string()));
                     // We assigned a color, so don't let the indices get out of sync!
                     colors.remove(blocks.len());
                 }
@@ -147,6 +166,9 @@ impl Blockfinder {
             let id = self.new_id();
             self.add_block(ctx, app, id, COLORS[color_idx % COLORS.len()], block);
         }
+
+        self.draw_failures = draw_failures(ctx, app, &self.failures);
+        self.panel = make_panel(ctx, &self.failures);
     }
 }
 
@@ -258,6 +280,41 @@ impl State<App> for Blockfinder {
                 "Reset" => {
                     return Transition::Replace(Blockfinder::new_state(ctx, app));
                 }
+                "List failures" => {
+                    let mut lines = Vec::new();
+                    for (idx, failure) in self.failures.iter().enumerate() {
+                        if !failure.is_expected() {
+                            lines.push(format!(
+                                "{}: {}",
+                                idx + 1,
+                                failure.describe(&app.primary.map)
+                            ));
+                        }
+                    }
+                    return Transition::Push(PopupMsg::new_state(
+                        ctx,
+                        "Blockfinding failures",
+                        lines,
+                    ));
+                }
+                "Export failures" => {
+                    let path = "blockfinding_failures.geojson".to_string();
+                    let result = BlockfindingFailure::to_geojson(&self.failures, &app.primary.map)
+                        .and_then(|contents| {
+                            fs_err::write(&path, contents)?;
+                            Ok(())
+                        });
+                    return Transition::Push(match result {
+                        Ok(()) => PopupMsg::new_state(
+                            ctx,
+                            "Failures exported",
+                            vec![format!("Data exported to {}", path)],
+                        ),
+                        Err(err) => {
+                            PopupMsg::new_state(ctx, "Export failed", vec![err.to_string()])
+                        }
+                    });
+                }
                 _ => unreachable!(),
             }
         }
@@ -300,6 +357,7 @@ impl State<App> for Blockfinder {
 
     fn draw(&self, g: &mut GfxCtx, _: &App) {
         self.world.draw(g);
+        g.redraw(&self.draw_failures);
         self.panel.draw(g);
 
         // If we've partitioned by neighborhood but not merged, show the grouping when hovering
@@ -436,7 +494,26 @@ impl SimpleState<App> for OneBlock {
     }
 }
 
-fn make_panel(ctx: &mut EventCtx) -> Panel {
+fn draw_failures(ctx: &EventCtx, app: &App, failures: &[BlockfindingFailure]) -> Drawable {
+    let mut batch = GeomBatch::new();
+    for failure in failures {
+        let color = if failure.is_expected() {
+            EXPECTED_FAILURE
+        } else {
+            FAILURE
+        };
+        for id in &failure.roads {
+            batch.push(
+                color.alpha(0.8),
+                id.get_outermost_lane(&app.primary.map).get_thick_polygon(),
+            );
+        }
+    }
+    ctx.upload(batch)
+}
+
+fn make_panel(ctx: &mut EventCtx, failures: &[BlockfindingFailure]) -> Panel {
+    let unexpected = failures.iter().filter(|f| !f.is_expected()).count();
     Panel::new_builder(Widget::col(vec![
         Widget::row(vec![
             Line("Blockfinder").small_heading().into_widget(ctx),
@@ -468,6 +545,24 @@ fn make_panel(ctx: &mut EventCtx) -> Panel {
             .btn_solid_destructive
             .text("Reset")
             .build_def(ctx),
+        format!(
+            "{} perimeters failed, drawn in red ({} expected ones in grey)",
+            unexpected,
+            failures.len() - unexpected
+        )
+        .text_widget(ctx),
+        Widget::row(vec![
+            ctx.style()
+                .btn_outline
+                .text("List failures")
+                .disabled(unexpected == 0)
+                .build_def(ctx),
+            ctx.style()
+                .btn_outline
+                .text("Export failures")
+                .disabled(failures.is_empty())
+                .build_def(ctx),
+        ]),
     ]))
     .aligned(HorizontalAlignment::Left, VerticalAlignment::Top)
     .build(ctx)
@@ -1,20 +1,28 @@
 use std::collections::HashSet;
 
+use geom::{ArrowCap, Distance};
 use map_gui::tools::ColorDiscrete;
-use map_model::{connectivity, LaneID, Map, PathConstraints};
+use map_gui::ID;
+use map_model::{connectivity, LaneID, Map, PathConstraints, TurnID};
 use widgetry::mapspace::ToggleZoomed;
 use widgetry::{
-    Choice, Color, EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, State, TextExt,
-    VerticalAlignment, Widget,
+    Choice, Color, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Line, Outcome,
+    Panel, State, Text, TextExt, VerticalAlignment, Widget,
 };
 
 use crate::app::App;
 use crate::app::Transition;
+use crate::common::CommonState;
+
+/// How many cut edges to list in the panel. All of them are drawn.
+const MAX_CUT_EDGES_LISTED: usize = 10;
 
 pub struct Floodfiller {
     panel: Panel,
     draw: ToggleZoomed,
+    draw_cut_edges: Drawable,
     source: Source,
+    constraints: PathConstraints,
 }
 
 impl Floodfiller {
@@ -33,8 +41,34 @@ impl Floodfiller {
         source: Source,
         constraints: PathConstraints,
     ) -> Box<dyn State<App>> {
-        let (reachable_lanes, unreachable_lanes, title) =
-            source.calculate(&app.primary.map, constraints);
+        let map = &app.primary.map;
+        let (reachable_lanes, unreachable_lanes, title) = source.calculate(map, constraints);
+        let cut_edges = find_cut_edges(map, &reachable_lanes);
+        let mut cut_edges_batch = GeomBatch::new();
+        for t in &cut_edges {
+            cut_edges_batch.push(
+                Color::YELLOW,
+                map.get_t(*t)
+                    .geom
+                    .make_arrow(Distance::meters(0.5), ArrowCap::Triangle),
+            );
+        }
+        let mut cut_edges_txt = Text::new();
+        for t in cut_edges.iter().take(MAX_CUT_EDGES_LISTED) {
+            cut_edges_txt.add_line(format!(
+                "- {} -> {} ({:?})",
+                t.src,
+                t.dst,
+                map.get_l(t.dst).lane_type
+            ));
+        }
+        if cut_edges.len() > MAX_CUT_EDGES_LISTED {
+            cut_edges_txt.add_line(format!(
+                "... and {} more",
+                cut_edges.len() - MAX_CUT_EDGES_LISTED
+            ));
+        }
+
         let mut colorer = ColorDiscrete::new(
             app,
             vec![("unreachable", Color::RED), ("reachable", Color::GREEN)],
@@ -56,6 +90,13 @@ impl Floodfiller {
                 ]),
                 format!("{} unreachable lanes", num_unreachable).text_widget(ctx),
                 legend,
+                format!(
+                    "{} cut edges (turns leaving the reachable area), drawn in yellow",
+                    cut_edges.len()
+                )
+                .text_widget(ctx),
+                cut_edges_txt.into_widget(ctx),
+                "Click a lane to floodfill from there".text_widget(ctx),
                 Widget::row(vec![
                     "Connectivity type:".text_widget(ctx),
                     Widget::dropdown(
@@ -72,7 +113,9 @@ impl Floodfiller {
             .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
             .build(ctx),
             draw,
+            draw_cut_edges: ctx.upload(cut_edges_batch),
             source,
+            constraints,
         })
     }
 }
@@ -84,6 +127,17 @@ impl State<App> for Floodfiller {
         }
         ctx.canvas_movement();
 
+        if let Some(ID::Lane(l)) = app.primary.current_selection {
+            if app.per_obj.left_click(ctx, "floodfill from this lane") {
+                return Transition::Replace(Floodfiller::new_state(
+                    ctx,
+                    app,
+                    Source::Floodfill(l),
+                    self.constraints,
+                ));
+            }
+        }
+
         match self.panel.event(ctx) {
             Outcome::Clicked(x) => match x.as_ref() {
                 "close" => {
@@ -105,9 +159,11 @@ impl State<App> for Floodfiller {
         Transition::Keep
     }
 
-    fn draw(&self, g: &mut GfxCtx, _: &App) {
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
         self.draw.draw(g);
+        g.redraw(&self.draw_cut_edges);
         self.panel.draw(g);
+        CommonState::draw_osd(g, app);
     }
 }
 
@@ -157,3 +213,18 @@ impl Source {
         }
     }
 }
+
+/// Find all turns starting from a reachable lane and leading somewhere unreachable. These are the
+/// places where connectivity breaks down, usually because of lane types or turn restrictions.
+fn find_cut_edges(map: &Map, reachable: &HashSet<LaneID>) -> Vec<TurnID> {
+    let mut cut_edges = Vec::new();
+    for l in reachable {
+        for t in map.get_turns_from_lane(*l) {
+            if !reachable.contains(&t.id.dst) {
+                cut_edges.push(t.id);
+            }
+        }
+    }
+    cut_edges.sort();
+    cut_edges
+}
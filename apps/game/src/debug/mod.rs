@@ -125,6 +125,7 @@ impl DebugMode {
                         .btn_outline
                         .text("sim internal stats")
                         .build_def(ctx),
+                    ctx.style().btn_outline.text("sim profile").build_def(ctx),
                     ctx.style()
                         .btn_outline
                         .text("blocked-by graph")
@@ -161,6 +162,7 @@ impl DebugMode {
                         .btn_outline
                         .text("find bad intersection polygons")
                         .build_def(ctx),
+                    ctx.style().btn_outline.text("validate map").build_def(ctx),
                     if cfg!(not(target_arch = "wasm32")) {
                         ctx.style()
                             .btn_outline
@@ -342,6 +344,13 @@ impl State<App> for DebugMode {
                         app.primary.sim.describe_internal_stats(),
                     ));
                 }
+                "sim profile" => {
+                    return Transition::Push(PopupMsg::new_state(
+                        ctx,
+                        "Where the simulation spends its time",
+                        app.primary.sim.get_profile().describe(),
+                    ));
+                }
                 "blocked-by graph" => {
                     return Transition::Push(blocked_by::Viewer::new_state(ctx, app));
                 }
@@ -400,6 +409,21 @@ impl State<App> for DebugMode {
                     });
                     self.reset_info(ctx);
                 }
+                "validate map" => {
+                    let report =
+                        ctx.loading_screen("validate map", |_, _| app.primary.map.validate());
+                    self.search_results = Some(SearchResults {
+                        query: "map validation problems".to_string(),
+                        num_matches: report.problems.len(),
+                        draw: draw_validation_report(ctx, app, &report),
+                    });
+                    self.reset_info(ctx);
+                    return Transition::Push(PopupMsg::new_state(
+                        ctx,
+                        "Map validation",
+                        report.describe(),
+                    ));
+                }
                 #[cfg(not(target_arch = "wasm32"))]
                 "undo all merged roads" => {
                     if let Err(err) =
@@ -1090,3 +1114,19 @@ fn draw_bad_intersections(ctx: &mut EventCtx, app: &App) -> Drawable {
     }
     ctx.upload(batch)
 }
+
+fn draw_validation_report(
+    ctx: &mut EventCtx,
+    app: &App,
+    report: &map_model::ValidationReport,
+) -> Drawable {
+    let map = &app.primary.map;
+    let mut batch = GeomBatch::new();
+    for l in report.all_lanes() {
+        batch.push(Color::RED.alpha(0.8), map.get_l(l).get_thick_polygon());
+    }
+    for i in report.all_intersections() {
+        batch.push(Color::PURPLE.alpha(0.8), map.get_i(i).polygon.clone());
+    }
+    ctx.upload(batch)
+}
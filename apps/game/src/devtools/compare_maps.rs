@@ -0,0 +1,169 @@
+use anyhow::Result;
+
+use abstutil::{prettyprint_usize, Timer};
+use geom::Distance;
+use map_gui::tools::{ColorLegend, FilePicker};
+use map_model::{Map, MapChange, MapDiff};
+use widgetry::mapspace::{DummyID, World};
+use widgetry::tools::PopupMsg;
+use widgetry::{
+    Color, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, State, Text,
+    TextExt, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+
+/// Compare the current map against an older (or newer) import of the same place, to explain why
+/// simulation results shifted after updating data.
+pub struct CompareMaps {
+    world: World<DummyID>,
+    panel: Panel,
+}
+
+impl CompareMaps {
+    /// Ask for the other version of the map, then compare against it
+    pub fn pick_file(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        FilePicker::new_state(
+            ctx,
+            Some(abstio::path(format!(
+                "system/{}/{}/maps",
+                app.primary.map.get_city_name().country,
+                app.primary.map.get_city_name().city
+            ))),
+            Box::new(|ctx, app, maybe_path| {
+                if let Ok(Some(path)) = maybe_path {
+                    Transition::Replace(CompareMaps::new_state(ctx, app, path))
+                } else {
+                    Transition::Pop
+                }
+            }),
+        )
+    }
+
+    fn new_state(ctx: &mut EventCtx, app: &App, path: String) -> Box<dyn State<App>> {
+        let map = &app.primary.map;
+        let diff = match ctx.loading_screen("compare maps", |_, timer| -> Result<MapDiff> {
+            let old = load_map(path.clone(), timer)?;
+            timer.start("diff maps");
+            let diff = MapDiff::new(&old, map);
+            timer.stop("diff maps");
+            Ok(diff)
+        }) {
+            Ok(diff) => diff,
+            Err(err) => {
+                return PopupMsg::new_state(
+                    ctx,
+                    "Error",
+                    vec![format!("Couldn't load {}: {}", path, err)],
+                );
+            }
+        };
+
+        let mut col = vec![
+            Widget::row(vec![
+                Line("Compare map versions")
+                    .small_heading()
+                    .into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            format!("Old version: {}", path).text_widget(ctx),
+        ];
+        if !map.get_edits().commands.is_empty() {
+            col.push(
+                Line("The current map has edits, which also show up as changes")
+                    .secondary()
+                    .into_widget(ctx),
+            );
+        }
+        for ((label, count), color) in diff.summarize().into_iter().zip(COLORS) {
+            col.push(ColorLegend::row(
+                ctx,
+                color,
+                format!("{} {}", prettyprint_usize(count), label),
+            ));
+        }
+        for line in diff.describe() {
+            info!("{}", line);
+        }
+
+        Box::new(CompareMaps {
+            world: make_world(ctx, app, &diff),
+            panel: Panel::new_builder(Widget::col(col))
+                .aligned(HorizontalAlignment::Right, VerticalAlignment::Top)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for CompareMaps {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        self.world.event(ctx);
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.world.draw(g);
+        self.panel.draw(g);
+    }
+}
+
+/// Matches the order of categories from `MapDiff::summarize`
+const COLORS: [Color; 7] = [
+    Color::GREEN,
+    Color::RED,
+    Color::YELLOW,
+    Color::CYAN,
+    Color::PINK,
+    Color::ORANGE,
+    Color::PURPLE,
+];
+
+fn make_world(ctx: &mut EventCtx, app: &App, diff: &MapDiff) -> World<DummyID> {
+    let map = &app.primary.map;
+    let mut world = World::bounded(map.get_bounds());
+
+    for change in &diff.changes {
+        let (polygon, color) = match change {
+            MapChange::RoadAdded(r) => (map.get_r(*r).get_thick_polygon(), COLORS[0]),
+            MapChange::RoadRemoved(_, pl) => (pl.make_polygons(Distance::meters(5.0)), COLORS[1]),
+            MapChange::LanesChanged { r, .. } => (map.get_r(*r).get_thick_polygon(), COLORS[2]),
+            MapChange::IntersectionAdded(i) => (map.get_i(*i).polygon.clone(), COLORS[3]),
+            MapChange::IntersectionRemoved(_, polygon) => (polygon.clone(), COLORS[4]),
+            MapChange::ControlChanged { i, .. } => (map.get_i(*i).polygon.clone(), COLORS[5]),
+            MapChange::SignalChanged(i) => (map.get_i(*i).polygon.clone(), COLORS[6]),
+        };
+        world
+            .add_unnamed()
+            .hitbox(polygon)
+            .draw_color(color.alpha(0.8))
+            .hover_alpha(0.5)
+            .tooltip(Text::from(change.to_string()))
+            .build(ctx);
+    }
+
+    world.draw_master_batch(
+        ctx,
+        GeomBatch::from(vec![(
+            app.cs.fade_map_dark,
+            map.get_boundary_polygon().clone(),
+        )]),
+    );
+    world.initialize_hover(ctx);
+    world
+}
+
+fn load_map(path: String, timer: &mut Timer) -> Result<Map> {
+    let mut map: Map = abstio::maybe_read_binary(path, timer)?;
+    map.map_loaded_directly(timer);
+    Ok(map)
+}
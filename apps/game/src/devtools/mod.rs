@@ -12,6 +12,7 @@ use crate::app::{App, Transition};
 
 mod collisions;
 pub mod compare_counts;
+mod compare_maps;
 mod destinations;
 pub mod kml;
 mod polygon;
@@ -56,6 +57,11 @@ impl DevToolsMode {
                     .text("story maps")
                     .hotkey(Key::S)
                     .build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("compare map versions")
+                    .hotkey(Key::M)
+                    .build_def(ctx),
                 if abstio::file_exists(app.primary.map.get_city_name().input_path("collisions.bin"))
                 {
                     ctx.style()
@@ -159,6 +165,9 @@ impl SimpleState<App> for DevToolsMode {
             "view KML" => Transition::Push(kml::ViewKML::new_state(ctx, app, None)),
             "story maps" => Transition::Push(story::StoryMapEditor::new_state(ctx, app)),
             "collisions" => Transition::Push(collisions::CollisionsViewer::new_state(ctx, app)),
+            "compare map versions" => {
+                Transition::Push(compare_maps::CompareMaps::pick_file(ctx, app))
+            }
             "OpenStreetMap viewer" => {
                 map_gui::tools::Executable::OSMViewer.replace_process(ctx, app, vec![])
             }
@@ -0,0 +1,248 @@
+use geom::{Distance, Duration, Polygon, Time};
+use map_model::ChargingCordon;
+use widgetry::tools::{Lasso, PopupMsg};
+use widgetry::{
+    Color, DrawBaselayer, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line,
+    Outcome, Panel, Spinner, State, Text, TextBox, TextExt, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::CommonState;
+use crate::edit::apply_map_edits;
+
+/// Manage the congestion pricing schemes in the current proposal.
+pub struct CordonEditor {
+    panel: Panel,
+    draw: Drawable,
+}
+
+impl CordonEditor {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let cordons = &app.primary.map.get_edits().charging_cordons;
+
+        let mut col = vec![
+            Widget::row(vec![
+                Line("Charging cordons").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(
+                "Cars entering a cordon while it's active pay a charge. Drivers consider this \
+                 when choosing a route.",
+            )
+            .wrap_to_pct(ctx, 30)
+            .into_widget(ctx),
+        ];
+        let mut batch = GeomBatch::new();
+        for (idx, cordon) in cordons.iter().enumerate() {
+            col.push(
+                Widget::row(vec![
+                    describe(cordon).into_widget(ctx).centered_vert(),
+                    ctx.style()
+                        .btn_solid_destructive
+                        .icon("system/assets/tools/trash.svg")
+                        .build_widget(ctx, format!("delete cordon {}", idx + 1))
+                        .align_right(),
+                ])
+                .padding(10)
+                .outline(ctx.style().section_outline),
+            );
+            draw_cordon(&mut batch, app, cordon);
+        }
+        col.push(
+            ctx.style()
+                .btn_outline
+                .text("Draw a new cordon")
+                .hotkey(Key::N)
+                .build_def(ctx),
+        );
+
+        Box::new(CordonEditor {
+            panel: Panel::new_builder(Widget::col(col))
+                .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+                .build(ctx),
+            draw: ctx.upload(batch),
+        })
+    }
+}
+
+impl State<App> for CordonEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Draw a new cordon" => {
+                    return Transition::Push(Box::new(DrawCordon {
+                        lasso: Lasso::new(),
+                    }));
+                }
+                x => {
+                    if let Some(x) = x.strip_prefix("delete cordon ") {
+                        let mut edits = app.primary.map.get_edits().clone();
+                        edits
+                            .charging_cordons
+                            .remove(x.parse::<usize>().unwrap() - 1);
+                        apply_map_edits(ctx, app, edits);
+                        return Transition::Replace(CordonEditor::new_state(ctx, app));
+                    } else {
+                        unreachable!()
+                    }
+                }
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.panel.draw(g);
+        CommonState::draw_osd(g, app);
+    }
+}
+
+struct DrawCordon {
+    lasso: Lasso,
+}
+
+impl State<App> for DrawCordon {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        if let Some(polygon) = self.lasso.event(ctx) {
+            return Transition::Replace(CordonDetails::new_state(ctx, polygon));
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.lasso.draw(g);
+    }
+}
+
+struct CordonDetails {
+    panel: Panel,
+    boundary: Polygon,
+}
+
+impl CordonDetails {
+    fn new_state(ctx: &mut EventCtx, boundary: Polygon) -> Box<dyn State<App>> {
+        Box::new(CordonDetails {
+            panel: Panel::new_builder(Widget::col(vec![
+                Line("New charging cordon").small_heading().into_widget(ctx),
+                Widget::row(vec![
+                    "Name:".text_widget(ctx).centered_vert(),
+                    TextBox::default_widget(ctx, "name", "Congestion charge".to_string()),
+                ]),
+                Widget::row(vec![
+                    "Price ($):".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "price", (0.5, 50.0), 5.0, 0.5),
+                ]),
+                Widget::row(vec![
+                    "Active from hour".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "start hour", (0, 23), 7_usize, 1),
+                    "until hour".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "end hour", (1, 24), 19_usize, 1),
+                ]),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_solid_primary
+                        .text("Apply")
+                        .hotkey(Key::Enter)
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_solid_destructive
+                        .text("Cancel")
+                        .hotkey(Key::Escape)
+                        .build_def(ctx),
+                ]),
+            ]))
+            .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+            .build(ctx),
+            boundary,
+        })
+    }
+}
+
+impl State<App> for CordonDetails {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "Apply" => {
+                    let start_hour: usize = self.panel.spinner("start hour");
+                    let end_hour: usize = self.panel.spinner("end hour");
+                    if start_hour >= end_hour {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec!["The cordon must end after it starts"],
+                        ));
+                    }
+
+                    let cordon = ChargingCordon::new(
+                        &app.primary.map,
+                        self.panel.text_box("name"),
+                        self.boundary.clone(),
+                        self.panel.spinner("price"),
+                        Time::START_OF_DAY + Duration::hours(start_hour),
+                        Time::START_OF_DAY + Duration::hours(end_hour),
+                    );
+                    let mut edits = app.primary.map.get_edits().clone();
+                    edits.charging_cordons.push(cordon);
+                    apply_map_edits(ctx, app, edits);
+                    return Transition::Multi(vec![
+                        Transition::Pop,
+                        Transition::Replace(CordonEditor::new_state(ctx, app)),
+                    ]);
+                }
+                "Cancel" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            }
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        g.draw_polygon(Color::PURPLE.alpha(0.5), self.boundary.clone());
+        self.panel.draw(g);
+    }
+}
+
+fn describe(cordon: &ChargingCordon) -> Text {
+    let mut txt = Text::from(Line(&cordon.name));
+    txt.add_line(
+        Line(format!(
+            "${:.2} to enter between {} and {}",
+            cordon.price,
+            cordon.start_time.ampm_tostring(),
+            cordon.end_time.ampm_tostring()
+        ))
+        .secondary(),
+    );
+    txt.add_line(Line(format!("{} roads inside", cordon.members.len())).secondary());
+    txt
+}
+
+fn draw_cordon(batch: &mut GeomBatch, app: &App, cordon: &ChargingCordon) {
+    batch.push(Color::PURPLE.alpha(0.3), cordon.boundary.clone());
+    if let Ok(outline) = cordon.boundary.to_outline(Distance::meters(5.0)) {
+        batch.push(Color::PURPLE, outline);
+    }
+    for r in &cordon.members {
+        batch.push(
+            Color::PURPLE.alpha(0.5),
+            app.primary.map.get_r(*r).get_thick_polygon(),
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use geom::Distance;
+use map_model::{DrivingSide, EditCmd, Intersection, IntersectionID, DEFAULT_CORNER_RADIUS};
+use widgetry::{
+    DrawBaselayer, EventCtx, Key, Line, Panel, SimpleState, Spinner, State, Text, TextExt, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::edit::apply_map_edits;
+
+/// Change the radius of the curb at every corner of an intersection. Tighter corners slow down
+/// turning vehicles and shorten crosswalks; curb extensions are modeled as a small radius.
+pub struct CornerRadiusEditor {
+    i: IntersectionID,
+}
+
+impl CornerRadiusEditor {
+    pub fn new_state(ctx: &mut EventCtx, app: &App, i: IntersectionID) -> Box<dyn State<App>> {
+        let current = app.primary.map.get_i(i).corner_radius;
+        let units = app.opts.units;
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Corner radius").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Widget::row(vec![
+                "Radius of the curb:".text_widget(ctx).centered_vert(),
+                Spinner::widget_with_custom_rendering(
+                    ctx,
+                    "radius",
+                    (Distance::meters(0.5), Distance::meters(20.0)),
+                    current.unwrap_or(DEFAULT_CORNER_RADIUS),
+                    Distance::meters(0.5),
+                    Box::new(move |x| x.to_string(&units)),
+                ),
+            ]),
+            describe_effects(ctx, app, current.unwrap_or(DEFAULT_CORNER_RADIUS)),
+            Widget::row(vec![
+                ctx.style()
+                    .btn_solid_primary
+                    .text("Apply")
+                    .hotkey(Key::Enter)
+                    .build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Use typical corners")
+                    .disabled(current.is_none())
+                    .build_def(ctx),
+            ]),
+        ]))
+        .build(ctx);
+        <dyn SimpleState<_>>::new_state(panel, Box::new(CornerRadiusEditor { i }))
+    }
+
+    fn change_radius(&self, ctx: &mut EventCtx, app: &mut App, new: Option<Distance>) {
+        let old = app.primary.map.get_i(self.i).corner_radius;
+        if old == new {
+            return;
+        }
+        let mut edits = app.primary.map.get_edits().clone();
+        edits.commands.push(EditCmd::ChangeCornerRadius {
+            i: self.i,
+            old,
+            new,
+        });
+        apply_map_edits(ctx, app, edits);
+    }
+}
+
+impl SimpleState<App> for CornerRadiusEditor {
+    fn on_click(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        x: &str,
+        panel: &mut Panel,
+    ) -> Transition {
+        match x {
+            "close" => Transition::Pop,
+            "Apply" => {
+                self.change_radius(ctx, app, Some(panel.spinner("radius")));
+                Transition::Pop
+            }
+            "Use typical corners" => {
+                self.change_radius(ctx, app, None);
+                Transition::Pop
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn panel_changed(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        panel: &mut Panel,
+    ) -> Option<Transition> {
+        let effects = describe_effects(ctx, app, panel.spinner("radius"));
+        panel.replace(ctx, "effects", effects);
+        None
+    }
+
+    fn other_event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        if ctx.normal_left_click() && ctx.canvas.get_cursor_in_screen_space().is_none() {
+            return Transition::Pop;
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+}
+
+fn describe_effects(ctx: &mut EventCtx, app: &App, radius: Distance) -> Widget {
+    let turn = if app.primary.map.get_config().driving_side == DrivingSide::Right {
+        "right"
+    } else {
+        "left"
+    };
+    let mut txt = Text::from(format!(
+        "Vehicles turning {} around the curb: at most {}",
+        turn,
+        Intersection::max_speed_around_curb(radius).to_string(&app.opts.units)
+    ));
+    let adjustment = Intersection::crossing_distance_adjustment_for(radius);
+    txt.add_line(if adjustment < Distance::ZERO {
+        format!(
+            "Crosswalks: {} shorter than with typical corners",
+            (-adjustment).to_string(&app.opts.units)
+        )
+    } else {
+        format!(
+            "Crosswalks: {} longer than with typical corners",
+            adjustment.to_string(&app.opts.units)
+        )
+    });
+    txt.into_widget(ctx).named("effects")
+}
@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use abstutil::prettyprint_usize;
+use geom::Distance;
+use map_gui::tools::ColorLegend;
+use map_model::{
+    CurbUse, LaneType, ParkingRestrictions, PermitZone, RoadID, BIKES_PER_PARKING_SPOT,
+};
+use widgetry::tools::PopupMsg;
+use widgetry::{
+    Choice, Color, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Line, Outcome,
+    Panel, State, Text, TextExt, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::{CommonState, RoadSelector};
+use crate::edit::apply_map_edits;
+
+/// Reassign the curb space along a corridor between parking, bus stops, loading zones, parklets,
+/// and bike corrals, and summarize how the corridor's curb is used. Parking along a corridor can
+/// also be reserved for residents with a permit.
+pub struct CurbEditor {
+    panel: Panel,
+    selector: RoadSelector,
+    draw: Drawable,
+}
+
+impl CurbEditor {
+    pub fn new_state(ctx: &mut EventCtx, app: &mut App) -> Box<dyn State<App>> {
+        let mut batch = GeomBatch::new();
+        let edits = app.primary.map.get_edits();
+        for road in app.primary.map.all_roads() {
+            let permit = edits.permit_zone_for_road(road.id).is_some();
+            for lane in &road.lanes {
+                if lane.lane_type == LaneType::Parking {
+                    let curb = road.curb_use(lane.id);
+                    if let Some(color) = color(curb) {
+                        batch.push(color.alpha(0.8), lane.get_thick_polygon());
+                    } else if permit {
+                        batch.push(PERMIT_COLOR.alpha(0.8), lane.get_thick_polygon());
+                    }
+                }
+            }
+        }
+
+        let selector = RoadSelector::new(ctx, app, BTreeSet::new());
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Curb management").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(
+                Line(
+                    "Select the roads along a corridor to see how its curb is used, then \
+                     reassign every parking curb along it. To change one side of one block, \
+                     edit the parking lane instead.",
+                )
+                .secondary(),
+            )
+            .wrap_to_pct(ctx, 30)
+            .into_widget(ctx),
+            Widget::col(
+                CurbUse::all()
+                    .into_iter()
+                    .filter_map(|x| color(x).map(|c| ColorLegend::row(ctx, c, x.describe())))
+                    .chain(std::iter::once(ColorLegend::row(
+                        ctx,
+                        PERMIT_COLOR,
+                        "resident permit parking",
+                    )))
+                    .collect(),
+            ),
+            selector.make_controls(ctx).named("selector"),
+            summarize(app, &selector.roads)
+                .into_widget(ctx)
+                .named("summary"),
+            Widget::row(vec![
+                "Use the curb for".text_widget(ctx).centered_vert(),
+                Widget::dropdown(
+                    ctx,
+                    "curb use",
+                    CurbUse::BusStop,
+                    CurbUse::all()
+                        .into_iter()
+                        .map(|x| Choice::new(x.describe(), x))
+                        .collect(),
+                ),
+            ]),
+            ctx.style()
+                .btn_solid_primary
+                .text("Reassign curbs on selected roads")
+                .build_def(ctx),
+            Widget::row(vec![
+                ctx.style()
+                    .btn_outline
+                    .text("Make a resident permit zone")
+                    .build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Remove permit zones")
+                    .build_def(ctx),
+            ]),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+        .build(ctx);
+
+        Box::new(CurbEditor {
+            panel,
+            selector,
+            draw: ctx.upload(batch),
+        })
+    }
+
+    fn apply(&self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        let curb: CurbUse = self.panel.dropdown_value("curb use");
+        let map = &app.primary.map;
+        let mut edits = map.get_edits().clone();
+        for r in &self.selector.roads {
+            let road = map.get_r(*r);
+            let sides: BTreeSet<_> = road
+                .lanes
+                .iter()
+                .filter(|l| l.lane_type == LaneType::Parking && road.curb_use(l.id) != curb)
+                .map(|l| ParkingRestrictions::side_for_lane(l.id.offset, road.lanes.len()))
+                .collect();
+            if sides.is_empty() {
+                continue;
+            }
+            edits.commands.push(map.edit_road_cmd(*r, |new| {
+                for side in &sides {
+                    new.curb.set(*side, curb);
+                }
+            }));
+        }
+        if edits.commands.len() == map.get_edits().commands.len() {
+            return Transition::Push(PopupMsg::new_state(
+                ctx,
+                "Error",
+                vec!["Select some roads with parking lanes first"],
+            ));
+        }
+        apply_map_edits(ctx, app, edits);
+        Transition::Replace(CurbEditor::new_state(ctx, app))
+    }
+
+    /// Reserve parking along the selected roads for the buildings along them. Each road belongs to
+    /// at most one zone, so this takes roads away from existing zones.
+    fn make_permit_zone(&self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if self.selector.roads.is_empty() {
+            return Transition::Push(PopupMsg::new_state(
+                ctx,
+                "Error",
+                vec!["Select some roads first"],
+            ));
+        }
+        let map = &app.primary.map;
+        let mut edits = map.get_edits().clone();
+        edits.permit_zones = remove_roads(app, &self.selector.roads);
+        let name = format!("Permit zone {}", edits.permit_zones.len() + 1);
+        edits
+            .permit_zones
+            .push(PermitZone::new(map, name, self.selector.roads.clone()));
+        apply_map_edits(ctx, app, edits);
+        Transition::Replace(CurbEditor::new_state(ctx, app))
+    }
+
+    fn remove_permit_zones(&self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        let mut edits = app.primary.map.get_edits().clone();
+        let zones = remove_roads(app, &self.selector.roads);
+        if zones == edits.permit_zones {
+            return Transition::Push(PopupMsg::new_state(
+                ctx,
+                "Error",
+                vec!["None of the selected roads are in a permit zone"],
+            ));
+        }
+        edits.permit_zones = zones;
+        apply_map_edits(ctx, app, edits);
+        Transition::Replace(CurbEditor::new_state(ctx, app))
+    }
+}
+
+/// Returns the current permit zones without some roads, dropping zones that become empty. The
+/// residents of the remaining zones are recalculated.
+fn remove_roads(app: &App, roads: &BTreeSet<RoadID>) -> Vec<PermitZone> {
+    let map = &app.primary.map;
+    map.get_edits()
+        .permit_zones
+        .iter()
+        .filter_map(|zone| {
+            if zone.roads.is_disjoint(roads) {
+                return Some(zone.clone());
+            }
+            let remaining: BTreeSet<RoadID> = zone.roads.difference(roads).cloned().collect();
+            if remaining.is_empty() {
+                None
+            } else {
+                Some(PermitZone::new(map, zone.name.clone(), remaining))
+            }
+        })
+        .collect()
+}
+
+impl State<App> for CurbEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        let clicked = match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Reassign curbs on selected roads" => {
+                    return self.apply(ctx, app);
+                }
+                "Make a resident permit zone" => {
+                    return self.make_permit_zone(ctx, app);
+                }
+                "Remove permit zones" => {
+                    return self.remove_permit_zones(ctx, app);
+                }
+                x => Some(x.to_string()),
+            },
+            _ => None,
+        };
+        if self.selector.event(ctx, app, clicked.as_deref()) {
+            let new_controls = self.selector.make_controls(ctx);
+            self.panel.replace(ctx, "selector", new_controls);
+            let summary = summarize(app, &self.selector.roads)
+                .into_widget(ctx)
+                .named("summary");
+            self.panel.replace(ctx, "summary", summary);
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.panel.draw(g);
+        self.selector.draw(g, app, true);
+        CommonState::draw_osd(g, app);
+    }
+}
+
+const PERMIT_COLOR: Color = Color::PURPLE;
+
+fn color(curb: CurbUse) -> Option<Color> {
+    match curb {
+        CurbUse::Parking => None,
+        CurbUse::BusStop => Some(Color::RED),
+        CurbUse::Loading => Some(Color::YELLOW),
+        CurbUse::Parklet => Some(Color::GREEN),
+        CurbUse::BikeCorral => Some(Color::CYAN),
+    }
+}
+
+/// Describes how the curb is used along some roads
+fn summarize(app: &App, roads: &BTreeSet<RoadID>) -> Text {
+    let map = &app.primary.map;
+    let mut txt = Text::new();
+    if roads.is_empty() {
+        txt.add_line(Line("No roads selected").secondary());
+        return txt;
+    }
+
+    // (number of blockfaces, total length, equivalent number of parking spots)
+    let mut per_use: BTreeMap<CurbUse, (usize, Distance, usize)> = BTreeMap::new();
+    let mut stops = 0;
+    let mut stops_with_clear_curb = 0;
+    for r in roads {
+        let road = map.get_r(*r);
+        for lane in &road.lanes {
+            if lane.lane_type != LaneType::Parking {
+                continue;
+            }
+            let entry = per_use
+                .entry(road.curb_use(lane.id))
+                .or_insert((0, Distance::ZERO, 0));
+            entry.0 += 1;
+            entry.1 += lane.length();
+            entry.2 += lane.number_parking_spots(map.get_config());
+        }
+        for ts in &road.transit_stops {
+            stops += 1;
+            if road.curb_use(map.get_ts(*ts).driving_pos.lane()) == CurbUse::BusStop {
+                stops_with_clear_curb += 1;
+            }
+        }
+    }
+
+    if per_use.is_empty() {
+        txt.add_line("None of these roads have curb space next to a parking lane");
+    }
+    for (curb, (count, length, spots)) in per_use {
+        let mut line = format!(
+            "{}: {} blockfaces, {}",
+            curb.describe(),
+            prettyprint_usize(count),
+            length.to_string(&app.opts.units)
+        );
+        match curb {
+            CurbUse::Parking => {
+                line.push_str(&format!(" ({} spots)", prettyprint_usize(spots)));
+            }
+            CurbUse::BikeCorral => {
+                line.push_str(&format!(
+                    " ({} bikes)",
+                    prettyprint_usize(spots * BIKES_PER_PARKING_SPOT)
+                ));
+            }
+            CurbUse::BusStop | CurbUse::Loading | CurbUse::Parklet => {}
+        }
+        txt.add_line(line);
+    }
+    txt.add_line(format!(
+        "{} of {} bus stops have a clear curb",
+        prettyprint_usize(stops_with_clear_curb),
+        prettyprint_usize(stops)
+    ));
+    for zone in &map.get_edits().permit_zones {
+        if zone.roads.is_disjoint(roads) {
+            continue;
+        }
+        txt.add_line(format!(
+            "{}: {} roads, residents of {} buildings may park",
+            zone.name,
+            prettyprint_usize(zone.roads.len()),
+            prettyprint_usize(zone.buildings.len())
+        ));
+    }
+    txt
+}
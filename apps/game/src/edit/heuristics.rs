@@ -91,6 +91,7 @@ pub fn add_new_lane(road: &mut EditRoad, lt: LaneType, osm_tags: &Tags) -> usize
             lt,
             dir,
             width: LaneSpec::typical_lane_widths(lt, osm_tags)[0].0,
+            pocket: None,
         },
     );
     idx
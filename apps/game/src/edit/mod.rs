@@ -1,21 +1,29 @@
+use std::cell::RefCell;
+
 use maplit::btreeset;
 
 use abstutil::{prettyprint_usize, Timer};
 use geom::Speed;
 use map_gui::options::OptionsPanel;
 use map_gui::render::DrawMap;
-use map_gui::tools::{grey_out_map, ChooseSomething, ColorLegend};
+use map_gui::tools::{describe_note, grey_out_map, ChooseSomething, ColorLegend, NoteEditor};
 use map_gui::ID;
-use map_model::{EditCmd, IntersectionID, LaneID, MapEdits};
+use map_model::{EditCmd, EditNote, IntersectionID, LaneID, MapEdits};
 use widgetry::mapspace::ToggleZoomed;
-use widgetry::tools::PopupMsg;
+use widgetry::tools::{Confirmation, PopupMsg, Wizard, WizardStep};
 use widgetry::{
     lctrl, Choice, Color, ControlState, EventCtx, GfxCtx, HorizontalAlignment, Image, Key, Line,
     Menu, Outcome, Panel, State, Text, TextBox, TextExt, VerticalAlignment, Widget,
 };
 
+pub use self::cordons::CordonEditor;
+pub use self::corners::CornerRadiusEditor;
+pub use self::curb::CurbEditor;
+pub use self::restricted_zones::RestrictedZoneEditor;
 pub use self::roads::RoadEditor;
 pub use self::routes::RouteEditor;
+pub use self::scheduled_closures::ScheduledClosureEditor;
+pub use self::school_streets::SchoolStreetsEditor;
 pub use self::stop_signs::StopSignEditor;
 pub use self::traffic_signals::TrafficSignalEditor;
 pub use self::validate::{check_blackholes, check_sidewalk_connectivity};
@@ -24,10 +32,17 @@ use crate::common::{tool_panel, CommonState, Warping};
 use crate::debug::DebugMode;
 use crate::sandbox::{GameplayMode, SandboxMode, TimeWarpScreen};
 
+mod cordons;
+mod corners;
+mod curb;
 mod heuristics;
 mod multiple_roads;
+mod query_roads;
+mod restricted_zones;
 mod roads;
 mod routes;
+mod scheduled_closures;
+mod school_streets;
 mod stop_signs;
 mod traffic_signals;
 mod validate;
@@ -276,18 +291,69 @@ impl State<App> for EditMode {
                                 ))
                             }
                             "delete this proposal and remove all edits" => {
-                                abstio::delete_file(abstio::path_edits(
-                                    app.primary.map.get_name(),
-                                    &app.primary.map.get_edits().edits_name,
-                                ));
-                                apply_map_edits(ctx, app, app.primary.map.new_edits());
-                                Transition::Pop
+                                Transition::Replace(Confirmation::new_state(
+                                    ctx,
+                                    &format!(
+                                        "Delete \"{}\"?",
+                                        app.primary.map.get_edits().edits_name
+                                    ),
+                                    vec!["The saved proposal and all of its edits will be lost."],
+                                    "Delete",
+                                    true,
+                                    Box::new(|ctx, app| {
+                                        abstio::delete_file(abstio::path_edits(
+                                            app.primary.map.get_name(),
+                                            &app.primary.map.get_edits().edits_name,
+                                        ));
+                                        apply_map_edits(ctx, app, app.primary.map.new_edits());
+                                        Transition::Pop
+                                    }),
+                                ))
                             }
                             _ => unreachable!(),
                         }),
                     ));
                 }
                 "load proposal" => {}
+                "charging cordons" => {
+                    return Transition::Push(CordonEditor::new_state(ctx, app));
+                }
+                "school streets" => {
+                    return Transition::Push(SchoolStreetsEditor::new_state(ctx, app));
+                }
+                "restricted zones" => {
+                    return Transition::Push(RestrictedZoneEditor::new_state(ctx, app));
+                }
+                "scheduled closures" => {
+                    if !self.mode.can_edit_roads() {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec!["Roads can't be edited in this mode"],
+                        ));
+                    }
+                    return Transition::Push(ScheduledClosureEditor::new_state(ctx, app));
+                }
+                "curb management" => {
+                    if !self.mode.can_edit_roads() {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec!["Roads can't be edited in this mode"],
+                        ));
+                    }
+                    return Transition::Push(CurbEditor::new_state(ctx, app));
+                }
+                "select roads by attributes" => {
+                    if !self.mode.can_edit_roads() {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec!["Roads can't be edited in this mode"],
+                        ));
+                    }
+                    return Transition::Push(query_roads::SelectRoadsByQuery::new_state(ctx, app));
+                }
                 "undo" => {
                     let mut edits = app.primary.map.get_edits().clone();
                     let maybe_id = cmd_to_id(&edits.commands.pop().unwrap());
@@ -361,6 +427,12 @@ impl State<App> for EditMode {
             }
         }
 
+        if let Some(id) = app.primary.current_selection.clone() {
+            if can_annotate(app, &id) && ctx.input.pressed(Key::N) {
+                return Transition::Push(annotate(ctx, app, id));
+            }
+        }
+
         match self.tool_panel.event(ctx) {
             Outcome::Clicked(x) => match x.as_ref() {
                 "back" => self.quit(ctx, app),
@@ -377,15 +449,22 @@ impl State<App> for EditMode {
         self.changelist.draw(g);
         self.draw.draw(g);
         CommonState::draw_osd(g, app);
+
+        if let Some(ref id) = app.primary.current_selection {
+            if can_annotate(app, id) {
+                let mut txt = get_note(app, id)
+                    .map(describe_note)
+                    .unwrap_or_else(Text::new);
+                txt.add_line(Line("Press N to write a note").secondary());
+                g.draw_mouse_tooltip(txt);
+            }
+        }
     }
 }
 
+/// The shared state of the wizard for naming and saving edits.
 pub struct SaveEdits {
-    panel: Panel,
-    current_name: String,
-    cancel: Option<Transition>,
-    on_success: Box<dyn Fn(&mut EventCtx, &mut App)>,
-    reset: bool,
+    name: String,
 }
 
 impl SaveEdits {
@@ -402,18 +481,15 @@ impl SaveEdits {
         } else {
             format!("copy of {}", app.primary.map.get_edits().edits_name)
         };
-        let mut save = SaveEdits {
-            current_name: initial_name.clone(),
-            panel: Panel::new_builder(Widget::col(vec![
-                Line(title).small_heading().into_widget(ctx),
-                Widget::row(vec![
-                    "Name:".text_widget(ctx).centered_vert(),
-                    TextBox::default_widget(ctx, "filename", initial_name),
-                ]),
-                // TODO Want this to always consistently be one line high, but it isn't for a blank
-                // line
-                Text::new().into_widget(ctx).named("warning"),
-                Widget::row(vec![
+
+        let mut step = WizardStep::new(
+            "",
+            Box::new(move |ctx, _, state: &SaveEdits| {
+                Widget::col(vec![
+                    Widget::row(vec![
+                        "Name:".text_widget(ctx).centered_vert(),
+                        TextBox::default_widget(ctx, "filename", state.name.clone()),
+                    ]),
                     if discard {
                         ctx.style()
                             .btn_solid_destructive
@@ -422,120 +498,62 @@ impl SaveEdits {
                     } else {
                         Widget::nothing()
                     },
-                    if cancel.is_some() {
-                        ctx.style()
-                            .btn_outline
-                            .text("Cancel")
-                            .hotkey(Key::Escape)
-                            .build_def(ctx)
-                    } else {
-                        Widget::nothing()
-                    },
-                    ctx.style()
-                        .btn_solid_primary
-                        .text("Save")
-                        .disabled(true)
-                        .build_def(ctx),
                 ])
-                .align_right(),
-            ]))
-            .build(ctx),
-            cancel,
-            on_success,
-            reset: discard,
-        };
-        save.recalc_btn(ctx, app);
-        Box::new(save)
-    }
-
-    fn recalc_btn(&mut self, ctx: &mut EventCtx, app: &App) {
-        if self.current_name.is_empty() {
-            self.panel.replace(
-                ctx,
-                "Save",
-                ctx.style()
-                    .btn_solid_primary
-                    .text("Save")
-                    .disabled(true)
-                    .build_def(ctx),
-            );
-            self.panel
-                .replace(ctx, "warning", Text::new().into_widget(ctx));
-        } else if abstio::file_exists(abstio::path_edits(
-            app.primary.map.get_name(),
-            &self.current_name,
-        )) {
-            self.panel.replace(
-                ctx,
-                "Save",
-                ctx.style()
-                    .btn_solid_primary
-                    .text("Save")
-                    .disabled(true)
-                    .build_def(ctx),
-            );
-            self.panel.replace(
-                ctx,
-                "warning",
-                Line("A proposal with this name already exists")
-                    .fg(Color::hex("#FF5E5E"))
-                    .into_widget(ctx),
-            );
-        } else {
-            self.panel.replace(
-                ctx,
-                "Save",
-                ctx.style()
-                    .btn_solid_primary
-                    .text("Save")
-                    .hotkey(Key::Enter)
-                    .build_def(ctx),
-            );
-            self.panel
-                .replace(ctx, "warning", Text::new().into_widget(ctx));
-        }
-    }
-}
-
-impl State<App> for SaveEdits {
-    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
-        if let Outcome::Clicked(x) = self.panel.event(ctx) {
-            match x.as_ref() {
-                "Save" => {
-                    let mut edits = app.primary.map.get_edits().clone();
-                    edits.edits_name = self.current_name.clone();
-                    app.primary
-                        .map
-                        .must_apply_edits(edits, &mut Timer::throwaway());
-                    app.primary.map.save_edits();
-                    if self.reset {
+            }),
+        )
+        .validate(Box::new(|panel, app, state| {
+            let name = panel.text_box("filename");
+            if name.is_empty() {
+                return Err("Name this proposal first".to_string());
+            }
+            if abstio::file_exists(abstio::path_edits(app.primary.map.get_name(), &name)) {
+                return Err("A proposal with this name already exists".to_string());
+            }
+            state.name = name;
+            Ok(())
+        }));
+        if discard {
+            step = step.on_click(Box::new(|ctx, _, _, x| match x {
+                "Discard proposal" => Transition::Push(Confirmation::new_state(
+                    ctx,
+                    "Discard this proposal?",
+                    vec!["All of your changes will be lost."],
+                    "Discard",
+                    true,
+                    Box::new(|ctx, app| {
                         apply_map_edits(ctx, app, app.primary.map.new_edits());
-                    }
-                    (self.on_success)(ctx, app);
-                    return Transition::Pop;
-                }
-                "Discard proposal" => {
-                    apply_map_edits(ctx, app, app.primary.map.new_edits());
-                    return Transition::Pop;
-                }
-                "Cancel" => {
-                    return self.cancel.take().unwrap();
-                }
+                        Transition::Multi(vec![Transition::Pop, Transition::Pop])
+                    }),
+                )),
                 _ => unreachable!(),
-            }
+            }));
         }
-        let name = self.panel.text_box("filename");
-        if name != self.current_name {
-            self.current_name = name;
-            self.recalc_btn(ctx, app);
-        }
-
-        Transition::Keep
-    }
 
-    fn draw(&self, g: &mut GfxCtx, app: &App) {
-        grey_out_map(g, app);
-        self.panel.draw(g);
+        let mut wizard = Wizard::builder(title, SaveEdits { name: initial_name })
+            .step(step)
+            .finish_label("Save");
+        if let Some(cancel) = cancel {
+            // The wizard may call this more than once, but it pops itself the first time
+            let cancel = RefCell::new(Some(cancel));
+            wizard = wizard.cancellable(Box::new(move |_, _| cancel.borrow_mut().take().unwrap()));
+        }
+        wizard.build(
+            ctx,
+            app,
+            Box::new(move |ctx, app, state| {
+                let mut edits = app.primary.map.get_edits().clone();
+                edits.edits_name = state.name.clone();
+                app.primary
+                    .map
+                    .must_apply_edits(edits, &mut Timer::throwaway());
+                app.primary.map.save_edits();
+                if discard {
+                    apply_map_edits(ctx, app, app.primary.map.new_edits());
+                }
+                (on_success)(ctx, app);
+                Transition::Pop
+            }),
+        )
     }
 }
 
@@ -699,16 +717,18 @@ fn make_topcenter(ctx: &mut EventCtx, app: &App) -> Panel {
 }
 
 pub fn apply_map_edits(ctx: &mut EventCtx, app: &mut App, edits: MapEdits) {
+    // Snapshots of the simulation so far are only valid for the old map
+    app.primary.savestates.clear();
     ctx.loading_screen("apply map edits", |ctx, timer| {
         if !app.store_unedited_map_in_secondary && app.primary.unedited_map.is_none() {
             timer.start("save unedited map");
-            assert!(app.primary.map.get_edits().commands.is_empty());
+            assert!(app.primary.map.get_edits().is_empty());
             app.primary.unedited_map = Some(app.primary.map.clone());
             timer.stop("save unedited map");
         }
         if app.store_unedited_map_in_secondary && app.secondary.is_none() {
             timer.start("save unedited map for toggling");
-            assert!(app.primary.map.get_edits().commands.is_empty());
+            assert!(app.primary.map.get_edits().is_empty());
             let mut per_map = crate::app::PerMap::map_loaded(
                 app.primary.map.clone(),
                 app.primary.sim.clone(),
@@ -761,6 +781,71 @@ pub fn apply_map_edits(ctx: &mut EventCtx, app: &mut App, edits: MapEdits) {
     });
 }
 
+fn get_note<'a>(app: &'a App, id: &ID) -> Option<&'a EditNote> {
+    let edits = app.primary.map.get_edits();
+    match id {
+        ID::Lane(l) => edits.road_notes.get(&l.road),
+        ID::Intersection(i) => edits.intersection_notes.get(i),
+        _ => None,
+    }
+}
+
+/// Notes can be attached to any road or intersection that's been changed, or that still has a
+/// note from before a change was undone.
+fn can_annotate(app: &App, id: &ID) -> bool {
+    let edits = app.primary.map.get_edits();
+    get_note(app, id).is_some()
+        || match id {
+            ID::Lane(l) => edits.changed_roads.contains(&l.road),
+            ID::Intersection(i) => {
+                edits.original_intersections.contains_key(i)
+                    || edits.changed_corner_radii.contains(i)
+            }
+            _ => false,
+        }
+}
+
+fn annotate(ctx: &mut EventCtx, app: &App, id: ID) -> Box<dyn State<App>> {
+    let title = match id {
+        ID::Lane(l) => format!(
+            "Note for {}",
+            app.primary
+                .map
+                .get_r(l.road)
+                .get_name(app.opts.language.as_ref())
+        ),
+        ID::Intersection(i) => format!("Note for {}", i),
+        _ => unreachable!(),
+    };
+    NoteEditor::new_state(
+        ctx,
+        title,
+        get_note(app, &id),
+        Box::new(move |note, ctx, app| {
+            let mut edits = app.primary.map.get_edits().clone();
+            match id {
+                ID::Lane(l) => {
+                    if let Some(note) = note {
+                        edits.road_notes.insert(l.road, note);
+                    } else {
+                        edits.road_notes.remove(&l.road);
+                    }
+                }
+                ID::Intersection(i) => {
+                    if let Some(note) = note {
+                        edits.intersection_notes.insert(i, note);
+                    } else {
+                        edits.intersection_notes.remove(&i);
+                    }
+                }
+                _ => unreachable!(),
+            }
+            apply_map_edits(ctx, app, edits);
+            Transition::Pop
+        }),
+    )
+}
+
 pub fn can_edit_lane(app: &App, l: LaneID) -> bool {
     let map = &app.primary.map;
     if map.get_l(l).is_light_rail() {
@@ -777,10 +862,17 @@ pub fn can_edit_lane(app: &App, l: LaneID) -> bool {
 
 pub fn speed_limit_choices(app: &App, preset: Option<Speed>) -> Vec<Choice<Speed>> {
     // Don't need anything higher than 70mph. Though now I kind of miss 3am drives on TX-71...
-    let mut speeds = (10..=70)
-        .step_by(5)
-        .map(|mph| Speed::miles_per_hour(mph as f64))
-        .collect::<Vec<_>>();
+    let mut speeds = if app.opts.units.metric {
+        (10..=110)
+            .step_by(10)
+            .map(|kmph| Speed::km_per_hour(kmph as f64))
+            .collect::<Vec<_>>()
+    } else {
+        (10..=70)
+            .step_by(5)
+            .map(|mph| Speed::miles_per_hour(mph as f64))
+            .collect::<Vec<_>>()
+    };
     if let Some(preset) = preset {
         if !speeds.contains(&preset) {
             speeds.push(preset);
@@ -860,6 +952,32 @@ fn make_changelist(ctx: &mut EventCtx, app: &App) -> Panel {
                 edits.original_intersections.len()
             ),
         ),
+        Widget::row(vec![
+            ctx.style()
+                .btn_outline
+                .text(format!("{} charging cordons", edits.charging_cordons.len()))
+                .build_widget(ctx, "charging cordons"),
+            ctx.style()
+                .btn_outline
+                .text("school streets")
+                .build_def(ctx),
+            ctx.style()
+                .btn_outline
+                .text(format!("{} restricted zones", edits.restricted_zones.len()))
+                .build_widget(ctx, "restricted zones"),
+            ctx.style()
+                .btn_outline
+                .text("scheduled closures")
+                .build_def(ctx),
+            ctx.style()
+                .btn_outline
+                .text("curb management")
+                .build_def(ctx),
+        ]),
+        ctx.style()
+            .btn_outline
+            .text("select roads by attributes")
+            .build_def(ctx),
     ];
 
     if edits.commands.len() > 5 {
@@ -905,6 +1023,9 @@ fn cmd_to_id(cmd: &EditCmd) -> Option<ID> {
         EditCmd::ChangeRoad { r, .. } => Some(ID::Road(*r)),
         EditCmd::ChangeIntersection { i, .. } => Some(ID::Intersection(*i)),
         EditCmd::ChangeRouteSchedule { .. } => None,
+        EditCmd::ChangeCornerRadius { i, .. } | EditCmd::ChangeBikeProtection { i, .. } => {
+            Some(ID::Intersection(*i))
+        }
     }
 }
 
@@ -0,0 +1,283 @@
+use std::collections::BTreeSet;
+
+use geom::{Bounds, Speed};
+use map_model::osm::RoadRank;
+use map_model::{LaneType, Road, RoadID};
+use widgetry::{
+    Choice, Color, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line, Outcome,
+    Panel, Spinner, State, TextExt, Toggle, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::CommonState;
+use crate::edit::{apply_map_edits, speed_limit_choices};
+
+/// Select every road matching some attributes, then change all of them at once. This complements
+/// selecting roads by drawing a region.
+pub struct SelectRoadsByQuery {
+    panel: Panel,
+    query: Query,
+    batch: BatchEdit,
+    // Only used when the query is limited to the viewport
+    bounds: Bounds,
+    matches: BTreeSet<RoadID>,
+    draw: Drawable,
+}
+
+#[derive(Clone, Copy)]
+struct Query {
+    /// Strictly faster than this
+    min_speed_limit: Option<Speed>,
+    min_driving_lanes: usize,
+    rank: Option<RoadRank>,
+    only_in_view: bool,
+}
+
+#[derive(Clone, Copy)]
+struct BatchEdit {
+    speed_limit: Option<Speed>,
+    rank: Option<RoadRank>,
+}
+
+impl Query {
+    fn matches(&self, road: &Road, bounds: &Bounds) -> bool {
+        if road.is_light_rail() {
+            return false;
+        }
+        if let Some(speed) = self.min_speed_limit {
+            if road.speed_limit <= speed {
+                return false;
+            }
+        }
+        let driving_lanes = road
+            .lanes
+            .iter()
+            .filter(|l| l.lane_type == LaneType::Driving)
+            .count();
+        if driving_lanes < self.min_driving_lanes {
+            return false;
+        }
+        if let Some(rank) = self.rank {
+            if road.get_rank() != rank {
+                return false;
+            }
+        }
+        if self.only_in_view && !bounds.contains(road.center_pts.middle()) {
+            return false;
+        }
+        true
+    }
+}
+
+impl SelectRoadsByQuery {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let mut state = SelectRoadsByQuery {
+            panel: Panel::empty(ctx),
+            query: Query {
+                min_speed_limit: None,
+                min_driving_lanes: 0,
+                rank: None,
+                only_in_view: true,
+            },
+            batch: BatchEdit {
+                speed_limit: None,
+                rank: None,
+            },
+            bounds: ctx.canvas.get_screen_bounds(),
+            matches: BTreeSet::new(),
+            draw: Drawable::empty(ctx),
+        };
+        state.recalculate(ctx, app);
+        Box::new(state)
+    }
+
+    fn recalculate(&mut self, ctx: &mut EventCtx, app: &App) {
+        self.matches = app
+            .primary
+            .map
+            .all_roads()
+            .iter()
+            .filter(|r| self.query.matches(r, &self.bounds))
+            .map(|r| r.id)
+            .collect();
+
+        let mut batch = GeomBatch::new();
+        for r in &self.matches {
+            batch.push(
+                Color::CYAN.alpha(0.5),
+                app.primary.map.get_r(*r).get_thick_polygon(),
+            );
+        }
+        self.draw = ctx.upload(batch);
+        self.panel = make_panel(ctx, app, &self.query, &self.batch, self.matches.len());
+    }
+
+    fn apply(&self, ctx: &mut EventCtx, app: &mut App) {
+        let mut edits = app.primary.map.get_edits().clone();
+        for r in &self.matches {
+            let road = app.primary.map.get_r(*r);
+            let rank_override = self
+                .batch
+                .rank
+                .map(|rank| Some(rank).filter(|x| *x != road.rank_from_osm()));
+            let batch = self.batch;
+            edits
+                .commands
+                .push(app.primary.map.edit_road_cmd(*r, |new| {
+                    if let Some(speed) = batch.speed_limit {
+                        new.speed_limit = speed;
+                    }
+                    if let Some(rank_override) = rank_override {
+                        new.rank_override = rank_override;
+                    }
+                }));
+        }
+        apply_map_edits(ctx, app, edits);
+    }
+}
+
+impl State<App> for SelectRoadsByQuery {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+        if self.query.only_in_view && ctx.canvas.get_screen_bounds() != self.bounds {
+            self.bounds = ctx.canvas.get_screen_bounds();
+            self.recalculate(ctx, app);
+        }
+
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" | "Cancel" => {
+                    return Transition::Pop;
+                }
+                "Apply" => {
+                    self.apply(ctx, app);
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => {
+                self.query = Query {
+                    min_speed_limit: self.panel.dropdown_value("min speed limit"),
+                    min_driving_lanes: self.panel.spinner("min driving lanes"),
+                    rank: self.panel.dropdown_value("query classification"),
+                    only_in_view: self.panel.is_checked("only roads in view"),
+                };
+                self.batch = BatchEdit {
+                    speed_limit: self.panel.dropdown_value("new speed limit"),
+                    rank: self.panel.dropdown_value("new classification"),
+                };
+                self.bounds = ctx.canvas.get_screen_bounds();
+                self.recalculate(ctx, app);
+            }
+            _ => {}
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.panel.draw(g);
+        CommonState::draw_osd(g, app);
+    }
+}
+
+fn make_panel(
+    ctx: &mut EventCtx,
+    app: &App,
+    query: &Query,
+    batch: &BatchEdit,
+    num_matches: usize,
+) -> Panel {
+    let speed_choices = |none: &str| {
+        let mut choices = vec![Choice::new(none, None)];
+        for choice in speed_limit_choices(app, None) {
+            choices.push(Choice::new(choice.label, Some(choice.data)));
+        }
+        choices
+    };
+    let rank_choices = |none: &str| {
+        vec![
+            Choice::new(none, None),
+            Choice::new("local", Some(RoadRank::Local)),
+            Choice::new("arterial", Some(RoadRank::Arterial)),
+            Choice::new("highway", Some(RoadRank::Highway)),
+        ]
+    };
+
+    Panel::new_builder(Widget::col(vec![
+        Widget::row(vec![
+            Line("Select roads by attributes")
+                .small_heading()
+                .into_widget(ctx),
+            ctx.style().btn_close_widget(ctx),
+        ]),
+        Widget::col(vec![
+            Widget::row(vec![
+                "Speed limit faster than".text_widget(ctx).centered_vert(),
+                Widget::dropdown(
+                    ctx,
+                    "min speed limit",
+                    query.min_speed_limit,
+                    speed_choices("any"),
+                ),
+            ]),
+            Widget::row(vec![
+                "At least".text_widget(ctx).centered_vert(),
+                Spinner::widget(
+                    ctx,
+                    "min driving lanes",
+                    (0, 10),
+                    query.min_driving_lanes,
+                    1,
+                ),
+                "driving lanes".text_widget(ctx).centered_vert(),
+            ]),
+            Widget::row(vec![
+                "Classification".text_widget(ctx).centered_vert(),
+                Widget::dropdown(ctx, "query classification", query.rank, rank_choices("any")),
+            ]),
+            Toggle::checkbox(ctx, "only roads in view", None, query.only_in_view),
+            format!("{} roads match", num_matches).text_widget(ctx),
+        ])
+        .section(ctx),
+        Widget::col(vec![
+            Line("Change all of them").small_heading().into_widget(ctx),
+            Widget::row(vec![
+                "Speed limit".text_widget(ctx).centered_vert(),
+                Widget::dropdown(
+                    ctx,
+                    "new speed limit",
+                    batch.speed_limit,
+                    speed_choices("unchanged"),
+                ),
+            ]),
+            Widget::row(vec![
+                "Classification".text_widget(ctx).centered_vert(),
+                Widget::dropdown(
+                    ctx,
+                    "new classification",
+                    batch.rank,
+                    rank_choices("unchanged"),
+                ),
+            ]),
+        ])
+        .section(ctx),
+        Widget::custom_row(vec![
+            ctx.style()
+                .btn_solid_primary
+                .text("Apply")
+                .hotkey(Key::Enter)
+                .disabled(num_matches == 0 || (batch.speed_limit.is_none() && batch.rank.is_none()))
+                .build_def(ctx),
+            ctx.style()
+                .btn_solid_destructive
+                .text("Cancel")
+                .hotkey(Key::Escape)
+                .build_def(ctx),
+        ])
+        .evenly_spaced(),
+    ]))
+    .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+    .build(ctx)
+}
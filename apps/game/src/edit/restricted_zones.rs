@@ -0,0 +1,270 @@
+use enumset::EnumSet;
+use geom::{Distance, Polygon};
+use map_model::{PathConstraints, RestrictedZone};
+use widgetry::tools::{Lasso, PopupMsg};
+use widgetry::{
+    Color, DrawBaselayer, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line,
+    Outcome, Panel, State, Text, TextBox, TextExt, Toggle, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::CommonState;
+use crate::edit::apply_map_edits;
+
+/// Manage the school streets, pedestrian zones, and other restricted areas in the current
+/// proposal.
+pub struct RestrictedZoneEditor {
+    panel: Panel,
+    draw: Drawable,
+}
+
+impl RestrictedZoneEditor {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let zones = &app.primary.map.get_edits().restricted_zones;
+
+        let mut col = vec![
+            Widget::row(vec![
+                Line("Restricted zones").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(
+                "Vehicles may not drive inside a zone while it's active, unless they're exempt. \
+                 Trips that can't avoid the zone ignore it.",
+            )
+            .wrap_to_pct(ctx, 30)
+            .into_widget(ctx),
+        ];
+        let mut batch = GeomBatch::new();
+        for (idx, zone) in zones.iter().enumerate() {
+            col.push(
+                Widget::row(vec![
+                    describe(zone).into_widget(ctx).centered_vert(),
+                    ctx.style()
+                        .btn_solid_destructive
+                        .icon("system/assets/tools/trash.svg")
+                        .build_widget(ctx, format!("delete zone {}", idx + 1))
+                        .align_right(),
+                ])
+                .padding(10)
+                .outline(ctx.style().section_outline),
+            );
+            draw_zone(&mut batch, app, zone);
+        }
+        col.push(
+            ctx.style()
+                .btn_outline
+                .text("Draw a new zone")
+                .hotkey(Key::N)
+                .build_def(ctx),
+        );
+
+        Box::new(RestrictedZoneEditor {
+            panel: Panel::new_builder(Widget::col(col))
+                .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+                .build(ctx),
+            draw: ctx.upload(batch),
+        })
+    }
+}
+
+impl State<App> for RestrictedZoneEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Draw a new zone" => {
+                    return Transition::Push(Box::new(DrawZone {
+                        lasso: Lasso::new(),
+                    }));
+                }
+                x => {
+                    if let Some(x) = x.strip_prefix("delete zone ") {
+                        let mut edits = app.primary.map.get_edits().clone();
+                        edits
+                            .restricted_zones
+                            .remove(x.parse::<usize>().unwrap() - 1);
+                        apply_map_edits(ctx, app, edits);
+                        return Transition::Replace(RestrictedZoneEditor::new_state(ctx, app));
+                    } else {
+                        unreachable!()
+                    }
+                }
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.panel.draw(g);
+        CommonState::draw_osd(g, app);
+    }
+}
+
+struct DrawZone {
+    lasso: Lasso,
+}
+
+impl State<App> for DrawZone {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        if let Some(polygon) = self.lasso.event(ctx) {
+            return Transition::Replace(ZoneDetails::new_state(ctx, polygon));
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.lasso.draw(g);
+    }
+}
+
+struct ZoneDetails {
+    panel: Panel,
+    boundary: Polygon,
+}
+
+impl ZoneDetails {
+    fn new_state(ctx: &mut EventCtx, boundary: Polygon) -> Box<dyn State<App>> {
+        Box::new(ZoneDetails {
+            panel: Panel::new_builder(Widget::col(vec![
+                Line("New restricted zone").small_heading().into_widget(ctx),
+                Widget::row(vec![
+                    "Name:".text_widget(ctx).centered_vert(),
+                    TextBox::default_widget(ctx, "name", "School street".to_string()),
+                ]),
+                Widget::row(vec![
+                    "Active hours:".text_widget(ctx).centered_vert(),
+                    TextBox::default_widget(ctx, "windows", "08:00-09:00, 15:00-16:00".to_string()),
+                ]),
+                Text::from(
+                    Line("Leave blank or write \"always\" for a permanent zone").secondary(),
+                )
+                .into_widget(ctx),
+                "Exempt:".text_widget(ctx),
+                Widget::row(vec![
+                    Toggle::checkbox(ctx, "buses", None, true),
+                    Toggle::checkbox(ctx, "bikes", None, true),
+                    Toggle::checkbox(ctx, "cars", None, false),
+                ]),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_solid_primary
+                        .text("Apply")
+                        .hotkey(Key::Enter)
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_solid_destructive
+                        .text("Cancel")
+                        .hotkey(Key::Escape)
+                        .build_def(ctx),
+                ]),
+            ]))
+            .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+            .build(ctx),
+            boundary,
+        })
+    }
+}
+
+impl State<App> for ZoneDetails {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "Apply" => {
+                    let windows =
+                        match RestrictedZone::parse_windows(&self.panel.text_box("windows")) {
+                            Ok(windows) => windows,
+                            Err(err) => {
+                                return Transition::Push(PopupMsg::new_state(
+                                    ctx,
+                                    "Error",
+                                    vec![format!("Can't understand the active hours: {}", err)],
+                                ));
+                            }
+                        };
+                    let mut exemptions = EnumSet::new();
+                    for (name, constraints) in [
+                        ("buses", PathConstraints::Bus),
+                        ("bikes", PathConstraints::Bike),
+                        ("cars", PathConstraints::Car),
+                    ] {
+                        if self.panel.is_checked(name) {
+                            exemptions.insert(constraints);
+                        }
+                    }
+
+                    let zone = RestrictedZone::new(
+                        &app.primary.map,
+                        self.panel.text_box("name"),
+                        self.boundary.clone(),
+                        exemptions,
+                        windows,
+                    );
+                    let mut edits = app.primary.map.get_edits().clone();
+                    edits.restricted_zones.push(zone);
+                    apply_map_edits(ctx, app, edits);
+                    return Transition::Multi(vec![
+                        Transition::Pop,
+                        Transition::Replace(RestrictedZoneEditor::new_state(ctx, app)),
+                    ]);
+                }
+                "Cancel" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            }
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        g.draw_polygon(Color::RED.alpha(0.5), self.boundary.clone());
+        self.panel.draw(g);
+    }
+}
+
+fn describe(zone: &RestrictedZone) -> Text {
+    let mut txt = Text::from(Line(&zone.name));
+    txt.add_line(Line(format!("Active {}", zone.describe_windows())).secondary());
+    let exempt: Vec<String> = zone
+        .exemptions
+        .iter()
+        .map(|c| format!("{:?}", c).to_ascii_lowercase())
+        .collect();
+    txt.add_line(
+        Line(if exempt.is_empty() {
+            "No vehicles exempt".to_string()
+        } else {
+            format!("Exempt: {}", exempt.join(", "))
+        })
+        .secondary(),
+    );
+    txt.add_line(Line(format!("{} roads inside", zone.members.len())).secondary());
+    txt
+}
+
+fn draw_zone(batch: &mut GeomBatch, app: &App, zone: &RestrictedZone) {
+    batch.push(Color::RED.alpha(0.2), zone.boundary.clone());
+    if let Ok(outline) = zone.boundary.to_outline(Distance::meters(5.0)) {
+        batch.push(Color::RED, outline);
+    }
+    for r in &zone.members {
+        batch.push(
+            Color::RED.alpha(0.4),
+            app.primary.map.get_r(*r).get_thick_polygon(),
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 
-use geom::{Bounds, CornerRadii, Distance, Polygon, Pt2D, UnitFmt};
+use geom::{Bounds, CornerRadii, Distance, Polygon, Pt2D};
 use map_gui::render::{Renderable, OUTLINE_THICKNESS};
+use map_gui::tools::PromptInput;
 use map_gui::ID;
+use map_model::osm::RoadRank;
 use map_model::{
-    BufferType, Direction, EditCmd, EditRoad, LaneID, LaneSpec, LaneType, MapEdits, Road, RoadID,
+    BufferType, CurbUse, Direction, EditCmd, EditRoad, LaneID, LaneSpec, LaneType, MapEdits,
+    ParkingRestriction, ParkingRestrictions, Road, RoadID, TimeRestrictions, TURN_POCKET_TAPER,
 };
 use widgetry::tools::PopupMsg;
 use widgetry::{
@@ -127,6 +130,30 @@ impl RoadEditor {
         Transition::Keep
     }
 
+    fn modify_road<F: Fn(&mut EditRoad)>(
+        &mut self,
+        ctx: &mut EventCtx,
+        app: &mut App,
+        f: F,
+    ) -> Transition {
+        let mut edits = app.primary.map.get_edits().clone();
+        edits
+            .commands
+            .push(app.primary.map.edit_road_cmd(self.r, f));
+        apply_map_edits(ctx, app, edits);
+        self.redo_stack.clear();
+
+        // Keep selecting the same lane, if one was selected
+        self.selected_lane = self
+            .selected_lane
+            .map(|id| self.lane_for_idx(app, id.offset));
+        self.recalc_hovering(ctx, app);
+
+        self.recalc_all_panels(ctx, app);
+
+        Transition::Keep
+    }
+
     fn recalc_all_panels(&mut self, ctx: &mut EventCtx, app: &App) {
         self.main_panel = make_main_panel(
             ctx,
@@ -298,6 +325,7 @@ impl State<App> for RoadEditor {
                     return self.modify_current_lane(ctx, app, Some(0), |new, idx| {
                         new.lanes_ltr[idx].lt = lt;
                         new.lanes_ltr[idx].width = width;
+                        new.lanes_ltr[idx].pocket = None;
                     });
                 } else if let Some(lt) = x.strip_prefix("add ") {
                     let lt = if lt == "buffer" {
@@ -334,6 +362,96 @@ impl State<App> for RoadEditor {
                     self.selected_lane = Some(self.lane_for_idx(app, idx));
                     self.recalc_hovering(ctx, app);
                     panels_need_recalc = true;
+                } else if x == "parking restrictions" {
+                    let road = app.primary.map.get_r(self.r);
+                    let side = ParkingRestrictions::side_for_lane(
+                        self.selected_lane.unwrap().offset,
+                        road.lanes.len(),
+                    );
+                    return Transition::Push(PromptInput::new_state(
+                        ctx,
+                        "Parking restrictions, like: no_parking @ (07:00-09:00); loading_only @ \
+                         (10:00-16:00)",
+                        ParkingRestriction::to_osm_conditional(road.parking_restrictions.get(side)),
+                        Box::new(|input, ctx, _| {
+                            match ParkingRestriction::parse_osm_conditional(&input) {
+                                Ok(list) => Transition::Multi(vec![
+                                    Transition::Pop,
+                                    Transition::ModifyState(Box::new(move |state, ctx, app| {
+                                        let editor = state.downcast_mut::<RoadEditor>().unwrap();
+                                        editor.modify_current_lane(
+                                            ctx,
+                                            app,
+                                            Some(0),
+                                            |new, idx| {
+                                                let side = ParkingRestrictions::side_for_lane(
+                                                    idx,
+                                                    new.lanes_ltr.len(),
+                                                );
+                                                *new.parking_restrictions.get_mut(side) =
+                                                    list.clone();
+                                            },
+                                        );
+                                    })),
+                                ]),
+                                Err(err) => Transition::Replace(PopupMsg::new_state(
+                                    ctx,
+                                    "Error",
+                                    vec![err.to_string()],
+                                )),
+                            }
+                        }),
+                    ));
+                } else if x == "rename road" {
+                    let road = app.primary.map.get_r(self.r);
+                    return Transition::Push(PromptInput::new_state(
+                        ctx,
+                        "Name this road, or leave blank to use the name from OpenStreetMap",
+                        road.name_override.clone().unwrap_or_default(),
+                        Box::new(|input, _, _| {
+                            let name = input.trim().to_string();
+                            Transition::Multi(vec![
+                                Transition::Pop,
+                                Transition::ModifyState(Box::new(move |state, ctx, app| {
+                                    let editor = state.downcast_mut::<RoadEditor>().unwrap();
+                                    editor.modify_road(ctx, app, |new| {
+                                        new.name_override = if name.is_empty() {
+                                            None
+                                        } else {
+                                            Some(name.clone())
+                                        };
+                                    });
+                                })),
+                            ])
+                        }),
+                    ));
+                } else if x == "Time restrictions" {
+                    let r = self.r;
+                    let road = app.primary.map.get_r(r);
+                    return Transition::Push(PromptInput::new_state(
+                        ctx,
+                        "Restrictions during certain hours, like: bus_only @ (07:00-10:00); \
+                         no_turn_onto Main St @ (07:00-09:00); closed @ (02:00-04:00)",
+                        road.time_restrictions.to_conditional(&app.primary.map),
+                        Box::new(move |input, ctx, app: &mut App| {
+                            match TimeRestrictions::parse(&input, r, &app.primary.map) {
+                                Ok(list) => Transition::Multi(vec![
+                                    Transition::Pop,
+                                    Transition::ModifyState(Box::new(move |state, ctx, app| {
+                                        let editor = state.downcast_mut::<RoadEditor>().unwrap();
+                                        editor.modify_road(ctx, app, |new| {
+                                            new.time_restrictions = list.clone();
+                                        });
+                                    })),
+                                ]),
+                                Err(err) => Transition::Replace(PopupMsg::new_state(
+                                    ctx,
+                                    "Error",
+                                    vec![err.to_string()],
+                                )),
+                            }
+                        }),
+                    ));
                 } else if x == "Access restrictions" {
                     // The RoadEditor maintains an undo/redo stack for a single road, but the
                     // ZoneEditor usually operates on multiple roads. So before we switch over to
@@ -349,25 +467,25 @@ impl State<App> for RoadEditor {
             Outcome::Changed(x) => match x.as_ref() {
                 "speed limit" => {
                     let speed_limit = self.main_panel.dropdown_value("speed limit");
-
-                    let mut edits = app.primary.map.get_edits().clone();
-                    let old = app.primary.map.get_r_edit(self.r);
-                    let mut new = old.clone();
-                    new.speed_limit = speed_limit;
-                    edits.commands.push(EditCmd::ChangeRoad {
-                        r: self.r,
-                        old,
-                        new,
+                    return self.modify_road(ctx, app, |new| {
+                        new.speed_limit = speed_limit;
+                    });
+                }
+                "classification" => {
+                    let rank: RoadRank = self.main_panel.dropdown_value("classification");
+                    // Only record an override when it differs from OSM
+                    let rank_override =
+                        Some(rank).filter(|x| *x != app.primary.map.get_r(self.r).rank_from_osm());
+                    return self.modify_road(ctx, app, |new| {
+                        new.rank_override = rank_override;
+                    });
+                }
+                "curb use" => {
+                    let curb: CurbUse = self.main_panel.dropdown_value("curb use");
+                    return self.modify_current_lane(ctx, app, Some(0), |new, idx| {
+                        let side = ParkingRestrictions::side_for_lane(idx, new.lanes_ltr.len());
+                        new.curb.set(side, curb);
                     });
-                    apply_map_edits(ctx, app, edits);
-                    self.redo_stack.clear();
-
-                    // Keep selecting the same lane, if one was selected
-                    self.selected_lane = self
-                        .selected_lane
-                        .map(|id| self.lane_for_idx(app, id.offset));
-                    self.recalc_hovering(ctx, app);
-                    panels_need_recalc = true;
                 }
                 "width preset" => {
                     let width = self.main_panel.dropdown_value("width preset");
@@ -381,6 +499,12 @@ impl State<App> for RoadEditor {
                         new.lanes_ltr[idx].width = width;
                     });
                 }
+                "turn pocket" => {
+                    let pocket = self.main_panel.dropdown_value("turn pocket");
+                    return self.modify_current_lane(ctx, app, Some(0), |new, idx| {
+                        new.lanes_ltr[idx].pocket = pocket;
+                    });
+                }
                 "lane cards" => {
                     // hovering index changed
                     panels_need_recalc = true;
@@ -815,23 +939,45 @@ fn make_main_panel(
                     .hotkey(Key::F)
                     .build_def(ctx)
                     .centered_vert(),
+                if lane.lane_type == LaneType::Parking {
+                    Widget::row(vec![
+                        ctx.style()
+                            .btn_plain
+                            .text("parking restrictions")
+                            .build_def(ctx)
+                            .centered_vert(),
+                        Line("Curb").secondary().into_widget(ctx).centered_vert(),
+                        Widget::dropdown(
+                            ctx,
+                            "curb use",
+                            road.curb_use(l),
+                            CurbUse::all()
+                                .into_iter()
+                                .map(|x| Choice::new(x.describe(), x))
+                                .collect(),
+                        ),
+                    ])
+                } else if lane.lane_type == LaneType::Driving || lane.lane_type == LaneType::Bus {
+                    Widget::row(vec![
+                        Line("Turn pocket")
+                            .secondary()
+                            .into_widget(ctx)
+                            .centered_vert(),
+                        Widget::dropdown(ctx, "turn pocket", lane.pocket, pocket_choices(app, l)),
+                    ])
+                } else {
+                    Widget::nothing()
+                },
                 Widget::row(vec![
                     Line("Width").secondary().into_widget(ctx).centered_vert(),
                     Widget::dropdown(ctx, "width preset", lane.width, width_choices(app, l)),
-                    Spinner::widget_with_custom_rendering(
+                    Spinner::distance_widget(
                         ctx,
                         "width custom",
                         (Distance::feet(1.0), Distance::feet(20.0)),
                         lane.width,
-                        Distance::feet(0.5),
-                        // Even if the user's settings are set to meters, our step size is in feet, so
-                        // just render in feet.
-                        Box::new(|x| {
-                            x.to_string(&UnitFmt {
-                                round_durations: false,
-                                metric: false,
-                            })
-                        }),
+                        (Distance::meters(0.1), Distance::feet(0.5)),
+                        app.opts.units,
                     ),
                 ])
                 .section(ctx),
@@ -897,12 +1043,41 @@ fn make_main_panel(
             .text("Access restrictions")
             .build_def(ctx)
             .centered_vert(),
+        ctx.style()
+            .btn_outline
+            .text("Time restrictions")
+            .build_def(ctx)
+            .centered_vert(),
+    ]);
+    let label_settings = Widget::row(vec![
+        Line("Name").secondary().into_widget(ctx).centered_vert(),
+        ctx.style()
+            .btn_outline
+            .text(road.get_name(app.opts.language.as_ref()))
+            .build_widget(ctx, "rename road")
+            .centered_vert(),
+        Line("Classification")
+            .secondary()
+            .into_widget(ctx)
+            .centered_vert(),
+        Widget::dropdown(
+            ctx,
+            "classification",
+            road.get_rank(),
+            vec![
+                Choice::new("local", RoadRank::Local),
+                Choice::new("arterial", RoadRank::Arterial),
+                Choice::new("highway", RoadRank::Highway),
+            ],
+        )
+        .centered_vert(),
     ]);
 
     Panel::new_builder(
         Widget::custom_col(vec![
             Widget::col(vec![
                 road_settings,
+                label_settings,
                 Widget::horiz_separator(ctx, 1.0),
                 add_lane_row,
             ])
@@ -995,6 +1170,28 @@ fn width_choices(app: &App, l: LaneID) -> Vec<Choice<Distance>> {
         .collect()
 }
 
+/// Turn pockets are measured back from the intersection, not counting the taper before them.
+fn pocket_choices(app: &App, l: LaneID) -> Vec<Choice<Option<Distance>>> {
+    let lane = app.primary.map.get_l(l);
+    let mut lengths: Vec<Distance> = [15.0, 30.0, 45.0, 60.0, 90.0]
+        .into_iter()
+        .map(Distance::meters)
+        .filter(|x| *x + TURN_POCKET_TAPER < lane.length())
+        .collect();
+    if let Some(current) = lane.pocket {
+        if !lengths.contains(&current) {
+            lengths.push(current);
+            lengths.sort();
+        }
+    }
+
+    let mut choices = vec![Choice::new("none (full length)", None)];
+    for x in lengths {
+        choices.push(Choice::new(x.to_string(&app.opts.units), Some(x)));
+    }
+    choices
+}
+
 // TODO We need to automatically fix the direction of sidewalks and parking as we initially place
 // them or shift them around. Until then, allow fixing in the UI manually.
 fn can_reverse(_: LaneType) -> bool {
@@ -0,0 +1,197 @@
+use std::collections::BTreeSet;
+
+use geom::{Duration, Time};
+use map_model::{ParkingRestriction, ParkingRestrictionType, RoadID, SideOfRoad, TimeWindow};
+use widgetry::tools::PopupMsg;
+use widgetry::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Line, Outcome, Panel,
+    Spinner, State, Text, TextExt, Toggle, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::{CommonState, RoadSelector};
+use crate::edit::apply_map_edits;
+
+/// Schedule temporary closures for a set of roads, like street sweeping or a snow emergency route.
+/// During the closure, vehicles route around the roads and nobody can park along them.
+pub struct ScheduledClosureEditor {
+    panel: Panel,
+    selector: RoadSelector,
+    draw: Drawable,
+}
+
+impl ScheduledClosureEditor {
+    pub fn new_state(ctx: &mut EventCtx, app: &mut App) -> Box<dyn State<App>> {
+        let scheduled = roads_with_closures(app);
+        let mut batch = GeomBatch::new();
+        for r in &scheduled {
+            batch.push(
+                Color::ORANGE.alpha(0.5),
+                app.primary.map.get_r(*r).get_thick_polygon(),
+            );
+        }
+
+        let mut txt = Text::from(format!(
+            "{} roads have a closure or parking ban scheduled",
+            scheduled.len()
+        ));
+        txt.add_line(
+            Line(
+                "Select roads, then pick what happens to them and when. Vehicles already driving \
+                 along a road when it closes are allowed to finish. Clearing removes every closure \
+                 and parking restriction from the selected roads. To see the disruption, run the \
+                 simulation and check the Scheduled Closures dashboard.",
+            )
+            .secondary(),
+        );
+
+        let selector = RoadSelector::new(ctx, app, BTreeSet::new());
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Scheduled closures").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            txt.wrap_to_pct(ctx, 30).into_widget(ctx),
+            selector.make_controls(ctx).named("selector"),
+            Toggle::checkbox(ctx, "Close to vehicles", None, true),
+            Toggle::checkbox(ctx, "Ban parking", None, true),
+            Widget::row(vec![
+                "From hour".text_widget(ctx).centered_vert(),
+                Spinner::widget(ctx, "start hour", (0, 23), 8_usize, 1),
+                "until hour".text_widget(ctx).centered_vert(),
+                Spinner::widget(ctx, "end hour", (1, 24), 10_usize, 1),
+            ]),
+            Widget::row(vec![
+                ctx.style()
+                    .btn_solid_primary
+                    .text("Schedule for selected roads")
+                    .build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Clear selected roads")
+                    .build_def(ctx),
+            ]),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+        .build(ctx);
+
+        Box::new(ScheduledClosureEditor {
+            panel,
+            selector,
+            draw: ctx.upload(batch),
+        })
+    }
+
+    fn apply(&self, ctx: &mut EventCtx, app: &mut App, clear: bool) -> Transition {
+        if self.selector.roads.is_empty() {
+            return Transition::Push(PopupMsg::new_state(
+                ctx,
+                "Error",
+                vec!["Select some roads first"],
+            ));
+        }
+        let close = self.panel.is_checked("Close to vehicles");
+        let ban_parking = self.panel.is_checked("Ban parking");
+        let start_hour: usize = self.panel.spinner("start hour");
+        let end_hour: usize = self.panel.spinner("end hour");
+        if !clear && start_hour >= end_hour {
+            return Transition::Push(PopupMsg::new_state(
+                ctx,
+                "Error",
+                vec!["The closure must end after it starts"],
+            ));
+        }
+        let window = TimeWindow {
+            start_time: Time::START_OF_DAY + Duration::hours(start_hour),
+            end_time: Time::START_OF_DAY + Duration::hours(end_hour),
+        };
+
+        let mut edits = app.primary.map.get_edits().clone();
+        for r in &self.selector.roads {
+            let road = app.primary.map.get_r(*r);
+            if clear
+                && road.time_restrictions.closed.is_empty()
+                && road.parking_restrictions.is_empty()
+            {
+                continue;
+            }
+            edits
+                .commands
+                .push(app.primary.map.edit_road_cmd(*r, |new| {
+                    if clear {
+                        new.time_restrictions.closed.clear();
+                        for side in [SideOfRoad::Left, SideOfRoad::Right] {
+                            new.parking_restrictions.get_mut(side).clear();
+                        }
+                        return;
+                    }
+                    if close {
+                        new.time_restrictions.closed.push(window);
+                    }
+                    if ban_parking {
+                        for side in [SideOfRoad::Left, SideOfRoad::Right] {
+                            new.parking_restrictions
+                                .get_mut(side)
+                                .push(ParkingRestriction {
+                                    restriction: ParkingRestrictionType::NoParking,
+                                    start_time: window.start_time,
+                                    end_time: window.end_time,
+                                });
+                        }
+                    }
+                }));
+        }
+        apply_map_edits(ctx, app, edits);
+        Transition::Replace(ScheduledClosureEditor::new_state(ctx, app))
+    }
+}
+
+impl State<App> for ScheduledClosureEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Schedule for selected roads" => {
+                    return self.apply(ctx, app, false);
+                }
+                "Clear selected roads" => {
+                    return self.apply(ctx, app, true);
+                }
+                x => {
+                    if self.selector.event(ctx, app, Some(x)) {
+                        let new_controls = self.selector.make_controls(ctx);
+                        self.panel.replace(ctx, "selector", new_controls);
+                    }
+                }
+            },
+            _ => {
+                if self.selector.event(ctx, app, None) {
+                    let new_controls = self.selector.make_controls(ctx);
+                    self.panel.replace(ctx, "selector", new_controls);
+                }
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.panel.draw(g);
+        self.selector.draw(g, app, true);
+        CommonState::draw_osd(g, app);
+    }
+}
+
+/// Roads closed to vehicles or with parking banned during some hours
+fn roads_with_closures(app: &App) -> BTreeSet<RoadID> {
+    app.primary
+        .map
+        .all_roads()
+        .iter()
+        .filter(|r| !r.time_restrictions.closed.is_empty() || !r.parking_restrictions.is_empty())
+        .map(|r| r.id)
+        .collect()
+}
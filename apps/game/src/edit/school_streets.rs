@@ -0,0 +1,132 @@
+use geom::{Duration, Time};
+use widgetry::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line, Outcome, Panel,
+    Spinner, State, Text, TextExt, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::CommonState;
+use crate::edit::apply_map_edits;
+
+/// Close the streets in front of schools to cars around the start and end of the school day.
+pub struct SchoolStreetsEditor {
+    panel: Panel,
+    draw: Drawable,
+}
+
+impl SchoolStreetsEditor {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let map = &app.primary.map;
+        let schools = synthpop::find_schools(map);
+        let num_closed = schools
+            .iter()
+            .filter(|b| synthpop::is_school_street(map, **b))
+            .count();
+        let any_zones = map
+            .get_edits()
+            .restricted_zones
+            .iter()
+            .any(|z| z.name.starts_with(synthpop::SCHOOL_STREET_ZONE));
+
+        let mut batch = GeomBatch::new();
+        for b in &schools {
+            batch.push(Color::ORANGE, map.get_b(*b).polygon.clone());
+        }
+        for r in synthpop::roads_fronting_schools(map) {
+            batch.push(Color::CYAN.alpha(0.5), map.get_r(r).get_thick_polygon());
+        }
+
+        let mut txt = Text::from(format!(
+            "{} schools found, {} with their street closed",
+            schools.len(),
+            num_closed
+        ));
+        txt.add_line(
+            Line(
+                "Closed streets only allow buses and bikes through during drop-off and pick-up. \
+                 Cars can still reach the school and homes along them.",
+            )
+            .secondary(),
+        );
+        txt.add_line(
+            Line(
+                "To simulate the school run, add school runs to the scenario, then compare \
+                 results in the School Streets dashboard.",
+            )
+            .secondary(),
+        );
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("School streets").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            txt.wrap_to_pct(ctx, 30).into_widget(ctx),
+            Widget::row(vec![
+                "Morning bell at hour".text_widget(ctx).centered_vert(),
+                Spinner::widget(ctx, "bell hour", (6, 10), 8_usize, 1),
+            ]),
+            Widget::row(vec![
+                ctx.style()
+                    .btn_solid_primary
+                    .text("Close all school streets")
+                    .hotkey(Key::C)
+                    .disabled(schools.is_empty() || num_closed == schools.len())
+                    .build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Reopen all school streets")
+                    .hotkey(Key::R)
+                    .disabled(!any_zones)
+                    .build_def(ctx),
+            ]),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+        .build(ctx);
+
+        Box::new(SchoolStreetsEditor {
+            panel,
+            draw: ctx.upload(batch),
+        })
+    }
+}
+
+impl State<App> for SchoolStreetsEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            let close = match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Close all school streets" => true,
+                "Reopen all school streets" => false,
+                _ => unreachable!(),
+            };
+
+            let mut edits = app.primary.map.get_edits().clone();
+            if close {
+                let bell_hour: usize = self.panel.spinner("bell hour");
+                edits.restricted_zones.extend(synthpop::school_street_zones(
+                    &app.primary.map,
+                    Time::START_OF_DAY + Duration::hours(bell_hour),
+                ));
+            } else {
+                edits
+                    .restricted_zones
+                    .retain(|z| !z.name.starts_with(synthpop::SCHOOL_STREET_ZONE));
+            }
+            apply_map_edits(ctx, app, edits);
+            return Transition::Replace(SchoolStreetsEditor::new_state(ctx, app));
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.panel.draw(g);
+        CommonState::draw_osd(g, app);
+    }
+}
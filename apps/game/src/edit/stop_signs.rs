@@ -15,7 +15,9 @@ use widgetry::{
 use crate::app::App;
 use crate::app::Transition;
 use crate::common::CommonState;
-use crate::edit::{apply_map_edits, check_sidewalk_connectivity, TrafficSignalEditor};
+use crate::edit::{
+    apply_map_edits, check_sidewalk_connectivity, CornerRadiusEditor, TrafficSignalEditor,
+};
 use crate::sandbox::GameplayMode;
 
 // TODO For now, individual turns can't be manipulated. Banning turns could be useful, but I'm not
@@ -68,6 +70,10 @@ impl StopSignEditor {
                 .btn_outline
                 .text("convert to traffic signal")
                 .build_def(ctx),
+            ctx.style()
+                .btn_outline
+                .text("change corner radius")
+                .build_def(ctx),
             ctx.style()
                 .btn_solid_primary
                 .text("Finish")
@@ -154,6 +160,9 @@ impl SimpleState<App> for StopSignEditor {
                     self.mode.clone(),
                 ))
             }
+            "change corner radius" => {
+                Transition::Push(CornerRadiusEditor::new_state(ctx, app, self.id))
+            }
             _ => unreachable!(),
         }
     }
@@ -11,7 +11,9 @@ use widgetry::{
 
 use crate::app::{App, Transition};
 use crate::edit::traffic_signals::{BundleEdits, TrafficSignalEditor};
-use crate::edit::{apply_map_edits, check_sidewalk_connectivity, StopSignEditor};
+use crate::edit::{
+    apply_map_edits, check_sidewalk_connectivity, CornerRadiusEditor, StopSignEditor,
+};
 use crate::sandbox::GameplayMode;
 
 pub struct ChangeDuration {
@@ -204,6 +206,7 @@ pub fn edit_entire_signal(
         .as_ref()
         .map(|x| format!("import from GMNS {}", x));
     let gmns_all = "import all traffic signals from a new GMNS timing.csv";
+    let corners = "change corner radius";
 
     let mut choices = vec![use_template.to_string()];
     if has_sidewalks {
@@ -221,6 +224,7 @@ pub fn edit_entire_signal(
         choices.push(x);
     }
     choices.push(gmns_all.to_string());
+    choices.push(corners.to_string());
 
     ChooseSomething::new_state(
         ctx,
@@ -409,6 +413,7 @@ pub fn edit_entire_signal(
                     }
                 }),
             )),
+            x if x == corners => Transition::Replace(CornerRadiusEditor::new_state(ctx, app, i)),
             _ => unreachable!(),
         }),
     )
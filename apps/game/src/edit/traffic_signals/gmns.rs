@@ -53,6 +53,8 @@ pub fn import(map: &Map, i: IntersectionID, path: &str) -> Result<ControlTraffic
                     protected_movements: BTreeSet::new(),
                     yield_movements: BTreeSet::new(),
                     stage_type: StageType::Fixed(Duration::seconds(rec.green_time as f64)),
+                    yellow: Duration::ZERO,
+                    all_red: Duration::ZERO,
                 });
             }
             std::cmp::Ordering::Less => {
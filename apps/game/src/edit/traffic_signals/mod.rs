@@ -1,9 +1,9 @@
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 use anyhow::Result;
 
 use abstutil::Timer;
-use geom::{Distance, Line, Polygon, Pt2D};
+use geom::{Distance, Duration, Line, Polygon, Pt2D};
 use map_gui::options::TrafficSignalStyle;
 use map_gui::render::{traffic_signal, DrawMovement, DrawOptions};
 use map_model::{
@@ -14,7 +14,7 @@ use widgetry::tools::PopupMsg;
 use widgetry::{
     include_labeled_bytes, lctrl, Color, ControlState, DragDrop, DrawBaselayer, Drawable, EventCtx,
     GeomBatch, GeomBatchStack, GfxCtx, HorizontalAlignment, Image, Key, Line, Outcome, Panel,
-    RewriteColor, StackAxis, State, Text, TextExt, VerticalAlignment, Widget,
+    RewriteColor, StackAxis, State, Text, TextExt, Toggle, VerticalAlignment, Widget,
 };
 
 use crate::app::{App, ShowEverything, Transition};
@@ -30,6 +30,9 @@ mod preview;
 
 // Welcome to one of the most overwhelmingly complicated parts of the UI...
 
+/// When adding yellow and all-red intervals to a fixed stage, keep at least this much green time
+const MIN_GREEN: Duration = Duration::const_seconds(5.0);
+
 pub struct TrafficSignalEditor {
     side_panel: Panel,
     top_panel: Panel,
@@ -234,6 +237,52 @@ impl State<App> for TrafficSignalEditor {
                     });
                     return Transition::Keep;
                 }
+                "Lengthen stages for slow walkers" => {
+                    let fixes = find_short_stages(app, &self.members);
+                    self.add_new_edit(ctx, app, self.current_stage, |ts| {
+                        for (idx, (_, needed)) in &fixes {
+                            match ts.stages[*idx].stage_type {
+                                StageType::Fixed(ref mut d) => {
+                                    *d = (*d).max(*needed);
+                                }
+                                StageType::Variable(ref mut min, _, _) => {
+                                    *min = (*min).max(*needed);
+                                }
+                            }
+                        }
+                    });
+                    return Transition::Keep;
+                }
+                "Set yellow and all-red intervals" => {
+                    let fixes = find_short_clearances(app, &self.members);
+                    self.add_new_edit(ctx, app, self.current_stage, |ts| {
+                        for (idx, (yellow, all_red)) in &fixes {
+                            let stage = &mut ts.stages[*idx];
+                            stage.yellow = stage.yellow.max(*yellow);
+                            stage.all_red = stage.all_red.max(*all_red);
+                            if let StageType::Fixed(ref mut d) = stage.stage_type {
+                                *d = (*d).max(stage.yellow + stage.all_red + MIN_GREEN);
+                            }
+                        }
+                    });
+                    return Transition::Keep;
+                }
+                "Make conflicting movements yield" => {
+                    let map = &app.primary.map;
+                    let fixed: BTreeMap<IntersectionID, ControlTrafficSignal> = self
+                        .members
+                        .iter()
+                        .map(|i| {
+                            let mut signal = map.get_traffic_signal(*i).clone();
+                            signal.demote_conflicts(map.get_i(*i));
+                            (*i, signal)
+                        })
+                        .collect();
+                    self.add_new_edit(ctx, app, self.current_stage, |ts| {
+                        *ts = fixed[&ts.id].clone();
+                    });
+                    return Transition::Keep;
+                }
                 "change duration" => {
                     return Transition::Push(edits::ChangeDuration::new_state(
                         ctx,
@@ -272,6 +321,23 @@ impl State<App> for TrafficSignalEditor {
                     ts.stages.swap(old_idx, new_idx);
                 });
             }
+            Outcome::Changed(x) => match x.as_ref() {
+                "bike box" => {
+                    let enabled = self.side_panel.is_checked("bike box");
+                    self.add_new_edit(ctx, app, self.current_stage, |ts| {
+                        ts.bike_box = enabled;
+                    });
+                    return Transition::Keep;
+                }
+                "two-stage left turns for bikes" => {
+                    let enabled = self.side_panel.is_checked("two-stage left turns for bikes");
+                    self.add_new_edit(ctx, app, self.current_stage, |ts| {
+                        ts.two_stage_bike_left_turns = enabled;
+                    });
+                    return Transition::Keep;
+                }
+                _ => {}
+            },
             _ => {}
         }
 
@@ -295,6 +361,15 @@ impl State<App> for TrafficSignalEditor {
                                 changes to include them.",
                             ],
                         ));
+                    } else if !find_conflicts(app, &self.members).is_empty() {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error: conflicting movements",
+                            vec![
+                                "Some stages protect movements that cross each other",
+                                "Make one of each pair yield or forbid it first.",
+                            ],
+                        ));
                     } else if let Err(err) = self.validate_all_members(app) {
                         // TODO There's some crash between usvg and lyon trying to tesellate the
                         // error text!
@@ -741,6 +816,113 @@ fn make_side_panel(
 
     col.push(drag_drop.into_widget(ctx));
 
+    col.push(Widget::row(vec![
+        Toggle::checkbox(ctx, "bike box", None, canonical_signal.bike_box),
+        Toggle::checkbox(
+            ctx,
+            "two-stage left turns for bikes",
+            None,
+            canonical_signal.two_stage_bike_left_turns,
+        ),
+    ]));
+
+    let short_stages = find_short_stages(app, members);
+    if !short_stages.is_empty() {
+        let mut txt = Text::from(Line(format!(
+            "Too short for people walking {:.2} m/s",
+            app.session.slow_walking_speed.inner_meters_per_second()
+        )));
+        for (idx, (given, needed)) in short_stages {
+            txt.add_line(
+                Line(format!(
+                    "Stage {} lasts {}, but crossing takes {}",
+                    idx + 1,
+                    given,
+                    needed
+                ))
+                .secondary(),
+            );
+        }
+        col.push(
+            Widget::col(vec![
+                txt.into_widget(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Lengthen stages for slow walkers")
+                    .build_def(ctx),
+            ])
+            .padding(10)
+            .bg(app.cs.inner_panel_bg),
+        );
+    }
+
+    let conflicts = find_conflicts(app, members);
+    if !conflicts.is_empty() {
+        let mut txt = Text::from(Line("Conflicting protected movements").fg(Color::RED));
+        for (idx, count) in conflicts {
+            txt.add_line(
+                Line(format!(
+                    "Stage {} protects {} pairs of movements that cross",
+                    idx + 1,
+                    count
+                ))
+                .secondary(),
+            );
+        }
+        col.push(
+            Widget::col(vec![
+                txt.into_widget(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Make conflicting movements yield")
+                    .build_def(ctx),
+            ])
+            .padding(10)
+            .bg(app.cs.inner_panel_bg),
+        );
+    }
+
+    let selected_stage = &canonical_signal.stages[selected];
+    if selected_stage.yellow + selected_stage.all_red > Duration::ZERO {
+        col.push(
+            format!(
+                "Stage {} ends with {} yellow and {} all-red",
+                selected + 1,
+                selected_stage.yellow,
+                selected_stage.all_red
+            )
+            .text_widget(ctx),
+        );
+    }
+    let short_clearances = find_short_clearances(app, members);
+    if !short_clearances.is_empty() {
+        let mut txt = Text::from(Line(
+            "Clearance intervals too short for the approach speeds",
+        ));
+        for (idx, (yellow, all_red)) in short_clearances {
+            txt.add_line(
+                Line(format!(
+                    "Stage {} needs {} yellow and {} all-red",
+                    idx + 1,
+                    yellow,
+                    all_red
+                ))
+                .secondary(),
+            );
+        }
+        col.push(
+            Widget::col(vec![
+                txt.into_widget(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Set yellow and all-red intervals")
+                    .build_def(ctx),
+            ])
+            .padding(10)
+            .bg(app.cs.inner_panel_bg),
+        );
+    }
+
     col.push(Widget::row(vec![
         // TODO Say "normally" to account for variable stages?
         format!(
@@ -833,6 +1015,58 @@ impl BundleEdits {
 }
 
 // If None, nothing missing.
+/// Finds stages too short for slow walkers to finish crossing at any member signal. Returns the
+/// stage's current duration and the longest time needed to cross.
+fn find_short_stages(
+    app: &App,
+    members: &BTreeSet<IntersectionID>,
+) -> BTreeMap<usize, (Duration, Duration)> {
+    let map = &app.primary.map;
+    let mut results: BTreeMap<usize, (Duration, Duration)> = BTreeMap::new();
+    for i in members {
+        for x in map
+            .get_traffic_signal(*i)
+            .find_short_crossings(map.get_i(*i), app.session.slow_walking_speed)
+        {
+            let entry = results.entry(x.stage).or_insert((x.given, x.needed));
+            entry.1 = entry.1.max(x.needed);
+        }
+    }
+    results
+}
+
+/// Counts the pairs of conflicting protected movements in each stage, across all member signals.
+fn find_conflicts(app: &App, members: &BTreeSet<IntersectionID>) -> BTreeMap<usize, usize> {
+    let map = &app.primary.map;
+    let mut results: BTreeMap<usize, usize> = BTreeMap::new();
+    for i in members {
+        for x in map.get_traffic_signal(*i).find_conflicts(map.get_i(*i)) {
+            *results.entry(x.stage).or_insert(0) += 1;
+        }
+    }
+    results
+}
+
+/// Finds stages with a yellow or all-red interval shorter than recommended at any member signal.
+/// Returns the longest recommended (yellow, all-red) intervals.
+fn find_short_clearances(
+    app: &App,
+    members: &BTreeSet<IntersectionID>,
+) -> BTreeMap<usize, (Duration, Duration)> {
+    let map = &app.primary.map;
+    let mut results: BTreeMap<usize, (Duration, Duration)> = BTreeMap::new();
+    for i in members {
+        for x in map.get_traffic_signal(*i).find_short_clearances(map) {
+            let entry = results
+                .entry(x.stage)
+                .or_insert((Duration::ZERO, Duration::ZERO));
+            entry.0 = entry.0.max(x.recommended_yellow);
+            entry.1 = entry.1.max(x.recommended_all_red);
+        }
+    }
+    results
+}
+
 fn check_for_missing_turns(app: &App, members: &BTreeSet<IntersectionID>) -> Option<BundleEdits> {
     let mut all_missing = BTreeSet::new();
     for i in members {
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 
+use abstutil::Counter;
 use geom::{Angle, Circle, Distance, Speed, Time};
 use map_gui::render::DrawPedestrian;
 use map_model::{BuildingID, LaneID, OffstreetParking, Traversable, SIDEWALK_THICKNESS};
@@ -154,6 +155,7 @@ fn people_body(ctx: &mut EventCtx, app: &App, details: &mut Details, id: Buildin
     let mut drivers = 0;
     let mut cyclists = 0;
     let mut others = 0;
+    let mut activities = Counter::new();
 
     let mut ppl: Vec<(Time, Widget)> = Vec::new();
     for p in app.primary.sim.bldg_to_people(id) {
@@ -193,11 +195,23 @@ fn people_body(ctx: &mut EventCtx, app: &App, details: &mut Details, id: Buildin
             }
         }
 
+        let activity = person.current_activity().map(|a| {
+            activities.inc(a.purpose);
+            format!(
+                "At {} for {}",
+                a.purpose,
+                a.duration(app.primary.sim.time())
+            )
+        });
+
         details
             .hyperlinks
             .insert(p.to_string(), Tab::PersonTrips(p, BTreeMap::new()));
         let widget = Widget::row(vec![
             ctx.style().btn_outline.text(p.to_string()).build_def(ctx),
+            activity
+                .unwrap_or_else(|| "Inside".to_string())
+                .text_widget(ctx),
             if let Some((t, mode)) = next_trip {
                 format!(
                     "Leaving in {} to {}",
@@ -229,6 +243,15 @@ fn people_body(ctx: &mut EventCtx, app: &App, details: &mut Details, id: Buildin
             )
             .text_widget(ctx),
         );
+        rows.push(
+            activities
+                .borrow()
+                .iter()
+                .map(|(purpose, cnt)| format!("{} at {}", cnt, purpose))
+                .collect::<Vec<_>>()
+                .join(", ")
+                .text_widget(ctx),
+        );
 
         for (_, w) in ppl {
             rows.push(w);
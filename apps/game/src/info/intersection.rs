@@ -4,6 +4,7 @@ use abstutil::prettyprint_usize;
 use geom::{ArrowCap, Distance, Duration, PolyLine, Polygon, Time};
 use map_gui::options::TrafficSignalStyle;
 use map_gui::render::traffic_signal::draw_signal_stage;
+use map_gui::tools::describe_note;
 use map_model::{IntersectionID, IntersectionType, StageType};
 use sim::AgentType;
 use widgetry::{
@@ -14,6 +15,7 @@ use widgetry::{
 use crate::app::App;
 use crate::common::color_for_agent_type;
 use crate::info::{header_btns, make_tabs, throughput, DataOptions, Details, Tab};
+use crate::layer::level_of_service;
 
 pub fn info(ctx: &EventCtx, app: &App, details: &mut Details, id: IntersectionID) -> Widget {
     Widget::custom_col(vec![
@@ -42,6 +44,35 @@ fn info_body(ctx: &EventCtx, app: &App, id: IntersectionID) -> Widget {
     }
     rows.push(txt.into_widget(ctx));
 
+    if let Some(note) = app.primary.map.get_edits().intersection_notes.get(&id) {
+        rows.push(describe_note(note).into_widget(ctx));
+    }
+
+    if let Some(data) = crate::layer::collisions::get_collisions(app) {
+        let indices = data.per_intersection.get(&id).cloned().unwrap_or_default();
+        let mut txt = Text::from(format!(
+            "{} recorded collisions",
+            prettyprint_usize(indices.len())
+        ));
+        let mut collisions: Vec<&collisions::Collision> = indices
+            .into_iter()
+            .map(|idx| &data.collisions[idx])
+            .collect();
+        collisions.sort_by_key(|c| c.time);
+        for c in collisions {
+            let mut line = format!(
+                "  {}: {:?}",
+                (Time::START_OF_DAY + c.time).ampm_tostring(),
+                c.severity
+            );
+            if let Some(mode) = c.mode {
+                line = format!("{}, involving {:?}", line, mode);
+            }
+            txt.add_line(Line(line).secondary());
+        }
+        rows.push(txt.into_widget(ctx));
+    }
+
     if app.opts.dev {
         rows.push(
             ctx.style()
@@ -113,6 +144,98 @@ fn traffic_body(ctx: &mut EventCtx, app: &App, id: IntersectionID, opts: &DataOp
     Widget::col(rows)
 }
 
+pub fn movements(
+    ctx: &mut EventCtx,
+    app: &App,
+    details: &mut Details,
+    id: IntersectionID,
+) -> Widget {
+    Widget::custom_col(vec![
+        header(ctx, app, details, id, Tab::IntersectionMovements(id)),
+        movements_body(ctx, app, id).tab_body(ctx),
+    ])
+}
+
+fn movements_body(ctx: &mut EventCtx, app: &App, id: IntersectionID) -> Widget {
+    let mut rows = vec![];
+    let counts = app
+        .primary
+        .sim
+        .get_analytics()
+        .turning_movement_counts(id, &app.primary.map);
+
+    let (start, peak) = match counts.peak_hour() {
+        Some(pair) => pair,
+        None => {
+            return "Nothing has crossed yet".text_widget(ctx);
+        }
+    };
+    let mut txt = Text::from(format!(
+        "Peak hour: {} to {}",
+        start.ampm_tostring(),
+        (start + Duration::hours(1)).ampm_tostring()
+    ));
+    for ((_, dir), c) in counts.approaches.iter().zip(peak.iter()) {
+        txt.add_line(Line(dir).small_heading());
+        txt.add_line(format!(
+            "  {} left, {} through, {} right, {} U-turns",
+            prettyprint_usize(c.left),
+            prettyprint_usize(c.through),
+            prettyprint_usize(c.right),
+            prettyprint_usize(c.u_turn)
+        ));
+        txt.add_line(
+            Line(format!(
+                "  {} bikes, {} pedestrians crossing",
+                prettyprint_usize(c.bikes),
+                prettyprint_usize(c.pedestrians)
+            ))
+            .secondary(),
+        );
+    }
+    rows.push(txt.into_widget(ctx));
+
+    // Left turns across heavy oncoming traffic are the usual reason for a protected left turn
+    // phase. The common rule of thumb compares the product of the two peak hour volumes to
+    // 50,000 when there's one opposing lane.
+    let mut txt = Text::from("Left turns against oncoming traffic during the peak hour");
+    txt.add_line(Line("Red ones may need a protected left turn phase").secondary());
+    let mut any = false;
+    for (idx, (_, dir)) in counts.approaches.iter().enumerate() {
+        if let Some(opposing) = counts.opposing(idx) {
+            if peak[idx].left == 0 {
+                continue;
+            }
+            any = true;
+            let product = peak[idx].left * peak[opposing].through;
+            let line = Line(format!(
+                "  {}: {} left x {} oncoming = {}",
+                dir,
+                prettyprint_usize(peak[idx].left),
+                prettyprint_usize(peak[opposing].through),
+                prettyprint_usize(product)
+            ));
+            txt.add_line(if product >= 50_000 {
+                line.fg(Color::RED)
+            } else {
+                line.secondary()
+            });
+        }
+    }
+    if any {
+        rows.push(txt.into_widget(ctx));
+    }
+
+    rows.push(
+        ctx.style()
+            .btn_outline
+            .text("Export to CSV")
+            .build_widget(ctx, format!("export turning movements at {}", id)),
+    );
+
+    Widget::col(rows)
+}
+
 pub fn delay(
     ctx: &mut EventCtx,
     app: &App,
@@ -155,6 +278,7 @@ fn delay_body(
     ));
 
     rows.push(delay_plot(ctx, app, id, opts, fan_chart));
+    rows.push(level_of_service(ctx, app, id, opts));
 
     Widget::col(rows)
 }
@@ -372,6 +496,40 @@ fn traffic_signal_body(ctx: &mut EventCtx, app: &App, id: IntersectionID) -> Wid
     Widget::col(rows)
 }
 
+fn level_of_service(ctx: &EventCtx, app: &App, i: IntersectionID, opts: &DataOptions) -> Widget {
+    let data = if opts.show_before {
+        app.prebaked()
+    } else {
+        app.primary.sim.get_analytics()
+    };
+    let limit = if opts.show_end_of_day {
+        app.primary.sim.get_end_of_day()
+    } else {
+        app.primary.sim.time()
+    };
+    let (overall, approaches) =
+        if let Some(pair) = data.level_of_service(i, &app.primary.map, limit) {
+            pair
+        } else {
+            return Widget::col(vec![
+                Line("Level of service").small_heading().into_widget(ctx),
+                Line("No vehicles have passed through yet")
+                    .secondary()
+                    .into_widget(ctx),
+            ]);
+        };
+    Widget::col(vec![
+        Widget::row(vec![
+            Line("Level of service").small_heading().into_widget(ctx),
+            Line(overall.grade.to_string())
+                .small_heading()
+                .fg(level_of_service::grade_color(overall.grade))
+                .into_widget(ctx),
+        ]),
+        level_of_service::describe(app, &overall, &approaches).into_widget(ctx),
+    ])
+}
+
 fn delay_plot(
     ctx: &EventCtx,
     app: &App,
@@ -466,6 +624,9 @@ fn header(
             tabs.push(("Current demand", Tab::IntersectionDemand(id)));
             tabs.push(("Signal", Tab::IntersectionTrafficSignal(id)));
         }
+        if !i.is_border() {
+            tabs.push(("Movements", Tab::IntersectionMovements(id)));
+        }
         if i.is_incoming_border() {
             tabs.push((
                 "Arrivals",
@@ -1,5 +1,6 @@
 use abstutil::prettyprint_usize;
-use map_model::{LaneID, PathConstraints};
+use map_gui::tools::describe_note;
+use map_model::{CurbUse, LaneID, ParkingRestrictions, PathConstraints};
 use widgetry::{EventCtx, Line, LinePlot, PlotOptions, Series, Text, TextExt, Widget};
 
 use crate::app::App;
@@ -35,6 +36,59 @@ fn info_body(ctx: &EventCtx, app: &App, id: LaneID) -> Widget {
             kv.push(("No through-traffic for", ban.join(", ")));
         }
     }
+    if !r.time_restrictions.bus_only.is_empty() {
+        kv.push((
+            "Bus-only",
+            r.time_restrictions
+                .bus_only
+                .iter()
+                .map(|w| {
+                    format!(
+                        "{} - {}",
+                        w.start_time.ampm_tostring(),
+                        w.end_time.ampm_tostring()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+    if !r.time_restrictions.closed.is_empty() {
+        kv.push((
+            "Closed to vehicles",
+            r.time_restrictions
+                .closed
+                .iter()
+                .map(|w| {
+                    format!(
+                        "{} - {}",
+                        w.start_time.ampm_tostring(),
+                        w.end_time.ampm_tostring()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+    for zone in &map.get_edits().restricted_zones {
+        if zone.members.contains(&r.id) {
+            kv.push((
+                "Restricted zone",
+                format!("{} ({})", zone.name, zone.describe_windows()),
+            ));
+        }
+    }
+    for (to, w) in &r.time_restrictions.turn_bans {
+        kv.push((
+            "No turns",
+            format!(
+                "onto {} {} - {}",
+                map.get_r(*to).get_name(app.opts.language.as_ref()),
+                w.start_time.ampm_tostring(),
+                w.end_time.ampm_tostring()
+            ),
+        ));
+    }
 
     if l.is_parking() {
         kv.push((
@@ -45,14 +99,67 @@ fn info_body(ctx: &EventCtx, app: &App, id: LaneID) -> Widget {
                 l.number_parking_spots(app.primary.map.get_config())
             ),
         ));
+        let curb = r.curb_use(l.id);
+        if curb != CurbUse::Parking {
+            kv.push(("Curb", format!("{}, not parking", curb.describe())));
+        }
+        if let Some(zone) = app.primary.map.get_edits().permit_zone_for_road(r.id) {
+            kv.push((
+                "Permit zone",
+                format!(
+                    "{}, only for residents of {} buildings",
+                    zone.name,
+                    zone.buildings.len()
+                ),
+            ));
+        }
+        let restrictions = r
+            .parking_restrictions
+            .get(ParkingRestrictions::side_for_lane(
+                l.id.offset,
+                r.lanes.len(),
+            ));
+        if !restrictions.is_empty() {
+            kv.push((
+                "Restrictions",
+                restrictions
+                    .iter()
+                    .map(|x| {
+                        format!(
+                            "{} {} - {}",
+                            x.restriction.describe(),
+                            x.start_time.ampm_tostring(),
+                            x.end_time.ampm_tostring()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
     } else {
         kv.push(("Speed limit", r.speed_limit.to_string(&app.opts.units)));
     }
 
     kv.push(("Length", l.length().to_string(&app.opts.units)));
+    if r.is_contraflow(l.id) {
+        kv.push((
+            "Direction",
+            "contraflow (against general traffic)".to_string(),
+        ));
+    }
+    if let Some(pocket) = l.pocket {
+        kv.push((
+            "Turn pocket",
+            format!("last {} of the lane", pocket.to_string(&app.opts.units)),
+        ));
+    }
 
     rows.extend(make_table(ctx, kv));
 
+    if let Some(note) = map.get_edits().road_notes.get(&id.road) {
+        rows.push(describe_note(note).into_widget(ctx));
+    }
+
     if l.is_parking() {
         let capacity = l.number_parking_spots(app.primary.map.get_config());
         let mut series = vec![Series {
@@ -61,7 +168,7 @@ fn info_body(ctx: &EventCtx, app: &App, id: LaneID) -> Widget {
             pts: app.primary.sim.get_analytics().parking_lane_availability(
                 app.primary.sim.time(),
                 l.id,
-                capacity,
+                map,
             ),
         }];
         if app.has_prebaked().is_some() {
@@ -71,7 +178,7 @@ fn info_body(ctx: &EventCtx, app: &App, id: LaneID) -> Widget {
                 pts: app.prebaked().parking_lane_availability(
                     app.primary.sim.get_end_of_day(),
                     l.id,
-                    capacity,
+                    map,
                 ),
             });
         }
@@ -12,7 +12,7 @@ use sim::{
     VehicleType,
 };
 use widgetry::mapspace::{ToggleZoomed, ToggleZoomedBuilder};
-use widgetry::tools::open_browser;
+use widgetry::tools::{open_browser, PopupMsg};
 use widgetry::{
     EventCtx, GfxCtx, Key, Line, LinePlot, Outcome, Panel, PlotOptions, Series, Text, TextExt,
     Toggle, Widget,
@@ -82,6 +82,7 @@ pub enum Tab {
     IntersectionDemand(IntersectionID),
     IntersectionArrivals(IntersectionID, DataOptions),
     IntersectionTrafficSignal(IntersectionID),
+    IntersectionMovements(IntersectionID),
 
     LaneInfo(LaneID),
     LaneDebug(LaneID),
@@ -129,6 +130,13 @@ impl Tab {
                         Tab::IntersectionInfo(i)
                     }
                 }
+                "movements" => {
+                    if app.primary.map.get_i(i).is_border() {
+                        Tab::IntersectionInfo(i)
+                    } else {
+                        Tab::IntersectionMovements(i)
+                    }
+                }
                 _ => unreachable!(),
             },
             ID::Building(b) => match app.session.info_panel_tab["bldg"] {
@@ -217,7 +225,8 @@ impl Tab {
             | Tab::IntersectionDelay(i, _, _)
             | Tab::IntersectionDemand(i)
             | Tab::IntersectionArrivals(i, _)
-            | Tab::IntersectionTrafficSignal(i) => Some(ID::Intersection(*i)),
+            | Tab::IntersectionTrafficSignal(i)
+            | Tab::IntersectionMovements(i) => Some(ID::Intersection(*i)),
             Tab::LaneInfo(l) | Tab::LaneDebug(l) | Tab::LaneTraffic(l, _) => Some(ID::Lane(*l)),
         }
     }
@@ -279,6 +288,7 @@ impl Tab {
             Tab::IntersectionDemand(_) => ("intersection", "demand"),
             Tab::IntersectionArrivals(_, _) => ("intersection", "arrivals"),
             Tab::IntersectionTrafficSignal(_) => ("intersection", "traffic signal"),
+            Tab::IntersectionMovements(_) => ("intersection", "movements"),
             Tab::LaneInfo(_) => ("lane", "info"),
             Tab::LaneDebug(_) => ("lane", "debug"),
             Tab::LaneTraffic(_, _) => ("lane", "traffic"),
@@ -346,7 +356,10 @@ impl InfoPanel {
             Tab::ParkingLot(pl) => (parking_lot::info(ctx, app, &mut details, pl), true),
             Tab::Crowd(ref members) => (person::crowd(ctx, app, &mut details, members), true),
             Tab::Area(a) => (debug::area(ctx, app, &mut details, a), true),
-            Tab::IntersectionInfo(i) => (intersection::info(ctx, app, &mut details, i), true),
+            Tab::IntersectionInfo(i) => {
+                crate::layer::collisions::load_collisions(app);
+                (intersection::info(ctx, app, &mut details, i), true)
+            }
             Tab::IntersectionTraffic(i, ref opts) => (
                 intersection::traffic(ctx, app, &mut details, i, opts),
                 false,
@@ -367,6 +380,9 @@ impl InfoPanel {
                 intersection::traffic_signal(ctx, app, &mut details, i),
                 false,
             ),
+            Tab::IntersectionMovements(i) => {
+                (intersection::movements(ctx, app, &mut details, i), false)
+            }
             Tab::LaneInfo(l) => (lane::info(ctx, app, &mut details, l), true),
             Tab::LaneDebug(l) => (lane::debug(ctx, app, &mut details, l), false),
             Tab::LaneTraffic(l, ref opts) => {
@@ -605,6 +621,37 @@ impl InfoPanel {
                             IntersectionID(x.parse::<usize>().unwrap()),
                         ))),
                     )
+                } else if let Some(x) =
+                    action.strip_prefix("export turning movements at Intersection #")
+                {
+                    let i = IntersectionID(x.parse::<usize>().unwrap());
+                    let path = format!(
+                        "turning_movements_{}_{}_{}.csv",
+                        app.primary.map.get_name().as_filename(),
+                        i.0,
+                        app.primary.sim.time().as_filename()
+                    );
+                    (
+                        false,
+                        Some(Transition::Push(
+                            match app
+                                .primary
+                                .sim
+                                .get_analytics()
+                                .turning_movement_counts(i, &app.primary.map)
+                                .export_csv(&path)
+                            {
+                                Ok(()) => PopupMsg::new_state(
+                                    ctx,
+                                    "Data exported",
+                                    vec![format!("Data exported to {}", path)],
+                                ),
+                                Err(err) => {
+                                    PopupMsg::new_state(ctx, "Export failed", vec![err.to_string()])
+                                }
+                            },
+                        )),
+                    )
                 } else if let Some(id) = maybe_id {
                     let mut close_panel = true;
                     let t = ctx_actions.execute(ctx, app, id, action, &mut close_panel);
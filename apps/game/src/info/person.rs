@@ -10,7 +10,7 @@ use sim::{
     AgentID, CarID, ParkingSpot, PedestrianID, Person, PersonID, PersonState, TripID, TripResult,
     VehicleType,
 };
-use synthpop::{TripEndpoint, TripMode};
+use synthpop::{TripEndpoint, TripMode, TripPurpose};
 use widgetry::{
     include_labeled_bytes, Color, ControlState, CornerRounding, EdgeInsets, EventCtx, GeomBatch,
     Image, Key, Line, RewriteColor, Text, TextExt, TextSpan, Widget,
@@ -183,6 +183,15 @@ fn trips_body(
             } else {
                 Widget::nothing()
             },
+            if let Some(purpose) = TripPurpose::infer(trip.departure, &trip.start, &trip.end, map) {
+                Line(purpose.to_string())
+                    .secondary()
+                    .batch(ctx)
+                    .centered_vert()
+                    .margin_right(15)
+            } else {
+                Widget::nothing()
+            },
             if trip_status == "finished" {
                 if let Some(before) = app
                     .has_prebaked()
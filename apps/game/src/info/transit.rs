@@ -3,7 +3,7 @@ use geom::{Circle, Distance, Time};
 use map_gui::tools::ColorNetwork;
 use map_gui::ID;
 use map_model::{PathStep, TransitRoute, TransitRouteID, TransitStopID};
-use sim::{AgentID, CarID};
+use sim::{AgentID, CarID, MissedConnectionChoice};
 use widgetry::{Color, ControlState, EventCtx, Key, Line, RewriteColor, Text, TextExt, Widget};
 
 use crate::app::App;
@@ -94,6 +94,25 @@ fn stop_body(ctx: &mut EventCtx, app: &App, details: &mut Details, id: TransitSt
     }
     rows.push(txt.into_widget(ctx));
 
+    if let Some(list) = app.primary.sim.get_analytics().missed_connections.get(&id) {
+        let mut choices: Counter<&'static str> = Counter::new();
+        for (_, _, choice) in list {
+            choices.inc(match choice {
+                MissedConnectionChoice::WaitForNext => "waited for the next one",
+                MissedConnectionChoice::SwitchRoute(_) => "switched routes",
+                MissedConnectionChoice::Walk => "walked instead",
+            });
+        }
+        let mut txt = Text::from(format!(
+            "{} people arrived just after their vehicle left",
+            prettyprint_usize(list.len())
+        ));
+        for (label, cnt) in choices.consume() {
+            txt.add_line(Line(format!("  {}: {}", label, prettyprint_usize(cnt))).secondary());
+        }
+        rows.push(txt.into_widget(ctx));
+    }
+
     // Draw where the bus/train stops
     details.draw_extra.zoomed.push(
         app.cs.bus_body.alpha(0.5),
@@ -136,6 +155,13 @@ fn bus_status_body(ctx: &mut EventCtx, app: &App, details: &mut Details, id: Car
         ))
         .into_widget(ctx),
     );
+    rows.push(Line("Progress today").secondary().into_widget(ctx));
+    rows.push(crate::info::trip::progress_sparkline(
+        ctx,
+        app,
+        AgentID::Car(id),
+        Time::START_OF_DAY,
+    ));
 
     Widget::col(rows)
 }
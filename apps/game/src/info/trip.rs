@@ -2,9 +2,9 @@ use std::collections::BTreeMap;
 
 use maplit::btreemap;
 
-use geom::{Distance, Duration, Percent, Polygon, Pt2D, UnitFmt};
+use geom::{Distance, Duration, Percent, PolyLine, Polygon, Pt2D, Speed, Time, UnitFmt};
 use map_gui::ID;
-use map_model::{Map, Path, PathStep, Traversable};
+use map_model::{Map, Path, PathStep, Traversable, TurnID};
 use sim::{AgentID, Analytics, PersonID, Problem, TripID, TripInfo, TripPhase, TripPhaseType};
 use synthpop::{TripEndpoint, TripMode};
 use widgetry::{
@@ -116,6 +116,14 @@ pub fn ongoing(
             ]),
         ]));
     }
+    col.push(Widget::custom_row(vec![
+        Line("Progress")
+            .secondary()
+            .into_widget(ctx)
+            .container()
+            .force_width_window_pct(ctx, col_width),
+        progress_sparkline(ctx, app, agent, trip.departure),
+    ]));
     col.push(describe_problems(
         ctx,
         app.primary.sim.get_analytics(),
@@ -700,6 +708,135 @@ fn make_timeline(
     DrawWithTooltips::new_widget(ctx, batch, tooltips, Box::new(|_| GeomBatch::new()))
 }
 
+/// A mini-chart of an agent's speed and accumulated delay at intersections since some time, with a
+/// tick for every intersection passed. Hovering on a tick describes the intersection.
+pub fn progress_sparkline(ctx: &EventCtx, app: &App, agent: AgentID, since: Time) -> Widget {
+    let map = &app.primary.map;
+    let now = app.primary.sim.time();
+    let progress = match app.primary.sim.get_analytics().agent_progress.get(&agent) {
+        Some(progress) => progress,
+        None => return Widget::nothing(),
+    };
+    let traversals: Vec<(Time, Traversable)> = progress
+        .traversals
+        .iter()
+        .filter(|(t, _)| *t >= since)
+        .cloned()
+        .collect();
+    if traversals.is_empty() || now <= since {
+        return Widget::nothing();
+    }
+
+    let width = 0.22 * ctx.canvas.window_width;
+    let height = 60.0;
+    let to_x = |t: Time| width * ((t - since) / (now - since));
+    let speed_color = Color::hex("#4CA7E9");
+    let delay_color = Color::RED;
+
+    // Assume a constant speed along each lane or turn
+    let mut speeds: Vec<(Time, Time, Speed)> = Vec::new();
+    for (idx, (t1, on)) in traversals.iter().enumerate() {
+        let t2 = traversals.get(idx + 1).map(|(t, _)| *t).unwrap_or(now);
+        if t2 > *t1 {
+            speeds.push((
+                *t1,
+                t2,
+                Speed::from_dist_time(on.get_polyline(map).length(), t2 - *t1),
+            ));
+        }
+    }
+    let max_speed = speeds
+        .iter()
+        .map(|(_, _, speed)| *speed)
+        .fold(Speed::ZERO, |a, b| if b > a { b } else { a });
+
+    let mut batch = GeomBatch::new();
+    batch.push(
+        app.cs.inner_panel_bg.dull(0.2),
+        Polygon::rectangle(width, height),
+    );
+    if max_speed > Speed::ZERO {
+        for (t1, t2, speed) in &speeds {
+            let bar_height = height * (*speed / max_speed);
+            if let Some(bar) = Polygon::rectangle_two_corners(
+                Pt2D::new(to_x(*t1), height - bar_height),
+                Pt2D::new(to_x(*t2), height),
+            ) {
+                batch.push(speed_color.alpha(0.7), bar);
+            }
+        }
+    }
+
+    // Accumulated delay, as a step function
+    let delays: Vec<(Time, TurnID, Duration)> = progress
+        .delays
+        .iter()
+        .filter(|(t, _, _)| *t >= since)
+        .cloned()
+        .collect();
+    let total_delay = delays
+        .iter()
+        .fold(Duration::ZERO, |sum, (_, _, dt)| sum + *dt);
+    if total_delay > Duration::ZERO {
+        let to_y = |dt: Duration| height * (1.0 - dt / total_delay);
+        let mut pts = vec![Pt2D::new(0.0, height)];
+        let mut sum = Duration::ZERO;
+        for (t, _, dt) in &delays {
+            pts.push(Pt2D::new(to_x(*t), to_y(sum)));
+            sum += *dt;
+            pts.push(Pt2D::new(to_x(*t), to_y(sum)));
+        }
+        pts.push(Pt2D::new(width, to_y(sum)));
+        if let Ok(pl) = PolyLine::deduping_new(pts) {
+            batch.push(delay_color, pl.make_polygons(Distance::meters(2.0)));
+        }
+    }
+
+    let mut tooltips = Vec::new();
+    for (t, on) in &traversals {
+        if let Traversable::Turn(turn) = on {
+            let x = to_x(*t);
+            batch.push(
+                Color::BLACK.alpha(0.5),
+                Polygon::rectangle(1.0, height).translate(x, 0.0),
+            );
+
+            let mut txt = Text::from(Line(format!(
+                "Passed {} at {}",
+                map.get_i(turn.parent).name(app.opts.language.as_ref(), map),
+                t.ampm_tostring()
+            )));
+            if let Some((_, _, dt)) = delays.iter().find(|(_, x, _)| x == turn) {
+                txt.add_line(format!("Waited {}", dt.to_string(&app.opts.units)));
+            }
+            tooltips.push((
+                Polygon::rectangle(5.0, height).translate(x - 2.5, 0.0),
+                txt,
+                None,
+            ));
+        }
+    }
+
+    Widget::col(vec![
+        DrawWithTooltips::new_widget(ctx, batch, tooltips, Box::new(|_| GeomBatch::new())),
+        Text::from_all(vec![
+            Line(format!(
+                "Speed (up to {})",
+                max_speed.to_string(&app.opts.units)
+            ))
+            .fg(speed_color)
+            .small(),
+            Line(format!(
+                ", {} waiting at intersections",
+                total_delay.to_string(&app.opts.units)
+            ))
+            .fg(delay_color)
+            .small(),
+        ])
+        .into_widget(ctx),
+    ])
+}
+
 /// Creates the timeline, location warp, and time warp buttons for one trip, and draws the route on
 /// the map.
 fn make_trip_details(
@@ -0,0 +1,179 @@
+use abstutil::prettyprint_usize;
+use geom::{Circle, Distance, Time};
+use map_gui::tools::ColorLegend;
+use map_gui::ID;
+use map_model::BuildingID;
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::{Color, EventCtx, GfxCtx, Line, Panel, Text, TextExt, Widget};
+
+use crate::app::App;
+use crate::layer::{header, Layer, LayerOutcome, PANEL_PLACEMENT};
+
+const EMPTY: Color = Color::RED;
+const FULL: Color = Color::BLUE;
+const AVAILABLE: Color = Color::GREEN;
+const FREE_FLOATING: Color = Color::CYAN;
+
+/// Shows how many shared bikes are waiting at each station right now.
+pub struct BikeShareAvailability {
+    time: Time,
+    tooltip: Option<Text>,
+    draw: ToggleZoomed,
+    panel: Panel,
+}
+
+impl Layer for BikeShareAvailability {
+    fn name(&self) -> Option<&'static str> {
+        Some("bike share")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = BikeShareAvailability::new(ctx, app);
+        }
+
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(ID::Building(b)) = app.mouseover_unzoomed_buildings(ctx) {
+                self.tooltip = describe(app, b);
+            }
+        }
+
+        <dyn Layer>::simple_event(ctx, &mut self.panel)
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl BikeShareAvailability {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> BikeShareAvailability {
+        let map = &app.primary.map;
+        let sim = &app.primary.sim;
+        let mut draw = ToggleZoomed::builder();
+        let mut col = vec![header(ctx, "Bike share availability")];
+
+        if let Some(system) = sim.get_bike_share_system() {
+            let available = sim.get_bike_share_availability();
+            let mut empty = 0;
+            let mut full = 0;
+            for station in &system.stations {
+                let bikes = available.get(&station.bldg).cloned().unwrap_or(0);
+                let color = if bikes == 0 {
+                    empty += 1;
+                    EMPTY
+                } else if bikes >= station.capacity {
+                    full += 1;
+                    FULL
+                } else {
+                    AVAILABLE
+                };
+                let circle = Circle::new(
+                    map.get_b(station.bldg).polygon.center(),
+                    Distance::meters(15.0),
+                )
+                .to_polygon();
+                draw.unzoomed.push(color, circle.clone());
+                draw.zoomed.push(color.alpha(0.5), circle);
+            }
+            let mut free_floating = 0;
+            for (b, bikes) in available {
+                if system.get_station(*b).is_none() {
+                    free_floating += bikes;
+                    let circle = Circle::new(map.get_b(*b).polygon.center(), Distance::meters(5.0))
+                        .to_polygon();
+                    draw.unzoomed.push(FREE_FLOATING, circle.clone());
+                    draw.zoomed.push(FREE_FLOATING.alpha(0.5), circle);
+                }
+            }
+
+            col.push(
+                format!(
+                    "{} stations: {} empty, {} full",
+                    prettyprint_usize(system.stations.len()),
+                    prettyprint_usize(empty),
+                    prettyprint_usize(full)
+                )
+                .text_widget(ctx),
+            );
+            if system.free_floating {
+                col.push(
+                    format!(
+                        "{} bikes left outside stations",
+                        prettyprint_usize(free_floating)
+                    )
+                    .text_widget(ctx),
+                );
+            }
+            col.push(ColorLegend::categories(
+                ctx,
+                vec![
+                    (EMPTY, "no bikes"),
+                    (AVAILABLE, "bikes and docks free"),
+                    (FULL, "no free docks"),
+                    (FREE_FLOATING, "bike outside a station"),
+                ],
+            ));
+        } else {
+            col.push(
+                Text::from(
+                    Line("No bike share system. Add stations from the Bike Share dashboard.")
+                        .secondary(),
+                )
+                .wrap_to_pct(ctx, 15)
+                .into_widget(ctx),
+            );
+        }
+
+        let panel = Panel::new_builder(Widget::col(col))
+            .aligned_pair(PANEL_PLACEMENT)
+            .build(ctx);
+
+        BikeShareAvailability {
+            time: sim.time(),
+            tooltip: None,
+            draw: draw.build(ctx),
+            panel,
+        }
+    }
+}
+
+fn describe(app: &App, b: BuildingID) -> Option<Text> {
+    let sim = &app.primary.sim;
+    let system = sim.get_bike_share_system()?;
+    let bikes = sim
+        .get_bike_share_availability()
+        .get(&b)
+        .cloned()
+        .unwrap_or(0);
+    let stats = sim.get_bike_share_stats();
+    let mut txt = Text::new();
+    if let Some(station) = system.get_station(b) {
+        txt.add_line(format!(
+            "{} / {} docks filled",
+            prettyprint_usize(bikes),
+            prettyprint_usize(station.capacity)
+        ));
+    } else if bikes > 0 {
+        txt.add_line(format!("{} bikes left here", prettyprint_usize(bikes)));
+    } else {
+        return None;
+    }
+    let pickups = stats.rides.iter().filter(|(_, from, _)| *from == b).count();
+    let dropoffs = stats.rides.iter().filter(|(_, _, to)| *to == b).count();
+    txt.add_line(
+        Line(format!(
+            "{} rides started here, {} headed here",
+            prettyprint_usize(pickups),
+            prettyprint_usize(dropoffs)
+        ))
+        .secondary(),
+    );
+    Some(txt)
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+use abstutil::{prettyprint_usize, Counter, Timer};
+use collisions::{Collision, CollisionDataset};
+use geom::{Distance, FindClosest};
+use map_gui::tools::{ColorLegend, ColorNetwork};
+use map_gui::ID;
+use map_model::{IntersectionID, Map, RoadID};
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::{EventCtx, GfxCtx, Line, Panel, Text, TextExt, Widget};
+
+use crate::app::App;
+use crate::layer::{header, Layer, LayerOutcome, PANEL_PLACEMENT};
+
+/// Real-world collisions within the current map, each matched to the nearest road or intersection.
+pub struct SnappedCollisions {
+    pub collisions: Vec<Collision>,
+    /// Indices into `collisions`
+    pub per_road: BTreeMap<RoadID, Vec<usize>>,
+    pub per_intersection: BTreeMap<IntersectionID, Vec<usize>>,
+}
+
+impl SnappedCollisions {
+    fn load(map: &Map, timer: &mut Timer) -> Option<SnappedCollisions> {
+        let path = map.get_city_name().input_path("collisions.bin");
+        if !abstio::file_exists(&path) {
+            return None;
+        }
+        let data: CollisionDataset = match abstio::maybe_read_binary(path, timer) {
+            Ok(data) => data,
+            Err(err) => {
+                warn!("Couldn't load collision data: {}", err);
+                return None;
+            }
+        };
+
+        let mut closest: FindClosest<ID> = FindClosest::new(map.get_bounds());
+        for i in map.all_intersections() {
+            closest.add(ID::Intersection(i.id), i.polygon.points());
+        }
+        for r in map.all_roads() {
+            closest.add(ID::Road(r.id), r.center_pts.points());
+        }
+
+        let mut result = SnappedCollisions {
+            collisions: Vec::new(),
+            per_road: BTreeMap::new(),
+            per_intersection: BTreeMap::new(),
+        };
+        for collision in data.collisions {
+            let pt = collision.location.to_pt(map.get_gps_bounds());
+            if !map.get_boundary_polygon().contains_pt(pt) {
+                continue;
+            }
+            // Search up to 10m away
+            let idx = result.collisions.len();
+            match closest.closest_pt(pt, Distance::meters(10.0)) {
+                Some((ID::Road(r), _)) => {
+                    result.per_road.entry(r).or_insert_with(Vec::new).push(idx);
+                }
+                Some((ID::Intersection(i), _)) => {
+                    result
+                        .per_intersection
+                        .entry(i)
+                        .or_insert_with(Vec::new)
+                        .push(idx);
+                }
+                _ => {
+                    continue;
+                }
+            }
+            result.collisions.push(collision);
+        }
+        Some(result)
+    }
+}
+
+/// Loads real-world collisions for the current map, if the city has any data. This only happens
+/// once per map.
+pub fn load_collisions(app: &mut App) {
+    let map = &app.primary.map;
+    app.session
+        .collisions
+        .update(Some(map.get_name().clone()), |_| {
+            SnappedCollisions::load(map, &mut Timer::throwaway())
+        });
+}
+
+/// Returns real-world collisions for the current map, if `load_collisions` has found any.
+pub fn get_collisions(app: &App) -> Option<&SnappedCollisions> {
+    if app.session.collisions.key().as_ref() != Some(app.primary.map.get_name()) {
+        return None;
+    }
+    app.session.collisions.value().and_then(|x| x.as_ref())
+}
+
+/// Colors roads and intersections by how many real-world collisions happened there. Hovering
+/// compares that with how many agents passed through in the simulation so far.
+pub struct CollisionHotspots {
+    panel: Panel,
+    draw: ToggleZoomed,
+    tooltip: Option<Text>,
+}
+
+impl Layer for CollisionHotspots {
+    fn name(&self) -> Option<&'static str> {
+        Some("collision hotspots")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(data) = get_collisions(app) {
+                let analytics = app.primary.sim.get_analytics();
+                let counts = match app.mouseover_unzoomed_roads_and_intersections(ctx) {
+                    Some(ID::Road(r)) => Some((
+                        data.per_road.get(&r).map(|x| x.len()).unwrap_or(0),
+                        analytics.road_thruput.total_for(r),
+                    )),
+                    Some(ID::Intersection(i)) => Some((
+                        data.per_intersection.get(&i).map(|x| x.len()).unwrap_or(0),
+                        analytics.intersection_thruput.total_for(i),
+                    )),
+                    _ => None,
+                };
+                if let Some((collisions, thruput)) = counts {
+                    self.tooltip = Some(Text::from_multiline(vec![
+                        Line(format!(
+                            "{} recorded collisions",
+                            prettyprint_usize(collisions)
+                        )),
+                        Line(format!(
+                            "{} agents passed through in the simulation so far",
+                            prettyprint_usize(thruput)
+                        ))
+                        .secondary(),
+                    ]));
+                }
+            }
+        }
+
+        <dyn Layer>::simple_event(ctx, &mut self.panel)
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl CollisionHotspots {
+    /// Returns `None` if the city has no collision data.
+    pub fn new(ctx: &mut EventCtx, app: &mut App) -> Option<CollisionHotspots> {
+        load_collisions(app);
+        let data = get_collisions(app)?;
+
+        let mut per_road = Counter::new();
+        for (r, indices) in &data.per_road {
+            per_road.add(*r, indices.len());
+        }
+        let mut per_intersection = Counter::new();
+        for (i, indices) in &data.per_intersection {
+            per_intersection.add(*i, indices.len());
+        }
+        let mut colorer = ColorNetwork::new(app);
+        colorer.ranked_roads(per_road, &app.cs.good_to_bad_red);
+        colorer.ranked_intersections(per_intersection, &app.cs.good_to_bad_red);
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            header(ctx, "Collision hotspots"),
+            format!(
+                "{} recorded collisions",
+                prettyprint_usize(data.collisions.len())
+            )
+            .text_widget(ctx),
+            ColorLegend::gradient(
+                ctx,
+                &app.cs.good_to_bad_red,
+                vec!["fewest", "most collisions"],
+            ),
+        ]))
+        .aligned_pair(PANEL_PLACEMENT)
+        .build(ctx);
+
+        Some(CollisionHotspots {
+            panel,
+            draw: colorer.build(ctx),
+            tooltip: None,
+        })
+    }
+}
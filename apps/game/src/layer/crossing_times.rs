@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use abstutil::prettyprint_usize;
+use geom::Speed;
+use map_gui::tools::ColorDiscrete;
+use map_gui::ID;
+use map_model::{CrossingShortfall, IntersectionID};
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::{
+    Color, EventCtx, GfxCtx, Line, Outcome, Panel, RoundedF64, Spinner, Text, TextExt, Widget,
+};
+
+use crate::app::App;
+use crate::layer::{header, Layer, LayerOutcome, PANEL_PLACEMENT};
+
+/// Design guidance often asks signals to accommodate people walking at 3 feet per second, much
+/// slower than the usual walking speed, to include elderly and disabled people.
+pub const DEFAULT_SLOW_WALKING_SPEED: Speed = Speed::const_meters_per_second(0.91);
+
+/// Flags traffic signals that don't give people walking slowly enough time to finish crossing.
+pub struct CrossingTimes {
+    shortfalls: BTreeMap<IntersectionID, Vec<CrossingShortfall>>,
+    tooltip: Option<Text>,
+    draw: ToggleZoomed,
+    panel: Panel,
+}
+
+impl Layer for CrossingTimes {
+    fn name(&self) -> Option<&'static str> {
+        Some("crossing times")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(ID::Intersection(i)) = app.mouseover_unzoomed_roads_and_intersections(ctx) {
+                if let Some(shortfalls) = self.shortfalls.get(&i) {
+                    self.tooltip = Some(describe_shortfalls(shortfalls));
+                }
+            }
+        }
+
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => {
+                    return Some(LayerOutcome::Close);
+                }
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => {
+                app.session.slow_walking_speed =
+                    Speed::meters_per_second(self.panel.spinner::<RoundedF64>("walking speed").0);
+                let mut new = CrossingTimes::new(ctx, app);
+                new.panel.restore(ctx, &self.panel);
+                *self = new;
+            }
+            _ => {}
+        }
+        None
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl CrossingTimes {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> CrossingTimes {
+        let map = &app.primary.map;
+        let pace = app.session.slow_walking_speed;
+        let mut colorer = ColorDiscrete::new(
+            app,
+            vec![
+                ("not enough time to cross", Color::RED),
+                ("enough time to cross", Color::GREEN),
+            ],
+        );
+        let mut shortfalls = BTreeMap::new();
+        let mut num_signals = 0;
+        for i in map.all_intersections() {
+            if !i.is_traffic_signal() {
+                continue;
+            }
+            num_signals += 1;
+            let problems = map.get_traffic_signal(i.id).find_short_crossings(i, pace);
+            if problems.is_empty() {
+                colorer.add_i(i.id, "enough time to cross");
+            } else {
+                colorer.add_i(i.id, "not enough time to cross");
+                shortfalls.insert(i.id, problems);
+            }
+        }
+        let (draw, legend) = colorer.build(ctx);
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            header(ctx, "Pedestrian crossing times"),
+            Widget::row(vec![
+                "Walking speed (m/s):".text_widget(ctx).centered_vert(),
+                Spinner::f64_widget(
+                    ctx,
+                    "walking speed",
+                    (0.5, 1.5),
+                    pace.inner_meters_per_second(),
+                    0.05,
+                ),
+            ]),
+            Text::from_all(vec![
+                Line(prettyprint_usize(shortfalls.len())),
+                Line(format!(
+                    " of {} signals are too short for slow walkers",
+                    prettyprint_usize(num_signals)
+                ))
+                .secondary(),
+            ])
+            .into_widget(ctx),
+            Text::from(
+                Line("Edit a flagged signal to lengthen its stages in one click").secondary(),
+            )
+            .wrap_to_pct(ctx, 15)
+            .into_widget(ctx),
+            legend,
+        ]))
+        .aligned_pair(PANEL_PLACEMENT)
+        .build(ctx);
+
+        CrossingTimes {
+            shortfalls,
+            tooltip: None,
+            draw,
+            panel,
+        }
+    }
+}
+
+/// Explains which stages are too short, and how to fix them
+fn describe_shortfalls(shortfalls: &[CrossingShortfall]) -> Text {
+    let mut txt = Text::new();
+    for x in shortfalls {
+        txt.add_line(format!(
+            "Stage {} lasts {}, but crossing takes {}",
+            x.stage + 1,
+            x.given,
+            x.needed
+        ));
+    }
+    txt.add_line(Line("Suggestion: lengthen these stages to the crossing time").secondary());
+    txt
+}
@@ -0,0 +1,592 @@
+use std::collections::{BTreeMap, HashMap};
+
+use geom::Time;
+use map_gui::tools::{ColorLegend, ColorNetwork};
+use map_gui::ID;
+use map_model::{Direction, Map, RoadID, StressThresholds};
+use sim::AgentType;
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::{
+    Color, EventCtx, GfxCtx, Key, Line, Outcome, Panel, Text, TextBox, TextExt, Widget,
+};
+
+use crate::app::App;
+use crate::layer::{header, Layer, LayerOutcome, PANEL_PLACEMENT};
+
+/// Colors roads by an expression the user types in, combining per-road fields with arithmetic
+/// and comparisons. An escape hatch for questions that don't have a bespoke layer yet.
+pub struct CustomLayer {
+    expression: String,
+    time: Time,
+    values: HashMap<RoadID, f64>,
+    tooltip: Option<Text>,
+    draw: ToggleZoomed,
+    panel: Panel,
+}
+
+impl Layer for CustomLayer {
+    fn name(&self) -> Option<&'static str> {
+        Some("custom expression")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = CustomLayer::new(ctx, app, self.expression.clone());
+        }
+
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(ID::Road(r)) = app.mouseover_unzoomed_roads_and_intersections(ctx) {
+                if let Some(value) = self.values.get(&r) {
+                    self.tooltip = Some(Text::from(format!("{} = {:.2}", self.expression, value)));
+                }
+            }
+        }
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Some(LayerOutcome::Close);
+                }
+                "Apply" => {
+                    let expression = self.panel.text_box("expression");
+                    *self = CustomLayer::new(ctx, app, expression);
+                }
+                _ => unreachable!(),
+            }
+        }
+        None
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl CustomLayer {
+    pub fn new(ctx: &mut EventCtx, app: &App, expression: String) -> CustomLayer {
+        let mut col = vec![
+            header(ctx, "Custom layer"),
+            Text::from_multiline(vec![
+                Line("Color roads by an expression, like"),
+                Line("volume / lanes, or speed_limit > 25 and stress").secondary(),
+            ])
+            .into_widget(ctx),
+            Text::from_multiline(
+                Field::all()
+                    .into_iter()
+                    .map(|f| Line(format!("- {}: {}", f.name(), f.describe(app))).secondary())
+                    .collect(),
+            )
+            .into_widget(ctx),
+            Widget::row(vec![
+                TextBox::default_widget(ctx, "expression", expression.clone()),
+                ctx.style()
+                    .btn_outline
+                    .text("Apply")
+                    .hotkey(Key::Enter)
+                    .build_def(ctx),
+            ]),
+        ];
+
+        let mut values = HashMap::new();
+        let mut colorer = ColorNetwork::new(app);
+        match Expr::parse(&expression) {
+            Ok(expr) => {
+                let fields = FieldValues::new(app);
+                for r in app.primary.map.all_roads() {
+                    let value = expr.eval(&|f| fields.get(app, r.id, f));
+                    if value.is_finite() {
+                        values.insert(r.id, value);
+                    }
+                }
+                col.push(CustomLayer::color(ctx, app, &expr, &values, &mut colorer));
+            }
+            Err(err) => {
+                if !expression.is_empty() {
+                    col.push(Line(err).fg(Color::RED).into_widget(ctx));
+                }
+            }
+        }
+
+        CustomLayer {
+            expression,
+            time: app.primary.sim.time(),
+            values,
+            tooltip: None,
+            draw: colorer.build(ctx),
+            panel: Panel::new_builder(Widget::col(col))
+                .aligned_pair(PANEL_PLACEMENT)
+                .build(ctx),
+        }
+    }
+
+    /// Adds every road to the colorer and returns a legend. Comparisons just highlight matching
+    /// roads; anything else is colored on a gradient from the lowest to highest value.
+    fn color(
+        ctx: &mut EventCtx,
+        app: &App,
+        expr: &Expr,
+        values: &HashMap<RoadID, f64>,
+        colorer: &mut ColorNetwork,
+    ) -> Widget {
+        if expr.is_boolean() {
+            let mut count = 0;
+            for (r, value) in values {
+                if *value != 0.0 {
+                    colorer.add_r(*r, app.cs.edits_layer);
+                    count += 1;
+                }
+            }
+            return Widget::col(vec![
+                ColorLegend::row(ctx, app.cs.edits_layer, "matching roads"),
+                format!("{} roads match", count).text_widget(ctx),
+            ]);
+        }
+
+        if values.is_empty() {
+            return "No roads have a value".text_widget(ctx);
+        }
+        let min = values.values().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.values().cloned().fold(f64::NEG_INFINITY, f64::max);
+        for (r, value) in values {
+            let pct = if max > min {
+                (value - min) / (max - min)
+            } else {
+                0.0
+            };
+            colorer.add_r(*r, app.cs.good_to_bad_red.eval(pct));
+        }
+        ColorLegend::gradient(
+            ctx,
+            &app.cs.good_to_bad_red,
+            vec![format!("{:.1}", min), format!("{:.1}", max)],
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    SpeedLimit,
+    Width,
+    Lanes,
+    Length,
+    Volume,
+    Delay,
+    Stress,
+}
+
+impl Field {
+    fn all() -> Vec<Field> {
+        vec![
+            Field::SpeedLimit,
+            Field::Width,
+            Field::Lanes,
+            Field::Length,
+            Field::Volume,
+            Field::Delay,
+            Field::Stress,
+        ]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::SpeedLimit => "speed_limit",
+            Field::Width => "width",
+            Field::Lanes => "lanes",
+            Field::Length => "length",
+            Field::Volume => "volume",
+            Field::Delay => "delay",
+            Field::Stress => "stress",
+        }
+    }
+
+    fn describe(self, app: &App) -> &'static str {
+        let metric = app.opts.units.metric;
+        match self {
+            Field::SpeedLimit => {
+                if metric {
+                    "km/h"
+                } else {
+                    "mph"
+                }
+            }
+            Field::Width | Field::Length => {
+                if metric {
+                    "meters"
+                } else {
+                    "feet"
+                }
+            }
+            Field::Lanes => "number of lanes, including sidewalks",
+            Field::Volume => "agents crossing the road so far today",
+            Field::Delay => "average seconds waiting at the next traffic signal",
+            Field::Stress => "1 if high stress for biking, otherwise 0",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Field> {
+        Field::all().into_iter().find(|f| f.name() == name)
+    }
+}
+
+/// Fields that depend on the simulation are calculated once for all roads.
+struct FieldValues {
+    volume: HashMap<RoadID, usize>,
+    delay: HashMap<RoadID, f64>,
+}
+
+impl FieldValues {
+    fn new(app: &App) -> FieldValues {
+        let map = &app.primary.map;
+        let analytics = app.primary.sim.get_analytics();
+        let volume = analytics
+            .road_thruput
+            .all_total_counts(&AgentType::all().into_iter().collect())
+            .consume()
+            .into_iter()
+            .collect();
+
+        let mut totals: BTreeMap<RoadID, (f64, usize)> = BTreeMap::new();
+        for (i, delays) in &analytics.intersection_delays {
+            let movements: Vec<_> = map.get_i(*i).movements.keys().collect();
+            for (idx, _, delay, _) in delays {
+                if let Some(m) = movements.get(*idx as usize) {
+                    let entry = totals.entry(m.from.road).or_insert((0.0, 0));
+                    entry.0 += delay.inner_seconds();
+                    entry.1 += 1;
+                }
+            }
+        }
+        let delay = totals
+            .into_iter()
+            .map(|(r, (sum, count))| (r, sum / (count as f64)))
+            .collect();
+
+        FieldValues { volume, delay }
+    }
+
+    fn get(&self, app: &App, r: RoadID, field: Field) -> f64 {
+        let map = &app.primary.map;
+        let road = map.get_r(r);
+        let metric = app.opts.units.metric;
+        match field {
+            Field::SpeedLimit => {
+                let mps = road.speed_limit.inner_meters_per_second();
+                if metric {
+                    mps * 3.6
+                } else {
+                    mps * 2.23694
+                }
+            }
+            Field::Width => to_units(road.get_width().inner_meters(), metric),
+            Field::Length => to_units(road.length().inner_meters(), metric),
+            Field::Lanes => road.lanes.len() as f64,
+            Field::Volume => self.volume.get(&r).cloned().unwrap_or(0) as f64,
+            Field::Delay => self.delay.get(&r).cloned().unwrap_or(0.0),
+            Field::Stress => {
+                if high_stress(map, r, &app.session.bike_stress) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+fn to_units(meters: f64, metric: bool) -> f64 {
+    if metric {
+        meters
+    } else {
+        meters * 3.28084
+    }
+}
+
+fn high_stress(map: &Map, r: RoadID, thresholds: &StressThresholds) -> bool {
+    let road = map.get_r(r);
+    road.high_stress_for_bikes(map, Direction::Fwd, thresholds)
+        || road.high_stress_for_bikes(map, Direction::Back, thresholds)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinOp {
+    fn is_boolean(self) -> bool {
+        !matches!(self, BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Number(f64),
+    Field(Field),
+    Negate(Box<Expr>),
+    BinOp(BinOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn parse(input: &str) -> Result<Expr, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("Type an expression".to_string());
+        }
+        let mut parser = Parser { tokens, idx: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.idx) {
+            return Err(format!("Unexpected {:?}", token));
+        }
+        Ok(expr)
+    }
+
+    /// Comparisons produce 1 for true and 0 for false.
+    fn eval(&self, lookup: &dyn Fn(Field) -> f64) -> f64 {
+        match self {
+            Expr::Number(x) => *x,
+            Expr::Field(f) => lookup(*f),
+            Expr::Negate(e) => -e.eval(lookup),
+            Expr::BinOp(op, a, b) => {
+                let (a, b) = (a.eval(lookup), b.eval(lookup));
+                let from_bool = |x: bool| if x { 1.0 } else { 0.0 };
+                match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    BinOp::Lt => from_bool(a < b),
+                    BinOp::Le => from_bool(a <= b),
+                    BinOp::Gt => from_bool(a > b),
+                    BinOp::Ge => from_bool(a >= b),
+                    BinOp::Eq => from_bool(a == b),
+                    BinOp::Ne => from_bool(a != b),
+                    BinOp::And => from_bool(a != 0.0 && b != 0.0),
+                    BinOp::Or => from_bool(a != 0.0 || b != 0.0),
+                }
+            }
+        }
+    }
+
+    fn is_boolean(&self) -> bool {
+        match self {
+            Expr::BinOp(op, _, _) => op.is_boolean(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(String),
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let num: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(
+                num.parse().map_err(|_| format!("Bad number {}", num))?,
+            ));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if "<>=!".contains(c) {
+            if chars.get(i + 1) == Some(&'=') {
+                tokens.push(Token::Op(format!("{}=", c)));
+                i += 2;
+            } else if c == '<' || c == '>' {
+                tokens.push(Token::Op(c.to_string()));
+                i += 1;
+            } else {
+                return Err(format!("Did you mean {}=?", c));
+            }
+        } else if "+-*/".contains(c) {
+            tokens.push(Token::Op(c.to_string()));
+            i += 1;
+        } else {
+            return Err(format!("Unexpected character {}", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser. From loosest to tightest binding: or, and, comparisons, addition,
+/// multiplication, negation.
+struct Parser {
+    tokens: Vec<Token>,
+    idx: usize,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.next_is_ident("or") {
+            let rhs = self.and()?;
+            expr = Expr::BinOp(BinOp::Or, Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.comparison()?;
+        while self.next_is_ident("and") {
+            let rhs = self.comparison()?;
+            expr = Expr::BinOp(BinOp::And, Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let expr = self.sum()?;
+        let op = match self.tokens.get(self.idx) {
+            Some(Token::Op(op)) => match op.as_str() {
+                "<" => BinOp::Lt,
+                "<=" => BinOp::Le,
+                ">" => BinOp::Gt,
+                ">=" => BinOp::Ge,
+                "==" => BinOp::Eq,
+                "!=" => BinOp::Ne,
+                _ => return Ok(expr),
+            },
+            _ => return Ok(expr),
+        };
+        self.idx += 1;
+        let rhs = self.sum()?;
+        Ok(Expr::BinOp(op, Box::new(expr), Box::new(rhs)))
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        loop {
+            let op = match self.tokens.get(self.idx) {
+                Some(Token::Op(op)) if op == "+" => BinOp::Add,
+                Some(Token::Op(op)) if op == "-" => BinOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.idx += 1;
+            let rhs = self.product()?;
+            expr = Expr::BinOp(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = match self.tokens.get(self.idx) {
+                Some(Token::Op(op)) if op == "*" => BinOp::Mul,
+                Some(Token::Op(op)) if op == "/" => BinOp::Div,
+                _ => return Ok(expr),
+            };
+            self.idx += 1;
+            let rhs = self.unary()?;
+            expr = Expr::BinOp(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let token = self
+            .tokens
+            .get(self.idx)
+            .cloned()
+            .ok_or_else(|| "Expression ends too early".to_string())?;
+        self.idx += 1;
+        match token {
+            Token::Number(x) => Ok(Expr::Number(x)),
+            Token::Ident(name) => Field::from_name(&name)
+                .map(Expr::Field)
+                .ok_or_else(|| format!("Unknown field {}", name)),
+            Token::Op(op) if op == "-" => Ok(Expr::Negate(Box::new(self.unary()?))),
+            Token::LParen => {
+                let expr = self.or()?;
+                if self.tokens.get(self.idx) != Some(&Token::RParen) {
+                    return Err("Missing )".to_string());
+                }
+                self.idx += 1;
+                Ok(expr)
+            }
+            x => Err(format!("Unexpected {:?}", x)),
+        }
+    }
+
+    fn next_is_ident(&mut self, name: &str) -> bool {
+        if let Some(Token::Ident(x)) = self.tokens.get(self.idx) {
+            if x == name {
+                self.idx += 1;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_eval() {
+        let lookup = |f: Field| match f {
+            Field::SpeedLimit => 30.0,
+            Field::Lanes => 4.0,
+            _ => 0.0,
+        };
+        for (input, expected) in [
+            ("1 + 2 * 3", 7.0),
+            ("(1 + 2) * 3", 9.0),
+            ("-lanes + 1", -3.0),
+            ("speed_limit / lanes", 7.5),
+            ("speed_limit > 25 and lanes <= 2", 0.0),
+            ("speed_limit >= 30 or lanes == 1", 1.0),
+        ] {
+            let expr = Expr::parse(input).unwrap();
+            assert_eq!(expr.eval(&lookup), expected, "{}", input);
+        }
+
+        assert!(Expr::parse("speed_limit > 25").unwrap().is_boolean());
+        assert!(!Expr::parse("volume / lanes").unwrap().is_boolean());
+        for bad in ["", "1 +", "foo", "(1", "1 = 2", "1 2"] {
+            assert!(Expr::parse(bad).is_err(), "{} should fail", bad);
+        }
+    }
+}
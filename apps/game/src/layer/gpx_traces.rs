@@ -0,0 +1,276 @@
+use anyhow::Result;
+
+use abstutil::{prettyprint_usize, Counter, Timer};
+use geom::{Distance, FindClosest, Time};
+use map_gui::tools::{ColorLegend, ColorNetwork, FilePicker};
+use map_gui::ID;
+use map_model::{BuildingID, Map, MapMatcher, PathConstraints, PathStep, RoadID};
+use synthpop::{IndividTrip, PersonSpec, Scenario, TripEndpoint, TripMode, TripPurpose};
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::tools::PopupMsg;
+use widgetry::{EventCtx, GfxCtx, Line, Panel, State, Text, TextExt, Widget};
+
+use crate::app::{App, Transition};
+use crate::layer::{header, Layer, LayerOutcome, PANEL_PLACEMENT};
+
+/// Bike rides recorded as GPX traces, matched to the roads they used.
+pub struct MatchedTraces {
+    pub traces: Vec<MatchedTrace>,
+    /// Traces that couldn't be matched to the map
+    pub skipped: usize,
+}
+
+pub struct MatchedTrace {
+    /// When the first point was recorded, if the trace has times
+    pub depart: Option<Time>,
+    /// The buildings closest to the start and end of the trace
+    pub origin: BuildingID,
+    pub destination: BuildingID,
+    /// The roads actually ridden, in order
+    pub roads: Vec<RoadID>,
+    /// The roads along the route the pathfinder picks between the same buildings
+    pub router_roads: Vec<RoadID>,
+}
+
+impl MatchedTraces {
+    pub fn load(map: &Map, path: String, timer: &mut Timer) -> Result<MatchedTraces> {
+        let gps_bounds = map.get_gps_bounds();
+        let matcher = MapMatcher::new(map, PathConstraints::Bike);
+        let mut closest_bldg: FindClosest<BuildingID> = FindClosest::new(map.get_bounds());
+        for b in map.all_buildings() {
+            closest_bldg.add(b.id, b.polygon.points());
+        }
+
+        let mut traces = Vec::new();
+        let mut skipped = 0;
+        let input = kml::load_gpx(path, timer)?;
+        timer.start_iter("match traces", input.len());
+        for trace in input {
+            timer.next();
+            let depart = trace.points.iter().find_map(|(_, time)| *time);
+            let pts: Vec<_> = trace
+                .points
+                .into_iter()
+                .filter(|(pt, _)| gps_bounds.contains(*pt))
+                .map(|(pt, _)| pt.to_pt(gps_bounds))
+                .collect();
+            let roads = matcher.match_trace(&pts);
+            let endpoints = match (pts.first(), pts.last()) {
+                (Some(first), Some(last)) => closest_bldg
+                    .closest_pt(*first, Distance::meters(100.0))
+                    .zip(closest_bldg.closest_pt(*last, Distance::meters(100.0))),
+                _ => None,
+            };
+            let (origin, destination) = match endpoints {
+                Some(((origin, _), (destination, _)))
+                    if roads.len() >= 2 && origin != destination =>
+                {
+                    (origin, destination)
+                }
+                _ => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            let mut router_roads = Vec::new();
+            if let Some(path) = TripEndpoint::path_req(
+                TripEndpoint::Building(origin),
+                TripEndpoint::Building(destination),
+                TripMode::Bike,
+                map,
+            )
+            .and_then(|req| map.pathfind(req).ok())
+            {
+                for step in path.get_steps() {
+                    if let PathStep::Lane(l) | PathStep::ContraflowLane(l) = step {
+                        if router_roads.last() != Some(&l.road) {
+                            router_roads.push(l.road);
+                        }
+                    }
+                }
+            }
+
+            traces.push(MatchedTrace {
+                depart,
+                origin,
+                destination,
+                roads,
+                router_roads,
+            });
+        }
+        Ok(MatchedTraces { traces, skipped })
+    }
+
+    /// What percent of the distance ridden is on roads the pathfinder would also use?
+    pub fn pct_agreement(&self, map: &Map) -> f64 {
+        let mut total = Distance::ZERO;
+        let mut agree = Distance::ZERO;
+        for trace in &self.traces {
+            for r in &trace.roads {
+                let length = map.get_r(*r).length();
+                total += length;
+                if trace.router_roads.contains(r) {
+                    agree += length;
+                }
+            }
+        }
+        if total == Distance::ZERO {
+            0.0
+        } else {
+            100.0 * (agree / total)
+        }
+    }
+
+    /// Turns each trace into one person biking between the same buildings. Traces without times
+    /// leave at midnight.
+    pub fn to_scenario(&self, map: &Map, name: &str) -> Scenario {
+        let mut scenario = Scenario::empty(map, name);
+        // Include all buses/trains
+        scenario.only_seed_buses = None;
+        for trace in &self.traces {
+            scenario.people.push(PersonSpec {
+                orig_id: None,
+                trips: vec![IndividTrip::new(
+                    trace.depart.unwrap_or(Time::START_OF_DAY),
+                    TripPurpose::Recreation,
+                    TripEndpoint::Building(trace.origin),
+                    TripEndpoint::Building(trace.destination),
+                    TripMode::Bike,
+                )],
+            });
+        }
+        scenario
+    }
+}
+
+/// Asks for a GPX file, matches its traces to the map, and shows them as a layer.
+pub fn import(ctx: &mut EventCtx) -> Box<dyn State<App>> {
+    FilePicker::new_state(
+        ctx,
+        None,
+        Box::new(|ctx, app: &mut App, maybe_path| {
+            if let Ok(Some(path)) = maybe_path {
+                let result = ctx.loading_screen("import GPX traces", |_, timer| {
+                    MatchedTraces::load(&app.primary.map, path, timer)
+                });
+                match result {
+                    Ok(traces) => {
+                        app.session
+                            .gpx_traces
+                            .set(app.primary.map.get_name().clone(), traces);
+                        app.primary.layer =
+                            GpxTraces::new(ctx, app).map(|layer| Box::new(layer) as Box<dyn Layer>);
+                        Transition::Pop
+                    }
+                    Err(err) => Transition::Replace(PopupMsg::new_state(
+                        ctx,
+                        "Error",
+                        vec![err.to_string()],
+                    )),
+                }
+            } else {
+                Transition::Pop
+            }
+        }),
+    )
+}
+
+/// Returns the GPX traces imported for the current map, if any.
+pub fn get_traces(app: &App) -> Option<&MatchedTraces> {
+    if app.session.gpx_traces.key().as_ref() != Some(app.primary.map.get_name()) {
+        return None;
+    }
+    app.session.gpx_traces.value()
+}
+
+/// Colors roads by how many imported GPX traces used them. Hovering compares that with how many
+/// of the same trips the pathfinder would send there.
+pub struct GpxTraces {
+    observed: Counter<RoadID>,
+    routed: Counter<RoadID>,
+    panel: Panel,
+    draw: ToggleZoomed,
+    tooltip: Option<Text>,
+}
+
+impl Layer for GpxTraces {
+    fn name(&self) -> Option<&'static str> {
+        Some("GPX traces")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(ID::Road(r)) = app.mouseover_unzoomed_roads_and_intersections(ctx) {
+                self.tooltip = Some(Text::from_multiline(vec![
+                    Line(format!(
+                        "{} traces rode here",
+                        prettyprint_usize(self.observed.get(r))
+                    )),
+                    Line(format!(
+                        "The router would send {} of the same trips here",
+                        prettyprint_usize(self.routed.get(r))
+                    ))
+                    .secondary(),
+                ]));
+            }
+        }
+
+        <dyn Layer>::simple_event(ctx, &mut self.panel)
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl GpxTraces {
+    /// Returns `None` if no traces have been imported for this map.
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Option<GpxTraces> {
+        let data = get_traces(app)?;
+        let mut observed = Counter::new();
+        let mut routed = Counter::new();
+        for trace in &data.traces {
+            for r in &trace.roads {
+                observed.inc(*r);
+            }
+            for r in &trace.router_roads {
+                routed.inc(*r);
+            }
+        }
+        let mut colorer = ColorNetwork::new(app);
+        colorer.ranked_roads(observed.clone(), &app.cs.good_to_bad_red);
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            header(ctx, "GPX traces"),
+            format!(
+                "{} traces matched, {} skipped",
+                prettyprint_usize(data.traces.len()),
+                prettyprint_usize(data.skipped)
+            )
+            .text_widget(ctx),
+            format!(
+                "{:.0}% of the distance ridden matches the router's choices",
+                data.pct_agreement(&app.primary.map)
+            )
+            .text_widget(ctx),
+            ColorLegend::gradient(ctx, &app.cs.good_to_bad_red, vec!["fewest", "most traces"]),
+        ]))
+        .aligned_pair(PANEL_PLACEMENT)
+        .build(ctx);
+
+        Some(GpxTraces {
+            observed,
+            routed,
+            panel,
+            draw: colorer.build(ctx),
+            tooltip: None,
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use abstutil::prettyprint_usize;
+use geom::Time;
+use map_gui::tools::ColorLegend;
+use map_gui::ID;
+use map_model::{IntersectionID, RoadID};
+use sim::{GradedDelay, LevelOfService};
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::{Color, EventCtx, GfxCtx, Line, Panel, Text, TextExt, Widget};
+
+use crate::app::App;
+use crate::layer::{header, Layer, LayerOutcome, PANEL_PLACEMENT};
+
+/// Colors every traffic signal by its level of service grade, from the average delay of vehicles
+/// passing through so far.
+pub struct LevelOfServiceGrades {
+    time: Time,
+    grades: BTreeMap<IntersectionID, (GradedDelay, BTreeMap<RoadID, GradedDelay>)>,
+    tooltip: Option<Text>,
+    draw: ToggleZoomed,
+    panel: Panel,
+}
+
+impl Layer for LevelOfServiceGrades {
+    fn name(&self) -> Option<&'static str> {
+        Some("level of service")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = LevelOfServiceGrades::new(ctx, app);
+        }
+
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(ID::Intersection(i)) = app.mouseover_unzoomed_roads_and_intersections(ctx) {
+                if let Some((overall, approaches)) = self.grades.get(&i) {
+                    self.tooltip = Some(describe(app, overall, approaches));
+                }
+            }
+        }
+
+        <dyn Layer>::simple_event(ctx, &mut self.panel)
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl LevelOfServiceGrades {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> LevelOfServiceGrades {
+        let map = &app.primary.map;
+        let now = app.primary.sim.time();
+        let analytics = app.primary.sim.get_analytics();
+
+        let mut grades = BTreeMap::new();
+        let mut count_per_grade: BTreeMap<LevelOfService, usize> = BTreeMap::new();
+        let mut draw = ToggleZoomed::builder();
+        for i in map.all_intersections() {
+            if !i.is_traffic_signal() {
+                continue;
+            }
+            if let Some((overall, approaches)) = analytics.level_of_service(i.id, map, now) {
+                let color = grade_color(overall.grade);
+                draw.unzoomed.push(color, i.polygon.clone());
+                draw.zoomed.push(color.alpha(0.5), i.polygon.clone());
+                *count_per_grade.entry(overall.grade).or_insert(0) += 1;
+                grades.insert(i.id, (overall, approaches));
+            }
+        }
+
+        let mut col = vec![header(ctx, "Level of service")];
+        if grades.is_empty() {
+            col.push(
+                Text::from(Line("No vehicles have passed through traffic signals yet").secondary())
+                    .wrap_to_pct(ctx, 15)
+                    .into_widget(ctx),
+            );
+        } else {
+            col.push(
+                format!(
+                    "{} traffic signals graded by average vehicle delay",
+                    prettyprint_usize(grades.len())
+                )
+                .text_widget(ctx),
+            );
+        }
+        let legend: Vec<(Color, String)> = LevelOfService::all()
+            .into_iter()
+            .map(|grade| {
+                (
+                    grade_color(grade),
+                    format!(
+                        "{}: {} ({})",
+                        grade,
+                        grade.describe(),
+                        prettyprint_usize(count_per_grade.get(&grade).cloned().unwrap_or(0))
+                    ),
+                )
+            })
+            .collect();
+        col.push(ColorLegend::categories(
+            ctx,
+            legend.iter().map(|(c, l)| (*c, l.as_str())).collect(),
+        ));
+
+        let panel = Panel::new_builder(Widget::col(col))
+            .aligned_pair(PANEL_PLACEMENT)
+            .build(ctx);
+
+        LevelOfServiceGrades {
+            time: now,
+            grades,
+            tooltip: None,
+            draw: draw.build(ctx),
+            panel,
+        }
+    }
+}
+
+pub fn grade_color(grade: LevelOfService) -> Color {
+    match grade {
+        LevelOfService::A => Color::hex("#1A9850"),
+        LevelOfService::B => Color::hex("#91CF60"),
+        LevelOfService::C => Color::hex("#D9EF8B"),
+        LevelOfService::D => Color::hex("#FEE08B"),
+        LevelOfService::E => Color::hex("#FC8D59"),
+        LevelOfService::F => Color::hex("#D73027"),
+    }
+}
+
+/// Lists the overall grade, then each approach's grade.
+pub fn describe(
+    app: &App,
+    overall: &GradedDelay,
+    approaches: &BTreeMap<RoadID, GradedDelay>,
+) -> Text {
+    let mut txt = Text::from(Line(format!(
+        "Level of service {}: {} average delay for {} vehicles",
+        overall.grade,
+        overall.avg_delay.to_string(&app.opts.units),
+        prettyprint_usize(overall.vehicles)
+    )));
+    for (r, x) in approaches {
+        txt.add_line(
+            Line(format!(
+                "  {}: {}, {} average delay",
+                app.primary
+                    .map
+                    .get_r(*r)
+                    .get_name(app.opts.language.as_ref()),
+                x.grade,
+                x.avg_delay.to_string(&app.opts.units)
+            ))
+            .secondary(),
+        );
+    }
+    txt
+}
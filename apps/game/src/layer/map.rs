@@ -326,8 +326,12 @@ impl Static {
         let mut colorer = ColorDiscrete::new(app, vec![("high stress", app.cs.edits_layer)]);
 
         for r in app.primary.map.all_roads() {
-            if r.high_stress_for_bikes(&app.primary.map, Direction::Fwd)
-                || r.high_stress_for_bikes(&app.primary.map, Direction::Back)
+            if r.high_stress_for_bikes(&app.primary.map, Direction::Fwd, &app.session.bike_stress)
+                || r.high_stress_for_bikes(
+                    &app.primary.map,
+                    Direction::Back,
+                    &app.session.bike_stress,
+                )
             {
                 colorer.add_r(r.id, "high stress");
             }
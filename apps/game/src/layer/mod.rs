@@ -8,8 +8,14 @@ use widgetry::{
 use crate::app::{App, Transition};
 use crate::sandbox::dashboards;
 
+mod bike_share;
+pub mod collisions;
+pub mod crossing_times;
+mod custom;
 pub mod elevation;
 pub mod favorites;
+pub mod gpx_traces;
+pub mod level_of_service;
 pub mod map;
 mod pandemic;
 mod parking;
@@ -100,54 +106,16 @@ impl PickLayer {
 
         col.push(btn("None", Key::N));
 
-        col.push(
-            Widget::custom_row(vec![
-                Widget::col(vec![
-                    "Traffic".text_widget(ctx),
-                    btn("delay", Key::D),
-                    btn("throughput", Key::T),
-                    btn("traffic jams", Key::J),
-                    btn("cycling activity", Key::B),
-                ]),
-                Widget::col(vec![
-                    "Map".text_widget(ctx),
-                    btn("map edits", Key::E),
-                    btn("parking occupancy", Key::P),
-                    btn("transit network", Key::U),
-                    btn("population map", Key::X),
-                    btn("no sidewalks", Key::S),
-                    btn("favorite buildings", Key::F),
-                ]),
-            ])
-            .evenly_spaced(),
-        );
-
-        col.push(
-            Widget::custom_row(vec![
-                Widget::col(vec![
-                    "Experimental".text_widget(ctx),
-                    btn("amenities", Key::A),
-                    btn("backpressure", Key::Z),
-                    btn("steep streets", Key::V),
-                    btn("elevation", Key::G),
-                    btn("parking efficiency", Key::O),
-                    btn("blackholes", Key::L),
-                    btn("problem map", Key::K),
-                    btn("high stress", Key::H),
-                    if app.primary.sim.get_pandemic_model().is_some() {
-                        btn("pandemic model", Key::Y)
-                    } else {
-                        Widget::nothing()
-                    },
-                ]),
-                Widget::col(vec![
-                    "Data".text_widget(ctx),
-                    btn("traffic signal demand", Key::M),
-                    btn("commuter patterns", Key::R),
-                ]),
-            ])
-            .evenly_spaced(),
-        );
+        let mut columns = layer_columns(app).into_iter().map(|(category, layers)| {
+            let mut layer_col = vec![category.text_widget(ctx)];
+            for (name, key) in layers {
+                layer_col.push(btn(name, key));
+            }
+            Widget::col(layer_col)
+        });
+        while let (Some(col1), Some(col2)) = (columns.next(), columns.next()) {
+            col.push(Widget::custom_row(vec![col1, col2]).evenly_spaced());
+        }
 
         Box::new(PickLayer {
             panel: Panel::new_builder(Widget::col(col))
@@ -165,97 +133,11 @@ impl State<App> for PickLayer {
                 "None" => {
                     app.primary.layer = None;
                 }
-                "amenities" => {
-                    app.primary.layer = Some(Box::new(map::Static::amenities(ctx, app)));
-                }
-                "backpressure" => {
-                    app.primary.layer = Some(Box::new(traffic::Backpressure::new(ctx, app)));
-                }
-                "cycling activity" => {
-                    app.primary.layer = Some(Box::new(map::BikeActivity::new(ctx, app)));
-                }
-                "delay" => {
-                    app.primary.layer = Some(Box::new(traffic::Delay::new(ctx, app)));
-                }
-                "steep streets" => {
-                    app.primary.layer = Some(Box::new(elevation::SteepStreets::new(ctx, app)));
-                }
-                "elevation" => {
-                    app.primary.layer = Some(Box::new(elevation::ElevationContours::new(ctx, app)));
-                }
-                "map edits" => {
-                    app.primary.layer = Some(Box::new(map::Static::edits(ctx, app)));
-                }
-                "no sidewalks" => {
-                    app.primary.layer = Some(Box::new(map::Static::no_sidewalks(ctx, app)));
-                }
-                "high stress" => {
-                    app.primary.layer = Some(Box::new(map::Static::high_stress(ctx, app)));
-                }
-                "favorite buildings" => {
-                    app.primary.layer = Some(Box::new(favorites::ShowFavorites::new(ctx, app)));
-                }
-                "pandemic model" => {
-                    app.primary.layer = Some(Box::new(pandemic::Pandemic::new(
-                        ctx,
-                        app,
-                        pandemic::Options {
-                            heatmap: Some(HeatmapOptions::new()),
-                            state: pandemic::Seir::Infected,
-                        },
-                    )));
-                }
-                "blackholes" => {
-                    app.primary.layer = Some(Box::new(map::Static::blackholes(ctx, app)));
-                }
-                "parking occupancy" => {
-                    app.primary.layer = Some(Box::new(parking::Occupancy::new(
-                        ctx, app, true, true, true, false, true,
-                    )));
-                }
-                "parking efficiency" => {
-                    app.primary.layer = Some(Box::new(parking::Efficiency::new(ctx, app)));
-                }
-                "population map" => {
-                    app.primary.layer = Some(Box::new(population::PopulationMap::new(
-                        ctx,
-                        app,
-                        population::Options {
-                            heatmap: Some(HeatmapOptions::new()),
-                        },
-                    )));
-                }
-                "problem map" => {
-                    app.primary.layer = Some(Box::new(problems::ProblemMap::new(
-                        ctx,
-                        app,
-                        problems::Options::new(app),
-                    )));
-                }
-                "throughput" => {
-                    app.primary.layer = Some(Box::new(traffic::Throughput::new(
-                        ctx,
-                        app,
-                        AgentType::all().into_iter().collect(),
-                    )));
-                }
-                "traffic jams" => {
-                    app.primary.layer = Some(Box::new(traffic::TrafficJams::new(ctx, app)));
-                }
-                "transit network" => {
-                    app.primary.layer = Some(Box::new(transit::TransitNetwork::new(
-                        ctx, app, false, true, true,
-                    )));
-                }
-                "traffic signal demand" => {
-                    return Transition::Replace(dashboards::TrafficSignalDemand::new_state(
-                        ctx, app,
-                    ));
+                name => {
+                    if let Some(t) = choose_layer(ctx, app, name) {
+                        return Transition::Replace(t);
+                    }
                 }
-                "commuter patterns" => {
-                    return Transition::Replace(dashboards::CommuterPatterns::new_state(ctx, app));
-                }
-                _ => unreachable!(),
             },
             _ => {
                 if self.panel.clicked_outside(ctx) {
@@ -292,3 +174,155 @@ pub const PANEL_PLACEMENT: (HorizontalAlignment, VerticalAlignment) = (
     HorizontalAlignment::Percent(0.02),
     VerticalAlignment::Percent(0.2),
 );
+
+/// Every entry in the layer picker, grouped into columns. Some entries open a dashboard instead.
+fn layer_columns(app: &App) -> Vec<(&'static str, Vec<(&'static str, Key)>)> {
+    let mut experimental = vec![
+        ("amenities", Key::A),
+        ("backpressure", Key::Z),
+        ("steep streets", Key::V),
+        ("elevation", Key::G),
+        ("parking efficiency", Key::O),
+        ("blackholes", Key::L),
+        ("problem map", Key::K),
+        ("crossing times", Key::I),
+        ("high stress", Key::H),
+        ("custom expression", Key::C),
+    ];
+    if app.primary.sim.get_pandemic_model().is_some() {
+        experimental.push(("pandemic model", Key::Y));
+    }
+    let mut data = vec![
+        ("traffic signal demand", Key::M),
+        ("commuter patterns", Key::R),
+        ("GPX traces", Key::Num1),
+    ];
+    if abstio::file_exists(app.primary.map.get_city_name().input_path("collisions.bin")) {
+        data.push(("collision hotspots", Key::W));
+    }
+
+    vec![
+        (
+            "Traffic",
+            vec![
+                ("delay", Key::D),
+                ("throughput", Key::T),
+                ("traffic jams", Key::J),
+                ("queue spillback", Key::Q),
+                ("cycling activity", Key::B),
+                ("bike share", Key::Num2),
+                ("level of service", Key::Num3),
+            ],
+        ),
+        (
+            "Map",
+            vec![
+                ("map edits", Key::E),
+                ("parking occupancy", Key::P),
+                ("transit network", Key::U),
+                ("population map", Key::X),
+                ("no sidewalks", Key::S),
+                ("favorite buildings", Key::F),
+            ],
+        ),
+        ("Experimental", experimental),
+        ("Data", data),
+    ]
+}
+
+/// The names of every layer that can be picked right now.
+pub fn all_layers(app: &App) -> Vec<&'static str> {
+    layer_columns(app)
+        .into_iter()
+        .flat_map(|(_, layers)| layers.into_iter().map(|(name, _)| name))
+        .collect()
+}
+
+/// Switches to the layer with this name. A few entries open a new state instead, which the caller
+/// should push.
+pub fn choose_layer(ctx: &mut EventCtx, app: &mut App, name: &str) -> Option<Box<dyn State<App>>> {
+    match name {
+        "traffic signal demand" => Some(dashboards::TrafficSignalDemand::new_state(ctx, app)),
+        "commuter patterns" => Some(dashboards::CommuterPatterns::new_state(ctx, app)),
+        "GPX traces" if gpx_traces::get_traces(app).is_none() => Some(gpx_traces::import(ctx)),
+        _ => {
+            app.primary.layer = make_layer(ctx, app, name);
+            None
+        }
+    }
+}
+
+/// Creates a layer by the name used in the picker. Returns `None` for unknown names, or if the
+/// layer doesn't apply to the current simulation.
+fn make_layer(ctx: &mut EventCtx, app: &mut App, name: &str) -> Option<Box<dyn Layer>> {
+    let layer: Box<dyn Layer> = match name {
+        "amenities" => Box::new(map::Static::amenities(ctx, app)),
+        "backpressure" => Box::new(traffic::Backpressure::new(ctx, app)),
+        "bike share" => Box::new(bike_share::BikeShareAvailability::new(ctx, app)),
+        "collision hotspots" => Box::new(collisions::CollisionHotspots::new(ctx, app)?),
+        "crossing times" => Box::new(crossing_times::CrossingTimes::new(ctx, app)),
+        "cycling activity" => Box::new(map::BikeActivity::new(ctx, app)),
+        "delay" => Box::new(traffic::Delay::new(ctx, app)),
+        "steep streets" => Box::new(elevation::SteepStreets::new(ctx, app)),
+        "elevation" => Box::new(elevation::ElevationContours::new(ctx, app)),
+        "map edits" => Box::new(map::Static::edits(ctx, app)),
+        "no sidewalks" => Box::new(map::Static::no_sidewalks(ctx, app)),
+        "GPX traces" => Box::new(gpx_traces::GpxTraces::new(ctx, app)?),
+        "level of service" => Box::new(level_of_service::LevelOfServiceGrades::new(ctx, app)),
+        "high stress" => Box::new(map::Static::high_stress(ctx, app)),
+        "custom expression" => Box::new(custom::CustomLayer::new(ctx, app, String::new())),
+        "favorite buildings" => Box::new(favorites::ShowFavorites::new(ctx, app)),
+        "pandemic model" => {
+            app.primary.sim.get_pandemic_model()?;
+            Box::new(pandemic::Pandemic::new(
+                ctx,
+                app,
+                pandemic::Options {
+                    heatmap: Some(HeatmapOptions::new()),
+                    state: pandemic::Seir::Infected,
+                },
+            ))
+        }
+        "blackholes" => Box::new(map::Static::blackholes(ctx, app)),
+        "parking occupancy" => Box::new(parking::Occupancy::new(
+            ctx, app, true, true, true, false, true,
+        )),
+        "parking efficiency" => Box::new(parking::Efficiency::new(ctx, app)),
+        "population map" => Box::new(population::PopulationMap::new(
+            ctx,
+            app,
+            population::Options {
+                heatmap: Some(HeatmapOptions::new()),
+                activity: None,
+            },
+        )),
+        "problem map" => Box::new(problems::ProblemMap::new(
+            ctx,
+            app,
+            problems::Options::new(app),
+        )),
+        "throughput" => Box::new(traffic::Throughput::new(
+            ctx,
+            app,
+            AgentType::all().into_iter().collect(),
+        )),
+        "traffic jams" => Box::new(traffic::TrafficJams::new(ctx, app)),
+        "queue spillback" => Box::new(traffic::QueueSpillback::new(ctx, app)),
+        "transit network" => Box::new(transit::TransitNetwork::new(ctx, app, false, true, true)),
+        _ => {
+            return None;
+        }
+    };
+    Some(layer)
+}
+
+/// If a layer was active when the game last quit, turn it back on. This only happens once per
+/// session, and does nothing if a layer is already active.
+pub fn restore_last_layer(ctx: &mut EventCtx, app: &mut App) {
+    if app.primary.layer.is_some() {
+        return;
+    }
+    if let Some(name) = app.ui_state.last_layer.take() {
+        app.primary.layer = make_layer(ctx, app, &name);
+    }
+}
@@ -4,8 +4,11 @@ use abstutil::prettyprint_usize;
 use geom::{Circle, Distance, Pt2D, Time};
 use map_gui::tools::{make_heatmap, HeatmapOptions};
 use sim::PersonState;
+use synthpop::TripPurpose;
 use widgetry::mapspace::ToggleZoomed;
-use widgetry::{Color, EventCtx, GfxCtx, Image, Line, Outcome, Panel, Toggle, Widget};
+use widgetry::{
+    Choice, Color, EventCtx, GfxCtx, Image, Line, Outcome, Panel, TextExt, Toggle, Widget,
+};
 
 use crate::app::App;
 use crate::layer::{header, Layer, LayerOutcome, PANEL_PLACEMENT};
@@ -59,6 +62,7 @@ impl PopulationMap {
     pub fn new(ctx: &mut EventCtx, app: &App, opts: Options) -> PopulationMap {
         let mut pts = Vec::new();
         // Faster to grab all agent positions than individually map trips to agent positions.
+        // People moving between buildings aren't doing any activity, so skip them when filtering.
         for a in app
             .primary
             .sim
@@ -70,7 +74,7 @@ impl PopulationMap {
                     .get_unzoomed_transit_riders(&app.primary.map),
             )
         {
-            if a.person.is_some() {
+            if a.person.is_some() && opts.activity.is_none() {
                 pts.push(a.pos);
             }
         }
@@ -85,6 +89,11 @@ impl PopulationMap {
                 // Already covered above
                 PersonState::Trip(_) => {}
                 PersonState::Inside(b) => {
+                    if let Some(purpose) = opts.activity {
+                        if person.current_activity().map(|a| a.purpose) != Some(purpose) {
+                            continue;
+                        }
+                    }
                     let pt = app.primary.map.get_b(b).polygon.center();
                     if seen_bldgs.contains(&b) {
                         repeat_pts.push(pt);
@@ -131,7 +140,10 @@ impl PopulationMap {
         } else {
             None
         };
-        Options { heatmap }
+        Options {
+            heatmap,
+            activity: self.panel.dropdown_value("activity"),
+        }
     }
 }
 
@@ -139,6 +151,8 @@ impl PopulationMap {
 pub struct Options {
     // If None, just a dot map
     pub heatmap: Option<HeatmapOptions>,
+    /// Only show people inside buildings doing this activity, like being at home or work
+    pub activity: Option<TripPurpose>,
 }
 
 fn make_controls(ctx: &mut EventCtx, app: &App, opts: &Options, legend: Option<Widget>) -> Panel {
@@ -163,6 +177,27 @@ fn make_controls(ctx: &mut EventCtx, app: &App, opts: &Options, legend: Option<W
         .centered(),
     ];
 
+    let mut choices = vec![Choice::new("everyone", None)];
+    for purpose in TripPurpose::all() {
+        choices.push(Choice::new(format!("at {}", purpose), Some(purpose)));
+    }
+    col.push(Widget::row(vec![
+        "Show:".text_widget(ctx).centered_vert(),
+        Widget::dropdown(ctx, "activity", opts.activity, choices),
+    ]));
+    if let Some(purpose) = opts.activity {
+        let count = app
+            .primary
+            .sim
+            .get_all_people()
+            .iter()
+            .filter(|p| p.current_activity().map(|a| a.purpose) == Some(purpose))
+            .count();
+        col.push(
+            format!("{} people at {} now", prettyprint_usize(count), purpose).text_widget(ctx),
+        );
+    }
+
     col.push(Toggle::switch(
         ctx,
         "Show heatmap",
@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::Result;
 use maplit::btreeset;
@@ -8,8 +8,8 @@ use geom::{Circle, Distance, Duration, Percent, Polygon, Pt2D, Time};
 use map_gui::render::unzoomed_agent_radius;
 use map_gui::tools::{ColorLegend, ColorNetwork, DivergingScale};
 use map_gui::ID;
-use map_model::{IntersectionID, Map, Traversable};
-use sim::{AgentType, VehicleType};
+use map_model::{IntersectionID, Map, RoadID, Traversable};
+use sim::{AgentType, Analytics, VehicleType};
 use widgetry::mapspace::ToggleZoomed;
 use widgetry::tools::PopupMsg;
 use widgetry::{Color, EventCtx, GfxCtx, Line, Outcome, Panel, Text, TextExt, Toggle, Widget};
@@ -558,13 +558,17 @@ impl Layer for Delay {
             *self = Delay::new(ctx, app);
         }
 
-        if let Outcome::Clicked(x) = self.panel.event(ctx) {
-            match x.as_ref() {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
                 "close" => {
                     return Some(LayerOutcome::Close);
                 }
                 _ => unreachable!(),
+            },
+            Outcome::Changed(_) => {
+                return Some(LayerOutcome::Replace(Box::new(CompareDelay::new(ctx, app))));
             }
+            _ => {}
         }
         None
     }
@@ -614,6 +618,11 @@ impl Delay {
             draw: draw.build(ctx),
             panel: Panel::new_builder(Widget::col(vec![
                 header(ctx, "Delay per agent (minutes)"),
+                if app.has_prebaked().is_some() {
+                    Toggle::switch(ctx, "Compare before proposal", None, false)
+                } else {
+                    Widget::nothing()
+                },
                 ColorLegend::gradient(ctx, &app.cs.good_to_bad_red, vec!["0", "5", "10", "15+"]),
             ]))
             .aligned_pair(PANEL_PLACEMENT)
@@ -622,6 +631,285 @@ impl Delay {
     }
 }
 
+/// Colors roads and intersections by how much the average delay at traffic signals so far today
+/// has changed, relative to the baseline before the proposal.
+pub struct CompareDelay {
+    time: Time,
+    before: AverageDelays,
+    after: AverageDelays,
+    tooltip: Option<Text>,
+    draw: ToggleZoomed,
+    panel: Panel,
+}
+
+impl Layer for CompareDelay {
+    fn name(&self) -> Option<&'static str> {
+        Some("delay")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        let mut recalc_tooltip = false;
+        if app.primary.sim.time() != self.time {
+            *self = CompareDelay::new(ctx, app);
+            recalc_tooltip = true;
+        }
+
+        // Show a tooltip with the average delays, only when unzoomed
+        if ctx.canvas.is_unzoomed() {
+            if ctx.redo_mouseover() || recalc_tooltip {
+                self.tooltip = match app.mouseover_unzoomed_roads_and_intersections(ctx) {
+                    Some(ID::Road(r)) => describe_delay_change(
+                        app,
+                        self.before.roads.get(&r),
+                        self.after.roads.get(&r),
+                    ),
+                    Some(ID::Intersection(i)) => describe_delay_change(
+                        app,
+                        self.before.intersections.get(&i),
+                        self.after.intersections.get(&i),
+                    ),
+                    _ => None,
+                };
+            }
+        } else {
+            self.tooltip = None;
+        }
+
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => {
+                    return Some(LayerOutcome::Close);
+                }
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => {
+                return Some(LayerOutcome::Replace(Box::new(Delay::new(ctx, app))));
+            }
+            _ => {}
+        }
+        None
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl CompareDelay {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> CompareDelay {
+        let now = app.primary.sim.time();
+        let map = &app.primary.map;
+        let before = AverageDelays::new(map, app.prebaked(), now);
+        let after = AverageDelays::new(map, app.primary.sim.get_analytics(), now);
+
+        let mut colorer = ColorNetwork::new(app);
+        // In seconds of average delay per crossing
+        let scale = DivergingScale::new(Color::hex("#5D9630"), Color::WHITE, Color::hex("#A32015"))
+            .range(-60.0, 60.0)
+            .ignore(-5.0, 5.0);
+        for (r, dt) in &after.roads {
+            if let Some(c) = before
+                .roads
+                .get(r)
+                .and_then(|before| scale.eval((*dt - *before).inner_seconds()))
+            {
+                colorer.add_r(*r, c);
+            }
+        }
+        for (i, dt) in &after.intersections {
+            if let Some(c) = before
+                .intersections
+                .get(i)
+                .and_then(|before| scale.eval((*dt - *before).inner_seconds()))
+            {
+                colorer.add_i(*i, c);
+            }
+        }
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            header(ctx, "Change in delay"),
+            Text::from(
+                Line("Average delay at traffic signals so far today, compared to before")
+                    .secondary(),
+            )
+            .wrap_to_pct(ctx, 15)
+            .into_widget(ctx),
+            Toggle::switch(ctx, "Compare before proposal", None, true),
+            scale.make_legend(ctx, vec!["-1 min", "same", "+1 min"]),
+        ]))
+        .aligned_pair(PANEL_PLACEMENT)
+        .build(ctx);
+
+        CompareDelay {
+            time: now,
+            before,
+            after,
+            tooltip: None,
+            draw: colorer.build(ctx),
+            panel,
+        }
+    }
+}
+
+/// The average delay per crossing, for roads approaching traffic signals and the signals
+/// themselves
+struct AverageDelays {
+    roads: BTreeMap<RoadID, Duration>,
+    intersections: BTreeMap<IntersectionID, Duration>,
+}
+
+impl AverageDelays {
+    fn new(map: &Map, analytics: &Analytics, now: Time) -> AverageDelays {
+        let mut roads: BTreeMap<RoadID, (Duration, usize)> = BTreeMap::new();
+        let mut intersections: BTreeMap<IntersectionID, (Duration, usize)> = BTreeMap::new();
+        for (i, delays) in &analytics.intersection_delays {
+            let movements: Vec<_> = map.get_i(*i).movements.keys().collect();
+            for (idx, t, dt, _) in delays {
+                // The baseline covers the whole day, but only compare up to the present
+                if *t > now {
+                    break;
+                }
+                let entry = intersections.entry(*i).or_insert((Duration::ZERO, 0));
+                entry.0 += *dt;
+                entry.1 += 1;
+                if let Some(m) = movements.get(*idx as usize) {
+                    let entry = roads.entry(m.from.road).or_insert((Duration::ZERO, 0));
+                    entry.0 += *dt;
+                    entry.1 += 1;
+                }
+            }
+        }
+        AverageDelays {
+            roads: roads
+                .into_iter()
+                .map(|(r, (sum, count))| (r, sum / (count as f64)))
+                .collect(),
+            intersections: intersections
+                .into_iter()
+                .map(|(i, (sum, count))| (i, sum / (count as f64)))
+                .collect(),
+        }
+    }
+}
+
+fn describe_delay_change(
+    app: &App,
+    before: Option<&Duration>,
+    after: Option<&Duration>,
+) -> Option<Text> {
+    let fmt = |dt: Option<&Duration>| {
+        dt.map(|dt| dt.to_string(&app.opts.units))
+            .unwrap_or_else(|| "no data".to_string())
+    };
+    if before.is_none() && after.is_none() {
+        return None;
+    }
+    Some(Text::from(format!(
+        "Average delay: {} before, {} after",
+        fmt(before),
+        fmt(after)
+    )))
+}
+
+pub struct QueueSpillback {
+    time: Time,
+    draw: ToggleZoomed,
+    panel: Panel,
+}
+
+impl Layer for QueueSpillback {
+    fn name(&self) -> Option<&'static str> {
+        Some("queue spillback")
+    }
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<LayerOutcome> {
+        if app.primary.sim.time() != self.time {
+            *self = QueueSpillback::new(ctx, app);
+        }
+
+        <dyn Layer>::simple_event(ctx, &mut self.panel)
+    }
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+        self.draw.draw(g);
+    }
+    fn draw_minimap(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw.unzoomed);
+    }
+}
+
+impl QueueSpillback {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> QueueSpillback {
+        let map = &app.primary.map;
+        let mut draw = ToggleZoomed::builder();
+        draw.unzoomed
+            .push(app.cs.fade_map_dark, map.get_boundary_polygon().clone());
+
+        // Draw a bar from the end of each lane back to the last stopped vehicle. As the simulation
+        // runs, the bars grow and shrink with the queues.
+        let extents = app.primary.sim.get_queue_extents();
+        let mut total = Distance::ZERO;
+        for (l, extent) in &extents {
+            let lane = map.get_l(*l);
+            let pl = &lane.lane_center_pts;
+            if let Ok(slice) = pl.maybe_exact_slice(pl.length() - *extent, pl.length()) {
+                draw.unzoomed
+                    .push(Color::RED, slice.make_polygons(lane.width));
+                draw.zoomed
+                    .push(Color::RED.alpha(0.5), slice.make_polygons(0.5 * lane.width));
+            }
+            total += *extent;
+        }
+
+        let mut col = vec![
+            header(ctx, "Queue spillback"),
+            Text::from(
+                Line("Red bars show how far vehicles are stopped back from each intersection")
+                    .secondary(),
+            )
+            .wrap_to_pct(ctx, 15)
+            .into_widget(ctx),
+            format!(
+                "{} lanes with stopped vehicles, {} total",
+                prettyprint_usize(extents.len()),
+                total.to_string(&app.opts.units)
+            )
+            .text_widget(ctx),
+        ];
+        if let Some((i, (time, extent))) = app
+            .primary
+            .sim
+            .get_analytics()
+            .max_queue_extent
+            .iter()
+            .max_by_key(|(_, (_, extent))| *extent)
+        {
+            col.push(
+                Text::from(format!(
+                    "Longest queue so far: {} back from {}, at {}",
+                    extent.to_string(&app.opts.units),
+                    i,
+                    time.ampm_tostring()
+                ))
+                .wrap_to_pct(ctx, 15)
+                .into_widget(ctx),
+            );
+        }
+
+        QueueSpillback {
+            time: app.primary.sim.time(),
+            draw: draw.build(ctx),
+            panel: Panel::new_builder(Widget::col(col))
+                .aligned_pair(PANEL_PLACEMENT)
+                .build(ctx),
+        }
+    }
+}
+
 fn export_throughput(app: &App) -> Result<(String, String)> {
     let path1 = format!(
         "road_throughput_{}_{}.csv",
@@ -280,13 +280,19 @@ fn setup_app(ctx: &mut EventCtx, mut setup: Setup) -> (App, Vec<Box<dyn State<Ap
         && !setup.flags.sim_flags.load.contains("/scenarios/")
         && setup.mode == Mode::SomethingElse;
 
+    let ui_state = map_gui::tools::UiState::load("game");
+
     // Load the map used previously if we're starting on the title screen without any overrides.
     if title && setup.flags.sim_flags.load == MapName::seattle("montlake").path() {
-        if let Ok(default) = abstio::maybe_read_json::<map_gui::tools::DefaultMap>(
-            abstio::path_player("maps.json"),
-            &mut Timer::throwaway(),
-        ) {
-            setup.flags.sim_flags.load = default.last_map.path();
+        if let Some(name) = ui_state.last_map.clone().or_else(|| {
+            abstio::maybe_read_json::<map_gui::tools::DefaultMap>(
+                abstio::path_player("maps.json"),
+                &mut Timer::throwaway(),
+            )
+            .ok()
+            .map(|default| default.last_map)
+        }) {
+            setup.flags.sim_flags.load = name.path();
         }
     }
 
@@ -349,6 +355,7 @@ fn setup_app(ctx: &mut EventCtx, mut setup: Setup) -> (App, Vec<Box<dyn State<Ap
             opts: setup.opts.clone(),
             per_obj: crate::app::PerObjectActions::new(),
             session: crate::app::SessionState::empty(),
+            ui_state,
         };
         let map_name = MapName::from_path(&app.primary.current_flags.sim_flags.load).unwrap();
         let states = vec![map_gui::load::MapLoader::new_state(
@@ -377,6 +384,7 @@ fn setup_app(ctx: &mut EventCtx, mut setup: Setup) -> (App, Vec<Box<dyn State<Ap
             opts: setup.opts.clone(),
             per_obj: crate::app::PerObjectActions::new(),
             session: crate::app::SessionState::empty(),
+            ui_state,
         };
 
         let states = continue_app_setup(ctx, &mut app, title, setup, None);
@@ -497,7 +505,9 @@ fn finish_app_setup(
     if let Some(edits) = edits {
         ctx.loading_screen("apply initial edits", |ctx, timer| {
             crate::edit::apply_map_edits(ctx, app, edits);
-            app.primary.map.recalculate_pathfinding_after_edits(timer);
+            app.primary
+                .map
+                .recalculate_pathfinding_after_loading_proposal(timer);
             app.primary.clear_sim();
         });
     }
@@ -152,7 +152,9 @@ fn launch(ctx: &mut EventCtx, app: &App, edits: PermanentMapEdits) -> Transition
                 match edits.into_edits(&app.primary.map) {
                     Ok(edits) => {
                         apply_map_edits(ctx, app, edits);
-                        app.primary.map.recalculate_pathfinding_after_edits(timer);
+                        app.primary
+                            .map
+                            .recalculate_pathfinding_after_loading_proposal(timer);
                         None
                     }
                     Err(err) => Some(err),
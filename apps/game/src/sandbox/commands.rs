@@ -0,0 +1,187 @@
+use map_gui::options::OptionsPanel;
+use map_gui::tools::{CommandPalette, FilePicker, Navigator, PromptInput};
+use sim::ReproBundle;
+use widgetry::tools::{CameraTour, PopupMsg, TourPlayer, TourRecorder};
+use widgetry::{EventCtx, State};
+
+use crate::app::{App, Transition};
+use crate::edit::EditMode;
+use crate::layer;
+use crate::sandbox::dashboards::DashTab;
+use crate::sandbox::gameplay::{freeform, GameplayMode};
+use crate::sandbox::maybe_exit_sandbox;
+
+#[derive(Clone)]
+enum Command {
+    Dashboard(DashTab),
+    Layer(&'static str),
+    HideLayer,
+    EditMap,
+    ChangeMap,
+    SearchStreets,
+    PlayTour,
+    RecordTour,
+    ExportRepro,
+    Settings,
+    Quit,
+}
+
+/// Lists everything that can be done from sandbox mode right now, for the Ctrl+P palette.
+pub fn command_palette(
+    ctx: &mut EventCtx,
+    app: &App,
+    gameplay: &GameplayMode,
+) -> Box<dyn State<App>> {
+    let mut commands = Vec::new();
+    if gameplay.can_edit_roads() {
+        commands.push(("Edit map".to_string(), Command::EditMap));
+    }
+    if matches!(gameplay, GameplayMode::Freeform(_)) {
+        commands.push(("Change map".to_string(), Command::ChangeMap));
+    }
+    commands.push(("Search for a street".to_string(), Command::SearchStreets));
+    for choice in DashTab::choices(app) {
+        commands.push((
+            format!("Open dashboard: {}", choice.label),
+            Command::Dashboard(choice.data),
+        ));
+    }
+    for name in layer::all_layers(app) {
+        commands.push((format!("Show layer: {}", name), Command::Layer(name)));
+    }
+    if app.primary.layer.is_some() {
+        commands.push(("Hide layer".to_string(), Command::HideLayer));
+    }
+    commands.push(("Play a guided tour".to_string(), Command::PlayTour));
+    commands.push(("Record a guided tour".to_string(), Command::RecordTour));
+    if matches!(gameplay, GameplayMode::PlayScenario(_, _, _)) {
+        commands.push((
+            "Export a repro for a bug report".to_string(),
+            Command::ExportRepro,
+        ));
+    }
+    commands.push(("Settings".to_string(), Command::Settings));
+    commands.push(("Quit to the title screen".to_string(), Command::Quit));
+
+    let gameplay = gameplay.clone();
+    CommandPalette::new_state(
+        ctx,
+        commands,
+        Box::new(move |cmd, ctx, app| match cmd {
+            Command::Dashboard(tab) => {
+                app.session.dash_tab = tab;
+                Transition::Push(tab.launch(ctx, app))
+            }
+            Command::Layer(name) => {
+                if let Some(state) = layer::choose_layer(ctx, app, name) {
+                    Transition::Push(state)
+                } else {
+                    Transition::Keep
+                }
+            }
+            Command::HideLayer => {
+                app.primary.layer = None;
+                Transition::Keep
+            }
+            Command::EditMap => Transition::Push(EditMode::new_state(ctx, app, gameplay)),
+            Command::ChangeMap => Transition::Push(freeform::change_map(ctx, app)),
+            Command::SearchStreets => Transition::Push(Navigator::new_state(ctx, app)),
+            Command::PlayTour => Transition::Push(play_tour(ctx)),
+            Command::RecordTour => {
+                let map = &app.primary.map;
+                // Don't overwrite earlier tours of the same map
+                let path = (1..)
+                    .map(|n| {
+                        abstio::path_player(format!(
+                            "tours/{}_{}.json",
+                            map.get_name().as_filename(),
+                            n
+                        ))
+                    })
+                    .find(|path| !abstio::file_exists(path))
+                    .unwrap();
+                Transition::Push(TourRecorder::new_state(
+                    ctx,
+                    format!("Tour of {}", map.get_name().describe()),
+                    map.get_gps_bounds().clone(),
+                    path,
+                ))
+            }
+            Command::ExportRepro => export_repro(ctx, app, &gameplay),
+            Command::Settings => Transition::Push(OptionsPanel::new_state(ctx, app)),
+            Command::Quit => maybe_exit_sandbox(ctx),
+        }),
+    )
+}
+
+/// Asks for a tour file and plays it
+fn play_tour(ctx: &mut EventCtx) -> Box<dyn State<App>> {
+    FilePicker::new_state(
+        ctx,
+        Some(abstio::path_player("tours")),
+        Box::new(|ctx, app: &mut App, maybe_path| {
+            if let Ok(Some(path)) = maybe_path {
+                match CameraTour::load(path) {
+                    Ok(tour) => Transition::Replace(TourPlayer::new_state(
+                        ctx,
+                        tour,
+                        app.primary.map.get_gps_bounds().clone(),
+                    )),
+                    Err(err) => Transition::Replace(PopupMsg::new_state(
+                        ctx,
+                        "Error",
+                        vec![err.to_string()],
+                    )),
+                }
+            } else {
+                Transition::Pop
+            }
+        }),
+    )
+}
+
+/// Bundles everything needed to replay the current simulation up to now on another machine
+fn export_repro(ctx: &mut EventCtx, app: &App, gameplay: &GameplayMode) -> Transition {
+    let (scenario, modifiers) = match (gameplay, &app.primary.scenario) {
+        (GameplayMode::PlayScenario(_, name, modifiers), Some(scenario))
+            if &scenario.scenario_name == name =>
+        {
+            (scenario.clone(), modifiers.clone())
+        }
+        _ => {
+            return Transition::Push(PopupMsg::new_state(
+                ctx,
+                "Error",
+                vec!["Only simulations of a scenario can be exported"],
+            ));
+        }
+    };
+    Transition::Push(PromptInput::new_state(
+        ctx,
+        "Describe the problem",
+        String::new(),
+        Box::new(move |notes, ctx, app| {
+            let flags = &app.primary.current_flags.sim_flags;
+            let bundle = ReproBundle::new(
+                &app.primary.map,
+                scenario,
+                modifiers,
+                flags.rng_seed,
+                flags.opts.clone(),
+                app.primary.sim.time(),
+                notes,
+            );
+            let path = bundle.save();
+            Transition::Replace(PopupMsg::new_state(
+                ctx,
+                "Repro exported",
+                vec![
+                    format!("Saved to {}", path),
+                    "Attach this file to your bug report. Starting the game with this path \
+                     replays the simulation up to now."
+                        .to_string(),
+                ],
+            ))
+        }),
+    ))
+}
@@ -0,0 +1,197 @@
+use abstutil::prettyprint_usize;
+use sim::PrebakeMetadata;
+use widgetry::{EventCtx, GfxCtx, Line, Outcome, Panel, State, Text, TextExt, Widget};
+
+use crate::app::{App, Transition};
+use crate::sandbox::dashboards::DashTab;
+
+/// Explains what prebaked baseline results the current comparisons use, and whether they still
+/// match the map and scenario. Stale results can be regenerated.
+pub struct BaselineResults {
+    panel: Panel,
+}
+
+impl BaselineResults {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let map_name = app.primary.map.get_name();
+        let scenario_name = app.primary.sim.get_run_name();
+        let mut col = vec![DashTab::BaselineResults.picker(ctx, app)];
+
+        let mut txt = Text::from(
+            Line(format!(
+                "Baseline results for {} on {}",
+                scenario_name,
+                map_name.describe()
+            ))
+            .small_heading(),
+        );
+        match app.has_prebaked() {
+            Some((m, s)) if m == map_name && s == scenario_name => {
+                txt.add_line(
+                    Line(
+                        "Dashboards compare the current simulation against these results, from \
+                         running the scenario without any edits.",
+                    )
+                    .secondary(),
+                );
+                for (metric, count) in app.prebaked().describe_contents() {
+                    if count == 0 {
+                        txt.add_line(
+                            Line(format!("{}: no data", metric))
+                                .fg(ctx.style().text_destructive_color),
+                        );
+                    } else {
+                        txt.add_line(format!("{}: {} records", metric, prettyprint_usize(count)));
+                    }
+                }
+            }
+            _ => {
+                txt.add_line(
+                    Line("There are no baseline results loaded, so there's nothing to compare to.")
+                        .fg(ctx.style().text_destructive_color),
+                );
+            }
+        }
+        col.push(txt.wrap_to_pct(ctx, 80).into_widget(ctx).section(ctx));
+
+        let mut txt = Text::from(Line("Where the results came from").small_heading());
+        let mut stale = true;
+        match PrebakeMetadata::load(map_name, scenario_name) {
+            Some(metadata) => {
+                txt.add_line(format!("Generated {}", describe_age(metadata.created)));
+                txt.add_line(format!(
+                    "Map checksum {}",
+                    describe_checksum(metadata.map_checksum)
+                ));
+                txt.add_line(format!(
+                    "Scenario checksum {}",
+                    describe_checksum(metadata.scenario_checksum)
+                ));
+                txt.add_line(format!(
+                    "{} trips simulated",
+                    prettyprint_usize(metadata.num_trips)
+                ));
+                if let Some(ref scenario) = app.primary.scenario {
+                    let reasons = metadata.stale_reasons(scenario);
+                    if reasons.is_empty() {
+                        txt.add_line(Line("These results match the current map and scenario."));
+                        stale = false;
+                    } else {
+                        for reason in reasons {
+                            txt.add_line(Line(reason).fg(ctx.style().text_destructive_color));
+                        }
+                    }
+                }
+            }
+            None => {
+                txt.add_line(
+                    Line(
+                        "There's no record of how these results were generated. They may not \
+                         match the current map and scenario.",
+                    )
+                    .fg(ctx.style().text_destructive_color),
+                );
+            }
+        }
+        col.push(txt.wrap_to_pct(ctx, 80).into_widget(ctx).section(ctx));
+
+        if cfg!(not(target_arch = "wasm32")) {
+            col.push(
+                ctx.style()
+                    .btn_solid_primary
+                    .text(if stale {
+                        "Regenerate baseline results"
+                    } else {
+                        "Regenerate anyway"
+                    })
+                    .disabled(app.primary.scenario.is_none())
+                    .disabled_tooltip("This scenario can't be simulated again from here")
+                    .build_widget(ctx, "regenerate"),
+            );
+            col.push(
+                "This simulates the whole day without edits, so it takes a while.".text_widget(ctx),
+            );
+        }
+
+        Box::new(BaselineResults {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for BaselineResults {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                "regenerate" => {
+                    regenerate(ctx, app);
+                    Transition::Replace(BaselineResults::new_state(ctx, app))
+                }
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => DashTab::BaselineResults
+                .transition(ctx, app, &self.panel)
+                .unwrap(),
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
+
+fn describe_checksum(checksum: Option<u64>) -> String {
+    match checksum {
+        Some(x) => format!("{:016x}", x),
+        None => "unknown".to_string(),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn describe_age(created: u64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|dt| dt.as_secs())
+        .unwrap_or(0);
+    let hours = now.saturating_sub(created) / 3600;
+    if hours < 1 {
+        "less than an hour ago".to_string()
+    } else if hours < 48 {
+        format!("{} hours ago", hours)
+    } else {
+        format!("{} days ago", hours / 24)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn describe_age(created: u64) -> String {
+    format!("{} seconds after the UNIX epoch", created)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn regenerate(ctx: &mut EventCtx, app: &mut App) {
+    let scenario = app.primary.scenario.clone().unwrap();
+    let prebaked = ctx.loading_screen("regenerate baseline results", |_, timer| {
+        // The baseline never has edits, so don't use the current map
+        let map = map_model::Map::load_synchronously(scenario.map_name.path(), timer);
+        sim::prebake::prebake(&map, scenario.clone(), timer);
+        abstio::read_binary::<sim::Analytics>(
+            abstio::path_prebaked_results(&scenario.map_name, &scenario.scenario_name),
+            timer,
+        )
+    });
+    app.set_prebaked(Some((
+        scenario.map_name.clone(),
+        scenario.scenario_name,
+        prebaked,
+    )));
+}
+
+#[cfg(target_arch = "wasm32")]
+fn regenerate(_: &mut EventCtx, _: &mut App) {
+    unreachable!()
+}
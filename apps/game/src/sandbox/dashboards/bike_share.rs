@@ -0,0 +1,380 @@
+use abstutil::{prettyprint_usize, Counter};
+use geom::{Circle, Distance, Duration, Time};
+use map_gui::tools::ColorLegend;
+use map_gui::ID;
+use map_model::BuildingID;
+use sim::{BikeShareStation, BikeShareSystem};
+use widgetry::tools::PopupMsg;
+use widgetry::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Line, LinePlot, Outcome,
+    Panel, PlotOptions, Series, Spinner, State, Text, TextExt, Toggle, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::CommonState;
+use crate::sandbox::dashboards::DashTab;
+use crate::sandbox::SandboxMode;
+
+/// Summarizes how the bike share system has been used so far, and where people couldn't find a
+/// bike or a dock.
+pub struct BikeShareUsage {
+    panel: Panel,
+}
+
+impl BikeShareUsage {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let sim = &app.primary.sim;
+        let map = &app.primary.map;
+        let mut col = vec![DashTab::BikeShare.picker(ctx, app)];
+
+        let system = if let Some(system) = sim.get_bike_share_system() {
+            system
+        } else {
+            col.push(
+                "No bike share system is running. Design one, then switch some trips to bike \
+                 share by changing the scenario's modifiers."
+                    .text_widget(ctx)
+                    .section(ctx),
+            );
+            col.push(
+                ctx.style()
+                    .btn_solid_primary
+                    .text("Design a bike share system")
+                    .build_def(ctx),
+            );
+            return Box::new(BikeShareUsage {
+                panel: Panel::new_builder(Widget::col(col))
+                    .exact_size_percent(90, 90)
+                    .build(ctx),
+            });
+        };
+        let stats = sim.get_bike_share_stats();
+
+        let mut txt = Text::new();
+        txt.add_line(format!(
+            "{} stations with {} docks and {} bikes{}",
+            prettyprint_usize(system.stations.len()),
+            prettyprint_usize(system.num_docks()),
+            prettyprint_usize(system.num_bikes()),
+            if system.free_floating {
+                ". Bikes can be left anywhere."
+            } else {
+                ""
+            }
+        ));
+        txt.add_line(format!(
+            "{} rides so far",
+            prettyprint_usize(stats.rides.len())
+        ));
+        txt.add_line(format!(
+            "{} people walked because no bike was close enough",
+            prettyprint_usize(stats.no_bike.sum())
+        ));
+        txt.add_line(format!(
+            "{} people walked because no dock near their destination was free",
+            prettyprint_usize(stats.no_dock.sum())
+        ));
+        txt.add_line(format!(
+            "{} bikes moved by rebalancing",
+            prettyprint_usize(stats.bikes_rebalanced)
+        ));
+        col.push(txt.into_widget(ctx).section(ctx));
+
+        if !stats.rides.is_empty() {
+            let mut pts = vec![(Time::START_OF_DAY, 0)];
+            for (idx, (time, _, _)) in stats.rides.iter().enumerate() {
+                pts.push((*time, idx + 1));
+            }
+            pts.push((sim.time(), stats.rides.len()));
+            col.push(
+                LinePlot::new_widget(
+                    ctx,
+                    "rides",
+                    vec![Series {
+                        label: "Rides started".to_string(),
+                        color: Color::GREEN,
+                        pts,
+                    }],
+                    PlotOptions::fixed(),
+                    app.opts.units,
+                )
+                .section(ctx),
+            );
+        }
+
+        let mut usage = Counter::new();
+        for (_, pickup, dropoff) in &stats.rides {
+            usage.inc(*pickup);
+            usage.inc(*dropoff);
+        }
+        let available = sim.get_bike_share_availability();
+        let mut busiest = Vec::new();
+        for (b, _) in usage.highest_n(10) {
+            let station = if let Some(station) = system.get_station(b) {
+                station
+            } else {
+                continue;
+            };
+            busiest.push(
+                format!(
+                    "{}: {} / {} docks filled, {} rides started, {} headed here, empty {} times",
+                    map.get_b(b).address,
+                    prettyprint_usize(available.get(&b).cloned().unwrap_or(0)),
+                    prettyprint_usize(station.capacity),
+                    prettyprint_usize(stats.rides.iter().filter(|(_, x, _)| *x == b).count()),
+                    prettyprint_usize(stats.rides.iter().filter(|(_, _, x)| *x == b).count()),
+                    prettyprint_usize(stats.no_bike.get(b))
+                )
+                .text_widget(ctx),
+            );
+        }
+        if !busiest.is_empty() {
+            col.push(
+                Widget::col(vec![
+                    Line("Busiest stations").small_heading().into_widget(ctx),
+                    Widget::col(busiest),
+                ])
+                .section(ctx),
+            );
+        }
+
+        col.push(
+            ctx.style()
+                .btn_outline
+                .text("Design a bike share system")
+                .build_def(ctx),
+        );
+
+        Box::new(BikeShareUsage {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for BikeShareUsage {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                "Design a bike share system" => {
+                    Transition::Push(BikeShareDesigner::new_state(ctx, app))
+                }
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => DashTab::BikeShare
+                .transition(ctx, app, &self.panel)
+                .unwrap(),
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
+
+/// Place bike share stations and pick the system's policies, then restart the simulation with
+/// it. The design is saved, so it can also be used with `--bike_share` from the command line.
+struct BikeShareDesigner {
+    panel: Panel,
+    system: BikeShareSystem,
+    draw: Drawable,
+}
+
+impl BikeShareDesigner {
+    fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        // Start from the system currently running, or the last saved design
+        let system = app
+            .primary
+            .sim
+            .get_bike_share_system()
+            .cloned()
+            .or_else(|| {
+                abstio::maybe_read_json(
+                    abstio::path_bike_share(app.primary.map.get_name()),
+                    &mut abstutil::Timer::throwaway(),
+                )
+                .ok()
+            })
+            .unwrap_or_default();
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Design a bike share system")
+                    .small_heading()
+                    .into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(Line("Click a building to add or remove a station next to it.").secondary())
+                .wrap_to_pct(ctx, 30)
+                .into_widget(ctx),
+            Widget::row(vec![
+                "New stations have".text_widget(ctx).centered_vert(),
+                Spinner::widget(ctx, "docks", (1, 100), 15_usize, 1),
+                "docks and start with".text_widget(ctx).centered_vert(),
+                Spinner::widget(ctx, "bikes", (0, 100), 10_usize, 1),
+                "bikes".text_widget(ctx).centered_vert(),
+            ]),
+            Widget::row(vec![
+                "People walk up to".text_widget(ctx).centered_vert(),
+                Spinner::widget(
+                    ctx,
+                    "max walk",
+                    (Distance::meters(100.0), Distance::meters(2000.0)),
+                    system.max_walk,
+                    Distance::meters(50.0),
+                ),
+                "to a bike or dock".text_widget(ctx).centered_vert(),
+            ]),
+            Widget::row(vec![
+                "Rebalance every".text_widget(ctx).centered_vert(),
+                Spinner::widget(
+                    ctx,
+                    "rebalance",
+                    (Duration::ZERO, Duration::hours(6)),
+                    system.rebalance_every.unwrap_or(Duration::ZERO),
+                    Duration::minutes(15),
+                ),
+                Line("(0 means never)")
+                    .secondary()
+                    .into_widget(ctx)
+                    .centered_vert(),
+            ]),
+            Toggle::checkbox(
+                ctx,
+                "let people leave bikes anywhere (free-floating)",
+                None,
+                system.free_floating,
+            ),
+            Text::new().into_widget(ctx).named("summary"),
+            ColorLegend::row(ctx, Color::GREEN, "station"),
+            ctx.style()
+                .btn_solid_primary
+                .text("Save and restart the simulation")
+                .build_def(ctx),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+        .build(ctx);
+
+        let mut state = BikeShareDesigner {
+            panel,
+            system,
+            draw: Drawable::empty(ctx),
+        };
+        state.stations_changed(ctx, app);
+        Box::new(state)
+    }
+
+    fn stations_changed(&mut self, ctx: &mut EventCtx, app: &App) {
+        let mut batch = GeomBatch::new();
+        for station in &self.system.stations {
+            batch.push(
+                Color::GREEN,
+                Circle::new(
+                    app.primary.map.get_b(station.bldg).polygon.center(),
+                    Distance::meters(15.0),
+                )
+                .to_polygon(),
+            );
+        }
+        self.draw = ctx.upload(batch);
+
+        let summary = format!(
+            "{} stations with {} docks and {} bikes",
+            prettyprint_usize(self.system.stations.len()),
+            prettyprint_usize(self.system.num_docks()),
+            prettyprint_usize(self.system.num_bikes())
+        )
+        .text_widget(ctx)
+        .named("summary");
+        self.panel.replace(ctx, "summary", summary);
+    }
+
+    fn toggle_station(&mut self, ctx: &mut EventCtx, app: &App, b: BuildingID) {
+        if self.system.get_station(b).is_some() {
+            self.system.stations.retain(|s| s.bldg != b);
+        } else {
+            self.system.stations.push(BikeShareStation {
+                bldg: b,
+                capacity: self.panel.spinner("docks"),
+                initial_bikes: self.panel.spinner("bikes"),
+            });
+        }
+        self.stations_changed(ctx, app);
+    }
+
+    fn save_and_restart(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        self.system.max_walk = self.panel.spinner("max walk");
+        let rebalance: Duration = self.panel.spinner("rebalance");
+        self.system.rebalance_every = if rebalance == Duration::ZERO {
+            None
+        } else {
+            Some(rebalance)
+        };
+        self.system.free_floating = self
+            .panel
+            .is_checked("let people leave bikes anywhere (free-floating)");
+        if self.system.stations.is_empty() {
+            return Transition::Push(PopupMsg::new_state(
+                ctx,
+                "Error",
+                vec!["Add at least one station first"],
+            ));
+        }
+
+        abstio::write_json(
+            abstio::path_bike_share(app.primary.map.get_name()),
+            &self.system,
+        );
+        app.primary.current_flags.sim_flags.opts.bike_share = Some(self.system.clone());
+        Transition::Multi(vec![
+            Transition::Pop,
+            Transition::Pop,
+            Transition::ConsumeState(Box::new(|state, _, app| {
+                let sandbox = state.downcast::<SandboxMode>().ok().unwrap();
+                vec![SandboxMode::simple_new(app, sandbox.gameplay_mode)]
+            })),
+        ])
+    }
+}
+
+impl State<App> for BikeShareDesigner {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+        if ctx.redo_mouseover() {
+            app.primary.current_selection = app.mouseover_unzoomed_buildings(ctx);
+        }
+        if let Some(ID::Building(b)) = app.primary.current_selection {
+            let label = if self.system.get_station(b).is_some() {
+                "remove this station"
+            } else {
+                "add a station here"
+            };
+            if app.per_obj.left_click(ctx, label) {
+                self.toggle_station(ctx, app, b);
+            }
+        }
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Save and restart the simulation" => {
+                    return self.save_and_restart(ctx, app);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.redraw(&self.draw);
+        self.panel.draw(g);
+        CommonState::draw_osd(g, app);
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use abstutil::prettyprint_usize;
+use geom::Time;
+use map_gui::tools::cmp_count;
+use map_model::RoadID;
+use widgetry::{EventCtx, GfxCtx, Line, Outcome, Panel, State, Text, TextExt, Widget};
+
+use crate::app::{App, Transition};
+use crate::sandbox::dashboards::DashTab;
+
+/// Summarizes the disruption from every scheduled road closure and parking ban in the current
+/// edits, grouped by the hours they're in effect.
+pub struct ScheduledClosures {
+    panel: Panel,
+}
+
+#[derive(Default)]
+struct Window {
+    closed: BTreeSet<RoadID>,
+    parking_spots: usize,
+}
+
+impl ScheduledClosures {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let map = &app.primary.map;
+        let now = app.primary.sim.time();
+        let analytics = app.primary.sim.get_analytics();
+
+        let mut windows: BTreeMap<(Time, Time), Window> = BTreeMap::new();
+        for r in map.all_roads() {
+            for w in &r.time_restrictions.closed {
+                windows
+                    .entry((w.start_time, w.end_time))
+                    .or_default()
+                    .closed
+                    .insert(r.id);
+            }
+            if r.parking_restrictions.is_empty() {
+                continue;
+            }
+            let mut times: BTreeSet<(Time, Time)> = BTreeSet::new();
+            for restriction in r
+                .parking_restrictions
+                .left
+                .iter()
+                .chain(&r.parking_restrictions.right)
+            {
+                times.insert((restriction.start_time, restriction.end_time));
+            }
+            for (start, end) in times {
+                let spots: usize = r
+                    .lanes
+                    .iter()
+                    .filter(|l| l.is_parking() && r.parking_restriction_at(l.id, start).is_some())
+                    .map(|l| l.number_parking_spots(map.get_config()))
+                    .sum();
+                windows.entry((start, end)).or_default().parking_spots += spots;
+            }
+        }
+
+        let mut col = vec![DashTab::ScheduledClosures.picker(ctx, app)];
+        if windows.is_empty() {
+            col.push(
+                "There are no scheduled closures or parking bans in the current proposal. Add \
+                 some in edit mode."
+                    .text_widget(ctx)
+                    .section(ctx),
+            );
+        }
+        for ((start, end), window) in windows {
+            let mut txt = Text::from(
+                Line(format!(
+                    "{} to {}",
+                    start.ampm_tostring(),
+                    end.ampm_tostring()
+                ))
+                .small_heading(),
+            );
+            txt.add_line(format!(
+                "{} roads closed to vehicles, {} parking spots unavailable",
+                prettyprint_usize(window.closed.len()),
+                prettyprint_usize(window.parking_spots)
+            ));
+
+            if !window.closed.is_empty() {
+                if now <= start {
+                    txt.add_line(Line("The closure hasn't started yet").secondary());
+                } else {
+                    // Only count the hours that have happened so far, including a partial one
+                    let last_hour = ((end.min(now) - Time::START_OF_DAY).inner_seconds() / 3600.0)
+                        .ceil() as usize;
+                    let hours = start.get_hours()..last_hour;
+                    let after: usize = hours
+                        .clone()
+                        .map(|hr| analytics.car_traffic_on_roads_during_hour(&window.closed, hr))
+                        .sum();
+                    txt.add_line(format!(
+                        "{} cars on the closed roads during these hours",
+                        prettyprint_usize(after)
+                    ));
+                    if app.has_prebaked().is_some() {
+                        let before: usize = hours
+                            .map(|hr| {
+                                app.prebaked()
+                                    .car_traffic_on_roads_during_hour(&window.closed, hr)
+                            })
+                            .sum();
+                        txt.add_line(Line("Compared to before these changes: ").secondary());
+                        cmp_count(&mut txt, before, after);
+                    }
+                }
+            }
+
+            col.push(txt.into_widget(ctx).section(ctx));
+        }
+
+        Box::new(ScheduledClosures {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for ScheduledClosures {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => DashTab::ScheduledClosures
+                .transition(ctx, app, &self.panel)
+                .unwrap(),
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
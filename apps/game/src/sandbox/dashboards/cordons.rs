@@ -0,0 +1,85 @@
+use abstutil::prettyprint_usize;
+use map_gui::tools::cmp_count;
+use widgetry::{EventCtx, GfxCtx, Line, Outcome, Panel, State, Text, TextExt, Widget};
+
+use crate::app::{App, Transition};
+use crate::sandbox::dashboards::DashTab;
+
+/// Summarizes revenue and traffic change for every charging cordon in the current edits.
+pub struct ChargingCordons {
+    panel: Panel,
+}
+
+impl ChargingCordons {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let now = app.primary.sim.time();
+        let analytics = app.primary.sim.get_analytics();
+        let cordons = &app.primary.map.get_edits().charging_cordons;
+
+        let mut col = vec![DashTab::ChargingCordons.picker(ctx, app)];
+        if cordons.is_empty() {
+            col.push(
+                "There are no charging cordons in the current proposal. Add some in edit mode."
+                    .text_widget(ctx)
+                    .section(ctx),
+            );
+        }
+        for (idx, cordon) in cordons.iter().enumerate() {
+            let (entries, revenue) = analytics.cordon_revenue(idx);
+
+            let mut txt = Text::from(Line(&cordon.name).small_heading());
+            txt.add_line(
+                Line(format!(
+                    "${:.2} to enter between {} and {}",
+                    cordon.price,
+                    cordon.start_time.ampm_tostring(),
+                    cordon.end_time.ampm_tostring()
+                ))
+                .secondary(),
+            );
+            txt.add_line(format!(
+                "{} charged entries, ${:.2} revenue",
+                prettyprint_usize(entries),
+                revenue
+            ));
+
+            let after = analytics.car_traffic_on_roads(&cordon.members, now);
+            txt.add_line(format!(
+                "{} cars on roads inside the cordon",
+                prettyprint_usize(after)
+            ));
+            if app.has_prebaked().is_some() {
+                let before = app.prebaked().car_traffic_on_roads(&cordon.members, now);
+                txt.add_line(Line("Compared to before these changes: ").secondary());
+                cmp_count(&mut txt, before, after);
+            }
+
+            col.push(txt.into_widget(ctx).section(ctx));
+        }
+
+        Box::new(ChargingCordons {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for ChargingCordons {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => DashTab::ChargingCordons
+                .transition(ctx, app, &self.panel)
+                .unwrap(),
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use abstutil::prettyprint_usize;
+use geom::{Distance, Duration, Time};
+use map_model::{EditRoad, IntersectionID, RoadID};
+use sim::{AgentType, Analytics};
+use widgetry::tools::PopupMsg;
+use widgetry::{
+    EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, State, Text, VerticalAlignment,
+    Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::common::RoadSelector;
+use crate::layer::collisions::{get_collisions, load_collisions};
+use crate::sandbox::dashboards::DashTab;
+
+/// Select the roads along a corridor, then export an HTML report describing its design, the
+/// simulated traffic along it, and how the current edits changed things.
+pub struct CorridorStudy {
+    panel: Panel,
+    selector: RoadSelector,
+}
+
+impl CorridorStudy {
+    pub fn new_state(ctx: &mut EventCtx, app: &mut App) -> Box<dyn State<App>> {
+        // The report includes real-world collisions, if the city has any
+        load_collisions(app);
+        let selector = RoadSelector::new(ctx, app, BTreeSet::new());
+        let panel = make_panel(ctx, app, &selector);
+        Box::new(CorridorStudy { panel, selector })
+    }
+}
+
+impl State<App> for CorridorStudy {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        let clicked = match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Export report" => {
+                    return Transition::Push(match export_report(app, &self.selector.roads) {
+                        Ok(path) => PopupMsg::new_state(
+                            ctx,
+                            "Report exported",
+                            vec![format!("Report written to {}", path)],
+                        ),
+                        Err(err) => {
+                            PopupMsg::new_state(ctx, "Export failed", vec![err.to_string()])
+                        }
+                    });
+                }
+                x => Some(x.to_string()),
+            },
+            Outcome::Changed(_) => {
+                if let Some(t) = DashTab::CorridorStudy.transition(ctx, app, &self.panel) {
+                    return t;
+                }
+                None
+            }
+            _ => None,
+        };
+        if self.selector.event(ctx, app, clicked.as_deref()) {
+            self.panel = make_panel(ctx, app, &self.selector);
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.panel.draw(g);
+        self.selector.draw(g, app, true);
+    }
+}
+
+fn make_panel(ctx: &mut EventCtx, app: &App, selector: &RoadSelector) -> Panel {
+    let map = &app.primary.map;
+    let mut txt = Text::new();
+    if selector.roads.is_empty() {
+        txt.add_line(Line("Select the roads along a corridor").secondary());
+    } else {
+        txt.add_line(format!(
+            "{} roads selected, {} long",
+            prettyprint_usize(selector.roads.len()),
+            selector
+                .roads
+                .iter()
+                .map(|r| map.get_r(*r).length())
+                .sum::<Distance>()
+                .to_string(&app.opts.units)
+        ));
+    }
+
+    Panel::new_builder(Widget::col(vec![
+        DashTab::CorridorStudy.picker(ctx, app),
+        txt.into_widget(ctx),
+        selector.make_controls(ctx),
+        ctx.style()
+            .btn_solid_primary
+            .text("Export report")
+            .disabled(selector.roads.is_empty())
+            .build_def(ctx),
+    ]))
+    .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+    .build(ctx)
+}
+
+fn export_report(app: &App, roads: &BTreeSet<RoadID>) -> Result<String> {
+    let path = format!(
+        "corridor_report_{}_{}.html",
+        app.primary.map.get_name().as_filename(),
+        app.primary.sim.time().as_filename()
+    );
+    fs_err::write(&path, make_report(app, roads)?)?;
+    Ok(path)
+}
+
+fn make_report(app: &App, roads: &BTreeSet<RoadID>) -> Result<String> {
+    let map = &app.primary.map;
+    let units = &app.opts.units;
+    let now = app.primary.sim.time();
+    let edits = map.get_edits();
+    let analytics = app.primary.sim.get_analytics();
+    let baseline = app.has_prebaked().map(|_| app.prebaked());
+
+    let mut intersections = BTreeSet::new();
+    for r in roads {
+        let road = map.get_r(*r);
+        intersections.insert(road.src_i);
+        intersections.insert(road.dst_i);
+    }
+
+    let mut f = String::new();
+    writeln!(f, "<!DOCTYPE html>")?;
+    writeln!(f, "<html><head><meta charset=\"utf-8\">")?;
+    writeln!(f, "<title>Corridor study</title>")?;
+    writeln!(
+        f,
+        "<style>table {{ border-collapse: collapse; }} th, td {{ border: 1px solid #999; \
+         padding: 4px 8px; }}</style>"
+    )?;
+    writeln!(f, "</head><body>")?;
+    writeln!(f, "<h1>Corridor study</h1>")?;
+    writeln!(
+        f,
+        "<p>Map: {}. Proposal: {}. Simulated until {}.</p>",
+        escape(&map.get_name().describe()),
+        escape(&edits.edits_name),
+        now.ampm_tostring()
+    )?;
+    if baseline.is_none() {
+        writeln!(
+            f,
+            "<p>There are no baseline results for this scenario, so nothing is compared to \
+             before the edits.</p>"
+        )?;
+    }
+
+    // The design of each road
+    writeln!(f, "<h2>Roads</h2>")?;
+    writeln!(f, "<table>")?;
+    writeln!(
+        f,
+        "<tr><th>Road</th><th>Length</th><th>Speed limit</th><th>Lanes, left to right</th>\
+         <th>Before edits</th></tr>"
+    )?;
+    for r in roads {
+        let road = map.get_r(*r);
+        let before = if edits.changed_roads.contains(r) {
+            let orig = EditRoad::get_orig_from_osm(road, map.get_config());
+            format!(
+                "{}; {}",
+                orig.speed_limit.to_string(units),
+                orig.lanes_ltr
+                    .iter()
+                    .map(|spec| spec.lt.short_name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        } else {
+            "unchanged".to_string()
+        };
+        writeln!(
+            f,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&road.get_name(app.opts.language.as_ref())),
+            road.length().to_string(units),
+            road.speed_limit.to_string(units),
+            road.lanes
+                .iter()
+                .map(|l| l.lane_type.short_name())
+                .collect::<Vec<_>>()
+                .join(", "),
+            escape(&before)
+        )?;
+    }
+    writeln!(f, "</table>")?;
+
+    // Traffic and delay by hour. Only compare hours simulated so far.
+    writeln!(f, "<h2>Traffic by hour</h2>")?;
+    writeln!(
+        f,
+        "<p>Volumes are averaged over the roads in the corridor. Delays are averaged over every \
+         agent crossing an intersection along it.</p>"
+    )?;
+    writeln!(f, "<table>")?;
+    write!(f, "<tr><th>Hour</th>")?;
+    for agent_type in AgentType::all() {
+        write!(f, "<th>{}</th>", agent_type.plural_noun())?;
+    }
+    write!(f, "<th>Average delay</th>")?;
+    if baseline.is_some() {
+        write!(
+            f,
+            "<th>All agents before edits</th><th>Average delay before edits</th>"
+        )?;
+    }
+    writeln!(f, "</tr>")?;
+    let after_volumes = volumes_per_hour(analytics, roads);
+    let after_delays = delays_per_hour(analytics, &intersections, now);
+    let before_volumes = baseline.map(|a| volumes_per_hour(a, roads));
+    let before_delays = baseline.map(|a| delays_per_hour(a, &intersections, now));
+    // Include the partial hour happening now
+    let num_hours = ((now - Time::START_OF_DAY).inner_seconds() / 3600.0).ceil() as usize;
+    for hour in 0..num_hours {
+        write!(
+            f,
+            "<tr><td>{}</td>",
+            (Time::START_OF_DAY + Duration::hours(hour)).ampm_tostring()
+        )?;
+        let mut all_agents = 0;
+        for agent_type in AgentType::all() {
+            let cnt = after_volumes.get(&(agent_type, hour)).cloned().unwrap_or(0);
+            all_agents += cnt;
+            write!(f, "<td>{}</td>", prettyprint_usize(cnt / roads.len()))?;
+        }
+        write!(f, "<td>{}</td>", describe_delay(after_delays.get(&hour)))?;
+        if let (Some(before_volumes), Some(before_delays)) = (&before_volumes, &before_delays) {
+            let before: usize = AgentType::all()
+                .into_iter()
+                .map(|agent_type| {
+                    before_volumes
+                        .get(&(agent_type, hour))
+                        .cloned()
+                        .unwrap_or(0)
+                })
+                .sum();
+            write!(
+                f,
+                "<td>{} (now {})</td><td>{}</td>",
+                prettyprint_usize(before / roads.len()),
+                prettyprint_usize(all_agents / roads.len()),
+                describe_delay(before_delays.get(&hour))
+            )?;
+        }
+        writeln!(f, "</tr>")?;
+    }
+    writeln!(f, "</table>")?;
+
+    // Transit
+    writeln!(f, "<h2>Transit</h2>")?;
+    let mut stops_per_route: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for r in roads {
+        for ts in &map.get_r(*r).transit_stops {
+            let boardings = analytics
+                .passengers_boarding
+                .get(ts)
+                .map(|x| x.len())
+                .unwrap_or(0);
+            for route in map.get_routes_serving_stop(*ts) {
+                let entry = stops_per_route
+                    .entry(route.short_name.clone())
+                    .or_insert((0, 0));
+                entry.0 += 1;
+                entry.1 += boardings;
+            }
+        }
+    }
+    if stops_per_route.is_empty() {
+        writeln!(f, "<p>No transit routes stop along this corridor.</p>")?;
+    } else {
+        writeln!(f, "<table>")?;
+        writeln!(
+            f,
+            "<tr><th>Route</th><th>Stops in the corridor</th><th>Boardings so far</th></tr>"
+        )?;
+        for (route, (stops, boardings)) in stops_per_route {
+            writeln!(
+                f,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&route),
+                prettyprint_usize(stops),
+                prettyprint_usize(boardings)
+            )?;
+        }
+        writeln!(f, "</table>")?;
+    }
+
+    // Real-world collisions
+    if let Some(data) = get_collisions(app) {
+        writeln!(f, "<h2>Recorded collisions</h2>")?;
+        let mut indices = Vec::new();
+        for r in roads {
+            indices.extend(data.per_road.get(r).cloned().unwrap_or_default());
+        }
+        for i in &intersections {
+            indices.extend(data.per_intersection.get(i).cloned().unwrap_or_default());
+        }
+        let mut per_severity: BTreeMap<String, usize> = BTreeMap::new();
+        for idx in &indices {
+            *per_severity
+                .entry(format!("{:?}", data.collisions[*idx].severity))
+                .or_insert(0) += 1;
+        }
+        writeln!(
+            f,
+            "<p>{} collisions were recorded along this corridor.</p>",
+            prettyprint_usize(indices.len())
+        )?;
+        writeln!(f, "<ul>")?;
+        for (severity, cnt) in per_severity {
+            writeln!(f, "<li>{}: {}</li>", severity, prettyprint_usize(cnt))?;
+        }
+        writeln!(f, "</ul>")?;
+    }
+
+    writeln!(f, "</body></html>")?;
+    Ok(f)
+}
+
+/// How many agents of each type crossed the roads each hour
+fn volumes_per_hour(
+    analytics: &Analytics,
+    roads: &BTreeSet<RoadID>,
+) -> BTreeMap<(AgentType, usize), usize> {
+    let mut results = BTreeMap::new();
+    for ((r, agent_type, hour), cnt) in &analytics.road_thruput.counts {
+        if roads.contains(r) {
+            *results.entry((*agent_type, *hour)).or_insert(0) += *cnt;
+        }
+    }
+    results
+}
+
+/// The total delay and number of agents crossing the intersections each hour, until some time
+fn delays_per_hour(
+    analytics: &Analytics,
+    intersections: &BTreeSet<IntersectionID>,
+    until: Time,
+) -> BTreeMap<usize, (Duration, usize)> {
+    let mut results = BTreeMap::new();
+    for i in intersections {
+        if let Some(delays) = analytics.intersection_delays.get(i) {
+            for (_, t, dt, _) in delays {
+                if *t > until {
+                    continue;
+                }
+                let entry = results.entry(t.get_hours()).or_insert((Duration::ZERO, 0));
+                entry.0 += *dt;
+                entry.1 += 1;
+            }
+        }
+    }
+    results
+}
+
+fn describe_delay(delay: Option<&(Duration, usize)>) -> String {
+    match delay {
+        Some((total, cnt)) if *cnt > 0 => format!("{}", *total / (*cnt as f64)),
+        _ => "-".to_string(),
+    }
+}
+
+fn escape(x: &str) -> String {
+    x.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use abstutil::prettyprint_usize;
+use geom::Time;
+use sim::TripID;
+use synthpop::TripPurpose;
+use widgetry::{
+    Color, EventCtx, GfxCtx, LinePlot, Outcome, Panel, PlotOptions, Series, State, Text, TextExt,
+    Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::sandbox::dashboards::DashTab;
+
+/// Shows how quickly people are clearing out of a hazard zone, for scenarios with an evacuation.
+pub struct EvacuationClearance {
+    panel: Panel,
+}
+
+impl EvacuationClearance {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let analytics = app.primary.sim.get_analytics();
+        let now = app.primary.sim.time();
+        let evacuations: BTreeMap<TripID, Time> = app
+            .primary
+            .sim
+            .all_trip_info()
+            .into_iter()
+            .filter(|(_, info)| info.purpose == TripPurpose::Evacuation)
+            .map(|(id, info)| (id, info.departure))
+            .collect();
+
+        let mut col = vec![DashTab::EvacuationClearance.picker(ctx, app)];
+        if evacuations.is_empty() {
+            col.push(
+                "Nobody is evacuating in this scenario. Add an evacuation by changing the \
+                 scenario's modifiers."
+                    .text_widget(ctx)
+                    .section(ctx),
+            );
+            return Box::new(EvacuationClearance {
+                panel: Panel::new_builder(Widget::col(col))
+                    .exact_size_percent(90, 90)
+                    .build(ctx),
+            });
+        }
+
+        // The order is given just before the first departure
+        let order = evacuations.values().min().cloned().unwrap();
+        let mut started: Vec<Time> = evacuations
+            .keys()
+            .filter_map(|id| analytics.started_trips.get(id).cloned())
+            .collect();
+        started.sort();
+        let mut arrived = Vec::new();
+        let mut cancelled = 0;
+        for (time, id, _, maybe_dt) in &analytics.finished_trips {
+            if !evacuations.contains_key(id) {
+                continue;
+            }
+            if maybe_dt.is_some() {
+                arrived.push(*time);
+            } else {
+                cancelled += 1;
+            }
+        }
+        arrived.sort();
+
+        let total = evacuations.len();
+        let mut txt = Text::new();
+        txt.add_line(format!(
+            "{} people ordered to evacuate at {}",
+            prettyprint_usize(total),
+            order.ampm_tostring()
+        ));
+        txt.add_line(format!(
+            "{} have left, {} reached safety, {} couldn't get out",
+            prettyprint_usize(started.len()),
+            prettyprint_usize(arrived.len()),
+            prettyprint_usize(cancelled)
+        ));
+        for pct in [50, 90, 100] {
+            // How many people have to arrive to count as clearing this much
+            let needed = ((total * pct) as f64 / 100.0).ceil() as usize;
+            match arrived.get(needed.max(1) - 1) {
+                Some(time) => {
+                    txt.add_line(format!("{}% safe after {}", pct, *time - order));
+                }
+                None => {
+                    txt.add_line(format!("{}% not safe yet", pct));
+                }
+            }
+        }
+        col.push(txt.into_widget(ctx).section(ctx));
+
+        if now > order {
+            col.push(
+                LinePlot::new_widget(
+                    ctx,
+                    "clearance",
+                    vec![
+                        Series {
+                            label: "Left the hazard zone".to_string(),
+                            color: Color::RED,
+                            pts: cumulative(order, &started, now),
+                        },
+                        Series {
+                            label: "Reached safety".to_string(),
+                            color: Color::GREEN,
+                            pts: cumulative(order, &arrived, now),
+                        },
+                    ],
+                    PlotOptions::fixed(),
+                    app.opts.units,
+                )
+                .section(ctx),
+            );
+        }
+
+        Box::new(EvacuationClearance {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+/// Turns sorted times into a running count, starting from the evacuation order and continuing
+/// until now.
+fn cumulative(order: Time, times: &[Time], now: Time) -> Vec<(Time, usize)> {
+    let mut pts = vec![(order, 0)];
+    for (idx, time) in times.iter().enumerate() {
+        pts.push((*time, idx + 1));
+    }
+    pts.push((now, times.len()));
+    pts
+}
+
+impl State<App> for EvacuationClearance {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => DashTab::EvacuationClearance
+                .transition(ctx, app, &self.panel)
+                .unwrap(),
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
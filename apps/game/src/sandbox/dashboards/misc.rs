@@ -151,7 +151,7 @@ impl TransitRoutes {
         }
         routes.sort();
 
-        let col = vec![
+        let mut col = vec![
             DashTab::TransitRoutes.picker(ctx, app),
             Line(format!("{} Transit routes", routes.len()))
                 .small_heading()
@@ -192,6 +192,28 @@ impl TransitRoutes {
             ),
         ];
 
+        let lots = app.primary.map.park_and_ride_lots();
+        if !lots.is_empty() && !app.primary.sim.infinite_parking() {
+            col.push(
+                Line(format!("{} Park-and-ride lots", lots.len()))
+                    .small_heading()
+                    .into_widget(ctx),
+            );
+            for pl in lots {
+                let capacity = app.primary.map.get_pl(pl).capacity();
+                let filled = capacity.saturating_sub(app.primary.sim.num_free_lot_spots(pl));
+                col.push(
+                    format!(
+                        "{}: {} / {} spots filled",
+                        pl,
+                        prettyprint_usize(filled),
+                        prettyprint_usize(capacity)
+                    )
+                    .text_widget(ctx),
+                );
+            }
+        }
+
         Box::new(TransitRoutes {
             panel: Panel::new_builder(Widget::col(col))
                 .exact_size_percent(90, 90)
@@ -1,4 +1,5 @@
 pub use commuter::CommuterPatterns;
+pub use school_streets::SchoolRunReport;
 pub use traffic_signals::TrafficSignalDemand;
 
 use widgetry::{Choice, EventCtx, Image, Line, Panel, State, TextExt, Widget};
@@ -6,13 +7,22 @@ use widgetry::{Choice, EventCtx, Image, Line, Panel, State, TextExt, Widget};
 use crate::app::App;
 use crate::app::Transition;
 
+mod baseline;
+mod bike_share;
+mod closures;
 mod commuter;
+mod cordons;
+mod corridor;
+mod evacuation;
 mod generic_trip_table;
 mod misc;
 mod mode_shift;
 mod parking_overhead;
+mod reliability;
 mod risks;
+mod school_streets;
 mod selector;
+mod summary;
 mod traffic_signals;
 mod travel_times;
 mod trip_problems;
@@ -30,10 +40,20 @@ pub enum DashTab {
     CommuterPatterns,
     TrafficSignals,
     ModeShift,
+    ChargingCordons,
+    SchoolStreets,
+    TravelTimeReliability,
+    BaselineResults,
+    EvacuationClearance,
+    ScheduledClosures,
+    CorridorStudy,
+    BikeShare,
+    SimSummary,
 }
 
 impl DashTab {
-    pub fn picker(self, ctx: &EventCtx, app: &App) -> Widget {
+    /// Every dashboard that makes sense for the current simulation.
+    pub fn choices(app: &App) -> Vec<Choice<DashTab>> {
         let mut choices = vec![
             Choice::new("Trip Table", DashTab::TripTable),
             Choice::new("Travel Times", DashTab::TravelTimes),
@@ -44,15 +64,28 @@ impl DashTab {
             Choice::new("Commuter Patterns", DashTab::CommuterPatterns),
             Choice::new("Traffic Signal Demand", DashTab::TrafficSignals),
             Choice::new("Mode shift (experimental)", DashTab::ModeShift),
+            Choice::new("Charging Cordons", DashTab::ChargingCordons),
+            Choice::new("School Streets", DashTab::SchoolStreets),
+            Choice::new("Travel Time Reliability", DashTab::TravelTimeReliability),
+            Choice::new("Baseline Results", DashTab::BaselineResults),
+            Choice::new("Evacuation Clearance", DashTab::EvacuationClearance),
+            Choice::new("Scheduled Closures", DashTab::ScheduledClosures),
+            Choice::new("Corridor Study", DashTab::CorridorStudy),
+            Choice::new("Bike Share", DashTab::BikeShare),
+            Choice::new("Summary", DashTab::SimSummary),
         ];
         if app.has_prebaked().is_none() {
             choices.remove(1);
             choices.remove(1);
         }
+        choices
+    }
+
+    pub fn picker(self, ctx: &EventCtx, app: &App) -> Widget {
         Widget::row(vec![
             Image::from_path("system/assets/meters/trip_histogram.svg").into_widget(ctx),
             Line("Data").big_heading_plain().into_widget(ctx),
-            Widget::dropdown(ctx, "tab", self, choices),
+            Widget::dropdown(ctx, "tab", self, DashTab::choices(app)),
             format!("By {}", app.primary.sim.time().ampm_tostring())
                 .text_widget(ctx)
                 .centered_vert(),
@@ -73,6 +106,18 @@ impl DashTab {
             DashTab::CommuterPatterns => CommuterPatterns::new_state(ctx, app),
             DashTab::TrafficSignals => TrafficSignalDemand::new_state(ctx, app),
             DashTab::ModeShift => mode_shift::ModeShift::new_state(ctx, app),
+            DashTab::ChargingCordons => cordons::ChargingCordons::new_state(ctx, app),
+            // Matches the default when adding school runs to a scenario
+            DashTab::SchoolStreets => school_streets::SchoolStreets::new_state(ctx, app, 9),
+            DashTab::TravelTimeReliability => {
+                reliability::TravelTimeReliability::new_state(ctx, app)
+            }
+            DashTab::BaselineResults => baseline::BaselineResults::new_state(ctx, app),
+            DashTab::EvacuationClearance => evacuation::EvacuationClearance::new_state(ctx, app),
+            DashTab::ScheduledClosures => closures::ScheduledClosures::new_state(ctx, app),
+            DashTab::CorridorStudy => corridor::CorridorStudy::new_state(ctx, app),
+            DashTab::BikeShare => bike_share::BikeShareUsage::new_state(ctx, app),
+            DashTab::SimSummary => summary::SimSummary::new_state(ctx, app),
         }
     }
 
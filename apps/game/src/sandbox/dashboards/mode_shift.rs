@@ -178,10 +178,18 @@ fn make_table(ctx: &mut EventCtx, app: &App) -> Table<App, Entry, Filters> {
             // Just some sample defaults
             max_driving_time: Duration::minutes(30),
             max_biking_time: Duration::minutes(30),
-            max_distance: Distance::miles(10.0),
-            max_elevation_gain: Distance::feet(30.0),
+            max_distance: if app.opts.units.metric {
+                Distance::meters(15_000.0)
+            } else {
+                Distance::miles(10.0)
+            },
+            max_elevation_gain: if app.opts.units.metric {
+                Distance::meters(10.0)
+            } else {
+                Distance::feet(30.0)
+            },
         },
-        to_controls: Box::new(|ctx, _, state| {
+        to_controls: Box::new(|ctx, app, state| {
             Widget::row(vec![
                 Widget::row(vec![
                     "Max driving time".text_widget(ctx).centered_vert(),
@@ -205,22 +213,24 @@ fn make_table(ctx: &mut EventCtx, app: &App) -> Table<App, Entry, Filters> {
                 ]),
                 Widget::row(vec![
                     "Max distance".text_widget(ctx).centered_vert(),
-                    Spinner::widget(
+                    Spinner::distance_widget(
                         ctx,
                         "max_distance",
                         (Distance::ZERO, Distance::miles(20.0)),
                         state.max_distance,
-                        Distance::miles(0.1),
+                        (Distance::meters(100.0), Distance::miles(0.1)),
+                        app.opts.units,
                     ),
                 ]),
                 Widget::row(vec![
                     "Max elevation gain".text_widget(ctx).centered_vert(),
-                    Spinner::widget(
+                    Spinner::distance_widget(
                         ctx,
                         "max_elevation_gain",
                         (Distance::ZERO, Distance::feet(500.0)),
                         state.max_elevation_gain,
-                        Distance::feet(10.0),
+                        (Distance::meters(5.0), Distance::feet(10.0)),
+                        app.opts.units,
                     ),
                 ]),
             ])
@@ -297,7 +307,7 @@ fn show_route_gaps(ctx: &mut EventCtx, app: &App, table: &Table<App, Entry, Filt
         let mut high_stress = HashSet::new();
         for r in map.all_roads() {
             for dr in r.id.both_directions() {
-                if r.high_stress_for_bikes(map, dr.dir) {
+                if r.high_stress_for_bikes(map, dr.dir, &app.session.bike_stress) {
                     high_stress.insert(dr);
                 }
             }
@@ -1,4 +1,7 @@
-use geom::Duration;
+use std::collections::BTreeMap;
+
+use geom::{Distance, Duration, Statistic};
+use map_gui::tools::cmp_dist;
 use sim::{TripID, TripPhaseType};
 use synthpop::TripEndpoint;
 use widgetry::table::{Col, Filter, Table};
@@ -54,6 +57,7 @@ impl ParkingOverhead {
                     Filler::square_width(ctx, 0.15).named("preview"),
                 ])
                 .evenly_spaced(),
+                walking_summary(app).into_widget(ctx),
                 table.render(ctx, app),
             ])
             .section(ctx),
@@ -71,6 +75,42 @@ impl ParkingOverhead {
     }
 }
 
+/// How far people walk to and from parking matters more than spot counts when judging a proposal
+/// that removes parking, so summarize the distribution and compare against the baseline.
+fn walking_summary(app: &App) -> Text {
+    let now = app.primary.sim.time();
+    let (to_car, from_car) = app.primary.sim.get_analytics().parking_walk_distances(now);
+    let baseline = app
+        .has_prebaked()
+        .map(|_| app.prebaked().parking_walk_distances(now));
+
+    let mut txt = Text::from(Line("Walking distance to and from parking").small_heading());
+    for (label, after, before) in [
+        (
+            "Walking to the car",
+            &to_car,
+            baseline.as_ref().map(|(x, _)| x),
+        ),
+        (
+            "Walking after parking",
+            &from_car,
+            baseline.as_ref().map(|(_, x)| x),
+        ),
+    ] {
+        txt.add_line(Line(label).secondary());
+        txt.add_line(after.describe());
+        if let Some(before) = before {
+            for stat in [Statistic::P50, Statistic::P90] {
+                if let (Some(b), Some(a)) = (before.select(stat), after.select(stat)) {
+                    txt.add_line(format!("{} compared to before these changes:", stat));
+                    cmp_dist(&mut txt, app, a - b, "shorter", "longer");
+                }
+            }
+        }
+    }
+    txt
+}
+
 impl State<App> for ParkingOverhead {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self.panel.event(ctx) {
@@ -111,6 +151,8 @@ struct Entry {
     driving_duration: Duration,
     parking_duration: Duration,
     walking_duration: Duration,
+    walk_to_car: Distance,
+    walk_from_car: Distance,
     percent_overhead: usize,
     starts_off_map: bool,
     ends_off_map: bool,
@@ -122,6 +164,19 @@ struct Filters {
 }
 
 fn produce_raw_data(app: &App) -> Vec<Entry> {
+    // Park-and-ride trips may walk to and from a car more than once
+    let mut walks: BTreeMap<TripID, (Distance, Distance)> = BTreeMap::new();
+    for (_, trip, dist, to_car) in &app.primary.sim.get_analytics().parking_walks {
+        let entry = walks
+            .entry(*trip)
+            .or_insert((Distance::ZERO, Distance::ZERO));
+        if *to_car {
+            entry.0 += *dist;
+        } else {
+            entry.1 += *dist;
+        }
+    }
+
     // Gather raw data
     let mut data = Vec::new();
     for (id, phases) in app.primary.sim.get_analytics().get_all_trip_phases() {
@@ -159,12 +214,18 @@ fn produce_raw_data(app: &App) -> Vec<Entry> {
             continue;
         }
 
+        let (walk_to_car, walk_from_car) = walks
+            .get(&id)
+            .cloned()
+            .unwrap_or((Distance::ZERO, Distance::ZERO));
         data.push(Entry {
             trip: id,
             total_duration,
             driving_duration,
             parking_duration,
             walking_duration,
+            walk_to_car,
+            walk_from_car,
             percent_overhead: (100.0 * (1.0 - (driving_duration / total_duration))) as usize,
             starts_off_map,
             ends_off_map,
@@ -234,6 +295,16 @@ fn make_table(app: &App) -> Table<App, Entry, Filters> {
         }),
         Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.walking_duration))),
     );
+    table.column(
+        "Walk to car",
+        Box::new(|ctx, app, x| Text::from(x.walk_to_car.to_string(&app.opts.units)).render(ctx)),
+        Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.walk_to_car))),
+    );
+    table.column(
+        "Walk after parking",
+        Box::new(|ctx, app, x| Text::from(x.walk_from_car.to_string(&app.opts.units)).render(ctx)),
+        Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.walk_from_car))),
+    );
     table.column(
         "Percent overhead",
         Box::new(|ctx, _, x| Text::from(format!("{}%", x.percent_overhead)).render(ctx)),
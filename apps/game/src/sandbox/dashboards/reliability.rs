@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use abstutil::{prettyprint_usize, Timer};
+use sim::ReliabilityResults;
+use synthpop::{TripEndpoint, TripMode};
+use widgetry::{EventCtx, GfxCtx, Line, Outcome, Panel, State, Text, TextExt, Widget};
+
+use crate::app::{App, Transition};
+use crate::sandbox::dashboards::DashTab;
+
+// An origin/destination pair needs a few samples before percentiles mean anything
+const MIN_SAMPLES: usize = 3;
+const NUM_WORST: usize = 20;
+
+/// Shows how much travel times for the same trips vary across several runs of the scenario with
+/// different random seeds.
+pub struct TravelTimeReliability {
+    panel: Panel,
+}
+
+impl TravelTimeReliability {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let map = &app.primary.map;
+        let scenario_name = app.primary.sim.get_run_name();
+        let mut col = vec![DashTab::TravelTimeReliability.picker(ctx, app)];
+
+        match abstio::maybe_read_binary::<ReliabilityResults>(
+            abstio::path_prebaked_reliability(map.get_name(), scenario_name),
+            &mut Timer::throwaway(),
+        ) {
+            Ok(results) => {
+                let per_od = results.per_od(MIN_SAMPLES);
+
+                let mut txt = Text::from(
+                    Line(format!(
+                        "Across {} runs of {} with different random seeds",
+                        results.num_runs, scenario_name
+                    ))
+                    .small_heading(),
+                );
+                txt.add_line(
+                    Line(
+                        "Reliability is the 95th percentile travel time divided by the median. \
+                         1.0 means a trip always takes about as long; 2.0 means it sometimes \
+                         takes twice as long as usual.",
+                    )
+                    .secondary(),
+                );
+                let mut per_mode: BTreeMap<TripMode, Vec<f64>> = BTreeMap::new();
+                for (_, _, mode, r) in &per_od {
+                    per_mode
+                        .entry(*mode)
+                        .or_insert_with(Vec::new)
+                        .push(r.ratio());
+                }
+                for (mode, mut ratios) in per_mode {
+                    ratios.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    txt.add_line(format!(
+                        "{}: {} origin/destination pairs, median reliability {:.2}",
+                        mode.ongoing_verb(),
+                        prettyprint_usize(ratios.len()),
+                        ratios[ratios.len() / 2]
+                    ));
+                }
+                col.push(txt.wrap_to_pct(ctx, 80).into_widget(ctx).section(ctx));
+
+                let mut worst = per_od;
+                worst.sort_by(|a, b| b.3.ratio().partial_cmp(&a.3.ratio()).unwrap());
+                let mut txt = Text::from(Line("Least reliable trips").small_heading());
+                for (from, to, mode, r) in worst.into_iter().take(NUM_WORST) {
+                    txt.add_line(format!(
+                        "{} from {} to {}",
+                        mode.ongoing_verb(),
+                        describe_endpoint(app, from),
+                        describe_endpoint(app, to)
+                    ));
+                    txt.add_line(
+                        Line(format!(
+                            "  usually {}, but up to {} ({:.2}), over {} trips",
+                            r.p50,
+                            r.p95,
+                            r.ratio(),
+                            prettyprint_usize(r.samples)
+                        ))
+                        .secondary(),
+                    );
+                }
+                col.push(txt.into_widget(ctx).section(ctx));
+            }
+            Err(_) => {
+                col.push(
+                    format!(
+                        "No reliability results for {} yet. Generate them with `cli \
+                         prebake-reliability` on the scenario.",
+                        scenario_name
+                    )
+                    .text_widget(ctx)
+                    .section(ctx),
+                );
+            }
+        }
+
+        Box::new(TravelTimeReliability {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for TravelTimeReliability {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => DashTab::TravelTimeReliability
+                .transition(ctx, app, &self.panel)
+                .unwrap(),
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
+
+fn describe_endpoint(app: &App, endpt: TripEndpoint) -> String {
+    let map = &app.primary.map;
+    match endpt {
+        TripEndpoint::Building(b) => map.get_b(b).address.clone(),
+        TripEndpoint::Border(i) => format!(
+            "off map, via {}",
+            map.get_i(i).name(app.opts.language.as_ref(), map)
+        ),
+        TripEndpoint::SuddenlyAppear(pos) => format!("somewhere along {}", pos.lane()),
+    }
+}
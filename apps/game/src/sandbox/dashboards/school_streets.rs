@@ -0,0 +1,207 @@
+use std::collections::BTreeSet;
+
+use abstutil::{prettyprint_usize, Counter};
+use geom::{Duration, Time};
+use map_gui::tools::cmp_count;
+use synthpop::{TripEndpoint, TripMode};
+use widgetry::{EventCtx, GfxCtx, Line, Outcome, Panel, Spinner, State, Text, TextExt, Widget};
+
+use crate::app::{App, Transition};
+use crate::sandbox::dashboards::DashTab;
+
+/// Summarizes traffic in front of schools and how students travel there, during the hour before
+/// the morning bell.
+pub struct SchoolStreets {
+    panel: Panel,
+}
+
+/// What happened around one school run, kept to compare against a later run.
+#[derive(Clone)]
+pub struct SchoolRunReport {
+    bell_hour: usize,
+    cars_fronting_schools: usize,
+    arrivals: Counter<TripMode>,
+}
+
+impl SchoolRunReport {
+    fn new(app: &App, bell_hour: usize) -> SchoolRunReport {
+        let map = &app.primary.map;
+        let analytics = app.primary.sim.get_analytics();
+        let schools: BTreeSet<_> = synthpop::find_schools(map).into_iter().collect();
+        let end = Time::START_OF_DAY + Duration::hours(bell_hour);
+        let start = end - Duration::hours(1);
+
+        let mut arrivals = Counter::new();
+        for (time, id, mode, maybe_dt) in &analytics.finished_trips {
+            if maybe_dt.is_none() || *time < start || *time > end {
+                continue;
+            }
+            if let TripEndpoint::Building(b) = app.primary.sim.trip_info(*id).end {
+                if schools.contains(&b) {
+                    arrivals.inc(*mode);
+                }
+            }
+        }
+
+        SchoolRunReport {
+            bell_hour,
+            cars_fronting_schools: analytics.car_traffic_on_roads_during_hour(
+                &synthpop::roads_fronting_schools(map),
+                bell_hour - 1,
+            ),
+            arrivals,
+        }
+    }
+
+    fn walking_share(&self) -> f64 {
+        let total = self.arrivals.sum();
+        if total == 0 {
+            return 0.0;
+        }
+        100.0 * (self.arrivals.get(TripMode::Walk) as f64) / (total as f64)
+    }
+
+    fn describe(&self, txt: &mut Text, baseline: Option<&SchoolRunReport>) {
+        txt.add_line(format!(
+            "{} cars on streets in front of schools",
+            prettyprint_usize(self.cars_fronting_schools)
+        ));
+        if let Some(before) = baseline {
+            txt.add_line(Line("Compared to the baseline: ").secondary());
+            cmp_count(
+                txt,
+                before.cars_fronting_schools,
+                self.cars_fronting_schools,
+            );
+        }
+
+        txt.add_line(format!(
+            "{} trips arrived at schools, {:.1}% walking",
+            prettyprint_usize(self.arrivals.sum()),
+            self.walking_share()
+        ));
+        if let Some(before) = baseline {
+            txt.add_line(
+                Line(format!(
+                    "Walking share was {:.1}% in the baseline",
+                    before.walking_share()
+                ))
+                .secondary(),
+            );
+        }
+        for mode in TripMode::all() {
+            txt.add_line(
+                Line(format!(
+                    "  {}: {}",
+                    mode.ongoing_verb(),
+                    prettyprint_usize(self.arrivals.get(mode))
+                ))
+                .secondary(),
+            );
+        }
+    }
+}
+
+impl SchoolStreets {
+    pub fn new_state(ctx: &mut EventCtx, app: &App, bell_hour: usize) -> Box<dyn State<App>> {
+        let num_schools = synthpop::find_schools(&app.primary.map).len();
+        let mut col = vec![DashTab::SchoolStreets.picker(ctx, app)];
+
+        col.push(
+            Widget::row(vec![
+                "Morning bell at hour".text_widget(ctx).centered_vert(),
+                Spinner::widget(ctx, "bell hour", (6, 10), bell_hour, 1),
+            ])
+            .section(ctx),
+        );
+
+        if num_schools == 0 {
+            col.push(
+                "There are no schools in this map."
+                    .text_widget(ctx)
+                    .section(ctx),
+            );
+        } else if app.primary.sim.time() < Time::START_OF_DAY + Duration::hours(bell_hour) {
+            col.push(
+                format!(
+                    "Run the simulation until {} to see the morning school run.",
+                    (Time::START_OF_DAY + Duration::hours(bell_hour)).ampm_tostring()
+                )
+                .text_widget(ctx)
+                .section(ctx),
+            );
+        } else {
+            let report = SchoolRunReport::new(app, bell_hour);
+            let baseline = app
+                .session
+                .school_run_baseline
+                .as_ref()
+                .filter(|b| b.bell_hour == bell_hour);
+
+            let mut txt = Text::from(
+                Line(format!(
+                    "{} schools, during the hour before the bell",
+                    num_schools
+                ))
+                .small_heading(),
+            );
+            report.describe(&mut txt, baseline);
+            col.push(
+                Widget::col(vec![
+                    txt.into_widget(ctx),
+                    Text::from(
+                        Line(
+                            "Save these results as a baseline, then close school streets in edit \
+                             mode and run again to compare.",
+                        )
+                        .secondary(),
+                    )
+                    .wrap_to_pct(ctx, 50)
+                    .into_widget(ctx),
+                    ctx.style()
+                        .btn_outline
+                        .text("Save as baseline")
+                        .build_def(ctx),
+                ])
+                .section(ctx),
+            );
+        }
+
+        Box::new(SchoolStreets {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for SchoolStreets {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                "Save as baseline" => {
+                    let bell_hour = self.panel.spinner("bell hour");
+                    app.session.school_run_baseline = Some(SchoolRunReport::new(app, bell_hour));
+                    Transition::Replace(SchoolStreets::new_state(ctx, app, bell_hour))
+                }
+                _ => unreachable!(),
+            },
+            Outcome::Changed(x) => {
+                if x == "bell hour" {
+                    let bell_hour = self.panel.spinner("bell hour");
+                    Transition::Replace(SchoolStreets::new_state(ctx, app, bell_hour))
+                } else {
+                    DashTab::SchoolStreets
+                        .transition(ctx, app, &self.panel)
+                        .unwrap()
+                }
+            }
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
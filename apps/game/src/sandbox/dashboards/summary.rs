@@ -0,0 +1,48 @@
+use widgetry::{EventCtx, GfxCtx, Outcome, Panel, State, Text, Widget};
+
+use crate::app::{App, Transition};
+use crate::sandbox::dashboards::DashTab;
+
+/// The same key performance indicators that headless runs and the API report.
+pub struct SimSummary {
+    panel: Panel,
+}
+
+impl SimSummary {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let kpis = app.primary.sim.kpis(&app.primary.map);
+        let mut txt = Text::new();
+        for line in kpis.describe() {
+            txt.add_line(line);
+        }
+
+        let col = vec![
+            DashTab::SimSummary.picker(ctx, app),
+            txt.into_widget(ctx).section(ctx),
+        ];
+        Box::new(SimSummary {
+            panel: Panel::new_builder(Widget::col(col))
+                .exact_size_percent(90, 90)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for SimSummary {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => DashTab::SimSummary
+                .transition(ctx, app, &self.panel)
+                .unwrap(),
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _app: &App) {
+        self.panel.draw(g);
+    }
+}
@@ -8,14 +8,14 @@ use abstutil::prettyprint_usize;
 use geom::{Distance, Duration, Polygon, Pt2D};
 use map_gui::tools::color_for_mode;
 use sim::TripID;
-use synthpop::TripMode;
+use synthpop::{TripMode, TripPurpose};
 use widgetry::tools::PopupMsg;
 use widgetry::{
     Choice, Color, CompareTimes, DrawWithTooltips, EventCtx, GeomBatch, GfxCtx, Line, Outcome,
     Panel, State, Text, TextExt, Toggle, Widget,
 };
 
-use super::trip_problems::{problem_matrix, ProblemType, TripProblemFilter};
+use super::trip_problems::{likely_purpose, problem_matrix, ProblemType, TripProblemFilter};
 use crate::app::{App, Transition};
 use crate::sandbox::dashboards::generic_trip_table::open_trip_transition;
 use crate::sandbox::dashboards::DashTab;
@@ -41,6 +41,18 @@ impl TravelTimes {
                 filter.modes.contains(&mode),
             ));
         }
+        filters.push(Widget::dropdown(
+            ctx,
+            "purpose",
+            filter.purpose,
+            std::iter::once(Choice::new("any purpose", None))
+                .chain(
+                    TripPurpose::all()
+                        .into_iter()
+                        .map(|p| Choice::new(p.to_string(), Some(p))),
+                )
+                .collect(),
+        ));
 
         // TODO We can make file downloads of dynamically generated data work on the browser too...
         if cfg!(not(target_arch = "wasm32")) {
@@ -145,6 +157,7 @@ impl State<App> for TravelTimes {
                 let mut filter = Filter {
                     changes_pct: self.panel.dropdown_value("filter"),
                     modes: BTreeSet::new(),
+                    purpose: self.panel.dropdown_value("purpose"),
                     include_no_changes: self.panel.is_checked("include trips without any changes"),
                 };
                 for m in TripMode::all() {
@@ -172,13 +185,13 @@ fn summary_boxes(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
     let mut num_slower = 0;
     let mut sum_faster = Duration::ZERO;
     let mut sum_slower = Duration::ZERO;
-    for (_, b, a, mode) in app
+    for (id, b, a, mode) in app
         .primary
         .sim
         .get_analytics()
         .both_finished_trips(app.primary.sim.time(), app.prebaked())
     {
-        if !filter.modes.contains(&mode) {
+        if !filter.modes.contains(&mode) || !filter.includes_purpose(likely_purpose(app, id)) {
             continue;
         }
         let same = if let Some(pct) = filter.changes_pct {
@@ -588,6 +601,7 @@ fn contingency_table(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
 pub struct Filter {
     changes_pct: Option<f64>,
     modes: BTreeSet<TripMode>,
+    purpose: Option<TripPurpose>,
     include_no_changes: bool,
 }
 
@@ -599,6 +613,10 @@ impl TripProblemFilter for Filter {
     fn include_no_changes(&self) -> bool {
         self.include_no_changes
     }
+
+    fn includes_purpose(&self, purpose: Option<TripPurpose>) -> bool {
+        self.purpose.is_none() || self.purpose == purpose
+    }
 }
 
 impl Filter {
@@ -606,19 +624,21 @@ impl Filter {
         Filter {
             changes_pct: None,
             modes: TripMode::all().into_iter().collect(),
+            purpose: None,
             include_no_changes: false,
         }
     }
 
     fn get_trips(&self, app: &App) -> Vec<(Duration, Duration)> {
         let mut points = Vec::new();
-        for (_, b, a, mode) in app
+        for (id, b, a, mode) in app
             .primary
             .sim
             .get_analytics()
             .both_finished_trips(app.primary.sim.time(), app.prebaked())
         {
             if self.modes.contains(&mode)
+                && self.includes_purpose(likely_purpose(app, id))
                 && self
                     .changes_pct
                     .map(|pct| pct_diff(a, b) > pct)
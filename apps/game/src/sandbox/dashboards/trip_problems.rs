@@ -5,7 +5,7 @@ use abstutil::{abbreviated_format, prettyprint_usize, CloneableAny};
 use geom::{Angle, Distance, Duration, Line, Polygon, Pt2D, Time};
 use map_gui::tools::ColorScale;
 use sim::{Problem, TripID};
-use synthpop::TripMode;
+use synthpop::{TripMode, TripPurpose};
 use widgetry::{
     ClickOutcome, Color, DrawWithTooltips, GeomBatch, GeomBatchStack, StackAlignment, Text, Widget,
 };
@@ -55,6 +55,9 @@ impl ProblemType {
 pub trait TripProblemFilter {
     fn includes_mode(&self, mode: &TripMode) -> bool;
     fn include_no_changes(&self) -> bool;
+    fn includes_purpose(&self, _: Option<TripPurpose>) -> bool {
+        true
+    }
 
     // Returns:
     // 1) trip ID
@@ -72,7 +75,7 @@ pub trait TripProblemFilter {
 
         let mut points = Vec::new();
         for (id, _, time_after, mode) in after.both_finished_trips(app.primary.sim.time(), before) {
-            if self.includes_mode(&mode) {
+            if self.includes_mode(&mode) && self.includes_purpose(likely_purpose(app, id)) {
                 let count_before = problem_type
                     .count(before.problems_per_trip.get(&id).unwrap_or(&empty))
                     as isize;
@@ -92,8 +95,8 @@ pub trait TripProblemFilter {
         let after = app.primary.sim.get_analytics();
 
         let mut count = 0;
-        for (_, _, _, mode) in after.both_finished_trips(app.primary.sim.time(), before) {
-            if self.includes_mode(&mode) {
+        for (id, _, _, mode) in after.both_finished_trips(app.primary.sim.time(), before) {
+            if self.includes_mode(&mode) && self.includes_purpose(likely_purpose(app, id)) {
                 count += 1;
             }
         }
@@ -101,6 +104,12 @@ pub trait TripProblemFilter {
     }
 }
 
+/// Guess why somebody took a trip. See `TripPurpose::infer`.
+pub fn likely_purpose(app: &App, id: TripID) -> Option<TripPurpose> {
+    let trip = app.primary.sim.trip_info(id);
+    TripPurpose::infer(trip.departure, &trip.start, &trip.end, &app.primary.map)
+}
+
 lazy_static::lazy_static! {
     static ref CLEAR_COLOR_SCALE: ColorScale = ColorScale(vec![Color::CLEAR, Color::CLEAR]);
 }
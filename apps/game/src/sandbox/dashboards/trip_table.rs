@@ -4,11 +4,11 @@ use abstutil::prettyprint_usize;
 use geom::{Duration, Polygon, Time};
 use map_gui::tools::{checkbox_per_mode, color_for_mode};
 use sim::TripID;
-use synthpop::{TripEndpoint, TripMode};
+use synthpop::{TripEndpoint, TripMode, TripPurpose};
 use widgetry::table::{Col, Filter, Table};
 use widgetry::{
-    Color, EventCtx, Filler, GeomBatch, GfxCtx, Line, Outcome, Panel, Stash, State, TabController,
-    Text, Toggle, Widget,
+    Choice, Color, EventCtx, Filler, GeomBatch, GfxCtx, Line, Outcome, Panel, Stash, State,
+    TabController, Text, Toggle, Widget,
 };
 
 use super::generic_trip_table::{open_trip_transition, preview_trip};
@@ -213,6 +213,7 @@ impl State<App> for TripTable {
 struct FinishedTrip {
     id: TripID,
     mode: TripMode,
+    purpose: Option<TripPurpose>,
     modified: bool,
     start: TripEndpoint,
     end: TripEndpoint,
@@ -226,6 +227,7 @@ struct FinishedTrip {
 struct CancelledTrip {
     id: TripID,
     mode: TripMode,
+    purpose: Option<TripPurpose>,
     departure: Time,
     start: TripEndpoint,
     end: TripEndpoint,
@@ -236,6 +238,7 @@ struct CancelledTrip {
 struct UnfinishedTrip {
     id: TripID,
     mode: TripMode,
+    purpose: Option<TripPurpose>,
     departure: Time,
     duration_before: Duration,
     // TODO Estimated wait time?
@@ -243,6 +246,8 @@ struct UnfinishedTrip {
 
 struct Filters {
     modes: BTreeSet<TripMode>,
+    /// Only show trips with this likely purpose
+    purpose: Option<TripPurpose>,
     off_map_starts: bool,
     off_map_ends: bool,
     starts_in: Option<Polygon>,
@@ -271,6 +276,7 @@ fn produce_raw_data(app: &App) -> (Vec<FinishedTrip>, Vec<CancelledTrip>) {
     let sim = &app.primary.sim;
     for (_, id, mode, maybe_duration_after) in &sim.get_analytics().finished_trips {
         let trip = sim.trip_info(*id);
+        let purpose = TripPurpose::infer(trip.departure, &trip.start, &trip.end, &app.primary.map);
         let duration_before = if let Some(ref times) = trip_times_before {
             times.get(id).cloned()
         } else {
@@ -284,6 +290,7 @@ fn produce_raw_data(app: &App) -> (Vec<FinishedTrip>, Vec<CancelledTrip>) {
             cancelled.push(CancelledTrip {
                 id: *id,
                 mode: *mode,
+                purpose,
                 departure: trip.departure,
                 start: trip.start,
                 end: trip.end,
@@ -299,6 +306,7 @@ fn produce_raw_data(app: &App) -> (Vec<FinishedTrip>, Vec<CancelledTrip>) {
         finished.push(FinishedTrip {
             id: *id,
             mode: *mode,
+            purpose,
             departure: trip.departure,
             modified: trip.modified,
             start: trip.start,
@@ -318,6 +326,7 @@ fn make_table_finished_trips(app: &App) -> Table<App, FinishedTrip, Filters> {
     let filter: Filter<App, FinishedTrip, Filters> = Filter {
         state: Filters {
             modes: TripMode::all().into_iter().collect(),
+            purpose: None,
             off_map_starts: true,
             off_map_ends: true,
             starts_in: None,
@@ -329,6 +338,7 @@ fn make_table_finished_trips(app: &App) -> Table<App, FinishedTrip, Filters> {
             Widget::col(vec![
                 checkbox_per_mode(ctx, app, &state.modes),
                 Widget::row(vec![
+                    purpose_dropdown(ctx, state.purpose),
                     Toggle::switch(ctx, "starting off-map", None, state.off_map_starts),
                     Toggle::switch(ctx, "ending off-map", None, state.off_map_ends),
                     ctx.style().btn_plain.text("filter starts").build_def(ctx),
@@ -367,6 +377,7 @@ fn make_table_finished_trips(app: &App) -> Table<App, FinishedTrip, Filters> {
             }
             Filters {
                 modes,
+                purpose: panel.dropdown_value("purpose"),
                 off_map_starts: panel.is_checked("starting off-map"),
                 off_map_ends: panel.is_checked("ending off-map"),
                 starts_in: panel.clone_stashed("starts_in"),
@@ -383,6 +394,9 @@ fn make_table_finished_trips(app: &App) -> Table<App, FinishedTrip, Filters> {
             if !state.modes.contains(&x.mode) {
                 return false;
             }
+            if state.purpose.is_some() && x.purpose != state.purpose {
+                return false;
+            }
             if !state.off_map_starts && matches!(x.start, TripEndpoint::Border(_)) {
                 return false;
             }
@@ -436,6 +450,7 @@ fn make_table_finished_trips(app: &App) -> Table<App, FinishedTrip, Filters> {
         }),
         Col::Static,
     );
+    table.static_col("Likely purpose", Box::new(|x| describe_purpose(x.purpose)));
     table.column(
         "Departure",
         Box::new(|ctx, _, x| Text::from(x.departure.ampm_tostring()).render(ctx)),
@@ -508,6 +523,7 @@ fn make_table_cancelled_trips(app: &App) -> Table<App, CancelledTrip, Filters> {
     let filter: Filter<App, CancelledTrip, Filters> = Filter {
         state: Filters {
             modes: TripMode::all().into_iter().collect(),
+            purpose: None,
             off_map_starts: true,
             off_map_ends: true,
             starts_in: None,
@@ -519,6 +535,7 @@ fn make_table_cancelled_trips(app: &App) -> Table<App, CancelledTrip, Filters> {
             Widget::col(vec![
                 checkbox_per_mode(ctx, app, &state.modes),
                 Widget::row(vec![
+                    purpose_dropdown(ctx, state.purpose),
                     Toggle::switch(ctx, "starting off-map", None, state.off_map_starts),
                     Toggle::switch(ctx, "ending off-map", None, state.off_map_ends),
                 ]),
@@ -533,6 +550,7 @@ fn make_table_cancelled_trips(app: &App) -> Table<App, CancelledTrip, Filters> {
             }
             Filters {
                 modes,
+                purpose: panel.dropdown_value("purpose"),
                 off_map_starts: panel.is_checked("starting off-map"),
                 off_map_ends: panel.is_checked("ending off-map"),
                 starts_in: None,
@@ -545,6 +563,9 @@ fn make_table_cancelled_trips(app: &App) -> Table<App, CancelledTrip, Filters> {
             if !state.modes.contains(&x.mode) {
                 return false;
             }
+            if state.purpose.is_some() && x.purpose != state.purpose {
+                return false;
+            }
             if !state.off_map_starts && matches!(x.start, TripEndpoint::Border(_)) {
                 return false;
             }
@@ -580,6 +601,7 @@ fn make_table_cancelled_trips(app: &App) -> Table<App, CancelledTrip, Filters> {
         }),
         Col::Static,
     );
+    table.static_col("Likely purpose", Box::new(|x| describe_purpose(x.purpose)));
     table.column(
         "Departure",
         Box::new(|ctx, _, x| Text::from(x.departure.ampm_tostring()).render(ctx)),
@@ -623,6 +645,12 @@ fn make_table_unfinished_trips(app: &App) -> Table<App, UnfinishedTrip, Filters>
             unfinished.push(UnfinishedTrip {
                 id,
                 mode: trip.mode,
+                purpose: TripPurpose::infer(
+                    trip.departure,
+                    &trip.start,
+                    &trip.end,
+                    &app.primary.map,
+                ),
                 departure: trip.departure,
                 duration_before,
             });
@@ -633,6 +661,7 @@ fn make_table_unfinished_trips(app: &App) -> Table<App, UnfinishedTrip, Filters>
     let filter: Filter<App, UnfinishedTrip, Filters> = Filter {
         state: Filters {
             modes: TripMode::all().into_iter().collect(),
+            purpose: None,
             off_map_starts: true,
             off_map_ends: true,
             starts_in: None,
@@ -640,7 +669,12 @@ fn make_table_unfinished_trips(app: &App) -> Table<App, UnfinishedTrip, Filters>
             unmodified_trips: true,
             modified_trips: true,
         },
-        to_controls: Box::new(move |ctx, app, state| checkbox_per_mode(ctx, app, &state.modes)),
+        to_controls: Box::new(move |ctx, app, state| {
+            Widget::col(vec![
+                checkbox_per_mode(ctx, app, &state.modes),
+                purpose_dropdown(ctx, state.purpose),
+            ])
+        }),
         from_controls: Box::new(|panel| {
             let mut modes = BTreeSet::new();
             for m in TripMode::all() {
@@ -650,6 +684,7 @@ fn make_table_unfinished_trips(app: &App) -> Table<App, UnfinishedTrip, Filters>
             }
             Filters {
                 modes,
+                purpose: panel.dropdown_value("purpose"),
                 off_map_starts: true,
                 off_map_ends: true,
                 starts_in: None,
@@ -662,6 +697,9 @@ fn make_table_unfinished_trips(app: &App) -> Table<App, UnfinishedTrip, Filters>
             if !state.modes.contains(&x.mode) {
                 return false;
             }
+            if state.purpose.is_some() && x.purpose != state.purpose {
+                return false;
+            }
             true
         }),
     };
@@ -681,6 +719,7 @@ fn make_table_unfinished_trips(app: &App) -> Table<App, UnfinishedTrip, Filters>
         }),
         Col::Static,
     );
+    table.static_col("Likely purpose", Box::new(|x| describe_purpose(x.purpose)));
     table.column(
         "Departure",
         Box::new(|ctx, _, x| Text::from(x.departure.ampm_tostring()).render(ctx)),
@@ -698,3 +737,17 @@ fn make_table_unfinished_trips(app: &App) -> Table<App, UnfinishedTrip, Filters>
 
     table
 }
+
+fn purpose_dropdown(ctx: &mut EventCtx, current: Option<TripPurpose>) -> Widget {
+    let mut choices = vec![Choice::new("any purpose", None)];
+    for p in TripPurpose::all() {
+        choices.push(Choice::new(p.to_string(), Some(p)));
+    }
+    Widget::dropdown(ctx, "purpose", current, choices)
+}
+
+fn describe_purpose(purpose: Option<TripPurpose>) -> String {
+    purpose
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
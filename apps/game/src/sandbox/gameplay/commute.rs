@@ -55,7 +55,7 @@ impl OptimizeCommute {
         })
     }
 
-    pub fn cutscene_pt1(ctx: &mut EventCtx, _: &App, mode: &GameplayMode) -> Box<dyn State<App>> {
+    pub fn cutscene_pt1(ctx: &mut EventCtx, app: &App, mode: &GameplayMode) -> Box<dyn State<App>> {
         CutsceneBuilder::new("Optimize one commute: part 1")
             .boss("Listen up, I've got a special job for you today.")
             .player("What is it? The scooter coalition back with demands for more valet parking?")
@@ -74,10 +74,10 @@ impl OptimizeCommute {
                 "(Somebody's blackmailing the boss. Guess it's time to help this Very Impatient \
                  Person.)",
             )
-            .build(ctx, cutscene_task(mode))
+            .build(ctx, app, cutscene_task(mode))
     }
 
-    pub fn cutscene_pt2(ctx: &mut EventCtx, _: &App, mode: &GameplayMode) -> Box<dyn State<App>> {
+    pub fn cutscene_pt2(ctx: &mut EventCtx, app: &App, mode: &GameplayMode) -> Box<dyn State<App>> {
         // TODO The person chosen for this currently has more of an issue needing PBLs, actually.
         CutsceneBuilder::new("Optimize one commute: part 2")
             .boss("I've got another, er, friend who's sick of this parking situation.")
@@ -95,7 +95,7 @@ impl OptimizeCommute {
             )
             .boss("Everyone's calling in favors these days. Just make it happen!")
             .player("(Too many people have dirt on the boss. Guess we have another VIP to help.)")
-            .build(ctx, cutscene_task(mode))
+            .build(ctx, app, cutscene_task(mode))
     }
 }
 
@@ -36,7 +36,7 @@ impl FixTrafficSignals {
         })
     }
 
-    pub fn cutscene_pt1(ctx: &mut EventCtx, _: &App, _: &GameplayMode) -> Box<dyn State<App>> {
+    pub fn cutscene_pt1(ctx: &mut EventCtx, app: &App, _: &GameplayMode) -> Box<dyn State<App>> {
         CutsceneBuilder::new("Traffic signal survivor")
             .boss("I hope you've had your coffee. There's a huge mess downtown.")
             .player("Did two buses get tangled together again?")
@@ -77,7 +77,7 @@ impl FixTrafficSignals {
                  the worst problems first.",
             )
             .player("Sigh... it's going to be a long day.")
-            .build(ctx, Box::new(cutscene_pt1_task))
+            .build(ctx, app, Box::new(cutscene_pt1_task))
     }
 }
 
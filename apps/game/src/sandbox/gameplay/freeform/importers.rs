@@ -11,6 +11,7 @@ use widgetry::tools::PopupMsg;
 use widgetry::EventCtx;
 
 use crate::app::Transition;
+use crate::layer::gpx_traces::MatchedTraces;
 use crate::sandbox::gameplay::GameplayMode;
 use crate::sandbox::SandboxMode;
 
@@ -93,6 +94,50 @@ pub fn import_json(ctx: &mut EventCtx) -> Transition {
     ))
 }
 
+/// Turns GPX traces into people biking between the same places, leaving when the traces started.
+/// The traces are also available as a layer afterwards.
+pub fn import_gpx(ctx: &mut EventCtx) -> Transition {
+    Transition::Push(FilePicker::new_state(
+        ctx,
+        None,
+        Box::new(|ctx, app, maybe_path| {
+            if let Ok(Some(path)) = maybe_path {
+                let result = ctx.loading_screen("import GPX traces", |_, timer| {
+                    MatchedTraces::load(&app.primary.map, path, timer)
+                });
+                match result {
+                    Ok(traces) => {
+                        let scenario = traces.to_scenario(&app.primary.map, "gpx_traces");
+                        scenario.save();
+                        app.session
+                            .gpx_traces
+                            .set(app.primary.map.get_name().clone(), traces);
+                        // Clear out the cached scenario. If we repeatedly use this import, the
+                        // scenario name is always the same, but the file is changing.
+                        app.primary.scenario = None;
+                        Transition::Replace(SandboxMode::simple_new(
+                            app,
+                            GameplayMode::PlayScenario(
+                                app.primary.map.get_name().clone(),
+                                scenario.scenario_name,
+                                Vec::new(),
+                            ),
+                        ))
+                    }
+                    Err(err) => Transition::Replace(PopupMsg::new_state(
+                        ctx,
+                        "Error",
+                        vec![err.to_string()],
+                    )),
+                }
+            } else {
+                // The user didn't pick a file, so stay on the scenario picker
+                Transition::Pop
+            }
+        }),
+    ))
+}
+
 // This works the same as importer/src/bin/import_traffic.rs. We should decide how to share
 // behavior between UI and CLI tools.
 fn import_json_scenario(map: &Map, input: String, timer: &mut Timer) -> Result<String> {
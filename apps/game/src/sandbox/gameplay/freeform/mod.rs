@@ -39,6 +39,29 @@ impl Freeform {
     }
 }
 
+/// Picks another map, then restarts freeform mode there.
+pub fn change_map(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+    CityPicker::new_state(
+        ctx,
+        app,
+        Box::new(|_, app| {
+            let sandbox = if app.opts.dev {
+                SandboxMode::async_new(
+                    app,
+                    GameplayMode::Freeform(app.primary.map.get_name().clone()),
+                    jump_to_time_upon_startup(Duration::hours(6)),
+                )
+            } else {
+                SandboxMode::simple_new(
+                    app,
+                    GameplayMode::Freeform(app.primary.map.get_name().clone()),
+                )
+            };
+            Transition::Multi(vec![Transition::Pop, Transition::Replace(sandbox)])
+        }),
+    )
+}
+
 impl GameplayState for Freeform {
     fn event(
         &mut self,
@@ -49,25 +72,7 @@ impl GameplayState for Freeform {
     ) -> Option<Transition> {
         match self.top_right.event(ctx) {
             Outcome::Clicked(x) => match x.as_ref() {
-                "change map" => Some(Transition::Push(CityPicker::new_state(
-                    ctx,
-                    app,
-                    Box::new(|_, app| {
-                        let sandbox = if app.opts.dev {
-                            SandboxMode::async_new(
-                                app,
-                                GameplayMode::Freeform(app.primary.map.get_name().clone()),
-                                jump_to_time_upon_startup(Duration::hours(6)),
-                            )
-                        } else {
-                            SandboxMode::simple_new(
-                                app,
-                                GameplayMode::Freeform(app.primary.map.get_name().clone()),
-                            )
-                        };
-                        Transition::Multi(vec![Transition::Pop, Transition::Replace(sandbox)])
-                    }),
-                ))),
+                "change map" => Some(Transition::Push(change_map(ctx, app))),
                 "change scenario" => Some(Transition::Push(ChangeScenario::new_state(
                     ctx, app, "none",
                 ))),
@@ -241,6 +246,12 @@ impl ChangeScenario {
                 "import json".to_string(),
                 "import JSON scenario".to_string(),
                 "Select a JSON file specified by https://a-b-street.github.io/docs/tech/dev/formats/scenarios.html"));
+            choices.push((
+                "import gpx".to_string(),
+                "import GPX traces".to_string(),
+                "Select a GPX file of recorded bike rides. Each ride becomes a trip between the \
+                 buildings closest to where it started and ended.",
+            ));
         }
 
         let mut col = vec![
@@ -309,6 +320,17 @@ impl SimpleState<App> for ChangeScenario {
                 let _ = ctx;
                 unreachable!()
             }
+        } else if x == "import gpx" {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                importers::import_gpx(ctx)
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                // Silence compiler warnings
+                let _ = ctx;
+                unreachable!()
+            }
         } else if x == "import json" {
             #[cfg(not(target_arch = "wasm32"))]
             {
@@ -345,6 +367,7 @@ impl SimpleState<App> for ChangeScenario {
 }
 
 pub fn spawn_agents_around(i: IntersectionID, app: &mut App) {
+    app.primary.stop_background_sim();
     let map = &app.primary.map;
     let mut rng = app.primary.current_flags.sim_flags.make_rng();
     let mut scenario = Scenario::empty(map, "one-shot");
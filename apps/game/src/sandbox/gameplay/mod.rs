@@ -192,6 +192,11 @@ impl GameplayMode {
                     _ => {}
                 },
                 EditCmd::ChangeRouteSchedule { .. } => {}
+                EditCmd::ChangeCornerRadius { .. } | EditCmd::ChangeBikeProtection { .. } => {
+                    if !self.can_edit_roads() {
+                        return false;
+                    }
+                }
             }
         }
         true
@@ -3,14 +3,16 @@ use std::collections::{BTreeMap, BTreeSet};
 use maplit::btreeset;
 
 use abstutil::prettyprint_usize;
-use geom::{Duration, Time};
+use geom::{Duration, Polygon, Time};
 use map_gui::tools::{checkbox_per_mode, grey_out_map, ChooseSomething, CityPicker};
+use map_gui::ID;
+use map_model::BuildingID;
 use sim::SlidingWindow;
-use synthpop::{ScenarioModifier, TripMode};
-use widgetry::tools::{PopupMsg, URLManager};
+use synthpop::{Evacuation, EventSurge, ModeChoice, ScenarioModifier, SurgeCurve, TripMode};
+use widgetry::tools::{Lasso, PopupMsg, URLManager};
 use widgetry::{
     lctrl, Choice, Color, EventCtx, GfxCtx, HorizontalAlignment, Key, Line, LinePlot, Outcome,
-    Panel, PlotOptions, Series, SimpleState, Slider, Spinner, State, Text, TextExt,
+    Panel, PlotOptions, Series, SimpleState, Slider, Spinner, State, Text, TextExt, Toggle,
     VerticalAlignment, Widget,
 };
 
@@ -265,6 +267,55 @@ impl EditScenarioModifiers {
                 .text("Repeat schedule multiple days")
                 .build_def(ctx),
         ]));
+        rows.push(Widget::row(vec![
+            Spinner::widget(ctx, "cordon_pct_ppl", (1, 100), 20_usize, 1),
+            ctx.style()
+                .btn_outline
+                .text("Shift drivers away from charging cordons")
+                .build_def(ctx),
+        ]));
+        rows.push(Widget::row(vec![
+            Spinner::widget(ctx, "park_and_ride_pct_ppl", (1, 100), 20_usize, 1),
+            ctx.style()
+                .btn_outline
+                .text("Switch drivers to park-and-ride")
+                .build_def(ctx),
+        ]));
+        rows.push(Widget::row(vec![
+            Spinner::widget(ctx, "mode_choice_pct_ppl", (1, 100), 100_usize, 1),
+            ctx.style()
+                .btn_outline
+                .text("Let people choose their mode")
+                .build_def(ctx),
+        ]));
+        rows.push(Widget::row(vec![
+            Spinner::widget(ctx, "bike_share_pct_ppl", (1, 100), 20_usize, 1),
+            ctx.style()
+                .btn_outline
+                .text("Switch some trips to bike share")
+                .build_def(ctx),
+        ]));
+        rows.push(Widget::row(vec![
+            Spinner::widget(ctx, "students_per_school", (10, 1000), 200_usize, 10),
+            "students arrive by".text_widget(ctx).centered_vert(),
+            Spinner::widget(ctx, "bell_hour", (6, 10), 9_usize, 1),
+            ctx.style()
+                .btn_outline
+                .text("Add school runs")
+                .build_def(ctx),
+        ]));
+        rows.push(
+            ctx.style()
+                .btn_outline
+                .text("Add an event surge")
+                .build_def(ctx),
+        );
+        rows.push(
+            ctx.style()
+                .btn_outline
+                .text("Evacuate an area")
+                .build_def(ctx),
+        );
         rows.push(Widget::horiz_separator(ctx, 1.0));
         rows.push(
             Widget::row(vec![
@@ -361,6 +412,74 @@ impl State<App> for EditScenarioModifiers {
                         self.modifiers.clone(),
                     ));
                 }
+                "Shift drivers away from charging cordons" => {
+                    self.modifiers
+                        .push(ScenarioModifier::RespondToCordonCharges {
+                            pct_ppl: self.panel.spinner("cordon_pct_ppl"),
+                        });
+                    return Transition::Replace(EditScenarioModifiers::new_state(
+                        ctx,
+                        self.scenario_name.clone(),
+                        self.modifiers.clone(),
+                    ));
+                }
+                "Switch drivers to park-and-ride" => {
+                    self.modifiers.push(ScenarioModifier::ParkAndRide {
+                        pct_ppl: self.panel.spinner("park_and_ride_pct_ppl"),
+                    });
+                    return Transition::Replace(EditScenarioModifiers::new_state(
+                        ctx,
+                        self.scenario_name.clone(),
+                        self.modifiers.clone(),
+                    ));
+                }
+                "Let people choose their mode" => {
+                    self.modifiers
+                        .push(ScenarioModifier::ChooseModes(ModeChoice::new(
+                            self.panel.spinner("mode_choice_pct_ppl"),
+                        )));
+                    return Transition::Replace(EditScenarioModifiers::new_state(
+                        ctx,
+                        self.scenario_name.clone(),
+                        self.modifiers.clone(),
+                    ));
+                }
+                "Switch some trips to bike share" => {
+                    self.modifiers.push(ScenarioModifier::UseBikeShare {
+                        pct_ppl: self.panel.spinner("bike_share_pct_ppl"),
+                    });
+                    return Transition::Replace(EditScenarioModifiers::new_state(
+                        ctx,
+                        self.scenario_name.clone(),
+                        self.modifiers.clone(),
+                    ));
+                }
+                "Add school runs" => {
+                    let bell_hour: usize = self.panel.spinner("bell_hour");
+                    self.modifiers.push(ScenarioModifier::AddSchoolRuns {
+                        bell_time: Time::START_OF_DAY + Duration::hours(bell_hour),
+                        students_per_school: self.panel.spinner("students_per_school"),
+                    });
+                    return Transition::Replace(EditScenarioModifiers::new_state(
+                        ctx,
+                        self.scenario_name.clone(),
+                        self.modifiers.clone(),
+                    ));
+                }
+                "Add an event surge" => {
+                    return Transition::Push(EventSurgeEditor::new_state(
+                        ctx,
+                        self.scenario_name.clone(),
+                        self.modifiers.clone(),
+                    ));
+                }
+                "Evacuate an area" => {
+                    return Transition::Push(DrawHazardZone::new_state(
+                        ctx,
+                        self.scenario_name.clone(),
+                        self.modifiers.clone(),
+                    ));
+                }
                 x => {
                     if let Some(x) = x.strip_prefix("delete modifier ") {
                         self.modifiers.remove(x.parse::<usize>().unwrap() - 1);
@@ -552,6 +671,435 @@ impl State<App> for ChangeMode {
     }
 }
 
+/// Define a big event at one building, like a game at a stadium. The venue is picked from the map,
+/// so unlike the other modifiers, this doesn't grey out the map.
+struct EventSurgeEditor {
+    panel: Panel,
+    scenario_name: String,
+    modifiers: Vec<ScenarioModifier>,
+    venue: Option<BuildingID>,
+    hovering: Option<BuildingID>,
+}
+
+impl EventSurgeEditor {
+    fn new_state(
+        ctx: &mut EventCtx,
+        scenario_name: String,
+        modifiers: Vec<ScenarioModifier>,
+    ) -> Box<dyn State<App>> {
+        let curve_choices = || {
+            vec![
+                Choice::new("evenly", SurgeCurve::Even),
+                Choice::new("mostly near the event", SurgeCurve::Peaked),
+            ]
+        };
+        let mut mode_split = Vec::new();
+        for (mode, pct) in [
+            (TripMode::Drive, 50_usize),
+            (TripMode::Transit, 30),
+            (TripMode::Walk, 15),
+            (TripMode::Bike, 5),
+        ] {
+            mode_split.push(Widget::row(vec![
+                format!("% {}", mode.ongoing_verb())
+                    .text_widget(ctx)
+                    .centered_vert(),
+                Spinner::widget(
+                    ctx,
+                    format!("pct {}", mode.ongoing_verb()),
+                    (0, 100),
+                    pct,
+                    5,
+                ),
+            ]));
+        }
+
+        Box::new(EventSurgeEditor {
+            scenario_name,
+            modifiers,
+            venue: None,
+            hovering: None,
+            panel: Panel::new_builder(Widget::col(vec![
+                Line("Add an event surge").small_heading().into_widget(ctx),
+                "Click a building to use as the venue"
+                    .text_widget(ctx)
+                    .named("venue"),
+                Widget::row(vec![
+                    "Attendance:".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "attendance", (100, 100_000), 20_000_usize, 500),
+                ]),
+                Widget::row(vec![
+                    "Starts at hour:".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "start hour", (1, 22), 19_usize, 1),
+                    "and lasts hours:".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "duration hours", (1, 8), 3_usize, 1),
+                ]),
+                Widget::row(vec![
+                    "Arrive during the minutes before:"
+                        .text_widget(ctx)
+                        .centered_vert(),
+                    Spinner::widget(ctx, "arrival minutes", (15, 240), 90_usize, 15),
+                    Widget::dropdown(ctx, "arrival curve", SurgeCurve::Peaked, curve_choices()),
+                ]),
+                Widget::row(vec![
+                    "Leave during the minutes after:"
+                        .text_widget(ctx)
+                        .centered_vert(),
+                    Spinner::widget(ctx, "departure minutes", (15, 240), 45_usize, 15),
+                    Widget::dropdown(ctx, "departure curve", SurgeCurve::Peaked, curve_choices()),
+                ]),
+                "Mode split:".text_widget(ctx),
+                Widget::col(mode_split),
+                Widget::row(vec![
+                    "% of drivers and cyclists from beyond the map:"
+                        .text_widget(ctx)
+                        .centered_vert(),
+                    Spinner::widget(ctx, "pct from outside", (0, 100), 50_usize, 5),
+                ]),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_solid_primary
+                        .text("Apply")
+                        .hotkey(Key::Enter)
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_solid_destructive
+                        .text("Discard changes")
+                        .hotkey(Key::Escape)
+                        .build_def(ctx),
+                ])
+                .centered(),
+            ]))
+            .aligned(HorizontalAlignment::Left, VerticalAlignment::Top)
+            .build(ctx),
+        })
+    }
+
+    fn make_event(&self, venue: BuildingID) -> EventSurge {
+        let start_time = Time::START_OF_DAY + Duration::hours(self.panel.spinner("start hour"));
+        let mut mode_split = BTreeMap::new();
+        for mode in TripMode::all() {
+            mode_split.insert(
+                mode,
+                self.panel.spinner(&format!("pct {}", mode.ongoing_verb())),
+            );
+        }
+        EventSurge {
+            venue,
+            attendance: self.panel.spinner("attendance"),
+            start_time,
+            end_time: start_time + Duration::hours(self.panel.spinner("duration hours")),
+            arrival_window: Duration::minutes(self.panel.spinner("arrival minutes")),
+            departure_window: Duration::minutes(self.panel.spinner("departure minutes")),
+            arrival_curve: self.panel.dropdown_value("arrival curve"),
+            departure_curve: self.panel.dropdown_value("departure curve"),
+            mode_split,
+            pct_from_outside: self.panel.spinner("pct from outside"),
+        }
+    }
+}
+
+impl State<App> for EventSurgeEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+        if ctx.redo_mouseover() {
+            self.hovering = match app.mouseover_unzoomed_buildings(ctx) {
+                Some(ID::Building(b)) => Some(b),
+                _ => None,
+            };
+        }
+        if let Some(b) = self.hovering {
+            if app
+                .per_obj
+                .left_click(ctx, "use this building as the venue")
+            {
+                self.venue = Some(b);
+                let label = format!("Venue: {}", app.primary.map.get_b(b).address)
+                    .text_widget(ctx)
+                    .named("venue");
+                self.panel.replace(ctx, "venue", label);
+            }
+        }
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "Discard changes" => {
+                    return Transition::Pop;
+                }
+                "Apply" => {
+                    let venue = if let Some(b) = self.venue {
+                        b
+                    } else {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec!["Click a building on the map to use as the venue first"],
+                        ));
+                    };
+                    let event = self.make_event(venue);
+                    if event.mode_split.values().all(|pct| *pct == 0) {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec!["At least one mode needs a share of attendees"],
+                        ));
+                    }
+
+                    let mut mods = self.modifiers.clone();
+                    mods.push(ScenarioModifier::AddEventSurge(event));
+                    return Transition::Multi(vec![
+                        Transition::Pop,
+                        Transition::Replace(EditScenarioModifiers::new_state(
+                            ctx,
+                            self.scenario_name.clone(),
+                            mods,
+                        )),
+                    ]);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        if let Some(b) = self.hovering {
+            g.draw_polygon(app.cs.selected, app.primary.map.get_b(b).polygon.clone());
+        }
+        if let Some(b) = self.venue {
+            g.draw_polygon(
+                Color::RED.alpha(0.8),
+                app.primary.map.get_b(b).polygon.clone(),
+            );
+        }
+        self.panel.draw(g);
+    }
+}
+
+struct DrawHazardZone {
+    panel: Panel,
+    lasso: Lasso,
+    scenario_name: String,
+    modifiers: Vec<ScenarioModifier>,
+}
+
+impl DrawHazardZone {
+    fn new_state(
+        ctx: &mut EventCtx,
+        scenario_name: String,
+        modifiers: Vec<ScenarioModifier>,
+    ) -> Box<dyn State<App>> {
+        Box::new(DrawHazardZone {
+            panel: Panel::new_builder(Widget::col(vec![
+                Line("Evacuate an area").small_heading().into_widget(ctx),
+                "Draw the hazard zone on the map".text_widget(ctx),
+                ctx.style()
+                    .btn_solid_destructive
+                    .text("Discard changes")
+                    .hotkey(Key::Escape)
+                    .build_def(ctx),
+            ]))
+            .aligned(HorizontalAlignment::Left, VerticalAlignment::Top)
+            .build(ctx),
+            lasso: Lasso::new(),
+            scenario_name,
+            modifiers,
+        })
+    }
+}
+
+impl State<App> for DrawHazardZone {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "Discard changes" => {
+                    return Transition::Pop;
+                }
+                _ => unreachable!(),
+            }
+        }
+        if let Some(zone) = self.lasso.event(ctx) {
+            return Transition::Replace(EvacuationEditor::new_state(
+                ctx,
+                app,
+                self.scenario_name.clone(),
+                self.modifiers.clone(),
+                zone,
+            ));
+        }
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.lasso.draw(g);
+        self.panel.draw(g);
+    }
+}
+
+/// Configure an evacuation of everybody inside a hazard zone. Clicking buildings outside the zone
+/// marks them as shelters.
+struct EvacuationEditor {
+    panel: Panel,
+    scenario_name: String,
+    modifiers: Vec<ScenarioModifier>,
+    zone_polygon: Polygon,
+    zone: BTreeSet<BuildingID>,
+    shelters: BTreeSet<BuildingID>,
+    hovering: Option<BuildingID>,
+}
+
+impl EvacuationEditor {
+    fn new_state(
+        ctx: &mut EventCtx,
+        app: &App,
+        scenario_name: String,
+        modifiers: Vec<ScenarioModifier>,
+        zone_polygon: Polygon,
+    ) -> Box<dyn State<App>> {
+        let zone: BTreeSet<BuildingID> = app
+            .primary
+            .map
+            .all_buildings()
+            .iter()
+            .filter(|b| zone_polygon.contains_pt(b.polygon.center()))
+            .map(|b| b.id)
+            .collect();
+
+        Box::new(EvacuationEditor {
+            panel: Panel::new_builder(Widget::col(vec![
+                Line("Evacuate an area").small_heading().into_widget(ctx),
+                format!(
+                    "{} buildings in the hazard zone",
+                    prettyprint_usize(zone.len())
+                )
+                .text_widget(ctx),
+                "Click buildings outside the zone to use as shelters"
+                    .text_widget(ctx)
+                    .named("shelters"),
+                Toggle::checkbox(ctx, "Evacuees may leave the map", None, true),
+                Widget::row(vec![
+                    "Order given at".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "order hour", (0, 23), 8_usize, 1),
+                    ":".text_widget(ctx).centered_vert(),
+                    Spinner::widget(ctx, "order minute", (0, 55), 0_usize, 5),
+                ]),
+                Widget::row(vec![
+                    "Everybody leaves within minutes:"
+                        .text_widget(ctx)
+                        .centered_vert(),
+                    Spinner::widget(ctx, "departure minutes", (5, 240), 60_usize, 5),
+                ]),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_solid_primary
+                        .text("Apply")
+                        .hotkey(Key::Enter)
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_solid_destructive
+                        .text("Discard changes")
+                        .hotkey(Key::Escape)
+                        .build_def(ctx),
+                ])
+                .centered(),
+            ]))
+            .aligned(HorizontalAlignment::Left, VerticalAlignment::Top)
+            .build(ctx),
+            scenario_name,
+            modifiers,
+            zone_polygon,
+            zone,
+            shelters: BTreeSet::new(),
+            hovering: None,
+        })
+    }
+}
+
+impl State<App> for EvacuationEditor {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+        if ctx.redo_mouseover() {
+            self.hovering = match app.mouseover_unzoomed_buildings(ctx) {
+                Some(ID::Building(b)) if !self.zone.contains(&b) => Some(b),
+                _ => None,
+            };
+        }
+        if let Some(b) = self.hovering {
+            let verb = if self.shelters.contains(&b) {
+                "stop using this building as a shelter"
+            } else {
+                "use this building as a shelter"
+            };
+            if app.per_obj.left_click(ctx, verb) {
+                if !self.shelters.remove(&b) {
+                    self.shelters.insert(b);
+                }
+                let label = format!("{} shelters", self.shelters.len())
+                    .text_widget(ctx)
+                    .named("shelters");
+                self.panel.replace(ctx, "shelters", label);
+            }
+        }
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "Discard changes" => {
+                    return Transition::Pop;
+                }
+                "Apply" => {
+                    let leave_map = self.panel.is_checked("Evacuees may leave the map");
+                    if self.shelters.is_empty() && !leave_map {
+                        return Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec!["Evacuees need somewhere to go. Pick shelters or let them leave                                   the map."],
+                        ));
+                    }
+                    let hour: usize = self.panel.spinner("order hour");
+                    let minute: usize = self.panel.spinner("order minute");
+                    let evacuation = Evacuation {
+                        zone: self.zone.clone(),
+                        shelters: self.shelters.clone(),
+                        leave_map,
+                        start_time: Time::START_OF_DAY
+                            + Duration::hours(hour)
+                            + Duration::minutes(minute),
+                        departure_window: Duration::minutes(
+                            self.panel.spinner("departure minutes"),
+                        ),
+                    };
+
+                    let mut mods = self.modifiers.clone();
+                    mods.push(ScenarioModifier::Evacuate(evacuation));
+                    return Transition::Multi(vec![
+                        Transition::Pop,
+                        Transition::Replace(EditScenarioModifiers::new_state(
+                            ctx,
+                            self.scenario_name.clone(),
+                            mods,
+                        )),
+                    ]);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        g.draw_polygon(Color::RED.alpha(0.3), self.zone_polygon.clone());
+        for b in &self.shelters {
+            g.draw_polygon(Color::GREEN, app.primary.map.get_b(*b).polygon.clone());
+        }
+        if let Some(b) = self.hovering {
+            g.draw_polygon(app.cs.selected, app.primary.map.get_b(b).polygon.clone());
+        }
+        self.panel.draw(g);
+    }
+}
+
 pub struct DepartureSummary {
     first_trip: Time,
 }
@@ -2,9 +2,9 @@ use std::collections::BTreeSet;
 
 use abstio::MapName;
 use abstutil::Timer;
-use geom::{ArrowCap, Distance, Duration, PolyLine, Pt2D, Time};
+use geom::{Duration, Time};
 use map_gui::load::MapLoader;
-use map_gui::tools::Minimap;
+use map_gui::tools::{MessageAction, Minimap, TutorialMessage};
 use map_gui::ID;
 use map_model::raw::OriginalRoad;
 use map_model::{osm, BuildingID, Map, Position};
@@ -12,8 +12,8 @@ use sim::{AgentID, BorderSpawnOverTime, CarID, ScenarioGenerator, SpawnOverTime,
 use synthpop::{IndividTrip, PersonSpec, Scenario, TripEndpoint, TripMode, TripPurpose};
 use widgetry::tools::PopupMsg;
 use widgetry::{
-    hotkeys, lctrl, Color, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Image, Key, Line,
-    Outcome, Panel, ScreenPt, State, Text, TextExt, VerticalAlignment, Widget,
+    lctrl, Color, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line, Outcome, Panel,
+    State, Text, VerticalAlignment, Widget,
 };
 
 use crate::app::{App, Transition};
@@ -75,7 +75,7 @@ impl Tutorial {
                                 .unwrap_or_else(|| TutorialPointer::new(0, 0)),
                         ),
                     )),
-                    Transition::Push(intro_story(ctx)),
+                    Transition::Push(intro_story(ctx, app)),
                 ])
             }),
         )
@@ -157,19 +157,16 @@ impl Tutorial {
         }
 
         if let Some(ref mut msg) = self.msg_panel {
-            match msg.event(ctx) {
-                Outcome::Clicked(x) => match x.as_ref() {
-                    "previous message" => {
-                        tut.prev();
-                        return Some(transition(app, tut));
-                    }
-                    "next message" | "Try it" => {
-                        tut.next();
-                        return Some(transition(app, tut));
-                    }
-                    _ => unreachable!(),
-                },
-                _ => {
+            match MessageAction::event(ctx, msg) {
+                MessageAction::Previous => {
+                    tut.prev();
+                    return Some(transition(app, tut));
+                }
+                MessageAction::Next => {
+                    tut.next();
+                    return Some(transition(app, tut));
+                }
+                MessageAction::Nothing => {
                     // Don't allow other interactions
                     return Some(Transition::Keep);
                 }
@@ -381,30 +378,8 @@ impl GameplayState for Tutorial {
 
         self.top_right.draw(g);
 
-        if let Some(ref msg) = self.msg_panel {
-            // Arrows underneath the message panel, but on top of other panels
-            if let Some(msg) = tut.message() {
-                if let Some(ref fxn) = msg.arrow {
-                    let pt = (fxn)(g, app);
-                    g.fork_screenspace();
-                    if let Ok(pl) = PolyLine::new(vec![
-                        self.msg_panel
-                            .as_ref()
-                            .unwrap()
-                            .center_of("next message")
-                            .to_pt(),
-                        pt,
-                    ]) {
-                        g.draw_polygon(
-                            Color::RED,
-                            pl.make_arrow(Distance::meters(20.0), ArrowCap::Triangle),
-                        );
-                    }
-                    g.unfork();
-                }
-            }
-
-            msg.draw(g);
+        if let (Some(ref panel), Some(msg)) = (&self.msg_panel, tut.message()) {
+            msg.draw(g, app, panel);
         }
 
         // Special things
@@ -570,43 +545,7 @@ struct Stage {
     make_scenario: Option<ScenarioGenerator>,
 }
 
-struct Message {
-    txt: Text,
-    aligned: HorizontalAlignment,
-    arrow: Option<Box<dyn Fn(&GfxCtx, &App) -> Pt2D>>,
-    icon: Option<&'static str>,
-}
-
-impl Message {
-    fn new(txt: Text) -> Message {
-        Message {
-            txt,
-            aligned: HorizontalAlignment::Center,
-            arrow: None,
-            icon: None,
-        }
-    }
-
-    fn arrow(mut self, pt: ScreenPt) -> Message {
-        self.arrow = Some(Box::new(move |_, _| pt.to_pt()));
-        self
-    }
-
-    fn dynamic_arrow(mut self, arrow: Box<dyn Fn(&GfxCtx, &App) -> Pt2D>) -> Message {
-        self.arrow = Some(arrow);
-        self
-    }
-
-    fn icon(mut self, path: &'static str) -> Message {
-        self.icon = Some(path);
-        self
-    }
-
-    fn left_aligned(mut self) -> Message {
-        self.aligned = HorizontalAlignment::Left;
-        self
-    }
-}
+type Message = TutorialMessage<App>;
 
 impl Stage {
     fn new(task: Task) -> Stage {
@@ -831,52 +770,14 @@ impl TutorialState {
             top_right: self.make_top_right(ctx, last_finished_task >= Task::WatchBikes),
             last_finished_task,
 
-            msg_panel: if let Some(msg) = self.message() {
-                let mut col = vec![{
-                    let mut txt = Text::new();
-                    txt.add_line(Line(self.stage().task.label()).small_heading());
-                    txt.add_line("");
-                    txt.into_widget(ctx)
-                }];
-                if let Some(icon) = msg.icon {
-                    col.push(Image::from_path(icon).dims(30.0).into_widget(ctx));
-                }
-                col.push(msg.txt.clone().wrap_to_pct(ctx, 30).into_widget(ctx));
-                let mut controls = vec![Widget::row(vec![
-                    ctx.style()
-                        .btn_prev()
-                        .disabled(self.current.part == 0)
-                        .hotkey(Key::LeftArrow)
-                        .build_widget(ctx, "previous message"),
-                    format!("{}/{}", self.current.part + 1, self.stage().messages.len())
-                        .text_widget(ctx)
-                        .centered_vert(),
-                    ctx.style()
-                        .btn_next()
-                        .disabled(self.current.part == self.stage().messages.len() - 1)
-                        .hotkey(Key::RightArrow)
-                        .build_widget(ctx, "next message"),
-                ])];
-                if self.current.part == self.stage().messages.len() - 1 {
-                    controls.push(
-                        ctx.style()
-                            .btn_solid_primary
-                            .text("Try it")
-                            .hotkey(hotkeys(vec![Key::RightArrow, Key::Space, Key::Enter]))
-                            .build_def(ctx),
-                    );
-                }
-                col.push(Widget::col(controls).align_bottom());
-
-                Some(
-                    Panel::new_builder(Widget::col(col).outline((5.0, Color::WHITE)))
-                        .exact_size_percent(40, 40)
-                        .aligned(msg.aligned, VerticalAlignment::Center)
-                        .build(ctx),
+            msg_panel: self.message().map(|msg| {
+                msg.make_panel(
+                    ctx,
+                    self.stage().task.label(),
+                    self.current.part,
+                    self.stage().messages.len(),
                 )
-            } else {
-                None
-            },
+            }),
             warped: false,
         })
     }
@@ -1381,7 +1282,7 @@ pub fn execute(ctx: &mut EventCtx, app: &mut App, id: ID, action: &str) -> Trans
     Transition::Push(response)
 }
 
-fn intro_story(ctx: &mut EventCtx) -> Box<dyn State<App>> {
+fn intro_story(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
     CutsceneBuilder::new("Introduction")
         .boss(
             "Argh, the mayor's on my case again about the West Seattle bridge. This day couldn't \
@@ -1414,6 +1315,7 @@ fn intro_story(ctx: &mut EventCtx) -> Box<dyn State<App>> {
         )
         .build(
             ctx,
+            app,
             Box::new(|ctx| {
                 Text::from(Line("Use the tutorial to learn the basic controls.").fg(Color::BLACK))
                     .into_widget(ctx)
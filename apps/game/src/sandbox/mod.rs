@@ -18,6 +18,8 @@ pub use self::minimap::MinimapController;
 use self::misc_tools::{RoutePreview, TrafficRecorder};
 pub use self::speed::{SpeedSetting, TimePanel};
 pub use self::time_warp::TimeWarpScreen;
+use self::trip_alerts::TripAlerts;
+pub use self::trip_alerts::TripTimeBudgets;
 use crate::app::{App, Transition};
 use crate::common::{tool_panel, CommonState};
 use crate::debug::DebugMode;
@@ -29,12 +31,14 @@ use crate::layer::favorites::{Favorites, ShowFavorites};
 use crate::layer::PickLayer;
 use crate::pregame::TitleScreen;
 
+mod commands;
 pub mod dashboards;
 pub mod gameplay;
 mod minimap;
 mod misc_tools;
 mod speed;
 mod time_warp;
+mod trip_alerts;
 
 pub struct SandboxMode {
     gameplay: Box<dyn gameplay::GameplayState>,
@@ -52,6 +56,7 @@ pub struct SandboxControls {
     tool_panel: Option<Panel>,
     pub time_panel: Option<TimePanel>,
     minimap: Option<Minimap<App, MinimapController>>,
+    trip_alerts: Option<TripAlerts>,
 }
 
 impl SandboxMode {
@@ -70,6 +75,7 @@ impl SandboxMode {
         finalize: Box<dyn FnOnce(&mut EventCtx, &mut App) -> Vec<Transition>>,
     ) -> Box<dyn State<App>> {
         app.primary.clear_sim();
+        app.primary.savestates.clear();
         Box::new(SandboxLoader {
             stage: Some(LoadStage::LoadingMap),
             mode,
@@ -105,10 +111,8 @@ impl SandboxMode {
             gameplay: self.gameplay_mode.clone(),
         }
     }
-}
 
-impl State<App> for SandboxMode {
-    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+    fn handle_event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         if app.opts.toggle_day_night_colors {
             if is_daytime(app) {
                 app.change_color_scheme(ctx, ColorSchemeChoice::DayMode)
@@ -144,6 +148,9 @@ impl State<App> for SandboxMode {
         if app.opts.dev && ctx.input.pressed(lctrl(Key::D)) {
             return Transition::Push(DebugMode::new_state(ctx, app));
         }
+        if ctx.input.pressed(lctrl(Key::P)) {
+            return Transition::Push(commands::command_palette(ctx, app, &self.gameplay_mode));
+        }
 
         if let Some(ref mut m) = self.controls.minimap {
             if let Some(t) = m.event(ctx, app) {
@@ -159,6 +166,19 @@ impl State<App> for SandboxMode {
                 return t;
             }
         }
+        if let Some(ref mut alerts) = self.controls.trip_alerts {
+            if alerts.check_trips(ctx, app) && app.session.trip_time_budgets.pause_on_alert {
+                if let Some(ref mut tp) = self.controls.time_panel {
+                    tp.pause(ctx, app);
+                }
+            }
+            if let Some(t) = alerts.event(ctx, app) {
+                return t;
+            }
+        }
+        // This only snapshots the sim once enough time has passed
+        app.primary.savestates.set_enabled(app.opts.rewind_history);
+        app.primary.savestates.maybe_record(&app.primary.sim);
 
         // We need to recalculate unzoomed agent mouseover when the mouse is still and time passes
         // (since something could move beneath the cursor), or when the mouse moves.
@@ -214,6 +234,17 @@ impl State<App> for SandboxMode {
             Transition::Keep
         }
     }
+}
+
+impl State<App> for SandboxMode {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        let transition = self.handle_event(ctx, app);
+        // Whatever happens next might change the sim, so take it back from the background thread
+        if !matches!(transition, Transition::Keep | Transition::KeepWithMouseover) {
+            app.primary.stop_background_sim();
+        }
+        transition
+    }
 
     fn draw(&self, g: &mut GfxCtx, app: &App) {
         if let Some(ref l) = app.primary.layer {
@@ -239,6 +270,9 @@ impl State<App> for SandboxMode {
         if let Some(ref r) = self.controls.route_preview {
             r.draw(g);
         }
+        if let Some(ref a) = self.controls.trip_alerts {
+            a.draw(g);
+        }
 
         if !app.opts.minimal_controls {
             self.gameplay.draw(g, app);
@@ -551,6 +585,9 @@ impl State<App> for SandboxLoader {
                                 scenario = m.apply(&app.primary.map, scenario);
                             }
                         }
+                        for m in app.primary.current_flags.sim_flags.opts.weather_modifiers() {
+                            scenario = m.apply(&app.primary.map, scenario);
+                        }
 
                         app.primary.sim.instantiate(
                             &scenario,
@@ -619,6 +656,12 @@ impl State<App> for SandboxLoader {
                 LoadStage::Finalizing => {
                     let mut gameplay = self.mode.initialize(ctx, app);
                     gameplay.recreate_panels(ctx, app);
+                    // Challenges and the tutorial control the layers themselves
+                    if let GameplayMode::Freeform(_) | GameplayMode::PlayScenario(_, _, _) =
+                        self.mode
+                    {
+                        crate::layer::restore_last_layer(ctx, app);
+                    }
                     let sandbox = Box::new(SandboxMode {
                         controls: SandboxControls::new(ctx, app, gameplay.as_ref()),
                         gameplay,
@@ -705,6 +748,12 @@ impl SandboxControls {
             } else {
                 None
             },
+            // Only shown once a baseline simulation is available to compare against
+            trip_alerts: if gameplay.has_time_panel() {
+                Some(TripAlerts::new())
+            } else {
+                None
+            },
         }
     }
 
@@ -718,5 +767,8 @@ impl SandboxControls {
         if let Some(ref mut minimap) = self.minimap {
             minimap.recreate_panel(ctx, app);
         }
+        if let Some(ref mut alerts) = self.trip_alerts {
+            alerts.recreate_panel(ctx, app);
+        }
     }
 }
@@ -395,6 +395,7 @@ impl TimePanel {
                     )));
                 }
                 "step forwards" => {
+                    app.primary.stop_background_sim();
                     let dt = self.panel.persistent_split_value("step forwards");
                     if dt == Duration::seconds(0.1) {
                         app.primary
@@ -424,6 +425,7 @@ impl TimePanel {
                     )));
                 }
                 "Finish Capture" => {
+                    app.primary.stop_background_sim();
                     app.primary.sim.save_recorded_traffic(&app.primary.map);
                 }
                 _ => unreachable!(),
@@ -475,6 +477,19 @@ impl TimePanel {
             }
         }
 
+        // Gameplay modes besides these change the sim as it runs, or use a callback, so they
+        // can't step in the background.
+        let background = app.opts.background_sim
+            && !cfg!(target_arch = "wasm32")
+            && app.primary.sim_cb.is_none()
+            && matches!(
+                maybe_mode,
+                Some(GameplayMode::Freeform(_)) | Some(GameplayMode::PlayScenario(_, _, _))
+            );
+        if self.paused || !background {
+            app.primary.stop_background_sim();
+        }
+
         if !self.paused {
             if let Some(real_dt) = ctx.input.nonblocking_is_update_event() {
                 ctx.input.use_update_event();
@@ -484,16 +499,22 @@ impl TimePanel {
                     SpeedSetting::Faster => 30.0,
                     SpeedSetting::Fastest => 3600.0,
                 };
-                let dt = multiplier * real_dt;
-                // TODO This should match the update frequency in widgetry. Plumb along the deadline
-                // or frequency to here.
-                app.primary.sim.time_limited_step(
-                    &app.primary.map,
-                    dt,
-                    Duration::seconds(0.033),
-                    &mut app.primary.sim_cb,
-                );
-                app.recalculate_current_selection(ctx);
+                if background {
+                    if app.primary.step_in_background(multiplier) {
+                        app.recalculate_current_selection(ctx);
+                    }
+                } else {
+                    let dt = multiplier * real_dt;
+                    // TODO This should match the update frequency in widgetry. Plumb along the
+                    // deadline or frequency to here.
+                    app.primary.sim.time_limited_step(
+                        &app.primary.map,
+                        dt,
+                        Duration::seconds(0.033),
+                        &mut app.primary.sim_cb,
+                    );
+                    app.recalculate_current_selection(ctx);
+                }
             }
         }
 
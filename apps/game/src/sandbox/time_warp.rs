@@ -120,6 +120,24 @@ impl State<App> for JumpToTime {
                 }
                 "jump to time" => {
                     if self.target < app.primary.sim.time() {
+                        app.primary.stop_background_sim();
+                        let target = self.target;
+                        let restored = ctx.loading_screen("rewind simulation", |_, _| {
+                            app.primary.savestates.rewind_to(target)
+                        });
+                        match restored {
+                            Some(Ok(sim)) => {
+                                app.primary.sim = sim;
+                                return Transition::Replace(TimeWarpScreen::new_state(
+                                    ctx, app, target, None,
+                                ));
+                            }
+                            Some(Err(err)) => {
+                                warn!("Couldn't restore a savestate, resetting instead: {}", err);
+                                app.primary.savestates.clear();
+                            }
+                            None => {}
+                        }
                         if let Some(mode) = self.maybe_mode.take() {
                             let target_time = self.target;
                             return Transition::Replace(SandboxMode::async_new(
@@ -277,6 +295,8 @@ impl State<App> for TimeWarpScreen {
                 Duration::seconds(0.033),
                 &mut app.primary.sim_cb,
             );
+            app.primary.savestates.set_enabled(app.opts.rewind_history);
+            app.primary.savestates.maybe_record(&app.primary.sim);
             #[allow(clippy::never_loop)]
             for (t, maybe_i, alert) in app.primary.sim.clear_alerts() {
                 // TODO Just the first :(
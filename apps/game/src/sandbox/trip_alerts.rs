@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashMap};
+
+use abstutil::prettyprint_usize;
+use geom::Duration;
+use map_model::MapName;
+use sim::TripID;
+use synthpop::TripMode;
+use widgetry::{
+    DrawBaselayer, EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, RoundedF64,
+    Spinner, State, Text, TextExt, Toggle, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+use crate::info::{OpenTrip, Tab};
+use crate::sandbox::SandboxMode;
+
+/// How many of the most recent alerts to list
+const MAX_SHOWN: usize = 5;
+
+/// Flag trips that take much longer than they did in the baseline simulation, so that long runs
+/// can be supervised live.
+#[derive(Clone, Default)]
+pub struct TripTimeBudgets {
+    /// A trip of this mode breaches its budget when it takes more than this multiple of its
+    /// baseline time. Modes without an entry are never flagged.
+    pub max_ratio: BTreeMap<TripMode, f64>,
+    pub pause_on_alert: bool,
+}
+
+impl TripTimeBudgets {
+    pub fn is_enabled(&self) -> bool {
+        !self.max_ratio.is_empty()
+    }
+}
+
+struct Breach {
+    trip: TripID,
+    mode: TripMode,
+    baseline: Duration,
+    actual: Duration,
+}
+
+/// A live feed of trips that finished over their time budget. Only trips that finish are checked.
+pub struct TripAlerts {
+    panel: Option<Panel>,
+    /// How many finished trips have been checked so far
+    checked: usize,
+    breaches: Vec<Breach>,
+    /// Baseline trip times, built the first time they're needed for each prebaked result
+    baseline: Option<((MapName, String), HashMap<TripID, Duration>)>,
+}
+
+impl TripAlerts {
+    pub fn new() -> TripAlerts {
+        TripAlerts {
+            panel: None,
+            checked: 0,
+            breaches: Vec::new(),
+            baseline: None,
+        }
+    }
+
+    /// Check trips that finished since last time. Returns true if any new ones breached their
+    /// budget.
+    pub fn check_trips(&mut self, ctx: &mut EventCtx, app: &App) -> bool {
+        let key = match app.has_prebaked() {
+            Some((name, scenario)) => (name.clone(), scenario.clone()),
+            None => {
+                self.panel = None;
+                return false;
+            }
+        };
+        if self.panel.is_none() {
+            self.recreate_panel(ctx, app);
+        }
+
+        let budgets = &app.session.trip_time_budgets;
+        let finished = &app.primary.sim.get_analytics().finished_trips;
+        if finished.len() < self.checked {
+            // The simulation was reset or a savestate was loaded
+            self.reset(ctx, app);
+        }
+        if !budgets.is_enabled() || finished.len() == self.checked {
+            self.checked = finished.len();
+            return false;
+        }
+
+        if self
+            .baseline
+            .as_ref()
+            .map(|(k, _)| k != &key)
+            .unwrap_or(true)
+        {
+            let times = app
+                .prebaked()
+                .finished_trips
+                .iter()
+                .filter_map(|(_, id, _, maybe_dt)| maybe_dt.map(|dt| (*id, dt)))
+                .collect();
+            self.baseline = Some((key, times));
+        }
+        let baseline = &self.baseline.as_ref().unwrap().1;
+
+        let before = self.breaches.len();
+        for (_, trip, mode, maybe_dt) in &finished[self.checked..] {
+            let (actual, ratio) = match (maybe_dt, budgets.max_ratio.get(mode)) {
+                (Some(dt), Some(ratio)) => (*dt, *ratio),
+                _ => continue,
+            };
+            if let Some(baseline) = baseline.get(trip) {
+                if actual > *ratio * *baseline {
+                    self.breaches.push(Breach {
+                        trip: *trip,
+                        mode: *mode,
+                        baseline: *baseline,
+                        actual,
+                    });
+                }
+            }
+        }
+        self.checked = finished.len();
+
+        if self.breaches.len() == before {
+            return false;
+        }
+        self.recreate_panel(ctx, app);
+        true
+    }
+
+    /// Forget all alerts and check every finished trip again
+    pub fn reset(&mut self, ctx: &mut EventCtx, app: &App) {
+        self.checked = 0;
+        self.breaches.clear();
+        self.recreate_panel(ctx, app);
+    }
+
+    pub fn recreate_panel(&mut self, ctx: &mut EventCtx, app: &App) {
+        if app.has_prebaked().is_none() {
+            self.panel = None;
+            return;
+        }
+
+        let mut col = Vec::new();
+        if app.session.trip_time_budgets.is_enabled() {
+            col.push(Widget::row(vec![
+                Line("Trip time alerts").small_heading().into_widget(ctx),
+                ctx.style()
+                    .btn_plain
+                    .icon("system/assets/tools/settings.svg")
+                    .build_widget(ctx, "configure trip time alerts"),
+            ]));
+            if self.breaches.is_empty() {
+                col.push("No trips over budget yet".text_widget(ctx));
+            } else {
+                col.push(
+                    format!(
+                        "{} trips over budget",
+                        prettyprint_usize(self.breaches.len())
+                    )
+                    .text_widget(ctx),
+                );
+                for (idx, breach) in self.breaches.iter().enumerate().rev().take(MAX_SHOWN) {
+                    col.push(
+                        ctx.style()
+                            .btn_outline
+                            .text(format!(
+                                "{} ({}): {} vs {} baseline",
+                                breach.trip,
+                                breach.mode.noun(),
+                                breach.actual,
+                                breach.baseline
+                            ))
+                            .build_widget(ctx, format!("alert {}", idx)),
+                    );
+                }
+                col.push(ctx.style().btn_plain.text("Clear alerts").build_def(ctx));
+            }
+        } else {
+            col.push(
+                ctx.style()
+                    .btn_outline
+                    .text("Set trip time alerts")
+                    .build_widget(ctx, "configure trip time alerts"),
+            );
+        }
+
+        self.panel = Some(
+            Panel::new_builder(Widget::col(col))
+                .aligned(HorizontalAlignment::Left, VerticalAlignment::Center)
+                .build(ctx),
+        );
+    }
+
+    pub fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Option<Transition> {
+        if let Outcome::Clicked(x) = self.panel.as_mut()?.event(ctx) {
+            match x.as_ref() {
+                "configure trip time alerts" => {
+                    return Some(Transition::Push(TripTimeBudgetSettings::new_state(
+                        ctx, app,
+                    )));
+                }
+                "Clear alerts" => {
+                    self.breaches.clear();
+                    self.recreate_panel(ctx, app);
+                }
+                x => {
+                    let idx = x["alert ".len()..].parse::<usize>().unwrap();
+                    let trip = self.breaches[idx].trip;
+                    let person = app.primary.sim.trip_to_person(trip)?;
+                    return Some(Transition::ModifyState(Box::new(move |state, ctx, app| {
+                        let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
+                        let mut actions = sandbox.contextual_actions();
+                        sandbox.controls.common.as_mut().unwrap().launch_info_panel(
+                            ctx,
+                            app,
+                            Tab::PersonTrips(person, OpenTrip::single(trip)),
+                            &mut actions,
+                        );
+                    })));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx) {
+        if let Some(ref panel) = self.panel {
+            panel.draw(g);
+        }
+    }
+}
+
+/// Choose which modes to watch and how much slower than the baseline a trip can be.
+struct TripTimeBudgetSettings {
+    panel: Panel,
+}
+
+impl TripTimeBudgetSettings {
+    fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let budgets = &app.session.trip_time_budgets;
+        let mut col = vec![
+            Widget::row(vec![
+                Line("Trip time alerts").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(Line(
+                "Get an alert when a trip finishes and took much longer than it did in the \
+                 baseline simulation",
+            ))
+            .wrap_to_pct(ctx, 30)
+            .into_widget(ctx),
+        ];
+        for mode in TripMode::all() {
+            let ratio = budgets.max_ratio.get(&mode);
+            col.push(Widget::row(vec![
+                Toggle::checkbox(
+                    ctx,
+                    &format!("Watch {} trips", mode.noun().to_lowercase()),
+                    None,
+                    ratio.is_some(),
+                ),
+                "over".text_widget(ctx).centered_vert(),
+                Spinner::f64_widget(
+                    ctx,
+                    format!("{} ratio", mode.noun()),
+                    (1.0, 10.0),
+                    ratio.cloned().unwrap_or(1.5),
+                    0.1,
+                ),
+                "x the baseline time".text_widget(ctx).centered_vert(),
+            ]));
+        }
+        col.push(Toggle::checkbox(
+            ctx,
+            "Pause the simulation on new alerts",
+            None,
+            budgets.pause_on_alert,
+        ));
+        col.push(ctx.style().btn_solid_primary.text("Apply").build_def(ctx));
+
+        Box::new(TripTimeBudgetSettings {
+            panel: Panel::new_builder(Widget::col(col))
+                .aligned(HorizontalAlignment::Center, VerticalAlignment::Center)
+                .build(ctx),
+        })
+    }
+}
+
+impl State<App> for TripTimeBudgetSettings {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Apply" => {
+                    let mut budgets = TripTimeBudgets {
+                        max_ratio: BTreeMap::new(),
+                        pause_on_alert: self.panel.is_checked("Pause the simulation on new alerts"),
+                    };
+                    for mode in TripMode::all() {
+                        if self
+                            .panel
+                            .is_checked(&format!("Watch {} trips", mode.noun().to_lowercase()))
+                        {
+                            budgets.max_ratio.insert(
+                                mode,
+                                self.panel
+                                    .spinner::<RoundedF64>(&format!("{} ratio", mode.noun()))
+                                    .0,
+                            );
+                        }
+                    }
+                    app.session.trip_time_budgets = budgets;
+
+                    return Transition::Multi(vec![
+                        Transition::Pop,
+                        Transition::ModifyState(Box::new(|state, ctx, app| {
+                            let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
+                            if let Some(ref mut alerts) = sandbox.controls.trip_alerts {
+                                alerts.reset(ctx, app);
+                            }
+                        })),
+                    ]);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+    }
+}
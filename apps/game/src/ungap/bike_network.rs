@@ -1,16 +1,144 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use map_model::{LaneType, PathConstraints, Road};
+use geom::Distance;
+use map_model::{LaneType, Map, PathConstraints, Road};
 use widgetry::mapspace::DrawUnzoomedShapes;
 use widgetry::{Color, Drawable, EventCtx, GeomBatch, GfxCtx};
 
 use crate::app::App;
 
-lazy_static::lazy_static! {
-    pub static ref DEDICATED_TRAIL: Color = Color::GREEN;
-    pub static ref PROTECTED_BIKE_LANE: Color = Color::hex("#A4DE02");
-    pub static ref PAINTED_BIKE_LANE: Color = Color::hex("#76BA1B");
-    pub static ref GREENWAY: Color = Color::hex("#4C9A2A");
+/// How comfortable is some existing piece of cycling infrastructure? Roads without any
+/// infrastructure at all don't get a tier.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum BikeTier {
+    Trail,
+    Protected,
+    Painted,
+    Shared,
+    Shoulder,
+}
+
+impl BikeTier {
+    pub fn all() -> Vec<BikeTier> {
+        vec![
+            BikeTier::Trail,
+            BikeTier::Protected,
+            BikeTier::Painted,
+            BikeTier::Shared,
+            BikeTier::Shoulder,
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BikeTier::Trail => "trail",
+            BikeTier::Protected => "protected bike lane",
+            BikeTier::Painted => "painted bike lane",
+            BikeTier::Shared => "shared lane",
+            BikeTier::Shoulder => "shoulder",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BikeTier> {
+        BikeTier::all().into_iter().find(|t| t.name() == name)
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            BikeTier::Trail => Color::GREEN,
+            BikeTier::Protected => Color::hex("#A4DE02"),
+            BikeTier::Painted => Color::hex("#76BA1B"),
+            BikeTier::Shared => Color::hex("#4C9A2A"),
+            BikeTier::Shoulder => Color::hex("#C5A100"),
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            BikeTier::Trail => "Trails like the Burke Gilman are usually well-separated from vehicle traffic. The space is usually shared between people walking, cycling, and rolling.",
+            BikeTier::Protected => "Bike lanes separated from vehicle traffic by physical barriers or a few feet of striping.",
+            BikeTier::Painted => "Bike lanes without any separation from vehicle traffic. Often uncomfortably close to the \"door zone\" of parked cars.",
+            BikeTier::Shared => "Sharrows, shared bus lanes, Stay Healthy Streets, and neighborhood greenways. There's no dedicated space, but the street is marked or calmed for biking.",
+            BikeTier::Shoulder => "A paved shoulder that's legal to bike on. Usually found on rural roads with fast traffic.",
+        }
+    }
+
+    /// Classifies a road using its current lanes, falling back to OSM tags for infrastructure
+    /// that isn't modeled as a separate lane, like sharrows and shoulders.
+    pub fn classify(map: &Map, road: &Road) -> Option<BikeTier> {
+        if road.is_cycleway() {
+            return Some(BikeTier::Trail);
+        }
+
+        let mut bike_lane = false;
+        let mut buffer = false;
+        for l in &road.lanes {
+            if l.lane_type == LaneType::Biking {
+                bike_lane = true;
+            } else if matches!(l.lane_type, LaneType::Buffer(_)) {
+                buffer = true;
+            }
+        }
+        if bike_lane && buffer {
+            return Some(BikeTier::Protected);
+        }
+        if bike_lane {
+            return Some(BikeTier::Painted);
+        }
+
+        // If the road has been edited, the original tags might describe lanes that no longer
+        // exist.
+        if map.get_edits().changed_roads.contains(&road.id) {
+            return if is_greenway(road) {
+                Some(BikeTier::Shared)
+            } else {
+                None
+            };
+        }
+
+        let cycleway_values: Vec<&String> = [
+            "cycleway",
+            "cycleway:both",
+            "cycleway:left",
+            "cycleway:right",
+        ]
+        .into_iter()
+        .filter_map(|k| road.osm_tags.get(k))
+        .collect();
+        let has_value =
+            |values: &[&str]| cycleway_values.iter().any(|v| values.contains(&v.as_str()));
+        if has_value(&["track", "opposite_track"]) {
+            Some(BikeTier::Protected)
+        } else if has_value(&["lane", "opposite_lane"]) {
+            Some(BikeTier::Painted)
+        } else if has_value(&["shared_lane", "share_busway", "opposite_share_busway"])
+            || road.osm_tags.is("bicycle_road", "yes")
+            || road.osm_tags.is("cyclestreet", "yes")
+            || is_greenway(road)
+        {
+            Some(BikeTier::Shared)
+        } else if has_value(&["shoulder"])
+            || road.osm_tags.is_any(
+                "shoulder:access:bicycle",
+                vec!["yes", "designated", "permissive"],
+            )
+        {
+            Some(BikeTier::Shoulder)
+        } else {
+            None
+        }
+    }
+
+    /// The total length of roads in each tier
+    pub fn total_lengths(map: &Map) -> HashMap<BikeTier, Distance> {
+        let mut totals = HashMap::new();
+        for r in map.all_roads() {
+            if let Some(tier) = BikeTier::classify(map, r) {
+                *totals.entry(tier).or_insert(Distance::ZERO) += r.length();
+            }
+        }
+        totals
+    }
 }
 
 /// Shows the bike network while unzoomed. Handles thickening the roads at low zoom levels.
@@ -20,32 +148,19 @@ pub struct DrawNetworkLayer {
 }
 
 impl DrawNetworkLayer {
-    pub fn new(ctx: &EventCtx, app: &App) -> DrawNetworkLayer {
+    /// Only roads in one of the `show` tiers are drawn. Edited roads are always drawn.
+    pub fn new(ctx: &EventCtx, app: &App, show: &BTreeSet<BikeTier>) -> DrawNetworkLayer {
+        let map = &app.primary.map;
         let mut lines = DrawUnzoomedShapes::builder();
         let mut intersections = HashMap::new();
-        for r in app.primary.map.all_roads() {
-            let mut bike_lane = false;
-            let mut buffer = false;
-            for l in &r.lanes {
-                if l.lane_type == LaneType::Biking {
-                    bike_lane = true;
-                } else if matches!(l.lane_type, LaneType::Buffer(_)) {
-                    buffer = true;
-                }
-            }
-
-            let color = if app.primary.map.get_edits().changed_roads.contains(&r.id) {
+        for r in map.all_roads() {
+            let color = if map.get_edits().changed_roads.contains(&r.id) {
                 Color::CYAN
-            } else if r.is_cycleway() {
-                *DEDICATED_TRAIL
-            } else if bike_lane && buffer {
-                *PROTECTED_BIKE_LANE
-            } else if bike_lane {
-                *PAINTED_BIKE_LANE
-            } else if is_greenway(r) {
-                *GREENWAY
             } else {
-                continue;
+                match BikeTier::classify(map, r) {
+                    Some(tier) if show.contains(&tier) => tier.color(),
+                    _ => continue,
+                }
             };
 
             lines.add_line(r.center_pts.clone(), r.get_width(), color);
@@ -55,10 +170,18 @@ impl DrawNetworkLayer {
             intersections.insert(r.dst_i, color);
         }
 
+        if show.contains(&BikeTier::Protected) {
+            for i in map.all_intersections() {
+                if i.protected_for_bikes {
+                    intersections.insert(i.id, BikeTier::Protected.color());
+                }
+            }
+        }
+
         let mut batch = GeomBatch::new();
         for (i, color) in intersections {
             // No clear way to thicken the intersection at different zoom levels
-            batch.push(color, app.primary.map.get_i(i).polygon.clone());
+            batch.push(color, map.get_i(i).polygon.clone());
         }
 
         DrawNetworkLayer {
@@ -1,25 +1,28 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use geom::Distance;
 use map_gui::tools::{DrawRoadLabels, Navigator};
 use map_model::osm::RoadRank;
-use map_model::LaneType;
 use widgetry::tools::PopupMsg;
 use widgetry::{
     ButtonBuilder, Color, ControlState, Drawable, EdgeInsets, EventCtx, GeomBatch, GfxCtx,
-    HorizontalAlignment, Image, Key, Line, Outcome, Panel, ScreenPt, Text, Toggle,
+    HorizontalAlignment, Image, Key, Line, Outcome, Panel, ScreenPt, Text, TextExt, Toggle,
     VerticalAlignment, Widget,
 };
 
 use crate::app::{App, Transition};
-use crate::ungap::bike_network;
-use crate::ungap::bike_network::DrawNetworkLayer;
+use crate::ungap::bike_network::{BikeTier, DrawNetworkLayer};
+
+/// For remembering whether the panel was minimized between sessions
+const PANEL_NAME: &str = "ungap layers";
 
 /// A bottom-right panel for managing a bunch of toggleable layers in the "ungap the map" tool.
 pub struct Layers {
     panel: Panel,
     minimized: bool,
     bike_network: Option<DrawNetworkLayer>,
+    bike_tiers: BTreeSet<BikeTier>,
+    bike_tier_lengths: HashMap<BikeTier, Distance>,
     labels: Option<DrawRoadLabels>,
     elevation: bool,
     steep_streets: Option<Drawable>,
@@ -33,10 +36,13 @@ pub struct Layers {
 
 impl Layers {
     pub fn new(ctx: &mut EventCtx, app: &App) -> Layers {
+        let bike_tiers: BTreeSet<BikeTier> = BikeTier::all().into_iter().collect();
         let mut l = Layers {
             panel: Panel::empty(ctx),
-            minimized: true,
-            bike_network: Some(DrawNetworkLayer::new(ctx, app)),
+            minimized: app.ui_state.is_minimized(PANEL_NAME, true),
+            bike_network: Some(DrawNetworkLayer::new(ctx, app, &bike_tiers)),
+            bike_tiers,
+            bike_tier_lengths: BikeTier::total_lengths(&app.primary.map),
             labels: Some(DrawRoadLabels::only_major_roads()),
             elevation: false,
             steep_streets: None,
@@ -59,9 +65,13 @@ impl Layers {
         if self.map_edit_key != key {
             self.map_edit_key = key;
             if self.bike_network.is_some() {
-                self.bike_network = Some(DrawNetworkLayer::new(ctx, app));
+                self.bike_network = Some(DrawNetworkLayer::new(ctx, app, &self.bike_tiers));
             }
+            self.bike_tier_lengths = BikeTier::total_lengths(&app.primary.map);
             self.road_types.clear();
+            if !self.minimized {
+                self.update_panel(ctx, app);
+            }
         }
 
         if ctx.redo_mouseover() && self.elevation && !self.minimized {
@@ -92,10 +102,14 @@ impl Layers {
                     "highway" => PopupMsg::new_state(ctx, "Highways", vec!["Unless there's a separate trail (like on the 520 or I90 bridge), highways aren't accessible to biking"]),
                     "major street" => PopupMsg::new_state(ctx, "Major streets", vec!["Arterials have more traffic, but are often where businesses are located"]),
                     "minor street" => PopupMsg::new_state(ctx, "Minor streets", vec!["Local streets have a low volume of traffic and are usually comfortable for biking, even without dedicated infrastructure"]),
-                    "trail" => PopupMsg::new_state(ctx, "Trails", vec!["Trails like the Burke Gilman are usually well-separated from vehicle traffic. The space is usually shared between people walking, cycling, and rolling."]),
-                    "protected bike lane" => PopupMsg::new_state(ctx, "Protected bike lanes", vec!["Bike lanes separated from vehicle traffic by physical barriers or a few feet of striping"]),
-                    "painted bike lane" => PopupMsg::new_state(ctx, "Painted bike lanes", vec!["Bike lanes without any separation from vehicle traffic. Often uncomfortably close to the \"door zone\" of parked cars."]),
-                    "greenway" => PopupMsg::new_state(ctx, "Stay Healthy Streets and neighborhood greenways", vec!["Residential streets with additional signage and light barriers. These are intended to be low traffic, dedicated for people walking and biking."]),
+                    "about the bike network" => {
+                        let mut lines = vec!["Existing infrastructure is classified by how separated it is from vehicle traffic.".to_string()];
+                        for tier in BikeTier::all() {
+                            lines.push(String::new());
+                            lines.push(format!("{}: {}", tier.name(), tier.describe()));
+                        }
+                        PopupMsg::new_state(ctx, "About the bike network", lines)
+                    }
                     // TODO Add URLs
                     "about the elevation data" => PopupMsg::new_state(ctx, "About the elevation data", vec!["Biking uphill next to traffic without any dedicated space isn't fun.", "Biking downhill next to traffic, especially in the door-zone of parked cars, and especially on Seattle's bumpy roads... is downright terrifying.", "", "Note the elevation data is incorrect near bridges.", "Thanks to King County LIDAR for the data, and Eldan Goldenberg for processing it."]),
                    "zoom map out" => {
@@ -113,11 +127,13 @@ impl Layers {
                     }
                     "hide panel" => {
                         self.minimized = true;
+                        app.ui_state.set_minimized(PANEL_NAME, true);
                         self.update_panel(ctx, app);
                         return Some(Transition::Keep);
                     }
                     "show panel" => {
                         self.minimized = false;
+                        app.ui_state.set_minimized(PANEL_NAME, false);
                         self.update_panel(ctx, app);
                         return Some(Transition::Keep);
                     }
@@ -127,12 +143,21 @@ impl Layers {
             Outcome::Changed(x) => match x.as_ref() {
                 "bike network" => {
                     if self.panel.is_checked("bike network") {
-                        self.bike_network = Some(DrawNetworkLayer::new(ctx, app));
+                        self.bike_network = Some(DrawNetworkLayer::new(ctx, app, &self.bike_tiers));
                     } else {
                         self.bike_network = None;
                     }
                     self.update_panel(ctx, app);
                 }
+                x if BikeTier::from_name(x).is_some() => {
+                    let tier = BikeTier::from_name(x).unwrap();
+                    if self.panel.is_checked(x) {
+                        self.bike_tiers.insert(tier);
+                    } else {
+                        self.bike_tiers.remove(&tier);
+                    }
+                    self.bike_network = Some(DrawNetworkLayer::new(ctx, app, &self.bike_tiers));
+                }
                 "road labels" => {
                     if self.panel.is_checked("road labels") {
                         self.labels = Some(DrawRoadLabels::only_major_roads());
@@ -202,11 +227,7 @@ impl Layers {
                 if let Some(draw) = self.road_types.get(name) {
                     g.redraw(draw);
                 }
-                if name == "trail"
-                    || name == "protected bike lane"
-                    || name == "painted bike lane"
-                    || name == "greenway"
-                {
+                if BikeTier::from_name(name).is_some() {
                     draw_bike_layer = false;
                 }
             }
@@ -239,8 +260,9 @@ impl Layers {
         }
     }
 
-    pub fn show_panel(&mut self, ctx: &mut EventCtx, app: &App) {
+    pub fn show_panel(&mut self, ctx: &mut EventCtx, app: &mut App) {
         self.minimized = false;
+        app.ui_state.set_minimized(PANEL_NAME, false);
         self.update_panel(ctx, app);
     }
 
@@ -290,27 +312,42 @@ impl Layers {
                     .build_widget(ctx, "hide panel")
                     .align_right(),
             ]),
-            Widget::custom_row({
-                let mut row = vec![Toggle::checkbox(
-                    ctx,
-                    "bike network",
-                    Key::B,
-                    self.bike_network.is_some(),
-                )];
-                if self.bike_network.is_some() {
-                    row.push(legend_btn(*bike_network::DEDICATED_TRAIL, "trail").build_def(ctx));
-                    row.push(
-                        legend_btn(*bike_network::PROTECTED_BIKE_LANE, "protected bike lane")
-                            .build_def(ctx),
-                    );
-                    row.push(
-                        legend_btn(*bike_network::PAINTED_BIKE_LANE, "painted bike lane")
-                            .build_def(ctx),
-                    );
-                    row.push(legend_btn(*bike_network::GREENWAY, "greenway").build_def(ctx));
-                }
-                row
-            }),
+            Widget::row(vec![
+                Toggle::checkbox(ctx, "bike network", Key::B, self.bike_network.is_some()),
+                ctx.style()
+                    .btn_plain
+                    .icon("system/assets/tools/info.svg")
+                    .build_widget(ctx, "about the bike network")
+                    .centered_vert(),
+            ]),
+            if self.bike_network.is_some() {
+                Widget::col(
+                    BikeTier::all()
+                        .into_iter()
+                        .map(|tier| {
+                            Widget::row(vec![
+                                Toggle::colored_checkbox(
+                                    ctx,
+                                    tier.name(),
+                                    tier.color(),
+                                    self.bike_tiers.contains(&tier),
+                                ),
+                                self.bike_tier_lengths
+                                    .get(&tier)
+                                    .cloned()
+                                    .unwrap_or(Distance::ZERO)
+                                    .to_string(&app.opts.units)
+                                    .text_widget(ctx)
+                                    .centered_vert()
+                                    .align_right(),
+                            ])
+                        })
+                        .collect(),
+                )
+                .padding_left(16)
+            } else {
+                Widget::nothing()
+            },
             // TODO Distinguish door-zone bike lanes?
             // TODO Call out bike turning boxes?
             // TODO Call out bike signals?
@@ -369,26 +406,14 @@ impl Layers {
             return;
         }
 
+        let tier = BikeTier::from_name(name);
         let mut batch = GeomBatch::new();
         for r in app.primary.map.all_roads() {
             let rank = r.get_rank();
-            let mut bike_lane = false;
-            let mut buffer = false;
-            for l in &r.lanes {
-                if l.lane_type == LaneType::Biking {
-                    bike_lane = true;
-                } else if matches!(l.lane_type, LaneType::Buffer(_)) {
-                    buffer = true;
-                }
-            }
-
             let show = (name == "highway" && rank == RoadRank::Highway)
                 || (name == "major street" && rank == RoadRank::Arterial)
                 || (name == "minor street" && rank == RoadRank::Local)
-                || (name == "trail" && r.is_cycleway())
-                || (name == "protected bike lane" && bike_lane && buffer)
-                || (name == "painted bike lane" && bike_lane && !buffer)
-                || (name == "greenway" && bike_network::is_greenway(r));
+                || (tier.is_some() && BikeTier::classify(&app.primary.map, r) == tier);
             if show {
                 let color = match name {
                     "highway" => app.cs.unzoomed_highway,
@@ -3,6 +3,7 @@ mod explore;
 mod layers;
 mod predict;
 mod quick_sketch;
+mod stress;
 mod trip;
 
 use geom::CornerRadii;
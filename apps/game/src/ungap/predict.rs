@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use abstio::Manifest;
 use abstutil::{prettyprint_bytes, prettyprint_usize, Counter, Timer};
-use geom::{Distance, Duration, UnitFmt};
+use geom::{Distance, Duration};
 use map_gui::tools::{percentage_bar, ColorNetwork};
 use map_gui::ID;
 use map_model::{PathRequest, PathStepV2, RoadID};
@@ -34,6 +34,17 @@ impl ShowGaps {
             tooltip: None,
         })
     }
+
+    fn recalculate_gaps(&mut self, ctx: &mut EventCtx, app: &mut App) {
+        let (map_name, mut data) = app.session.mode_shift.take().unwrap();
+        data.filters = Filters::from_controls(&self.top_panel);
+        ctx.loading_screen("update mode shift", |ctx, timer| {
+            data.recalculate_gaps(ctx, app, timer)
+        });
+        app.session.mode_shift.set(map_name, data);
+        // TODO This is heavy-handed for just updating the counters
+        self.top_panel = make_top_panel(ctx, app);
+    }
 }
 
 impl State<App> for ShowGaps {
@@ -86,6 +97,17 @@ impl State<App> for ShowGaps {
                             ])
                         }),
                     ));
+                } else if x == "configure high-stress roads" {
+                    return Transition::Push(crate::ungap::stress::StressSettings::new_state(
+                        ctx,
+                        app,
+                        Box::new(|_, _| {
+                            Transition::ModifyState(Box::new(|state, ctx, app| {
+                                let state = state.downcast_mut::<ShowGaps>().unwrap();
+                                state.recalculate_gaps(ctx, app);
+                            }))
+                        }),
+                    ));
                 }
 
                 return Tab::PredictImpact
@@ -93,14 +115,7 @@ impl State<App> for ShowGaps {
                     .unwrap();
             }
             Outcome::Changed(_) => {
-                let (map_name, mut data) = app.session.mode_shift.take().unwrap();
-                data.filters = Filters::from_controls(&self.top_panel);
-                ctx.loading_screen("update mode shift", |ctx, timer| {
-                    data.recalculate_gaps(ctx, app, timer)
-                });
-                app.session.mode_shift.set(map_name, data);
-                // TODO This is heavy-handed for just updating the counters
-                self.top_panel = make_top_panel(ctx, app);
+                self.recalculate_gaps(ctx, app);
             }
             _ => {}
         }
@@ -149,9 +164,22 @@ fn make_top_panel(ctx: &mut EventCtx, app: &App) -> Panel {
                 ))),
                 0.0,
             ),
+            Text::from(
+                Line(format!(
+                    "{} of them start or end beyond the map",
+                    prettyprint_usize(
+                        data.all_candidate_trips
+                            .iter()
+                            .filter(|x| x.off_map_endpoints > 0)
+                            .count()
+                    )
+                ))
+                .secondary(),
+            )
+            .into_widget(ctx),
             Widget::col(vec![
                 "Who might cycle if it was safer?".text_widget(ctx),
-                data.filters.to_controls(ctx),
+                data.filters.to_controls(ctx, app),
                 percentage_bar(
                     ctx,
                     Text::from(Line(format!(
@@ -165,6 +193,7 @@ fn make_top_panel(ctx: &mut EventCtx, app: &App) -> Panel {
             .section(ctx),
             Widget::col(vec![
                 "How many would switch based on your proposal?".text_widget(ctx),
+                crate::ungap::stress::stress_button(ctx, app),
                 percentage_bar(
                     ctx,
                     Text::from(Line(format!(
@@ -223,14 +252,33 @@ pub struct ModeShiftData {
 
 struct CandidateTrip {
     bike_req: PathRequest,
+    /// Only the part of the trip inside the map
     estimated_biking_time: Duration,
+    /// Only the part of the trip inside the map
     driving_distance: Distance,
     total_elevation_gain: Distance,
+    /// How many of the trip's endpoints are borders. The rest of the trip happens somewhere we
+    /// can't see, so its length has to be estimated.
+    off_map_endpoints: usize,
+}
+
+impl CandidateTrip {
+    fn total_biking_time(&self, filters: &Filters) -> Duration {
+        self.estimated_biking_time
+            + (self.off_map_endpoints as f64)
+                * (filters.off_map_distance / map_model::MAX_BIKE_SPEED)
+    }
+
+    fn total_driving_distance(&self, filters: &Filters) -> Distance {
+        self.driving_distance + (self.off_map_endpoints as f64) * filters.off_map_distance
+    }
 }
 
 struct Filters {
     max_biking_time: Duration,
     max_elevation_gain: Distance,
+    /// For trips starting or ending beyond the map, assume they travel this far outside the map.
+    off_map_distance: Distance,
 }
 
 struct NetworkGaps {
@@ -252,15 +300,16 @@ impl Filters {
         Self {
             max_biking_time: Duration::minutes(30),
             max_elevation_gain: Distance::feet(100.0),
+            off_map_distance: Distance::miles(1.0),
         }
     }
 
     fn apply(&self, x: &CandidateTrip) -> bool {
-        x.estimated_biking_time <= self.max_biking_time
+        x.total_biking_time(self) <= self.max_biking_time
             && x.total_elevation_gain <= self.max_elevation_gain
     }
 
-    fn to_controls(&self, ctx: &mut EventCtx) -> Widget {
+    fn to_controls(&self, ctx: &mut EventCtx, app: &App) -> Widget {
         Widget::col(vec![
             Widget::row(vec![
                 "Max biking time".text_widget(ctx).centered_vert(),
@@ -274,20 +323,26 @@ impl Filters {
             ]),
             Widget::row(vec![
                 "Max elevation gain".text_widget(ctx).centered_vert(),
-                Spinner::widget_with_custom_rendering(
+                Spinner::distance_widget(
                     ctx,
                     "max_elevation_gain",
                     (Distance::ZERO, Distance::feet(500.0)),
                     self.max_elevation_gain,
-                    Distance::feet(10.0),
-                    // Even if the user's settings are set to meters, our step size is in feet, so
-                    // just render in feet.
-                    Box::new(|x| {
-                        x.to_string(&UnitFmt {
-                            round_durations: false,
-                            metric: false,
-                        })
-                    }),
+                    (Distance::meters(5.0), Distance::feet(10.0)),
+                    app.opts.units,
+                ),
+            ]),
+            Widget::row(vec![
+                "Distance traveled beyond the map"
+                    .text_widget(ctx)
+                    .centered_vert(),
+                Spinner::distance_widget(
+                    ctx,
+                    "off_map_distance",
+                    (Distance::ZERO, Distance::miles(20.0)),
+                    self.off_map_distance,
+                    (Distance::meters(500.0), Distance::miles(0.5)),
+                    app.opts.units,
                 ),
             ]),
         ])
@@ -297,6 +352,7 @@ impl Filters {
         Filters {
             max_biking_time: panel.spinner("max_biking_time"),
             max_elevation_gain: panel.spinner("max_elevation_gain"),
+            off_map_distance: panel.spinner("off_map_distance"),
         }
     }
 }
@@ -357,9 +413,13 @@ impl ModeShiftData {
                 scenario
                     .all_trips()
                     .filter(|trip| {
+                        // Trips passing through the map are excluded; nothing about them is known
+                        // besides where they cross the map.
                         trip.mode == TripMode::Drive
-                            && matches!(trip.origin, TripEndpoint::Building(_))
-                            && matches!(trip.destination, TripEndpoint::Building(_))
+                            && (matches!(trip.origin, TripEndpoint::Building(_))
+                                || matches!(trip.destination, TripEndpoint::Building(_)))
+                            && !matches!(trip.origin, TripEndpoint::SuddenlyAppear(_))
+                            && !matches!(trip.destination, TripEndpoint::SuddenlyAppear(_))
                     })
                     .collect(),
                 |trip| {
@@ -388,6 +448,10 @@ impl ModeShiftData {
                                 .estimate_duration(unedited_map, Some(map_model::MAX_BIKE_SPEED)),
                             driving_distance: driving_path.total_length(),
                             total_elevation_gain,
+                            off_map_endpoints: [trip.origin, trip.destination]
+                                .into_iter()
+                                .filter(|x| matches!(x, TripEndpoint::Border(_)))
+                                .count(),
                         })
                     } else {
                         None
@@ -414,7 +478,7 @@ impl ModeShiftData {
         let mut high_stress = HashSet::new();
         for r in unedited_map.all_roads() {
             for dr in r.id.both_directions() {
-                if r.high_stress_for_bikes(unedited_map, dr.dir) {
+                if r.high_stress_for_bikes(unedited_map, dr.dir, &app.session.bike_stress) {
                     high_stress.insert(dr);
                 }
             }
@@ -458,7 +522,7 @@ impl ModeShiftData {
             if crosses_edited_road {
                 self.results.num_trips += 1;
                 self.results.total_driving_distance +=
-                    self.all_candidate_trips[idx].driving_distance;
+                    self.all_candidate_trips[idx].total_driving_distance(&self.filters);
             }
         }
 
@@ -1,8 +1,10 @@
 use abstutil::Tags;
 use geom::Distance;
+use map_gui::tools::ChooseSomething;
 use map_gui::ID;
 use map_model::{
-    BufferType, Direction, DrivingSide, EditCmd, EditRoad, LaneSpec, LaneType, RoadID,
+    BufferType, Direction, DrivingSide, EditCmd, EditRoad, Intersection, IntersectionID, LaneSpec,
+    LaneType, RoadID,
 };
 use widgetry::tools::{PopupMsg, URLManager};
 use widgetry::{
@@ -45,7 +47,10 @@ impl QuickSketch {
     fn update_top_panel(&mut self, ctx: &mut EventCtx, app: &App) {
         let mut col = Vec::new();
         if !self.route_sketcher.is_route_started() {
-            col.push("Zoom in and click a road to edit in detail".text_widget(ctx));
+            col.push(
+                "Zoom in and click a road to edit in detail, or an intersection to protect it"
+                    .text_widget(ctx),
+            );
         }
         col.push(self.route_sketcher.get_widget_to_describe(ctx));
 
@@ -112,27 +117,36 @@ impl State<App> for QuickSketch {
             self.update_top_panel(ctx, app);
         }
 
-        // Only when zoomed in and not drawing a route, click to edit a road in detail
+        // Only when zoomed in and not drawing a route, click to edit a road in detail or protect
+        // an intersection
         if !self.route_sketcher.is_route_started() && ctx.canvas.is_zoomed() {
             if ctx.redo_mouseover() {
+                let map = &app.primary.map;
+                let road = |r: RoadID| {
+                    if map.get_r(r).is_light_rail() {
+                        None
+                    } else {
+                        Some(ID::Road(r))
+                    }
+                };
                 app.primary.current_selection =
                     match app.mouseover_unzoomed_roads_and_intersections(ctx) {
-                        Some(ID::Road(r)) => Some(r),
-                        Some(ID::Lane(l)) => Some(l.road),
-                        _ => None,
-                    }
-                    .and_then(|r| {
-                        if app.primary.map.get_r(r).is_light_rail() {
-                            None
-                        } else {
-                            Some(ID::Road(r))
+                        Some(ID::Road(r)) => road(r),
+                        Some(ID::Lane(l)) => road(l.road),
+                        Some(ID::Intersection(i)) if !map.get_i(i).is_border() => {
+                            Some(ID::Intersection(i))
                         }
-                    });
+                        _ => None,
+                    };
             }
-            if let Some(ID::Road(r)) = app.primary.current_selection {
-                if ctx.normal_left_click() {
+            match app.primary.current_selection {
+                Some(ID::Road(r)) if ctx.normal_left_click() => {
                     return Transition::Push(RoadEditor::new_state_without_lane(ctx, app, r));
                 }
+                Some(ID::Intersection(i)) if ctx.normal_left_click() => {
+                    return Transition::Push(choose_intersection_protection(ctx, app, i));
+                }
+                _ => {}
             }
         } else {
             app.primary.current_selection = None;
@@ -207,6 +221,91 @@ impl State<App> for QuickSketch {
     }
 }
 
+/// Protected intersections can't be expressed by editing lanes one at a time, so offer to apply
+/// the whole design as a template.
+fn choose_intersection_protection(
+    ctx: &mut EventCtx,
+    app: &App,
+    i: IntersectionID,
+) -> Box<dyn State<App>> {
+    let protected = app.primary.map.get_i(i).protected_for_bikes;
+    ChooseSomething::new_state(
+        ctx,
+        i.to_string(),
+        vec![Choice::new(
+            if protected {
+                "Remove the protected design"
+            } else {
+                "Protect this intersection"
+            },
+            (),
+        )],
+        Box::new(move |_, ctx, app| {
+            let messages = protect_intersection(ctx, app, i, !protected);
+            Transition::Replace(PopupMsg::new_state(ctx, "Changes made", messages))
+        }),
+    )
+}
+
+/// Corner refuge islands force vehicles to turn around a much tighter curve than the curb.
+const CORNER_ISLAND_RADIUS: Distance = Distance::const_meters(3.0);
+
+/// Applies (or removes) corner refuge islands, setback crossings, and a leading interval for bikes
+/// at signals, all at once.
+fn protect_intersection(
+    ctx: &mut EventCtx,
+    app: &mut App,
+    i: IntersectionID,
+    protect: bool,
+) -> Vec<String> {
+    let map = &app.primary.map;
+    let intersection = map.get_i(i);
+    let mut edits = map.get_edits().clone();
+    edits.commands.push(EditCmd::ChangeBikeProtection {
+        i,
+        old: intersection.protected_for_bikes,
+        new: protect,
+    });
+
+    let old_radius = intersection.corner_radius;
+    let new_radius = if protect {
+        Some(CORNER_ISLAND_RADIUS)
+    } else if old_radius == Some(CORNER_ISLAND_RADIUS) {
+        None
+    } else {
+        // The radius was edited separately; leave it alone
+        old_radius
+    };
+    if old_radius != new_radius {
+        edits.commands.push(EditCmd::ChangeCornerRadius {
+            i,
+            old: old_radius,
+            new: new_radius,
+        });
+    }
+
+    let mut messages = Vec::new();
+    if protect {
+        messages.push(format!(
+            "Added corner refuge islands. Vehicles turning around them go at most {}",
+            Intersection::max_speed_around_curb(CORNER_ISLAND_RADIUS).to_string(&app.opts.units)
+        ));
+        messages.push("Set crossings back from the intersection".to_string());
+        if intersection.is_traffic_signal() {
+            messages.push(
+                "People biking and walking get a head start whenever the signal changes"
+                    .to_string(),
+            );
+        }
+        messages.push("Routing no longer considers this intersection stressful".to_string());
+    } else {
+        messages.push(format!("Removed the protected design from {}", i));
+    }
+
+    apply_map_edits(ctx, app, edits);
+    messages
+}
+
 fn make_quick_changes(
     ctx: &mut EventCtx,
     app: &mut App,
@@ -328,6 +427,7 @@ fn maybe_add_bike_lanes(
                 lt: LaneType::Biking,
                 dir,
                 width: LaneSpec::typical_lane_widths(LaneType::Biking, &dummy_tags)[0].0,
+                pocket: None,
             };
             if let Some(buffer) = buffer_type {
                 side.insert(
@@ -338,6 +438,7 @@ fn maybe_add_bike_lanes(
                         width: LaneSpec::typical_lane_widths(LaneType::Buffer(buffer), &dummy_tags)
                             [0]
                         .0,
+                        pocket: None,
                     },
                 );
             }
@@ -0,0 +1,207 @@
+use geom::Speed;
+use map_model::{StressThresholds, TrafficStress};
+use widgetry::{
+    Choice, DrawBaselayer, EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, Spinner,
+    State, Text, TextExt, Toggle, VerticalAlignment, Widget,
+};
+
+use crate::app::{App, Transition};
+
+/// A button describing the current definition of high-stress roads, opening `StressSettings`
+pub fn stress_button(ctx: &mut EventCtx, app: &App) -> Widget {
+    ctx.style()
+        .btn_outline
+        .text(format!(
+            "High-stress: {} or worse",
+            app.session.bike_stress.high_stress
+        ))
+        .build_widget(ctx, "configure high-stress roads")
+}
+
+/// Configure the Level of Traffic Stress thresholds used to find high-stress roads. The route
+/// planner, gap predictor, and layers all use the same thresholds.
+pub struct StressSettings {
+    panel: Panel,
+    on_change: Box<dyn Fn(&mut EventCtx, &mut App) -> Transition>,
+}
+
+impl StressSettings {
+    pub fn new_state(
+        ctx: &mut EventCtx,
+        app: &App,
+        on_change: Box<dyn Fn(&mut EventCtx, &mut App) -> Transition>,
+    ) -> Box<dyn State<App>> {
+        let thresholds = app.session.bike_stress;
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("High-stress roads").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(Line(
+                "Roads are classified by Level of Traffic Stress, from LTS 1 (comfortable for \
+                 children) to LTS 4 (only for the strong and fearless)",
+            ))
+            .wrap_to_pct(ctx, 30)
+            .into_widget(ctx),
+            Widget::row(vec![
+                "Roads count as high-stress at"
+                    .text_widget(ctx)
+                    .centered_vert(),
+                Widget::dropdown(
+                    ctx,
+                    "high stress",
+                    thresholds.high_stress,
+                    TrafficStress::all()
+                        .into_iter()
+                        .skip(1)
+                        .map(|x| Choice::new(format!("{} or worse", x), x))
+                        .collect(),
+                ),
+            ]),
+            Line("Mixing with traffic").small_heading().into_widget(ctx),
+            speed_spinner(
+                ctx,
+                app,
+                "LTS 1 up to",
+                "mixed traffic low speed",
+                thresholds.mixed_traffic_low_speed,
+            ),
+            speed_spinner(
+                ctx,
+                app,
+                "LTS 2 up to",
+                "mixed traffic high speed",
+                thresholds.mixed_traffic_high_speed,
+            ),
+            Line("Painted bike lanes").small_heading().into_widget(ctx),
+            speed_spinner(
+                ctx,
+                app,
+                "LTS 1 up to",
+                "bike lane low speed",
+                thresholds.bike_lane_low_speed,
+            ),
+            speed_spinner(
+                ctx,
+                app,
+                "LTS 3 up to",
+                "bike lane high speed",
+                thresholds.bike_lane_high_speed,
+            ),
+            Toggle::checkbox(
+                ctx,
+                "Bike lanes next to parking are more stressful",
+                None,
+                thresholds.penalize_parking_adjacent_bike_lanes,
+            ),
+            Widget::row(vec![
+                "Roads with more lanes per direction than"
+                    .text_widget(ctx)
+                    .centered_vert(),
+                Spinner::widget(
+                    ctx,
+                    "max lanes per direction",
+                    (1, 5),
+                    thresholds.max_lanes_per_direction,
+                    1,
+                ),
+                "are more stressful".text_widget(ctx).centered_vert(),
+            ]),
+            Widget::row(vec![
+                ctx.style().btn_solid_primary.text("Apply").build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Reset to defaults")
+                    .build_def(ctx),
+            ]),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Center)
+        .build(ctx);
+        Box::new(StressSettings { panel, on_change })
+    }
+
+    fn apply(&self, ctx: &mut EventCtx, app: &mut App, thresholds: StressThresholds) -> Transition {
+        app.session.bike_stress = thresholds;
+        Transition::Multi(vec![Transition::Pop, (self.on_change)(ctx, app)])
+    }
+}
+
+impl State<App> for StressSettings {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Apply" => {
+                    let thresholds = StressThresholds {
+                        mixed_traffic_low_speed: from_spinner(
+                            app,
+                            self.panel.spinner("mixed traffic low speed"),
+                        ),
+                        mixed_traffic_high_speed: from_spinner(
+                            app,
+                            self.panel.spinner("mixed traffic high speed"),
+                        ),
+                        bike_lane_low_speed: from_spinner(
+                            app,
+                            self.panel.spinner("bike lane low speed"),
+                        ),
+                        bike_lane_high_speed: from_spinner(
+                            app,
+                            self.panel.spinner("bike lane high speed"),
+                        ),
+                        max_lanes_per_direction: self.panel.spinner("max lanes per direction"),
+                        penalize_parking_adjacent_bike_lanes: self
+                            .panel
+                            .is_checked("Bike lanes next to parking are more stressful"),
+                        high_stress: self.panel.dropdown_value("high stress"),
+                    };
+                    return self.apply(ctx, app, thresholds);
+                }
+                "Reset to defaults" => {
+                    return self.apply(ctx, app, StressThresholds::default());
+                }
+                _ => unreachable!(),
+            }
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+    }
+}
+
+// Speed limits are usually posted in round numbers, so use whole km/h or mph, depending on the
+// user's preferred units
+fn speed_spinner(ctx: &EventCtx, app: &App, label: &str, name: &str, speed: Speed) -> Widget {
+    let (range, current, unit) = if app.opts.units.metric {
+        ((5, 110), speed.inner_meters_per_second() * 3.6, "km/h")
+    } else {
+        ((5, 70), speed.inner_meters_per_second() * 2.23694, "mph")
+    };
+    Widget::row(vec![
+        label.text_widget(ctx).centered_vert(),
+        Spinner::widget_with_custom_rendering(
+            ctx,
+            name,
+            range,
+            current.round() as usize,
+            5,
+            Box::new(move |x| format!("{} {}", x, unit)),
+        ),
+    ])
+}
+
+fn from_spinner(app: &App, x: usize) -> Speed {
+    if app.opts.units.metric {
+        Speed::km_per_hour(x as f64)
+    } else {
+        Speed::miles_per_hour(x as f64)
+    }
+}
@@ -1,5 +1,5 @@
 use map_gui::tools::{InputWaypoints, TripManagement, TripManagementState, WaypointID};
-use map_model::RoutingParams;
+use map_model::{RoutingParams, StressThresholds};
 use widgetry::mapspace::{ObjectID, World, WorldOutcome};
 use widgetry::{
     ControlState, EventCtx, GfxCtx, Key, Line, Outcome, Panel, State, Text, Toggle, Widget,
@@ -164,6 +164,7 @@ impl TripPlanner {
                         None,
                         app.session.routing_preferences.avoid_stressful_roads,
                     ),
+                    crate::ungap::stress::stress_button(ctx, app),
                 ])
                 .section(ctx),
             );
@@ -210,6 +211,29 @@ impl State<App> for TripPlanner {
                 }
                 return t;
             }
+            if x == "add a waypoint by address" {
+                return Transition::Push(InputWaypoints::search_address_state(
+                    ctx,
+                    |state: &mut TripPlanner, ctx, app: &mut App, at| {
+                        if state.waypoints.add(app, at) {
+                            state.files.current.waypoints = state.waypoints.get_waypoints();
+                            state.recalculate_routes(ctx, app);
+                        }
+                    },
+                ));
+            }
+            if x == "configure high-stress roads" {
+                return Transition::Push(crate::ungap::stress::StressSettings::new_state(
+                    ctx,
+                    app,
+                    Box::new(|_, _| {
+                        Transition::ModifyState(Box::new(|state, ctx, app| {
+                            let state = state.downcast_mut::<TripPlanner>().unwrap();
+                            state.recalculate_routes(ctx, app);
+                        }))
+                    }),
+                ));
+            }
             if x == "show original map" || x == "show edited map" {
                 app.swap_map();
                 // We're assuming building and intersection IDs haven't changed
@@ -294,10 +318,11 @@ impl RoutingPreferences {
         }
     }
 
-    fn routing_params(self) -> RoutingParams {
+    fn routing_params(self, bike_stress: StressThresholds) -> RoutingParams {
         RoutingParams {
             avoid_steep_incline_penalty: if self.avoid_hills { 2.0 } else { 1.0 },
             avoid_high_stress: if self.avoid_stressful_roads { 2.0 } else { 1.0 },
+            bike_stress,
             ..Default::default()
         }
     }
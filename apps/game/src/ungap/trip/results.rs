@@ -1,8 +1,11 @@
 use std::cmp::Ordering;
+use std::fmt::Write;
+
+use anyhow::Result;
 
 use geom::{Circle, Distance, Duration, FindClosest, PolyLine, Polygon};
 use map_gui::tools::{cmp_dist, cmp_duration};
-use map_model::{DrivingSide, Path, PathStep, PathfinderCaching, NORMAL_LANE_THICKNESS};
+use map_model::{DrivingSide, Path, PathStep, PathfinderCaching, TurnType, NORMAL_LANE_THICKNESS};
 use synthpop::{TripEndpoint, TripMode};
 use widgetry::mapspace::{ToggleZoomed, ToggleZoomedBuilder};
 use widgetry::tools::PopupMsg;
@@ -118,7 +121,7 @@ impl RouteDetails {
         let mut paths = Vec::new();
         let mut closest_path_segment = FindClosest::new(map.get_bounds());
 
-        let routing_params = preferences.routing_params();
+        let routing_params = preferences.routing_params(app.session.bike_stress);
 
         for pair in waypoints.windows(2) {
             if let Some(path) = TripEndpoint::path_req(pair[0], pair[1], TripMode::Bike, map)
@@ -135,7 +138,11 @@ impl RouteDetails {
                     match step {
                         PathStep::Lane(l) | PathStep::ContraflowLane(l) => {
                             let road = map.get_parent(*l);
-                            if road.high_stress_for_bikes(map, road.lanes[l.offset].dir) {
+                            if road.high_stress_for_bikes(
+                                map,
+                                road.lanes[l.offset].dir,
+                                &app.session.bike_stress,
+                            ) {
                                 dist_along_high_stress_roads += this_pl.length();
 
                                 // TODO It'd be nicer to build up contiguous subsets of the path
@@ -253,12 +260,44 @@ impl RouteDetails {
                         ctx,
                         "High-stress roads",
                         vec![
-                            "Roads are defined as high-stress for biking if:",
-                            "- they're classified as arterials",
-                            "- they lack dedicated space for biking",
+                            format!(
+                                "Roads are high-stress for biking at {} or worse.",
+                                app.session.bike_stress.high_stress
+                            ),
+                            "The Level of Traffic Stress depends on:".to_string(),
+                            "- the speed limit and number of lanes".to_string(),
+                            "- whether there's a bike lane, and if it's next to parking"
+                                .to_string(),
+                            "- whether the road is classified as an arterial".to_string(),
                         ],
                     )));
                 }
+                "Directions" => {
+                    let lines = self
+                        .directions(app)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, line)| format!("{}. {}", idx + 1, line))
+                        .collect();
+                    return Some(Transition::Push(PopupMsg::new_state(
+                        ctx,
+                        "Turn-by-turn directions",
+                        lines,
+                    )));
+                }
+                "Export to GPX" | "Export to KML" => {
+                    let gpx = x == "Export to GPX";
+                    return Some(Transition::Push(match self.export(app, gpx) {
+                        Ok(path) => PopupMsg::new_state(
+                            ctx,
+                            "Route exported",
+                            vec![format!("Route exported to {}", path)],
+                        ),
+                        Err(err) => {
+                            PopupMsg::new_state(ctx, "Export failed", vec![err.to_string()])
+                        }
+                    }));
+                }
                 // No effect. Maybe these should be toggles, so people can pan the map around and
                 // see these in more detail?
                 "traffic signals" | "unprotected turns" => {
@@ -346,6 +385,143 @@ impl RouteDetails {
         None
     }
 
+    /// Describes the route as turn-by-turn directions with street names
+    fn directions(&self, app: &App) -> Vec<String> {
+        let map = &app.primary.map;
+        let mut lines = Vec::new();
+        for (idx, (path, _)) in self.paths.iter().enumerate() {
+            if idx > 0 {
+                lines.push(format!("Reach waypoint {}", idx + 1));
+            }
+            // The street currently being followed, and how far along it
+            let mut current: Option<(String, Distance)> = None;
+            let mut last_turn = None;
+            for step in path.get_steps() {
+                match step {
+                    PathStep::Lane(l) | PathStep::ContraflowLane(l) => {
+                        let name = map.get_parent(*l).get_name(app.opts.language.as_ref());
+                        let dist = step.as_traversable().get_polyline(map).length();
+                        if let Some((ref street, ref mut so_far)) = current {
+                            if *street == name {
+                                *so_far += dist;
+                                continue;
+                            }
+                        }
+                        if let Some((street, so_far)) = current.take() {
+                            lines.push(format!(
+                                "Ride {} along {}",
+                                so_far.to_string(&app.opts.units),
+                                street
+                            ));
+                        }
+                        lines.push(match last_turn.take() {
+                            None if idx == 0 => format!("Start on {}", name),
+                            Some(TurnType::Left) => format!("Turn left onto {}", name),
+                            Some(TurnType::Right) => format!("Turn right onto {}", name),
+                            Some(TurnType::UTurn) => format!("Make a U-turn onto {}", name),
+                            _ => format!("Continue onto {}", name),
+                        });
+                        current = Some((name, dist));
+                    }
+                    PathStep::Turn(t) | PathStep::ContraflowTurn(t) => {
+                        last_turn = Some(map.get_t(*t).turn_type);
+                    }
+                }
+            }
+            if let Some((street, so_far)) = current {
+                lines.push(format!(
+                    "Ride {} along {}",
+                    so_far.to_string(&app.opts.units),
+                    street
+                ));
+            }
+        }
+        if !lines.is_empty() {
+            lines.push("Arrive at your destination".to_string());
+        }
+        lines
+    }
+
+    /// Writes the route as GPX or KML, returning the path
+    fn export(&self, app: &App, gpx: bool) -> Result<String> {
+        let gps_bounds = app.primary.map.get_gps_bounds();
+        let directions = escape_xml(&self.directions(app).join("; "));
+        let segments: Vec<Vec<(f64, f64)>> = self
+            .paths
+            .iter()
+            .filter_map(|(_, maybe_pl)| maybe_pl.as_ref())
+            .map(|pl| {
+                pl.points()
+                    .iter()
+                    .map(|pt| {
+                        let gps = pt.to_gps(gps_bounds);
+                        (gps.x(), gps.y())
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut f = String::new();
+        writeln!(f, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        if gpx {
+            writeln!(
+                f,
+                "<gpx version=\"1.1\" creator=\"A/B Street\" \
+                 xmlns=\"http://www.topografix.com/GPX/1/1\">"
+            )?;
+            writeln!(f, "<trk>")?;
+            writeln!(
+                f,
+                "<name>{} route</name>",
+                escape_xml(self.preferences.name())
+            )?;
+            writeln!(f, "<desc>{}</desc>", directions)?;
+            for segment in segments {
+                writeln!(f, "<trkseg>")?;
+                for (lon, lat) in segment {
+                    writeln!(f, "<trkpt lat=\"{}\" lon=\"{}\"></trkpt>", lat, lon)?;
+                }
+                writeln!(f, "</trkseg>")?;
+            }
+            writeln!(f, "</trk>")?;
+            writeln!(f, "</gpx>")?;
+        } else {
+            writeln!(f, "<kml xmlns=\"http://www.opengis.net/kml/2.2\">")?;
+            writeln!(f, "<Document>")?;
+            writeln!(f, "<Placemark>")?;
+            writeln!(
+                f,
+                "<name>{} route</name>",
+                escape_xml(self.preferences.name())
+            )?;
+            writeln!(f, "<description>{}</description>", directions)?;
+            writeln!(f, "<MultiGeometry>")?;
+            for segment in segments {
+                writeln!(
+                    f,
+                    "<LineString><coordinates>{}</coordinates></LineString>",
+                    segment
+                        .into_iter()
+                        .map(|(lon, lat)| format!("{},{}", lon, lat))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                )?;
+            }
+            writeln!(f, "</MultiGeometry>")?;
+            writeln!(f, "</Placemark>")?;
+            writeln!(f, "</Document>")?;
+            writeln!(f, "</kml>")?;
+        }
+
+        let path = format!(
+            "route_{}.{}",
+            app.primary.map.get_name().as_filename(),
+            if gpx { "gpx" } else { "kml" }
+        );
+        fs_err::write(&path, f)?;
+        Ok(path)
+    }
+
     pub fn draw(&self, g: &mut GfxCtx, panel: &Panel) {
         if let Some((_, ref draw)) = self.hover_on_line_plot {
             g.redraw(draw);
@@ -468,9 +644,21 @@ fn make_detail_widget(
             },
             app.opts.units,
         ),
+        Widget::row(vec![
+            ctx.style().btn_outline.text("Directions").build_def(ctx),
+            ctx.style().btn_outline.text("Export to GPX").build_def(ctx),
+            ctx.style().btn_outline.text("Export to KML").build_def(ctx),
+        ]),
     ])
 }
 
+fn escape_xml(x: &str) -> String {
+    x.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 fn compare_routes(
     app: &App,
     main: &RouteStats,
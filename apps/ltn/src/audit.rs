@@ -0,0 +1,372 @@
+use geom::{Distance, Polygon};
+use map_model::raw::RestrictionType;
+use map_model::{Direction, LaneType, Map, Road, RoadID};
+use widgetry::tools::{Lasso, PopupMsg};
+use widgetry::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, Key, Line, Outcome, Panel, State, Text, TextExt,
+    Widget,
+};
+
+use crate::{colors, App, Transition};
+
+// OSM tags that restrict who can use a road
+const ACCESS_TAGS: [&str; 4] = ["access", "vehicle", "motor_vehicle", "motorcar"];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Category {
+    ModalFilter,
+    AccessRestriction,
+    OneWay,
+    TurnRestriction,
+}
+
+impl Category {
+    fn all() -> Vec<Category> {
+        vec![
+            Category::ModalFilter,
+            Category::AccessRestriction,
+            Category::OneWay,
+            Category::TurnRestriction,
+        ]
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Category::ModalFilter => "Modal filters",
+            Category::AccessRestriction => "Access restrictions",
+            Category::OneWay => "One-way streets",
+            Category::TurnRestriction => "Turn restrictions",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Category::ModalFilter => *colors::FILTER_OUTER,
+            Category::AccessRestriction => Color::PURPLE,
+            Category::OneWay => Color::BLUE,
+            Category::TurnRestriction => Color::ORANGE,
+        }
+    }
+}
+
+/// One existing restriction, as currently mapped in OSM
+struct Restriction {
+    category: Category,
+    road: RoadID,
+    details: String,
+}
+
+/// Inventory every existing restriction on roads whose center is inside the area. Edits made in
+/// this tool aren't included; this is the baseline before any changes.
+fn find_restrictions(map: &Map, area: &Polygon) -> Vec<Restriction> {
+    let mut results = Vec::new();
+    for road in map.all_roads() {
+        if !area.contains_pt(road.center_pts.middle()) {
+            continue;
+        }
+        let mut add = |category, details: String| {
+            results.push(Restriction {
+                category,
+                road: road.id,
+                details,
+            });
+        };
+
+        if crate::filters::is_existing_filter(map, road) {
+            add(
+                Category::ModalFilter,
+                "short cycleway connecting two driveable roads".to_string(),
+            );
+        }
+
+        let mut access = Vec::new();
+        for key in ACCESS_TAGS {
+            if let Some(value) = road.osm_tags.get(key) {
+                if value != "yes" {
+                    access.push(format!("{}={}", key, value));
+                }
+            }
+        }
+        if road.is_private() {
+            let allowed: Vec<String> = road
+                .access_restrictions
+                .allow_through_traffic
+                .iter()
+                .map(|c| format!("{:?}", c))
+                .collect();
+            access.push(if allowed.is_empty() {
+                "no through-traffic".to_string()
+            } else {
+                format!("through-traffic only for {}", allowed.join(", "))
+            });
+        }
+        if !access.is_empty() {
+            add(Category::AccessRestriction, access.join("; "));
+        }
+
+        if let Some(dir) = oneway_for_driving(road) {
+            add(
+                Category::OneWay,
+                format!(
+                    "one-way towards {}",
+                    map.get_i(if dir == Direction::Fwd {
+                        road.dst_i
+                    } else {
+                        road.src_i
+                    })
+                    .name(None, map)
+                ),
+            );
+        }
+
+        for (restriction, to) in &road.turn_restrictions {
+            add(
+                Category::TurnRestriction,
+                format!(
+                    "{} onto {}",
+                    match restriction {
+                        RestrictionType::BanTurns => "no turn",
+                        RestrictionType::OnlyAllowTurns => "only turn",
+                    },
+                    describe_road(map.get_r(*to))
+                ),
+            );
+        }
+        for (via, to) in &road.complicated_turn_restrictions {
+            add(
+                Category::TurnRestriction,
+                format!(
+                    "no turn onto {} via {}",
+                    describe_road(map.get_r(*to)),
+                    describe_road(map.get_r(*via))
+                ),
+            );
+        }
+    }
+    results.sort_by_key(|x| (x.category, x.road));
+    results
+}
+
+/// If every driving lane points the same way, returns that direction
+fn oneway_for_driving(road: &Road) -> Option<Direction> {
+    let mut dirs = road
+        .lanes
+        .iter()
+        .filter(|l| l.lane_type == LaneType::Driving)
+        .map(|l| l.dir);
+    let first = dirs.next()?;
+    if dirs.all(|dir| dir == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+fn describe_road(road: &Road) -> String {
+    format!(
+        "{} (way {})",
+        road.get_name(None),
+        road.orig_id.osm_way_id.0
+    )
+}
+
+fn to_csv(map: &Map, restrictions: &[Restriction]) -> String {
+    let mut lines = vec!["category,road,osm_way_id,details".to_string()];
+    for x in restrictions {
+        let road = map.get_r(x.road);
+        lines.push(format!(
+            "{},{},{},{}",
+            csv_field(x.category.label()),
+            csv_field(&road.get_name(None)),
+            road.orig_id.osm_way_id.0,
+            csv_field(&x.details)
+        ));
+    }
+    lines.join("\n")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Lets planners draw an area and get a baseline inventory of the restrictions that already
+/// exist there, before designing any changes.
+pub struct AuditRestrictions {
+    top_panel: Panel,
+    left_panel: Panel,
+    lasso: Option<Lasso>,
+    restrictions: Vec<Restriction>,
+    draw: Drawable,
+}
+
+impl AuditRestrictions {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let top_panel = crate::common::app_top_panel(ctx, app);
+        let left_panel = make_panel_for_lasso(ctx, &top_panel);
+        Box::new(AuditRestrictions {
+            top_panel,
+            left_panel,
+            lasso: Some(Lasso::new()),
+            restrictions: Vec::new(),
+            draw: Drawable::empty(ctx),
+        })
+    }
+
+    fn audit(&mut self, ctx: &mut EventCtx, app: &App, area: Polygon) {
+        self.restrictions = find_restrictions(&app.map, &area);
+
+        let mut batch = GeomBatch::new();
+        if let Ok(outline) = area.to_outline(Distance::meters(5.0)) {
+            batch.push(Color::BLACK, outline);
+        }
+        for x in &self.restrictions {
+            batch.push(
+                x.category.color().alpha(0.6),
+                app.map.get_r(x.road).get_thick_polygon(),
+            );
+        }
+        self.draw = ctx.upload(batch);
+        self.left_panel = make_panel(ctx, app, &self.top_panel, &self.restrictions);
+    }
+}
+
+impl State<App> for AuditRestrictions {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Some(ref mut lasso) = self.lasso {
+            if let Some(polygon) = lasso.event(ctx) {
+                self.lasso = None;
+                self.audit(ctx, app, polygon);
+            }
+            return Transition::Keep;
+        }
+
+        if let Some(t) = crate::common::handle_top_panel(ctx, app, &mut self.top_panel, help) {
+            return t;
+        }
+        if let Outcome::Clicked(x) = self.left_panel.event(ctx) {
+            match x.as_ref() {
+                "Browse neighborhoods" => {
+                    return Transition::Pop;
+                }
+                "Draw another area" => {
+                    self.lasso = Some(Lasso::new());
+                    self.restrictions.clear();
+                    self.draw = Drawable::empty(ctx);
+                    self.left_panel = make_panel_for_lasso(ctx, &self.top_panel);
+                }
+                "Export to CSV" => {
+                    let path = format!("existing_restrictions_{}.csv", app.map.get_name().map);
+                    let result = abstio::write_file(path, to_csv(&app.map, &self.restrictions));
+                    return Transition::Push(match result {
+                        Ok(path) => PopupMsg::new_state(
+                            ctx,
+                            "Restrictions exported",
+                            vec![format!("Data exported to {}", path)],
+                        ),
+                        Err(err) => {
+                            PopupMsg::new_state(ctx, "Export failed", vec![err.to_string()])
+                        }
+                    });
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        ctx.canvas_movement();
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.draw.draw(g);
+        self.top_panel.draw(g);
+        self.left_panel.draw(g);
+        if let Some(ref lasso) = self.lasso {
+            lasso.draw(g);
+        }
+        crate::onboarding::draw(g, app);
+    }
+}
+
+fn make_panel_for_lasso(ctx: &mut EventCtx, top_panel: &Panel) -> Panel {
+    crate::common::left_panel_builder(
+        ctx,
+        top_panel,
+        Widget::col(vec![
+            Line("Audit existing restrictions")
+                .small_heading()
+                .into_widget(ctx),
+            Text::from_all(vec![
+                Line("Click and drag").fg(ctx.style().text_hotkey_color),
+                Line(" to draw the area to audit"),
+            ])
+            .into_widget(ctx),
+        ]),
+    )
+    .build(ctx)
+}
+
+fn make_panel(
+    ctx: &mut EventCtx,
+    app: &App,
+    top_panel: &Panel,
+    restrictions: &[Restriction],
+) -> Panel {
+    let mut col = vec![
+        ctx.style()
+            .btn_back("Browse neighborhoods")
+            .hotkey(Key::Escape)
+            .build_def(ctx),
+        Line("Existing restrictions")
+            .small_heading()
+            .into_widget(ctx),
+        "As currently mapped in OpenStreetMap".text_widget(ctx),
+    ];
+    for category in Category::all() {
+        let count = restrictions
+            .iter()
+            .filter(|x| x.category == category)
+            .count();
+        col.push(
+            Line(format!("{}: {}", category.label(), count))
+                .fg(category.color())
+                .into_widget(ctx),
+        );
+    }
+
+    let mut txt = Text::new();
+    // The full list is in the export
+    for x in restrictions.iter().take(30) {
+        txt.add_line(Line(describe_road(app.map.get_r(x.road))).fg(x.category.color()));
+        txt.add_line(Line(format!("  {}", x.details)).secondary());
+    }
+    if restrictions.len() > 30 {
+        txt.add_line(format!("... and {} more", restrictions.len() - 30));
+    }
+    col.push(txt.wrap_to_pct(ctx, 20).into_widget(ctx));
+
+    col.push(Widget::row(vec![
+        ctx.style()
+            .btn_outline
+            .text("Export to CSV")
+            .disabled(restrictions.is_empty())
+            .build_def(ctx),
+        ctx.style()
+            .btn_outline
+            .text("Draw another area")
+            .build_def(ctx),
+    ]));
+
+    crate::common::left_panel_builder(ctx, top_panel, Widget::col(col)).build(ctx)
+}
+
+fn help() -> Vec<&'static str> {
+    vec![
+        "Draw an area to list the modal filters, access restrictions, one-way streets, and turn \
+         restrictions already mapped there.",
+        "Export the list to cross-reference the OSM way IDs.",
+    ]
+}
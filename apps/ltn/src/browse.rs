@@ -7,8 +7,8 @@ use synthpop::Scenario;
 use widgetry::mapspace::{ToggleZoomed, World, WorldOutcome};
 use widgetry::tools::PopupMsg;
 use widgetry::{
-    Choice, DrawBaselayer, EventCtx, GfxCtx, Key, Line, Outcome, Panel, State, Text, TextExt,
-    Toggle, Widget,
+    Choice, DrawBaselayer, Drawable, EventCtx, GeomBatch, GfxCtx, Key, Line, Outcome, Panel, State,
+    Text, TextExt, Toggle, Widget,
 };
 
 use crate::filters::auto::Heuristic;
@@ -21,6 +21,7 @@ pub struct BrowseNeighborhoods {
     draw_over_roads: ToggleZoomed,
     labels: DrawRoadLabels,
     draw_boundary_roads: ToggleZoomed,
+    draw_blockfinding_failures: Drawable,
 }
 
 impl BrowseNeighborhoods {
@@ -58,6 +59,20 @@ impl BrowseNeighborhoods {
                         .build_def(ctx),
                 ])
                 .section(ctx),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_outline
+                        .text("Compare neighborhoods")
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_outline
+                        .text("Audit existing restrictions")
+                        .build_def(ctx),
+                ]),
+                ctx.style()
+                    .btn_outline
+                    .text("Find existing rat-runs")
+                    .build_def(ctx),
                 Toggle::checkbox(ctx, "Expert mode", None, app.opts.dev),
                 if app.opts.dev {
                     Widget::col(vec![
@@ -69,12 +84,10 @@ impl BrowseNeighborhoods {
                                     ctx,
                                     "style",
                                     app.session.draw_neighborhood_style,
-                                    vec![
-                                        Choice::new("simple", Style::SimpleColoring),
-                                        Choice::new("cells", Style::Cells),
-                                        Choice::new("quietness", Style::Quietness),
-                                        Choice::new("all rat-runs", Style::RatRuns),
-                                    ],
+                                    Style::all()
+                                        .into_iter()
+                                        .map(|style| Choice::new(style.label(), style))
+                                        .collect(),
                                 ),
                             ]),
                             Toggle::checkbox(
@@ -85,6 +98,7 @@ impl BrowseNeighborhoods {
                             ),
                         ])
                         .section(ctx),
+                        blockfinding_failures_widget(ctx, app).section(ctx),
                         Widget::col(vec![
                             "Predict proposal impact".text_widget(ctx),
                             impact_widget(ctx, app),
@@ -117,6 +131,7 @@ impl BrowseNeighborhoods {
             draw_over_roads,
             labels: DrawRoadLabels::only_major_roads().light_background(),
             draw_boundary_roads: draw_boundary_roads(ctx, app),
+            draw_blockfinding_failures: draw_blockfinding_failures(ctx, app),
         })
     }
 }
@@ -141,9 +156,45 @@ impl State<App> for BrowseNeighborhoods {
                         }
                     });
                 }
+                "Export blockfinding failures" => {
+                    let result = crate::export::write_blockfinding_failures(app);
+                    return Transition::Push(match result {
+                        Ok(path) => PopupMsg::new_state(
+                            ctx,
+                            "Blockfinding failures exported",
+                            vec![
+                                format!("Data exported to {}", path),
+                                "Please attach this file when reporting a problem".to_string(),
+                            ],
+                        ),
+                        Err(err) => {
+                            PopupMsg::new_state(ctx, "Export failed", vec![err.to_string()])
+                        }
+                    });
+                }
+                "List blockfinding failures" => {
+                    return Transition::Push(PopupMsg::new_state(
+                        ctx,
+                        "Blockfinding failures",
+                        list_blockfinding_failures(app),
+                    ));
+                }
                 "Calculate" | "Show impact" => {
                     return Transition::Push(crate::impact::ShowResults::new_state(ctx, app));
                 }
+                "Compare neighborhoods" => {
+                    return Transition::Push(crate::metrics::NeighborhoodMetrics::new_state(
+                        ctx, app,
+                    ));
+                }
+                "Audit existing restrictions" => {
+                    return Transition::Push(crate::audit::AuditRestrictions::new_state(ctx, app));
+                }
+                "Find existing rat-runs" => {
+                    return Transition::Push(crate::rat_run_ranking::RatRunRanking::new_state(
+                        ctx, app,
+                    ));
+                }
                 "Plan a route" => {
                     return Transition::Push(crate::route_planner::RoutePlanner::new_state(
                         ctx, app,
@@ -192,9 +243,15 @@ impl State<App> for BrowseNeighborhoods {
                     if x == "highlight boundary roads" {
                         app.session.highlight_boundary_roads =
                             self.left_panel.is_checked("highlight boundary roads");
+                    } else if x == "show blockfinding failures" {
+                        app.session.show_blockfinding_failures =
+                            self.left_panel.is_checked("show blockfinding failures");
+                        return Transition::Keep;
                     } else {
                         app.session.draw_neighborhood_style =
                             self.left_panel.dropdown_value("style");
+                        app.ui_state.last_layer =
+                            Some(app.session.draw_neighborhood_style.label().to_string());
                     }
 
                     ctx.loading_screen("change style", |ctx, timer| {
@@ -207,6 +264,7 @@ impl State<App> for BrowseNeighborhoods {
         }
 
         if let WorldOutcome::ClickedObject(id) = self.world.event(ctx) {
+            crate::onboarding::record(app, crate::onboarding::Milestone::OpenedNeighborhood);
             return Transition::Push(crate::connectivity::Viewer::new_state(ctx, app, id));
         }
 
@@ -226,10 +284,14 @@ impl State<App> for BrowseNeighborhoods {
         if app.session.highlight_boundary_roads {
             self.draw_boundary_roads.draw(g);
         }
+        if app.session.show_blockfinding_failures {
+            g.redraw(&self.draw_blockfinding_failures);
+        }
         app.session.draw_all_filters.draw(g);
         if g.canvas.is_unzoomed() {
             self.labels.draw(g, app);
         }
+        crate::onboarding::draw(g, app);
     }
 }
 
@@ -358,6 +420,73 @@ pub fn draw_boundary_roads(ctx: &EventCtx, app: &App) -> ToggleZoomed {
     batch.build(ctx)
 }
 
+fn blockfinding_failures_widget(ctx: &mut EventCtx, app: &App) -> Widget {
+    let failures = app.session.partitioning.blockfinding_failures();
+    let unexpected = failures.iter().filter(|f| !f.is_expected()).count();
+    Widget::col(vec![
+        format!(
+            "{} areas couldn't be traced into blocks ({} expected near the map boundary)",
+            failures.len(),
+            failures.len() - unexpected
+        )
+        .text_widget(ctx),
+        Toggle::checkbox(
+            ctx,
+            "show blockfinding failures",
+            None,
+            app.session.show_blockfinding_failures,
+        ),
+        Widget::row(vec![
+            ctx.style()
+                .btn_outline
+                .text("List blockfinding failures")
+                .disabled(unexpected == 0)
+                .build_def(ctx),
+            ctx.style()
+                .btn_outline
+                .text("Export blockfinding failures")
+                .disabled(failures.is_empty())
+                .build_def(ctx),
+        ]),
+    ])
+}
+
+fn list_blockfinding_failures(app: &App) -> Vec<String> {
+    // Expected failures near the map boundary would drown out everything else
+    let failures: Vec<String> = app
+        .session
+        .partitioning
+        .blockfinding_failures()
+        .iter()
+        .filter(|f| !f.is_expected())
+        .map(|f| f.describe(&app.map))
+        .collect();
+    let max = 20;
+    let mut lines: Vec<String> = failures.iter().take(max).cloned().collect();
+    if failures.len() > max {
+        lines.push(format!(
+            "... and {} more. Export them to see everything.",
+            failures.len() - max
+        ));
+    }
+    lines
+}
+
+fn draw_blockfinding_failures(ctx: &EventCtx, app: &App) -> Drawable {
+    let mut batch = GeomBatch::new();
+    for failure in app.session.partitioning.blockfinding_failures() {
+        let color = if failure.is_expected() {
+            colors::EXPECTED_BLOCKFINDING_FAILURE
+        } else {
+            colors::BLOCKFINDING_FAILURE
+        };
+        for id in &failure.roads {
+            batch.push(color, id.get_outermost_lane(&app.map).get_thick_polygon());
+        }
+    }
+    ctx.upload(batch)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Style {
     SimpleColoring,
@@ -366,6 +495,34 @@ pub enum Style {
     RatRuns,
 }
 
+impl Style {
+    fn all() -> Vec<Style> {
+        vec![
+            Style::SimpleColoring,
+            Style::Cells,
+            Style::Quietness,
+            Style::RatRuns,
+        ]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Style::SimpleColoring => "simple",
+            Style::Cells => "cells",
+            Style::Quietness => "quietness",
+            Style::RatRuns => "all rat-runs",
+        }
+    }
+
+    /// Parses the result of `label`, falling back to the default style.
+    pub fn from_label(label: &str) -> Style {
+        Style::all()
+            .into_iter()
+            .find(|style| style.label() == label)
+            .unwrap_or(Style::SimpleColoring)
+    }
+}
+
 fn impact_widget(ctx: &EventCtx, app: &App) -> Widget {
     let map_name = app.map.get_name();
 
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+
+use geom::{Duration, PolyLine};
+use map_model::{
+    BuildingID, Direction, IntersectionID, Map, PathConstraints, PathRequest, PathfinderCache,
+    Position, NORMAL_LANE_THICKNESS,
+};
+use widgetry::mapspace::{ObjectID, ToggleZoomed, World, WorldOutcome};
+use widgetry::{
+    Color, DrawBaselayer, EventCtx, GfxCtx, Key, Line, Outcome, Panel, State, Text, TextExt, Widget,
+};
+
+use crate::{colors, App, Neighborhood, NeighborhoodID, Transition};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Access {
+    /// A driver can enter the neighborhood and reach the building
+    Reachable,
+    /// The building's cell isn't connected to the neighborhood boundary
+    Unreachable,
+    /// The building is on a road that already bans cars
+    CarFree,
+}
+
+/// Checks every building inside a neighborhood, based on the cell its driveway connects to.
+/// Returns the access and the index of the cell.
+fn check_buildings(
+    map: &Map,
+    neighborhood: &Neighborhood,
+) -> BTreeMap<BuildingID, (Access, Option<usize>)> {
+    let mut results = BTreeMap::new();
+    for b in map.all_buildings() {
+        let r = b.sidewalk().road;
+        if !neighborhood.orig_perimeter.interior.contains(&r) {
+            continue;
+        }
+        let pos = match b.driving_connection(map) {
+            Some((pos, _)) => pos,
+            None => {
+                results.insert(b.id, (Access::CarFree, None));
+                continue;
+            }
+        };
+        let lane = map.get_l(pos.lane());
+        // Cells measure distance along the road's center line
+        let dist = if lane.dir == Direction::Fwd {
+            pos.dist_along()
+        } else {
+            lane.length() - pos.dist_along()
+        };
+        let cell = neighborhood
+            .cells
+            .iter()
+            .position(|cell| {
+                cell.roads
+                    .get(&lane.id.road)
+                    .map(|interval| interval.start <= dist && dist <= interval.end)
+                    .unwrap_or(false)
+            })
+            .or_else(|| {
+                neighborhood
+                    .cells
+                    .iter()
+                    .position(|cell| cell.roads.contains_key(&lane.id.road))
+            });
+        let access = match cell.map(|idx| &neighborhood.cells[idx]) {
+            Some(cell) if cell.car_free => Access::CarFree,
+            Some(cell) if cell.is_disconnected() => Access::Unreachable,
+            Some(_) => Access::Reachable,
+            None => Access::Unreachable,
+        };
+        results.insert(b.id, (access, cell));
+    }
+    results
+}
+
+/// The route a driver would take from the neighborhood boundary to a building
+struct RouteProof {
+    border: IntersectionID,
+    duration: Duration,
+    pl: PolyLine,
+}
+
+/// Try entering from every border of the building's cell, respecting all modal filters, and
+/// return the quickest route.
+fn route_to_building(
+    app: &App,
+    neighborhood: &Neighborhood,
+    cell: usize,
+    b: BuildingID,
+    cache: &mut PathfinderCache,
+) -> Option<RouteProof> {
+    let map = &app.map;
+    let (end, r) = end_position(app, b)?;
+
+    let mut params = map.routing_params().clone();
+    app.session.modal_filters.update_routing_params(&mut params);
+    params.main_road_penalty = app.session.main_road_penalty;
+    // The building's own road may be filtered. end_position enters from the correct side.
+    params.avoid_roads.remove(&r);
+
+    let mut best: Option<RouteProof> = None;
+    for border in &neighborhood.cells[cell].borders {
+        for start in entrances(map, neighborhood, *border) {
+            let path = match cache.pathfind_with_params(
+                map,
+                PathRequest::vehicle(start, end, PathConstraints::Car),
+                params.clone(),
+            ) {
+                Some(path) => path,
+                None => continue,
+            };
+            let duration = path.get_cost();
+            if best
+                .as_ref()
+                .map(|x| duration >= x.duration)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            if let Some(pl) = path.into_v1(map).ok().and_then(|path| path.trace(map)) {
+                best = Some(RouteProof {
+                    border: *border,
+                    duration,
+                    pl,
+                });
+            }
+        }
+    }
+    best
+}
+
+/// Where to finish driving to a building. If the building's road has a filter, arrive on the
+/// lane that enters from the building's side of the filter.
+fn end_position(app: &App, b: BuildingID) -> Option<(Position, map_model::RoadID)> {
+    let map = &app.map;
+    let bldg = map.get_b(b);
+    let (pos, _) = bldg.driving_connection(map)?;
+    let road = map.get_parent(pos.lane());
+    if let Some(filter_dist) = app.session.modal_filters.roads.get(&road.id) {
+        let (dist, _) = road
+            .center_pts
+            .dist_along_of_point(bldg.sidewalk_pos.pt(map))?;
+        let want_dir = if dist < *filter_dist {
+            Direction::Fwd
+        } else {
+            Direction::Back
+        };
+        if let Some(lane) = road
+            .lanes
+            .iter()
+            .find(|l| l.dir == want_dir && PathConstraints::Car.can_use(l, map))
+        {
+            return Some((bldg.sidewalk_pos.equiv_pos(lane.id, map), road.id));
+        }
+    }
+    Some((pos, road.id))
+}
+
+/// Driving lanes on the neighborhood's perimeter that lead to a border intersection
+fn entrances(map: &Map, neighborhood: &Neighborhood, i: IntersectionID) -> Vec<Position> {
+    let mut results = Vec::new();
+    for r in &map.get_i(i).roads {
+        if !neighborhood.perimeter.contains(r) {
+            continue;
+        }
+        let road = map.get_r(*r);
+        for lane in &road.lanes {
+            let toward_i = if lane.dir == Direction::Fwd {
+                road.dst_i == i
+            } else {
+                road.src_i == i
+            };
+            if toward_i && PathConstraints::Car.can_use(lane, map) {
+                results.push(Position::start(lane.id));
+            }
+        }
+    }
+    results
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Obj(BuildingID);
+impl ObjectID for Obj {}
+
+/// Shows whether every building in a neighborhood can still be reached by car, and the route a
+/// driver would take to any building clicked.
+pub struct CarAccess {
+    top_panel: Panel,
+    left_panel: Panel,
+    neighborhood: Neighborhood,
+    world: World<Obj>,
+    buildings: BTreeMap<BuildingID, (Access, Option<usize>)>,
+    cache: PathfinderCache,
+    draw_route: ToggleZoomed,
+}
+
+impl CarAccess {
+    pub fn new_state(ctx: &mut EventCtx, app: &App, id: NeighborhoodID) -> Box<dyn State<App>> {
+        let neighborhood = Neighborhood::new(ctx, app, id);
+        let buildings = check_buildings(&app.map, &neighborhood);
+
+        let mut world = World::bounded(app.map.get_bounds());
+        for (b, (access, _)) in &buildings {
+            let color = match access {
+                Access::Reachable => Color::GREEN,
+                Access::Unreachable => Color::RED,
+                Access::CarFree => Color::grey(0.5),
+            };
+            world
+                .add(Obj(*b))
+                .hitbox(app.map.get_b(*b).polygon.clone())
+                .draw_color(color.alpha(0.5))
+                .hover_alpha(0.9)
+                .clickable()
+                .build(ctx);
+        }
+        world.initialize_hover(ctx);
+
+        let top_panel = crate::common::app_top_panel(ctx, app);
+        let left_panel = make_panel(ctx, &top_panel, &buildings, None);
+        Box::new(CarAccess {
+            top_panel,
+            left_panel,
+            neighborhood,
+            world,
+            buildings,
+            cache: PathfinderCache::new(),
+            draw_route: ToggleZoomed::empty(ctx),
+        })
+    }
+
+    fn show_route(&mut self, ctx: &mut EventCtx, app: &App, b: BuildingID) {
+        let (access, cell) = self.buildings[&b];
+        let route = if access == Access::Reachable {
+            route_to_building(app, &self.neighborhood, cell.unwrap(), b, &mut self.cache)
+        } else {
+            None
+        };
+
+        let mut txt = Text::from(Line(app.map.get_b(b).address.clone()).small_heading());
+        let mut draw = ToggleZoomed::builder();
+        match access {
+            Access::CarFree => {
+                txt.add_line("This building is on a road that already bans cars");
+            }
+            Access::Unreachable => {
+                txt.add_line(Line("Drivers can't reach this building").fg(Color::RED));
+            }
+            Access::Reachable => {
+                if let Some(route) = route {
+                    txt.add_line(format!(
+                        "Drivers enter the neighborhood at {}",
+                        app.map.get_i(route.border).name(None, &app.map)
+                    ));
+                    txt.add_line(format!(
+                        "{} from the boundary, respecting every filter",
+                        route.duration
+                    ));
+                    let shape = route.pl.make_polygons(5.0 * NORMAL_LANE_THICKNESS);
+                    let color = *colors::PLAN_ROUTE_AFTER;
+                    draw.unzoomed.push(color.alpha(0.8), shape.clone());
+                    draw.zoomed.push(color.alpha(0.5), shape);
+                } else {
+                    // The cell touches the boundary, but one-way streets or turn restrictions
+                    // might still prevent entering it
+                    txt.add_line(
+                        Line("No route found from the boundary, despite the cell touching it")
+                            .fg(Color::RED),
+                    );
+                }
+            }
+        }
+        self.draw_route = draw.build(ctx);
+        self.left_panel = make_panel(ctx, &self.top_panel, &self.buildings, Some(txt));
+    }
+}
+
+impl State<App> for CarAccess {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Some(t) = crate::common::handle_top_panel(ctx, app, &mut self.top_panel, help) {
+            return t;
+        }
+        if let Outcome::Clicked(x) = self.left_panel.event(ctx) {
+            if x == "Back to neighborhood" {
+                return Transition::Pop;
+            }
+            unreachable!()
+        }
+
+        if let WorldOutcome::ClickedObject(Obj(b)) = self.world.event(ctx) {
+            self.show_route(ctx, app, b);
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        crate::draw_with_layering(g, app, |g| self.world.draw(g));
+        g.redraw(&self.neighborhood.fade_irrelevant);
+        self.draw_route.draw(g);
+        app.session.draw_all_filters.draw(g);
+        self.top_panel.draw(g);
+        self.left_panel.draw(g);
+        if g.canvas.is_unzoomed() {
+            self.neighborhood.labels.draw(g, app);
+        }
+        crate::onboarding::draw(g, app);
+    }
+}
+
+fn make_panel(
+    ctx: &mut EventCtx,
+    top_panel: &Panel,
+    buildings: &BTreeMap<BuildingID, (Access, Option<usize>)>,
+    selected: Option<Text>,
+) -> Panel {
+    let count = |access| buildings.values().filter(|(x, _)| *x == access).count();
+    let unreachable = count(Access::Unreachable);
+    crate::common::left_panel_builder(
+        ctx,
+        top_panel,
+        Widget::col(vec![
+            ctx.style()
+                .btn_back("Back to neighborhood")
+                .hotkey(Key::Escape)
+                .build_def(ctx),
+            Line("Car access").small_heading().into_widget(ctx),
+            if unreachable == 0 {
+                Line("You can still drive to every building")
+                    .fg(Color::GREEN)
+                    .into_widget(ctx)
+            } else {
+                Line(format!("{} buildings can't be reached by car", unreachable))
+                    .fg(Color::RED)
+                    .into_widget(ctx)
+            },
+            format!(
+                "{} buildings reachable from the boundary",
+                count(Access::Reachable)
+            )
+            .text_widget(ctx),
+            format!(
+                "{} buildings on roads that already ban cars",
+                count(Access::CarFree)
+            )
+            .text_widget(ctx),
+            selected
+                .unwrap_or_else(|| Text::from("Click a building to see how drivers reach it"))
+                .wrap_to_pct(ctx, 15)
+                .into_widget(ctx)
+                .section(ctx),
+        ]),
+    )
+    .build(ctx)
+}
+
+fn help() -> Vec<&'static str> {
+    vec![
+        "Every building inside the neighborhood is checked for car access after your filters.",
+        "Green buildings can be reached from the boundary, and red ones can't.",
+        "",
+        "Click a building to see the quickest route a driver would take from the boundary.",
+    ]
+}
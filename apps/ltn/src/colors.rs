@@ -42,3 +42,6 @@ pub const RAT_RUN_PATH: Color = Color::RED;
 
 pub const BLOCK_IN_BOUNDARY: Color = Color::BLUE.alpha(0.5);
 pub const BLOCK_IN_FRONTIER: Color = Color::CYAN.alpha(0.2);
+
+pub const BLOCKFINDING_FAILURE: Color = Color::RED.alpha(0.8);
+pub const EXPECTED_BLOCKFINDING_FAILURE: Color = Color::grey(0.5).alpha(0.8);
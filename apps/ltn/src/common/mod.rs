@@ -1,5 +1,5 @@
 use geom::CornerRadii;
-use map_gui::tools::grey_out_map;
+use map_gui::tools::{grey_out_map, CommandPalette, GeoJsonOverlay};
 use widgetry::tools::{open_browser, PopupMsg};
 use widgetry::{
     lctrl, CornerRounding, EventCtx, GfxCtx, HorizontalAlignment, Key, Line, Outcome, Panel,
@@ -23,6 +23,12 @@ pub fn app_top_panel(ctx: &mut EventCtx, app: &App) -> Panel {
                 .centered_vert(),
             map_gui::tools::change_map_btn(ctx, app).centered_vert(),
             Widget::row(vec![
+                ctx.style()
+                    .btn_plain
+                    .text("Tutorial")
+                    .disabled(app.session.onboarding.is_some())
+                    .build_def(ctx)
+                    .centered_vert(),
                 ctx.style()
                     .btn_plain
                     .icon("system/assets/tools/search.svg")
@@ -51,10 +57,22 @@ pub fn app_top_panel(ctx: &mut EventCtx, app: &App) -> Panel {
 
 pub fn handle_top_panel<F: Fn() -> Vec<&'static str>>(
     ctx: &mut EventCtx,
-    app: &App,
+    app: &mut App,
     panel: &mut Panel,
     help: F,
 ) -> Option<Transition> {
+    if let Some(t) = crate::onboarding::event(ctx, app) {
+        return Some(t);
+    }
+    if let Some(state) =
+        GeoJsonOverlay::handle_event(ctx, &app.map, &mut app.session.geojson_overlay)
+    {
+        return Some(Transition::Push(state));
+    }
+    if ctx.input.pressed(lctrl(Key::P)) {
+        return Some(Transition::Push(command_palette(ctx, help())));
+    }
+
     if let Outcome::Clicked(x) = panel.event(ctx) {
         match x.as_ref() {
             "Home" => Some(Transition::Clear(vec![
@@ -74,6 +92,7 @@ pub fn handle_top_panel<F: Fn() -> Vec<&'static str>>(
                 ctx, app,
             ))),
             "help" => Some(Transition::Push(PopupMsg::new_state(ctx, "Help", help()))),
+            "Tutorial" => Some(crate::onboarding::start(ctx, app)),
             "about this tool" => Some(Transition::Push(About::new_state(ctx))),
             _ => unreachable!(),
         }
@@ -82,6 +101,98 @@ pub fn handle_top_panel<F: Fn() -> Vec<&'static str>>(
     }
 }
 
+#[derive(Clone)]
+enum Command {
+    BrowseNeighborhoods,
+    PlanRoute,
+    CompareNeighborhoods,
+    AuditRestrictions,
+    FindRatRuns,
+    PredictImpact,
+    Search,
+    Tutorial,
+    ChangeMap,
+    Home,
+    Help,
+    About,
+}
+
+/// Lists the actions from the top panel and every mode that can be reached from browsing
+/// neighborhoods, for the Ctrl+P palette. Switching modes starts over from browsing, so the stack
+/// of states doesn't keep growing.
+fn command_palette(ctx: &mut EventCtx, help: Vec<&'static str>) -> Box<dyn State<App>> {
+    let commands = vec![
+        ("Browse neighborhoods", Command::BrowseNeighborhoods),
+        ("Plan a route", Command::PlanRoute),
+        ("Compare neighborhoods", Command::CompareNeighborhoods),
+        ("Audit existing restrictions", Command::AuditRestrictions),
+        ("Find existing rat-runs", Command::FindRatRuns),
+        ("Predict proposal impact", Command::PredictImpact),
+        ("Search for a street", Command::Search),
+        ("Start the tutorial", Command::Tutorial),
+        ("Change map", Command::ChangeMap),
+        ("Go to the title screen", Command::Home),
+        ("Help", Command::Help),
+        ("About this tool", Command::About),
+    ]
+    .into_iter()
+    .map(|(label, cmd)| (label.to_string(), cmd))
+    .collect();
+
+    CommandPalette::new_state(
+        ctx,
+        commands,
+        Box::new(move |cmd, ctx, app| {
+            let mode = match cmd {
+                Command::BrowseNeighborhoods => None,
+                Command::PlanRoute => Some(crate::route_planner::RoutePlanner::new_state(ctx, app)),
+                Command::CompareNeighborhoods => {
+                    Some(crate::metrics::NeighborhoodMetrics::new_state(ctx, app))
+                }
+                Command::AuditRestrictions => {
+                    Some(crate::audit::AuditRestrictions::new_state(ctx, app))
+                }
+                Command::FindRatRuns => {
+                    Some(crate::rat_run_ranking::RatRunRanking::new_state(ctx, app))
+                }
+                Command::PredictImpact => Some(crate::impact::ShowResults::new_state(ctx, app)),
+                Command::Search => {
+                    return Transition::Push(map_gui::tools::Navigator::new_state(ctx, app));
+                }
+                Command::Tutorial => {
+                    return crate::onboarding::start(ctx, app);
+                }
+                Command::ChangeMap => {
+                    return Transition::Push(map_gui::tools::CityPicker::new_state(
+                        ctx,
+                        app,
+                        Box::new(|ctx, app| {
+                            Transition::Replace(BrowseNeighborhoods::new_state(ctx, app))
+                        }),
+                    ));
+                }
+                Command::Home => {
+                    return Transition::Clear(vec![map_gui::tools::TitleScreen::new_state(
+                        ctx,
+                        app,
+                        map_gui::tools::Executable::LTN,
+                        Box::new(|ctx, app, _| BrowseNeighborhoods::new_state(ctx, app)),
+                    )]);
+                }
+                Command::Help => {
+                    return Transition::Push(PopupMsg::new_state(ctx, "Help", help));
+                }
+                Command::About => {
+                    return Transition::Push(About::new_state(ctx));
+                }
+            };
+            let mut stack = vec![BrowseNeighborhoods::new_state(ctx, app)];
+            stack.extend(mode);
+            Transition::Clear(stack)
+        }),
+    )
+}
+
 pub fn left_panel_builder(ctx: &EventCtx, top_panel: &Panel, contents: Widget) -> PanelBuilder {
     let top_height = top_panel.panel_dims().height;
     Panel::new_builder(
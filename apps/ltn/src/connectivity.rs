@@ -72,6 +72,10 @@ impl Viewer {
                         )
                         .hotkey(Key::F)
                         .build_def(ctx),
+                    ctx.style()
+                        .btn_outline
+                        .text("Check car access")
+                        .build_def(ctx),
                     warning.text_widget(ctx),
                     Toggle::checkbox(ctx, "Expert mode", None, app.opts.dev),
                     if app.opts.dev {
@@ -146,6 +150,12 @@ impl State<App> for Viewer {
                         &self.neighborhood,
                         self.left_panel.center_of("Create filters along a shape"),
                     ));
+                } else if x == "Check car access" {
+                    return Transition::Push(crate::car_access::CarAccess::new_state(
+                        ctx,
+                        app,
+                        self.neighborhood.id,
+                    ));
                 } else if let Some(t) =
                     Tab::Connectivity.handle_action(ctx, app, x.as_ref(), self.neighborhood.id)
                 {
@@ -184,6 +194,15 @@ impl State<App> for Viewer {
         }
 
         let world_outcome = self.world.event(ctx);
+        if let Some(t) = crate::per_neighborhood::maybe_annotate(
+            ctx,
+            app,
+            &world_outcome,
+            Tab::Connectivity,
+            self.neighborhood.id,
+        ) {
+            return t;
+        }
         if crate::per_neighborhood::handle_world_outcome(ctx, app, world_outcome) {
             self.neighborhood = Neighborhood::new(ctx, app, self.neighborhood.id);
             self.update(ctx, app);
@@ -210,6 +229,7 @@ impl State<App> for Viewer {
         if g.canvas.is_unzoomed() {
             self.neighborhood.labels.draw(g, app);
         }
+        crate::onboarding::draw(g, app);
     }
 }
 
@@ -1,6 +1,7 @@
 use anyhow::Result;
 
 use geom::{PolyLine, Pt2D};
+use map_model::{BlockfindingFailure, EditNote};
 use widgetry::EventCtx;
 
 use crate::{App, Neighborhood};
@@ -9,39 +10,18 @@ use crate::{App, Neighborhood};
 pub fn write_geojson_file(ctx: &EventCtx, app: &App) -> Result<String> {
     let contents = geojson_string(ctx, app)?;
     let path = format!("ltn_{}.geojson", app.map.get_name().map);
+    abstio::write_file(path, contents)
+}
 
-    // TODO Refactor into map_gui or abstio and handle errors better
-    #[cfg(target_arch = "wasm32")]
-    {
-        use wasm_bindgen::JsCast;
-
-        let data: String = js_sys::JsString::from("data:text/json;charset=utf-8,")
-            .concat(&js_sys::encode_uri_component(&contents))
-            .into();
-
-        let window = web_sys::window().unwrap();
-        let document = window.document().unwrap();
-        let node = document
-            .create_element("a")
-            .unwrap()
-            .dyn_into::<web_sys::HtmlElement>()
-            .unwrap();
-        node.set_attribute("href", &data).unwrap();
-        node.set_attribute("download", &path).unwrap();
-        document.body().unwrap().append_child(&node).unwrap();
-        node.click();
-        node.remove();
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        use std::io::Write;
-
-        let mut file = fs_err::File::create(&path)?;
-        write!(file, "{}", contents)?;
-    }
-
-    Ok(path)
+/// Exports every area that couldn't be traced into blocks, so they can be reported. Returns the
+/// path where the file was written
+pub fn write_blockfinding_failures(app: &App) -> Result<String> {
+    let contents = BlockfindingFailure::to_geojson(
+        app.session.partitioning.blockfinding_failures(),
+        &app.map,
+    )?;
+    let path = format!("blockfinding_failures_{}.geojson", app.map.get_name().map);
+    abstio::write_file(path, contents)
 }
 
 fn geojson_string(ctx: &EventCtx, app: &App) -> Result<String> {
@@ -105,10 +85,11 @@ fn geojson_string(ctx: &EventCtx, app: &App) -> Result<String> {
             };
             feature.set_property("type", "road filter");
             feature.set_property("stroke", "red");
+            set_note(&mut feature, app.session.modal_filters.road_notes.get(r));
             features.push(feature);
         }
     }
-    for (_, filter) in &app.session.modal_filters.intersections {
+    for (i, filter) in &app.session.modal_filters.intersections {
         let pl = filter.geometry(map).to_polyline();
         let mut feature = Feature {
             bbox: None,
@@ -119,6 +100,10 @@ fn geojson_string(ctx: &EventCtx, app: &App) -> Result<String> {
         };
         feature.set_property("type", "diagonal filter");
         feature.set_property("stroke", "red");
+        set_note(
+            &mut feature,
+            app.session.modal_filters.intersection_notes.get(i),
+        );
         features.push(feature);
     }
 
@@ -151,3 +136,10 @@ fn geojson_string(ctx: &EventCtx, app: &App) -> Result<String> {
     let x = serde_json::to_string_pretty(&gj)?;
     Ok(x)
 }
+
+fn set_note(feature: &mut geojson::Feature, note: Option<&EditNote>) {
+    if let Some(note) = note {
+        feature.set_property("note", note.text.clone());
+        feature.set_property("photos", note.images.clone());
+    }
+}
@@ -21,11 +21,13 @@ pub fn transform_existing_filters(ctx: &EventCtx, app: &mut App, timer: &mut Tim
                     lt: LaneType::Driving,
                     dir: Direction::Fwd,
                     width: LaneSpec::typical_lane_widths(LaneType::Driving, &tags)[0].0,
+                    pocket: None,
                 },
                 LaneSpec {
                     lt: LaneType::Sidewalk,
                     dir: Direction::Fwd,
                     width: LaneSpec::typical_lane_widths(LaneType::Sidewalk, &tags)[0].0,
+                    pocket: None,
                 },
             ];
             let back = vec![
@@ -33,11 +35,13 @@ pub fn transform_existing_filters(ctx: &EventCtx, app: &mut App, timer: &mut Tim
                     lt: LaneType::Driving,
                     dir: Direction::Back,
                     width: LaneSpec::typical_lane_widths(LaneType::Driving, &tags)[0].0,
+                    pocket: None,
                 },
                 LaneSpec {
                     lt: LaneType::Sidewalk,
                     dir: Direction::Back,
                     width: LaneSpec::typical_lane_widths(LaneType::Sidewalk, &tags)[0].0,
+                    pocket: None,
                 },
             ];
             new.lanes_ltr = LaneSpec::assemble_ltr(fwd, back, app.map.get_config().driving_side);
@@ -82,45 +86,45 @@ pub fn transform_existing_filters(ctx: &EventCtx, app: &mut App, timer: &mut Tim
 }
 
 fn detect_filters(map: &Map) -> Vec<&Road> {
-    let mut filtered_roads = Vec::new();
-    'ROAD: for r in map.all_roads() {
-        // A/B Street currently treats most footpaths as cycle-focused. Don't look at the lane
-        // configuration; just look for this one tag. For example,
-        // https://www.openstreetmap.org/way/392685069 is a highway=footway that is NOT a filtered
-        // road.
-        if !r.osm_tags.is(osm::HIGHWAY, "cycleway") {
-            continue;
-        }
-        // A one-way cycleway is usually part of a complicated junction, like
-        // https://www.openstreetmap.org/way/1002273098
-        if r.osm_tags.is("oneway", "yes") {
-            continue;
-        }
-        // Long cycleways are probably not physically driveable. Like
-        // https://www.openstreetmap.org/way/174529602
-        if r.length() > Distance::meters(20.0) {
-            continue;
-        }
-        // Make sure both ends connect a driveable road, to avoid connections like
-        // https://www.openstreetmap.org/way/881433973
-        let mut num_degenerate = 0;
-        for i in [r.src_i, r.dst_i] {
-            let i = map.get_i(i);
-            if !i.roads.iter().any(|r| map.get_r(*r).is_driveable()) {
-                continue 'ROAD;
-            }
-            if i.is_degenerate() {
-                num_degenerate += 1;
-            }
+    map.all_roads()
+        .iter()
+        .filter(|r| is_existing_filter(map, r))
+        .collect()
+}
+
+/// Is this road really a modal filter modelled in OSM as a short cycleway?
+pub fn is_existing_filter(map: &Map, r: &Road) -> bool {
+    // A/B Street currently treats most footpaths as cycle-focused. Don't look at the lane
+    // configuration; just look for this one tag. For example,
+    // https://www.openstreetmap.org/way/392685069 is a highway=footway that is NOT a filtered
+    // road.
+    if !r.osm_tags.is(osm::HIGHWAY, "cycleway") {
+        return false;
+    }
+    // A one-way cycleway is usually part of a complicated junction, like
+    // https://www.openstreetmap.org/way/1002273098
+    if r.osm_tags.is("oneway", "yes") {
+        return false;
+    }
+    // Long cycleways are probably not physically driveable. Like
+    // https://www.openstreetmap.org/way/174529602
+    if r.length() > Distance::meters(20.0) {
+        return false;
+    }
+    // Make sure both ends connect a driveable road, to avoid connections like
+    // https://www.openstreetmap.org/way/881433973
+    let mut num_degenerate = 0;
+    for i in [r.src_i, r.dst_i] {
+        let i = map.get_i(i);
+        if !i.roads.iter().any(|r| map.get_r(*r).is_driveable()) {
+            return false;
         }
-        // Make sure the OSM way was split for the no-car section by looking for a degenerate
-        // intersection on at least one end. Avoid https://www.openstreetmap.org/node/4603472923,
-        // but still detect https://www.openstreetmap.org/way/51538523
-        if num_degenerate == 0 {
-            continue;
+        if i.is_degenerate() {
+            num_degenerate += 1;
         }
-
-        filtered_roads.push(r);
     }
-    filtered_roads
+    // Make sure the OSM way was split for the no-car section by looking for a degenerate
+    // intersection on at least one end. Avoid https://www.openstreetmap.org/node/4603472923,
+    // but still detect https://www.openstreetmap.org/way/51538523
+    num_degenerate != 0
 }
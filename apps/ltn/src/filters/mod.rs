@@ -6,11 +6,11 @@ use std::collections::{BTreeMap, BTreeSet};
 use serde::{Deserialize, Serialize};
 
 use geom::{Circle, Distance, Line};
-use map_model::{IntersectionID, Map, RoadID, RoutingParams, TurnID};
+use map_model::{EditNote, IntersectionID, Map, RoadID, RoutingParams, TurnID};
 use widgetry::mapspace::{DrawUnzoomedShapes, ToggleZoomed};
 use widgetry::{EventCtx, GeomBatch, GfxCtx};
 
-pub use self::existing::transform_existing_filters;
+pub use self::existing::{is_existing_filter, transform_existing_filters};
 use crate::{after_edit, colors, App};
 
 /// Stored in App session state. Before making any changes, call `before_edit`.
@@ -19,6 +19,12 @@ pub struct ModalFilters {
     /// For filters placed along a road, where is the filter located?
     pub roads: BTreeMap<RoadID, Distance>,
     pub intersections: BTreeMap<IntersectionID, DiagonalFilter>,
+    /// Why some filters were placed. A note outlives its filter being removed, in case the filter
+    /// is put back.
+    #[serde(default)]
+    pub road_notes: BTreeMap<RoadID, EditNote>,
+    #[serde(default)]
+    pub intersection_notes: BTreeMap<IntersectionID, EditNote>,
 
     /// Edit history is preserved recursively
     #[serde(skip_serializing, skip_deserializing)]
@@ -60,7 +66,11 @@ impl ModalFilters {
     /// the redundant piece of history. Returns true if the edit was indeed empty.
     pub fn cancel_empty_edit(&mut self) -> bool {
         if let Some(prev) = self.previous_version.take() {
-            if self.roads == prev.roads && self.intersections == prev.intersections {
+            if self.roads == prev.roads
+                && self.intersections == prev.intersections
+                && self.road_notes == prev.road_notes
+                && self.intersection_notes == prev.intersection_notes
+            {
                 self.previous_version = prev.previous_version;
                 return true;
             } else {
@@ -156,6 +166,58 @@ impl ModalFilters {
             intersections: self.intersections.clone(),
         }
     }
+
+    /// A copy of the current filters, without any edit history
+    pub fn without_history(&self) -> ModalFilters {
+        ModalFilters {
+            roads: self.roads.clone(),
+            intersections: self.intersections.clone(),
+            road_notes: self.road_notes.clone(),
+            intersection_notes: self.intersection_notes.clone(),
+            previous_version: Box::new(None),
+        }
+    }
+
+    /// Describe every filter added, removed, or moved since `before`.
+    pub fn describe_changes(&self, before: &ChangeKey, map: &Map) -> Vec<String> {
+        let mut changes = Vec::new();
+        for (r, dist) in &self.roads {
+            let name = map.get_r(*r).get_name(None);
+            match before.roads.get(r) {
+                None => changes.push(format!("Added a filter on {}", name)),
+                Some(old) if old != dist => changes.push(format!("Moved the filter on {}", name)),
+                Some(_) => {}
+            }
+        }
+        for r in before.roads.keys() {
+            if !self.roads.contains_key(r) {
+                changes.push(format!(
+                    "Removed the filter on {}",
+                    map.get_r(*r).get_name(None)
+                ));
+            }
+        }
+
+        for (i, filter) in &self.intersections {
+            let name = map.get_i(*i).name(None, map);
+            match before.intersections.get(i) {
+                None => changes.push(format!("Added a diagonal filter at {}", name)),
+                Some(old) if old != filter => {
+                    changes.push(format!("Changed the diagonal filter at {}", name))
+                }
+                Some(_) => {}
+            }
+        }
+        for i in before.intersections.keys() {
+            if !self.intersections.contains_key(i) {
+                changes.push(format!(
+                    "Removed the diagonal filter at {}",
+                    map.get_i(*i).name(None, map)
+                ));
+            }
+        }
+        changes
+    }
 }
 
 impl DiagonalFilter {
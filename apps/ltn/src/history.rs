@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+use geom::Duration;
+use map_gui::tools::PromptInput;
+use widgetry::{
+    DrawBaselayer, EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, State, Text,
+    TextExt, VerticalAlignment, Widget,
+};
+
+use crate::save::PreserveState;
+use crate::{after_edit, App, ModalFilters, Partitioning, Transition};
+
+/// One entry in a proposal's change log. Entries are only ever appended, even when rolling back
+/// to an earlier version.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChangeLogEntry {
+    pub author: String,
+    /// Seconds since the Unix epoch
+    pub time: u64,
+    pub changes: Vec<String>,
+}
+
+/// A named snapshot of a proposal that can be restored later
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProposalVersion {
+    pub name: String,
+    /// Seconds since the Unix epoch
+    pub time: u64,
+    partitioning: Partitioning,
+    modal_filters: ModalFilters,
+}
+
+/// Record changes to the current proposal in its log.
+pub fn log_change(app: &mut App, changes: Vec<String>) {
+    app.session.change_log.push(ChangeLogEntry {
+        author: current_author(),
+        time: now(),
+        changes,
+    });
+}
+
+/// Log any modal filters that changed since the last logged change.
+pub fn log_filter_changes(app: &mut App) {
+    let changes = app
+        .session
+        .modal_filters
+        .describe_changes(&app.session.logged_filters, &app.map);
+    if !changes.is_empty() {
+        log_change(app, changes);
+        app.session.logged_filters = app.session.modal_filters.get_change_key();
+    }
+}
+
+fn save_version(app: &mut App, name: String) {
+    log_change(app, vec![format!("Saved version \"{}\"", name)]);
+    app.session.versions.push(ProposalVersion {
+        name,
+        time: now(),
+        partitioning: app.session.partitioning.clone(),
+        modal_filters: app.session.modal_filters.without_history(),
+    });
+}
+
+fn restore_version(ctx: &EventCtx, app: &mut App, idx: usize) {
+    let version = app.session.versions[idx].clone();
+    log_change(
+        app,
+        vec![format!("Rolled back to version \"{}\"", version.name)],
+    );
+    app.session.partitioning = version.partitioning;
+    // Let the rollback be undone like any other edit
+    app.session.modal_filters.before_edit();
+    app.session.modal_filters.roads = version.modal_filters.roads;
+    app.session.modal_filters.intersections = version.modal_filters.intersections;
+    // This logs exactly which filters changed
+    after_edit(ctx, app);
+}
+
+fn current_author() -> String {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if let Ok(name) = std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+            return name;
+        }
+    }
+    "anonymous".to_string()
+}
+
+fn now() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+fn describe_time(time: u64) -> String {
+    format!(
+        "{} ago",
+        Duration::seconds(now().saturating_sub(time) as f64)
+    )
+}
+
+pub struct ProposalHistory {
+    panel: Panel,
+    preserve_state: Option<PreserveState>,
+}
+
+impl ProposalHistory {
+    pub fn new_state(
+        ctx: &mut EventCtx,
+        app: &App,
+        preserve_state: PreserveState,
+    ) -> Box<dyn State<App>> {
+        Box::new(ProposalHistory {
+            panel: make_panel(ctx, app),
+            preserve_state: Some(preserve_state),
+        })
+    }
+}
+
+impl State<App> for ProposalHistory {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Save this version" => {
+                    return Transition::Push(PromptInput::new_state(
+                        ctx,
+                        "Name this version",
+                        format!("version {}", app.session.versions.len() + 1),
+                        Box::new(|name, _, app| {
+                            save_version(app, name);
+                            Transition::Multi(vec![
+                                Transition::Pop,
+                                Transition::ModifyState(Box::new(|state, ctx, app| {
+                                    let state = state.downcast_mut::<ProposalHistory>().unwrap();
+                                    state.panel = make_panel(ctx, app);
+                                })),
+                            ])
+                        }),
+                    ));
+                }
+                x => {
+                    if let Some(idx) = x.strip_prefix("restore version ") {
+                        restore_version(ctx, app, idx.parse::<usize>().unwrap());
+                        // The partitioning may have changed, so recreate the previous state
+                        return Transition::Multi(vec![
+                            Transition::Pop,
+                            self.preserve_state
+                                .take()
+                                .unwrap()
+                                .switch_to_state(ctx, app),
+                        ]);
+                    }
+                    unreachable!()
+                }
+            }
+        }
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &App) {
+        self.panel.draw(g);
+    }
+}
+
+fn make_panel(ctx: &mut EventCtx, app: &App) -> Panel {
+    let mut col = vec![
+        Widget::row(vec![
+            Line("Proposal history").small_heading().into_widget(ctx),
+            ctx.style().btn_close_widget(ctx),
+        ]),
+        Line("Versions").small_heading().into_widget(ctx),
+    ];
+    if app.session.versions.is_empty() {
+        col.push("No versions saved yet".text_widget(ctx));
+    }
+    for (idx, version) in app.session.versions.iter().enumerate().rev() {
+        col.push(Widget::row(vec![
+            format!("{} ({})", version.name, describe_time(version.time))
+                .text_widget(ctx)
+                .centered_vert(),
+            ctx.style()
+                .btn_outline
+                .text("Restore")
+                .build_widget(ctx, &format!("restore version {}", idx)),
+        ]));
+    }
+    col.push(
+        ctx.style()
+            .btn_solid_primary
+            .text("Save this version")
+            .build_def(ctx),
+    );
+
+    col.push(Line("Change log").small_heading().into_widget(ctx));
+    if app.session.change_log.is_empty() {
+        col.push("No changes yet".text_widget(ctx));
+    }
+    // Only show the most recent changes; the full log is saved with the proposal
+    for entry in app.session.change_log.iter().rev().take(10) {
+        let mut txt = Text::from(
+            Line(format!("{} by {}", describe_time(entry.time), entry.author)).secondary(),
+        );
+        for change in &entry.changes {
+            txt.add_line(format!("- {}", change));
+        }
+        col.push(txt.wrap_to_pct(ctx, 30).into_widget(ctx));
+    }
+
+    Panel::new_builder(Widget::col(col))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::Center)
+        .build(ctx)
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeSet;
+
+use geom::Duration;
+use map_model::{
+    ControlTrafficSignal, DirectedRoadID, Intersection, IntersectionID, Map, PathConstraints,
+    TurnPriority,
+};
+use widgetry::mapspace::{ObjectID, World};
+use widgetry::tools::PopupMsg;
+use widgetry::{
+    Color, EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, State, Text, TextExt,
+    VerticalAlignment, Widget,
+};
+
+use crate::{App, Transition};
+
+// Traffic engineers usually assume the busiest hour carries about this share of a day's traffic.
+// The impact counts cover the whole departure time window, which defaults to the full day.
+const PEAK_HOUR_SHARE: f64 = 0.1;
+// How many vehicles per hour a single lane can discharge with a permanent green light
+const SATURATION_FLOW_PER_LANE: f64 = 1800.0;
+// Vehicles per hour through one lane that has to stop first
+const STOP_SIGN_FLOW_PER_LANE: f64 = 500.0;
+// Flag junctions whose volume is at least this fraction of their estimated capacity
+const NEAR_CAPACITY: f64 = 0.85;
+
+/// Estimated peak hour demand and capacity of one junction, before and after filters
+pub struct JunctionCapacity {
+    pub i: IntersectionID,
+    pub before_per_hour: f64,
+    pub after_per_hour: f64,
+    pub capacity_per_hour: f64,
+}
+
+impl JunctionCapacity {
+    pub fn ratio_before(&self) -> f64 {
+        self.before_per_hour / self.capacity_per_hour
+    }
+
+    pub fn ratio_after(&self) -> f64 {
+        self.after_per_hour / self.capacity_per_hour
+    }
+
+    /// Did the filters push this junction near or over capacity, when it wasn't before?
+    pub fn is_new_bottleneck(&self) -> bool {
+        self.ratio_before() < NEAR_CAPACITY
+    }
+
+    fn describe(&self, map: &Map) -> String {
+        format!(
+            "{}: {:.0}% of capacity before, {:.0}% after",
+            map.get_i(self.i).name(None, map),
+            100.0 * self.ratio_before(),
+            100.0 * self.ratio_after()
+        )
+    }
+}
+
+/// Look at every junction along the boundary of a neighborhood, where traffic diverted by the
+/// filters will concentrate. Returns the junctions where the filters increase traffic to near or
+/// beyond the estimated capacity, with the most overloaded first.
+pub fn check_boundary_junctions(app: &App) -> Vec<JunctionCapacity> {
+    let map = &app.map;
+    let counts_a = &app.session.impact.compare_counts.counts_a;
+    let counts_b = &app.session.impact.compare_counts.counts_b;
+
+    let mut boundary_junctions = BTreeSet::new();
+    for (block, _) in app.session.partitioning.all_neighborhoods().values() {
+        for id in &block.perimeter.roads {
+            let road = map.get_r(id.road);
+            boundary_junctions.insert(road.src_i);
+            boundary_junctions.insert(road.dst_i);
+        }
+    }
+
+    let mut results = Vec::new();
+    for i in boundary_junctions {
+        let before = counts_a.per_intersection.get(i);
+        let after = counts_b.per_intersection.get(i);
+        if after <= before || map.get_i(i).is_border() {
+            continue;
+        }
+        let capacity_per_hour = estimate_capacity(map, map.get_i(i));
+        if capacity_per_hour == 0.0 {
+            continue;
+        }
+        let junction = JunctionCapacity {
+            i,
+            before_per_hour: PEAK_HOUR_SHARE * before as f64,
+            after_per_hour: PEAK_HOUR_SHARE * after as f64,
+            capacity_per_hour,
+        };
+        if junction.ratio_after() >= NEAR_CAPACITY {
+            results.push(junction);
+        }
+    }
+    results.sort_by(|a, b| b.ratio_after().partial_cmp(&a.ratio_after()).unwrap());
+    results
+}
+
+/// Add up how many vehicles per hour each approach lane can send through the junction, based on
+/// how it's controlled.
+fn estimate_capacity(map: &Map, i: &Intersection) -> f64 {
+    let mut total = 0.0;
+    for l in i.get_incoming_lanes(map, PathConstraints::Car) {
+        let from = map.get_l(l).get_directed_parent();
+        total += if let Some(signal) = map.maybe_get_traffic_signal(i.id) {
+            SATURATION_FLOW_PER_LANE * green_fraction(signal, i, from)
+        } else if let Some(stop_sign) = map.maybe_get_stop_sign(i.id) {
+            if stop_sign
+                .roads
+                .get(&from.road)
+                .map(|r| r.must_stop)
+                .unwrap_or(false)
+            {
+                STOP_SIGN_FLOW_PER_LANE
+            } else {
+                SATURATION_FLOW_PER_LANE
+            }
+        } else {
+            SATURATION_FLOW_PER_LANE
+        };
+    }
+    total
+}
+
+/// What fraction of the signal's cycle can traffic from one approach move? Permitted movements
+/// only count half, since they yield to other traffic.
+fn green_fraction(signal: &ControlTrafficSignal, i: &Intersection, from: DirectedRoadID) -> f64 {
+    let cycle = signal.simple_cycle_duration();
+    if cycle == Duration::ZERO {
+        return 0.0;
+    }
+    let mut green = Duration::ZERO;
+    for stage in &signal.stages {
+        let mut best = TurnPriority::Banned;
+        for m in i.movements.keys() {
+            if m.from != from || m.crosswalk {
+                continue;
+            }
+            match stage.get_priority_of_movement(*m) {
+                TurnPriority::Protected => {
+                    best = TurnPriority::Protected;
+                }
+                TurnPriority::Yield if best == TurnPriority::Banned => {
+                    best = TurnPriority::Yield;
+                }
+                _ => {}
+            }
+        }
+        green += match best {
+            TurnPriority::Protected => stage.stage_type.simple_duration(),
+            TurnPriority::Yield => 0.5 * stage.stage_type.simple_duration(),
+            TurnPriority::Banned => Duration::ZERO,
+        };
+    }
+    green / cycle
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Obj(IntersectionID);
+impl ObjectID for Obj {}
+
+pub struct ShowJunctionCapacity {
+    panel: Panel,
+    world: World<Obj>,
+}
+
+impl ShowJunctionCapacity {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let results = check_boundary_junctions(app);
+        if results.is_empty() {
+            return PopupMsg::new_state(
+                ctx,
+                "No bottlenecks",
+                vec!["No junctions along neighborhood boundaries are pushed near capacity"],
+            );
+        }
+
+        let map = &app.map;
+        let mut world = World::bounded(map.get_bounds());
+        let num_new = results.iter().filter(|j| j.is_new_bottleneck()).count();
+        for junction in &results {
+            let color = if junction.is_new_bottleneck() {
+                Color::RED
+            } else {
+                Color::ORANGE
+            };
+            let mut txt = Text::from(Line(map.get_i(junction.i).name(None, map)).small_heading());
+            txt.add_line(format!(
+                "Estimated capacity: {:.0} vehicles per hour",
+                junction.capacity_per_hour
+            ));
+            txt.add_line(format!(
+                "Peak hour before filters: {:.0} ({:.0}%)",
+                junction.before_per_hour,
+                100.0 * junction.ratio_before()
+            ));
+            txt.add_line(format!(
+                "Peak hour after filters: {:.0} ({:.0}%)",
+                junction.after_per_hour,
+                100.0 * junction.ratio_after()
+            ));
+            world
+                .add(Obj(junction.i))
+                .hitbox(map.get_i(junction.i).polygon.clone())
+                .draw_color(color.alpha(0.8))
+                .hover_alpha(0.5)
+                .tooltip(txt)
+                .build(ctx);
+        }
+        world.initialize_hover(ctx);
+
+        let mut col = vec![
+            Widget::row(vec![
+                Line("Junction capacity").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(Line(
+                "Junctions along neighborhood boundaries where the filters push peak hour \
+                 traffic near or beyond the estimated capacity",
+            ))
+            .wrap_to_pct(ctx, 20)
+            .into_widget(ctx),
+            Line(format!("{} new bottlenecks", num_new))
+                .fg(Color::RED)
+                .into_widget(ctx),
+            Line(format!(
+                "{} junctions already near capacity get worse",
+                results.len() - num_new
+            ))
+            .fg(Color::ORANGE)
+            .into_widget(ctx),
+        ];
+        for junction in results.iter().take(5) {
+            col.push(junction.describe(map).text_widget(ctx));
+        }
+        col.push(
+            Text::from(Line(
+                "Capacity is a rough estimate from lanes and signal timing or stop signs",
+            ))
+            .wrap_to_pct(ctx, 20)
+            .into_widget(ctx),
+        );
+
+        Box::new(ShowJunctionCapacity {
+            panel: Panel::new_builder(Widget::col(col))
+                .aligned(HorizontalAlignment::Center, VerticalAlignment::Top)
+                .build(ctx),
+            world,
+        })
+    }
+}
+
+impl State<App> for ShowJunctionCapacity {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut App) -> Transition {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            if x == "close" {
+                return Transition::Pop;
+            }
+        }
+        self.world.event(ctx);
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.world.draw(g);
+        app.session.draw_all_filters.draw(g);
+        self.panel.draw(g);
+    }
+}
@@ -1,3 +1,4 @@
+mod junctions;
 mod ui;
 
 use std::collections::BTreeSet;
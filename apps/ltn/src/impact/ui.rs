@@ -9,6 +9,7 @@ use widgetry::{
     Outcome, Panel, Slider, State, Text, TextExt, Toggle, VerticalAlignment, Widget,
 };
 
+use crate::impact::junctions::ShowJunctionCapacity;
 use crate::impact::{end_of_day, Filters, Impact};
 use crate::{colors, App, BrowseNeighborhoods, Transition};
 
@@ -63,6 +64,7 @@ impl ShowResults {
             // TODO Dropdown for the scenario, and explain its source/limitations
             app.session.impact.filters.to_panel(ctx, app),
             app.session.impact.compare_counts.get_panel_widget(ctx).named("compare counts"),
+            ctx.style().btn_outline.text("Check junction capacity").build_def(ctx),
             ctx.style().btn_outline.text("Save before/after counts to files").build_def(ctx),
         ]);
         let top_panel = crate::common::app_top_panel(ctx, app);
@@ -86,6 +88,9 @@ impl State<App> for ShowResults {
                     // loading
                     return Transition::Replace(BrowseNeighborhoods::new_state(ctx, app));
                 }
+                "Check junction capacity" => {
+                    return Transition::Push(ShowJunctionCapacity::new_state(ctx, app));
+                }
                 "Save before/after counts to files" => {
                     let path1 = "counts_a.json";
                     let path2 = "counts_b.json";
@@ -160,6 +165,7 @@ impl State<App> for ShowResults {
 
         self.top_panel.draw(g);
         self.left_panel.draw(g);
+        crate::onboarding::draw(g, app);
     }
 }
 
@@ -2,11 +2,13 @@
 
 use structopt::StructOpt;
 
+use abstio::MapName;
 use abstutil::Timer;
-use widgetry::{EventCtx, GfxCtx, Settings};
+use map_gui::tools::GeoJsonOverlay;
+use widgetry::{Cached, EventCtx, GfxCtx, Settings};
 
 pub use browse::BrowseNeighborhoods;
-use filters::Toggle3Zoomed;
+use filters::{ChangeKey, Toggle3Zoomed};
 pub use filters::{DiagonalFilter, ModalFilters};
 pub use neighborhood::{Cell, DistanceInterval, Neighborhood};
 pub use partition::{NeighborhoodID, Partitioning};
@@ -16,17 +18,23 @@ extern crate anyhow;
 #[macro_use]
 extern crate log;
 
+mod audit;
 mod browse;
+mod car_access;
 mod colors;
 mod common;
 mod connectivity;
 mod draw_cells;
 mod export;
 mod filters;
+mod history;
 mod impact;
+mod metrics;
 mod neighborhood;
+mod onboarding;
 mod partition;
 mod per_neighborhood;
+mod rat_run_ranking;
 mod rat_run_viewer;
 mod rat_runs;
 mod route_planner;
@@ -61,27 +69,40 @@ fn run(mut settings: Settings) {
         .update_widgetry_settings(settings)
         .canvas_settings(opts.canvas_settings.clone());
     widgetry::run(settings, move |ctx| {
+        let ui_state = map_gui::tools::UiState::load("ltn");
         let session = Session {
             proposal_name: None,
             partitioning: Partitioning::empty(),
             modal_filters: ModalFilters::default(),
+            change_log: Vec::new(),
+            versions: Vec::new(),
+            logged_filters: ChangeKey::default(),
 
             alt_proposals: save::AltProposals::new(),
             draw_all_filters: Toggle3Zoomed::empty(ctx),
             impact: impact::Impact::empty(ctx),
 
             highlight_boundary_roads: false,
-            draw_neighborhood_style: browse::Style::SimpleColoring,
+            show_blockfinding_failures: false,
+            draw_neighborhood_style: ui_state
+                .last_layer
+                .as_deref()
+                .map(browse::Style::from_label)
+                .unwrap_or(browse::Style::SimpleColoring),
             draw_cells_as_areas: true,
             heuristic: filters::auto::Heuristic::SplitCells,
             main_road_penalty: 1.0,
+            camera_enforcement: false,
+            geojson_overlay: Cached::new(),
+            through_traffic: Cached::new(),
+            onboarding: None,
 
             current_trip_name: None,
         };
-        map_gui::SimpleApp::new(
+        let (mut app, states) = map_gui::SimpleApp::new(
             ctx,
             opts,
-            args.app_args.map_name(),
+            args.app_args.map_name_for(&ui_state),
             args.app_args.cam,
             session,
             move |ctx, app| {
@@ -105,7 +126,9 @@ fn run(mut settings: Settings) {
                 }
                 states
             },
-        )
+        );
+        app.ui_state = ui_state;
+        (app, states)
     });
 }
 
@@ -130,6 +153,10 @@ pub struct Session {
     pub proposal_name: Option<String>,
     pub partitioning: Partitioning,
     pub modal_filters: ModalFilters,
+    pub change_log: Vec<history::ChangeLogEntry>,
+    pub versions: Vec<history::ProposalVersion>,
+    // The filters as of the last change log entry
+    pub logged_filters: ChangeKey,
 
     pub alt_proposals: save::AltProposals,
     pub draw_all_filters: Toggle3Zoomed,
@@ -138,12 +165,21 @@ pub struct Session {
     // Remember form settings in different tabs.
     // Browse neighborhoods:
     pub highlight_boundary_roads: bool,
+    pub show_blockfinding_failures: bool,
     pub draw_neighborhood_style: browse::Style,
     // Connectivity:
     pub draw_cells_as_areas: bool,
     pub heuristic: filters::auto::Heuristic,
     // Pathfinding
     pub main_road_penalty: f64,
+    // Penalize driving through any neighborhood, as if cameras enforced it
+    pub camera_enforcement: bool,
+    // A GeoJSON file dropped onto the window, drawn as a reference layer
+    pub geojson_overlay: Cached<MapName, GeoJsonOverlay>,
+    // Through-traffic on local streets in the travel demand model, before any new filters
+    pub through_traffic: Cached<MapName, Vec<synthpop::ThroughTraffic>>,
+    // The interactive tutorial, if it's running
+    pub onboarding: Option<onboarding::Onboarding>,
 
     current_trip_name: Option<String>,
 }
@@ -179,10 +215,14 @@ fn draw_with_layering<F: Fn(&mut GfxCtx)>(g: &mut GfxCtx, app: &App, custom: F)
             }
         }
     }
+
+    GeoJsonOverlay::draw_cached(g, &app.map, &app.session.geojson_overlay);
 }
 
 pub fn after_edit(ctx: &EventCtx, app: &mut App) {
     app.session.draw_all_filters = app.session.modal_filters.draw(ctx, &app.map);
+    history::log_filter_changes(app);
+    onboarding::record(app, onboarding::Milestone::EditedFilters);
 }
 
 pub fn clear_current_proposal(ctx: &EventCtx, app: &mut App, timer: &mut Timer) {
@@ -190,6 +230,10 @@ pub fn clear_current_proposal(ctx: &EventCtx, app: &mut App, timer: &mut Timer)
     // Reset this first. transform_existing_filters will fill some out.
     app.session.modal_filters = ModalFilters::default();
     crate::filters::transform_existing_filters(ctx, app, timer);
+    // Filters that already exist aren't part of the proposal's changes
+    app.session.change_log.clear();
+    app.session.versions.clear();
+    app.session.logged_filters = app.session.modal_filters.get_change_key();
     app.session.partitioning = Partitioning::seed_using_heuristics(app, timer);
     app.session.draw_all_filters = app.session.modal_filters.draw(ctx, &app.map);
 }
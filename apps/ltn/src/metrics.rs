@@ -0,0 +1,221 @@
+use abstutil::prettyprint_usize;
+use map_model::BuildingType;
+use widgetry::table::{Col, Filter, Table};
+use widgetry::{
+    DrawBaselayer, EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, State, Text,
+    TextExt, VerticalAlignment, Widget,
+};
+
+use crate::{colors, App, Neighborhood, NeighborhoodID, Transition};
+
+/// Lists every neighborhood in the city with some summary metrics, so users can triage where to
+/// work next.
+pub struct NeighborhoodMetrics {
+    table: Table<App, Entry, ()>,
+    panel: Panel,
+    // Indexed by the row labels
+    ids: Vec<NeighborhoodID>,
+}
+
+struct Entry {
+    // Rows are labelled with this index, and it's also displayed
+    idx: usize,
+    area_square_meters: f64,
+    population: usize,
+    existing_filters: usize,
+    rat_runs: usize,
+    new_filters: usize,
+}
+
+impl NeighborhoodMetrics {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        let ids: Vec<NeighborhoodID> = app
+            .session
+            .partitioning
+            .all_neighborhoods()
+            .keys()
+            .cloned()
+            .collect();
+        let entries = ctx.loading_screen("calculate neighborhood metrics", |ctx, timer| {
+            timer.start_iter("measure neighborhoods", ids.len());
+            ids.iter()
+                .enumerate()
+                .map(|(idx, id)| {
+                    timer.next();
+                    let neighborhood = Neighborhood::new(ctx, app, *id);
+                    let rat_runs = crate::rat_runs::find_rat_runs(app, &neighborhood, timer);
+                    measure(app, &neighborhood, idx, rat_runs.paths.len())
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let mut table: Table<App, Entry, ()> = Table::new(
+            "neighborhood_metrics",
+            entries,
+            Box::new(|x| x.idx.to_string()),
+            "Rat-runs",
+            Filter::empty(),
+        );
+        table.column(
+            "Neighborhood",
+            Box::new(|ctx, _, x| Text::from(format!("#{}", x.idx + 1)).render(ctx)),
+            Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.idx))),
+        );
+        table.column(
+            "Area",
+            Box::new(|ctx, app, x| {
+                Text::from(describe_area(app, x.area_square_meters)).render(ctx)
+            }),
+            Col::Sortable(Box::new(|rows| {
+                rows.sort_by(|a, b| {
+                    a.area_square_meters
+                        .partial_cmp(&b.area_square_meters)
+                        .unwrap()
+                })
+            })),
+        );
+        table.column(
+            "Population",
+            Box::new(|ctx, _, x| Text::from(prettyprint_usize(x.population)).render(ctx)),
+            Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.population))),
+        );
+        table.column(
+            "Existing filters",
+            Box::new(|ctx, _, x| Text::from(prettyprint_usize(x.existing_filters)).render(ctx)),
+            Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.existing_filters))),
+        );
+        table.column(
+            "Rat-runs",
+            Box::new(|ctx, _, x| Text::from(prettyprint_usize(x.rat_runs)).render(ctx)),
+            Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.rat_runs))),
+        );
+        table.column(
+            "Proposal",
+            Box::new(|ctx, _, x| {
+                Text::from(if x.new_filters == 0 {
+                    "Untouched".to_string()
+                } else {
+                    format!("{} new filters", prettyprint_usize(x.new_filters))
+                })
+                .render(ctx)
+            }),
+            Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.new_filters))),
+        );
+
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Neighborhood metrics")
+                    .small_heading()
+                    .into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            "Click a neighborhood to edit filters".text_widget(ctx),
+            table.render(ctx, app),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::TopInset)
+        .build(ctx);
+
+        Box::new(NeighborhoodMetrics { table, panel, ids })
+    }
+}
+
+impl State<App> for NeighborhoodMetrics {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => {
+                if self.table.clicked(&x) {
+                    self.table.replace_render(ctx, app, &mut self.panel)
+                } else if x == "close" {
+                    return Transition::Pop;
+                } else if let Ok(idx) = x.parse::<usize>() {
+                    return Transition::Replace(crate::connectivity::Viewer::new_state(
+                        ctx,
+                        app,
+                        self.ids[idx],
+                    ));
+                } else {
+                    unreachable!()
+                }
+            }
+            Outcome::Changed(_) => {
+                self.table.panel_changed(&self.panel);
+                self.table.replace_render(ctx, app, &mut self.panel)
+            }
+            _ => {}
+        }
+
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        if let Some(idx) = self
+            .panel
+            .currently_hovering()
+            .and_then(|x| x.parse::<usize>().ok())
+        {
+            let block = app.session.partitioning.neighborhood_block(self.ids[idx]);
+            g.draw_polygon(colors::OUTLINE.alpha(0.5), block.polygon.clone());
+        }
+        self.panel.draw(g);
+    }
+}
+
+fn measure(app: &App, neighborhood: &Neighborhood, idx: usize, rat_runs: usize) -> Entry {
+    let map = &app.map;
+    let block = app.session.partitioning.neighborhood_block(neighborhood.id);
+
+    let bounds = block.polygon.get_bounds();
+    let mut population = 0;
+    for b in map.all_buildings() {
+        let pt = b.polygon.center();
+        if !bounds.contains(pt) || !block.polygon.contains_pt(pt) {
+            continue;
+        }
+        population += match b.bldg_type {
+            BuildingType::Residential { num_residents, .. } => num_residents,
+            BuildingType::ResidentialCommercial(num_residents, _) => num_residents,
+            BuildingType::Commercial(_) | BuildingType::Empty => 0,
+        };
+    }
+
+    let mut existing_filters = 0;
+    let mut new_filters = 0;
+    for r in &neighborhood.orig_perimeter.interior {
+        if !app.session.modal_filters.roads.contains_key(r) {
+            continue;
+        }
+        if crate::filters::is_existing_filter(map, map.get_r(*r)) {
+            existing_filters += 1;
+        } else {
+            new_filters += 1;
+        }
+    }
+    new_filters += neighborhood
+        .interior_intersections
+        .iter()
+        .filter(|i| app.session.modal_filters.intersections.contains_key(i))
+        .count();
+
+    Entry {
+        idx,
+        area_square_meters: block.polygon.area(),
+        population,
+        existing_filters,
+        rat_runs,
+        new_filters,
+    }
+}
+
+fn describe_area(app: &App, square_meters: f64) -> String {
+    if app.opts.units.metric {
+        format!("{:.2} km²", square_meters / 1_000_000.0)
+    } else {
+        format!("{:.2} mi²", square_meters / 2_589_988.0)
+    }
+}
@@ -0,0 +1,276 @@
+//! An interactive walkthrough of the basic flow -- pick a neighborhood, filter it, look at the
+//! rat-runs, and save -- so people can learn the tool without somebody showing them around.
+//!
+//! The walkthrough lives in the session and follows the player between modes. Modes report
+//! milestones when the player does something, and the current task finishes when its milestone is
+//! reported.
+
+use map_gui::tools::{MessageAction, TutorialMessage};
+use widgetry::{
+    EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, Text, VerticalAlignment, Widget,
+};
+
+use crate::{App, BrowseNeighborhoods, Transition};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Milestone {
+    OpenedNeighborhood,
+    EditedFilters,
+    ViewedRatRuns,
+    SavedProposal,
+}
+
+struct Step {
+    label: &'static str,
+    instructions: &'static str,
+    messages: Vec<TutorialMessage<App>>,
+    /// The last step has no task
+    task: Option<Milestone>,
+}
+
+impl Step {
+    fn new(label: &'static str, instructions: &'static str, task: Option<Milestone>) -> Step {
+        Step {
+            label,
+            instructions,
+            messages: Vec::new(),
+            task,
+        }
+    }
+
+    fn msg(mut self, msg: TutorialMessage<App>) -> Step {
+        self.messages.push(msg);
+        self
+    }
+}
+
+pub struct Onboarding {
+    steps: Vec<Step>,
+    step: usize,
+    /// Index into the step's messages. messages.len() means the player is doing the task.
+    part: usize,
+    /// Milestones are reported without an EventCtx, so this is recreated lazily when it's None.
+    panel: Option<Panel>,
+}
+
+impl Onboarding {
+    fn new() -> Onboarding {
+        let steps = vec![
+            Step::new(
+                "Pick a neighborhood",
+                "Click one of the neighborhoods on the map",
+                Some(Milestone::OpenedNeighborhood),
+            )
+            .msg(TutorialMessage::new(Text::from_multiline(vec![
+                Line("This tool helps you design low-traffic neighborhoods."),
+                Line(""),
+                Line(
+                    "A neighborhood is an area surrounded by main roads. Inside, drivers should \
+                     only be going to or from somewhere in the neighborhood, not cutting through \
+                     it.",
+                ),
+            ])))
+            .msg(TutorialMessage::new(Text::from(Line(
+                "The map has already been split into neighborhoods, each one drawn in a different \
+                 color. Choose one to work on.",
+            )))),
+            Step::new(
+                "Add a modal filter",
+                "Click a road inside the neighborhood to filter it",
+                Some(Milestone::EditedFilters),
+            )
+            .msg(
+                TutorialMessage::new(Text::from(Line(
+                    "The colored areas inside the neighborhood are cells. Drivers can reach \
+                     anywhere in a cell without leaving through a main road.",
+                )))
+                .left_aligned(),
+            )
+            .msg(
+                TutorialMessage::new(Text::from(Line(
+                    "A modal filter stops cars from passing, but lets people walk and cycle \
+                     through. Click a road to add one, and watch the cells split apart.",
+                )))
+                .icon("system/assets/tools/pencil.svg")
+                .left_aligned(),
+            ),
+            Step::new(
+                "Read the rat-runs",
+                "Open the \"Rat runs\" tab on the left",
+                Some(Milestone::ViewedRatRuns),
+            )
+            .msg(
+                TutorialMessage::new(Text::from(Line(
+                    "Rat-runs are shortcuts drivers could take through the neighborhood to avoid \
+                     the main roads. Thicker red lines mean more shortcuts use that street.",
+                )))
+                .left_aligned(),
+            )
+            .msg(
+                TutorialMessage::new(Text::from(Line(
+                    "Look through the remaining rat-runs one at a time, and add filters until \
+                     none are left.",
+                )))
+                .left_aligned(),
+            ),
+            Step::new(
+                "Save your proposal",
+                "Click \"Save\" at the top of the left panel and name your proposal",
+                Some(Milestone::SavedProposal),
+            )
+            .msg(
+                TutorialMessage::new(Text::from(Line(
+                    "Your filters make up a proposal. Save it so you can come back to it, share \
+                     it, or compare it with other ideas.",
+                )))
+                .left_aligned(),
+            ),
+            Step::new(
+                "Tutorial complete",
+                "Keep designing, or try predicting the impact of your proposal from the \
+                 neighborhood browser.",
+                None,
+            )
+            .msg(TutorialMessage::new(Text::from(Line(
+                "That's the basic flow! Try the other neighborhoods, and open the help from the \
+                 top bar any time.",
+            )))),
+        ];
+
+        Onboarding {
+            steps,
+            step: 0,
+            part: 0,
+            panel: None,
+        }
+    }
+
+    fn message(&self) -> Option<&TutorialMessage<App>> {
+        self.steps[self.step].messages.get(self.part)
+    }
+
+    fn make_panel(&self, ctx: &mut EventCtx) -> Panel {
+        let step = &self.steps[self.step];
+        if let Some(msg) = self.message() {
+            return msg.make_panel(ctx, step.label, self.part, step.messages.len());
+        }
+
+        let mut col = vec![
+            Line(format!(
+                "Tutorial: step {}/{}",
+                self.step + 1,
+                self.steps.len()
+            ))
+            .small_heading()
+            .into_widget(ctx),
+            Line(step.label).into_widget(ctx),
+            Text::from(Line(step.instructions).secondary())
+                .wrap_to_pct(ctx, 20)
+                .into_widget(ctx),
+        ];
+        if step.task.is_some() {
+            col.push(Widget::row(vec![
+                ctx.style()
+                    .btn_plain
+                    .icon("system/assets/tools/info.svg")
+                    .build_widget(ctx, "instructions"),
+                ctx.style()
+                    .btn_outline
+                    .text("Skip this step")
+                    .build_def(ctx),
+                ctx.style().btn_outline.text("Quit tutorial").build_def(ctx),
+            ]));
+        } else {
+            col.push(
+                ctx.style()
+                    .btn_solid_primary
+                    .text("Finish tutorial")
+                    .build_def(ctx),
+            );
+        }
+        Panel::new_builder(Widget::col(col))
+            .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+            .build(ctx)
+    }
+
+    fn next_step(&mut self) {
+        self.step += 1;
+        self.part = 0;
+        self.panel = None;
+    }
+}
+
+/// Start the walkthrough from browsing neighborhoods
+pub fn start(ctx: &mut EventCtx, app: &mut App) -> Transition {
+    app.session.onboarding = Some(Onboarding::new());
+    Transition::Clear(vec![BrowseNeighborhoods::new_state(ctx, app)])
+}
+
+/// The player did something. If it's what the current task asks for, move on.
+pub fn record(app: &mut App, milestone: Milestone) {
+    if let Some(ref mut onboarding) = app.session.onboarding {
+        if onboarding.message().is_none()
+            && onboarding.steps[onboarding.step].task == Some(milestone)
+        {
+            onboarding.next_step();
+        }
+    }
+}
+
+/// Every mode calls this through `handle_top_panel`. While a message is showing, it blocks every
+/// other interaction.
+pub fn event(ctx: &mut EventCtx, app: &mut App) -> Option<Transition> {
+    let onboarding = app.session.onboarding.as_mut()?;
+    if onboarding.panel.is_none() {
+        onboarding.panel = Some(onboarding.make_panel(ctx));
+    }
+    let showing_message = onboarding.message().is_some();
+    let panel = onboarding.panel.as_mut().unwrap();
+
+    if showing_message {
+        match MessageAction::event(ctx, panel) {
+            MessageAction::Previous => {
+                onboarding.part -= 1;
+            }
+            MessageAction::Next => {
+                onboarding.part += 1;
+            }
+            MessageAction::Nothing => {
+                return Some(Transition::Keep);
+            }
+        }
+        onboarding.panel = Some(onboarding.make_panel(ctx));
+        return Some(Transition::Keep);
+    }
+
+    if let Outcome::Clicked(x) = panel.event(ctx) {
+        match x.as_ref() {
+            "instructions" => {
+                onboarding.part = 0;
+                onboarding.panel = Some(onboarding.make_panel(ctx));
+            }
+            "Skip this step" => {
+                onboarding.next_step();
+            }
+            "Quit tutorial" | "Finish tutorial" => {
+                app.session.onboarding = None;
+            }
+            _ => unreachable!(),
+        }
+        return Some(Transition::Keep);
+    }
+    None
+}
+
+/// Modes call this last, so the walkthrough is drawn over everything else.
+pub fn draw(g: &mut GfxCtx, app: &App) {
+    if let Some(ref onboarding) = app.session.onboarding {
+        if let Some(ref panel) = onboarding.panel {
+            if let Some(msg) = onboarding.message() {
+                msg.draw(g, app, panel);
+            } else {
+                panel.draw(g);
+            }
+        }
+    }
+}
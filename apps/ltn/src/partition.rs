@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use abstio::MapName;
 use abstutil::Timer;
 use map_model::osm::RoadRank;
-use map_model::{Block, Map, Perimeter, RoadID, RoadSideID};
+use map_model::{Block, BlockfindingFailure, Map, Perimeter, RoadID, RoadSideID};
 use widgetry::Color;
 
 use crate::{colors, App};
@@ -36,6 +36,10 @@ pub struct Partitioning {
     block_to_neighborhood: BTreeMap<BlockID, NeighborhoodID>,
 
     use_expensive_blockfinding: bool,
+
+    // Areas that couldn't become blocks, and so are missing from all neighborhoods
+    #[serde(default)]
+    failures: Vec<BlockfindingFailure>,
 }
 
 impl Partitioning {
@@ -51,6 +55,8 @@ impl Partitioning {
             block_to_neighborhood: BTreeMap::new(),
 
             use_expensive_blockfinding: false,
+
+            failures: Vec::new(),
         }
     }
 
@@ -65,14 +71,23 @@ impl Partitioning {
             timer.start("find single blocks");
             let mut single_blocks = Vec::new();
             let mut single_block_perims = Vec::new();
-            for mut perim in Perimeter::find_all_single_blocks(map) {
+            let (perimeters, mut failures) = Perimeter::find_all_single_blocks_with_failures(map);
+            for mut perim in perimeters {
                 // TODO Some perimeters don't blockify after collapsing dead-ends. So do this
                 // upfront, and separately work on any blocks that don't show up.
                 // https://github.com/a-b-street/abstreet/issues/841
                 perim.collapse_deadends();
-                if let Ok(block) = perim.to_block(map) {
-                    single_block_perims.push(block.perimeter.clone());
-                    single_blocks.push(block);
+                match perim.clone().to_block(map) {
+                    Ok(block) => {
+                        single_block_perims.push(block.perimeter.clone());
+                        single_blocks.push(block);
+                    }
+                    Err(err) => {
+                        failures.push(BlockfindingFailure::new(
+                            &perim,
+                            format!("after collapsing dead-ends: {}", err),
+                        ));
+                    }
                 }
             }
             timer.stop("find single blocks");
@@ -100,12 +115,16 @@ impl Partitioning {
             let mut blocks = Vec::new();
             for perimeter in merged {
                 timer.next();
-                match perimeter.to_block(map) {
+                match perimeter.clone().to_block(map) {
                     Ok(block) => {
                         blocks.push(block);
                     }
                     Err(err) => {
                         warn!("Failed to make a block from a merged perimeter: {}", err);
+                        failures.push(BlockfindingFailure::new(
+                            &perimeter,
+                            format!("merged perimeter: {}", err),
+                        ));
                     }
                 }
             }
@@ -123,6 +142,8 @@ impl Partitioning {
                 neighborhood_id_counter,
                 block_to_neighborhood: BTreeMap::new(),
                 use_expensive_blockfinding,
+
+                failures,
             };
 
             // TODO We could probably build this up as we go
@@ -320,6 +341,17 @@ impl Partitioning {
         &self.neighborhoods
     }
 
+    /// Group the interior roads of every neighborhood, for `RoutingParams::through_traffic_cells`
+    pub fn through_traffic_cells(&self) -> BTreeMap<RoadID, usize> {
+        let mut cells = BTreeMap::new();
+        for (id, (block, _)) in &self.neighborhoods {
+            for r in &block.perimeter.interior {
+                cells.insert(*r, id.0);
+            }
+        }
+        cells
+    }
+
     // Just used for initial creation
     fn neighborhood_containing(&self, find_block: BlockID) -> Option<NeighborhoodID> {
         // TODO We could probably build this mapping up when we do Perimeter::merge_all
@@ -332,6 +364,11 @@ impl Partitioning {
         None
     }
 
+    /// Areas that couldn't be traced into blocks. Their roads don't belong to any neighborhood.
+    pub fn blockfinding_failures(&self) -> &Vec<BlockfindingFailure> {
+        &self.failures
+    }
+
     pub fn all_single_blocks(&self) -> Vec<(BlockID, &Block)> {
         self.single_blocks
             .iter()
@@ -1,5 +1,6 @@
 use geom::Distance;
-use map_model::{IntersectionID, PathConstraints, RoadID};
+use map_gui::tools::{describe_note, NoteEditor};
+use map_model::{EditNote, IntersectionID, PathConstraints, RoadID};
 use widgetry::mapspace::{ObjectID, World, WorldOutcome};
 use widgetry::tools::open_browser;
 use widgetry::{
@@ -90,9 +91,12 @@ impl Tab {
             "Connectivity" => Some(Transition::Replace(crate::connectivity::Viewer::new_state(
                 ctx, app, id,
             ))),
-            "Rat runs" => Some(Transition::Replace(
-                crate::rat_run_viewer::BrowseRatRuns::new_state(ctx, app, id, None),
-            )),
+            "Rat runs" => {
+                crate::onboarding::record(app, crate::onboarding::Milestone::ViewedRatRuns);
+                Some(Transition::Replace(
+                    crate::rat_run_viewer::BrowseRatRuns::new_state(ctx, app, id, None),
+                ))
+            }
             "undo" => {
                 let prev = app.session.modal_filters.previous_version.take().unwrap();
                 app.session.modal_filters = prev;
@@ -173,40 +177,132 @@ pub fn make_world(
 
     for r in &neighborhood.orig_perimeter.interior {
         let road = map.get_r(*r);
-        world
+        let mut txt = Text::from(format!(
+            "{} rat-runs cross {}",
+            rat_runs.count_per_road.get(*r),
+            road.get_name(app.opts.language.as_ref()),
+        ));
+        let filtered = app.session.modal_filters.roads.contains_key(r);
+        if filtered {
+            add_note_to_tooltip(&mut txt, app.session.modal_filters.road_notes.get(r));
+        }
+        let mut obj = world
             .add(FilterableObj::InteriorRoad(*r))
             .hitbox(road.get_thick_polygon())
             .drawn_in_master_batch()
             .hover_outline(colors::OUTLINE, Distance::meters(5.0))
-            .tooltip(Text::from(format!(
-                "{} rat-runs cross {}",
-                rat_runs.count_per_road.get(*r),
-                road.get_name(app.opts.language.as_ref()),
-            )))
-            .hotkey(lctrl(Key::D), "debug")
-            .clickable()
-            .build(ctx);
+            .tooltip(txt)
+            .hotkey(lctrl(Key::D), "debug");
+        if filtered {
+            obj = obj.hotkey(Key::N, "annotate");
+        }
+        obj.clickable().build(ctx);
     }
 
     for i in &neighborhood.interior_intersections {
-        world
+        let mut txt = Text::from(format!(
+            "{} rat-runs cross this intersection",
+            rat_runs.count_per_intersection.get(*i)
+        ));
+        let filtered = app.session.modal_filters.intersections.contains_key(i);
+        if filtered {
+            add_note_to_tooltip(
+                &mut txt,
+                app.session.modal_filters.intersection_notes.get(i),
+            );
+        }
+        let mut obj = world
             .add(FilterableObj::InteriorIntersection(*i))
             .hitbox(map.get_i(*i).polygon.clone())
             .drawn_in_master_batch()
             .hover_outline(colors::OUTLINE, Distance::meters(5.0))
-            .tooltip(Text::from(format!(
-                "{} rat-runs cross this intersection",
-                rat_runs.count_per_intersection.get(*i)
-            )))
+            .tooltip(txt)
             .clickable()
-            .hotkey(lctrl(Key::D), "debug")
-            .build(ctx);
+            .hotkey(lctrl(Key::D), "debug");
+        if filtered {
+            obj = obj.hotkey(Key::N, "annotate");
+        }
+        obj.build(ctx);
     }
 
     world.initialize_hover(ctx);
     world
 }
 
+fn add_note_to_tooltip(txt: &mut Text, note: Option<&EditNote>) {
+    if let Some(note) = note {
+        txt.extend(describe_note(note));
+    }
+    txt.add_line(Line("Press N to write a note about this filter").secondary());
+}
+
+/// Writing a note about a filter opens a new state, so this must be checked before
+/// `handle_world_outcome`. Afterwards, the current tab is recreated to pick up the note.
+pub fn maybe_annotate(
+    ctx: &mut EventCtx,
+    app: &App,
+    outcome: &WorldOutcome<FilterableObj>,
+    tab: Tab,
+    id: NeighborhoodID,
+) -> Option<Transition> {
+    let obj = match outcome {
+        WorldOutcome::Keypress("annotate", obj) => *obj,
+        _ => {
+            return None;
+        }
+    };
+    let filters = &app.session.modal_filters;
+    let (title, current) = match obj {
+        FilterableObj::InteriorRoad(r) => (
+            format!(
+                "Note for the filter on {}",
+                app.map.get_r(r).get_name(app.opts.language.as_ref())
+            ),
+            filters.road_notes.get(&r),
+        ),
+        FilterableObj::InteriorIntersection(i) => (
+            "Note for the diagonal filter".to_string(),
+            filters.intersection_notes.get(&i),
+        ),
+    };
+    Some(Transition::Push(NoteEditor::new_state(
+        ctx,
+        title,
+        current,
+        Box::new(move |note, ctx, app| {
+            let filters = &mut app.session.modal_filters;
+            filters.before_edit();
+            match (obj, note) {
+                (FilterableObj::InteriorRoad(r), Some(note)) => {
+                    filters.road_notes.insert(r, note);
+                }
+                (FilterableObj::InteriorRoad(r), None) => {
+                    filters.road_notes.remove(&r);
+                }
+                (FilterableObj::InteriorIntersection(i), Some(note)) => {
+                    filters.intersection_notes.insert(i, note);
+                }
+                (FilterableObj::InteriorIntersection(i), None) => {
+                    filters.intersection_notes.remove(&i);
+                }
+            }
+            if filters.cancel_empty_edit() {
+                return Transition::Pop;
+            }
+            after_edit(ctx, app);
+            Transition::Multi(vec![
+                Transition::Pop,
+                Transition::Replace(match tab {
+                    Tab::Connectivity => crate::connectivity::Viewer::new_state(ctx, app, id),
+                    Tab::RatRuns => {
+                        crate::rat_run_viewer::BrowseRatRuns::new_state(ctx, app, id, None)
+                    }
+                }),
+            ])
+        }),
+    )))
+}
+
 /// If true, the neighborhood has changed and the caller should recalculate stuff, including the
 /// panel
 pub fn handle_world_outcome(
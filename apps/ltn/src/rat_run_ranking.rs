@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+
+use abstutil::{prettyprint_usize, Counter};
+use map_gui::tools::ColorNetwork;
+use map_model::RoadID;
+use synthpop::Scenario;
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::table::{Col, Filter, Table};
+use widgetry::tools::{FileLoader, PopupMsg};
+use widgetry::{
+    DrawBaselayer, EventCtx, GfxCtx, HorizontalAlignment, Line, Outcome, Panel, Spinner, State,
+    Text, TextExt, VerticalAlignment, Widget,
+};
+
+use crate::{colors, App, NeighborhoodID, Transition};
+
+/// Ranks the residential streets across the whole map carrying the most through-traffic today,
+/// according to the travel demand model. This is a starting point for deciding which
+/// neighborhoods to work on.
+pub struct RatRunRanking {
+    table: Table<App, Entry, usize>,
+    panel: Panel,
+    draw: ToggleZoomed,
+    // Indexed by the row labels
+    roads: Vec<RoadID>,
+    neighborhoods: Vec<Option<NeighborhoodID>>,
+}
+
+struct Entry {
+    // Rows are labelled with this index. The rank is one more.
+    idx: usize,
+    name: String,
+    through: usize,
+    total: usize,
+    pct_through: f64,
+    // Numbered like the neighborhood comparison table
+    neighborhood: Option<usize>,
+}
+
+impl RatRunRanking {
+    pub fn new_state(ctx: &mut EventCtx, app: &mut App) -> Box<dyn State<App>> {
+        let map_name = app.map.get_name().clone();
+        if app.session.through_traffic.key().as_ref() != Some(&map_name) {
+            let scenario_name = Scenario::default_scenario_for_map(&map_name);
+            return FileLoader::<App, Scenario>::new_state(
+                ctx,
+                abstio::path_scenario(&map_name, &scenario_name),
+                Box::new(
+                    move |ctx, app, timer, maybe_scenario| match maybe_scenario {
+                        Ok(scenario) => {
+                            let ranking =
+                                synthpop::find_through_traffic(&app.map, &scenario, timer);
+                            app.session.through_traffic.set(map_name, ranking);
+                            Transition::Replace(RatRunRanking::new_state(ctx, app))
+                        }
+                        Err(err) => Transition::Replace(PopupMsg::new_state(
+                            ctx,
+                            "Error",
+                            vec![format!("Couldn't load the travel demand model: {}", err)],
+                        )),
+                    },
+                ),
+            );
+        }
+        let ranking = app.session.through_traffic.value().unwrap();
+
+        let mut road_to_neighborhood = BTreeMap::new();
+        for (idx, (id, (block, _))) in app
+            .session
+            .partitioning
+            .all_neighborhoods()
+            .iter()
+            .enumerate()
+        {
+            for r in &block.perimeter.interior {
+                road_to_neighborhood.insert(*r, (idx, *id));
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut roads = Vec::new();
+        let mut neighborhoods = Vec::new();
+        let mut counter = Counter::new();
+        for (idx, x) in ranking.iter().enumerate() {
+            let neighborhood = road_to_neighborhood.get(&x.road).cloned();
+            entries.push(Entry {
+                idx,
+                name: app.map.get_r(x.road).get_name(app.opts.language.as_ref()),
+                through: x.through,
+                total: x.total,
+                pct_through: x.pct_through(),
+                neighborhood: neighborhood.map(|(idx, _)| idx),
+            });
+            roads.push(x.road);
+            neighborhoods.push(neighborhood.map(|(_, id)| id));
+            counter.add(x.road, x.through);
+        }
+
+        let mut colorer = ColorNetwork::no_fading(app);
+        colorer.ranked_roads(counter, &app.cs.good_to_bad_red);
+        let draw = colorer.build(ctx);
+
+        let table = make_table(entries);
+        let panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Rat-run ranking").small_heading().into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(
+                Line(
+                    "Every driving trip in the travel demand model is routed before any new \
+                     filters. A trip passes through a residential street when it comes from a \
+                     main road and continues to another main road.",
+                )
+                .secondary(),
+            )
+            .wrap_to_pct(ctx, 50)
+            .into_widget(ctx),
+            "Click a street to edit filters in its neighborhood".text_widget(ctx),
+            table.render(ctx, app),
+        ]))
+        .aligned(HorizontalAlignment::Center, VerticalAlignment::TopInset)
+        .build(ctx);
+
+        Box::new(RatRunRanking {
+            table,
+            panel,
+            draw,
+            roads,
+            neighborhoods,
+        })
+    }
+}
+
+fn make_table(entries: Vec<Entry>) -> Table<App, Entry, usize> {
+    let filter: Filter<App, Entry, usize> = Filter {
+        // By default, only show streets where most traffic is passing through
+        state: 50,
+        to_controls: Box::new(|ctx, _, state| {
+            Widget::row(vec![
+                "Only streets with at least"
+                    .text_widget(ctx)
+                    .centered_vert(),
+                Spinner::widget(ctx, "min pct through", (0, 100), *state, 5),
+                "% through-traffic".text_widget(ctx).centered_vert(),
+            ])
+        }),
+        from_controls: Box::new(|panel| panel.spinner("min pct through")),
+        apply: Box::new(|state, x, _| x.pct_through >= *state as f64),
+    };
+
+    let mut table: Table<App, Entry, usize> = Table::new(
+        "rat_run_ranking",
+        entries,
+        Box::new(|x| x.idx.to_string()),
+        "Through trips",
+        filter,
+    );
+    table.column(
+        "Rank",
+        Box::new(|ctx, _, x| Text::from(format!("#{}", x.idx + 1)).render(ctx)),
+        Col::Static,
+    );
+    table.column(
+        "Street",
+        Box::new(|ctx, _, x| Text::from(&x.name).render(ctx)),
+        Col::Static,
+    );
+    table.column(
+        "Through trips",
+        Box::new(|ctx, _, x| Text::from(prettyprint_usize(x.through)).render(ctx)),
+        Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.through))),
+    );
+    table.column(
+        "All trips",
+        Box::new(|ctx, _, x| Text::from(prettyprint_usize(x.total)).render(ctx)),
+        Col::Sortable(Box::new(|rows| rows.sort_by_key(|x| x.total))),
+    );
+    table.column(
+        "Through-traffic",
+        Box::new(|ctx, _, x| Text::from(format!("{:.0}%", x.pct_through)).render(ctx)),
+        Col::Sortable(Box::new(|rows| {
+            rows.sort_by(|a, b| a.pct_through.partial_cmp(&b.pct_through).unwrap())
+        })),
+    );
+    table.column(
+        "Neighborhood",
+        Box::new(|ctx, _, x| {
+            Text::from(match x.neighborhood {
+                Some(idx) => format!("#{}", idx + 1),
+                None => "None".to_string(),
+            })
+            .render(ctx)
+        }),
+        Col::Static,
+    );
+    table
+}
+
+impl State<App> for RatRunRanking {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        ctx.canvas_movement();
+
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => {
+                if self.table.clicked(&x) {
+                    self.table.replace_render(ctx, app, &mut self.panel)
+                } else if x == "close" {
+                    return Transition::Pop;
+                } else if let Ok(idx) = x.parse::<usize>() {
+                    return match self.neighborhoods[idx] {
+                        Some(id) => Transition::Replace(crate::connectivity::Viewer::new_state(
+                            ctx, app, id,
+                        )),
+                        None => Transition::Push(PopupMsg::new_state(
+                            ctx,
+                            "Not in a neighborhood",
+                            vec![
+                                "This street isn't inside any neighborhood. Adjust the \
+                                  neighborhood boundaries to include it first.",
+                            ],
+                        )),
+                    };
+                } else {
+                    unreachable!()
+                }
+            }
+            Outcome::Changed(_) => {
+                self.table.panel_changed(&self.panel);
+                self.table.replace_render(ctx, app, &mut self.panel)
+            }
+            _ => {}
+        }
+
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        self.draw.draw(g);
+        if let Some(idx) = self
+            .panel
+            .currently_hovering()
+            .and_then(|x| x.parse::<usize>().ok())
+        {
+            g.draw_polygon(
+                colors::OUTLINE.alpha(0.5),
+                app.map.get_r(self.roads[idx]).get_thick_polygon(),
+            );
+        }
+        self.panel.draw(g);
+    }
+}
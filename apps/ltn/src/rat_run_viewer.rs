@@ -201,6 +201,15 @@ impl State<App> for BrowseRatRuns {
         // TODO Bit weird to allow this while showing individual paths, since we don't draw the
         // world
         let world_outcome = self.world.event(ctx);
+        if let Some(t) = crate::per_neighborhood::maybe_annotate(
+            ctx,
+            app,
+            &world_outcome,
+            Tab::RatRuns,
+            self.neighborhood.id,
+        ) {
+            return t;
+        }
         if crate::per_neighborhood::handle_world_outcome(ctx, app, world_outcome) {
             // Reset state, but if possible, preserve the current individual rat run.
             let current_request = self.rat_runs.paths[self.current_idx].get_req().clone();
@@ -227,6 +236,7 @@ impl State<App> for BrowseRatRuns {
         if g.canvas.is_unzoomed() {
             self.neighborhood.labels.draw(g, app);
         }
+        crate::onboarding::draw(g, app);
     }
 }
 
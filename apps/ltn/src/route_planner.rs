@@ -7,7 +7,7 @@ use synthpop::{TripEndpoint, TripMode};
 use widgetry::mapspace::{ToggleZoomed, World};
 use widgetry::{
     ButtonBuilder, Color, ControlState, EventCtx, GeomBatch, GfxCtx, Key, Line, Outcome, Panel,
-    RoundedF64, Spinner, State, Text, Widget,
+    RoundedF64, Spinner, State, Text, Toggle, Widget,
 };
 
 use crate::{colors, App, BrowseNeighborhoods, Transition};
@@ -98,6 +98,15 @@ impl RoutePlanner {
                         .secondary(),
                 ])
                 .into_widget(ctx),
+                Toggle::checkbox(
+                    ctx,
+                    "Enforce neighborhoods with cameras",
+                    None,
+                    app.session.camera_enforcement,
+                ),
+                Line("Drivers avoid cutting through any neighborhood, even without filters")
+                    .secondary()
+                    .into_widget(ctx),
             ])
             .section(ctx),
             results_widget.section(ctx),
@@ -160,6 +169,9 @@ impl RoutePlanner {
             let mut params = map.routing_params().clone();
             app.session.modal_filters.update_routing_params(&mut params);
             params.main_road_penalty = app.session.main_road_penalty;
+            if app.session.camera_enforcement {
+                params.through_traffic_cells = app.session.partitioning.through_traffic_cells();
+            }
 
             let mut total_time = Duration::ZERO;
             let mut draw_after = ToggleZoomed::builder();
@@ -260,7 +272,12 @@ impl RoutePlanner {
                     card(
                         ctx,
                         "Driving around filters",
-                        "This route drives around all filters",
+                        if app.session.camera_enforcement {
+                            "This route drives around all filters and avoids cutting through \
+                             neighborhoods"
+                        } else {
+                            "This route drives around all filters"
+                        },
                         drive_around_filters_time,
                         *colors::PLAN_ROUTE_AFTER,
                     )
@@ -268,8 +285,8 @@ impl RoutePlanner {
             ])
             .evenly_spaced(),
             Widget::row(vec![
-                card(ctx, "Biking", "This cycling route doesn't avoid high-stress roads or hills, and assumes an average 10mph pace", biking_time, *colors::PLAN_ROUTE_BIKE),
-                card(ctx, "Walking", "This walking route doesn't avoid high-stress roads or hills, and assumes an average 3 mph pace", walking_time, *colors::PLAN_ROUTE_WALK),
+                card(ctx, "Biking", format!("This cycling route doesn't avoid high-stress roads or hills, and assumes an average {} pace", map_model::MAX_BIKE_SPEED.to_string(&app.opts.units)), biking_time, *colors::PLAN_ROUTE_BIKE),
+                card(ctx, "Walking", format!("This walking route doesn't avoid high-stress roads or hills, and assumes an average {} pace", map_model::MAX_WALKING_SPEED.to_string(&app.opts.units)), walking_time, *colors::PLAN_ROUTE_WALK),
             ])
             .evenly_spaced(),
         ])
@@ -287,6 +304,17 @@ impl State<App> for RoutePlanner {
             if x == "Browse neighborhoods" {
                 return Transition::Replace(BrowseNeighborhoods::new_state(ctx, app));
             }
+            if x == "add a waypoint by address" {
+                return Transition::Push(InputWaypoints::search_address_state(
+                    ctx,
+                    |state: &mut RoutePlanner, ctx, app: &mut App, at| {
+                        if state.waypoints.add(app, at) {
+                            state.files.current.waypoints = state.waypoints.get_waypoints();
+                            state.update_everything(ctx, app);
+                        }
+                    },
+                ));
+            }
             if let Some(t) = self.files.on_click(ctx, app, x) {
                 // Bit hacky...
                 if matches!(t, Transition::Keep) {
@@ -309,6 +337,11 @@ impl State<App> for RoutePlanner {
                 app.session.main_road_penalty =
                     self.left_panel.spinner::<RoundedF64>("main road penalty").0;
                 self.update_everything(ctx, app);
+            } else if x == "Enforce neighborhoods with cameras" {
+                app.session.camera_enforcement = self
+                    .left_panel
+                    .is_checked("Enforce neighborhoods with cameras");
+                self.update_everything(ctx, app);
             }
         }
 
@@ -335,6 +368,7 @@ impl State<App> for RoutePlanner {
         if g.canvas.is_unzoomed() {
             self.labels.draw(g, app);
         }
+        crate::onboarding::draw(g, app);
     }
 }
 
@@ -350,7 +384,7 @@ fn help() -> Vec<&'static str> {
 fn card(
     ctx: &EventCtx,
     label: &'static str,
-    tooltip: &'static str,
+    tooltip: impl Into<Text>,
     time: Duration,
     color: Color,
 ) -> Widget {
@@ -8,6 +8,7 @@ use map_model::PathRequest;
 use widgetry::tools::PopupMsg;
 use widgetry::{Choice, EventCtx, Key, Line, State, Widget};
 
+use crate::history::{ChangeLogEntry, ProposalHistory, ProposalVersion};
 use crate::partition::BlockID;
 use crate::{App, BrowseNeighborhoods, ModalFilters, Partitioning, Transition};
 
@@ -24,6 +25,10 @@ pub struct Proposal {
 
     pub partitioning: Partitioning,
     pub modal_filters: ModalFilters,
+    /// Every change made to the proposal, oldest first
+    pub change_log: Vec<ChangeLogEntry>,
+    /// Named snapshots that can be restored
+    pub versions: Vec<ProposalVersion>,
 }
 
 impl Proposal {
@@ -39,6 +44,8 @@ impl Proposal {
 
             partitioning: app.session.partitioning.clone(),
             modal_filters: app.session.modal_filters.clone(),
+            change_log: app.session.change_log.clone(),
+            versions: app.session.versions.clone(),
         }
     }
 
@@ -46,6 +53,9 @@ impl Proposal {
         app.session.proposal_name = Some(self.name);
         app.session.partitioning = self.partitioning;
         app.session.modal_filters = self.modal_filters;
+        app.session.change_log = self.change_log;
+        app.session.versions = self.versions;
+        app.session.logged_filters = app.session.modal_filters.get_change_key();
         app.session.draw_all_filters = app.session.modal_filters.draw(ctx, &app.map);
     }
 
@@ -128,6 +138,7 @@ fn save_ui(ctx: &mut EventCtx, app: &App, preserve_state: PreserveState) -> Box<
             let path = abstio::path_ltn_proposals(app.map.get_name(), &name);
             let proposal = Proposal::from_app(app);
             abstio::write_binary(path, &proposal);
+            crate::onboarding::record(app, crate::onboarding::Milestone::SavedProposal);
 
             // If we changed the name, we'll want to recreate the panel
             preserve_state.switch_to_state(ctx, app)
@@ -175,6 +186,7 @@ impl AltProposals {
             ctx.style().btn_outline.text("New").build_def(ctx),
             ctx.style().btn_outline.text("Load").build_def(ctx),
             ctx.style().btn_outline.text("Save").build_def(ctx),
+            ctx.style().btn_outline.text("History").build_def(ctx),
         ])];
         for (idx, proposal) in self.list.iter().enumerate() {
             let button = if let Some(proposal) = proposal {
@@ -238,6 +250,13 @@ impl AltProposals {
             "Save" => {
                 return Some(Transition::Push(save_ui(ctx, app, preserve_state)));
             }
+            "History" => {
+                return Some(Transition::Push(ProposalHistory::new_state(
+                    ctx,
+                    app,
+                    preserve_state,
+                )));
+            }
             _ => {
                 if let Some(x) = action.strip_prefix("switch to proposal ") {
                     let idx = x.parse::<usize>().unwrap();
@@ -279,7 +298,7 @@ pub enum PreserveState {
 }
 
 impl PreserveState {
-    fn switch_to_state(self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+    pub fn switch_to_state(self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self {
             PreserveState::BrowseNeighborhoods => {
                 Transition::Replace(BrowseNeighborhoods::new_state(ctx, app))
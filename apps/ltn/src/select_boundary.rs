@@ -320,6 +320,7 @@ impl State<App> for SelectBoundary {
         if let Some(ref lasso) = self.lasso {
             lasso.draw(g);
         }
+        crate::onboarding::draw(g, app);
     }
 }
 
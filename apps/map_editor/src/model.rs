@@ -346,6 +346,7 @@ impl Model {
                 osm_tags,
                 turn_restrictions: Vec::new(),
                 complicated_turn_restrictions: Vec::new(),
+                conditional_turn_restrictions: Vec::new(),
                 percent_incline: 0.0,
                 crosswalk_forward: true,
                 crosswalk_backward: true,
@@ -0,0 +1,150 @@
+use map_gui::tools::{ColorDiscrete, ColorLegend};
+use map_gui::SimpleApp;
+use map_model::{AmenityType, LaneType};
+use widgetry::mapspace::ToggleZoomed;
+use widgetry::{Color, EventCtx, GfxCtx, Widget};
+
+type App = SimpleApp<()>;
+
+/// Static layers describing the map. None of these depend on a simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LayerType {
+    Elevation,
+    BikeNetwork,
+    Amenities,
+}
+
+impl LayerType {
+    pub fn all() -> Vec<LayerType> {
+        vec![
+            LayerType::Elevation,
+            LayerType::BikeNetwork,
+            LayerType::Amenities,
+        ]
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            LayerType::Elevation => "elevation",
+            LayerType::BikeNetwork => "bike network",
+            LayerType::Amenities => "amenities",
+        }
+    }
+}
+
+pub struct Layer {
+    draw: ToggleZoomed,
+    categories: Vec<(String, Color)>,
+}
+
+impl Layer {
+    pub fn new(ctx: &mut EventCtx, app: &App, layer: LayerType) -> Layer {
+        let colorer = match layer {
+            LayerType::Elevation => elevation(app),
+            LayerType::BikeNetwork => bike_network(app),
+            LayerType::Amenities => amenities(app),
+        };
+        let categories = colorer.categories.clone();
+        let (draw, _) = colorer.build(ctx);
+        Layer { draw, categories }
+    }
+
+    pub fn legend(&self, ctx: &EventCtx) -> Widget {
+        Widget::col(
+            self.categories
+                .iter()
+                .map(|(name, color)| ColorLegend::row(ctx, *color, name))
+                .collect(),
+        )
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx) {
+        self.draw.draw(g);
+    }
+}
+
+fn elevation(app: &App) -> ColorDiscrete {
+    let mut colorer = ColorDiscrete::new(
+        app,
+        vec![
+            ("0-3% (flat)", Color::hex("#296B07")),
+            ("3-5%", Color::hex("#689A03")),
+            ("5-8%", Color::hex("#EB9A04")),
+            ("8-10%", Color::hex("#D30800")),
+            (">10% (steep)", Color::hex("#980104")),
+        ],
+    );
+    for r in app.map.all_roads() {
+        if r.is_light_rail() {
+            continue;
+        }
+        let pct = r.percent_incline.abs();
+        let bucket = if pct < 0.03 {
+            "0-3% (flat)"
+        } else if pct < 0.05 {
+            "3-5%"
+        } else if pct < 0.08 {
+            "5-8%"
+        } else if pct < 0.1 {
+            "8-10%"
+        } else {
+            ">10% (steep)"
+        };
+        colorer.add_r(r.id, bucket);
+    }
+    colorer
+}
+
+fn bike_network(app: &App) -> ColorDiscrete {
+    let mut colorer = ColorDiscrete::new(
+        app,
+        vec![
+            ("dedicated trail", Color::GREEN),
+            ("protected bike lane", Color::hex("#A4DE02")),
+            ("painted bike lane", Color::hex("#76BA1B")),
+        ],
+    );
+    for r in app.map.all_roads() {
+        let bike_lane = r.lanes.iter().any(|l| l.lane_type == LaneType::Biking);
+        let buffer = r
+            .lanes
+            .iter()
+            .any(|l| matches!(l.lane_type, LaneType::Buffer(_)));
+        if r.is_cycleway() {
+            colorer.add_r(r.id, "dedicated trail");
+        } else if bike_lane && buffer {
+            colorer.add_r(r.id, "protected bike lane");
+        } else if bike_lane {
+            colorer.add_r(r.id, "painted bike lane");
+        }
+    }
+    colorer
+}
+
+fn amenities(app: &App) -> ColorDiscrete {
+    let categories = vec![
+        (AmenityType::Food.to_string(), Color::RED),
+        (AmenityType::School.to_string(), Color::CYAN),
+        (AmenityType::Shopping.to_string(), Color::PURPLE),
+        ("other".to_string(), Color::GREEN),
+    ];
+    let mut colorer = ColorDiscrete::new(app, categories);
+    for b in app.map.all_buildings() {
+        if b.amenities.is_empty() {
+            continue;
+        }
+        let category = b
+            .amenities
+            .iter()
+            .find_map(|a| AmenityType::categorize(&a.amenity_type))
+            .and_then(|t| match t {
+                AmenityType::Food | AmenityType::School | AmenityType::Shopping => {
+                    Some(t.to_string())
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| "other".to_string());
+        colorer.add_b(b.id, category);
+    }
+    colorer
+}
@@ -0,0 +1,3 @@
+fn main() {
+    map_viewer::main();
+}
@@ -0,0 +1,207 @@
+use map_gui::options::OptionsPanel;
+use map_gui::render::DrawOptions;
+use map_gui::tools::{CityPicker, Executable, Minimap, MinimapControls, Navigator};
+use map_gui::{SimpleApp, ID};
+use widgetry::tools::URLManager;
+use widgetry::{
+    lctrl, Choice, DrawBaselayer, EventCtx, GfxCtx, HorizontalAlignment, Key, Outcome, Panel,
+    State, Text, TextExt, Transition, UpdateType, VerticalAlignment, Widget,
+};
+
+use crate::layers::{Layer, LayerType};
+
+type App = SimpleApp<()>;
+
+pub struct Viewer {
+    panel: Panel,
+    minimap: Minimap<App, MinimapController>,
+    layer: Option<(LayerType, Layer)>,
+}
+
+impl Viewer {
+    pub fn new_state(ctx: &mut EventCtx, app: &App) -> Box<dyn State<App>> {
+        map_gui::tools::update_url_map_name(app);
+
+        let mut viewer = Viewer {
+            panel: Panel::empty(ctx),
+            minimap: Minimap::new(ctx, app, MinimapController),
+            layer: None,
+        };
+        viewer.recalculate_panel(ctx, app);
+        Box::new(viewer)
+    }
+
+    fn recalculate_panel(&mut self, ctx: &mut EventCtx, app: &App) {
+        let mut layer_choices = vec![Choice::new("none", None)];
+        for layer in LayerType::all() {
+            layer_choices.push(Choice::new(layer.describe(), Some(layer)));
+        }
+
+        self.panel = Panel::new_builder(Widget::col(vec![
+            map_gui::tools::app_header(ctx, app, "Map viewer"),
+            Widget::row(vec![
+                ctx.style()
+                    .btn_plain
+                    .icon("system/assets/tools/settings.svg")
+                    .build_widget(ctx, "settings"),
+                ctx.style()
+                    .btn_plain
+                    .icon("system/assets/tools/search.svg")
+                    .hotkey(lctrl(Key::F))
+                    .build_widget(ctx, "search"),
+                ctx.style()
+                    .btn_outline
+                    .text("screenshot")
+                    .hotkey(lctrl(Key::S))
+                    .build_def(ctx),
+            ]),
+            Widget::horiz_separator(ctx, 1.0),
+            Widget::row(vec![
+                "Layer:".text_widget(ctx).centered_vert(),
+                Widget::dropdown(
+                    ctx,
+                    "layer",
+                    self.layer.as_ref().map(|(l, _)| *l),
+                    layer_choices,
+                ),
+            ]),
+            match self.layer {
+                Some((_, ref layer)) => layer.legend(ctx),
+                None => Widget::nothing(),
+            },
+            Widget::horiz_separator(ctx, 1.0),
+            describe_selection(ctx, app),
+        ]))
+        .aligned(HorizontalAlignment::Left, VerticalAlignment::Top)
+        .build(ctx);
+    }
+}
+
+impl State<App> for Viewer {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition<App> {
+        if ctx.canvas_movement() {
+            URLManager::update_url_cam(ctx, app.map.get_gps_bounds());
+        }
+
+        if ctx.redo_mouseover() {
+            let old_id = app.current_selection.clone();
+            app.recalculate_current_selection(ctx);
+            if old_id != app.current_selection {
+                self.recalculate_panel(ctx, app);
+            }
+        }
+
+        if let Some(t) = self.minimap.event(ctx, app) {
+            return t;
+        }
+
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "Home" => {
+                    return Executable::ABStreet.replace_process(ctx, app, vec![]);
+                }
+                "change map" => {
+                    return Transition::Push(CityPicker::new_state(
+                        ctx,
+                        app,
+                        Box::new(|ctx, app| {
+                            Transition::Multi(vec![
+                                Transition::Pop,
+                                Transition::Replace(Viewer::new_state(ctx, app)),
+                            ])
+                        }),
+                    ));
+                }
+                "settings" => {
+                    return Transition::Push(OptionsPanel::new_state(ctx, app));
+                }
+                "search" => {
+                    return Transition::Push(Navigator::new_state(ctx, app));
+                }
+                "screenshot" => {
+                    let name = app.map.get_name();
+                    ctx.request_update(UpdateType::ScreenCaptureEverything {
+                        dir: format!(
+                            "screenshots/{}/{}/{}",
+                            name.city.country, name.city.city, name.map
+                        ),
+                        zoom: ctx.canvas.cam_zoom,
+                        dims: ctx.canvas.get_window_dims(),
+                    });
+                }
+                _ => unreachable!(),
+            },
+            Outcome::Changed(_) => {
+                let layer: Option<LayerType> = self.panel.dropdown_value("layer");
+                self.layer = layer.map(|l| (l, Layer::new(ctx, app, l)));
+                self.recalculate_panel(ctx, app);
+            }
+            _ => {}
+        }
+
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::Custom
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        if g.canvas.is_unzoomed() {
+            app.draw_unzoomed(g);
+        } else {
+            app.draw_zoomed(g, DrawOptions::new());
+        }
+        if let Some((_, ref layer)) = self.layer {
+            layer.draw(g);
+        }
+
+        self.panel.draw(g);
+        self.minimap.draw(g, app);
+    }
+}
+
+fn describe_selection(ctx: &mut EventCtx, app: &App) -> Widget {
+    let mut txt = Text::new();
+    match app.current_selection {
+        Some(ID::Lane(l)) => {
+            let r = app.map.get_parent(l);
+            txt.add_line(r.get_name(app.opts.language.as_ref()));
+            txt.add_line(format!("{} lanes, {:?}", r.lanes.len(), r.get_rank()));
+            txt.add_line(format!("Speed limit: {}", r.speed_limit));
+            txt.add_line(format!("{:.1}% incline", r.percent_incline.abs() * 100.0));
+        }
+        Some(ID::Intersection(i)) => {
+            let i = app.map.get_i(i);
+            txt.add_line(format!("{} ({:?})", i.id, i.intersection_type));
+            txt.add_line(format!("Elevation: {}", i.elevation));
+        }
+        Some(ID::Building(b)) => {
+            let b = app.map.get_b(b);
+            txt.add_line(format!("Address: {}", b.address));
+            for a in &b.amenities {
+                txt.add_line(format!(
+                    "  {} ({})",
+                    a.names.get(app.opts.language.as_ref()),
+                    a.amenity_type
+                ));
+            }
+        }
+        _ => {
+            txt.add_line("Hover on something to examine it");
+        }
+    }
+    txt.into_widget(ctx)
+}
+
+struct MinimapController;
+
+impl MinimapControls<App> for MinimapController {
+    fn has_zorder(&self, _: &App) -> bool {
+        true
+    }
+
+    fn make_legend(&self, _: &mut EventCtx, _: &App) -> Widget {
+        Widget::nothing()
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use geojson::{Feature, FeatureCollection};
+
+use map_model::{Map, RoadID};
+
+/// Something that looks wrong about a road in OSM, noticed while inspecting it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlagType {
+    WrongLaneCount,
+    MissingCycleway,
+    MissingSidewalk,
+    WrongSpeedLimit,
+    Other,
+}
+
+impl FlagType {
+    pub fn all() -> Vec<FlagType> {
+        vec![
+            FlagType::WrongLaneCount,
+            FlagType::MissingCycleway,
+            FlagType::MissingSidewalk,
+            FlagType::WrongSpeedLimit,
+            FlagType::Other,
+        ]
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            FlagType::WrongLaneCount => "wrong number of lanes",
+            FlagType::MissingCycleway => "missing cycleway tag",
+            FlagType::MissingSidewalk => "missing sidewalk tag",
+            FlagType::WrongSpeedLimit => "wrong speed limit",
+            FlagType::Other => "something else",
+        }
+    }
+}
+
+pub struct Flag {
+    pub road: RoadID,
+    pub flag_type: FlagType,
+    pub note: String,
+}
+
+/// All of the problems flagged so far in this session.
+#[derive(Default)]
+pub struct Flags {
+    pub list: Vec<Flag>,
+}
+
+impl Flags {
+    /// Write the flags as GeoJSON, usable as a MapRoulette challenge. On the web, this downloads
+    /// the file. Returns the path to the file.
+    pub fn export(&self, map: &Map) -> Result<String> {
+        let path = format!("osm_flags_{}.geojson", map.get_name().as_filename());
+        abstio::write_file(path, self.to_geojson(map).to_string())
+    }
+
+    fn to_geojson(&self, map: &Map) -> FeatureCollection {
+        let mut features = Vec::new();
+        for flag in &self.list {
+            let road = map.get_r(flag.road);
+            let mut feature = Feature {
+                bbox: None,
+                geometry: Some(road.center_pts.to_geojson(Some(map.get_gps_bounds()))),
+                id: None,
+                properties: None,
+                foreign_members: None,
+            };
+            // MapRoulette shows properties to the mapper fixing the task
+            feature.set_property("@id", format!("way/{}", road.orig_id.osm_way_id.0));
+            feature.set_property("osm_url", road.orig_id.osm_way_id.to_string());
+            feature.set_property("problem", flag.flag_type.describe());
+            feature.set_property(
+                "instructions",
+                if flag.note.is_empty() {
+                    format!("Check this way: {}", flag.flag_type.describe())
+                } else {
+                    format!(
+                        "Check this way: {}. {}",
+                        flag.flag_type.describe(),
+                        flag.note
+                    )
+                },
+            );
+            features.push(feature);
+        }
+        FeatureCollection {
+            bbox: None,
+            features,
+            foreign_members: None,
+        }
+    }
+}
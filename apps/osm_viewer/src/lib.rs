@@ -1,3 +1,4 @@
+mod flags;
 mod viewer;
 
 use structopt::StructOpt;
@@ -4,15 +4,20 @@ use abstutil::{prettyprint_usize, Counter};
 use geom::ArrowCap;
 use map_gui::options::OptionsPanel;
 use map_gui::render::{DrawOptions, BIG_ARROW_THICKNESS};
-use map_gui::tools::{CityPicker, Minimap, MinimapControls, Navigator, TurnExplorer};
+use map_gui::tools::{
+    ChooseSomething, CityPicker, Minimap, MinimapControls, Navigator, PromptInput, TurnExplorer,
+};
 use map_gui::{SimpleApp, ID};
 use map_model::osm;
 use widgetry::tools::{open_browser, PopupMsg, URLManager};
 use widgetry::{
-    lctrl, Color, DrawBaselayer, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key,
-    Line, Outcome, Panel, State, Text, TextExt, Toggle, Transition, VerticalAlignment, Widget,
+    lctrl, Choice, Color, DrawBaselayer, Drawable, EventCtx, GeomBatch, GfxCtx,
+    HorizontalAlignment, Key, Line, Outcome, Panel, State, Text, TextExt, Toggle, Transition,
+    VerticalAlignment, Widget,
 };
 
+use crate::flags::{Flag, FlagType, Flags};
+
 type App = SimpleApp<()>;
 
 pub struct Viewer {
@@ -20,6 +25,8 @@ pub struct Viewer {
     fixed_object_outline: Option<Drawable>,
     minimap: Minimap<App, MinimapController>,
     businesses: Option<BusinessSearch>,
+    flags: Flags,
+    draw_flags: Drawable,
 }
 
 impl Viewer {
@@ -31,6 +38,8 @@ impl Viewer {
             minimap: Minimap::new(ctx, app, MinimapController),
             businesses: None,
             top_panel: Panel::empty(ctx),
+            flags: Flags::default(),
+            draw_flags: Drawable::empty(ctx),
         };
         viewer.recalculate_top_panel(ctx, app, None);
         Box::new(viewer)
@@ -57,6 +66,14 @@ impl Viewer {
                     .hotkey(lctrl(Key::F))
                     .build_widget(ctx, "search"),
                 ctx.style().btn_plain.text("About").build_def(ctx),
+                if self.flags.list.is_empty() {
+                    Widget::nothing()
+                } else {
+                    ctx.style()
+                        .btn_outline
+                        .text(format!("Export {} flagged problems", self.flags.list.len()))
+                        .build_widget(ctx, "export flags")
+                },
             ]),
             Widget::horiz_separator(ctx, 1.0),
             self.calculate_tags(ctx, app),
@@ -103,6 +120,14 @@ impl Viewer {
                     ])
                     .evenly_spaced(),
                 );
+                if self.fixed_object_outline.is_some() {
+                    col.push(
+                        ctx.style()
+                            .btn_outline
+                            .text("Flag a problem with this road")
+                            .build_def(ctx),
+                    );
+                }
 
                 let tags = &r.osm_tags;
                 for (k, v) in tags.inner() {
@@ -308,6 +333,27 @@ impl State<App> for Viewer {
                     self.businesses = Some(BusinessSearch::new(ctx, app));
                     self.recalculate_top_panel(ctx, app, None);
                 }
+                "Flag a problem with this road" => {
+                    if let Some(ID::Lane(l)) = app.current_selection {
+                        return Transition::Push(flag_road(ctx, l.road));
+                    }
+                }
+                "export flags" => {
+                    return Transition::Push(match self.flags.export(&app.map) {
+                        Ok(path) => PopupMsg::new_state(
+                            ctx,
+                            "Flags exported",
+                            vec![
+                                format!("Wrote {}", path),
+                                "You can use this file to create a MapRoulette challenge"
+                                    .to_string(),
+                            ],
+                        ),
+                        Err(err) => {
+                            PopupMsg::new_state(ctx, "Export failed", vec![err.to_string()])
+                        }
+                    });
+                }
                 "Hide business search" => {
                     self.businesses = None;
                     self.recalculate_top_panel(ctx, app, None);
@@ -350,6 +396,7 @@ impl State<App> for Viewer {
             app.draw_zoomed(g, DrawOptions::new());
         }
 
+        g.redraw(&self.draw_flags);
         self.top_panel.draw(g);
         self.minimap.draw(g, app);
         if let Some(ref d) = self.fixed_object_outline {
@@ -364,6 +411,58 @@ impl State<App> for Viewer {
     }
 }
 
+impl Viewer {
+    fn add_flag(&mut self, ctx: &mut EventCtx, app: &App, flag: Flag) {
+        self.flags.list.push(flag);
+        let mut batch = GeomBatch::new();
+        for flag in &self.flags.list {
+            batch.push(
+                Color::ORANGE.alpha(0.8),
+                app.map.get_r(flag.road).get_thick_polygon(),
+            );
+        }
+        self.draw_flags = ctx.upload(batch);
+
+        let biz_search = self.top_panel.take("Search for businesses");
+        self.recalculate_top_panel(ctx, app, Some(biz_search));
+    }
+}
+
+fn flag_road(ctx: &mut EventCtx, road: map_model::RoadID) -> Box<dyn State<App>> {
+    ChooseSomething::new_state(
+        ctx,
+        "What's wrong with this road?",
+        FlagType::all()
+            .into_iter()
+            .map(|f| Choice::new(f.describe(), f))
+            .collect(),
+        Box::new(move |flag_type, ctx, _| {
+            Transition::Replace(PromptInput::new_state(
+                ctx,
+                "Any notes for the mapper fixing this? (optional)",
+                String::new(),
+                Box::new(move |note, _, _| {
+                    Transition::Multi(vec![
+                        Transition::Pop,
+                        Transition::ModifyState(Box::new(move |state, ctx, app| {
+                            let viewer = state.downcast_mut::<Viewer>().unwrap();
+                            viewer.add_flag(
+                                ctx,
+                                app,
+                                Flag {
+                                    road,
+                                    flag_type,
+                                    note,
+                                },
+                            );
+                        })),
+                    ])
+                }),
+            ))
+        }),
+    )
+}
+
 struct BusinessSearch {
     counts: Counter<String>,
     show: BTreeSet<String>,
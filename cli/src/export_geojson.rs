@@ -0,0 +1,53 @@
+use geojson::{Feature, FeatureCollection, GeoJson};
+
+use abstutil::Timer;
+use map_model::Map;
+
+pub fn run(map: String, output: String) {
+    let map = Map::load_synchronously(map, &mut Timer::new("load map"));
+    let gps_bounds = Some(map.get_gps_bounds());
+
+    let mut features = Vec::new();
+    let mut add = |geometry, kind: &str, id: String| {
+        let mut props = geojson::JsonObject::new();
+        props.insert("type".to_string(), kind.into());
+        props.insert("id".to_string(), id.into());
+        features.push(Feature {
+            bbox: None,
+            geometry: Some(geometry),
+            id: None,
+            properties: Some(props),
+            foreign_members: None,
+        });
+    };
+    for i in map.all_intersections() {
+        add(
+            i.polygon.to_geojson(gps_bounds),
+            "intersection",
+            i.orig_id.to_string(),
+        );
+    }
+    for r in map.all_roads() {
+        add(
+            r.center_pts
+                .to_thick_ring(r.get_width())
+                .to_geojson(gps_bounds),
+            "road",
+            r.orig_id.osm_way_id.to_string(),
+        );
+    }
+    for b in map.all_buildings() {
+        add(
+            b.polygon.to_geojson(gps_bounds),
+            "building",
+            b.orig_id.to_string(),
+        );
+    }
+
+    let geojson = GeoJson::from(FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    });
+    abstio::write_json(output, &geojson);
+}
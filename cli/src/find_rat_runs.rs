@@ -0,0 +1,59 @@
+use geojson::{Feature, FeatureCollection, GeoJson};
+
+use abstutil::{prettyprint_usize, Timer};
+use map_model::Map;
+use synthpop::Scenario;
+
+pub fn run(scenario: String, min_pct_through: usize, output: String) {
+    let mut timer = Timer::new("find rat-runs");
+    let scenario: Scenario = abstio::must_read_object(scenario, &mut timer);
+    let map = Map::load_synchronously(scenario.map_name.path(), &mut timer);
+
+    let ranking: Vec<_> = synthpop::find_through_traffic(&map, &scenario, &mut timer)
+        .into_iter()
+        .filter(|x| x.pct_through() >= min_pct_through as f64)
+        .collect();
+
+    let mut features = Vec::new();
+    for (idx, x) in ranking.iter().enumerate() {
+        let road = map.get_r(x.road);
+        if idx < 10 {
+            info!(
+                "#{}: {} carries {} trips passing through, {:.0}% of its traffic",
+                idx + 1,
+                road.get_name(None),
+                prettyprint_usize(x.through),
+                x.pct_through()
+            );
+        }
+
+        let mut props = geojson::JsonObject::new();
+        props.insert("rank".to_string(), (idx + 1).into());
+        props.insert("name".to_string(), road.get_name(None).into());
+        props.insert(
+            "osm_way_id".to_string(),
+            road.orig_id.osm_way_id.to_string().into(),
+        );
+        props.insert("total_trips".to_string(), x.total.into());
+        props.insert("through_trips".to_string(), x.through.into());
+        props.insert("pct_through".to_string(), x.pct_through().into());
+        features.push(Feature {
+            bbox: None,
+            geometry: Some(road.center_pts.to_geojson(Some(map.get_gps_bounds()))),
+            id: None,
+            properties: Some(props),
+            foreign_members: None,
+        });
+    }
+    info!(
+        "{} local streets carry through-traffic",
+        prettyprint_usize(features.len())
+    );
+
+    let geojson = GeoJson::from(FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    });
+    abstio::write_json(output, &geojson);
+}
@@ -0,0 +1,67 @@
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use abstio::CityName;
+use abstutil::prettyprint_usize;
+use collisions::{Collision, CollisionDataset, Mode, Severity};
+use geom::{Duration, LonLat};
+
+pub fn run(csv_path: String, city: String, source_url: Option<String>) -> Result<()> {
+    let city = CityName::parse(&city)?;
+    let mut data = CollisionDataset {
+        source_url: source_url.unwrap_or_else(|| csv_path.clone()),
+        collisions: Vec::new(),
+    };
+    for (idx, rec) in csv::Reader::from_reader(fs_err::File::open(csv_path)?)
+        .deserialize()
+        .enumerate()
+    {
+        let rec: Record = rec?;
+        // The header is line 1
+        let line = idx + 2;
+        data.collisions.push(Collision {
+            location: LonLat::new(rec.longitude, rec.latitude),
+            time: parse_time(&rec.time)
+                .ok_or_else(|| anyhow!("Line {}: bad time {}", line, rec.time))?,
+            severity: match rec.severity.to_lowercase().as_ref() {
+                "slight" | "minor" => Severity::Slight,
+                "serious" => Severity::Serious,
+                "fatal" => Severity::Fatal,
+                x => bail!("Line {}: unknown severity {}", line, x),
+            },
+            mode: match rec.mode.as_ref().map(|x| x.to_lowercase()).as_deref() {
+                None => None,
+                Some("pedestrian") | Some("walk") => Some(Mode::Pedestrian),
+                Some("bicycle") | Some("bike") => Some(Mode::Bicycle),
+                Some("motorcycle") => Some(Mode::Motorcycle),
+                Some("car") | Some("vehicle") => Some(Mode::Car),
+                Some(x) => bail!("Line {}: unknown mode {}", line, x),
+            },
+        });
+    }
+
+    println!(
+        "Imported {} collisions",
+        prettyprint_usize(data.collisions.len())
+    );
+    abstio::write_binary(city.input_path("collisions.bin"), &data);
+    Ok(())
+}
+
+// Accepts HH:MM or HH:MM:SS
+fn parse_time(x: &str) -> Option<Duration> {
+    if x.matches(':').count() == 1 {
+        Duration::parse(&format!("{}:00", x)).ok()
+    } else {
+        Duration::parse(x).ok()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Record {
+    longitude: f64,
+    latitude: f64,
+    time: String,
+    severity: String,
+    mode: Option<String>,
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use serde::Deserialize;
+
+use abstutil::{prettyprint_usize, Timer};
+use geom::Polygon;
+use map_model::Map;
+use popdat::od::{IncludeZonePolicy, ZoneTrips};
+use synthpop::{Scenario, TripMode};
+
+pub fn run(
+    csv_path: String,
+    zones_path: String,
+    zone_id_key: String,
+    map: String,
+    rng_seed: u64,
+    scenario_name: String,
+) -> Result<()> {
+    let mut timer = Timer::new("import origin-destination matrix");
+    let map = Map::load_synchronously(map, &mut timer);
+
+    timer.start("parse input");
+    let counts = parse_counts(csv_path)?;
+    let zones = parse_zones(&map, zones_path, &zone_id_key)?;
+    timer.stop("parse input");
+
+    let mut rng = XorShiftRng::seed_from_u64(rng_seed);
+    let mut s = Scenario::empty(&map, &scenario_name);
+    // Include all buses/trains
+    s.only_seed_buses = None;
+    s.people = popdat::od::disaggregate_trips(
+        &map,
+        zones,
+        counts,
+        IncludeZonePolicy::AllowRemote,
+        &mut rng,
+        &mut timer,
+    );
+    s = s.remove_weird_schedules(true);
+    println!("Imported {} people", prettyprint_usize(s.people.len()));
+    s.save();
+
+    Ok(())
+}
+
+fn parse_counts(csv_path: String) -> Result<Vec<ZoneTrips>> {
+    let mut counts = Vec::new();
+    for rec in csv::Reader::from_reader(fs_err::File::open(csv_path)?).deserialize() {
+        let rec: Record = rec?;
+        let mode = match rec.mode.as_ref() {
+            "drive" => TripMode::Drive,
+            "bike" => TripMode::Bike,
+            "walk" => TripMode::Walk,
+            "transit" => TripMode::Transit,
+            x => bail!("Unknown mode {}", x),
+        };
+        if rec.hour > 23 {
+            bail!("Hour {} isn't between 0 and 23", rec.hour);
+        }
+        if rec.count > 0 {
+            counts.push(ZoneTrips {
+                origin_zone: rec.origin,
+                destination_zone: rec.destination,
+                mode,
+                hour: rec.hour,
+                count: rec.count,
+            });
+        }
+    }
+    Ok(counts)
+}
+
+// Transforms all zones into the map's coordinate space, no matter how far out-of-bounds they are.
+fn parse_zones(map: &Map, path: String, zone_id_key: &str) -> Result<HashMap<String, Polygon>> {
+    let mut zones = HashMap::new();
+    let require_in_bounds = false;
+    for (polygon, tags) in Polygon::from_geojson_bytes(
+        &abstio::slurp_file(path)?,
+        map.get_gps_bounds(),
+        require_in_bounds,
+    )? {
+        zones.insert(tags.get_result(zone_id_key)?.to_string(), polygon);
+    }
+    Ok(zones)
+}
+
+#[derive(Debug, Deserialize)]
+struct Record {
+    origin: String,
+    destination: String,
+    mode: String,
+    hour: usize,
+    count: usize,
+}
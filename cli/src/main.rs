@@ -1,16 +1,24 @@
 //! A collection of tools, mostly related to importing maps and scenarios. These are bundled as a
-//! single executable to reduce the cost of static linking in the release's file size.
+//! single executable to reduce the cost of static linking in the release's file size. Run with
+//! `--help` to list everything it can do.
 
+#[macro_use]
+extern crate anyhow;
 #[macro_use]
 extern crate log;
 
 mod augment_scenario;
 mod clip_osm;
+mod export_geojson;
+mod find_rat_runs;
 mod generate_houses;
 mod geojson_to_osmosis;
+mod import_collisions;
 mod import_grid2demand;
+mod import_od_matrix;
 mod import_scenario;
 mod one_step_import;
+mod route;
 
 use std::io::Write;
 
@@ -21,11 +29,25 @@ use importer::Job;
 use structopt::StructOpt;
 
 use abstutil::Timer;
+use geom::LonLat;
 
 #[derive(StructOpt)]
 #[structopt(name = "abcli", about = "The A/B Street multi-tool")]
+struct Args {
+    /// Print logs and progress as JSON lines, for other programs to parse.
+    #[structopt(long, global = true)]
+    json_logs: bool,
+    /// Use at most this many threads for parallel work. By default, all CPUs are used.
+    #[structopt(long, global = true)]
+    max_threads: Option<usize>,
+    #[structopt(subcommand)]
+    cmd: Command,
+}
+
+#[derive(StructOpt)]
 enum Command {
     /// Print a binary map or scenario file as JSON
+    #[structopt(alias = "export")]
     DumpJSON {
         #[structopt()]
         path: String,
@@ -86,6 +108,21 @@ enum Command {
         #[structopt()]
         input: String,
     },
+    /// Import real-world collision records, such as police reports, for a city. The apps match them
+    /// to the nearest road or intersection.
+    ImportCollisions {
+        /// The path to a CSV file with columns longitude, latitude, time, severity, and mode. Time
+        /// is HH:MM or HH:MM:SS. Severity is slight, serious, or fatal. Mode is optional, and can
+        /// be pedestrian, bicycle, motorcycle, or car.
+        #[structopt(long)]
+        input: String,
+        /// The city to import for, like `us/seattle`. Any previous collision data is replaced.
+        #[structopt(long)]
+        city: String,
+        /// A URL describing the original data source. Defaults to the input path.
+        #[structopt(long)]
+        source_url: Option<String>,
+    },
     /// Import a scenario from https://github.com/asu-trans-ai-lab/grid2demand.
     ImportGrid2Demand {
         /// The path to a grid2demand CSV file
@@ -95,6 +132,30 @@ enum Command {
         #[structopt(long)]
         map: String,
     },
+    /// Generate a scenario from an origin-destination matrix. Each person takes a single trip
+    /// between buildings sampled from the origin and destination zones.
+    #[structopt(name = "import-od-matrix")]
+    ImportODMatrix {
+        /// The path to a CSV file with columns origin, destination, mode, hour, and count. Modes
+        /// are drive, bike, walk, or transit. Hours are between 0 and 23.
+        #[structopt(long)]
+        input: String,
+        /// The path to a GeoJSON file with a polygon for each zone
+        #[structopt(long)]
+        zones: String,
+        /// The property in the GeoJSON file identifying each zone
+        #[structopt(long, default_value = "id")]
+        zone_id_key: String,
+        /// The path to a map to generate a scenario for
+        #[structopt(long)]
+        map: String,
+        /// A seed for generating random numbers
+        #[structopt(long, default_value = "42")]
+        rng_seed: u64,
+        /// The name of the scenario to create
+        #[structopt(long, default_value = "od_matrix")]
+        scenario_name: String,
+    },
     /// Import a JSON scenario in the
     /// https://a-b-street.github.io/docs/tech/dev/formats/scenarios.html format
     ImportScenario {
@@ -119,12 +180,68 @@ enum Command {
         #[structopt(long)]
         output: String,
     },
+    /// Write the geometry of a map's intersections, roads, and buildings as GeoJSON.
+    #[structopt(name = "export-geojson")]
+    ExportGeoJSON {
+        /// The path to a map
+        #[structopt(long)]
+        map: String,
+        /// The GeoJSON file to write
+        #[structopt(long)]
+        output: String,
+    },
+    /// Rank the local streets carrying the most through-traffic in a scenario, and write them as
+    /// GeoJSON. This is a starting point for planning low-traffic neighborhoods.
+    FindRatRuns {
+        /// The path to a scenario. Only driving trips are used.
+        #[structopt(long)]
+        scenario: String,
+        /// Only include streets where at least this percent of the traffic is passing through
+        #[structopt(long, default_value = "0")]
+        min_pct_through: usize,
+        /// The GeoJSON file to write
+        #[structopt(long)]
+        output: String,
+    },
+    /// Simulate a map, scenario, or savestate, printing progress as it runs.
+    Sim {
+        /// How many hours to simulate. If omitted, run until every trip is done.
+        #[structopt(long)]
+        hours: Option<usize>,
+        #[structopt(flatten)]
+        flags: sim::SimFlags,
+    },
+    /// Find a route between two points on a map, and print it as a GeoJSON feature.
+    Route {
+        /// The path to a map
+        #[structopt(long)]
+        map: String,
+        /// Where to start, as "longitude,latitude". The closest building is used.
+        #[structopt(long, parse(try_from_str = parse_lon_lat))]
+        from: LonLat,
+        /// Where to end, as "longitude,latitude". The closest building is used.
+        #[structopt(long, parse(try_from_str = parse_lon_lat))]
+        to: LonLat,
+        /// walk, bike, or drive
+        #[structopt(long, default_value = "drive")]
+        mode: String,
+    },
     /// Removes nonessential parts of a Map, for the bike network tool.
     MinifyMap {
         /// The path to a map to shrink. The map is modified in-place.
         #[structopt()]
         map: String,
     },
+    /// Compares two imports of the same map, listing roads and intersections that were added or
+    /// removed, lane changes, and traffic signal changes.
+    DiffMaps {
+        /// The path to the older version of the map
+        #[structopt(long)]
+        old: String,
+        /// The path to the newer version of the map
+        #[structopt(long)]
+        new: String,
+    },
     /// Procedurally generates houses along empty residential roads of a map
     GenerateHouses {
         /// The path to a map to generate houses for
@@ -169,6 +286,10 @@ enum Command {
         /// Downgrade crosswalks not matching a `highway=crossing` OSM node into unmarked crossings.
         #[structopt(long)]
         filter_crosswalks: bool,
+        /// Do vehicles already in a roundabout yield to those entering it, like some older French
+        /// roundabouts? Normally entering vehicles yield.
+        #[structopt(long)]
+        roundabouts_yield_to_entering: bool,
         /// Generate a simple travel demand model based on 2011 UK commuting data. This will only
         /// work if the boundary is in the UK.
         #[structopt(long)]
@@ -188,6 +309,10 @@ enum Command {
         /// Downgrade crosswalks not matching a `highway=crossing` OSM node into unmarked crossings.
         #[structopt(long)]
         filter_crosswalks: bool,
+        /// Do vehicles already in a roundabout yield to those entering it, like some older French
+        /// roundabouts? Normally entering vehicles yield.
+        #[structopt(long)]
+        roundabouts_yield_to_entering: bool,
         /// Generate a simple travel demand model based on 2011 UK commuting data. This will only
         /// work if the boundary is in the UK.
         #[structopt(long)]
@@ -215,12 +340,23 @@ enum Command {
     },
     /// Simulate a full day of a scenario, and write the "prebaked results," so the UI can later be
     /// used for A/B testing.
-    #[structopt(name = "prebake-scenario")]
+    #[structopt(name = "prebake-scenario", alias = "prebake")]
     PrebakeScenario {
         /// The path to a scenario file
         #[structopt()]
         scenario_path: String,
     },
+    /// Simulate a full day of a scenario several times with different random seeds, and write how
+    /// much travel times vary between runs.
+    #[structopt(name = "prebake-reliability")]
+    PrebakeReliability {
+        /// The path to a scenario file
+        #[structopt()]
+        scenario_path: String,
+        /// How many times to run the scenario
+        #[structopt(long, default_value = "10")]
+        num_seeds: u64,
+    },
 }
 
 // See https://github.com/TeXitoi/structopt/issues/94
@@ -230,21 +366,35 @@ fn parse_modifiers(x: &str) -> Result<ModifierList> {
     abstutil::from_json(&x.to_string().into_bytes())
 }
 
+fn parse_lon_lat(x: &str) -> Result<LonLat> {
+    let parts = x.split(',').collect::<Vec<_>>();
+    if parts.len() != 2 {
+        bail!("{} isn't \"longitude,latitude\"", x);
+    }
+    Ok(LonLat::new(
+        parts[0].trim().parse::<f64>()?,
+        parts[1].trim().parse::<f64>()?,
+    ))
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cmd = Command::from_args();
+    let args = Args::from_args();
 
     // All but a few commands want logging
-    if !matches!(
-        cmd,
-        Command::DumpJSON { .. } | Command::PickGeofabrik { .. },
+    if args.json_logs {
+        abstutil::logger::setup_json();
+    } else if !matches!(
+        args.cmd,
+        Command::DumpJSON { .. } | Command::PickGeofabrik { .. } | Command::Route { .. },
     ) {
         abstutil::logger::setup();
     }
+    abstutil::set_max_threads(args.max_threads);
 
     // Short implementations can stay in this file, but please split larger subcommands to their
     // own module.
-    match Command::from_args() {
+    match args.cmd {
         Command::DumpJSON { path } => dump_json(path),
         Command::RandomScenario {
             rng_seed,
@@ -270,14 +420,41 @@ async fn main() -> Result<()> {
             out_path,
         } => clip_osm::run(pbf_path, clip_path, out_path)?,
         Command::GeoJSONToOsmosis { input } => geojson_to_osmosis::run(input)?,
+        Command::ImportCollisions {
+            input,
+            city,
+            source_url,
+        } => import_collisions::run(input, city, source_url)?,
         Command::ImportGrid2Demand { input, map } => import_grid2demand::run(input, map)?,
+        Command::ImportODMatrix {
+            input,
+            zones,
+            zone_id_key,
+            map,
+            rng_seed,
+            scenario_name,
+        } => import_od_matrix::run(input, zones, zone_id_key, map, rng_seed, scenario_name)?,
         Command::ImportScenario {
             input,
             map,
             skip_problems,
         } => import_scenario::run(input, map, skip_problems),
         Command::ImportJSONMap { input, output } => import_json_map(input, output),
+        Command::ExportGeoJSON { map, output } => export_geojson::run(map, output),
+        Command::FindRatRuns {
+            scenario,
+            min_pct_through,
+            output,
+        } => find_rat_runs::run(scenario, min_pct_through, output),
+        Command::Sim { hours, flags } => run_sim(hours, flags),
+        Command::Route {
+            map,
+            from,
+            to,
+            mode,
+        } => route::run(map, from, to, mode)?,
         Command::MinifyMap { map } => minify_map(map),
+        Command::DiffMaps { old, new } => diff_maps(old, new),
         Command::GenerateHouses {
             map,
             num_required,
@@ -293,6 +470,7 @@ async fn main() -> Result<()> {
             drive_on_left,
             use_geofabrik,
             filter_crosswalks,
+            roundabouts_yield_to_entering,
             create_uk_travel_demand_model,
         } => {
             one_step_import::run(
@@ -301,6 +479,7 @@ async fn main() -> Result<()> {
                 drive_on_left,
                 use_geofabrik,
                 filter_crosswalks,
+                roundabouts_yield_to_entering,
                 create_uk_travel_demand_model,
             )
             .await?
@@ -310,6 +489,7 @@ async fn main() -> Result<()> {
             clip_path,
             drive_on_left,
             filter_crosswalks,
+            roundabouts_yield_to_entering,
             create_uk_travel_demand_model,
             opts,
         } => {
@@ -318,6 +498,7 @@ async fn main() -> Result<()> {
                 clip_path,
                 drive_on_left,
                 filter_crosswalks,
+                roundabouts_yield_to_entering,
                 create_uk_travel_demand_model,
                 opts,
             )
@@ -330,6 +511,10 @@ async fn main() -> Result<()> {
         Command::RegenerateEverythingExternally => regenerate_everything_externally()?,
         Command::Import { job } => job.run(&mut Timer::new("import one city")).await,
         Command::PrebakeScenario { scenario_path } => prebake_scenario(scenario_path),
+        Command::PrebakeReliability {
+            scenario_path,
+            num_seeds,
+        } => prebake_reliability(scenario_path, num_seeds),
     }
     Ok(())
 }
@@ -377,6 +562,28 @@ fn import_json_map(input: String, output: String) {
     abstio::write_binary(output, &map);
 }
 
+fn run_sim(hours: Option<usize>, mut flags: sim::SimFlags) {
+    flags.initialize();
+    let mut timer = Timer::new("run simulation");
+    let (map, mut sim, _) = flags.load_synchronously(&mut timer);
+    match hours {
+        Some(hours) => {
+            sim.timed_step(&map, geom::Duration::hours(hours), &mut None, &mut timer);
+        }
+        None => {
+            // Some scenarios have trips that never finish, so don't run past the end of the day
+            while !sim.is_done()
+                && sim.time() < geom::Time::START_OF_DAY + geom::Duration::hours(24)
+            {
+                sim.timed_step(&map, geom::Duration::hours(1), &mut None, &mut timer);
+            }
+        }
+    }
+    for line in sim.kpis(&map).describe() {
+        info!("{}", line);
+    }
+}
+
 fn minify_map(path: String) {
     let mut timer = Timer::new("minify map");
     let mut map = map_model::Map::load_synchronously(path, &mut timer);
@@ -385,6 +592,20 @@ fn minify_map(path: String) {
     map.save();
 }
 
+fn diff_maps(old: String, new: String) {
+    let mut timer = Timer::new("diff maps");
+    let old = map_model::Map::load_synchronously(old, &mut timer);
+    let new = map_model::Map::load_synchronously(new, &mut timer);
+    let diff = map_model::MapDiff::new(&old, &new);
+    for line in diff.describe() {
+        println!("{}", line);
+    }
+    println!();
+    for (label, count) in diff.summarize() {
+        println!("{} {}", abstutil::prettyprint_usize(count), label);
+    }
+}
+
 fn regenerate_everything_externally() -> Result<()> {
     let path = "regenerate.sh";
     let mut f = File::create(path)?;
@@ -412,3 +633,15 @@ fn prebake_scenario(path: String) {
     let map = map_model::Map::load_synchronously(scenario.map_name.path(), &mut timer);
     sim::prebake::prebake(&map, scenario, &mut timer);
 }
+
+fn prebake_reliability(path: String, num_seeds: u64) {
+    let mut timer = Timer::new("prebake reliability");
+    let scenario: synthpop::Scenario = abstio::must_read_object(path, &mut timer);
+    let map = map_model::Map::load_synchronously(scenario.map_name.path(), &mut timer);
+    let results = sim::prebake::prebake_reliability(&map, scenario, num_seeds, &mut timer);
+    println!(
+        "{} origin/destination pairs over {} runs",
+        results.travel_times.len(),
+        results.num_runs
+    );
+}
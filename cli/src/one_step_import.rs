@@ -11,6 +11,7 @@ pub async fn run(
     drive_on_left: bool,
     use_geofabrik: bool,
     filter_crosswalks: bool,
+    roundabouts_yield_to_entering: bool,
     create_uk_travel_demand_model: bool,
 ) -> Result<()> {
     if name.contains(' ') || name.is_empty() {
@@ -84,6 +85,7 @@ pub async fn run(
         Some("boundary0.poly".to_string()),
         !drive_on_left,
         filter_crosswalks,
+        roundabouts_yield_to_entering,
         create_uk_travel_demand_model,
         map_model::RawToMapOptions::default(),
     )
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use geojson::{Feature, GeoJson};
+
+use abstutil::Timer;
+use geom::{Distance, FindClosest, LonLat};
+use map_model::{Map, PathConstraints, PathRequest};
+
+pub fn run(map: String, from: LonLat, to: LonLat, mode: String) -> Result<()> {
+    let constraints = match mode.as_ref() {
+        "walk" => PathConstraints::Pedestrian,
+        "bike" => PathConstraints::Bike,
+        "drive" => PathConstraints::Car,
+        _ => bail!("Unknown mode {}; use walk, bike, or drive", mode),
+    };
+    let map = Map::load_synchronously(map, &mut Timer::new("load map"));
+
+    let mut closest = FindClosest::new(map.get_bounds());
+    for b in map.all_buildings() {
+        closest.add(b.id, &[b.polygon.center()]);
+    }
+    let threshold = Distance::meters(100.0);
+    let find = |pt: LonLat| {
+        closest
+            .closest_pt(pt.to_pt(map.get_gps_bounds()), threshold)
+            .map(|(b, _)| b)
+            .ok_or_else(|| anyhow!("No building within {} of {}", threshold, pt))
+    };
+    let start = find(from)?;
+    let end = find(to)?;
+
+    let req = PathRequest::between_buildings(&map, start, end, constraints)
+        .ok_or_else(|| anyhow!("Can't {} between {} and {}", mode, start, end))?;
+    let path = map.pathfind(req)?;
+    let pl = path
+        .trace(&map)
+        .ok_or_else(|| anyhow!("The path from {} to {} has no geometry", start, end))?;
+    // Assume the agent can go as fast as the road allows
+    let duration = path.estimate_duration(&map, None);
+    info!(
+        "Route from {} to {} is {}, taking about {}",
+        start,
+        end,
+        path.total_length(),
+        duration
+    );
+
+    let mut props = geojson::JsonObject::new();
+    props.insert("mode".to_string(), mode.into());
+    props.insert(
+        "length_meters".to_string(),
+        path.total_length().inner_meters().into(),
+    );
+    props.insert(
+        "duration_seconds".to_string(),
+        duration.inner_seconds().into(),
+    );
+    let feature = Feature {
+        bbox: None,
+        geometry: Some(pl.to_geojson(Some(map.get_gps_bounds()))),
+        id: None,
+        properties: Some(props),
+        foreign_members: None,
+    };
+    println!("{}", GeoJson::from(feature));
+    Ok(())
+}
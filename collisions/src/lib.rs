@@ -27,6 +27,8 @@ pub struct Collision {
     pub time: Duration,
     /// The severity reported in the original data source.
     pub severity: Severity,
+    /// The most vulnerable road user involved, if the data source records it.
+    pub mode: Option<Mode>,
     /* TODO Many more interesting and common things: the date, the number of
      * people/vehicles/bikes/casualties, road/weather/alcohol/speeding conditions possibly
      * influencing the event, etc. */
@@ -42,6 +44,16 @@ pub enum Severity {
     Fatal,
 }
 
+/// The kind of road user involved in a collision. When several are involved, data sources should
+/// report the most vulnerable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    Pedestrian,
+    Bicycle,
+    Motorcycle,
+    Car,
+}
+
 /// Import data from the UK STATS19 dataset. See https://github.com/ropensci/stats19. Any parsing
 /// errors will skip the row and log a warning.
 pub fn import_stats19(input: ExtraShapes, source_url: &str) -> CollisionDataset {
@@ -75,6 +87,7 @@ pub fn import_stats19(input: ExtraShapes, source_url: &str) -> CollisionDataset
             location: shape.points[0],
             time,
             severity,
+            mode: None,
         });
     }
     data
@@ -119,6 +132,7 @@ pub fn import_seattle(input: ExtraShapes, source_url: &str) -> CollisionDataset
             location: shape.points[0],
             time,
             severity,
+            mode: None,
         });
     }
     data
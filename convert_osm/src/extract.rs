@@ -24,8 +24,12 @@ pub struct OsmExtract {
     pub simple_turn_restrictions: Vec<(RestrictionType, WayID, NodeID, WayID)>,
     /// (relation ID, from way ID, via way ID, to way ID)
     pub complicated_turn_restrictions: Vec<(RelationID, WayID, WayID, WayID)>,
+    /// (from way ID, via node ID, to way ID, condition like `no_left_turn @ (07:00-09:00)`)
+    pub conditional_turn_restrictions: Vec<(WayID, NodeID, WayID, String)>,
     /// (location, amenity)
     pub amenities: Vec<(Pt2D, Amenity)>,
+    /// (location, addr:* tags) from nodes that aren't part of a building's outline
+    pub address_pts: Vec<(Pt2D, Tags)>,
     /// Crosswalks located at these points, which should be on a RawRoad's center line
     pub crosswalks: HashSet<HashablePt2D>,
 }
@@ -64,7 +68,9 @@ pub fn extract_osm(
         osm_node_ids: HashMap::new(),
         simple_turn_restrictions: Vec::new(),
         complicated_turn_restrictions: Vec::new(),
+        conditional_turn_restrictions: Vec::new(),
         amenities: Vec::new(),
+        address_pts: Vec::new(),
         crosswalks: HashSet::new(),
     };
 
@@ -87,6 +93,10 @@ pub fn extract_osm(
         for amenity in get_bldg_amenities(&node.tags) {
             out.amenities.push((node.pt, amenity));
         }
+        if node.tags.contains_key("addr:housenumber") {
+            out.address_pts
+                .push((node.pt, get_address_tags(&node.tags)));
+        }
     }
 
     // and cycleways
@@ -117,6 +127,7 @@ pub fn extract_osm(
                     osm_tags: way.tags.clone(),
                     turn_restrictions: Vec::new(),
                     complicated_turn_restrictions: Vec::new(),
+                    conditional_turn_restrictions: Vec::new(),
                     percent_incline: 0.0,
                     // Start assuming there's a crosswalk everywhere, and maybe filter it down
                     // later
@@ -260,6 +271,13 @@ pub fn extract_osm(
                     }
                 }
             }
+            // Only handle time-of-day restrictions via a node. The hours are parsed later.
+            if let Some(condition) = rel.tags.get("restriction:conditional") {
+                if let (Some(from), Some(via), Some(to)) = (from_way_id, via_node_id, to_way_id) {
+                    out.conditional_turn_restrictions
+                        .push((from, via, to, condition.clone()));
+                }
+            }
         } else if is_bldg(&rel.tags) {
             match multipoly_geometry(id, rel, &doc) {
                 Ok(polygon) => {
@@ -367,9 +385,9 @@ fn is_road(tags: &mut Tags, opts: &Options, name: &MapName) -> bool {
     if tags.is("railway", "rail") && opts.include_railroads {
         return true;
     }
-    // Explicitly need this to avoid overlapping geometry in Berlin.
     if tags.is("railway", "tram") {
-        return false;
+        // Explicitly need to skip street-running trams to avoid overlapping geometry in Berlin.
+        return opts.include_trams && !tags.is("embedded", "yes");
     }
 
     let highway = if let Some(x) = tags.get(osm::HIGHWAY) {
@@ -525,6 +543,17 @@ fn is_bldg(tags: &Tags) -> bool {
     tags.contains_key("building") && !tags.contains_key("abandoned:man_made")
 }
 
+/// Only keep the addr:* tags
+fn get_address_tags(tags: &Tags) -> Tags {
+    let mut result = Tags::empty();
+    for (k, v) in tags.inner() {
+        if k.starts_with("addr:") {
+            result.insert(k, v);
+        }
+    }
+    result
+}
+
 fn get_bldg_amenities(tags: &Tags) -> Vec<Amenity> {
     let mut amenities = Vec::new();
     for key in ["amenity", "shop", "craft", "office", "tourism", "leisure"] {
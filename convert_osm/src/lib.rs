@@ -31,6 +31,9 @@ pub struct Options {
     pub private_offstreet_parking: PrivateOffstreetParking,
     /// OSM railway=rail will be included as light rail if so. Cosmetic only.
     pub include_railroads: bool,
+    /// OSM railway=tram on its own right-of-way will be included as light rail if so. Tracks
+    /// embedded in a street are always skipped, since they overlap the road.
+    pub include_trams: bool,
     /// If provided, read polygons from this GeoJSON file and add them to the RawMap as buildings.
     pub extra_buildings: Option<String>,
     /// Only include highways and arterials. This may make sense for some region-wide maps for
@@ -103,6 +106,7 @@ pub fn convert(
     map.roads.retain(|r, _| r.i1 != r.i2);
 
     use_amenities(&mut map, split_output.amenities, timer);
+    use_address_points(&mut map, split_output.address_pts, timer);
 
     parking::apply_parking(&mut map, &opts, timer);
 
@@ -152,6 +156,28 @@ fn use_amenities(map: &mut RawMap, amenities: Vec<(Pt2D, Amenity)>, timer: &mut
     }
 }
 
+/// Addresses are often mapped as separate points inside a building, especially when a building
+/// has a few entrances. Copy the address onto the building, unless it already has one.
+fn use_address_points(map: &mut RawMap, address_pts: Vec<(Pt2D, Tags)>, timer: &mut Timer) {
+    let mut closest: FindClosest<osm::OsmID> = FindClosest::new(&map.gps_bounds.to_bounds());
+    for (id, b) in &map.buildings {
+        closest.add(*id, b.polygon.points());
+    }
+
+    timer.start_iter("match building address points", address_pts.len());
+    for (pt, tags) in address_pts {
+        timer.next();
+        if let Some((id, _)) = closest.closest_pt(pt, Distance::meters(50.0)) {
+            let b = map.buildings.get_mut(&id).unwrap();
+            if b.polygon.contains_pt(pt) && !b.osm_tags.contains_key("addr:housenumber") {
+                for (k, v) in tags.inner() {
+                    b.osm_tags.insert(k, v);
+                }
+            }
+        }
+    }
+}
+
 fn add_extra_buildings(map: &mut RawMap, path: &str) -> Result<()> {
     let require_in_bounds = true;
     let mut id = -1;
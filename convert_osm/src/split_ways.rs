@@ -1,6 +1,6 @@
 use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 
-use abstutil::{Counter, Timer};
+use abstutil::{Counter, Tags, Timer};
 use geom::{Distance, HashablePt2D, Pt2D};
 use raw_map::{
     osm, Amenity, Direction, IntersectionType, OriginalRoad, RawIntersection, RawMap, RawRoad,
@@ -10,6 +10,7 @@ use crate::extract::OsmExtract;
 
 pub struct Output {
     pub amenities: Vec<(Pt2D, Amenity)>,
+    pub address_pts: Vec<(Pt2D, Tags)>,
     pub crosswalks: HashSet<HashablePt2D>,
     /// A mapping of all points to the split road. Some internal points on roads get removed in
     /// `split_up_roads`, so this mapping isn't redundant.
@@ -161,6 +162,21 @@ pub fn split_up_roads(map: &mut RawMap, mut input: OsmExtract, timer: &mut Timer
             .push((rt, to));
     }
 
+    // Resolve turn restrictions that only apply during certain hours. Same idea.
+    for (from_osm, via_osm, to_osm, condition) in input.conditional_turn_restrictions {
+        let roads = map.roads_per_intersection(via_osm);
+        if let (Some(from), Some(to)) = (
+            roads.iter().find(|r| r.osm_way_id == from_osm),
+            roads.iter().find(|r| r.osm_way_id == to_osm),
+        ) {
+            map.roads
+                .get_mut(from)
+                .unwrap()
+                .conditional_turn_restrictions
+                .push((*to, condition));
+        }
+    }
+
     // Resolve complicated turn restrictions (via a way). TODO Only handle via ways immediately
     // connected to both roads, for now
     let mut complicated_restrictions = Vec::new();
@@ -229,6 +245,7 @@ pub fn split_up_roads(map: &mut RawMap, mut input: OsmExtract, timer: &mut Timer
     timer.stop("splitting up roads");
     Output {
         amenities: input.amenities,
+        address_pts: input.address_pts,
         crosswalks: input.crosswalks,
         pt_to_road,
     }
@@ -117,3 +117,9 @@ impl ops::Mul<Duration> for Speed {
         Distance::meters(self.0 * other.inner_seconds())
     }
 }
+
+impl Default for Speed {
+    fn default() -> Speed {
+        Speed::ZERO
+    }
+}
@@ -8,12 +8,18 @@
 //! it's now 01:01:00.0
 //! > curl http://localhost:1234/data/get-road-thruput
 //! ... huge JSON blob
+//!
+//! It can also check for unintended changes to the simulation after importing maps again:
+//!
+//! > cd headless; cargo run -- --regression-test
 
 #[macro_use]
 extern crate anyhow;
 #[macro_use]
 extern crate log;
 
+mod regression;
+
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::RwLock;
 
@@ -25,20 +31,28 @@ use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 
 use abstio::MapName;
-use abstutil::{serialize_btreemap, Timer};
-use geom::{Distance, Duration, FindClosest, LonLat, Time};
+use abstutil::{serialize_btreemap, Tags, Timer};
+use geom::{Distance, Duration, FindClosest, LonLat, Speed, Time};
+use map_model::raw::OriginalRoad;
 use map_model::{
-    CompressedMovementID, ControlTrafficSignal, EditCmd, EditIntersection, IntersectionID, Map,
-    MovementID, PermanentMapEdits, RoadID, TurnID,
+    connectivity, osm, AccessRestrictions, CompressedMovementID, ControlTrafficSignal, Direction,
+    EditCmd, EditIntersection, IntersectionID, LaneID, LaneType, Map, MovementID, PathConstraints,
+    PermanentEditCmd, PermanentMapEdits, Road, RoadID, TurnID,
 };
 use sim::{
-    AgentID, AgentType, DelayCause, PersonID, Sim, SimFlags, SimOptions, TripID, VehicleType,
+    AgentID, AgentType, DelayCause, PersonID, SavestateHistory, Sim, SimFlags, SimOptions, TripID,
+    TripInfo, TripResult, VehicleType,
 };
-use synthpop::{ExternalPerson, Scenario, ScenarioModifier, TripMode};
+use synthpop::{ExternalPerson, Scenario, ScenarioModifier, TripEndpoint, TripMode};
 
 lazy_static::lazy_static! {
     static ref MAP: RwLock<Map> = RwLock::new(Map::blank());
     static ref SIM: RwLock<Sim> = RwLock::new(Sim::new(&Map::blank(), SimOptions::new("tmp")));
+    static ref SAVESTATES: RwLock<SavestateHistory> = RwLock::new(SavestateHistory::new(
+        Duration::minutes(30),
+        Duration::minutes(1),
+        1 << 30
+    ));
     static ref LOAD: RwLock<LoadSim> = RwLock::new({
         LoadSim {
             scenario: abstio::path_scenario(&MapName::seattle("montlake"), "weekday"),
@@ -57,22 +71,68 @@ lazy_static::lazy_static! {
 )]
 struct Args {
     /// What port to run the JSON API on.
-    #[structopt(long)]
-    port: u16,
+    #[structopt(long, required_unless = "regression-test")]
+    port: Option<u16>,
     /// An arbitrary number to seed the random number generator. This is input to the deterministic
     /// simulation, so different values affect results.
     // TODO default_value can only handle strings, so copying SimFlags::RNG_SEED
     #[structopt(long, default_value = "42")]
     rng_seed: u64,
+    /// Print logs, progress, and summaries as JSON lines, for other programs to parse.
+    #[structopt(long)]
+    json_logs: bool,
     #[structopt(flatten)]
     opts: SimOptions,
+    /// Keep snapshots of the simulation in memory, so /sim/rewind-to-time doesn't have to start
+    /// over from midnight. Uses up to 1GB.
+    #[structopt(long)]
+    rewind_history: bool,
+    /// Instead of serving the API, run a short canonical scenario on maps and compare key metrics
+    /// against a goldenfile.
+    #[structopt(long)]
+    regression_test: bool,
+    /// With --regression-test, the path to a map to check. Can be repeated. Defaults to all maps
+    /// available locally.
+    #[structopt(long = "map")]
+    maps: Vec<String>,
+    /// With --regression-test, where the expected metrics are stored
+    #[structopt(long)]
+    goldenfile: Option<String>,
+    /// With --regression-test, record the current results in the goldenfile instead of comparing
+    #[structopt(long)]
+    update_goldenfile: bool,
+    /// With --regression-test, how much any metric may differ from the goldenfile, as a fraction
+    /// of the previous value
+    #[structopt(long, default_value = "0.05")]
+    tolerance: f64,
 }
 
 #[tokio::main]
 async fn main() {
-    abstutil::logger::setup();
     let args = Args::from_args();
+    if args.json_logs {
+        abstutil::logger::setup_json();
+    } else {
+        abstutil::logger::setup();
+    }
+
+    if args.regression_test {
+        let goldenfile = args
+            .goldenfile
+            .unwrap_or_else(|| abstio::path("../tests/goldenfiles/regression_metrics.json"));
+        if let Err(err) = regression::run(
+            args.maps,
+            goldenfile,
+            args.update_goldenfile,
+            args.tolerance,
+        ) {
+            error!("Regression test failed: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
 
+    SAVESTATES.write().unwrap().set_enabled(args.rewind_history);
     {
         let mut load = LOAD.write().unwrap();
         load.rng_seed = args.rng_seed;
@@ -83,7 +143,7 @@ async fn main() {
         *SIM.write().unwrap() = sim;
     }
 
-    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], args.port));
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], args.port.unwrap()));
     info!("Listening on http://{}", addr);
     let serve_future = Server::bind(&addr).serve(hyper::service::make_service_fn(|_| async {
         Ok::<_, hyper::Error>(hyper::service::service_fn(serve_req))
@@ -112,6 +172,7 @@ async fn serve_req(req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
             &mut SIM.write().unwrap(),
             &mut MAP.write().unwrap(),
             &mut LOAD.write().unwrap(),
+            &mut SAVESTATES.write().unwrap(),
         ) {
             Ok(resp) => Response::new(Body::from(resp)),
             Err(err) => {
@@ -132,6 +193,7 @@ fn handle_command(
     sim: &mut Sim,
     map: &mut Map,
     load: &mut LoadSim,
+    savestates: &mut SavestateHistory,
 ) -> Result<String> {
     let get = |key: &str| {
         params
@@ -145,6 +207,7 @@ fn handle_command(
             let (new_map, new_sim) = load.setup(&mut Timer::new("reset sim"));
             *map = new_map;
             *sim = new_sim;
+            savestates.clear();
             Ok("sim reloaded".to_string())
         }
         "/sim/load" => {
@@ -158,6 +221,7 @@ fn handle_command(
             let (new_map, new_sim) = load.setup(&mut Timer::new("reset sim"));
             *map = new_map;
             *sim = new_sim;
+            savestates.clear();
 
             Ok("flags changed and sim reloaded".to_string())
         }
@@ -165,19 +229,39 @@ fn handle_command(
             *map =
                 Map::load_synchronously(get("map")?.to_string(), &mut Timer::new("load new map"));
             *sim = Sim::new(&map, SimOptions::default());
+            savestates.clear();
             Ok("map changed, blank simulation".to_string())
         }
         "/sim/get-time" => Ok(sim.time().to_string()),
         "/sim/goto-time" => {
             let t = Time::parse(get("t")?)?;
             if t <= sim.time() {
-                bail!("{} is in the past. call /sim/reset first?", t)
+                bail!("{} is in the past. call /sim/rewind-to-time first?", t)
             } else {
-                let dt = t - sim.time();
-                sim.timed_step(map, dt, &mut None, &mut Timer::new("goto-time"));
+                goto_time(sim, map, savestates, t);
                 Ok(format!("it's now {}", t))
             }
         }
+        "/sim/rewind-to-time" => {
+            let t = Time::parse(get("t")?)?;
+            if t > sim.time() {
+                bail!("{} is in the future. call /sim/goto-time instead", t);
+            }
+            match savestates.rewind_to(t) {
+                Some(restored) => {
+                    *sim = restored?;
+                }
+                None => {
+                    let (new_map, new_sim) = load.setup(&mut Timer::new("reset sim"));
+                    *map = new_map;
+                    *sim = new_sim;
+                }
+            }
+            if t > sim.time() {
+                goto_time(sim, map, savestates, t);
+            }
+            Ok(format!("it's now {}", t))
+        }
         "/sim/new-person" => {
             let input: ExternalPerson = abstutil::from_json(body)?;
             for trip in &input.trips {
@@ -199,6 +283,17 @@ fn handle_command(
                 sim.get_all_people().last().unwrap().id
             ))
         }
+        "/sim/schedule-trip" => {
+            let input: ScheduleTrip = abstutil::from_json(body)?;
+            let trip = sim.schedule_trip(
+                input.departure,
+                input.origin,
+                input.destination,
+                input.mode,
+                map,
+            )?;
+            Ok(abstutil::to_json(&trip))
+        }
         // Traffic signals
         "/traffic-signals/get" => {
             let i = IntersectionID(get("id")?.parse::<usize>()?);
@@ -278,27 +373,35 @@ fn handle_command(
         "/traffic-signals/get-all-current-state" => {
             let mut all_state = BTreeMap::new();
             for i in map.all_intersections() {
-                if !i.is_traffic_signal() {
-                    continue;
+                if i.is_traffic_signal() {
+                    all_state.insert(i.id, TrafficSignalState::new(sim, i.id));
                 }
-                let (current_stage_idx, remaining_time) =
-                    sim.current_stage_and_remaining_time(i.id);
-                all_state.insert(
-                    i.id,
-                    TrafficSignalState {
-                        current_stage_idx,
-                        remaining_time,
-                        accepted: sim
-                            .get_accepted_agents(i.id)
-                            .into_iter()
-                            .map(|(a, _)| a)
-                            .collect(),
-                        waiting: sim.get_waiting_agents(i.id),
-                    },
-                );
             }
             Ok(abstutil::to_json(&all_state))
         }
+        "/traffic-signals/get-current-state" => {
+            let i = IntersectionID(get("id")?.parse::<usize>()?);
+            if map.maybe_get_traffic_signal(i).is_none() {
+                bail!("{} isn't a traffic signal", i);
+            }
+            Ok(abstutil::to_json(&TrafficSignalState::new(sim, i)))
+        }
+        "/traffic-signals/set-external-control" => {
+            let i = IntersectionID(get("id")?.parse::<usize>()?);
+            let enabled = get("enabled")?.parse::<bool>()?;
+            sim.set_traffic_signal_external_control(i, enabled, map)?;
+            Ok(format!(
+                "{} is {} externally controlled",
+                i,
+                if enabled { "now" } else { "no longer" }
+            ))
+        }
+        "/traffic-signals/set-stage" => {
+            let i = IntersectionID(get("id")?.parse::<usize>()?);
+            let stage = get("stage")?.parse::<usize>()?;
+            sim.set_traffic_signal_stage(i, stage, map)?;
+            Ok(format!("{} switched to stage {}", i, stage))
+        }
         // Querying data
         "/data/get-finished-trips" => {
             let mut trips = Vec::new();
@@ -318,6 +421,28 @@ fn handle_command(
             }
             Ok(abstutil::to_json(&trips))
         }
+        "/data/get-kpis" => Ok(abstutil::to_json(&sim.kpis(map))),
+        "/data/get-profile" => Ok(abstutil::to_json(sim.get_profile())),
+        "/data/get-trip-status" => {
+            let id = TripID(get("id")?.parse::<usize>()?);
+            let (agent, status) = match sim.trip_to_agent(id) {
+                TripResult::Ok(a) => (Some(a), "ongoing"),
+                TripResult::ModeChange => (None, "ongoing"),
+                TripResult::TripDone => (None, "finished"),
+                TripResult::TripNotStarted => (None, "not started"),
+                TripResult::TripCancelled => (None, "cancelled"),
+                TripResult::TripDoesntExist => bail!("{} doesn't exist", id),
+            };
+            Ok(abstutil::to_json(&TripStatus {
+                status: status.to_string(),
+                info: sim.trip_info(id),
+                pos: agent
+                    .and_then(|a| sim.canonical_pt_for_agent(a, map))
+                    .map(|pt| pt.to_gps(map.get_gps_bounds())),
+                agent,
+                duration: sim.finished_trip_details(id).map(|(dt, _, _)| dt),
+            }))
+        }
         "/data/get-agent-positions" => Ok(abstutil::to_json(&AgentPositions {
             agents: sim
                 .get_unzoomed_agents(map)
@@ -388,10 +513,53 @@ fn handle_command(
                 &map.edit_road_cmd(r, |_| {}).to_perma(map),
             ))
         }
+        "/map/apply-edits" => {
+            let input: ApplyEdits = abstutil::from_json(body)?;
+            let result = apply_edits(map, sim, input)?;
+            // Savestates from before the edits may refer to lanes that don't exist anymore.
+            // Resetting or rewinding reloads the map without these edits; pass them to /sim/load
+            // to keep them.
+            savestates.clear();
+            Ok(abstutil::to_json(&result))
+        }
         "/map/get-intersection-geometry" => {
-            let i = IntersectionID(get("id")?.parse::<usize>()?);
+            // Callers may know the OSM node instead
+            let i = if let Some(osm_id) = params.get("osm_id") {
+                let node = osm::NodeID(osm_id.parse::<i64>()?);
+                map.find_i_by_osm_id(node)?
+            } else {
+                IntersectionID(get("id")?.parse::<usize>()?)
+            };
             Ok(abstutil::to_json(&export_geometry(map, i)))
         }
+        "/map/get-road" => {
+            let r = RoadID(get("id")?.parse::<usize>()?);
+            if r.0 >= map.all_roads().len() {
+                bail!("{} doesn't exist", r);
+            }
+            Ok(abstutil::to_json(&RoadAttributes::new(map.get_r(r))))
+        }
+        "/map/get-roads-by-osm-id" => {
+            // One OSM way is usually split into many roads
+            let way = osm::WayID(get("id")?.parse::<i64>()?);
+            let roads: Vec<RoadAttributes> = map
+                .all_roads()
+                .iter()
+                .filter(|r| r.orig_id.osm_way_id == way)
+                .map(RoadAttributes::new)
+                .collect();
+            if roads.is_empty() {
+                bail!("No roads from {}", way);
+            }
+            Ok(abstutil::to_json(&roads))
+        }
+        "/map/get-lanes" => {
+            let r = RoadID(get("id")?.parse::<usize>()?);
+            if r.0 >= map.all_roads().len() {
+                bail!("{} doesn't exist", r);
+            }
+            Ok(abstutil::to_json(&RoadAttributes::new(map.get_r(r)).lanes))
+        }
         "/map/get-all-geometry" => Ok(abstutil::to_json(&export_all_geometry(map))),
         "/map/get-nearest-road" => {
             let pt = LonLat::new(get("lon")?.parse::<f64>()?, get("lat")?.parse::<f64>()?);
@@ -420,6 +588,26 @@ struct FinishedTrip {
     mode: TripMode,
 }
 
+#[derive(Deserialize)]
+struct ScheduleTrip {
+    departure: Time,
+    origin: TripEndpoint,
+    destination: TripEndpoint,
+    mode: TripMode,
+}
+
+#[derive(Serialize)]
+struct TripStatus {
+    /// "not started", "ongoing", "finished", or "cancelled"
+    status: String,
+    info: TripInfo,
+    /// The agent currently making progress on the trip, if it's ongoing
+    agent: Option<AgentID>,
+    pos: Option<LonLat>,
+    /// Only filled out once the trip finishes
+    duration: Option<Duration>,
+}
+
 #[derive(Serialize)]
 struct Delays {
     #[serde(serialize_with = "serialize_btreemap")]
@@ -469,6 +657,57 @@ struct RoadThroughput {
     counts: Vec<(RoadID, AgentType, usize, usize)>,
 }
 
+#[derive(Serialize)]
+struct RoadAttributes {
+    id: RoadID,
+    orig_id: OriginalRoad,
+    name: String,
+    speed_limit: Speed,
+    length: Distance,
+    width: Distance,
+    percent_incline: f64,
+    zorder: isize,
+    access_restrictions: AccessRestrictions,
+    osm_tags: Tags,
+    /// Ordered from the left side of the road, as seen when facing the road's direction
+    lanes: Vec<LaneAttributes>,
+}
+
+#[derive(Serialize)]
+struct LaneAttributes {
+    id: LaneID,
+    lane_type: LaneType,
+    dir: Direction,
+    width: Distance,
+}
+
+impl RoadAttributes {
+    fn new(r: &Road) -> RoadAttributes {
+        RoadAttributes {
+            id: r.id,
+            orig_id: r.orig_id,
+            name: r.get_name(None),
+            speed_limit: r.speed_limit,
+            length: r.length(),
+            width: r.get_width(),
+            percent_incline: r.percent_incline,
+            zorder: r.zorder,
+            access_restrictions: r.access_restrictions.clone(),
+            osm_tags: r.osm_tags.clone(),
+            lanes: r
+                .lanes
+                .iter()
+                .map(|l| LaneAttributes {
+                    id: l.id,
+                    lane_type: l.lane_type,
+                    dir: l.dir,
+                    width: l.width,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct TrafficSignalState {
     current_stage_idx: usize,
@@ -478,6 +717,22 @@ struct TrafficSignalState {
     waiting: Vec<(AgentID, TurnID, Time)>,
 }
 
+impl TrafficSignalState {
+    fn new(sim: &Sim, i: IntersectionID) -> TrafficSignalState {
+        let (current_stage_idx, remaining_time) = sim.current_stage_and_remaining_time(i);
+        TrafficSignalState {
+            current_stage_idx,
+            remaining_time,
+            accepted: sim
+                .get_accepted_agents(i)
+                .into_iter()
+                .map(|(a, _)| a)
+                .collect(),
+            waiting: sim.get_waiting_agents(i),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct BlockedByGraph {
     /// Each entry indicates that some agent has been stuck in one place for some amount of time,
@@ -487,6 +742,33 @@ struct BlockedByGraph {
     blocked_by: BTreeMap<AgentID, (Duration, DelayCause, Option<TripID>, Option<PersonID>)>,
 }
 
+/// Either a complete set of edits, replacing whatever's applied now, or individual commands to add
+/// on top of the current edits
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApplyEdits {
+    Replace(PermanentMapEdits),
+    Append(Vec<PermanentEditCmd>),
+}
+
+#[derive(Serialize)]
+struct ApplyEditsResult {
+    /// How many commands were applied
+    applied: usize,
+    /// Commands that were skipped, and why
+    rejected: Vec<RejectedEdit>,
+    /// Trips cancelled because the map changed underneath them
+    trips_interrupted: usize,
+    parked_cars_displaced: usize,
+}
+
+#[derive(Serialize)]
+struct RejectedEdit {
+    /// The position of the command in the input
+    idx: usize,
+    reason: String,
+}
+
 #[derive(Deserialize)]
 struct LoadSim {
     scenario: String,
@@ -507,10 +789,14 @@ impl LoadSim {
         if let Some(perma) = self.edits.clone() {
             let edits = perma.into_edits(&map).unwrap();
             map.must_apply_edits(edits, timer);
-            map.recalculate_pathfinding_after_edits(timer);
+            map.recalculate_pathfinding_after_loading_proposal(timer);
         }
 
-        for m in &self.modifiers {
+        for m in self
+            .modifiers
+            .iter()
+            .chain(self.opts.weather_modifiers().iter())
+        {
             scenario = m.apply(&map, scenario);
         }
 
@@ -614,3 +900,102 @@ fn export_all_geometry(map: &Map) -> geojson::GeoJson {
         foreign_members: None,
     })
 }
+
+/// Checks each command against the map in order, applies the valid ones, and updates the live
+/// simulation.
+fn apply_edits(map: &mut Map, sim: &mut Sim, input: ApplyEdits) -> Result<ApplyEditsResult> {
+    let mut timer = Timer::new("apply edits");
+    let (mut edits, commands) = match input {
+        ApplyEdits::Replace(perma) => {
+            if &perma.map_name != map.get_name() {
+                bail!(
+                    "these edits are for {}, but {} is loaded",
+                    perma.map_name.describe(),
+                    map.get_name().describe()
+                );
+            }
+            // Start from a clean map, so the first command can be checked against it
+            let (edits, commands) = perma.into_edits_and_commands(map)?;
+            map.must_apply_edits(edits.clone(), &mut timer);
+            (edits, commands)
+        }
+        ApplyEdits::Append(commands) => (map.get_edits().clone(), commands),
+    };
+
+    let mut applied = 0;
+    let mut rejected = Vec::new();
+    let mut disconnected_sidewalks = connectivity::find_scc(map, PathConstraints::Pedestrian).1;
+    for (idx, cmd) in commands.into_iter().enumerate() {
+        let cmd = match check_edit(map, cmd) {
+            Ok(cmd) => cmd,
+            Err(err) => {
+                rejected.push(RejectedEdit {
+                    idx,
+                    reason: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let mut candidate = edits.clone();
+        candidate.commands.push(cmd);
+        map.try_apply_edits(candidate.clone(), &mut timer);
+        let disconnected_after = connectivity::find_scc(map, PathConstraints::Pedestrian).1;
+        let newly_disconnected = disconnected_after
+            .difference(&disconnected_sidewalks)
+            .count();
+        if newly_disconnected > 0 {
+            map.must_apply_edits(edits.clone(), &mut timer);
+            rejected.push(RejectedEdit {
+                idx,
+                reason: format!("{} sidewalks would be disconnected", newly_disconnected),
+            });
+            continue;
+        }
+        edits = candidate;
+        disconnected_sidewalks = disconnected_after;
+        applied += 1;
+    }
+
+    map.must_apply_edits(edits, &mut timer);
+    map.recalculate_pathfinding_after_edits(&mut timer);
+    sim.handle_live_edited_traffic_signals(map);
+    let (trips_interrupted, parked_cars_displaced) = sim.handle_live_edits(map, &mut timer);
+
+    Ok(ApplyEditsResult {
+        applied,
+        rejected,
+        trips_interrupted,
+        parked_cars_displaced,
+    })
+}
+
+/// Problems with a command that can be detected before applying it
+fn check_edit(map: &Map, cmd: PermanentEditCmd) -> Result<EditCmd> {
+    let cmd = cmd.into_cmd(map)?;
+    cmd.check_current(map)?;
+    if let EditCmd::ChangeRoad { r, ref new, .. } = cmd {
+        // Bus stops need a lane for the bus to stop in
+        if !map.get_r(r).transit_stops.is_empty()
+            && !new
+                .lanes_ltr
+                .iter()
+                .any(|spec| spec.lt == LaneType::Driving || spec.lt == LaneType::Bus)
+        {
+            bail!("{} has a bus stop, so it needs a driving or bus lane", r);
+        }
+    }
+    Ok(cmd)
+}
+
+/// Steps the simulation forwards to some time, snapshotting along the way, so rewinding later is
+/// cheap.
+fn goto_time(sim: &mut Sim, map: &Map, savestates: &mut SavestateHistory, t: Time) {
+    let mut timer = Timer::new("goto-time");
+    savestates.maybe_record(sim);
+    while sim.time() < t {
+        let dt = (t - sim.time()).min(Duration::minutes(1));
+        sim.timed_step(map, dt, &mut None, &mut timer);
+        savestates.maybe_record(sim);
+    }
+}
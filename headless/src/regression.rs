@@ -0,0 +1,139 @@
+//! Run a short, canonical simulation on maps and compare a few key metrics against a goldenfile.
+//! This catches map importer changes that unintentionally affect the simulation, without needing
+//! to pin an exact event checksum that any small geometry fix would invalidate.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use abstio::MapName;
+use abstutil::Timer;
+use geom::Duration;
+use map_model::Map;
+use sim::{AlertHandler, ScenarioGenerator, Sim, SimFlags, SimOptions};
+
+/// The canonical scenario's trips leave home between 7 and 10am, so simulate the morning peak.
+const SIMULATE_UNTIL: Duration = Duration::const_seconds(10.0 * 3600.0);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Metrics {
+    num_roads: usize,
+    num_intersections: usize,
+    num_buildings: usize,
+    num_people: usize,
+    num_events: usize,
+    finished_trips: usize,
+    cancelled_trips: usize,
+    unfinished_trips: usize,
+    total_trip_duration_seconds: f64,
+}
+
+impl Metrics {
+    fn values(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("roads", self.num_roads as f64),
+            ("intersections", self.num_intersections as f64),
+            ("buildings", self.num_buildings as f64),
+            ("people", self.num_people as f64),
+            ("events", self.num_events as f64),
+            ("finished trips", self.finished_trips as f64),
+            ("cancelled trips", self.cancelled_trips as f64),
+            ("unfinished trips", self.unfinished_trips as f64),
+            (
+                "total trip duration (seconds)",
+                self.total_trip_duration_seconds,
+            ),
+        ]
+    }
+}
+
+/// Simulate every map (or all maps available locally, if none are specified). If `update` is
+/// true, record the results in the goldenfile. Otherwise, fail if any metric differs from the
+/// goldenfile by more than `tolerance`, expressed as a fraction of the previous value.
+pub fn run(map_paths: Vec<String>, goldenfile: String, update: bool, tolerance: f64) -> Result<()> {
+    let mut timer = Timer::new("run regression test");
+    let map_paths = if map_paths.is_empty() {
+        MapName::list_all_maps_locally()
+            .into_iter()
+            .map(|name| name.path())
+            .collect()
+    } else {
+        map_paths
+    };
+
+    let mut golden: BTreeMap<String, Metrics> = if abstio::file_exists(&goldenfile) {
+        abstio::maybe_read_json(goldenfile.clone(), &mut timer)?
+    } else {
+        BTreeMap::new()
+    };
+
+    let mut failures = 0;
+    for path in map_paths {
+        let map = Map::load_synchronously(path, &mut timer);
+        let name = map.get_name().describe();
+        let metrics = simulate(&map, &mut timer);
+
+        if update {
+            golden.insert(name, metrics);
+            continue;
+        }
+        let expected = if let Some(x) = golden.get(&name) {
+            x
+        } else {
+            println!(
+                "{}: no previous results. Run with --update-goldenfile to record them.",
+                name
+            );
+            failures += 1;
+            continue;
+        };
+        for ((key, before), (_, after)) in expected.values().into_iter().zip(metrics.values()) {
+            if (after - before).abs() > tolerance * before.abs().max(1.0) {
+                println!("{}: {} changed from {} to {}", name, key, before, after);
+                failures += 1;
+            }
+        }
+    }
+
+    if update {
+        abstio::write_json(goldenfile, &golden);
+        return Ok(());
+    }
+    if failures > 0 {
+        bail!(
+            "{} differences beyond {}% tolerance",
+            failures,
+            tolerance * 100.0
+        );
+    }
+    println!("All metrics match the goldenfile");
+    Ok(())
+}
+
+/// Every map gets the same kind of generated scenario with a fixed RNG seed, so results only
+/// change when the map or the simulation does.
+fn simulate(map: &Map, timer: &mut Timer) -> Metrics {
+    let mut rng = SimFlags::for_test("regression_test").make_rng();
+    let scenario = ScenarioGenerator::proletariat_robot(map, &mut rng, timer);
+
+    let mut opts = SimOptions::new("regression_test");
+    opts.alerts = AlertHandler::Silence;
+    let mut sim = Sim::new(map, opts);
+    sim.instantiate(&scenario, map, &mut rng, timer);
+    let (_, num_events) = sim.run_and_checksum(map, SIMULATE_UNTIL, timer);
+
+    let kpis = sim.kpis(map);
+
+    Metrics {
+        num_roads: map.all_roads().len(),
+        num_intersections: map.all_intersections().len(),
+        num_buildings: map.all_buildings().len(),
+        num_people: scenario.people.len(),
+        num_events,
+        finished_trips: kpis.finished_trips,
+        cancelled_trips: kpis.cancelled_trips,
+        unfinished_trips: kpis.unfinished_trips,
+        total_trip_duration_seconds: kpis.total_trip_duration_seconds,
+    }
+}
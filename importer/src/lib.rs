@@ -51,6 +51,7 @@ pub async fn oneshot(
     clip: Option<String>,
     drive_on_right: bool,
     filter_crosswalks: bool,
+    roundabouts_yield_to_entering: bool,
     create_uk_travel_demand_model: bool,
     opts: RawToMapOptions,
 ) {
@@ -72,12 +73,19 @@ pub async fn oneshot(
                 inferred_sidewalks: true,
                 street_parking_spot_length: Distance::meters(8.0),
                 turn_on_red: true,
+                default_speed_limits: map_model::DefaultSpeedLimits::usa(),
+                roundabout_priority: if roundabouts_yield_to_entering {
+                    map_model::RoundaboutPriority::Entering
+                } else {
+                    map_model::RoundaboutPriority::Circulating
+                },
             },
 
             onstreet_parking: convert_osm::OnstreetParking::JustOSM,
             public_offstreet_parking: convert_osm::PublicOffstreetParking::None,
             private_offstreet_parking: convert_osm::PrivateOffstreetParking::FixedPerBldg(1),
             include_railroads: true,
+            include_trams: true,
             extra_buildings: None,
             skip_local_roads: false,
             filter_crosswalks,
@@ -121,6 +129,9 @@ pub struct Job {
     /// Produce a city overview from all of the individual maps in a city.
     #[structopt(long)]
     pub city_overview: bool,
+    /// Check the final map for geometry and connectivity problems and print a report.
+    #[structopt(long)]
+    pub validate: bool,
 
     /// Only process one map. If not specified, process all maps defined by clipping polygons in
     /// importer/config/$city/.
@@ -139,6 +150,7 @@ impl Job {
             raw_to_map: true,
             scenario: false,
             city_overview: false,
+            validate: false,
             only_map: None,
             opts: RawToMapOptions::default(),
         };
@@ -180,6 +192,9 @@ impl Job {
         if self.city_overview {
             flags.push("--city-overview".to_string());
         }
+        if self.validate {
+            flags.push("--validate".to_string());
+        }
         if let Some(ref name) = self.only_map {
             flags.push(name.clone());
         }
@@ -187,9 +202,14 @@ impl Job {
     }
 
     pub async fn run(self, timer: &mut Timer<'_>) {
-        if !self.osm_to_raw && !self.raw_to_map && !self.scenario && !self.city_overview {
+        if !self.osm_to_raw
+            && !self.raw_to_map
+            && !self.scenario
+            && !self.city_overview
+            && !self.validate
+        {
             println!(
-                "Nothing to do! Pass some combination of --raw, --map, --scenario, or --city_overview"
+                "Nothing to do! Pass some combination of --raw, --map, --scenario, --city_overview, or --validate"
             );
             std::process::exit(1);
         }
@@ -269,12 +289,22 @@ impl Job {
                 }
 
                 Some(map)
-            } else if self.scenario {
+            } else if self.scenario || self.validate {
                 Some(map_model::Map::load_synchronously(name.path(), timer))
             } else {
                 None
             };
 
+            if self.validate {
+                timer.start(format!("validate {}", name.describe()));
+                let report = maybe_map.as_ref().unwrap().validate();
+                println!("Validation report for {}:", name.describe());
+                for line in report.describe() {
+                    println!("- {}", line);
+                }
+                timer.stop(format!("validate {}", name.describe()));
+            }
+
             if self.scenario {
                 if self.city == CityName::seattle() {
                     timer.start(format!("scenario for {}", name.describe()));
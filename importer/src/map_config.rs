@@ -32,6 +32,13 @@ pub fn config_for_map(name: &MapName) -> convert_osm::Options {
                 Distance::meters(8.0)
             },
             turn_on_red: name.city.country == "us" && name.city.city != "nyc",
+            default_speed_limits: match name.city.country.as_ref() {
+                "us" => map_model::DefaultSpeedLimits::usa(),
+                "gb" => map_model::DefaultSpeedLimits::uk(),
+                "at" | "ch" | "de" | "fr" | "pl" | "pt" => map_model::DefaultSpeedLimits::europe(),
+                _ => map_model::DefaultSpeedLimits::usa(),
+            },
+            roundabout_priority: map_model::RoundaboutPriority::Circulating,
         },
         onstreet_parking: match name.city.city.as_ref() {
             "seattle" => {
@@ -69,6 +76,7 @@ pub fn config_for_map(name: &MapName) -> convert_osm::Options {
             "phoenix" | "seattle" | "tucson" => false,
             _ => true,
         },
+        include_trams: name.city.city != "berlin",
         extra_buildings,
         skip_local_roads: name == &MapName::new("us", "phoenix", "loop101"),
         filter_crosswalks: false,
@@ -0,0 +1,68 @@
+use anyhow::Result;
+
+use abstutil::{prettyprint_usize, Timer};
+use geom::{LonLat, Time};
+
+/// One recorded trip from a GPX file, like an exported ride from a fitness tracker.
+#[derive(Clone, Debug)]
+pub struct GpxTrace {
+    pub name: Option<String>,
+    /// The time is only the time of day. Dates and timezones are ignored, so times are in
+    /// whatever timezone the file uses -- often UTC.
+    pub points: Vec<(LonLat, Option<Time>)>,
+}
+
+/// Parses every track and route in a .gpx file.
+pub fn load_gpx(path: String, timer: &mut Timer) -> Result<Vec<GpxTrace>> {
+    timer.start(format!("read {}", path));
+    let bytes = abstio::slurp_file(&path)?;
+    let raw_string = std::str::from_utf8(&bytes)?;
+    let tree = roxmltree::Document::parse(raw_string)?;
+    timer.stop(format!("read {}", path));
+
+    let mut traces = Vec::new();
+    for node in tree.descendants() {
+        let pt_tag = match node.tag_name().name() {
+            "trk" => "trkpt",
+            "rte" => "rtept",
+            _ => continue,
+        };
+        let name = node
+            .children()
+            .find(|x| x.tag_name().name() == "name")
+            .and_then(|x| x.text())
+            .map(|x| x.to_string());
+        let mut points = Vec::new();
+        for pt in node.descendants().filter(|x| x.tag_name().name() == pt_tag) {
+            let (lon, lat) = match (pt.attribute("lon"), pt.attribute("lat")) {
+                (Some(lon), Some(lat)) => (lon.parse::<f64>()?, lat.parse::<f64>()?),
+                _ => bail!("A {} in {} is missing lon or lat", pt_tag, path),
+            };
+            let time = pt
+                .children()
+                .find(|x| x.tag_name().name() == "time")
+                .and_then(|x| x.text())
+                .and_then(parse_time_of_day);
+            points.push((LonLat::new(lon, lat), time));
+        }
+        if !points.is_empty() {
+            traces.push(GpxTrace { name, points });
+        }
+    }
+
+    info!(
+        "Got {} traces from {}",
+        prettyprint_usize(traces.len()),
+        path
+    );
+    Ok(traces)
+}
+
+/// Extracts the time of day from an ISO 8601 timestamp like "2021-06-01T07:31:02Z"
+fn parse_time_of_day(raw: &str) -> Option<Time> {
+    let (_, time) = raw.split_once('T')?;
+    let time = time.trim_end_matches('Z');
+    // Drop any timezone offset
+    let time = time.split(|c| c == '+' || c == '-').next()?;
+    Time::parse(time).ok()
+}
@@ -13,6 +13,10 @@ use serde::{Deserialize, Serialize};
 use abstutil::{prettyprint_usize, Timer};
 use geom::{GPSBounds, LonLat, Polygon};
 
+pub use self::gpx::{load_gpx, GpxTrace};
+
+mod gpx;
+
 /// Some dataset imported from KML, CSV, or something else. If the dataset is large, converting to
 /// this format and serializing is faster than parsing the original again.
 #[derive(Serialize, Deserialize)]
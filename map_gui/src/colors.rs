@@ -96,6 +96,7 @@ pub struct ColorScheme {
     pub unzoomed_arterial: Color,
     pub unzoomed_residential: Color,
     pub unzoomed_trail: Color,
+    pub unzoomed_road_label: Color,
 
     // Intersections
     pub normal_intersection: Color,
@@ -217,6 +218,7 @@ impl ColorScheme {
             unzoomed_arterial: hex("#FFC73E"),
             unzoomed_residential: Color::WHITE,
             unzoomed_trail: hex("#0F7D4B"),
+            unzoomed_road_label: Color::grey(0.2),
 
             // Intersections
             normal_intersection: Color::grey(0.2),
@@ -314,6 +316,7 @@ impl ColorScheme {
         cs.unzoomed_highway = cs.parking_lane;
         cs.unzoomed_arterial = cs.sidewalk;
         cs.unzoomed_residential = cs.driving_lane;
+        cs.unzoomed_road_label = cs.general_road_marking;
         cs.unzoomed_interesting_intersection = cs.unzoomed_highway;
         cs.stop_sign = hex("#A32015");
         cs.private_road = Some(hex("#9E757F"));
@@ -46,6 +46,14 @@ pub struct Options {
     pub dont_draw_time_warp: bool,
     /// The delay threshold to halt on when jumping to the next delay
     pub jump_to_delay: Duration,
+    /// Advance the simulation on a background thread, drawing from periodic snapshots of it. Not
+    /// supported on the web.
+    #[serde(default)]
+    pub background_sim: bool,
+    /// Keep snapshots of the simulation in memory, so jumping backwards in time doesn't have to
+    /// start over from midnight. Uses lots of memory on big maps.
+    #[serde(default)]
+    pub rewind_history: bool,
 
     /// Display roads and buildings in an alternate language, if possible. None means to use the
     /// OSM native name.
@@ -83,6 +91,8 @@ impl Options {
             time_increment: Duration::minutes(10),
             dont_draw_time_warp: false,
             jump_to_delay: Duration::minutes(5),
+            background_sim: false,
+            rewind_history: false,
 
             minimal_controls: false,
             canvas_settings: CanvasSettings::new(),
@@ -272,6 +282,22 @@ impl OptionsPanel {
                         None,
                         app.opts().debug_all_agents,
                     ),
+                    if cfg!(target_arch = "wasm32") {
+                        Widget::nothing()
+                    } else {
+                        Toggle::checkbox(
+                            ctx,
+                            "Run the simulation on a background thread (experimental)",
+                            None,
+                            app.opts().background_sim,
+                        )
+                    },
+                    Toggle::checkbox(
+                        ctx,
+                        "Keep simulation snapshots to rewind quickly (uses lots of memory)",
+                        None,
+                        app.opts().rewind_history,
+                    ),
                 ])
                 .bg(app.cs().inner_panel_bg)
                 .padding(8),
@@ -300,6 +326,14 @@ impl<A: AppLike> State<A> for OptionsPanel {
                     opts.debug_all_agents = self
                         .panel
                         .is_checked("Draw all agents to debug geometry (Slow!)");
+                    opts.rewind_history = self.panel.is_checked(
+                        "Keep simulation snapshots to rewind quickly (uses lots of memory)",
+                    );
+                    if !cfg!(target_arch = "wasm32") {
+                        opts.background_sim = self
+                            .panel
+                            .is_checked("Run the simulation on a background thread (experimental)");
+                    }
 
                     ctx.canvas.settings.invert_scroll = self
                         .panel
@@ -374,6 +408,7 @@ impl<A: AppLike> State<A> for OptionsPanel {
                         for r in &mut app.mut_draw_map().roads {
                             r.clear_rendering();
                         }
+                        app.draw_map().road_labels.clear();
                     }
 
                     // Be careful -- there are some options not exposed by this panel, but per app.
@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use aabb_quadtree::QuadTree;
+use geom::{Distance, Polygon, Pt2D};
+use map_model::Road;
+use widgetry::{Drawable, GeomBatch, GfxCtx, Line, Text};
+
+use crate::AppLike;
+
+/// Street names drawn along the roads while unzoomed, so the map can be read without hovering
+/// over anything. Labels stay about the same size on the screen, so which ones fit depends on the
+/// zoom. They're rendered lazily for a few discrete zoom levels.
+pub struct DrawRoadLabels {
+    per_zoom_level: RefCell<HashMap<i32, Drawable>>,
+}
+
+impl DrawRoadLabels {
+    pub fn new() -> DrawRoadLabels {
+        DrawRoadLabels {
+            per_zoom_level: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Forget all rendered labels, like after changing the language
+    pub fn clear(&self) {
+        self.per_zoom_level.borrow_mut().clear();
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx, app: &dyn AppLike) {
+        // Each level is a factor of sqrt(2) apart
+        let level = (g.canvas.cam_zoom.log2() * 2.0).floor() as i32;
+        let mut per_zoom_level = self.per_zoom_level.borrow_mut();
+        if !per_zoom_level.contains_key(&level) {
+            let batch = render(g, app, 2.0_f64.powf(level as f64 / 2.0));
+            per_zoom_level.insert(level, g.upload(batch));
+        }
+        g.redraw(&per_zoom_level[&level]);
+    }
+}
+
+fn render(g: &GfxCtx, app: &dyn AppLike, zoom: f64) -> GeomBatch {
+    let map = app.map();
+    // The text is rendered at the default font size, then scaled to about 14 pixels on the screen
+    let scale = 0.66 / zoom;
+
+    let mut roads: Vec<&Road> = map
+        .all_roads()
+        .iter()
+        .filter(|r| !r.is_light_rail())
+        .collect();
+    // The most important and longest roads get labelled first
+    roads.sort_by_key(|r| (Reverse(r.get_detailed_rank()), Reverse(r.length())));
+
+    let mut batch = GeomBatch::new();
+    // Where each placed label is, to avoid overlapping them
+    let mut quadtree = QuadTree::default(map.get_bounds().as_bbox());
+    let mut footprints: Vec<Polygon> = Vec::new();
+    // Long streets are split into many roads, so don't repeat the name too often
+    let mut placed_names: HashMap<String, Vec<Pt2D>> = HashMap::new();
+
+    for r in roads {
+        let name = r.get_name(app.opts().language.as_ref());
+        if name == "???" {
+            continue;
+        }
+        let dims = Text::from(Line(&name)).render(g).get_dims();
+        let width = Distance::meters(dims.width * scale);
+        let height = Distance::meters(dims.height * scale);
+        // Leave some room at both ends
+        if r.length() < width + 2.0 * height {
+            continue;
+        }
+
+        // Don't draw text upside down
+        let pl = if r.center_pts.quadrant() > 1 && r.center_pts.quadrant() < 4 {
+            r.center_pts.reversed()
+        } else {
+            r.center_pts.clone()
+        };
+        let footprint =
+            match pl.maybe_exact_slice((pl.length() - width) / 2.0, (pl.length() + width) / 2.0) {
+                Ok(slice) => slice.make_polygons(height),
+                Err(_) => {
+                    continue;
+                }
+            };
+
+        let center = pl.middle();
+        if placed_names
+            .get(&name)
+            .map(|pts| pts.iter().any(|pt| pt.dist_to(center) < 10.0 * width))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let bbox = footprint.get_bounds().as_bbox();
+        if quadtree
+            .query(bbox)
+            .into_iter()
+            .any(|(idx, _, _)| footprints[*idx].intersects(&footprint))
+        {
+            continue;
+        }
+        quadtree.insert_with_box(footprints.len(), bbox);
+        footprints.push(footprint);
+        placed_names
+            .entry(name.clone())
+            .or_insert_with(Vec::new)
+            .push(center);
+
+        // The text sits on top of the path, so shift it down to center the text over the road
+        let path = pl.shift_right(height / 3.0).unwrap_or(pl);
+        batch.append(
+            Line(name)
+                .fg(app.cs().unzoomed_road_label)
+                .render_curvey(g, &path, scale),
+        );
+    }
+    batch
+}
@@ -4,7 +4,10 @@ use lyon_geom::math::F64Point;
 use lyon_geom::{CubicBezierSegment, QuadraticBezierSegment};
 
 use geom::{Angle, ArrowCap, Circle, Distance, InfiniteLine, Line, PolyLine, Polygon, Pt2D};
-use map_model::{BufferType, Direction, DrivingSide, Lane, LaneID, LaneType, Map, Road, TurnID};
+use map_model::{
+    osm::RoadRank, BufferType, Direction, DrivingSide, Lane, LaneID, LaneType, Map, Road, TurnID,
+    TURN_POCKET_TAPER,
+};
 use widgetry::{Color, Drawable, GeomBatch, GfxCtx, Prerender, RewriteColor};
 
 use crate::render::{DrawOptions, Renderable, OUTLINE_THICKNESS};
@@ -97,6 +100,9 @@ impl DrawLane {
                             .rotate(angle.shortest_rotation_towards(Angle::degrees(-90.0))),
                     );
                 }
+                if road.is_contraflow(lane.id) {
+                    batch.extend(general_road_marking, calculate_one_way_markings(lane, road));
+                }
             }
             LaneType::SharedLeftTurn => {
                 let thickness = Distance::meters(0.25);
@@ -173,6 +179,7 @@ impl DrawLane {
                 calculate_buffer_markings(app, style, lane, &mut batch);
             }
         }
+        calculate_turn_pocket_markings(app, lane, rank, &mut batch);
 
         if road.is_private() {
             if let Some(color) = app.cs().private_road {
@@ -452,10 +459,12 @@ fn calculate_turn_markings(map: &Map, lane: &Lane) -> Vec<Polygon> {
 
 fn calculate_one_way_markings(lane: &Lane, road: &Road) -> Vec<Polygon> {
     let mut results = Vec::new();
-    if road
-        .lanes
-        .iter()
-        .any(|l| l.dir != lane.dir && l.lane_type == LaneType::Driving)
+    // Contraflow lanes always get arrows, so nobody mistakes them for going with traffic
+    if !road.is_contraflow(lane.id)
+        && road
+            .lanes
+            .iter()
+            .any(|l| l.dir != lane.dir && l.lane_type == LaneType::Driving)
     {
         // Not a one-way
         return results;
@@ -482,6 +491,48 @@ fn calculate_one_way_markings(lane: &Lane, road: &Road) -> Vec<Polygon> {
     results
 }
 
+/// Before a turn pocket opens, the space it'll later occupy is striped off. Then two lines taper
+/// in to where the pocket reaches full width.
+fn calculate_turn_pocket_markings(
+    app: &dyn AppLike,
+    lane: &Lane,
+    rank: RoadRank,
+    batch: &mut GeomBatch,
+) {
+    let pocket_start = lane.pocket_start();
+    if pocket_start == Distance::ZERO {
+        return;
+    }
+    let taper_start = (pocket_start - TURN_POCKET_TAPER).max(Distance::ZERO);
+    let color = app.cs().general_road_marking;
+    let thickness = Distance::meters(0.25);
+
+    if let Ok(pl) = lane
+        .lane_center_pts
+        .maybe_exact_slice(Distance::ZERO, taper_start)
+    {
+        batch.push(
+            app.cs()
+                .zoomed_road_surface(LaneType::Buffer(BufferType::Stripes), RoadRank::Local),
+            pl.make_polygons(lane.width),
+        );
+        for (center, angle) in pl.step_along(Distance::meters(3.0), Distance::meters(1.5)) {
+            let left = center.project_away(lane.width / 2.0, angle.rotate_degs(45.0));
+            let right = center.project_away(lane.width / 2.0, angle.rotate_degs(45.0).opposite());
+            batch.push(color, Line::must_new(left, right).make_polygons(thickness));
+        }
+    }
+
+    let (pt1, angle) = lane.lane_center_pts.must_dist_along(taper_start);
+    let (pt2, _) = lane.lane_center_pts.must_dist_along(pocket_start);
+    for rotate in [90.0, -90.0] {
+        let edge = pt1.project_away(lane.width / 2.0, angle.rotate_degs(rotate));
+        if let Ok(line) = Line::new(edge, pt2) {
+            batch.push(color, line.make_polygons(thickness));
+        }
+    }
+}
+
 fn calculate_buffer_markings(
     app: &dyn AppLike,
     style: BufferType,
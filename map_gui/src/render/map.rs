@@ -17,7 +17,7 @@ use crate::render::lane::DrawLane;
 use crate::render::parking_lot::DrawParkingLot;
 use crate::render::road::DrawRoad;
 use crate::render::transit_stop::DrawTransitStop;
-use crate::render::{AgentCache, DrawArea, Renderable};
+use crate::render::{AgentCache, DrawArea, DrawRoadLabels, Renderable};
 use crate::{AppLike, ID};
 
 pub struct DrawMap {
@@ -34,6 +34,7 @@ pub struct DrawMap {
     pub draw_all_building_outlines: Drawable,
     pub draw_all_unzoomed_parking_lots: Drawable,
     pub draw_all_areas: Drawable,
+    pub road_labels: DrawRoadLabels,
 
     pub zorder_range: (isize, isize),
     pub show_zorder: isize,
@@ -177,6 +178,7 @@ impl DrawMap {
             draw_all_building_outlines,
             draw_all_unzoomed_parking_lots,
             draw_all_areas,
+            road_labels: DrawRoadLabels::new(),
 
             quadtree,
             quadtree_ids,
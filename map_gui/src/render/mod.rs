@@ -12,6 +12,7 @@ use crate::render::bike::DrawBike;
 pub use crate::render::building::DrawBuilding;
 use crate::render::car::DrawCar;
 pub use crate::render::intersection::{calculate_corners, DrawIntersection};
+pub use crate::render::labels::DrawRoadLabels;
 pub use crate::render::map::DrawMap;
 pub use crate::render::pedestrian::{DrawPedCrowd, DrawPedestrian};
 pub use crate::render::turn::DrawMovement;
@@ -23,6 +24,7 @@ mod bike;
 mod building;
 mod car;
 mod intersection;
+mod labels;
 mod lane;
 mod map;
 mod parking_lot;
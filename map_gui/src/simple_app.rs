@@ -13,7 +13,7 @@ use crate::load::MapLoader;
 use crate::options::Options;
 use crate::render::DrawMap;
 use crate::render::{DrawOptions, Renderable};
-use crate::tools::CameraState;
+use crate::tools::{CameraState, UiState};
 use crate::{AppLike, ID};
 
 /// Simple app state that just renders a static map, without any dynamic agents on the map.
@@ -27,6 +27,9 @@ pub struct SimpleApp<T> {
     pub session: T,
     /// If desired, this can be advanced to render traffic signals changing.
     pub time: Time,
+    /// Saved when the app quits. Apps that want to persist this should replace the default with
+    /// `UiState::load`.
+    pub ui_state: UiState,
 }
 
 // A SimpleApp can directly use this (`let args = SimpleAppArgs::from_iter(abstutil::cli_args())`)
@@ -79,11 +82,17 @@ impl SimpleAppArgs {
     }
 
     pub fn map_name(&self) -> MapName {
+        self.map_name_for(&UiState::default())
+    }
+
+    /// Like `map_name`, but prefer the last map used by this particular app.
+    pub fn map_name_for(&self, ui_state: &UiState) -> MapName {
         self.map_path
             .as_ref()
             .map(|path| {
                 MapName::from_path(path).unwrap_or_else(|| panic!("bad map path: {}", path))
             })
+            .or_else(|| ui_state.last_map.clone())
             .or_else(|| {
                 abstio::maybe_read_json::<crate::tools::DefaultMap>(
                     abstio::path_player("maps.json"),
@@ -122,6 +131,7 @@ impl<T: 'static> SimpleApp<T> {
             current_selection: None,
             session,
             time: Time::START_OF_DAY,
+            ui_state: UiState::default(),
         };
 
         let states = vec![MapLoader::new_state(
@@ -144,6 +154,7 @@ impl<T: 'static> SimpleApp<T> {
         g.redraw(&self.draw_map.draw_all_unzoomed_roads_and_intersections);
         g.redraw(&self.draw_map.draw_all_buildings);
         g.redraw(&self.draw_map.draw_all_building_outlines);
+        self.draw_map.road_labels.draw(g, self);
         // Not the building paths
 
         // Still show some shape selection when zoomed out.
@@ -359,10 +370,12 @@ impl<T: 'static> SharedAppState for SimpleApp<T> {
 
     fn dump_before_abort(&self, canvas: &Canvas) {
         CameraState::save(canvas, self.map.get_name());
+        self.ui_state.save_with_map(self.map.get_name());
     }
 
     fn before_quit(&self, canvas: &Canvas) {
         CameraState::save(canvas, self.map.get_name());
+        self.ui_state.save_with_map(self.map.get_name());
     }
 
     fn free_memory(&mut self) {
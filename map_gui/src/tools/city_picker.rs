@@ -208,6 +208,7 @@ impl<A: AppLike + 'static> State<A> for CityPicker<A> {
                     {
                         return Transition::Replace(crate::tools::importer::ImportCity::new_state(
                             ctx,
+                            app,
                             self.on_load.take().unwrap(),
                         ));
                     }
@@ -533,6 +534,9 @@ fn reimport_city<A: AppLike + 'static>(ctx: &mut EventCtx, app: &A) -> Transitio
     if app.map().get_config().driving_side == map_model::DrivingSide::Left {
         args.push("--drive-on-left".to_string());
     }
+    if app.map().get_config().roundabout_priority == map_model::RoundaboutPriority::Entering {
+        args.push("--roundabouts-yield-to-entering".to_string());
+    }
 
     // Write the current map boundary
     abstio::write_json(
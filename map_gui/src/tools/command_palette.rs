@@ -0,0 +1,161 @@
+use widgetry::{
+    Choice, DrawBaselayer, EventCtx, GfxCtx, Line, Menu, Outcome, Panel, State, Text, TextBox,
+    Transition, Widget,
+};
+
+use crate::tools::grey_out_map;
+use crate::AppLike;
+
+const MAX_RESULTS: usize = 15;
+
+/// Type to filter a list of every action available in the current state, then run one. The
+/// palette pops itself before the callback's transition is applied, so the callback acts on
+/// whatever state opened it.
+pub struct CommandPalette<A: AppLike, T> {
+    panel: Panel,
+    commands: Vec<(String, T)>,
+    // Wrapped in an Option so that we can consume it once
+    cb: Option<Box<dyn FnOnce(T, &mut EventCtx, &mut A) -> Transition<A>>>,
+}
+
+impl<A: AppLike + 'static, T: 'static + Clone> CommandPalette<A, T> {
+    pub fn new_state(
+        ctx: &mut EventCtx,
+        commands: Vec<(String, T)>,
+        cb: Box<dyn FnOnce(T, &mut EventCtx, &mut A) -> Transition<A>>,
+    ) -> Box<dyn State<A>> {
+        Box::new(CommandPalette {
+            panel: Panel::new_builder(Widget::col(vec![
+                Widget::row(vec![
+                    Line("Run a command").small_heading().into_widget(ctx),
+                    ctx.style().btn_close_widget(ctx),
+                ]),
+                TextBox::default_widget(ctx, "query", String::new()),
+                results_widget(ctx, &commands, ""),
+            ]))
+            .build(ctx),
+            commands,
+            cb: Some(cb),
+        })
+    }
+}
+
+fn results_widget<T: 'static + Clone>(
+    ctx: &EventCtx,
+    commands: &[(String, T)],
+    query: &str,
+) -> Widget {
+    let mut matches: Vec<(usize, &(String, T))> = commands
+        .iter()
+        .filter_map(|cmd| fuzzy_score(query, &cmd.0).map(|score| (score, cmd)))
+        .collect();
+    // Stable, so ties keep the order the caller listed commands in
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    let num_matches = matches.len();
+    let choices: Vec<Choice<T>> = matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, (label, data))| Choice::new(label, data.clone()))
+        .collect();
+    if choices.is_empty() {
+        return Line("No matching commands")
+            .secondary()
+            .into_widget(ctx)
+            .named("results");
+    }
+    let mut col = vec![Menu::widget(ctx, choices).named("menu")];
+    if num_matches > MAX_RESULTS {
+        col.push(
+            Text::from(
+                Line(format!("{} more, keep typing", num_matches - MAX_RESULTS)).secondary(),
+            )
+            .into_widget(ctx),
+        );
+    }
+    Widget::col(col).named("results")
+}
+
+impl<A: AppLike + 'static, T: 'static + Clone> State<A> for CommandPalette<A, T> {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut A) -> Transition<A> {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                _ => {
+                    let data = self.panel.take_menu_choice::<T>("menu");
+                    Transition::Multi(vec![
+                        Transition::Pop,
+                        (self.cb.take().unwrap())(data, ctx, app),
+                    ])
+                }
+            },
+            Outcome::Changed(_) => {
+                let results = results_widget(ctx, &self.commands, &self.panel.text_box("query"));
+                self.panel.replace(ctx, "results", results);
+                Transition::Keep
+            }
+            _ => {
+                if self.panel.clicked_outside(ctx) {
+                    return Transition::Pop;
+                }
+                Transition::Keep
+            }
+        }
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &A) {
+        grey_out_map(g, app);
+        self.panel.draw(g);
+    }
+}
+
+/// Does every character of the query appear in the candidate, in order? Case and spaces in the
+/// query are ignored. Higher scores are better matches: runs of consecutive characters and
+/// matches at the start of words count for more, so "dm" prefers "delay map" over "demand".
+fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut idx = 0;
+    let mut last_match: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        loop {
+            let c = *candidate.get(idx)?;
+            idx += 1;
+            if c != q {
+                continue;
+            }
+            let pos = idx - 1;
+            score += 1;
+            if last_match.is_some() && last_match == pos.checked_sub(1) {
+                score += 5;
+            }
+            if pos == 0 || !candidate[pos - 1].is_alphanumeric() {
+                score += 3;
+            }
+            last_match = Some(pos);
+            break;
+        }
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("xyz", "edit map").is_none());
+        assert!(fuzzy_score("pam", "map").is_none());
+        assert!(fuzzy_score("EdMa", "edit map").is_some());
+
+        // Word starts and consecutive runs beat scattered letters
+        assert!(fuzzy_score("dm", "delay map") > fuzzy_score("dm", "demand"));
+        assert!(fuzzy_score("edit", "edit map") > fuzzy_score("edit", "expected deviation time"));
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use aabb_quadtree::QuadTree;
+use anyhow::Result;
+
+use abstio::MapName;
+use geom::{Circle, Distance, GPSBounds, LonLat, PolyLine, Polygon, Pt2D, Ring};
+use map_model::Map;
+use widgetry::tools::PopupMsg;
+use widgetry::{Cached, Color, Drawable, EventCtx, GeomBatch, GfxCtx, Line, State, Text};
+
+const POINT_RADIUS: Distance = Distance::const_meters(5.0);
+const LINE_THICKNESS: Distance = Distance::const_meters(3.0);
+const OUTLINE_THICKNESS: Distance = Distance::const_meters(2.0);
+
+const DEFAULT_COLOR: Color = Color::PURPLE;
+
+/// An arbitrary GeoJSON file drawn on top of the map as a reference layer, like a planned bike
+/// network or crash locations. Hovering on an object shows its properties. Objects are styled
+/// using the simplestyle properties (`fill`, `fill-opacity`, `stroke`, `marker-color`) when
+/// present.
+pub struct GeoJsonOverlay {
+    name: String,
+    objects: Vec<Object>,
+    quadtree: QuadTree<usize>,
+    draw: Drawable,
+    hovering: Option<usize>,
+}
+
+struct Object {
+    // Points and lines are thickened, so this is always something that can be hovered on
+    polygon: Polygon,
+    properties: BTreeMap<String, String>,
+}
+
+impl GeoJsonOverlay {
+    /// Loads a GeoJSON file, keeping only objects that touch the map.
+    pub fn load(ctx: &mut EventCtx, map: &Map, path: String) -> Result<GeoJsonOverlay> {
+        let raw = String::from_utf8(abstio::slurp_file(&path)?)?;
+        let features = match raw.parse::<geojson::GeoJson>()? {
+            geojson::GeoJson::Feature(feature) => vec![feature],
+            geojson::GeoJson::FeatureCollection(collection) => collection.features,
+            geojson::GeoJson::Geometry(geometry) => vec![geojson::Feature {
+                bbox: None,
+                geometry: Some(geometry),
+                id: None,
+                properties: None,
+                foreign_members: None,
+            }],
+        };
+
+        let gps_bounds = map.get_gps_bounds();
+        let boundary = map.get_boundary_polygon();
+        let mut batch = GeomBatch::new();
+        let mut objects = Vec::new();
+        let mut quadtree = QuadTree::default(map.get_bounds().as_bbox());
+        for feature in features {
+            let geometry = if let Some(ref geometry) = feature.geometry {
+                geometry
+            } else {
+                continue;
+            };
+            let mut properties = BTreeMap::new();
+            for (key, value) in feature.properties_iter() {
+                properties.insert(
+                    key.to_string(),
+                    value
+                        .as_str()
+                        .map(|x| x.to_string())
+                        .unwrap_or_else(|| value.to_string()),
+                );
+            }
+
+            let mut shapes = Vec::new();
+            convert_geometry(&geometry.value, gps_bounds, &mut shapes);
+            for shape in shapes {
+                // The polygon used for hovering, and what to draw
+                let (polygon, pieces) = match shape {
+                    Shape::Point(pt) => {
+                        let polygon = Circle::new(pt, POINT_RADIUS).to_polygon();
+                        let color = style_color(&properties, "marker-color", None, 0.8);
+                        (polygon.clone(), vec![(color, polygon)])
+                    }
+                    Shape::Line(pl) => {
+                        let polygon = pl.make_polygons(LINE_THICKNESS);
+                        let color = style_color(&properties, "stroke", None, 0.8);
+                        (polygon.clone(), vec![(color, polygon)])
+                    }
+                    Shape::Area(ring) => {
+                        let fill = style_color(&properties, "fill", Some("fill-opacity"), 0.5);
+                        let stroke = style_color(&properties, "stroke", None, 0.8);
+                        let outline = ring.to_outline(OUTLINE_THICKNESS);
+                        let polygon = ring.into_polygon();
+                        (polygon.clone(), vec![(fill, polygon), (stroke, outline)])
+                    }
+                };
+                if !polygon.intersects(boundary) {
+                    continue;
+                }
+                for (color, piece) in pieces {
+                    batch.push(color, piece);
+                }
+                quadtree.insert_with_box(objects.len(), polygon.get_bounds().as_bbox());
+                objects.push(Object {
+                    polygon,
+                    properties: properties.clone(),
+                });
+            }
+        }
+
+        Ok(GeoJsonOverlay {
+            name: abstutil::basename(&path),
+            objects,
+            quadtree,
+            draw: ctx.upload(batch),
+            hovering: None,
+        })
+    }
+
+    /// Apps keep one overlay per map. Call this every event from states showing the map. If a file
+    /// is dropped onto the window, it replaces the overlay. If loading fails, returns a state
+    /// explaining why.
+    pub fn handle_event<A: 'static>(
+        ctx: &mut EventCtx,
+        map: &Map,
+        overlay: &mut Cached<MapName, GeoJsonOverlay>,
+    ) -> Option<Box<dyn State<A>>> {
+        if overlay.key().as_ref() != Some(map.get_name()) {
+            overlay.clear();
+        }
+        if let Some(path) = ctx.input.dropped_file() {
+            match ctx.loading_screen("load GeoJSON overlay", |ctx, _| {
+                GeoJsonOverlay::load(ctx, map, path.clone())
+            }) {
+                Ok(x) => {
+                    overlay.set(map.get_name().clone(), x);
+                }
+                Err(err) => {
+                    return Some(PopupMsg::new_state(
+                        ctx,
+                        "Couldn't load overlay",
+                        vec![format!("{} isn't valid GeoJSON: {}", path, err)],
+                    ));
+                }
+            }
+        }
+        if let Some(x) = overlay.value_mut() {
+            x.event(ctx);
+        }
+        None
+    }
+
+    /// Draws the overlay, if there is one for this map.
+    pub fn draw_cached(g: &mut GfxCtx, map: &Map, overlay: &Cached<MapName, GeoJsonOverlay>) {
+        if overlay.key().as_ref() == Some(map.get_name()) {
+            overlay.value().unwrap().draw(g);
+        }
+    }
+
+    /// Call every event to keep track of what the mouse is hovering on.
+    pub fn event(&mut self, ctx: &mut EventCtx) {
+        if !ctx.redo_mouseover() {
+            return;
+        }
+        self.hovering = None;
+        if let Some(pt) = ctx.canvas.get_cursor_in_map_space() {
+            // If objects overlap, prefer the last one drawn
+            let mut candidates: Vec<usize> = self
+                .quadtree
+                .query(
+                    Circle::new(pt, Distance::meters(1.0))
+                        .get_bounds()
+                        .as_bbox(),
+                )
+                .into_iter()
+                .map(|(idx, _, _)| *idx)
+                .filter(|idx| self.objects[*idx].polygon.contains_pt(pt))
+                .collect();
+            candidates.sort();
+            self.hovering = candidates.pop();
+        }
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx) {
+        g.redraw(&self.draw);
+        if let Some(idx) = self.hovering {
+            let obj = &self.objects[idx];
+            g.draw_polygon(Color::CYAN.alpha(0.5), obj.polygon.clone());
+
+            let mut txt = Text::from(Line(&self.name).small_heading());
+            if obj.properties.is_empty() {
+                txt.add_line(Line("No properties").secondary());
+            }
+            for (key, value) in &obj.properties {
+                txt.add_line(format!("{} = {}", key, value));
+            }
+            g.draw_mouse_tooltip(txt);
+        }
+    }
+}
+
+enum Shape {
+    Point(Pt2D),
+    Line(PolyLine),
+    Area(Ring),
+}
+
+fn convert_geometry(value: &geojson::Value, gps_bounds: &GPSBounds, shapes: &mut Vec<Shape>) {
+    let convert = |pts: &[Vec<f64>]| -> Vec<Pt2D> {
+        gps_bounds.convert(
+            &pts.iter()
+                .map(|pt| LonLat::new(pt[0], pt[1]))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let add_line = |pts: &[Vec<f64>], shapes: &mut Vec<Shape>| {
+        if let Ok(pl) = PolyLine::deduping_new(convert(pts)) {
+            shapes.push(Shape::Line(pl));
+        }
+    };
+    // Holes in polygons are ignored
+    let add_area = |rings: &[Vec<Vec<f64>>], shapes: &mut Vec<Shape>| {
+        if let Some(outer) = rings.first() {
+            if let Ok(ring) = Ring::deduping_new(convert(outer)) {
+                shapes.push(Shape::Area(ring));
+            }
+        }
+    };
+
+    match value {
+        geojson::Value::Point(pt) => {
+            shapes.push(Shape::Point(LonLat::new(pt[0], pt[1]).to_pt(gps_bounds)));
+        }
+        geojson::Value::MultiPoint(pts) => {
+            for pt in convert(pts) {
+                shapes.push(Shape::Point(pt));
+            }
+        }
+        geojson::Value::LineString(pts) => add_line(pts, shapes),
+        geojson::Value::MultiLineString(lines) => {
+            for pts in lines {
+                add_line(pts, shapes);
+            }
+        }
+        geojson::Value::Polygon(rings) => add_area(rings, shapes),
+        geojson::Value::MultiPolygon(polygons) => {
+            for rings in polygons {
+                add_area(rings, shapes);
+            }
+        }
+        geojson::Value::GeometryCollection(geometries) => {
+            for geometry in geometries {
+                convert_geometry(&geometry.value, gps_bounds, shapes);
+            }
+        }
+    }
+}
+
+/// Parses a simplestyle color like "#ff0000", falling back to a default.
+fn style_color(
+    properties: &BTreeMap<String, String>,
+    key: &str,
+    opacity_key: Option<&str>,
+    default_opacity: f32,
+) -> Color {
+    let color = properties
+        .get(key)
+        .and_then(|x| parse_hex_color(x))
+        .unwrap_or(DEFAULT_COLOR);
+    let opacity = opacity_key
+        .and_then(|k| properties.get(k))
+        .and_then(|x| x.parse::<f32>().ok())
+        .unwrap_or(default_opacity);
+    color.alpha(opacity.clamp(0.0, 1.0))
+}
+
+fn parse_hex_color(raw: &str) -> Option<Color> {
+    if raw.len() == 7 && raw.starts_with('#') && raw[1..].chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(Color::hex(raw))
+    } else {
+        None
+    }
+}
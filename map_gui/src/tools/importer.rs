@@ -4,17 +4,19 @@ use anyhow::Result;
 use clipboard::{ClipboardContext, ClipboardProvider};
 
 use abstio::MapName;
-use widgetry::tools::{open_browser, PopupMsg};
-use widgetry::{
-    EventCtx, GfxCtx, Line, Outcome, Panel, State, TextBox, TextExt, Toggle, Transition, Widget,
-};
+use widgetry::tools::{open_browser, Wizard, WizardStep};
+use widgetry::{EventCtx, Line, State, TextBox, TextExt, Toggle, Transition, Widget};
 
 use crate::load::MapLoader;
 use crate::tools::find_exe;
 use crate::AppLike;
 
 pub struct ImportCity<A: AppLike> {
-    panel: Panel,
+    name: String,
+    drive_on_left: bool,
+    use_geofabrik: bool,
+    filter_crosswalks: bool,
+    uk_travel_demand: bool,
     // Wrapped in an Option just to make calling from event() work.
     on_load: Option<Box<dyn FnOnce(&mut EventCtx, &mut A) -> Transition<A>>>,
 }
@@ -22,161 +24,167 @@ pub struct ImportCity<A: AppLike> {
 impl<A: AppLike + 'static> ImportCity<A> {
     pub fn new_state(
         ctx: &mut EventCtx,
+        app: &A,
         on_load: Box<dyn FnOnce(&mut EventCtx, &mut A) -> Transition<A>>,
     ) -> Box<dyn State<A>> {
-        let panel = Panel::new_builder(Widget::col(vec![
-            Widget::row(vec![
-                Line("Import a new city").small_heading().into_widget(ctx),
-                ctx.style().btn_close_widget(ctx),
-            ]),
-            Widget::col(vec![
-                Widget::row(vec![
-                    "Step 1)".text_widget(ctx).centered_vert(),
-                    ctx.style()
-                        .btn_plain
-                        .btn()
-                        .label_underlined_text("Go to geojson.io")
-                        .build_def(ctx),
-                ]),
-                Widget::row(vec![
-                    "Step 2)".text_widget(ctx).margin_right(16),
-                    "Draw a polygon boundary where you want to import"
-                        .text_widget(ctx)
-                        .margin_below(16),
-                ])
-                .margin_below(16),
-                Widget::row(vec![
-                    "Step 3)".text_widget(ctx).margin_right(16),
-                    "Copy the JSON text on the right into your clipboard".text_widget(ctx),
-                ])
-                .margin_below(16),
-                Toggle::choice(
-                    ctx,
-                    "left handed driving",
-                    "drive on the left",
-                    "right",
-                    None,
-                    false,
-                ),
-                Widget::row(vec![
-                    "Name the map:".text_widget(ctx).centered_vert(),
-                    TextBox::widget(ctx, "new_map_name", generate_new_map_name(), true, 20),
-                ]),
-                ctx.style()
-                    .btn_solid_primary
-                    .text("Import the area from your clipboard")
-                    .build_def(ctx)
-                    .margin_below(32),
-                ctx.style()
-                    .btn_plain
-                    .btn()
-                    .label_underlined_text("Alternate instructions")
-                    .build_def(ctx),
-                Widget::col(vec![
-                    Line("Advanced settings").secondary().into_widget(ctx),
-                    Widget::row(vec![
-                        "Import data from:".text_widget(ctx).centered_vert(),
-                        Toggle::choice(
-                            ctx,
-                            "source",
-                            "GeoFabrik",
-                            "Overpass (faster)",
-                            None,
-                            false,
-                        ),
-                    ]),
-                    Toggle::switch(ctx, "Filter crosswalks", None, false),
-                    Toggle::switch(ctx, "Generate travel demand model (UK only)", None, false),
-                ])
-                .section(ctx),
-            ])
-            .section(ctx),
-        ]))
-        .build(ctx);
-        Box::new(ImportCity {
-            panel,
+        let state = ImportCity {
+            name: generate_new_map_name(),
+            drive_on_left: false,
+            use_geofabrik: false,
+            filter_crosswalks: false,
+            uk_travel_demand: false,
             on_load: Some(on_load),
-        })
-    }
-}
-
-impl<A: AppLike + 'static> State<A> for ImportCity<A> {
-    fn event(&mut self, ctx: &mut EventCtx, _: &mut A) -> Transition<A> {
-        match self.panel.event(ctx) {
-            Outcome::Clicked(x) => match x.as_ref() {
-                "close" => Transition::Pop,
-                "Alternate instructions" => {
-                    open_browser("https://a-b-street.github.io/docs/user/new_city.html");
-                    Transition::Keep
-                }
-                "Go to geojson.io" => {
-                    open_browser("http://geojson.io");
-                    Transition::Keep
-                }
-                "Import the area from your clipboard" => {
-                    let name = sanitize_name(self.panel.text_box("new_map_name"));
+        };
 
-                    let mut args = vec![
-                        find_exe("cli"),
-                        "one-step-import".to_string(),
-                        "--geojson-path=boundary.geojson".to_string(),
-                        format!("--map-name={}", name),
-                    ];
-                    if self.panel.is_checked("left handed driving") {
-                        args.push("--drive-on-left".to_string());
-                    }
-                    if self.panel.is_checked("source") {
-                        args.push("--use-geofabrik".to_string());
+        Wizard::builder("Import a new city", state)
+            .step(
+                WizardStep::new(
+                    "Draw the boundary",
+                    Box::new(|ctx, _, _| {
+                        Widget::col(vec![
+                            ctx.style()
+                                .btn_plain
+                                .btn()
+                                .label_underlined_text("Go to geojson.io")
+                                .build_def(ctx),
+                            "Draw a polygon boundary where you want to import".text_widget(ctx),
+                            ctx.style()
+                                .btn_plain
+                                .btn()
+                                .label_underlined_text("Alternate instructions")
+                                .build_def(ctx),
+                        ])
+                    }),
+                )
+                .on_click(Box::new(|_, _, _, x| {
+                    match x {
+                        "Go to geojson.io" => open_browser("http://geojson.io"),
+                        "Alternate instructions" => {
+                            open_browser("https://a-b-street.github.io/docs/user/new_city.html")
+                        }
+                        _ => unreachable!(),
                     }
-                    if self.panel.is_checked("Filter crosswalks") {
-                        args.push("--filter-crosswalks".to_string());
-                    }
-                    if self
-                        .panel
-                        .is_checked("Generate travel demand model (UK only)")
-                    {
-                        args.push("--create-uk-travel-demand-model".to_string());
+                    Transition::Keep
+                })),
+            )
+            .step(
+                WizardStep::new(
+                    "Copy the boundary",
+                    Box::new(|ctx, _, _| {
+                        "Copy the JSON text on the right into your clipboard".text_widget(ctx)
+                    }),
+                )
+                .validate(Box::new(|_, _, _| {
+                    grab_geojson_from_clipboard()
+                        .map_err(|err| format!("Couldn't get GeoJSON from your clipboard: {}", err))
+                })),
+            )
+            .step(
+                WizardStep::new(
+                    "Settings",
+                    Box::new(|ctx, _, state: &ImportCity<A>| {
+                        Widget::col(vec![
+                            Toggle::choice(
+                                ctx,
+                                "left handed driving",
+                                "drive on the left",
+                                "right",
+                                None,
+                                state.drive_on_left,
+                            ),
+                            Widget::row(vec![
+                                "Name the map:".text_widget(ctx).centered_vert(),
+                                TextBox::widget(ctx, "new_map_name", state.name.clone(), true, 20),
+                            ]),
+                            Widget::col(vec![
+                                Line("Advanced settings").secondary().into_widget(ctx),
+                                Widget::row(vec![
+                                    "Import data from:".text_widget(ctx).centered_vert(),
+                                    Toggle::choice(
+                                        ctx,
+                                        "source",
+                                        "GeoFabrik",
+                                        "Overpass (faster)",
+                                        None,
+                                        state.use_geofabrik,
+                                    ),
+                                ]),
+                                Toggle::switch(
+                                    ctx,
+                                    "Filter crosswalks",
+                                    None,
+                                    state.filter_crosswalks,
+                                ),
+                                Toggle::switch(
+                                    ctx,
+                                    "Generate travel demand model (UK only)",
+                                    None,
+                                    state.uk_travel_demand,
+                                ),
+                            ])
+                            .section(ctx),
+                        ])
+                    }),
+                )
+                .validate(Box::new(|panel, _, state| {
+                    let name = sanitize_name(panel.text_box("new_map_name"));
+                    if name.is_empty() {
+                        return Err("Name the map first".to_string());
                     }
-                    match grab_geojson_from_clipboard() {
-                        Ok(()) => Transition::Push(crate::tools::RunCommand::new_state(
-                            ctx,
-                            true,
-                            args,
-                            Box::new(|_, _, success, _| {
-                                if success {
-                                    abstio::delete_file("boundary.geojson");
+                    state.name = name;
+                    state.drive_on_left = panel.is_checked("left handed driving");
+                    state.use_geofabrik = panel.is_checked("source");
+                    state.filter_crosswalks = panel.is_checked("Filter crosswalks");
+                    state.uk_travel_demand =
+                        panel.is_checked("Generate travel demand model (UK only)");
+                    Ok(())
+                })),
+            )
+            .finish_label("Import")
+            .cancellable(Box::new(|_, _| Transition::Pop))
+            .build(ctx, app, Box::new(|ctx, _, state| state.import(ctx)))
+    }
 
-                                    Transition::ConsumeState(Box::new(move |state, ctx, app| {
-                                        let mut state =
-                                            state.downcast::<ImportCity<A>>().ok().unwrap();
-                                        let on_load = state.on_load.take().unwrap();
-                                        let map_name = MapName::new("zz", "oneshot", &name);
-                                        vec![MapLoader::new_state(ctx, app, map_name, on_load)]
-                                    }))
-                                } else {
-                                    // The popup already explained the failure
-                                    Transition::Keep
-                                }
-                            }),
-                        )),
-                        Err(err) => Transition::Push(PopupMsg::new_state(
-                            ctx,
-                            "Error",
-                            vec![
-                                "Couldn't get GeoJSON from your clipboard".to_string(),
-                                err.to_string(),
-                            ],
-                        )),
-                    }
-                }
-                _ => unreachable!(),
-            },
-            _ => Transition::Keep,
+    fn import(&self, ctx: &mut EventCtx) -> Transition<A> {
+        let mut args = vec![
+            find_exe("cli"),
+            "one-step-import".to_string(),
+            "--geojson-path=boundary.geojson".to_string(),
+            format!("--map-name={}", self.name),
+        ];
+        if self.drive_on_left {
+            args.push("--drive-on-left".to_string());
         }
-    }
+        if self.use_geofabrik {
+            args.push("--use-geofabrik".to_string());
+        }
+        if self.filter_crosswalks {
+            args.push("--filter-crosswalks".to_string());
+        }
+        if self.uk_travel_demand {
+            args.push("--create-uk-travel-demand-model".to_string());
+        }
+        let name = self.name.clone();
+        Transition::Push(crate::tools::RunCommand::new_state(
+            ctx,
+            true,
+            args,
+            Box::new(|_, _, success, _| {
+                if success {
+                    abstio::delete_file("boundary.geojson");
 
-    fn draw(&self, g: &mut GfxCtx, _: &A) {
-        self.panel.draw(g);
+                    Transition::ConsumeState(Box::new(move |state, ctx, app| {
+                        let mut state = state.downcast::<Wizard<A, ImportCity<A>>>().ok().unwrap();
+                        let on_load = state.state_mut().on_load.take().unwrap();
+                        let map_name = MapName::new("zz", "oneshot", &name);
+                        vec![MapLoader::new_state(ctx, app, map_name, on_load)]
+                    }))
+                } else {
+                    // The popup already explained the failure
+                    Transition::Keep
+                }
+            }),
+        ))
     }
 }
 
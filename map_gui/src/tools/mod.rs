@@ -10,18 +10,23 @@ use widgetry::{lctrl, EventCtx, GfxCtx, Key, Line, Text, Widget};
 pub use self::camera::{CameraState, DefaultMap};
 pub use self::city_picker::CityPicker;
 pub use self::colors::{ColorDiscrete, ColorLegend, ColorNetwork, ColorScale, DivergingScale};
+pub use self::command_palette::CommandPalette;
+pub use self::geojson_overlay::GeoJsonOverlay;
 pub use self::heatmap::{draw_isochrone, make_heatmap, Grid, HeatmapOptions};
 pub use self::icons::{goal_marker, start_marker};
 pub use self::labels::DrawRoadLabels;
 pub use self::minimap::{Minimap, MinimapControls};
-pub use self::navigate::Navigator;
+pub use self::navigate::{Navigator, SearchAddress};
+pub use self::notes::{describe_note, NoteEditor};
 pub use self::title_screen::{Executable, TitleScreen};
 pub use self::trip_files::{TripManagement, TripManagementState};
 pub use self::turn_explorer::TurnExplorer;
+pub use self::tutorial::{MessageAction, TutorialMessage};
 pub use self::ui::{
     checkbox_per_mode, cmp_count, cmp_dist, cmp_duration, color_for_mode, percentage_bar,
     ChooseSomething, FilePicker, PromptInput,
 };
+pub use self::ui_state::UiState;
 pub use self::waypoints::{InputWaypoints, WaypointID};
 use crate::AppLike;
 
@@ -35,7 +40,9 @@ mod city_picker;
 mod colors;
 #[cfg(not(target_arch = "wasm32"))]
 mod command;
+mod command_palette;
 pub mod compare_counts;
+mod geojson_overlay;
 mod heatmap;
 mod icons;
 #[cfg(not(target_arch = "wasm32"))]
@@ -43,10 +50,13 @@ mod importer;
 mod labels;
 mod minimap;
 mod navigate;
+mod notes;
 mod title_screen;
 mod trip_files;
 mod turn_explorer;
+mod tutorial;
 mod ui;
+mod ui_state;
 #[cfg(not(target_arch = "wasm32"))]
 mod updater;
 mod waypoints;
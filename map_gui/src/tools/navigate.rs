@@ -1,9 +1,9 @@
 use std::collections::HashSet;
 
-use map_model::RoadID;
+use map_model::{BuildingID, RoadID};
 use widgetry::{
     Autocomplete, Color, Drawable, EventCtx, GeomBatch, GfxCtx, Key, Line, Outcome, Panel, State,
-    Text, Transition, Widget,
+    Text, TextBox, Transition, Widget,
 };
 
 use crate::tools::grey_out_map;
@@ -32,11 +32,17 @@ impl Navigator {
                     10,
                 )
                 .named("street"),
-                ctx.style()
-                    .btn_outline
-                    .text("Search by business name or address")
-                    .hotkey(Key::Tab)
-                    .build_def(ctx),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_outline
+                        .text("Search by business name or address")
+                        .hotkey(Key::Tab)
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_outline
+                        .text("Find a street address")
+                        .build_def(ctx),
+                ]),
             ]))
             .build(ctx),
         })
@@ -53,6 +59,12 @@ impl<A: AppLike + 'static> State<A> for Navigator {
                 "Search by business name or address" => {
                     return Transition::Replace(SearchBuildings::new_state(ctx, app));
                 }
+                "Find a street address" => {
+                    return Transition::Replace(SearchAddress::new_state(
+                        ctx,
+                        Box::new(warp_to_building),
+                    ));
+                }
                 _ => unreachable!(),
             }
         }
@@ -240,11 +252,17 @@ impl SearchBuildings {
                     10,
                 )
                 .named("bldg"),
-                ctx.style()
-                    .btn_outline
-                    .text("Search for streets")
-                    .hotkey(Key::Tab)
-                    .build_def(ctx),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_outline
+                        .text("Search for streets")
+                        .hotkey(Key::Tab)
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_outline
+                        .text("Find a street address")
+                        .build_def(ctx),
+                ]),
             ]))
             .build(ctx),
         })
@@ -261,6 +279,12 @@ impl<A: AppLike + 'static> State<A> for SearchBuildings {
                 "Search for streets" => {
                     return Transition::Replace(Navigator::new_state(ctx, app));
                 }
+                "Find a street address" => {
+                    return Transition::Replace(SearchAddress::new_state(
+                        ctx,
+                        Box::new(warp_to_building),
+                    ));
+                }
                 _ => unreachable!(),
             }
         }
@@ -268,14 +292,115 @@ impl<A: AppLike + 'static> State<A> for SearchBuildings {
             if bldgs.is_empty() {
                 return Transition::Pop;
             }
-            let b = app.map().get_b(bldgs[0]);
-            let pt = b.label_center;
-            return Transition::Replace(app.make_warper(
-                ctx,
-                pt,
-                Some(ctx.canvas.settings.min_zoom_for_detail),
-                Some(ID::Building(bldgs[0])),
-            ));
+            return warp_to_building(ctx, app, bldgs[0]);
+        }
+
+        if self.panel.clicked_outside(ctx) {
+            return Transition::Pop;
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &A) {
+        grey_out_map(g, app);
+        self.panel.draw(g);
+    }
+}
+
+fn warp_to_building<A: AppLike + 'static>(
+    ctx: &mut EventCtx,
+    app: &mut A,
+    b: BuildingID,
+) -> Transition<A> {
+    let pt = app.map().get_b(b).label_center;
+    Transition::Replace(app.make_warper(
+        ctx,
+        pt,
+        Some(ctx.canvas.settings.min_zoom_for_detail),
+        Some(ID::Building(b)),
+    ))
+}
+
+/// Type a street address and pick a building from the closest matches. Used to jump the camera
+/// or to choose trip endpoints without hunting for the building on the map.
+pub struct SearchAddress<A: AppLike> {
+    panel: Panel,
+    results: Vec<BuildingID>,
+    /// Must pop or replace this state
+    on_choose: Option<Box<dyn FnOnce(&mut EventCtx, &mut A, BuildingID) -> Transition<A>>>,
+}
+
+impl<A: AppLike + 'static> SearchAddress<A> {
+    pub fn new_state(
+        ctx: &mut EventCtx,
+        on_choose: Box<dyn FnOnce(&mut EventCtx, &mut A, BuildingID) -> Transition<A>>,
+    ) -> Box<dyn State<A>> {
+        Box::new(SearchAddress {
+            panel: Panel::new_builder(Widget::col(vec![
+                Widget::row(vec![
+                    Line("Enter a street address")
+                        .small_heading()
+                        .into_widget(ctx),
+                    ctx.style().btn_close_widget(ctx),
+                ]),
+                Widget::row(vec![
+                    TextBox::default_widget(ctx, "query", String::new()),
+                    ctx.style()
+                        .btn_solid_primary
+                        .text("Search")
+                        .hotkey(Key::Enter)
+                        .build_def(ctx),
+                ]),
+                Text::from(Line("Like \"1234 N 34th St\"").secondary())
+                    .into_widget(ctx)
+                    .named("results"),
+            ]))
+            .build(ctx),
+            results: Vec::new(),
+            on_choose: Some(on_choose),
+        })
+    }
+
+    fn search(&mut self, ctx: &mut EventCtx, app: &A) {
+        let map = app.map();
+        self.results = map.geocode(&self.panel.text_box("query"), 10);
+        let results = if self.results.is_empty() {
+            Text::from(Line("No matching addresses").secondary()).into_widget(ctx)
+        } else {
+            Widget::col(
+                self.results
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, b)| {
+                        ctx.style()
+                            .btn_plain
+                            .text(&map.get_b(*b).address)
+                            .build_widget(ctx, &format!("result {}", idx))
+                    })
+                    .collect(),
+            )
+        };
+        self.panel.replace(ctx, "results", results.named("results"));
+    }
+}
+
+impl<A: AppLike + 'static> State<A> for SearchAddress<A> {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut A) -> Transition<A> {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Search" => {
+                    self.search(ctx, app);
+                }
+                x => {
+                    let idx = x.strip_prefix("result ").unwrap().parse::<usize>().unwrap();
+                    let b = self.results[idx];
+                    return (self.on_choose.take().unwrap())(ctx, app, b);
+                }
+            }
         }
 
         if self.panel.clicked_outside(ctx) {
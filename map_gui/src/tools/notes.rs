@@ -0,0 +1,110 @@
+use map_model::EditNote;
+use widgetry::{
+    DrawBaselayer, EventCtx, GfxCtx, Key, Line, Outcome, Panel, State, Text, TextBox, TextExt,
+    Transition, Widget,
+};
+
+use crate::tools::grey_out_map;
+use crate::AppLike;
+
+/// Write or change the note explaining an edit, then feed the result to a callback. `None` means
+/// the note was deleted.
+pub struct NoteEditor<A: AppLike> {
+    panel: Panel,
+    cb: Option<Box<dyn FnOnce(Option<EditNote>, &mut EventCtx, &mut A) -> Transition<A>>>,
+}
+
+impl<A: AppLike + 'static> NoteEditor<A> {
+    pub fn new_state<I: Into<String>>(
+        ctx: &mut EventCtx,
+        title: I,
+        current: Option<&EditNote>,
+        cb: Box<dyn FnOnce(Option<EditNote>, &mut EventCtx, &mut A) -> Transition<A>>,
+    ) -> Box<dyn State<A>> {
+        let (text, images) = current
+            .map(|note| (note.text.clone(), note.images.join(", ")))
+            .unwrap_or_default();
+        Box::new(NoteEditor {
+            panel: Panel::new_builder(Widget::col(vec![
+                Widget::row(vec![
+                    Line(title).small_heading().into_widget(ctx),
+                    ctx.style().btn_close_widget(ctx),
+                ]),
+                "Why was this changed?".text_widget(ctx),
+                TextBox::widget(ctx, "note", text, true, 50),
+                "Photos or sketches (file paths or links, separated by commas)".text_widget(ctx),
+                TextBox::widget(ctx, "images", images, false, 50),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_solid_primary
+                        .text("Save")
+                        .hotkey(Key::Enter)
+                        .build_def(ctx),
+                    ctx.style()
+                        .btn_solid_destructive
+                        .text("Delete note")
+                        .disabled(current.is_none())
+                        .build_def(ctx),
+                ]),
+            ]))
+            .build(ctx),
+            cb: Some(cb),
+        })
+    }
+}
+
+impl<A: AppLike + 'static> State<A> for NoteEditor<A> {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut A) -> Transition<A> {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "close" => Transition::Pop,
+                "Save" => {
+                    let text = self.panel.text_box("note").trim().to_string();
+                    let images: Vec<String> = self
+                        .panel
+                        .text_box("images")
+                        .split(',')
+                        .map(|x| x.trim().to_string())
+                        .filter(|x| !x.is_empty())
+                        .collect();
+                    // An empty note is the same as none
+                    let note = if text.is_empty() && images.is_empty() {
+                        None
+                    } else {
+                        Some(EditNote { text, images })
+                    };
+                    (self.cb.take().unwrap())(note, ctx, app)
+                }
+                "Delete note" => (self.cb.take().unwrap())(None, ctx, app),
+                _ => unreachable!(),
+            },
+            _ => {
+                if self.panel.clicked_outside(ctx) {
+                    return Transition::Pop;
+                }
+                Transition::Keep
+            }
+        }
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &A) {
+        grey_out_map(g, app);
+        self.panel.draw(g);
+    }
+}
+
+/// Describes a note for a tooltip or info panel.
+pub fn describe_note(note: &EditNote) -> Text {
+    let mut txt = Text::from(Line("Note").small_heading());
+    if !note.text.is_empty() {
+        txt.add_line(note.text.clone());
+    }
+    for image in &note.images {
+        txt.add_line(Line(format!("Photo: {}", image)).secondary());
+    }
+    txt
+}
@@ -0,0 +1,139 @@
+//! Pieces shared by the interactive tutorials in different apps. A tutorial alternates between
+//! pages of instructions and tasks where the player tries something out; each app decides what its
+//! tasks are and how to detect they're done.
+
+use geom::{ArrowCap, Distance, PolyLine, Pt2D};
+use widgetry::{
+    hotkeys, Color, EventCtx, GfxCtx, HorizontalAlignment, Image, Key, Line, Outcome, Panel,
+    ScreenPt, Text, TextExt, VerticalAlignment, Widget,
+};
+
+/// One page of instructions, optionally pointing at something on the screen.
+pub struct TutorialMessage<A> {
+    pub txt: Text,
+    pub aligned: HorizontalAlignment,
+    pub arrow: Option<Box<dyn Fn(&GfxCtx, &A) -> Pt2D>>,
+    pub icon: Option<&'static str>,
+}
+
+/// What the player did with the message panel
+pub enum MessageAction {
+    Previous,
+    /// Either the next message, or the last one was dismissed to try the task
+    Next,
+    /// Nothing clicked. Other interactions should be blocked while a message is shown.
+    Nothing,
+}
+
+impl MessageAction {
+    /// Handle events on a panel made by `TutorialMessage::make_panel`.
+    pub fn event(ctx: &mut EventCtx, panel: &mut Panel) -> MessageAction {
+        match panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "previous message" => MessageAction::Previous,
+                "next message" | "Try it" => MessageAction::Next,
+                _ => unreachable!(),
+            },
+            _ => MessageAction::Nothing,
+        }
+    }
+}
+
+impl<A> TutorialMessage<A> {
+    pub fn new(txt: Text) -> TutorialMessage<A> {
+        TutorialMessage {
+            txt,
+            aligned: HorizontalAlignment::Center,
+            arrow: None,
+            icon: None,
+        }
+    }
+
+    pub fn arrow(mut self, pt: ScreenPt) -> TutorialMessage<A> {
+        self.arrow = Some(Box::new(move |_, _| pt.to_pt()));
+        self
+    }
+
+    pub fn dynamic_arrow(mut self, arrow: Box<dyn Fn(&GfxCtx, &A) -> Pt2D>) -> TutorialMessage<A> {
+        self.arrow = Some(arrow);
+        self
+    }
+
+    pub fn icon(mut self, path: &'static str) -> TutorialMessage<A> {
+        self.icon = Some(path);
+        self
+    }
+
+    pub fn left_aligned(mut self) -> TutorialMessage<A> {
+        self.aligned = HorizontalAlignment::Left;
+        self
+    }
+
+    /// Show this message as page `part` of `num_parts`, under a title. The last page has a button
+    /// to go try the task.
+    pub fn make_panel(
+        &self,
+        ctx: &mut EventCtx,
+        title: &str,
+        part: usize,
+        num_parts: usize,
+    ) -> Panel {
+        let mut col = vec![{
+            let mut txt = Text::new();
+            txt.add_line(Line(title).small_heading());
+            txt.add_line("");
+            txt.into_widget(ctx)
+        }];
+        if let Some(icon) = self.icon {
+            col.push(Image::from_path(icon).dims(30.0).into_widget(ctx));
+        }
+        col.push(self.txt.clone().wrap_to_pct(ctx, 30).into_widget(ctx));
+        let mut controls = vec![Widget::row(vec![
+            ctx.style()
+                .btn_prev()
+                .disabled(part == 0)
+                .hotkey(Key::LeftArrow)
+                .build_widget(ctx, "previous message"),
+            format!("{}/{}", part + 1, num_parts)
+                .text_widget(ctx)
+                .centered_vert(),
+            ctx.style()
+                .btn_next()
+                .disabled(part == num_parts - 1)
+                .hotkey(Key::RightArrow)
+                .build_widget(ctx, "next message"),
+        ])];
+        if part == num_parts - 1 {
+            controls.push(
+                ctx.style()
+                    .btn_solid_primary
+                    .text("Try it")
+                    .hotkey(hotkeys(vec![Key::RightArrow, Key::Space, Key::Enter]))
+                    .build_def(ctx),
+            );
+        }
+        col.push(Widget::col(controls).align_bottom());
+
+        Panel::new_builder(Widget::col(col).outline((5.0, Color::WHITE)))
+            .exact_size_percent(40, 40)
+            .aligned(self.aligned, VerticalAlignment::Center)
+            .build(ctx)
+    }
+
+    /// Draw a panel made by `make_panel`, with the arrow underneath it, but on top of other
+    /// panels.
+    pub fn draw(&self, g: &mut GfxCtx, app: &A, panel: &Panel) {
+        if let Some(ref fxn) = self.arrow {
+            let pt = (fxn)(g, app);
+            g.fork_screenspace();
+            if let Ok(pl) = PolyLine::new(vec![panel.center_of("next message").to_pt(), pt]) {
+                g.draw_polygon(
+                    Color::RED,
+                    pl.make_arrow(Distance::meters(20.0), ArrowCap::Triangle),
+                );
+            }
+            g.unfork();
+        }
+        panel.draw(g);
+    }
+}
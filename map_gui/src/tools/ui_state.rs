@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use abstio::MapName;
+use abstutil::Timer;
+
+/// Context that an app remembers between sessions, so restarting it doesn't lose your place. Each
+/// app keeps its own file in the player directory. (The camera is remembered per map by
+/// `CameraState`, so restoring the last map also restores the view.)
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct UiState {
+    #[serde(skip)]
+    app_name: String,
+
+    pub last_map: Option<MapName>,
+    /// The name of the last active layer, for apps with layers.
+    pub last_layer: Option<String>,
+    /// Whether the user left some panels minimized, keyed by a name each app picks.
+    pub minimized_panels: BTreeMap<String, bool>,
+}
+
+impl UiState {
+    /// Load the state for one app, or start fresh if the file is missing or broken. The state
+    /// from `UiState::default()` isn't tied to any app, and saving it does nothing.
+    pub fn load(app_name: &str) -> UiState {
+        let mut state = abstio::maybe_read_json::<UiState>(
+            abstio::path_ui_state(app_name),
+            &mut Timer::throwaway(),
+        )
+        .unwrap_or_default();
+        state.app_name = app_name.to_string();
+        state
+    }
+
+    pub fn save(&self) {
+        if self.app_name.is_empty() {
+            return;
+        }
+        abstio::write_json(abstio::path_ui_state(&self.app_name), self);
+    }
+
+    /// Save, but first remember the current map.
+    pub fn save_with_map(&self, name: &MapName) {
+        let mut state = self.clone();
+        if name != &MapName::blank() {
+            state.last_map = Some(name.clone());
+        }
+        state.save();
+    }
+
+    /// Returns `default` if the user has never changed this panel.
+    pub fn is_minimized(&self, panel: &str, default: bool) -> bool {
+        self.minimized_panels.get(panel).cloned().unwrap_or(default)
+    }
+
+    pub fn set_minimized(&mut self, panel: &str, minimized: bool) {
+        self.minimized_panels.insert(panel.to_string(), minimized);
+    }
+}
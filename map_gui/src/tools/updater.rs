@@ -15,16 +15,7 @@ use crate::AppLike;
 
 /// How many bytes to download for a city?
 fn size_of_city(map: &MapName) -> u64 {
-    let mut data_packs = DataPacks {
-        runtime: BTreeSet::new(),
-        input: BTreeSet::new(),
-    };
-    data_packs.runtime.insert(map.to_data_pack_name());
-    let mut manifest = Manifest::load().filter(data_packs);
-    // Don't download files that already exist
-    manifest
-        .entries
-        .retain(|path, _| !abstio::file_exists(&abstio::path(path.strip_prefix("data/").unwrap())));
+    let manifest = files_to_download(vec![map.to_data_pack_name()]);
     let mut bytes = 0;
     for (_, entry) in manifest.entries {
         bytes += entry.compressed_size_bytes;
@@ -97,18 +88,9 @@ async fn download_cities(
     mut outer_progress: mpsc::Sender<String>,
     mut inner_progress: mpsc::Sender<String>,
 ) -> Vec<String> {
-    let mut data_packs = DataPacks {
-        runtime: BTreeSet::new(),
-        input: BTreeSet::new(),
-    };
-    data_packs.runtime.extend(cities);
-    let mut manifest = Manifest::load().filter(data_packs);
-    // Don't download files that already exist
-    manifest
-        .entries
-        .retain(|path, _| !abstio::file_exists(&abstio::path(path.strip_prefix("data/").unwrap())));
-
+    let manifest = files_to_download(cities);
     let num_files = manifest.entries.len();
+    let summary = manifest.describe_changes(manifest.entries.keys());
     let mut messages = Vec::new();
     let mut files_so_far = 0;
 
@@ -130,19 +112,24 @@ async fn download_cities(
             warn!("Couldn't send progress: {}", err);
         }
 
-        match abstio::download_bytes(&url, None, &mut inner_progress)
+        match abstio::download_bytes_resumable(&url, &entry, &mut inner_progress)
             .await
             .and_then(|bytes| {
                 // TODO Instead of holding everything in memory like this, we could also try to
                 // stream the gunzipping and output writing
                 info!("Decompressing {}", path);
-                fs_err::create_dir_all(std::path::Path::new(&local_path).parent().unwrap())
-                    .unwrap();
-                let mut out = File::create(&local_path).unwrap();
+                fs_err::create_dir_all(std::path::Path::new(&local_path).parent().unwrap())?;
+                let mut out = File::create(&local_path)?;
                 let mut decoder = flate2::read::GzDecoder::new(&bytes[..]);
-                std::io::copy(&mut decoder, &mut out).map_err(|err| err.into())
+                std::io::copy(&mut decoder, &mut out)?;
+                // Don't leave a corrupt file behind; it'd be skipped by the next download
+                if let Err(err) = abstio::verify_file(&local_path, &entry) {
+                    fs_err::remove_file(&local_path)?;
+                    return Err(err);
+                }
+                Ok(())
             }) {
-            Ok(_) => {}
+            Ok(()) => {}
             Err(err) => {
                 let msg = format!("Problem with {}: {}", url, err);
                 error!("{}", msg);
@@ -150,6 +137,30 @@ async fn download_cities(
             }
         }
     }
-    messages.insert(0, format!("Downloaded {} files", num_files));
-    messages
+    let mut result = vec![format!("Downloaded {} files:", num_files)];
+    result.extend(summary);
+    result.extend(messages);
+    result
+}
+
+/// The runtime files for some cities that're missing or don't match the manifest, like the
+/// command-line updater decides. A file left behind by an older version would otherwise never be
+/// replaced.
+fn files_to_download(cities: Vec<String>) -> Manifest {
+    let mut data_packs = DataPacks {
+        runtime: BTreeSet::new(),
+        input: BTreeSet::new(),
+    };
+    data_packs.runtime.extend(cities);
+    let mut manifest = Manifest::load().filter(data_packs);
+    manifest.entries.retain(|path, entry| {
+        let local_path = abstio::path(path.strip_prefix("data/").unwrap());
+        // Only checksum files of the right size
+        let up_to_date = fs_err::metadata(&local_path)
+            .map(|metadata| metadata.len() == entry.uncompressed_size_bytes)
+            .unwrap_or(false)
+            && abstio::verify_file(&local_path, entry).is_ok();
+        !up_to_date
+    });
+    manifest
 }
@@ -3,9 +3,10 @@ use synthpop::TripEndpoint;
 use widgetry::mapspace::{ObjectID, World, WorldOutcome};
 use widgetry::{
     Color, ControlState, CornerRounding, DragDrop, EventCtx, GeomBatch, Image, Key, Line, Outcome,
-    RewriteColor, StackAxis, Text, Widget,
+    RewriteColor, StackAxis, State, Text, Transition, Widget,
 };
 
+use crate::tools::SearchAddress;
 use crate::AppLike;
 
 /// Click to add waypoints, drag them, see the list on a panel and delete them. The caller owns the
@@ -134,9 +135,47 @@ impl InputWaypoints {
                 ])
                 .into_widget(ctx),
             ]),
+            ctx.style()
+                .btn_outline
+                .icon_text(
+                    "system/assets/tools/search.svg",
+                    "add a waypoint by address",
+                )
+                .disabled(Some(self.waypoints.len()) == self.max_waypts)
+                .build_def(ctx),
         ])
     }
 
+    /// Returns true if the waypoint was added. The caller should call `rebuild_world` after this.
+    pub fn add(&mut self, app: &dyn AppLike, at: TripEndpoint) -> bool {
+        if Some(self.waypoints.len()) == self.max_waypts {
+            return false;
+        }
+        self.waypoints.push(Waypoint::new(app, at));
+        true
+    }
+
+    /// The panel has an "add a waypoint by address" button, which `event` doesn't handle. Callers
+    /// should push this state when it's clicked. When the player picks an address, `add` is called
+    /// on the caller's state with the new endpoint.
+    pub fn search_address_state<A: AppLike + 'static, S: State<A>>(
+        ctx: &mut EventCtx,
+        add: fn(&mut S, &mut EventCtx, &mut A, TripEndpoint),
+    ) -> Box<dyn State<A>> {
+        SearchAddress::new_state(
+            ctx,
+            Box::new(move |_, _, b| {
+                Transition::Multi(vec![
+                    Transition::Pop,
+                    Transition::ModifyState(Box::new(move |state, ctx, app| {
+                        let state = state.downcast_mut::<S>().unwrap();
+                        add(state, ctx, app, TripEndpoint::Building(b));
+                    })),
+                ])
+            }),
+        )
+    }
+
     pub fn get_waypoints(&self) -> Vec<TripEndpoint> {
         self.waypoints.iter().map(|w| w.at).collect()
     }
@@ -160,8 +199,7 @@ impl InputWaypoints {
                     return false;
                 }
                 if let Some((at, _)) = self.snap_to_endpts.closest_pt(pt, Distance::meters(30.0)) {
-                    self.waypoints.push(Waypoint::new(app, at));
-                    return true;
+                    return self.add(app, at);
                 }
                 return false;
             }
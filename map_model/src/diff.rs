@@ -0,0 +1,206 @@
+//! Compare two imports of the same map, so people can understand why simulation results shifted
+//! after updating the underlying data.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use geom::{PolyLine, Polygon, Pt2D, Ring};
+
+use crate::raw::OriginalRoad;
+use crate::{osm, Direction, IntersectionID, IntersectionType, LaneSpec, Map, RoadID};
+
+/// One difference between an old and new version of a map. IDs refer to the new map; things that
+/// only exist in the old map are identified by OSM IDs, with geometry transformed into the new
+/// map's coordinate space.
+#[derive(Clone, Debug)]
+pub enum MapChange {
+    RoadAdded(RoadID),
+    RoadRemoved(OriginalRoad, PolyLine),
+    /// The lane types or directions changed. Lanes are described left-to-right.
+    LanesChanged {
+        r: RoadID,
+        old: String,
+        new: String,
+    },
+    IntersectionAdded(IntersectionID),
+    IntersectionRemoved(osm::NodeID, Polygon),
+    ControlChanged {
+        i: IntersectionID,
+        old: IntersectionType,
+        new: IntersectionType,
+    },
+    /// Both versions have a traffic signal here, but the stages or timing differ
+    SignalChanged(IntersectionID),
+}
+
+impl fmt::Display for MapChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapChange::RoadAdded(r) => write!(f, "{} was added", r),
+            MapChange::RoadRemoved(id, _) => write!(f, "{} was removed", id),
+            MapChange::LanesChanged { r, old, new } => {
+                write!(f, "{} lanes changed from {} to {}", r, old, new)
+            }
+            MapChange::IntersectionAdded(i) => write!(f, "{} was added", i),
+            MapChange::IntersectionRemoved(id, _) => write!(f, "{} was removed", id),
+            MapChange::ControlChanged { i, old, new } => {
+                write!(f, "{} changed from {:?} to {:?}", i, old, new)
+            }
+            MapChange::SignalChanged(i) => write!(f, "{} has a different signal timing", i),
+        }
+    }
+}
+
+/// The result of `MapDiff::new`.
+#[derive(Clone, Debug, Default)]
+pub struct MapDiff {
+    pub changes: Vec<MapChange>,
+}
+
+impl MapDiff {
+    /// Match roads and intersections between two versions of a map by their OSM IDs. A road that
+    /// got split or merged in the new import shows up as removed and added.
+    pub fn new(old: &Map, new: &Map) -> MapDiff {
+        let mut changes = Vec::new();
+
+        let old_roads: HashMap<OriginalRoad, RoadID> =
+            old.all_roads().iter().map(|r| (r.orig_id, r.id)).collect();
+        let new_roads: HashMap<OriginalRoad, RoadID> =
+            new.all_roads().iter().map(|r| (r.orig_id, r.id)).collect();
+        for r in new.all_roads() {
+            if let Some(old_r) = old_roads.get(&r.orig_id) {
+                let before = describe_lanes(&old.get_r(*old_r).lane_specs());
+                let after = describe_lanes(&r.lane_specs());
+                if before != after {
+                    changes.push(MapChange::LanesChanged {
+                        r: r.id,
+                        old: before,
+                        new: after,
+                    });
+                }
+            } else {
+                changes.push(MapChange::RoadAdded(r.id));
+            }
+        }
+        for r in old.all_roads() {
+            if !new_roads.contains_key(&r.orig_id) {
+                let pts = r
+                    .center_pts
+                    .points()
+                    .iter()
+                    .map(|pt| transform(*pt, old, new))
+                    .collect();
+                changes.push(MapChange::RoadRemoved(
+                    r.orig_id,
+                    PolyLine::unchecked_new(pts),
+                ));
+            }
+        }
+
+        let old_intersections: HashMap<osm::NodeID, IntersectionID> = old
+            .all_intersections()
+            .iter()
+            .map(|i| (i.orig_id, i.id))
+            .collect();
+        let new_intersections: HashMap<osm::NodeID, IntersectionID> = new
+            .all_intersections()
+            .iter()
+            .map(|i| (i.orig_id, i.id))
+            .collect();
+        for i in new.all_intersections() {
+            if let Some(old_i) = old_intersections.get(&i.orig_id) {
+                let old_i = old.get_i(*old_i);
+                if old_i.intersection_type != i.intersection_type {
+                    changes.push(MapChange::ControlChanged {
+                        i: i.id,
+                        old: old_i.intersection_type,
+                        new: i.intersection_type,
+                    });
+                } else if let (Some(before), Some(after)) = (
+                    old.maybe_get_traffic_signal(old_i.id),
+                    new.maybe_get_traffic_signal(i.id),
+                ) {
+                    // The exported form refers to OSM IDs, so it's comparable across imports
+                    if before.export(old) != after.export(new) {
+                        changes.push(MapChange::SignalChanged(i.id));
+                    }
+                }
+            } else {
+                changes.push(MapChange::IntersectionAdded(i.id));
+            }
+        }
+        for i in old.all_intersections() {
+            if !new_intersections.contains_key(&i.orig_id) {
+                if let Some(ring) = i.polygon.get_outer_ring() {
+                    let pts = ring
+                        .into_points()
+                        .into_iter()
+                        .map(|pt| transform(pt, old, new))
+                        .collect();
+                    if let Ok(ring) = Ring::new(pts) {
+                        changes.push(MapChange::IntersectionRemoved(
+                            i.orig_id,
+                            ring.into_polygon(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        MapDiff { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// One line per change
+    pub fn describe(&self) -> Vec<String> {
+        if self.changes.is_empty() {
+            return vec!["No differences found".to_string()];
+        }
+        self.changes.iter().map(|c| c.to_string()).collect()
+    }
+
+    /// Count changes by category: roads added, roads removed, lanes changed, intersections
+    /// added, intersections removed, control changed, signals changed
+    pub fn summarize(&self) -> Vec<(&'static str, usize)> {
+        let mut counts = vec![
+            ("roads added", 0),
+            ("roads removed", 0),
+            ("roads with different lanes", 0),
+            ("intersections added", 0),
+            ("intersections removed", 0),
+            ("intersections with different control", 0),
+            ("traffic signals with different timing", 0),
+        ];
+        for change in &self.changes {
+            let idx = match change {
+                MapChange::RoadAdded(_) => 0,
+                MapChange::RoadRemoved(_, _) => 1,
+                MapChange::LanesChanged { .. } => 2,
+                MapChange::IntersectionAdded(_) => 3,
+                MapChange::IntersectionRemoved(_, _) => 4,
+                MapChange::ControlChanged { .. } => 5,
+                MapChange::SignalChanged(_) => 6,
+            };
+            counts[idx].1 += 1;
+        }
+        counts
+    }
+}
+
+/// Like "sdds (vv^^)"
+fn describe_lanes(specs: &[LaneSpec]) -> String {
+    let types: String = specs.iter().map(|spec| spec.lt.to_char()).collect();
+    let dirs: String = specs
+        .iter()
+        .map(|spec| if spec.dir == Direction::Fwd { '^' } else { 'v' })
+        .collect();
+    format!("{} ({})", types, dirs)
+}
+
+/// The two maps might have slightly different bounds, so go through GPS.
+fn transform(pt: Pt2D, old: &Map, new: &Map) -> Pt2D {
+    pt.to_gps(old.get_gps_bounds()).to_pt(new.get_gps_bounds())
+}
@@ -111,6 +111,41 @@ pub fn upgrade(mut value: Value, map: &Map) -> Result<PermanentMapEdits> {
             .unwrap()
             .insert("version".to_string(), Value::Number(11.into()));
     }
+    if value["version"] == Value::Number(11.into()) {
+        fix_charging_cordons(&mut value);
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("version".to_string(), Value::Number(12.into()));
+    }
+    if value["version"] == Value::Number(12.into()) {
+        fix_edit_notes(&mut value);
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("version".to_string(), Value::Number(13.into()));
+    }
+    if value["version"] == Value::Number(13.into()) {
+        fix_permit_zones(&mut value);
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("version".to_string(), Value::Number(14.into()));
+    }
+    if value["version"] == Value::Number(14.into()) {
+        fix_restricted_zones(&mut value);
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("version".to_string(), Value::Number(15.into()));
+    }
+    if value["version"] == Value::Number(15.into()) {
+        fix_turn_ban_ids(&mut value, map);
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("version".to_string(), Value::Number(16.into()));
+    }
 
     abstutil::from_json(&value.to_string().into_bytes())
 }
@@ -393,6 +428,7 @@ fn fix_lane_widths(value: &mut Value, map: &Map) -> Result<()> {
                         // Before this commit, lane widths weren't modifiable, so this lookup works
                         // for both "old" and "new".
                         width: road.lanes[idx].width,
+                        pocket: None,
                     });
                 }
                 cmd[key]["lanes_ltr"] = serde_json::to_value(lanes_ltr).unwrap();
@@ -442,6 +478,75 @@ fn remove_vehicle_caps(value: &mut Value) {
     });
 }
 
+// Charging cordons were added as a map-wide field
+fn fix_charging_cordons(value: &mut Value) {
+    let obj = value.as_object_mut().unwrap();
+    if !obj.contains_key("charging_cordons") {
+        obj.insert("charging_cordons".to_string(), Value::Array(Vec::new()));
+    }
+}
+
+// Resident permit zones were added as a map-wide field
+fn fix_permit_zones(value: &mut Value) {
+    let obj = value.as_object_mut().unwrap();
+    if !obj.contains_key("permit_zones") {
+        obj.insert("permit_zones".to_string(), Value::Array(Vec::new()));
+    }
+}
+
+// Restricted zones were added as a map-wide field
+fn fix_restricted_zones(value: &mut Value) {
+    let obj = value.as_object_mut().unwrap();
+    if !obj.contains_key("restricted_zones") {
+        obj.insert("restricted_zones".to_string(), Value::Array(Vec::new()));
+    }
+}
+
+// Notes explaining edits were added as map-wide fields
+fn fix_edit_notes(value: &mut Value) {
+    let obj = value.as_object_mut().unwrap();
+    for key in ["road_notes", "intersection_notes"] {
+        if !obj.contains_key(key) {
+            obj.insert(key.to_string(), Value::Array(Vec::new()));
+        }
+    }
+}
+
+// Time-of-day turn bans in ChangeRoad referred to the destination by RoadID. Translate to
+// OriginalRoad, dropping bans onto roads that don't exist anymore.
+fn fix_turn_ban_ids(value: &mut Value, map: &Map) {
+    walk(value, &|obj| {
+        if let Some(Value::Array(turn_bans)) = obj.get_mut("turn_bans") {
+            let mut fixed = Vec::new();
+            for ban in turn_bans.drain(..) {
+                // fix_old_lane_cmds may have already written the new format
+                let r = match ban[0].as_u64() {
+                    Some(r) => RoadID(r as usize),
+                    None => {
+                        fixed.push(ban);
+                        continue;
+                    }
+                };
+                match map.maybe_get_r(r) {
+                    Some(road) => {
+                        fixed.push(Value::Array(vec![
+                            serde_json::to_value(road.orig_id).unwrap(),
+                            ban[1].clone(),
+                        ]));
+                    }
+                    None => {
+                        warn!("Dropping a turn ban onto {}, which doesn't exist", r);
+                    }
+                }
+            }
+            *turn_bans = fixed;
+            true
+        } else {
+            false
+        }
+    });
+}
+
 // These're old structs used in fix_old_lane_cmds.
 #[derive(Debug, Deserialize)]
 struct OriginalLane {
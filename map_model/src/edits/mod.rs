@@ -11,12 +11,14 @@ use abstutil::Timer;
 use geom::{Distance, HashablePt2D, Line, Speed, Time};
 use raw_map::{get_lane_specs_ltr, initial};
 
-pub use self::perma::PermanentMapEdits;
+pub use self::perma::{PermanentEditCmd, PermanentMapEdits};
 use crate::make::{match_points_to_lanes, snap_driveway, trim_path};
 use crate::{
-    connectivity, AccessRestrictions, BuildingID, ControlStopSign, ControlTrafficSignal, Direction,
-    IntersectionID, IntersectionType, LaneID, LaneSpec, LaneType, Map, MapConfig, Movement,
-    ParkingLotID, PathConstraints, Pathfinder, Road, RoadID, TransitRouteID, TurnID, Zone,
+    connectivity, osm, AccessRestrictions, BuildingID, ChargingCordon, ControlStopSign,
+    ControlTrafficSignal, CurbUses, Direction, IntersectionID, IntersectionType, LaneID, LaneSpec,
+    LaneType, Map, MapConfig, Movement, ParkingLotID, ParkingRestrictions, PathConstraints,
+    Pathfinder, PermitZone, RestrictedZone, Road, RoadID, TimeRestrictions, TransitRouteID, TurnID,
+    Zone,
 };
 
 mod compat;
@@ -34,11 +36,24 @@ pub struct MapEdits {
     /// Zone; every Road will be its own Zone. This is used to experiment with a per-road cap. Note
     /// this is a map-wide setting.
     pub merge_zones: bool,
+    /// Congestion pricing schemes. Like merge_zones, these aren't part of the command stack.
+    pub charging_cordons: Vec<ChargingCordon>,
+    /// Resident permit parking. These also aren't part of the command stack.
+    pub permit_zones: Vec<PermitZone>,
+    /// School streets, pedestrian zones, and other areas closed to most vehicles. Also not part
+    /// of the command stack.
+    pub restricted_zones: Vec<RestrictedZone>,
+    /// Why some roads and intersections were changed. These also aren't part of the command stack,
+    /// so undoing a change keeps its note.
+    pub road_notes: BTreeMap<RoadID, EditNote>,
+    pub intersection_notes: BTreeMap<IntersectionID, EditNote>,
 
     /// Derived from commands, kept up to date by update_derived
     pub changed_roads: BTreeSet<RoadID>,
     pub original_intersections: BTreeMap<IntersectionID, EditIntersection>,
     pub changed_routes: BTreeSet<TransitRouteID>,
+    pub changed_corner_radii: BTreeSet<IntersectionID>,
+    pub protected_intersections: BTreeSet<IntersectionID>,
 
     /// Some edits are included in the game by default, in data/system/proposals, as "community
     /// proposals." They require a description and may have a link to a write-up.
@@ -46,6 +61,15 @@ pub struct MapEdits {
     pub proposal_link: Option<String>,
 }
 
+/// A designer's rationale for changing something, so a proposal carries it along for others
+/// reviewing it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditNote {
+    pub text: String,
+    /// Paths or URLs of photos or sketches. Only the references are stored, not the images.
+    pub images: Vec<String>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum EditIntersection {
     StopSign(ControlStopSign),
@@ -60,14 +84,31 @@ pub struct EditRoad {
     pub lanes_ltr: Vec<LaneSpec>,
     pub speed_limit: Speed,
     pub access_restrictions: AccessRestrictions,
+    #[serde(default)]
+    pub parking_restrictions: ParkingRestrictions,
+    #[serde(default)]
+    pub time_restrictions: TimeRestrictions,
+    #[serde(default)]
+    pub curb: CurbUses,
+    /// Corrects the name from OSM
+    #[serde(default)]
+    pub name_override: Option<String>,
+    /// Corrects the classification from OSM
+    #[serde(default)]
+    pub rank_override: Option<osm::RoadRank>,
 }
 
 impl EditRoad {
     pub fn get_orig_from_osm(r: &Road, cfg: &MapConfig) -> EditRoad {
         EditRoad {
             lanes_ltr: get_lane_specs_ltr(&r.osm_tags, cfg),
-            speed_limit: r.speed_limit_from_osm(),
+            speed_limit: r.speed_limit_from_osm(cfg),
             access_restrictions: r.access_restrictions_from_osm(),
+            parking_restrictions: r.parking_restrictions_from_osm(),
+            time_restrictions: r.time_restrictions_from_osm(),
+            curb: CurbUses::new(),
+            name_override: None,
+            rank_override: None,
         }
     }
 
@@ -76,6 +117,7 @@ impl EditRoad {
         let mut lt = 0;
         let mut dir = 0;
         let mut width = 0;
+        let mut pocket = 0;
         for (spec1, spec2) in self.lanes_ltr.iter().zip(other.lanes_ltr.iter()) {
             if spec1.lt != spec2.lt {
                 lt += 1;
@@ -86,6 +128,9 @@ impl EditRoad {
             if spec1.width != spec2.width {
                 width += 1;
             }
+            if spec1.pocket != spec2.pocket {
+                pocket += 1;
+            }
         }
 
         let mut changes = Vec::new();
@@ -104,12 +149,32 @@ impl EditRoad {
         } else {
             changes.push(format!("{} lane widths", width));
         }
+        if pocket == 1 {
+            changes.push("1 turn pocket".to_string());
+        } else if pocket > 1 {
+            changes.push(format!("{} turn pockets", pocket));
+        }
         if self.speed_limit != other.speed_limit {
             changes.push("speed limit".to_string());
         }
         if self.access_restrictions != other.access_restrictions {
             changes.push("access restrictions".to_string());
         }
+        if self.parking_restrictions != other.parking_restrictions {
+            changes.push("parking restrictions".to_string());
+        }
+        if self.time_restrictions != other.time_restrictions {
+            changes.push("time-of-day restrictions".to_string());
+        }
+        if self.curb != other.curb {
+            changes.push("curb use".to_string());
+        }
+        if self.name_override != other.name_override {
+            changes.push("name".to_string());
+        }
+        if self.rank_override != other.rank_override {
+            changes.push("classification".to_string());
+        }
         changes
     }
 
@@ -130,10 +195,16 @@ impl EditRoad {
                     },
                     // Dummy
                     width: Distance::ZERO,
+                    pocket: None,
                 })
                 .collect(),
             speed_limit: Speed::ZERO,
             access_restrictions: AccessRestrictions::new(),
+            parking_restrictions: ParkingRestrictions::new(),
+            time_restrictions: TimeRestrictions::new(),
+            curb: CurbUses::new(),
+            name_override: None,
+            rank_override: None,
         }
     }
 
@@ -189,6 +260,16 @@ pub enum EditCmd {
         old: Vec<Time>,
         new: Vec<Time>,
     },
+    ChangeCornerRadius {
+        i: IntersectionID,
+        old: Option<Distance>,
+        new: Option<Distance>,
+    },
+    ChangeBikeProtection {
+        i: IntersectionID,
+        old: bool,
+        new: bool,
+    },
 }
 
 pub struct EditEffects {
@@ -209,10 +290,17 @@ impl MapEdits {
             proposal_link: None,
             commands: Vec::new(),
             merge_zones: true,
+            charging_cordons: Vec::new(),
+            permit_zones: Vec::new(),
+            restricted_zones: Vec::new(),
+            road_notes: BTreeMap::new(),
+            intersection_notes: BTreeMap::new(),
 
             changed_roads: BTreeSet::new(),
             original_intersections: BTreeMap::new(),
             changed_routes: BTreeSet::new(),
+            changed_corner_radii: BTreeSet::new(),
+            protected_intersections: BTreeSet::new(),
         }
     }
 
@@ -231,7 +319,7 @@ impl MapEdits {
             }
         };
         let edits = perma.into_edits_permissive(map);
-        if edits.commands.is_empty() {
+        if edits.is_empty() {
             bail!("None of the edits apply to this map");
         }
         Ok(edits)
@@ -251,15 +339,30 @@ impl MapEdits {
             }
         };
         let edits = perma.into_edits_permissive(map);
-        if edits.commands.is_empty() {
+        if edits.is_empty() {
             bail!("None of the edits apply to this map");
         }
         Ok(edits)
     }
 
+    /// True if there are no commands, charging cordons, permit zones, restricted zones, or notes.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+            && self.charging_cordons.is_empty()
+            && self.permit_zones.is_empty()
+            && self.restricted_zones.is_empty()
+            && self.road_notes.is_empty()
+            && self.intersection_notes.is_empty()
+    }
+
+    /// The first resident permit zone covering this road, if any
+    pub fn permit_zone_for_road(&self, r: RoadID) -> Option<&PermitZone> {
+        self.permit_zones.iter().find(|z| z.roads.contains(&r))
+    }
+
     fn save(&self, map: &Map) {
         // If untitled and empty, don't actually save anything.
-        if self.edits_name.starts_with("Untitled Proposal") && self.commands.is_empty() {
+        if self.edits_name.starts_with("Untitled Proposal") && self.is_empty() {
             return;
         }
 
@@ -273,6 +376,8 @@ impl MapEdits {
         self.changed_roads.clear();
         self.original_intersections.clear();
         self.changed_routes.clear();
+        self.changed_corner_radii.clear();
+        self.protected_intersections.clear();
 
         for cmd in &self.commands {
             match cmd {
@@ -287,6 +392,12 @@ impl MapEdits {
                 EditCmd::ChangeRouteSchedule { id, .. } => {
                     self.changed_routes.insert(*id);
                 }
+                EditCmd::ChangeCornerRadius { i, .. } => {
+                    self.changed_corner_radii.insert(*i);
+                }
+                EditCmd::ChangeBikeProtection { i, .. } => {
+                    self.protected_intersections.insert(*i);
+                }
             }
         }
 
@@ -299,6 +410,10 @@ impl MapEdits {
             let r = map.get_tr(*br);
             r.spawn_times != r.orig_spawn_times
         });
+        self.changed_corner_radii
+            .retain(|i| map.get_i(*i).corner_radius.is_some());
+        self.protected_intersections
+            .retain(|i| map.get_i(*i).protected_for_bikes);
     }
 
     /// Assumes update_derived has been called.
@@ -325,6 +440,20 @@ impl MapEdits {
                 old: r.orig_spawn_times.clone(),
             });
         }
+        for i in &self.changed_corner_radii {
+            self.commands.push(EditCmd::ChangeCornerRadius {
+                i: *i,
+                old: None,
+                new: map.get_i(*i).corner_radius,
+            });
+        }
+        for i in &self.protected_intersections {
+            self.commands.push(EditCmd::ChangeBikeProtection {
+                i: *i,
+                old: false,
+                new: true,
+            });
+        }
     }
 
     /// Pick apart changed_roads and figure out if an entire road was edited, or just a few lanes.
@@ -338,6 +467,11 @@ impl MapEdits {
             // What exactly changed?
             if r.speed_limit != orig.speed_limit
                 || r.access_restrictions != orig.access_restrictions
+                || r.parking_restrictions != orig.parking_restrictions
+                || r.time_restrictions != orig.time_restrictions
+                || r.curb != orig.curb
+                || r.name_override != orig.name_override
+                || r.rank_override != orig.rank_override
                 // If a lane was added or deleted, figuring out if any were modified is kind of
                 // unclear -- just mark the entire road.
                 || r.lanes.len() != orig.lanes_ltr.len()
@@ -397,10 +531,59 @@ impl EditCmd {
             EditCmd::ChangeRouteSchedule { id, .. } => {
                 format!("reschedule route {}", map.get_tr(*id).short_name)
             }
+            EditCmd::ChangeCornerRadius { i, new, .. } => {
+                if let Some(radius) = new {
+                    details.push(format!("corner radius {}", radius));
+                }
+                format!("corners of {}", i)
+            }
+            EditCmd::ChangeBikeProtection { i, new, .. } => {
+                if *new {
+                    format!("protect {} for biking", i)
+                } else {
+                    format!("remove bike protection from {}", i)
+                }
+            }
         };
         (summary, details)
     }
 
+    /// Make sure the `old` state recorded in this command matches the map right now. Commands
+    /// built against a different version of the map would otherwise silently clobber changes.
+    pub fn check_current(&self, map: &Map) -> Result<()> {
+        match self {
+            EditCmd::ChangeRoad { r, old, .. } => {
+                if map.get_r_edit(*r) != *old {
+                    bail!("the old state of {} doesn't match the map", r);
+                }
+            }
+            EditCmd::ChangeIntersection { i, old, .. } => {
+                if map.get_i(*i).is_border() {
+                    bail!("{} is a border and can't be edited", i);
+                }
+                if map.get_i_edit(*i) != *old {
+                    bail!("the old state of {} doesn't match the map", i);
+                }
+            }
+            EditCmd::ChangeRouteSchedule { id, old, .. } => {
+                if map.get_tr(*id).spawn_times != *old {
+                    bail!("the old schedule of {} doesn't match the map", id);
+                }
+            }
+            EditCmd::ChangeCornerRadius { i, old, .. } => {
+                if map.get_i(*i).corner_radius != *old {
+                    bail!("the old corner radius of {} doesn't match the map", i);
+                }
+            }
+            EditCmd::ChangeBikeProtection { i, old, .. } => {
+                if map.get_i(*i).protected_for_bikes != *old {
+                    bail!("{} bike protection is already {}", i, !*old);
+                }
+            }
+        }
+        Ok(())
+    }
+
     // Must be idempotent
     fn apply(&self, effects: &mut EditEffects, map: &mut Map) {
         match self {
@@ -413,6 +596,11 @@ impl EditCmd {
                 let road = &mut map.roads[r.0];
                 road.speed_limit = new.speed_limit;
                 road.access_restrictions = new.access_restrictions.clone();
+                road.parking_restrictions = new.parking_restrictions.clone();
+                road.time_restrictions = new.time_restrictions.clone();
+                road.curb = new.curb.clone();
+                road.name_override = new.name_override.clone();
+                road.rank_override = new.rank_override;
 
                 effects.changed_roads.insert(road.id);
                 for i in [road.src_i, road.dst_i] {
@@ -473,6 +661,12 @@ impl EditCmd {
             EditCmd::ChangeRouteSchedule { id, new, .. } => {
                 map.transit_routes[id.0].spawn_times = new.clone();
             }
+            EditCmd::ChangeCornerRadius { i, new, .. } => {
+                map.intersections[i.0].corner_radius = *new;
+            }
+            EditCmd::ChangeBikeProtection { i, new, .. } => {
+                map.intersections[i.0].protected_for_bikes = *new;
+            }
         }
     }
 
@@ -493,6 +687,16 @@ impl EditCmd {
                 old: new,
                 new: old,
             },
+            EditCmd::ChangeCornerRadius { i, old, new } => EditCmd::ChangeCornerRadius {
+                i,
+                old: new,
+                new: old,
+            },
+            EditCmd::ChangeBikeProtection { i, old, new } => EditCmd::ChangeBikeProtection {
+                i,
+                old: new,
+                new: old,
+            },
         }
     }
 }
@@ -773,7 +977,7 @@ impl Map {
     }
 
     pub fn unsaved_edits(&self) -> bool {
-        self.edits.edits_name.starts_with("Untitled Proposal") && !self.edits.commands.is_empty()
+        self.edits.edits_name.starts_with("Untitled Proposal") && !self.edits.is_empty()
     }
 
     pub fn get_r_edit(&self, r: RoadID) -> EditRoad {
@@ -782,6 +986,11 @@ impl Map {
             lanes_ltr: r.lane_specs(),
             speed_limit: r.speed_limit,
             access_restrictions: r.access_restrictions.clone(),
+            parking_restrictions: r.parking_restrictions.clone(),
+            time_restrictions: r.time_restrictions.clone(),
+            curb: r.curb.clone(),
+            name_override: r.name_override.clone(),
+            rank_override: r.rank_override,
         }
     }
 
@@ -948,6 +1157,7 @@ impl Map {
             .extend(more_changed_intersections);
 
         self.recalculate_road_to_buildings();
+        self.recalculate_roads_with_time_restrictions();
 
         effects
     }
@@ -955,12 +1165,38 @@ impl Map {
     /// This can expensive, so don't constantly do it while editing in the UI. But this must happen
     /// before the simulation resumes.
     pub fn recalculate_pathfinding_after_edits(&mut self, timer: &mut Timer) {
+        self.recalculate_pathfinding(false, timer);
+    }
+
+    /// Like `recalculate_pathfinding_after_edits`, but for edits loaded from a saved proposal,
+    /// which are likely to be loaded again later. The prepared pathfinder is cached on disk.
+    pub fn recalculate_pathfinding_after_loading_proposal(&mut self, timer: &mut Timer) {
+        self.recalculate_pathfinding(true, timer);
+    }
+
+    fn recalculate_pathfinding(&mut self, use_disk_cache: bool, timer: &mut Timer) {
         if !self.pathfinder_dirty {
             return;
         }
 
         let mut pathfinder = std::mem::replace(&mut self.pathfinder, Pathfinder::empty());
-        pathfinder.apply_edits(self, timer);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if !use_disk_cache {
+                pathfinder.apply_edits(self, timer);
+            } else if let Some(cached) = crate::pathfind::disk_cache::load(self, timer) {
+                pathfinder = cached;
+            } else {
+                pathfinder.apply_edits(self, timer);
+                crate::pathfind::disk_cache::save(self, &pathfinder);
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            // There's no disk to cache on
+            let _ = use_disk_cache;
+            pathfinder.apply_edits(self, timer);
+        }
         self.pathfinder = pathfinder;
 
         // Also recompute blackholes. This is cheap enough to do from scratch.
@@ -5,11 +5,16 @@ use serde::{Deserialize, Serialize};
 
 use abstio::MapName;
 use abstutil::{deserialize_btreemap, serialize_btreemap};
-use geom::Time;
+use enumset::EnumSet;
+use geom::{Distance, LonLat, Ring, Speed, Time};
 
-use crate::edits::{EditCmd, EditIntersection, EditRoad, MapEdits};
+use crate::edits::{EditCmd, EditIntersection, EditNote, EditRoad, MapEdits};
 use crate::raw::OriginalRoad;
-use crate::{osm, ControlStopSign, IntersectionID, Map};
+use crate::{
+    osm, AccessRestrictions, BuildingID, ChargingCordon, ControlStopSign, CurbUses, IntersectionID,
+    LaneSpec, Map, ParkingRestrictions, PathConstraints, PermitZone, RestrictedZone,
+    TimeRestrictions, TimeWindow,
+};
 
 /// MapEdits are converted to this before serializing. Referencing things like LaneID in a Map won't
 /// work if the basemap is rebuilt from new OSM data, so instead we use stabler OSM IDs that're less
@@ -24,6 +29,19 @@ pub struct PermanentMapEdits {
     /// Zone; every Road will be its own Zone. This is used to experiment with a per-road cap. Note
     /// this is a map-wide setting.
     merge_zones: bool,
+    charging_cordons: Vec<PermanentChargingCordon>,
+    permit_zones: Vec<PermanentPermitZone>,
+    restricted_zones: Vec<PermanentRestrictedZone>,
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    road_notes: BTreeMap<OriginalRoad, EditNote>,
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    intersection_notes: BTreeMap<osm::NodeID, EditNote>,
 
     /// Edits without these are player generated.
     pub proposal_description: Vec<String>,
@@ -31,6 +49,59 @@ pub struct PermanentMapEdits {
     pub proposal_link: Option<String>,
 }
 
+/// A ChargingCordon with the boundary in GPS coordinates, so it survives the basemap changing.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PermanentChargingCordon {
+    name: String,
+    boundary: Vec<LonLat>,
+    price: f64,
+    start_time: Time,
+    end_time: Time,
+}
+
+/// A PermitZone referring to OSM IDs.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PermanentPermitZone {
+    name: String,
+    roads: Vec<OriginalRoad>,
+    buildings: Vec<osm::OsmID>,
+}
+
+/// A RestrictedZone with the boundary in GPS coordinates, like PermanentChargingCordon.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PermanentRestrictedZone {
+    name: String,
+    boundary: Vec<LonLat>,
+    exemptions: EnumSet<PathConstraints>,
+    windows: Vec<TimeWindow>,
+}
+
+/// An EditRoad whose time-of-day turn bans refer to the destination road by OSM IDs.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PermanentEditRoad {
+    lanes_ltr: Vec<LaneSpec>,
+    speed_limit: Speed,
+    access_restrictions: AccessRestrictions,
+    #[serde(default)]
+    parking_restrictions: ParkingRestrictions,
+    #[serde(default)]
+    time_restrictions: PermanentTimeRestrictions,
+    #[serde(default)]
+    curb: CurbUses,
+    #[serde(default)]
+    name_override: Option<String>,
+    #[serde(default)]
+    rank_override: Option<osm::RoadRank>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct PermanentTimeRestrictions {
+    bus_only: Vec<TimeWindow>,
+    turn_bans: Vec<(OriginalRoad, TimeWindow)>,
+    #[serde(default)]
+    closed: Vec<TimeWindow>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub enum PermanentEditIntersection {
     StopSign {
@@ -49,8 +120,8 @@ pub enum PermanentEditIntersection {
 pub enum PermanentEditCmd {
     ChangeRoad {
         r: OriginalRoad,
-        new: EditRoad,
-        old: EditRoad,
+        new: PermanentEditRoad,
+        old: PermanentEditRoad,
     },
     ChangeIntersection {
         i: osm::NodeID,
@@ -62,6 +133,16 @@ pub enum PermanentEditCmd {
         old: Vec<Time>,
         new: Vec<Time>,
     },
+    ChangeCornerRadius {
+        i: osm::NodeID,
+        old: Option<Distance>,
+        new: Option<Distance>,
+    },
+    ChangeBikeProtection {
+        i: osm::NodeID,
+        old: bool,
+        new: bool,
+    },
 }
 
 impl EditCmd {
@@ -69,8 +150,8 @@ impl EditCmd {
         match self {
             EditCmd::ChangeRoad { r, new, old } => PermanentEditCmd::ChangeRoad {
                 r: map.get_r(*r).orig_id,
-                new: new.clone(),
-                old: old.clone(),
+                new: new.to_permanent(map),
+                old: old.to_permanent(map),
             },
             EditCmd::ChangeIntersection { i, new, old } => PermanentEditCmd::ChangeIntersection {
                 i: map.get_i(*i).orig_id,
@@ -84,6 +165,18 @@ impl EditCmd {
                     new: new.clone(),
                 }
             }
+            EditCmd::ChangeCornerRadius { i, old, new } => PermanentEditCmd::ChangeCornerRadius {
+                i: map.get_i(*i).orig_id,
+                old: *old,
+                new: *new,
+            },
+            EditCmd::ChangeBikeProtection { i, old, new } => {
+                PermanentEditCmd::ChangeBikeProtection {
+                    i: map.get_i(*i).orig_id,
+                    old: *old,
+                    new: *new,
+                }
+            }
         }
     }
 }
@@ -104,7 +197,15 @@ impl PermanentEditCmd {
                         old.lanes_ltr.len()
                     );
                 }
-                Ok(EditCmd::ChangeRoad { r: id, new, old })
+                Ok(EditCmd::ChangeRoad {
+                    r: id,
+                    new: new
+                        .with_permanent(map)
+                        .with_context(|| format!("new ChangeRoad of {} invalid", r))?,
+                    old: old
+                        .with_permanent(map)
+                        .with_context(|| format!("old ChangeRoad of {} invalid", r))?,
+                })
             }
             PermanentEditCmd::ChangeIntersection { i, new, old } => {
                 let id = map.find_i_by_osm_id(i)?;
@@ -124,6 +225,14 @@ impl PermanentEditCmd {
                     .ok_or_else(|| anyhow!("can't find {}", gtfs_id))?;
                 Ok(EditCmd::ChangeRouteSchedule { id, old, new })
             }
+            PermanentEditCmd::ChangeCornerRadius { i, old, new } => {
+                let id = map.find_i_by_osm_id(i)?;
+                Ok(EditCmd::ChangeCornerRadius { i: id, old, new })
+            }
+            PermanentEditCmd::ChangeBikeProtection { i, old, new } => {
+                let id = map.find_i_by_osm_id(i)?;
+                Ok(EditCmd::ChangeBikeProtection { i: id, old, new })
+            }
         }
     }
 }
@@ -135,11 +244,51 @@ impl MapEdits {
             map_name: map.get_name().clone(),
             edits_name: self.edits_name.clone(),
             // Increase this every time there's a schema change
-            version: 11,
+            version: 16,
             proposal_description: self.proposal_description.clone(),
             proposal_link: self.proposal_link.clone(),
             commands: self.commands.iter().map(|cmd| cmd.to_perma(map)).collect(),
             merge_zones: self.merge_zones,
+            charging_cordons: self
+                .charging_cordons
+                .iter()
+                .map(|c| PermanentChargingCordon {
+                    name: c.name.clone(),
+                    boundary: c.boundary_gps(map),
+                    price: c.price,
+                    start_time: c.start_time,
+                    end_time: c.end_time,
+                })
+                .collect(),
+            permit_zones: self
+                .permit_zones
+                .iter()
+                .map(|z| PermanentPermitZone {
+                    name: z.name.clone(),
+                    roads: z.roads.iter().map(|r| map.get_r(*r).orig_id).collect(),
+                    buildings: z.buildings.iter().map(|b| map.get_b(*b).orig_id).collect(),
+                })
+                .collect(),
+            restricted_zones: self
+                .restricted_zones
+                .iter()
+                .map(|z| PermanentRestrictedZone {
+                    name: z.name.clone(),
+                    boundary: z.boundary_gps(map),
+                    exemptions: z.exemptions,
+                    windows: z.windows.clone(),
+                })
+                .collect(),
+            road_notes: self
+                .road_notes
+                .iter()
+                .map(|(r, note)| (map.get_r(*r).orig_id, note.clone()))
+                .collect(),
+            intersection_notes: self
+                .intersection_notes
+                .iter()
+                .map(|(i, note)| (map.get_i(*i).orig_id, note.clone()))
+                .collect(),
         }
     }
 }
@@ -158,15 +307,53 @@ impl PermanentMapEdits {
                 .map(|cmd| cmd.into_cmd(map))
                 .collect::<Result<Vec<EditCmd>>>()?,
             merge_zones: self.merge_zones,
+            charging_cordons: self
+                .charging_cordons
+                .into_iter()
+                .map(|c| c.into_cordon(map))
+                .collect::<Result<Vec<_>>>()?,
+            permit_zones: self
+                .permit_zones
+                .into_iter()
+                .map(|z| z.into_permit_zone(map))
+                .collect::<Result<Vec<_>>>()?,
+            restricted_zones: self
+                .restricted_zones
+                .into_iter()
+                .map(|z| z.into_restricted_zone(map))
+                .collect::<Result<Vec<_>>>()?,
+            road_notes: self
+                .road_notes
+                .into_iter()
+                .map(|(r, note)| Ok((map.find_r_by_osm_id(r)?, note)))
+                .collect::<Result<BTreeMap<_, _>>>()?,
+            intersection_notes: self
+                .intersection_notes
+                .into_iter()
+                .map(|(i, note)| Ok((map.find_i_by_osm_id(i)?, note)))
+                .collect::<Result<BTreeMap<_, _>>>()?,
 
             changed_roads: BTreeSet::new(),
             original_intersections: BTreeMap::new(),
             changed_routes: BTreeSet::new(),
+            changed_corner_radii: BTreeSet::new(),
+            protected_intersections: BTreeSet::new(),
         };
         edits.update_derived(map);
         Ok(edits)
     }
 
+    /// Like `into_edits`, but leave out the commands and return them separately, so they can be
+    /// checked and applied one at a time.
+    pub fn into_edits_and_commands(
+        mut self,
+        map: &Map,
+    ) -> Result<(MapEdits, Vec<PermanentEditCmd>)> {
+        let commands = std::mem::take(&mut self.commands);
+        let edits = self.into_edits(map)?;
+        Ok((edits, commands))
+    }
+
     /// Transform permanent edits to MapEdits, looking up the map IDs by the hopefully stabler OSM
     /// IDs. Strip out commands that're broken, but log warnings.
     pub fn into_edits_permissive(self, map: &Map) -> MapEdits {
@@ -186,10 +373,67 @@ impl PermanentMapEdits {
                 })
                 .collect(),
             merge_zones: self.merge_zones,
+            charging_cordons: self
+                .charging_cordons
+                .into_iter()
+                .filter_map(|c| match c.into_cordon(map) {
+                    Ok(c) => Some(c),
+                    Err(err) => {
+                        warn!("Skipping broken charging cordon: {}", err);
+                        None
+                    }
+                })
+                .collect(),
+            permit_zones: self
+                .permit_zones
+                .into_iter()
+                .filter_map(|z| match z.into_permit_zone(map) {
+                    Ok(z) => Some(z),
+                    Err(err) => {
+                        warn!("Skipping broken permit zone: {}", err);
+                        None
+                    }
+                })
+                .collect(),
+            restricted_zones: self
+                .restricted_zones
+                .into_iter()
+                .filter_map(|z| match z.into_restricted_zone(map) {
+                    Ok(z) => Some(z),
+                    Err(err) => {
+                        warn!("Skipping broken restricted zone: {}", err);
+                        None
+                    }
+                })
+                .collect(),
+            road_notes: self
+                .road_notes
+                .into_iter()
+                .filter_map(|(r, note)| match map.find_r_by_osm_id(r) {
+                    Ok(r) => Some((r, note)),
+                    Err(err) => {
+                        warn!("Skipping note on a missing road: {}", err);
+                        None
+                    }
+                })
+                .collect(),
+            intersection_notes: self
+                .intersection_notes
+                .into_iter()
+                .filter_map(|(i, note)| match map.find_i_by_osm_id(i) {
+                    Ok(i) => Some((i, note)),
+                    Err(err) => {
+                        warn!("Skipping note on a missing intersection: {}", err);
+                        None
+                    }
+                })
+                .collect(),
 
             changed_roads: BTreeSet::new(),
             original_intersections: BTreeMap::new(),
             changed_routes: BTreeSet::new(),
+            changed_corner_radii: BTreeSet::new(),
+            protected_intersections: BTreeSet::new(),
         };
         edits.update_derived(map);
         edits
@@ -206,6 +450,64 @@ impl PermanentMapEdits {
     }
 }
 
+impl PermanentChargingCordon {
+    fn into_cordon(self, map: &Map) -> Result<ChargingCordon> {
+        let boundary = Ring::new(map.get_gps_bounds().convert(&self.boundary))
+            .with_context(|| format!("boundary of charging cordon {} invalid", self.name))?
+            .into_polygon();
+        Ok(ChargingCordon::new(
+            map,
+            self.name,
+            boundary,
+            self.price,
+            self.start_time,
+            self.end_time,
+        ))
+    }
+}
+
+impl PermanentRestrictedZone {
+    fn into_restricted_zone(self, map: &Map) -> Result<RestrictedZone> {
+        let boundary = Ring::new(map.get_gps_bounds().convert(&self.boundary))
+            .with_context(|| format!("boundary of restricted zone {} invalid", self.name))?
+            .into_polygon();
+        Ok(RestrictedZone::new(
+            map,
+            self.name,
+            boundary,
+            self.exemptions,
+            self.windows,
+        ))
+    }
+}
+
+impl PermanentPermitZone {
+    fn into_permit_zone(self, map: &Map) -> Result<PermitZone> {
+        let roads = self
+            .roads
+            .into_iter()
+            .map(|r| map.find_r_by_osm_id(r))
+            .collect::<Result<BTreeSet<_>>>()
+            .with_context(|| format!("permit zone {} has a missing road", self.name))?;
+        // Buildings are often re-imported, so skip ones that've vanished instead of failing
+        let lookup: BTreeMap<osm::OsmID, BuildingID> = map
+            .all_buildings()
+            .iter()
+            .map(|b| (b.orig_id, b.id))
+            .collect();
+        let buildings = self
+            .buildings
+            .into_iter()
+            .filter_map(|id| lookup.get(&id).cloned())
+            .collect();
+        Ok(PermitZone {
+            name: self.name,
+            roads,
+            buildings,
+        })
+    }
+}
+
 impl EditIntersection {
     fn to_permanent(&self, map: &Map) -> PermanentEditIntersection {
         match self {
@@ -257,3 +559,50 @@ impl PermanentEditIntersection {
         }
     }
 }
+
+impl EditRoad {
+    fn to_permanent(&self, map: &Map) -> PermanentEditRoad {
+        PermanentEditRoad {
+            lanes_ltr: self.lanes_ltr.clone(),
+            speed_limit: self.speed_limit,
+            access_restrictions: self.access_restrictions.clone(),
+            parking_restrictions: self.parking_restrictions.clone(),
+            time_restrictions: PermanentTimeRestrictions {
+                bus_only: self.time_restrictions.bus_only.clone(),
+                turn_bans: self
+                    .time_restrictions
+                    .turn_bans
+                    .iter()
+                    .map(|(r, w)| (map.get_r(*r).orig_id, *w))
+                    .collect(),
+                closed: self.time_restrictions.closed.clone(),
+            },
+            curb: self.curb.clone(),
+            name_override: self.name_override.clone(),
+            rank_override: self.rank_override,
+        }
+    }
+}
+
+impl PermanentEditRoad {
+    fn with_permanent(self, map: &Map) -> Result<EditRoad> {
+        let mut turn_bans = Vec::new();
+        for (r, w) in self.time_restrictions.turn_bans {
+            turn_bans.push((map.find_r_by_osm_id(r)?, w));
+        }
+        Ok(EditRoad {
+            lanes_ltr: self.lanes_ltr,
+            speed_limit: self.speed_limit,
+            access_restrictions: self.access_restrictions,
+            parking_restrictions: self.parking_restrictions,
+            time_restrictions: TimeRestrictions {
+                bus_only: self.time_restrictions.bus_only,
+                turn_bans,
+                closed: self.time_restrictions.closed,
+            },
+            curb: self.curb,
+            name_override: self.name_override,
+            rank_override: self.rank_override,
+        })
+    }
+}
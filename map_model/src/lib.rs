@@ -20,6 +20,21 @@
 //! - ss = stop sign
 //! - t = turn
 //! - ts = traffic signal
+//!
+//! # Public API
+//!
+//! Tools outside this repository can rely on the items shown in these docs staying compatible
+//! within a minor release:
+//!
+//! - loading: `Map::load_synchronously`, `Map::blank`, and `MapEdits::load_from_file`
+//! - queries: the `Map::get_*` / `Map::maybe_get_*` / `Map::all_*` accessors and the map objects
+//!   they return
+//! - pathfinding: `Map::pathfind`, `Map::pathfind_v2`, `PathRequest`, `PathConstraints`, `Path`,
+//!   and `RoutingParams`
+//! - edits: `MapEdits`, `EditCmd`, `PermanentMapEdits`, and `Map::must_apply_edits`
+//!
+//! Anything hidden from the docs is only exported for other crates in this workspace, like the
+//! importer and the simulation, and can change at any time.
 
 #![allow(clippy::new_without_default)]
 
@@ -28,52 +43,78 @@ extern crate anyhow;
 #[macro_use]
 extern crate log;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use serde::{Deserialize, Serialize};
 
 use abstio::MapName;
 use abstutil::{deserialize_btreemap, serialize_btreemap, MultiMap};
 use geom::{Bounds, GPSBounds, Polygon};
+#[doc(hidden)]
 pub use raw_map as raw;
 pub use raw_map::{
-    osm, Amenity, AmenityType, AreaType, BufferType, Direction, DrivingSide, IntersectionType,
-    LaneSpec, LaneType, MapConfig, NamePerLanguage, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
+    osm, Amenity, AmenityType, AreaType, BufferType, DefaultSpeedLimits, Direction, DrivingSide,
+    IntersectionType, LaneSpec, LaneType, MapConfig, NamePerLanguage, RoundaboutPriority,
+    NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
 };
 
 pub use crate::city::City;
+pub use crate::diff::{MapChange, MapDiff};
 pub use crate::edits::{
-    EditCmd, EditEffects, EditIntersection, EditRoad, MapEdits, PermanentMapEdits,
+    EditCmd, EditEffects, EditIntersection, EditNote, EditRoad, MapEdits, PermanentEditCmd,
+    PermanentMapEdits,
 };
+#[doc(hidden)]
 pub use crate::make::RawToMapOptions;
+pub use crate::objects::address::Address;
 pub use crate::objects::area::{Area, AreaID};
-pub use crate::objects::block::{Block, Perimeter};
+pub use crate::objects::block::{Block, BlockfindingFailure, Perimeter};
 pub use crate::objects::building::{Building, BuildingID, BuildingType, OffstreetParking};
-pub use crate::objects::intersection::{Intersection, IntersectionID};
-pub use crate::objects::lane::{CommonEndpoint, Lane, LaneID, PARKING_LOT_SPOT_LENGTH};
+pub use crate::objects::cordon::ChargingCordon;
+pub use crate::objects::curb::{CurbUse, CurbUses, BIKES_PER_PARKING_SPOT};
+pub use crate::objects::intersection::{Intersection, IntersectionID, DEFAULT_CORNER_RADIUS};
+pub use crate::objects::lane::{
+    CommonEndpoint, Lane, LaneID, PARKING_LOT_SPOT_LENGTH, TURN_POCKET_TAPER,
+};
 pub use crate::objects::movement::{CompressedMovementID, Movement, MovementID};
 pub use crate::objects::parking_lot::{ParkingLot, ParkingLotID};
+pub use crate::objects::parking_restriction::{
+    ParkingRestriction, ParkingRestrictionType, ParkingRestrictions,
+};
+pub use crate::objects::permit::PermitZone;
+pub use crate::objects::restricted_zone::RestrictedZone;
 pub use crate::objects::road::{DirectedRoadID, Road, RoadID, RoadSideID, SideOfRoad};
 pub use crate::objects::stop_signs::{ControlStopSign, RoadWithStopSign};
-pub use crate::objects::traffic_signals::{ControlTrafficSignal, Stage, StageType};
+pub use crate::objects::time_restriction::{TimeRestrictions, TimeWindow};
+pub use crate::objects::traffic_signals::{
+    ClearanceShortfall, ControlTrafficSignal, CrossingShortfall, Stage, StageConflict, StageType,
+};
+pub use crate::objects::traffic_stress::{StressThresholds, TrafficStress};
 pub use crate::objects::transit::{TransitRoute, TransitRouteID, TransitStop, TransitStopID};
 pub use crate::objects::turn::{Turn, TurnID, TurnPriority, TurnType};
-pub use crate::objects::zone::{AccessRestrictions, Zone};
+pub use crate::objects::zone::AccessRestrictions;
+pub(crate) use crate::objects::zone::Zone;
+#[doc(hidden)]
 pub use crate::pathfind::uber_turns::{IntersectionCluster, UberTurn};
+#[doc(hidden)]
+pub use crate::pathfind::{MapMatcher, Pathfinder, PathfinderCache, PathfinderCaching};
 pub use crate::pathfind::{
-    Path, PathConstraints, PathRequest, PathStep, PathStepV2, PathV2, Pathfinder, PathfinderCache,
-    PathfinderCaching, RoutingParams,
+    Path, PathConstraints, PathRequest, PathStep, PathStepV2, PathV2, RoutingParams,
 };
 pub use crate::traversable::{Position, Traversable, MAX_BIKE_SPEED, MAX_WALKING_SPEED};
+pub use crate::validate::{ValidationProblem, ValidationReport};
 
 mod city;
+#[doc(hidden)]
 pub mod connectivity;
+mod diff;
 mod edits;
 mod make;
 mod map;
 mod objects;
 mod pathfind;
 mod traversable;
+mod validate;
 
 // The map used by the simulation and UI. This struct is declared here so that the rest of the
 // crate can reach into private fields.
@@ -114,4 +155,7 @@ pub struct Map {
     edits_generation: usize,
     #[serde(skip_serializing, skip_deserializing)]
     road_to_buildings: MultiMap<RoadID, BuildingID>,
+    /// Cached, so routing doesn't have to check every road
+    #[serde(skip_serializing, skip_deserializing)]
+    roads_with_time_restrictions: BTreeSet<RoadID>,
 }
@@ -9,7 +9,7 @@ use geom::{Distance, HashablePt2D, Line};
 use crate::make::{match_points_to_lanes, trim_path};
 use crate::raw::RawBuilding;
 use crate::{
-    osm, Amenity, Building, BuildingID, BuildingType, LaneID, Map, NamePerLanguage,
+    osm, Address, Amenity, Building, BuildingID, BuildingType, LaneID, Map, NamePerLanguage,
     OffstreetParking,
 };
 
@@ -75,6 +75,7 @@ pub fn make_all_buildings(
                 polygon: b.polygon.clone(),
                 levels,
                 address: get_address(&b.osm_tags, sidewalk_pos.lane(), map),
+                address_parts: Address::from_tags(&b.osm_tags),
                 name: NamePerLanguage::new(&b.osm_tags),
                 orig_id,
                 label_center: b.polygon.polylabel(),
@@ -14,8 +14,9 @@ use crate::pathfind::{CreateEngine, Pathfinder};
 use crate::raw::{OriginalRoad, RawMap};
 use crate::{
     connectivity, osm, AccessRestrictions, Area, AreaID, ControlStopSign, ControlTrafficSignal,
-    Intersection, IntersectionID, IntersectionType, Lane, LaneID, Map, MapEdits, PathConstraints,
-    Position, Road, RoadID, RoutingParams, Zone,
+    CurbUses, Intersection, IntersectionID, IntersectionType, Lane, LaneID, Map, MapEdits,
+    ParkingRestrictions, PathConstraints, Position, Road, RoadID, RoutingParams, TimeRestrictions,
+    Zone,
 };
 
 mod bridges;
@@ -73,6 +74,7 @@ impl Map {
             edits: MapEdits::new(),
             edits_generation: 0,
             road_to_buildings: MultiMap::new(),
+            roads_with_time_restrictions: BTreeSet::new(),
         };
         map.edits = map.new_edits();
 
@@ -98,6 +100,8 @@ impl Map {
                 outgoing_lanes: Vec::new(),
                 roads: i.roads.iter().map(|id| road_id_mapping[id]).collect(),
                 merged: !raw.intersections[&i.id].trim_roads_for_merging.is_empty(),
+                corner_radius: None,
+                protected_for_bikes: false,
             });
             intersection_id_mapping.insert(i.id, id);
         }
@@ -139,6 +143,13 @@ impl Map {
                         }
                     })
                     .collect(),
+                conditional_turn_restrictions: raw_road
+                    .conditional_turn_restrictions
+                    .iter()
+                    .filter_map(|(to, condition)| {
+                        road_id_mapping.get(to).map(|to| (*to, condition.clone()))
+                    })
+                    .collect(),
                 orig_id: r.id,
                 lanes: Vec::new(),
                 center_pts: r.trimmed_center_pts,
@@ -148,13 +159,20 @@ impl Map {
                 speed_limit: Speed::ZERO,
                 zorder: raw_road.get_zorder(),
                 access_restrictions: AccessRestrictions::new(),
+                parking_restrictions: ParkingRestrictions::new(),
+                time_restrictions: TimeRestrictions::new(),
+                curb: CurbUses::new(),
+                name_override: None,
+                rank_override: None,
                 percent_incline: raw_road.percent_incline,
                 crosswalk_forward: raw_road.crosswalk_forward,
                 crosswalk_backward: raw_road.crosswalk_backward,
                 transit_stops: BTreeSet::new(),
             };
-            road.speed_limit = road.speed_limit_from_osm();
+            road.speed_limit = road.speed_limit_from_osm(&map.config);
             road.access_restrictions = road.access_restrictions_from_osm();
+            road.parking_restrictions = road.parking_restrictions_from_osm();
+            road.time_restrictions = road.time_restrictions_from_osm();
 
             road.recreate_lanes(r.lane_specs_ltr);
             for lane in &road.lanes {
@@ -252,6 +270,7 @@ impl Map {
 
         bridges::find_bridges(&mut map.roads, &map.bounds, timer);
 
+        map.recalculate_roads_with_time_restrictions();
         map.recalculate_all_movements(timer);
 
         let mut stop_signs: BTreeMap<IntersectionID, ControlStopSign> = BTreeMap::new();
@@ -107,6 +107,8 @@ fn new(id: IntersectionID) -> ControlTrafficSignal {
         id,
         stages: Vec::new(),
         offset: Duration::ZERO,
+        bike_box: false,
+        two_stage_bike_left_turns: false,
     }
 }
 
@@ -8,16 +8,16 @@ use petgraph::graphmap::{DiGraphMap, UnGraphMap};
 use abstio::{CityName, MapName};
 use abstutil::{prettyprint_usize, serialized_size_bytes, MultiMap, Tags, Timer};
 use geom::{Bounds, Distance, Duration, GPSBounds, Polygon, Pt2D, Ring, Time};
-use raw_map::{DrivingSide, MapConfig};
+use raw_map::{DefaultSpeedLimits, DrivingSide, MapConfig, RoundaboutPriority};
 
 use crate::raw::{OriginalRoad, RawMap};
 use crate::{
-    osm, Area, AreaID, AreaType, Building, BuildingID, BuildingType, CommonEndpoint,
+    osm, AmenityType, Area, AreaID, AreaType, Building, BuildingID, BuildingType, CommonEndpoint,
     CompressedMovementID, ControlStopSign, ControlTrafficSignal, DirectedRoadID, Direction,
     Intersection, IntersectionID, Lane, LaneID, LaneType, Map, MapEdits, Movement, MovementID,
     OffstreetParking, ParkingLot, ParkingLotID, Path, PathConstraints, PathRequest, PathV2,
     Pathfinder, PathfinderCaching, Position, Road, RoadID, RoutingParams, TransitRoute,
-    TransitRouteID, TransitStop, TransitStopID, Turn, TurnID, TurnType, Zone,
+    TransitRouteID, TransitStop, TransitStopID, Turn, TurnID, TurnType,
 };
 
 impl Map {
@@ -61,12 +61,14 @@ impl Map {
     }
 
     /// After deserializing a map directly, call this after.
+    #[doc(hidden)]
     pub fn map_loaded_directly(&mut self, timer: &mut Timer) {
         #![allow(clippy::logic_bug)]
         // For debugging map file sizes
 
         self.edits = self.new_edits();
         self.recalculate_road_to_buildings();
+        self.recalculate_roads_with_time_restrictions();
         self.recalculate_all_movements(timer);
 
         // Enable to work on shrinking map file sizes. Never run this on the web though --
@@ -152,6 +154,8 @@ impl Map {
                 inferred_sidewalks: true,
                 street_parking_spot_length: Distance::meters(8.0),
                 turn_on_red: true,
+                default_speed_limits: DefaultSpeedLimits::usa(),
+                roundabout_priority: RoundaboutPriority::Circulating,
             },
             pathfinder: Pathfinder::empty(),
             pathfinder_dirty: false,
@@ -160,6 +164,7 @@ impl Map {
             edits: MapEdits::new(),
             edits_generation: 0,
             road_to_buildings: MultiMap::new(),
+            roads_with_time_restrictions: BTreeSet::new(),
         }
     }
 
@@ -191,10 +196,6 @@ impl Map {
         &self.parking_lots
     }
 
-    pub fn all_zones(&self) -> &Vec<Zone> {
-        &self.zones
-    }
-
     pub fn maybe_get_r(&self, id: RoadID) -> Option<&Road> {
         self.roads.get(id.0)
     }
@@ -431,6 +432,25 @@ impl Map {
         &self.transit_stops[&stop]
     }
 
+    /// Parking lots with a transit stop on the same road or one connected to it, where people can
+    /// park and continue by transit.
+    pub fn park_and_ride_lots(&self) -> Vec<ParkingLotID> {
+        self.parking_lots
+            .iter()
+            .filter(|pl| {
+                let road = self.get_parent(pl.sidewalk_pos.lane());
+                !road.transit_stops.is_empty()
+                    || [road.src_i, road.dst_i].into_iter().any(|i| {
+                        self.get_i(i)
+                            .roads
+                            .iter()
+                            .any(|r| !self.get_r(*r).transit_stops.is_empty())
+                    })
+            })
+            .map(|pl| pl.id)
+            .collect()
+    }
+
     pub fn get_tr(&self, route: TransitRouteID) -> &TransitRoute {
         &self.transit_routes[route.0]
     }
@@ -543,6 +563,7 @@ impl Map {
         &self.boundary_polygon
     }
 
+    #[doc(hidden)]
     pub fn get_pathfinder(&self) -> &Pathfinder {
         &self.pathfinder
     }
@@ -576,6 +597,65 @@ impl Map {
             .pathfind_with_params(req.clone(), params, cache_custom, self)
             .ok_or_else(|| anyhow!("can't fulfill {}", req))
     }
+    /// Like `pathfind`, but also respects restrictions active at some time, like bus-only hours,
+    /// turns banned during rush hour, and restricted zones, and the price of entering active
+    /// charging cordons. Use this when an agent departs or changes route. A trip may still begin or
+    /// end on a restricted road or inside a restricted zone, but can't pass through any others.
+    pub fn pathfind_at(&self, req: PathRequest, time: Time) -> Result<Path> {
+        let endpoints = [req.start.lane().road, req.end.lane().road];
+        match self.routing_params_at(req.constraints, time, &endpoints) {
+            Some(params) => {
+                self.pathfind_with_params(req, &params, PathfinderCaching::CacheDijkstra)
+            }
+            None => self.pathfind(req),
+        }
+    }
+    /// If some time-of-day restrictions, restricted zones, or charging cordons affect a type of
+    /// vehicle at some time, returns the routing params needed to avoid them. Restrictions on the
+    /// `access` roads, and zones containing them, are skipped, so that trips can reach
+    /// destinations inside.
+    pub fn routing_params_at(
+        &self,
+        constraints: PathConstraints,
+        time: Time,
+        access: &[RoadID],
+    ) -> Option<RoutingParams> {
+        if constraints == PathConstraints::Pedestrian {
+            return None;
+        }
+        let mut params = self.routing_params().clone();
+        for r in &self.roads_with_time_restrictions {
+            let restrictions = &self.get_r(*r).time_restrictions;
+            if !access.contains(r)
+                && ((constraints == PathConstraints::Car && restrictions.is_bus_only(time))
+                    || restrictions.is_closed(time))
+            {
+                params.avoid_roads.insert(*r);
+            }
+            for to in restrictions.banned_turns(time) {
+                params.avoid_movements_between.insert((*r, to));
+            }
+        }
+        for zone in &self.edits.restricted_zones {
+            if !zone.allows(constraints, time) && !access.iter().any(|r| zone.members.contains(r)) {
+                params.avoid_roads.extend(zone.members.iter().cloned());
+            }
+        }
+        if constraints == PathConstraints::Car {
+            for cordon in &self.edits.charging_cordons {
+                if cordon.is_active(time) {
+                    params
+                        .cordon_charges
+                        .push((cordon.members.clone(), cordon.generalized_cost()));
+                }
+            }
+        }
+        if params == self.routing_params {
+            None
+        } else {
+            Some(params)
+        }
+    }
     pub fn should_use_transit(
         &self,
         start: Position,
@@ -644,14 +724,7 @@ impl Map {
         None
     }
 
-    // TODO Sort of a temporary hack
-    pub fn hack_override_offstreet_spots(&mut self, spots_per_bldg: usize) {
-        for b in &mut self.buildings {
-            if let OffstreetParking::Private(ref mut num_spots, _) = b.parking {
-                *num_spots = spots_per_bldg;
-            }
-        }
-    }
+    #[doc(hidden)]
     pub fn hack_override_offstreet_spots_individ(&mut self, b: BuildingID, spots: usize) {
         let b = &mut self.buildings[b.0];
         if let OffstreetParking::Private(ref mut num_spots, _) = b.parking {
@@ -659,15 +732,12 @@ impl Map {
         }
     }
 
+    #[doc(hidden)]
     pub fn hack_override_bldg_type(&mut self, b: BuildingID, bldg_type: BuildingType) {
         self.buildings[b.0].bldg_type = bldg_type;
     }
 
-    pub fn hack_override_orig_spawn_times(&mut self, br: TransitRouteID, times: Vec<Time>) {
-        self.transit_routes[br.0].orig_spawn_times = times.clone();
-        self.transit_routes[br.0].spawn_times = times;
-    }
-
+    #[doc(hidden)]
     pub fn hack_add_area(&mut self, area_type: AreaType, polygon: Polygon, osm_tags: Tags) {
         self.areas.push(Area {
             id: AreaID(self.areas.len()),
@@ -681,6 +751,7 @@ impl Map {
     /// Normally after applying edits, you must call `recalculate_pathfinding_after_edits`.
     /// Alternatively, you can keep the old pathfinder exactly as it is. Use with caution -- the
     /// pathfinder and the map may be out-of-sync in arbitrary ways.
+    #[doc(hidden)]
     pub fn keep_pathfinder_despite_edits(&mut self) {
         self.pathfinder_dirty = false;
     }
@@ -792,6 +863,59 @@ impl Map {
         self.road_to_buildings.get(r)
     }
 
+    /// Finds buildings whose entrance is within `max_dist` of a path, returning each with its
+    /// distance along the path. The results are sorted in the order the path passes them.
+    pub fn buildings_along_path(
+        &self,
+        path: &Path,
+        max_dist: Distance,
+    ) -> Vec<(BuildingID, Distance)> {
+        self.buildings_along_path_matching(path, max_dist, |_| true)
+    }
+
+    /// Like `buildings_along_path`, but only returns buildings containing an amenity of the
+    /// category.
+    pub fn amenities_along_path(
+        &self,
+        path: &Path,
+        max_dist: Distance,
+        category: AmenityType,
+    ) -> Vec<(BuildingID, Distance)> {
+        self.buildings_along_path_matching(path, max_dist, |b| b.has_amenity(category))
+    }
+
+    fn buildings_along_path_matching<F: Fn(&Building) -> bool>(
+        &self,
+        path: &Path,
+        max_dist: Distance,
+        filter: F,
+    ) -> Vec<(BuildingID, Distance)> {
+        let pl = match path.trace(self) {
+            Some(pl) => pl,
+            None => return Vec::new(),
+        };
+        let mut bounds = pl.get_bounds();
+        bounds.add_buffer(max_dist);
+
+        let mut results = Vec::new();
+        for b in &self.buildings {
+            let pt = b.sidewalk_pos.pt(self);
+            // Cheaply skip most buildings before projecting onto the path
+            if !bounds.contains(pt) || !filter(b) {
+                continue;
+            }
+            let closest = pl.project_pt(pt);
+            if closest.dist_to(pt) > max_dist {
+                continue;
+            }
+            if let Some((dist, _)) = pl.dist_along_of_point(closest) {
+                results.push((b.id, dist));
+            }
+        }
+        results.sort_by_key(|(_, dist)| *dist);
+        results
+    }
+
     pub(crate) fn recalculate_road_to_buildings(&mut self) {
         let mut mapping = MultiMap::new();
         for b in self.all_buildings() {
@@ -800,6 +924,15 @@ impl Map {
         self.road_to_buildings = mapping;
     }
 
+    pub(crate) fn recalculate_roads_with_time_restrictions(&mut self) {
+        self.roads_with_time_restrictions = self
+            .roads
+            .iter()
+            .filter(|r| !r.time_restrictions.is_empty())
+            .map(|r| r.id)
+            .collect();
+    }
+
     pub(crate) fn recalculate_all_movements(&mut self, timer: &mut Timer) {
         let movements = timer.parallelize(
             "generate movements",
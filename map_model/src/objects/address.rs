@@ -0,0 +1,184 @@
+//! Finding buildings from free-form street addresses, like "1234 N 34th St". There's no external
+//! geocoding service; everything comes from the addr:* tags imported from OSM.
+
+use serde::{Deserialize, Serialize};
+
+use abstutil::Tags;
+
+use crate::{BuildingID, Map};
+
+/// The parts of a building's address, from OSM addr:* tags. Any of these may be missing.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Address {
+    pub house_number: Option<String>,
+    pub street: Option<String>,
+    pub unit: Option<String>,
+    pub city: Option<String>,
+    pub postcode: Option<String>,
+}
+
+impl Address {
+    pub fn from_tags(tags: &Tags) -> Address {
+        Address {
+            house_number: tags.get("addr:housenumber").cloned(),
+            street: tags.get("addr:street").cloned(),
+            unit: tags.get("addr:unit").cloned(),
+            city: tags.get("addr:city").cloned(),
+            postcode: tags.get("addr:postcode").cloned(),
+        }
+    }
+}
+
+impl Map {
+    /// Finds buildings matching a free-form address, best matches first. Abbreviations like "st"
+    /// and "n" are understood, and the last word can be partly typed. If no building has the exact
+    /// house number, buildings on the same street with the closest numbers are returned instead.
+    pub fn geocode(&self, query: &str, max_results: usize) -> Vec<BuildingID> {
+        let mut words = normalize(query);
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let number = if is_house_number(&words[0]) {
+            Some(words.remove(0))
+        } else {
+            None
+        };
+
+        // (exact house number, distance from the requested number, address, building)
+        let mut results: Vec<(bool, usize, &str, BuildingID)> = Vec::new();
+        for b in self.all_buildings() {
+            let mut bldg_words = match b.address_parts.street {
+                Some(ref street) => normalize(street),
+                None => normalize(&self.get_parent(b.sidewalk()).get_name(None)),
+            };
+            for extra in [&b.address_parts.city, &b.address_parts.postcode]
+                .into_iter()
+                .flatten()
+            {
+                bldg_words.extend(normalize(extra));
+            }
+            if !words_match(&words, &bldg_words) {
+                continue;
+            }
+
+            let bldg_number = b
+                .address_parts
+                .house_number
+                .as_ref()
+                .map(|n| normalize(n).join(""));
+            let (exact, dist) = match (&number, bldg_number) {
+                (None, _) => (true, 0),
+                (Some(want), Some(have)) if *want == have => (true, 0),
+                (Some(want), Some(have)) => match (leading_digits(want), leading_digits(&have)) {
+                    (Some(x), Some(y)) => (false, if x > y { x - y } else { y - x }),
+                    _ => continue,
+                },
+                (Some(_), None) => continue,
+            };
+            results.push((exact, dist, b.address.as_str(), b.id));
+        }
+
+        results.sort_by_key(|(exact, dist, addr, _)| (!exact, *dist, *addr));
+        // Only fall back to nearby house numbers if nothing matched exactly
+        if results.first().map(|x| x.0).unwrap_or(false) {
+            results.retain(|x| x.0);
+        }
+        results
+            .into_iter()
+            .take(max_results)
+            .map(|(_, _, _, b)| b)
+            .collect()
+    }
+}
+
+/// Lowercase words without punctuation, with common abbreviations expanded.
+fn normalize(input: &str) -> Vec<String> {
+    input
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '-')
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .map(|word| expand_abbreviation(&word).unwrap_or(&word).to_string())
+        .collect()
+}
+
+fn expand_abbreviation(word: &str) -> Option<&'static str> {
+    Some(match word {
+        "st" => "street",
+        "ave" | "av" => "avenue",
+        "rd" => "road",
+        "blvd" => "boulevard",
+        "dr" => "drive",
+        "ln" => "lane",
+        "pl" => "place",
+        "ct" => "court",
+        "ter" => "terrace",
+        "hwy" => "highway",
+        "pkwy" => "parkway",
+        "sq" => "square",
+        "n" => "north",
+        "s" => "south",
+        "e" => "east",
+        "w" => "west",
+        "ne" => "northeast",
+        "nw" => "northwest",
+        "se" => "southeast",
+        "sw" => "southwest",
+        _ => {
+            return None;
+        }
+    })
+}
+
+/// "1234" or "12a" are house numbers, but "34th" is part of a street name.
+fn is_house_number(word: &str) -> bool {
+    if !word.starts_with(|c: char| c.is_ascii_digit()) {
+        return false;
+    }
+    let suffix = word.trim_start_matches(|c: char| c.is_ascii_digit());
+    !matches!(suffix, "st" | "nd" | "rd" | "th")
+}
+
+fn leading_digits(word: &str) -> Option<usize> {
+    let digits: String = word.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Every query word has to appear in the building's words. The last one may be partly typed.
+fn words_match(query: &[String], bldg: &[String]) -> bool {
+    query.iter().enumerate().all(|(idx, word)| {
+        if idx == query.len() - 1 {
+            bldg.iter().any(|w| w.starts_with(word.as_str()))
+        } else {
+            bldg.contains(word)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("1234 N. 34th St, Seattle"),
+            vec!["1234", "north", "34th", "street", "seattle"]
+        );
+        assert!(is_house_number("1234"));
+        assert!(is_house_number("12a"));
+        assert!(!is_house_number("34th"));
+        assert!(words_match(
+            &normalize("N 34th stre"),
+            &normalize("North 34th Street")
+        ));
+        assert!(!words_match(
+            &normalize("S 34th St"),
+            &normalize("North 34th Street")
+        ));
+    }
+}
@@ -32,6 +32,92 @@ pub struct Perimeter {
     pub interior: BTreeSet<RoadID>,
 }
 
+const HIT_MAP_BOUNDARY: &str = "hit the map boundary";
+const SKIPPED_ROAD: &str = "started on a road we shouldn't trace";
+
+/// Some areas of the map can't be traced into blocks, for reasons ranging from reaching the map
+/// boundary to tricky geometry. The roads involved are then missing from any analysis built on
+/// blocks, so this remembers what went wrong where.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlockfindingFailure {
+    /// The sides of roads forming the perimeter, in order. If tracing the perimeter failed, this
+    /// is only the part traced before the problem.
+    pub roads: Vec<RoadSideID>,
+    pub reason: String,
+}
+
+impl BlockfindingFailure {
+    pub fn new(perimeter: &Perimeter, reason: String) -> BlockfindingFailure {
+        BlockfindingFailure {
+            roads: perimeter.roads.clone(),
+            reason,
+        }
+    }
+
+    /// Failures near the map boundary or starting on roads deliberately skipped are expected,
+    /// while everything else is likely a bug worth reporting.
+    pub fn is_expected(&self) -> bool {
+        self.reason == HIT_MAP_BOUNDARY || self.reason == SKIPPED_ROAD
+    }
+
+    /// A short description, including the names of the roads involved.
+    pub fn describe(&self, map: &Map) -> String {
+        let names: BTreeSet<String> = self
+            .roads
+            .iter()
+            .map(|id| map.get_r(id.road).get_name(None))
+            .collect();
+        format!(
+            "{} (along {})",
+            self.reason,
+            abstutil::plain_list_names(names)
+        )
+    }
+
+    /// Produces a GeoJSON FeatureCollection with one feature per failure, tracing the sides of
+    /// the roads involved. Properties include the reason and OSM IDs, so problems can be reported.
+    pub fn to_geojson(failures: &[BlockfindingFailure], map: &Map) -> Result<String> {
+        let gps_bounds = map.get_gps_bounds();
+        let mut features = Vec::new();
+        for failure in failures {
+            let mut lines = Vec::new();
+            let mut osm_ways = BTreeSet::new();
+            for id in &failure.roads {
+                let lane = id.get_outermost_lane(map);
+                lines.push(
+                    lane.lane_center_pts
+                        .points()
+                        .iter()
+                        .map(|pt| {
+                            let gps = pt.to_gps(gps_bounds);
+                            vec![gps.x(), gps.y()]
+                        })
+                        .collect::<Vec<_>>(),
+                );
+                osm_ways.insert(map.get_r(id.road).orig_id.osm_way_id.0);
+            }
+            features.push(serde_json::json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "MultiLineString",
+                    "coordinates": lines,
+                },
+                "properties": {
+                    "reason": failure.reason,
+                    "expected": failure.is_expected(),
+                    "description": failure.describe(map),
+                    "osm_way_ids": osm_ways,
+                    "stroke": "red",
+                },
+            }));
+        }
+        Ok(serde_json::to_string_pretty(&serde_json::json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }))?)
+    }
+}
+
 impl Perimeter {
     /// Starting at any lane, snap to the nearest side of that road, then begin tracing a single
     /// block, with no interior roads. This will fail if a map boundary is reached. The results are
@@ -39,10 +125,25 @@ impl Perimeter {
     /// tracing there.
     pub fn single_block(map: &Map, start: LaneID, skip: &HashSet<RoadID>) -> Result<Perimeter> {
         let mut roads = Vec::new();
+        Perimeter::trace_single_block(map, start, skip, &mut roads)?;
+        Ok(Perimeter {
+            roads,
+            interior: BTreeSet::new(),
+        })
+    }
+
+    /// Like `single_block`, but if tracing fails, `roads` is left with everything traced so far.
+    fn trace_single_block(
+        map: &Map,
+        start: LaneID,
+        skip: &HashSet<RoadID>,
+        roads: &mut Vec<RoadSideID>,
+    ) -> Result<()> {
         let start_road_side = map.get_l(start).get_nearest_side_of_road(map);
 
         if skip.contains(&start_road_side.road) {
-            bail!("Started on a road we shouldn't trace");
+            roads.push(start_road_side);
+            bail!("{}", SKIPPED_ROAD);
         }
 
         // We need to track which side of the road we're at, but also which direction we're facing
@@ -51,7 +152,8 @@ impl Perimeter {
         loop {
             let i = map.get_i(current_intersection);
             if i.is_border() {
-                bail!("hit the map boundary");
+                roads.push(current_road_side);
+                bail!("{}", HIT_MAP_BOUNDARY);
             }
             let mut sorted_roads = i.get_road_sides_sorted_by_incoming_angle(map);
             sorted_roads.retain(|id| !skip.contains(&id.road));
@@ -83,41 +185,51 @@ impl Perimeter {
             }
         }
         assert_eq!(roads[0], *roads.last().unwrap());
-        Ok(Perimeter {
-            roads,
-            interior: BTreeSet::new(),
-        })
+        Ok(())
     }
 
     /// This calculates all single block perimeters for the entire map. The resulting list does not
     /// cover roads near the map boundary.
     pub fn find_all_single_blocks(map: &Map) -> Vec<Perimeter> {
+        Perimeter::find_all_single_blocks_with_failures(map).0
+    }
+
+    /// Like `find_all_single_blocks`, but also returns every place where tracing failed.
+    pub fn find_all_single_blocks_with_failures(
+        map: &Map,
+    ) -> (Vec<Perimeter>, Vec<BlockfindingFailure>) {
         let skip = Perimeter::find_roads_to_skip_tracing(map);
 
         let mut seen = HashSet::new();
         let mut perimeters = Vec::new();
+        let mut failures = Vec::new();
         for lane in map.all_lanes() {
             let side = lane.get_nearest_side_of_road(map);
             if seen.contains(&side) {
                 continue;
             }
-            match Perimeter::single_block(map, lane.id, &skip) {
-                Ok(perimeter) => {
-                    seen.extend(perimeter.roads.clone());
-                    perimeters.push(perimeter);
+            let mut roads = Vec::new();
+            match Perimeter::trace_single_block(map, lane.id, &skip, &mut roads) {
+                Ok(()) => {
+                    seen.extend(roads.clone());
+                    perimeters.push(Perimeter {
+                        roads,
+                        interior: BTreeSet::new(),
+                    });
                 }
                 Err(err) => {
-                    // The logs are quite spammy and not helpful yet, since they're all expected
-                    // cases near the map boundary
-                    if false {
-                        warn!("Failed from {}: {}", lane.id, err);
-                    }
-                    // Don't try again
+                    // Don't try again. Tracing is deterministic, so starting from anywhere along
+                    // the partial trace would fail the same way.
                     seen.insert(side);
+                    seen.extend(roads.clone());
+                    failures.push(BlockfindingFailure {
+                        roads,
+                        reason: err.to_string(),
+                    });
                 }
             }
         }
-        perimeters
+        (perimeters, failures)
     }
 
     /// Trying to form blocks near railways or cycleways that involve bridges/tunnels often causes
@@ -7,7 +7,7 @@ use abstutil::{deserialize_usize, serialize_usize, Tags};
 use geom::{Distance, PolyLine, Polygon, Pt2D};
 use raw_map::{Amenity, AmenityType, NamePerLanguage};
 
-use crate::{osm, LaneID, Map, PathConstraints, Position};
+use crate::{osm, Address, LaneID, Map, PathConstraints, Position};
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct BuildingID(
@@ -31,7 +31,9 @@ pub struct Building {
     pub id: BuildingID,
     pub polygon: Polygon,
     pub levels: f64,
+    /// A human-readable address. If OSM doesn't say, the street is guessed from the sidewalk.
     pub address: String,
+    pub address_parts: Address,
     pub name: Option<NamePerLanguage>,
     pub orig_id: osm::OsmID,
     /// Where a text label should be centered to have the best chances of being contained within
@@ -87,12 +89,7 @@ impl Building {
     }
 
     pub fn house_number(&self) -> Option<String> {
-        let num = self.address.split(' ').next().unwrap();
-        if num != "???" {
-            Some(num.to_string())
-        } else {
-            None
-        }
+        self.address_parts.house_number.clone()
     }
 
     /// The polyline goes from the building to the driving position
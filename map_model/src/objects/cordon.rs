@@ -0,0 +1,92 @@
+//! Charging cordons model congestion pricing schemes, where drivers pay to enter some area during
+//! certain hours.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use geom::{Duration, LonLat, Polygon, Time};
+
+use crate::{Map, MovementID, PathConstraints, RoadID, RoutingParams};
+
+/// How much somebody driving values their time, used to convert a price into an equivalent delay
+/// for routing. This is a rough guess, in dollars per hour.
+pub const VALUE_OF_TIME_PER_HOUR: f64 = 20.0;
+
+/// Vehicles entering this area during a time window are charged.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChargingCordon {
+    pub name: String,
+    pub boundary: Polygon,
+    /// In dollars, charged once every time a vehicle enters the cordon
+    pub price: f64,
+    pub start_time: Time,
+    pub end_time: Time,
+
+    /// Derived from the boundary: every road whose center is inside
+    pub members: BTreeSet<RoadID>,
+}
+
+impl ChargingCordon {
+    pub fn new(
+        map: &Map,
+        name: String,
+        boundary: Polygon,
+        price: f64,
+        start_time: Time,
+        end_time: Time,
+    ) -> ChargingCordon {
+        let members = map
+            .all_roads()
+            .iter()
+            .filter(|r| boundary.contains_pt(r.center_pts.middle()))
+            .map(|r| r.id)
+            .collect();
+        ChargingCordon {
+            name,
+            boundary,
+            price,
+            start_time,
+            end_time,
+            members,
+        }
+    }
+
+    /// Does moving between these two roads cross into the cordon from outside?
+    pub fn is_entered_by(&self, from: RoadID, to: RoadID) -> bool {
+        !self.members.contains(&from) && self.members.contains(&to)
+    }
+
+    pub fn is_active(&self, time: Time) -> bool {
+        time >= self.start_time && time <= self.end_time
+    }
+
+    /// The price expressed as an equivalent delay
+    pub fn generalized_cost(&self) -> Duration {
+        Duration::hours(1) * (self.price / VALUE_OF_TIME_PER_HOUR)
+    }
+
+    pub(crate) fn boundary_gps(&self, map: &Map) -> Vec<LonLat> {
+        map.get_gps_bounds().convert_back(self.boundary.points())
+    }
+}
+
+/// Only cars pay to enter cordons. The charges are only present in `RoutingParams` while the
+/// cordon is active; see `Map::routing_params_at`.
+pub(crate) fn cordon_cost(
+    mvmnt: MovementID,
+    constraints: PathConstraints,
+    params: &RoutingParams,
+) -> Duration {
+    if constraints != PathConstraints::Car {
+        return Duration::ZERO;
+    }
+    params
+        .cordon_charges
+        .iter()
+        .filter(|(members, _)| {
+            !members.contains(&mvmnt.from.road) && members.contains(&mvmnt.to.road)
+        })
+        .map(|(_, cost)| *cost)
+        .sum()
+}
@@ -0,0 +1,93 @@
+//! The space along the curb is usually for parking, but cities reassign blockfaces to other uses:
+//! bus stops where buses can pull in, loading zones for deliveries, parklets with outdoor seating,
+//! or corrals for parking bikes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::SideOfRoad;
+
+/// How many bikes fit in the space of one car in a bike corral
+pub const BIKES_PER_PARKING_SPOT: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CurbUse {
+    Parking,
+    /// Buses pull up to a clear curb instead of maneuvering around parked cars, so boarding is
+    /// quicker. This only helps stops that already exist; it doesn't create new ones.
+    BusStop,
+    /// Reserved for deliveries. Since the simulation doesn't model deliveries, nobody parks here.
+    Loading,
+    Parklet,
+    BikeCorral,
+}
+
+impl Default for CurbUse {
+    fn default() -> CurbUse {
+        CurbUse::Parking
+    }
+}
+
+impl CurbUse {
+    pub fn all() -> Vec<CurbUse> {
+        vec![
+            CurbUse::Parking,
+            CurbUse::BusStop,
+            CurbUse::Loading,
+            CurbUse::Parklet,
+            CurbUse::BikeCorral,
+        ]
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            CurbUse::Parking => "parking",
+            CurbUse::BusStop => "bus stop",
+            CurbUse::Loading => "loading zone",
+            CurbUse::Parklet => "parklet",
+            CurbUse::BikeCorral => "bike corral",
+        }
+    }
+
+    /// Can cars park along a curb used this way?
+    pub fn allows_parking(self) -> bool {
+        self == CurbUse::Parking
+    }
+}
+
+/// How the curb along each side of a road is used. Left and right are relative to the road's
+/// orientation, like `ParkingRestrictions`. Only sides with a parking lane have curb space to
+/// reassign.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct CurbUses {
+    pub left: CurbUse,
+    pub right: CurbUse,
+}
+
+impl CurbUses {
+    pub fn new() -> CurbUses {
+        CurbUses::default()
+    }
+
+    /// Is the curb on both sides just used for parking?
+    pub fn is_default(&self) -> bool {
+        self.left == CurbUse::Parking && self.right == CurbUse::Parking
+    }
+
+    pub fn get(&self, side: SideOfRoad) -> CurbUse {
+        match side {
+            SideOfRoad::Left => self.left,
+            SideOfRoad::Right => self.right,
+        }
+    }
+
+    pub fn set(&mut self, side: SideOfRoad, curb: CurbUse) {
+        match side {
+            SideOfRoad::Left => {
+                self.left = curb;
+            }
+            SideOfRoad::Right => {
+                self.right = curb;
+            }
+        }
+    }
+}
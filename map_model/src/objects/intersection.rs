@@ -5,14 +5,20 @@ use std::fmt;
 use serde::{Deserialize, Serialize};
 
 use abstutil::{deserialize_usize, serialize_usize};
-use geom::{Distance, Polygon};
-use raw_map::IntersectionType;
+use geom::{Distance, Polygon, Speed};
+use raw_map::{IntersectionType, NORMAL_LANE_THICKNESS};
 
 use crate::{
     osm, CompressedMovementID, DirectedRoadID, LaneID, Map, Movement, MovementID, PathConstraints,
-    Road, RoadID, RoadSideID, SideOfRoad, Turn, TurnID,
+    Road, RoadID, RoadSideID, SideOfRoad, StressThresholds, Turn, TurnID,
 };
 
+/// A typical curb radius at urban corners, used as the baseline when measuring how much an edited
+/// radius changes crossing distances.
+pub const DEFAULT_CORNER_RADIUS: Distance = Distance::const_meters(4.5);
+/// How much sideways acceleration drivers tolerate when turning a corner, in m/s^2
+const COMFORTABLE_LATERAL_ACCELERATION: f64 = 2.0;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct IntersectionID(
     #[serde(
@@ -54,6 +60,12 @@ pub struct Intersection {
 
     /// Was a short road adjacent to this intersection merged?
     pub merged: bool,
+    /// The radius of the curb at every corner. OSM doesn't describe this, so it's only set by map
+    /// edits; when it's None, corners don't affect turning speeds or crossing distances.
+    pub corner_radius: Option<Distance>,
+    /// Does this intersection have a protected design for biking -- corner refuge islands, setback
+    /// crossings, and at signals, a leading interval for bikes? Only set by map edits.
+    pub protected_for_bikes: bool,
     // These increase the map file size, so instead, just use `recalculate_all_movements` after
     // deserializing.
     #[serde(skip_serializing, skip_deserializing)]
@@ -71,6 +83,46 @@ impl Intersection {
         self.intersection_type == IntersectionType::Border && !self.incoming_lanes.is_empty()
     }
 
+    /// How fast can vehicles turn tightly around the corner, hugging the curb? Returns None if
+    /// the corner radius isn't known.
+    pub fn max_speed_around_corner(&self) -> Option<Speed> {
+        self.corner_radius.map(Intersection::max_speed_around_curb)
+    }
+
+    /// How much longer (or shorter) is every crosswalk here, compared to typical corners?
+    pub fn crossing_distance_adjustment(&self) -> Distance {
+        self.corner_radius
+            .map(Intersection::crossing_distance_adjustment_for)
+            .unwrap_or(Distance::ZERO)
+    }
+
+    /// Vehicles turning tightly around a corner follow a curve a bit wider than the curb. The
+    /// speed they can comfortably take it at is sqrt(lateral acceleration * radius).
+    pub fn max_speed_around_curb(corner_radius: Distance) -> Speed {
+        let radius = corner_radius + NORMAL_LANE_THICKNESS / 2.0;
+        Speed::meters_per_second((COMFORTABLE_LATERAL_ACCELERATION * radius.inner_meters()).sqrt())
+    }
+
+    /// Crosswalks start where the curb's curve is about halfway around the corner, which is
+    /// radius * (1 - 1/sqrt(2)) farther out than the tangent lines of a sharp corner. A crosswalk
+    /// has a corner at each end. Curb extensions have a smaller radius than typical, so this is
+    /// negative for them.
+    pub fn crossing_distance_adjustment_for(corner_radius: Distance) -> Distance {
+        2.0 * (corner_radius - DEFAULT_CORNER_RADIUS) * (1.0 - std::f64::consts::FRAC_1_SQRT_2)
+    }
+
+    /// Crossing or turning through an intersection with a high-stress road is stressful for
+    /// cycling, unless the intersection has a protected design. Bike lanes on the roads don't
+    /// help here.
+    pub fn high_stress_for_bikes(&self, map: &Map, thresholds: &StressThresholds) -> bool {
+        if self.protected_for_bikes || self.is_border() {
+            return false;
+        }
+        self.roads
+            .iter()
+            .any(|r| map.get_r(*r).mixed_traffic_stress(thresholds) >= thresholds.high_stress)
+    }
+
     pub fn is_closed(&self) -> bool {
         self.intersection_type == IntersectionType::Construction
     }
@@ -15,6 +15,9 @@ use crate::{
 /// bit different than the length in parking lots, so set a different value here.
 pub const PARKING_LOT_SPOT_LENGTH: Distance = Distance::const_meters(6.4);
 
+/// Before a turn pocket reaches full width, it widens gradually from nothing over this distance.
+pub const TURN_POCKET_TAPER: Distance = Distance::const_meters(20.0);
+
 /// A lane is identified by its parent road and its position, ordered from the left.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub struct LaneID {
@@ -81,6 +84,8 @@ pub struct Lane {
     pub lane_center_pts: PolyLine,
     pub width: Distance,
     pub dir: Direction,
+    /// If this is a turn pocket, the length of its full-width part, ending at dst_i.
+    pub pocket: Option<Distance>,
 
     pub src_i: IntersectionID,
     pub dst_i: IntersectionID,
@@ -137,6 +142,15 @@ impl Lane {
         self.lane_center_pts.length()
     }
 
+    /// How far along the lane the full-width part of a turn pocket begins, after its taper. This
+    /// is zero for lanes running the road's whole length.
+    pub fn pocket_start(&self) -> Distance {
+        match self.pocket {
+            Some(len) => (self.length() - len).max(Distance::ZERO),
+            None => Distance::ZERO,
+        }
+    }
+
     pub fn intersections(&self) -> Vec<IntersectionID> {
         // TODO I think we're assuming there are no loop lanes
         vec![self.src_i, self.dst_i]
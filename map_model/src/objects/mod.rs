@@ -1,13 +1,21 @@
+pub mod address;
 pub mod area;
 pub mod block;
 pub mod building;
+pub mod cordon;
+pub mod curb;
 pub mod intersection;
 pub mod lane;
 pub mod movement;
 pub mod parking_lot;
+pub mod parking_restriction;
+pub mod permit;
+pub mod restricted_zone;
 pub mod road;
 pub mod stop_signs;
+pub mod time_restriction;
 pub mod traffic_signals;
+pub mod traffic_stress;
 pub mod transit;
 pub mod turn;
 pub mod zone;
@@ -0,0 +1,278 @@
+//! Onstreet parking is often restricted during certain hours -- no parking during the morning rush
+//! hour so the lane can carry traffic, or only deliveries allowed during the middle of the day.
+//! These come from OSM conditional tags like
+//! `parking:right:restriction:conditional=no_parking @ (07:00-09:00); loading_only @ (10:00-16:00)`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use abstutil::Tags;
+use geom::Time;
+
+use crate::objects::time_restriction::{hours_mins, parse_osm_conditional};
+use crate::SideOfRoad;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParkingRestrictionType {
+    NoParking,
+    /// Only delivery vehicles may stop. Since the simulation doesn't model deliveries, this
+    /// behaves like NoParking for everybody else.
+    LoadingOnly,
+}
+
+impl ParkingRestrictionType {
+    fn osm_value(self) -> &'static str {
+        match self {
+            ParkingRestrictionType::NoParking => "no_parking",
+            ParkingRestrictionType::LoadingOnly => "loading_only",
+        }
+    }
+
+    fn from_osm_value(value: &str) -> Option<ParkingRestrictionType> {
+        match value {
+            "no_parking" | "no_stopping" | "no_standing" => Some(ParkingRestrictionType::NoParking),
+            "loading_only" | "loading" => Some(ParkingRestrictionType::LoadingOnly),
+            _ => None,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            ParkingRestrictionType::NoParking => "no parking",
+            ParkingRestrictionType::LoadingOnly => "loading only",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ParkingRestriction {
+    pub restriction: ParkingRestrictionType,
+    pub start_time: Time,
+    pub end_time: Time,
+}
+
+impl ParkingRestriction {
+    pub fn is_active(&self, time: Time) -> bool {
+        time >= self.start_time && time < self.end_time
+    }
+
+    /// Parses restrictions in the syntax of OSM conditional tags, like
+    /// `no_parking @ (07:00-09:00); loading_only @ (10:00-16:00)`. Unrecognized restriction values
+    /// are skipped. The simulation only covers one day, so days of the week are ignored; only the
+    /// hours matter.
+    pub fn parse_osm_conditional(value: &str) -> Result<Vec<ParkingRestriction>> {
+        Ok(
+            parse_osm_conditional(value, ParkingRestrictionType::from_osm_value)?
+                .into_iter()
+                .map(|(restriction, window)| ParkingRestriction {
+                    restriction,
+                    start_time: window.start_time,
+                    end_time: window.end_time,
+                })
+                .collect(),
+        )
+    }
+
+    /// The inverse of `parse_osm_conditional`
+    pub fn to_osm_conditional(list: &[ParkingRestriction]) -> String {
+        list.iter()
+            .map(|r| {
+                format!(
+                    "{} @ ({}-{})",
+                    r.restriction.osm_value(),
+                    hours_mins(r.start_time),
+                    hours_mins(r.end_time)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// The restrictions for parking along each side of a road. Left and right are relative to the
+/// road's orientation, matching OSM's convention for the way.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ParkingRestrictions {
+    pub left: Vec<ParkingRestriction>,
+    pub right: Vec<ParkingRestriction>,
+}
+
+impl ParkingRestrictions {
+    pub fn new() -> ParkingRestrictions {
+        ParkingRestrictions::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+
+    pub fn get(&self, side: SideOfRoad) -> &Vec<ParkingRestriction> {
+        match side {
+            SideOfRoad::Left => &self.left,
+            SideOfRoad::Right => &self.right,
+        }
+    }
+
+    pub fn get_mut(&mut self, side: SideOfRoad) -> &mut Vec<ParkingRestriction> {
+        match side {
+            SideOfRoad::Left => &mut self.left,
+            SideOfRoad::Right => &mut self.right,
+        }
+    }
+
+    /// What restriction, if any, applies to one side of the road at some time? If several
+    /// overlap, the first one listed wins.
+    pub fn active(&self, side: SideOfRoad, time: Time) -> Option<ParkingRestrictionType> {
+        self.get(side)
+            .iter()
+            .find(|r| r.is_active(time))
+            .map(|r| r.restriction)
+    }
+
+    /// All of the times when some restriction starts or stops, in order
+    pub fn all_transitions(&self) -> Vec<Time> {
+        let mut times = Vec::new();
+        for r in self.left.iter().chain(self.right.iter()) {
+            times.push(r.start_time);
+            times.push(r.end_time);
+        }
+        times.sort();
+        times.dedup();
+        times
+    }
+
+    /// Which side of the road is a lane on? Parking lanes are always near one edge, so splitting
+    /// the lanes in half is good enough.
+    pub fn side_for_lane(idx: usize, num_lanes: usize) -> SideOfRoad {
+        if idx < num_lanes / 2 {
+            SideOfRoad::Left
+        } else {
+            SideOfRoad::Right
+        }
+    }
+
+    pub(crate) fn from_osm(tags: &Tags) -> ParkingRestrictions {
+        let mut result = ParkingRestrictions::new();
+        for (osm_side, sides) in [
+            ("left", vec![SideOfRoad::Left]),
+            ("right", vec![SideOfRoad::Right]),
+            ("both", vec![SideOfRoad::Left, SideOfRoad::Right]),
+        ] {
+            for key in [
+                format!("parking:{}:restriction:conditional", osm_side),
+                format!("parking:condition:{}:conditional", osm_side),
+            ] {
+                if let Some(value) = tags.get(&key) {
+                    match ParkingRestriction::parse_osm_conditional(value) {
+                        Ok(list) => {
+                            for side in &sides {
+                                result.get_mut(*side).extend(list.clone());
+                            }
+                        }
+                        Err(err) => {
+                            warn!("Can't parse {}={}: {}", key, value, err);
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(kv: Vec<&str>) -> Tags {
+        let mut tags = Tags::empty();
+        for pair in kv {
+            let (k, v) = pair.split_once('=').unwrap();
+            tags.insert(k, v);
+        }
+        tags
+    }
+
+    #[test]
+    fn test_from_osm() {
+        let mut ok = true;
+        for (description, input, expected_left, expected_right) in vec![
+            (
+                "one side, several restrictions",
+                vec![
+                    "parking:right:restriction:conditional=no_parking @ (07:00-09:00); loading_only @ (10:00-16:00)",
+                ],
+                "",
+                "no_parking @ (07:00-09:00); loading_only @ (10:00-16:00)",
+            ),
+            (
+                "both sides, weekdays and several windows",
+                vec![
+                    "parking:both:restriction:conditional=no_stopping @ (Mo-Fr 07:00-09:00,16:00-18:00)",
+                ],
+                "no_parking @ (07:00-09:00); no_parking @ (16:00-18:00)",
+                "no_parking @ (07:00-09:00); no_parking @ (16:00-18:00)",
+            ),
+            (
+                "the older parking:condition scheme",
+                vec!["parking:condition:left:conditional=loading @ (08:00-11:00)"],
+                "loading_only @ (08:00-11:00)",
+                "",
+            ),
+            (
+                "one side and both sides together",
+                vec![
+                    "parking:left:restriction:conditional=loading_only @ (10:00-12:00)",
+                    "parking:both:restriction:conditional=no_parking @ (07:00-09:00)",
+                ],
+                "loading_only @ (10:00-12:00); no_parking @ (07:00-09:00)",
+                "no_parking @ (07:00-09:00)",
+            ),
+            (
+                "unrecognized values are skipped",
+                vec![
+                    "parking:left:restriction:conditional=residents @ (08:00-18:00); no_parking @ (07:00-09:00)",
+                ],
+                "no_parking @ (07:00-09:00)",
+                "",
+            ),
+            (
+                "missing condition",
+                vec!["parking:right:restriction:conditional=no_parking"],
+                "",
+                "",
+            ),
+            (
+                "ends before it starts",
+                vec!["parking:right:restriction:conditional=no_parking @ (09:00-07:00)"],
+                "",
+                "",
+            ),
+            (
+                "no hours",
+                vec!["parking:right:restriction:conditional=no_parking @ (Mo-Fr)"],
+                "",
+                "",
+            ),
+        ] {
+            let actual = ParkingRestrictions::from_osm(&tags(input.clone()));
+            let actual_left = ParkingRestriction::to_osm_conditional(&actual.left);
+            let actual_right = ParkingRestriction::to_osm_conditional(&actual.right);
+            if actual_left != expected_left || actual_right != expected_right {
+                ok = false;
+                println!("For input ({}):", description);
+                for kv in input {
+                    println!("    {}", kv);
+                }
+                println!("Got:");
+                println!("    left: {}", actual_left);
+                println!("    right: {}", actual_right);
+                println!("Expected:");
+                println!("    left: {}", expected_left);
+                println!("    right: {}", expected_right);
+                println!();
+            }
+        }
+        assert!(ok);
+    }
+}
@@ -0,0 +1,39 @@
+//! Resident permit zones reserve onstreet parking along some roads for the people living there, so
+//! visitors and commuters have to park elsewhere.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BuildingID, Map, RoadID};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PermitZone {
+    pub name: String,
+    /// Onstreet parking along these roads is only for residents
+    pub roads: BTreeSet<RoadID>,
+    /// Cars belonging to these buildings have a permit
+    pub buildings: BTreeSet<BuildingID>,
+}
+
+impl PermitZone {
+    /// Every building along the roads gets a permit.
+    pub fn new(map: &Map, name: String, roads: BTreeSet<RoadID>) -> PermitZone {
+        let buildings = map
+            .all_buildings()
+            .iter()
+            .filter(|b| roads.contains(&b.sidewalk().road))
+            .map(|b| b.id)
+            .collect();
+        PermitZone {
+            name,
+            roads,
+            buildings,
+        }
+    }
+
+    /// Can a car belonging to this building, if any, park in this zone?
+    pub fn allows(&self, home: Option<BuildingID>) -> bool {
+        home.map(|b| self.buildings.contains(&b)).unwrap_or(false)
+    }
+}
@@ -0,0 +1,95 @@
+//! Restricted zones close an area to most vehicles, sometimes only during certain hours -- school
+//! streets around the start and end of the school day, or a pedestrianized shopping district.
+//! They're drawn as a polygon and cover every road inside, so there's no need to restrict each
+//! road individually.
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use enumset::EnumSet;
+use serde::{Deserialize, Serialize};
+
+use geom::{LonLat, Polygon, Time};
+
+use crate::objects::time_restriction::parse_osm_conditional;
+use crate::{Map, PathConstraints, RoadID, TimeWindow};
+
+/// Vehicles not exempt may not drive inside this area while it's active. Pedestrians are never
+/// restricted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RestrictedZone {
+    pub name: String,
+    pub boundary: Polygon,
+    /// These vehicles may still drive inside, like buses or bikes
+    pub exemptions: EnumSet<PathConstraints>,
+    /// When the zone is active. If empty, it always is.
+    pub windows: Vec<TimeWindow>,
+
+    /// Derived from the boundary: every road whose center is inside
+    pub members: BTreeSet<RoadID>,
+}
+
+impl RestrictedZone {
+    pub fn new(
+        map: &Map,
+        name: String,
+        boundary: Polygon,
+        exemptions: EnumSet<PathConstraints>,
+        windows: Vec<TimeWindow>,
+    ) -> RestrictedZone {
+        let members = map
+            .all_roads()
+            .iter()
+            .filter(|r| boundary.contains_pt(r.center_pts.middle()))
+            .map(|r| r.id)
+            .collect();
+        RestrictedZone {
+            name,
+            boundary,
+            exemptions,
+            windows,
+            members,
+        }
+    }
+
+    pub fn is_active(&self, time: Time) -> bool {
+        self.windows.is_empty() || self.windows.iter().any(|w| w.is_active(time))
+    }
+
+    /// May this type of traffic drive inside the zone at some time?
+    pub fn allows(&self, constraints: PathConstraints, time: Time) -> bool {
+        constraints == PathConstraints::Pedestrian
+            || self.exemptions.contains(constraints)
+            || !self.is_active(time)
+    }
+
+    /// Like "07:30-09:00, 14:30-16:00", or "always"
+    pub fn describe_windows(&self) -> String {
+        if self.windows.is_empty() {
+            return "always".to_string();
+        }
+        self.windows
+            .iter()
+            .map(|w| w.describe())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The inverse of `describe_windows`. An empty string or "always" means no windows.
+    pub fn parse_windows(value: &str) -> Result<Vec<TimeWindow>> {
+        let value = value.trim();
+        if value.is_empty() || value == "always" {
+            return Ok(Vec::new());
+        }
+        Ok(
+            parse_osm_conditional(&format!("restricted @ ({})", value), |_| Some(()))?
+                .into_iter()
+                .map(|(_, w)| w)
+                .collect(),
+        )
+    }
+
+    pub(crate) fn boundary_gps(&self, map: &Map) -> Vec<LonLat> {
+        map.get_gps_bounds().convert_back(self.boundary.points())
+    }
+}
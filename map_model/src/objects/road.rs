@@ -6,13 +6,14 @@ use enumset::EnumSet;
 use serde::{Deserialize, Serialize};
 
 use abstutil::{deserialize_usize, serialize_usize, Tags};
-use geom::{Distance, PolyLine, Polygon, Speed};
+use geom::{Distance, PolyLine, Polygon, Speed, Time};
 use raw_map::Direction;
 
 use crate::raw::{OriginalRoad, RestrictionType};
 use crate::{
-    osm, AccessRestrictions, CommonEndpoint, DrivingSide, IntersectionID, Lane, LaneID, LaneSpec,
-    LaneType, Map, PathConstraints, TransitStopID, Zone,
+    osm, AccessRestrictions, CommonEndpoint, CurbUse, CurbUses, DrivingSide, IntersectionID, Lane,
+    LaneID, LaneSpec, LaneType, Map, MapConfig, ParkingRestrictionType, ParkingRestrictions,
+    PathConstraints, TimeRestrictions, TransitStopID, Zone,
 };
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -150,9 +151,21 @@ pub struct Road {
     pub turn_restrictions: Vec<(RestrictionType, RoadID)>,
     /// self is 'from'. (via, to). Only BanTurns.
     pub complicated_turn_restrictions: Vec<(RoadID, RoadID)>,
+    /// self is 'from'. (to, raw OSM condition). Only used to calculate `time_restrictions`.
+    pub conditional_turn_restrictions: Vec<(RoadID, String)>,
     pub orig_id: OriginalRoad,
     pub speed_limit: Speed,
     pub access_restrictions: AccessRestrictions,
+    /// Time-of-day restrictions on the parking lanes along each side
+    pub parking_restrictions: ParkingRestrictions,
+    /// Bus-only hours and turns banned during certain hours
+    pub time_restrictions: TimeRestrictions,
+    /// How the curb along each side is used
+    pub curb: CurbUses,
+    /// Replaces the name from OSM in every language, when the OSM data is wrong
+    pub name_override: Option<String>,
+    /// Replaces the classification implied by the OSM highway tag, when the OSM data is wrong
+    pub rank_override: Option<osm::RoadRank>,
     pub zorder: isize,
     /// [-1.0, 1.0] theoretically, but in practice, about [-0.25, 0.25]. 0 is flat,
     /// positive is uphill from src_i -> dst_i, negative is downhill.
@@ -186,6 +199,7 @@ impl Road {
                 lt: l.lane_type,
                 dir: l.dir,
                 width: l.width,
+                pocket: l.pocket,
             })
             .collect()
     }
@@ -210,7 +224,7 @@ impl Road {
         self.find_closest_lane(parking, |l| l.is_driving())
     }
 
-    pub(crate) fn speed_limit_from_osm(&self) -> Speed {
+    pub(crate) fn speed_limit_from_osm(&self, cfg: &MapConfig) -> Speed {
         if let Some(limit) = self.osm_tags.get(osm::MAXSPEED) {
             if let Ok(kmph) = limit.parse::<f64>() {
                 if kmph == 0.0 {
@@ -230,21 +244,21 @@ impl Road {
             // TODO Handle implicits, like PL:zone30
         }
 
-        // These're half reasonable guesses. Better to explicitly tag in OSM.
+        // These're guesses that depend on the country. Better to explicitly tag in OSM.
+        let defaults = &cfg.default_speed_limits;
         if self
             .osm_tags
             .is_any(osm::HIGHWAY, vec!["primary", "secondary", "motorway_link"])
         {
-            return Speed::miles_per_hour(40.0);
+            return defaults.arterial;
         }
         if self.osm_tags.is(osm::HIGHWAY, "living_street") {
-            // about 12mph
-            return Speed::km_per_hour(20.0);
+            return defaults.living_street;
         }
         if self.is_service() {
-            return Speed::miles_per_hour(10.0);
+            return defaults.service;
         }
-        Speed::miles_per_hour(20.0)
+        defaults.other
     }
 
     /// Includes off-side
@@ -343,6 +357,9 @@ impl Road {
     }
 
     pub fn get_name(&self, lang: Option<&String>) -> String {
+        if let Some(ref name) = self.name_override {
+            return name.clone();
+        }
         if let Some(lang) = lang {
             if let Some(name) = self.osm_tags.get(&format!("name:{}", lang)) {
                 return name.to_string();
@@ -380,6 +397,14 @@ impl Road {
     }
 
     pub fn get_rank(&self) -> osm::RoadRank {
+        if let Some(rank) = self.rank_override {
+            return rank;
+        }
+        self.rank_from_osm()
+    }
+
+    /// The classification implied by the OSM highway tag, ignoring any edits
+    pub fn rank_from_osm(&self) -> osm::RoadRank {
         if let Some(x) = self.osm_tags.get(osm::HIGHWAY) {
             if x == "construction" {
                 // What exactly is under construction?
@@ -449,6 +474,17 @@ impl Road {
         }
     }
 
+    /// Is this a bike or bus lane going against the direction of all general traffic lanes, like
+    /// a contraflow lane on a one-way street?
+    pub fn is_contraflow(&self, l: LaneID) -> bool {
+        let lane = self.lanes.iter().find(|x| x.id == l).unwrap();
+        if !matches!(lane.lane_type, LaneType::Biking | LaneType::Bus) {
+            return false;
+        }
+        let mut driving = self.lanes.iter().filter(|x| x.is_driving()).peekable();
+        driving.peek().is_some() && driving.all(|x| x.dir != lane.dir)
+    }
+
     pub fn is_private(&self) -> bool {
         self.access_restrictions != AccessRestrictions::new() && !self.is_light_rail()
     }
@@ -470,6 +506,32 @@ impl Road {
         }
     }
 
+    pub(crate) fn parking_restrictions_from_osm(&self) -> ParkingRestrictions {
+        ParkingRestrictions::from_osm(&self.osm_tags)
+    }
+
+    pub(crate) fn time_restrictions_from_osm(&self) -> TimeRestrictions {
+        TimeRestrictions::from_osm(&self.osm_tags, &self.conditional_turn_restrictions)
+    }
+
+    /// How is the curb along the side of the road with this lane used?
+    pub fn curb_use(&self, l: LaneID) -> CurbUse {
+        assert_eq!(l.road, self.id);
+        self.curb.get(ParkingRestrictions::side_for_lane(
+            l.offset,
+            self.lanes.len(),
+        ))
+    }
+
+    /// Is parking along this lane restricted at some time?
+    pub fn parking_restriction_at(&self, l: LaneID, time: Time) -> Option<ParkingRestrictionType> {
+        assert_eq!(l.road, self.id);
+        self.parking_restrictions.active(
+            ParkingRestrictions::side_for_lane(l.offset, self.lanes.len()),
+            time,
+        )
+    }
+
     pub fn get_zone<'a>(&self, map: &'a Map) -> Option<&'a Zone> {
         if !self.is_private() {
             return None;
@@ -552,6 +614,7 @@ impl Road {
                 dst_i,
                 lane_type: lane.lt,
                 dir: lane.dir,
+                pocket: lane.pocket,
                 driving_blackhole: false,
                 biking_blackhole: false,
             });
@@ -574,30 +637,6 @@ impl Road {
         }
         panic!("{} doesn't contain both {} and {}", self.id, l1, l2);
     }
-
-    /// A simple classification of if the directed road is stressful or not for cycling. Arterial
-    /// roads without a bike lane match this. Why arterial, instead of looking at speed limits?
-    /// Even on arterial roads with official speed limits lowered, in practice vehicles still
-    /// travel at the speed suggested by the design of the road.
-    // TODO Should elevation matter or not? Flat high-speed roads are still terrifying, but there's
-    // something about slogging up (or flying down!) a pothole-filled road inches from cars.
-    pub fn high_stress_for_bikes(&self, map: &Map, dir: Direction) -> bool {
-        let mut bike_lanes = false;
-        let mut can_use = false;
-        // Can a bike even use it, or is it a highway?
-        for l in &self.lanes {
-            if l.lane_type == LaneType::Biking && l.dir == dir {
-                bike_lanes = true;
-            }
-            if PathConstraints::Bike.can_use(l, map) {
-                can_use = true;
-            }
-        }
-        if !can_use || bike_lanes {
-            return false;
-        }
-        self.get_rank() != osm::RoadRank::Local
-    }
 }
 
 // TODO All of this is kind of deprecated? Some callers seem to really need to still handle lanes
@@ -5,8 +5,8 @@ use serde::{Deserialize, Serialize};
 use abstutil::{deserialize_btreemap, serialize_btreemap};
 
 use crate::{
-    osm, Direction, DrivingSide, IntersectionID, LaneID, Map, RoadID, TurnID, TurnPriority,
-    TurnType,
+    osm, Direction, DrivingSide, IntersectionID, LaneID, Map, RoadID, RoundaboutPriority, TurnID,
+    TurnPriority, TurnType,
 };
 
 // TODO These are old notes, they don't reflect current reality. But some of the ideas here should
@@ -111,7 +111,8 @@ impl ControlStopSign {
 
         // Rank each road based on OSM highway type, and additionally:
         // - Treat cycleways as lower priority than local roads (sad but typical reality)
-        // - Prioritize roundabouts, so they clear out faster than people enter them
+        // - Prioritize roundabouts, so they clear out faster than people enter them (unless the
+        //   map's rules say people in the roundabout yield)
         // - Treat on/off ramps with less priority than the main part of the highway
         // - Lower the priority of service roads
         let mut rank: HashMap<RoadID, (osm::RoadRank, usize)> = HashMap::new();
@@ -121,7 +122,11 @@ impl ControlStopSign {
             let priority = if r.is_cycleway() || r.osm_tags.is(osm::HIGHWAY, "service") {
                 0
             } else if r.osm_tags.is("junction", "roundabout") {
-                3
+                match map.get_config().roundabout_priority {
+                    RoundaboutPriority::Circulating => 3,
+                    // Yield to entering traffic
+                    RoundaboutPriority::Entering => 1,
+                }
             } else if r
                 .osm_tags
                 .get("highway")
@@ -0,0 +1,386 @@
+//! Some restrictions on vehicles only apply during certain hours -- no left turns during the
+//! morning rush hour, or only buses allowed along a main street while people commute. These come
+//! from OSM conditional tags like `motor_vehicle:conditional=no @ (07:00-10:00)` on a way, or
+//! `restriction:conditional=no_left_turn @ (07:00-09:00)` on a turn restriction relation.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use abstutil::Tags;
+use geom::Time;
+
+use crate::{Map, RoadID};
+
+/// A range of hours during one day
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TimeWindow {
+    pub start_time: Time,
+    pub end_time: Time,
+}
+
+impl TimeWindow {
+    pub fn is_active(&self, time: Time) -> bool {
+        time >= self.start_time && time < self.end_time
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{}-{}",
+            hours_mins(self.start_time),
+            hours_mins(self.end_time)
+        )
+    }
+}
+
+/// Restrictions on vehicles using one road that only apply during certain hours. Permanent
+/// restrictions are expressed through lane types, `AccessRestrictions`, and turn restrictions
+/// instead.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct TimeRestrictions {
+    /// Only buses may drive along the road during these hours. Bikes and pedestrians aren't
+    /// affected.
+    pub bus_only: Vec<TimeWindow>,
+    /// Vehicles may not turn from this road onto another one during these hours.
+    pub turn_bans: Vec<(RoadID, TimeWindow)>,
+    /// No vehicles may drive along the road during these hours, like while it's swept or plowed.
+    /// Pedestrians aren't affected.
+    #[serde(default)]
+    pub closed: Vec<TimeWindow>,
+}
+
+impl TimeRestrictions {
+    pub fn new() -> TimeRestrictions {
+        TimeRestrictions::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bus_only.is_empty() && self.turn_bans.is_empty() && self.closed.is_empty()
+    }
+
+    pub fn is_bus_only(&self, time: Time) -> bool {
+        self.bus_only.iter().any(|w| w.is_active(time))
+    }
+
+    pub fn is_closed(&self, time: Time) -> bool {
+        self.closed.iter().any(|w| w.is_active(time))
+    }
+
+    /// The roads that vehicles may not turn onto from this road at some time
+    pub fn banned_turns(&self, time: Time) -> Vec<RoadID> {
+        self.turn_bans
+            .iter()
+            .filter(|(_, w)| w.is_active(time))
+            .map(|(r, _)| *r)
+            .collect()
+    }
+
+    /// Parses restrictions on road `from` in a syntax based on OSM conditional tags, like
+    /// `bus_only @ (07:00-10:00); no_turn_onto Main St @ (07:00-09:00); closed @ (02:00-04:00)`.
+    /// Turns are banned onto every road with that name connected to either end of `from`. Days of
+    /// the week are ignored.
+    pub fn parse(value: &str, from: RoadID, map: &Map) -> Result<TimeRestrictions> {
+        let road = map.get_r(from);
+        TimeRestrictions::parse_with(value, |name| {
+            let mut roads = Vec::new();
+            for i in [road.src_i, road.dst_i] {
+                for r in &map.get_i(i).roads {
+                    if *r != from
+                        && !roads.contains(r)
+                        && map.get_r(*r).get_name(None).eq_ignore_ascii_case(name)
+                    {
+                        roads.push(*r);
+                    }
+                }
+            }
+            if roads.is_empty() {
+                bail!("{} doesn't connect to this road", name);
+            }
+            Ok(roads)
+        })
+    }
+
+    /// The inverse of `parse`
+    pub fn to_conditional(&self, map: &Map) -> String {
+        self.to_conditional_with(|r| map.get_r(r).get_name(None))
+    }
+
+    /// Like `parse`, but `resolve_road` finds the roads that a name in `no_turn_onto` refers to.
+    fn parse_with<F: Fn(&str) -> Result<Vec<RoadID>>>(
+        value: &str,
+        resolve_road: F,
+    ) -> Result<TimeRestrictions> {
+        let mut result = TimeRestrictions::new();
+        for (restriction, window) in parse_osm_conditional(value, |x| Some(x.to_string()))? {
+            if restriction == "bus_only" {
+                result.bus_only.push(window);
+            } else if restriction == "closed" {
+                result.closed.push(window);
+            } else if let Some(to) = restriction.strip_prefix("no_turn_onto ") {
+                for r in resolve_road(to.trim())? {
+                    result.turn_bans.push((r, window));
+                }
+            } else {
+                bail!("unknown restriction {}", restriction);
+            }
+        }
+        Ok(result)
+    }
+
+    fn to_conditional_with<F: Fn(RoadID) -> String>(&self, road_name: F) -> String {
+        let mut clauses = Vec::new();
+        for w in &self.bus_only {
+            clauses.push(format!("bus_only @ ({})", w.describe()));
+        }
+        for (r, w) in &self.turn_bans {
+            // Bans onto both sides of a cross street share a name
+            let clause = format!("no_turn_onto {} @ ({})", road_name(*r), w.describe());
+            if !clauses.contains(&clause) {
+                clauses.push(clause);
+            }
+        }
+        for w in &self.closed {
+            clauses.push(format!("closed @ ({})", w.describe()));
+        }
+        clauses.join("; ")
+    }
+
+    /// `conditional_turns` are (to, raw OSM value) from turn restriction relations.
+    pub(crate) fn from_osm(
+        tags: &Tags,
+        conditional_turns: &[(RoadID, String)],
+    ) -> TimeRestrictions {
+        let mut result = TimeRestrictions::new();
+
+        // A road where general traffic is banned, but buses are allowed
+        if tags.is_any("bus", vec!["yes", "designated"])
+            || tags.is_any("psv", vec!["yes", "designated"])
+        {
+            for key in ["motor_vehicle:conditional", "motorcar:conditional"] {
+                if let Some(value) = tags.get(key) {
+                    match parse_osm_conditional(value, |x| Some(x.to_string())) {
+                        Ok(list) => {
+                            result.bus_only.extend(
+                                list.into_iter().filter(|(x, _)| x == "no").map(|(_, w)| w),
+                            );
+                        }
+                        Err(err) => {
+                            warn!("Can't parse {}={}: {}", key, value, err);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (to, value) in conditional_turns {
+            // Turns onto only one road are restricted, so "only_" restrictions would need to ban
+            // everything else at the intersection. Those aren't supported yet.
+            match parse_osm_conditional(value, |x| Some(x.to_string())) {
+                Ok(list) => {
+                    result.turn_bans.extend(
+                        list.into_iter()
+                            .filter(|(x, _)| x.starts_with("no_"))
+                            .map(|(_, w)| (*to, w)),
+                    );
+                }
+                Err(err) => {
+                    warn!("Can't parse restriction:conditional={}: {}", value, err);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Parses the syntax of OSM conditional tags, like `no_parking @ (07:00-09:00); loading_only @
+/// (Mo-Fr 10:00-16:00)`. Clauses where `parse_value` returns `None` are skipped. The simulation
+/// only covers one day, so days of the week are ignored; only the hours matter.
+pub(crate) fn parse_osm_conditional<T: Clone, F: Fn(&str) -> Option<T>>(
+    value: &str,
+    parse_value: F,
+) -> Result<Vec<(T, TimeWindow)>> {
+    let mut result = Vec::new();
+    for clause in split_top_level(value) {
+        let clause = clause.trim();
+        if clause.is_empty() {
+            continue;
+        }
+        let (raw_value, condition) = match clause.split_once('@') {
+            Some(pair) => pair,
+            None => bail!("{} is missing a condition after @", clause),
+        };
+        let parsed = match parse_value(raw_value.trim()) {
+            Some(x) => x,
+            None => continue,
+        };
+        let condition = condition
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')');
+        let mut found = false;
+        for word in condition.split(|c: char| c.is_whitespace() || c == ',' || c == ';') {
+            if let Some((start, end)) = word.split_once('-') {
+                if !start.contains(':') || !end.contains(':') {
+                    // Probably a range of weekdays
+                    continue;
+                }
+                let start_time = Time::parse(start)?;
+                let end_time = Time::parse(end)?;
+                if end_time <= start_time {
+                    bail!("{} ends before it starts", word);
+                }
+                result.push((
+                    parsed.clone(),
+                    TimeWindow {
+                        start_time,
+                        end_time,
+                    },
+                ));
+                found = true;
+            }
+        }
+        if !found {
+            bail!("{} doesn't specify any hours", clause);
+        }
+    }
+    Ok(result)
+}
+
+pub(crate) fn hours_mins(time: Time) -> String {
+    let mins = (time - Time::START_OF_DAY).inner_seconds() as usize / 60;
+    format!("{:02}:{:02}", mins / 60, mins % 60)
+}
+
+// Split on semicolons that aren't inside parentheses
+fn split_top_level(value: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ';' if depth == 0 => {
+                pieces.push(&value[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    pieces.push(&value[start..]);
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Main St connects through roads #1 and #2, Pine St is #3
+    fn resolve_road(name: &str) -> Result<Vec<RoadID>> {
+        match name {
+            "Main St" => Ok(vec![RoadID(1), RoadID(2)]),
+            "Pine St" => Ok(vec![RoadID(3)]),
+            _ => bail!("{} doesn't connect to this road", name),
+        }
+    }
+
+    fn road_name(r: RoadID) -> String {
+        if r == RoadID(3) {
+            "Pine St".to_string()
+        } else {
+            "Main St".to_string()
+        }
+    }
+
+    fn hours(x: usize) -> Time {
+        Time::START_OF_DAY + geom::Duration::hours(x)
+    }
+
+    #[test]
+    fn test_parse_time_restrictions() {
+        let input = "bus_only @ (07:00-10:00); no_turn_onto Pine St @ (07:00-09:00)";
+        let parsed = TimeRestrictions::parse_with(input, resolve_road).unwrap();
+        assert_eq!(parsed.bus_only.len(), 1);
+        assert_eq!(parsed.banned_turns(Time::START_OF_DAY), Vec::new());
+        assert_eq!(parsed.banned_turns(hours(8)), vec![RoadID(3)]);
+        assert_eq!(parsed.to_conditional_with(road_name), input);
+    }
+
+    #[test]
+    fn test_parse_time_restrictions_table() {
+        let mut ok = true;
+        for (input, bus_only, turn_bans, closed, output) in vec![
+            ("", 0, vec![], 0, ""),
+            (
+                "closed @ (02:00-04:00)",
+                0,
+                vec![],
+                1,
+                "closed @ (02:00-04:00)",
+            ),
+            (
+                "bus_only @ (Mo-Fr 07:00-10:00,16:00-19:00)",
+                2,
+                vec![],
+                0,
+                "bus_only @ (07:00-10:00); bus_only @ (16:00-19:00)",
+            ),
+            // Both sides of the cross street are banned, but only written once
+            (
+                "no_turn_onto Main St @ (07:00-09:00)",
+                0,
+                vec![RoadID(1), RoadID(2)],
+                0,
+                "no_turn_onto Main St @ (07:00-09:00)",
+            ),
+            (
+                "closed @ (02:00-04:00);no_turn_onto Pine St @ (16:00-18:00)",
+                0,
+                vec![RoadID(3)],
+                1,
+                "no_turn_onto Pine St @ (16:00-18:00); closed @ (02:00-04:00)",
+            ),
+        ] {
+            let parsed = match TimeRestrictions::parse_with(input, resolve_road) {
+                Ok(x) => x,
+                Err(err) => {
+                    println!("Parsing {} failed: {}", input, err);
+                    ok = false;
+                    continue;
+                }
+            };
+            let actual_turn_bans: Vec<RoadID> = parsed.turn_bans.iter().map(|(r, _)| *r).collect();
+            if parsed.bus_only.len() != bus_only
+                || actual_turn_bans != turn_bans
+                || parsed.closed.len() != closed
+            {
+                println!("Parsing {} gave {:?}", input, parsed);
+                ok = false;
+            }
+            let actual_output = parsed.to_conditional_with(road_name);
+            if actual_output != output {
+                println!("{} was written back as {}", input, actual_output);
+                ok = false;
+            }
+        }
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_parse_time_restrictions_errors() {
+        for input in [
+            "no_turn_onto Elm St @ (07:00-09:00)",
+            "no_turn_onto #12 @ (07:00-09:00)",
+            "bus_only",
+            "bus_only @ (Mo-Fr)",
+            "closed @ (10:00-09:00)",
+            "no_parking @ (07:00-09:00)",
+        ] {
+            assert!(
+                TimeRestrictions::parse_with(input, resolve_road).is_err(),
+                "{} should fail to parse",
+                input
+            );
+        }
+    }
+}
@@ -16,6 +16,16 @@ use crate::{
 // https://en.wikipedia.org/wiki/Preferred_walking_speed
 const CROSSWALK_PACE: Speed = Speed::const_meters_per_second(1.4);
 
+// Inputs to the ITE kinematic formulas for clearance intervals: yellow lasts long enough for a
+// driver to react and comfortably stop, and all-red long enough for a vehicle that entered at the
+// end of the yellow to clear the intersection.
+const PERCEPTION_REACTION_TIME: Duration = Duration::const_seconds(1.0);
+const COMFORTABLE_DECELERATION: f64 = 3.05;
+const CLEARANCE_VEHICLE_LENGTH: Distance = Distance::const_meters(6.1);
+const MIN_YELLOW: Duration = Duration::const_seconds(3.0);
+const MAX_YELLOW: Duration = Duration::const_seconds(6.0);
+const MIN_ALL_RED: Duration = Duration::const_seconds(1.0);
+
 /// A traffic signal consists of a sequence of Stages that repeat in a cycle. Most Stages last for a
 /// fixed duration. During a single Stage, some movements are protected (can proceed with the
 /// highest priority), while others are permitted (have to yield before proceeding).
@@ -24,6 +34,10 @@ pub struct ControlTrafficSignal {
     pub id: IntersectionID,
     pub stages: Vec<Stage>,
     pub offset: Duration,
+    /// Bikes wait in an advanced stop box in front of motor vehicles, so they start first.
+    pub bike_box: bool,
+    /// Bikes turn left in two stages instead of merging across traffic.
+    pub two_stage_bike_left_turns: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -33,6 +47,40 @@ pub struct Stage {
     // TODO Not renaming this, because this is going to change radically in
     // https://github.com/a-b-street/abstreet/pull/298 anyway
     pub stage_type: StageType,
+    /// The end of the stage's duration is used for a yellow, then an all-red interval. Nothing
+    /// new may start a movement that's ending during this time.
+    #[serde(default)]
+    pub yellow: Duration,
+    #[serde(default)]
+    pub all_red: Duration,
+}
+
+/// Two protected movements in the same stage that cross each other
+#[derive(Clone, Debug, PartialEq)]
+pub struct StageConflict {
+    pub stage: usize,
+    pub movements: (MovementID, MovementID),
+}
+
+/// A stage whose yellow or all-red interval is shorter than recommended for the approach speeds
+/// and size of the intersection
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClearanceShortfall {
+    pub stage: usize,
+    pub yellow: Duration,
+    pub all_red: Duration,
+    pub recommended_yellow: Duration,
+    pub recommended_all_red: Duration,
+}
+
+/// A stage that's too short for somebody to finish crossing the street
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossingShortfall {
+    pub stage: usize,
+    /// How long the longest protected crosswalk takes to cross
+    pub needed: Duration,
+    /// The duration of the stage
+    pub given: Duration,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -83,18 +131,128 @@ impl ControlTrafficSignal {
     }
 
     pub fn get_min_crossing_time(&self, idx: usize, i: &Intersection) -> Duration {
+        self.get_crossing_time_at_pace(idx, i, CROSSWALK_PACE)
+    }
+
+    /// How long does it take somebody walking at this pace to finish the longest crosswalk
+    /// protected during one stage?
+    pub fn get_crossing_time_at_pace(&self, idx: usize, i: &Intersection, pace: Speed) -> Duration {
         let mut max_distance = Distance::meters(0.0);
         for movement in &self.stages[idx].protected_movements {
             if movement.crosswalk {
                 max_distance = max_distance.max(i.movements[movement].geom.length());
             }
         }
-        let time = max_distance / CROSSWALK_PACE;
+        let time = max_distance / pace;
         assert!(time >= Duration::ZERO);
         // Round up because it is converted to a usize elsewhere
         Duration::seconds(time.inner_seconds().ceil())
     }
 
+    /// Finds every stage that doesn't give somebody walking at this pace enough time to finish
+    /// crossing. For variable stages, only the minimum duration is guaranteed.
+    pub fn find_short_crossings(&self, i: &Intersection, pace: Speed) -> Vec<CrossingShortfall> {
+        let mut results = Vec::new();
+        for (idx, stage) in self.stages.iter().enumerate() {
+            let needed = self.get_crossing_time_at_pace(idx, i, pace);
+            let given = stage.stage_type.simple_duration();
+            if given < needed {
+                results.push(CrossingShortfall {
+                    stage: idx,
+                    needed,
+                    given,
+                });
+            }
+        }
+        results
+    }
+
+    /// Finds every pair of protected movements in the same stage that conflict.
+    pub fn find_conflicts(&self, i: &Intersection) -> Vec<StageConflict> {
+        let mut results = Vec::new();
+        for (idx, stage) in self.stages.iter().enumerate() {
+            for m1 in &stage.protected_movements {
+                for m2 in stage.protected_movements.range(m1..).skip(1) {
+                    if i.movements[m1].conflicts_with(&i.movements[m2]) {
+                        results.push(StageConflict {
+                            stage: idx,
+                            movements: (*m1, *m2),
+                        });
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// Vehicle movements allowed during one stage, but not the next, need a yellow and all-red
+    /// interval at the end of the stage. Returns the (yellow, all-red) durations the ITE formulas
+    /// give for the fastest approach and the longest movement, rounded up to whole seconds.
+    pub fn recommended_clearance(&self, idx: usize, map: &Map) -> (Duration, Duration) {
+        let i = map.get_i(self.id);
+        let stage = &self.stages[idx];
+        let next = &self.stages[(idx + 1) % self.stages.len()];
+        let mut yellow = Duration::ZERO;
+        let mut all_red = Duration::ZERO;
+        for m in stage
+            .protected_movements
+            .iter()
+            .chain(stage.yield_movements.iter())
+        {
+            if m.crosswalk || next.get_priority_of_movement(*m) != TurnPriority::Banned {
+                continue;
+            }
+            let speed = map.get_r(m.from.road).speed_limit;
+            yellow = yellow.max(
+                PERCEPTION_REACTION_TIME
+                    + Duration::seconds(
+                        speed.inner_meters_per_second() / (2.0 * COMFORTABLE_DECELERATION),
+                    ),
+            );
+            all_red =
+                all_red.max((i.movements[m].geom.length() + CLEARANCE_VEHICLE_LENGTH) / speed);
+        }
+        if yellow == Duration::ZERO {
+            return (Duration::ZERO, Duration::ZERO);
+        }
+        (
+            Duration::seconds(yellow.inner_seconds().ceil()).clamp(MIN_YELLOW, MAX_YELLOW),
+            Duration::seconds(all_red.inner_seconds().ceil()).max(MIN_ALL_RED),
+        )
+    }
+
+    /// Finds every stage with a yellow or all-red interval shorter than recommended.
+    pub fn find_short_clearances(&self, map: &Map) -> Vec<ClearanceShortfall> {
+        let mut results = Vec::new();
+        for (idx, stage) in self.stages.iter().enumerate() {
+            let (recommended_yellow, recommended_all_red) = self.recommended_clearance(idx, map);
+            if stage.yellow < recommended_yellow || stage.all_red < recommended_all_red {
+                results.push(ClearanceShortfall {
+                    stage: idx,
+                    yellow: stage.yellow,
+                    all_red: stage.all_red,
+                    recommended_yellow,
+                    recommended_all_red,
+                });
+            }
+        }
+        results
+    }
+
+    /// Resolves every conflict found by `find_conflicts` by making one of the movements yield.
+    /// Crosswalks can't yield, so if one is involved, the vehicle movement yields to it.
+    pub fn demote_conflicts(&mut self, i: &Intersection) {
+        while let Some(conflict) = self.find_conflicts(i).into_iter().next() {
+            let (m1, m2) = conflict.movements;
+            let demote = if m2.crosswalk { m1 } else { m2 };
+            let stage = &mut self.stages[conflict.stage];
+            stage.protected_movements.remove(&demote);
+            if !demote.crosswalk {
+                stage.yield_movements.insert(demote);
+            }
+        }
+    }
+
     pub fn validate(&self, i: &Intersection) -> Result<()> {
         // Does the assignment cover the correct set of movements?
         let expected_movements: BTreeSet<MovementID> = i.movements.keys().cloned().collect();
@@ -137,6 +295,19 @@ impl ControlTrafficSignal {
                 // TODO Maybe make UnmarkedCrossing yield
                 assert!(!m.turn_type.pedestrian_crossing())
             }
+            // Is there any green time left after the clearance intervals?
+            if let StageType::Fixed(d) = stage.stage_type {
+                if stage.yellow + stage.all_red >= d {
+                    bail!(
+                        "Stage {} of {} lasts {}, but the yellow and all-red intervals take {}",
+                        stage_index + 1,
+                        self.id,
+                        d,
+                        stage.yellow + stage.all_red
+                    );
+                }
+            }
+
             // Is there enough time in each stage to walk across the crosswalk
             let min_crossing_time = self.get_min_crossing_time(stage_index, i);
             if stage.stage_type.simple_duration() < min_crossing_time {
@@ -301,6 +472,8 @@ impl Stage {
             yield_movements: BTreeSet::new(),
             // TODO Set a default
             stage_type: StageType::Fixed(Duration::seconds(30.0)),
+            yellow: Duration::ZERO,
+            all_red: Duration::ZERO,
         }
     }
 
@@ -410,10 +583,14 @@ impl ControlTrafficSignal {
                                 )
                             }
                         },
+                        yellow_seconds: s.yellow.inner_seconds() as usize,
+                        all_red_seconds: s.all_red.inner_seconds() as usize,
                     })
                     .collect(),
                 offset_seconds: self.offset.inner_seconds() as usize,
             }],
+            bike_box: self.bike_box,
+            two_stage_bike_left_turns: self.two_stage_bike_left_turns,
         }
     }
 
@@ -465,6 +642,8 @@ impl ControlTrafficSignal {
                             )
                         }
                     },
+                    yellow: Duration::seconds(s.yellow_seconds as f64),
+                    all_red: Duration::seconds(s.all_red_seconds as f64),
                 });
             } else {
                 bail!("{}", errors.join("; "));
@@ -474,6 +653,8 @@ impl ControlTrafficSignal {
             id,
             stages,
             offset: Duration::seconds(plan.offset_seconds as f64),
+            bike_box: raw.bike_box,
+            two_stage_bike_left_turns: raw.two_stage_bike_left_turns,
         };
         ts.validate(map.get_i(id))?;
         Ok(ts)
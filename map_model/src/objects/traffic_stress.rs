@@ -0,0 +1,181 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use geom::Speed;
+
+use crate::{osm, BufferType, Direction, LaneType, Map, PathConstraints, Road};
+
+/// The Level of Traffic Stress for cycling, from "Low-Stress Bicycling and Network Connectivity"
+/// (Mekuria, Furth, and Nixon, 2012). LTS 1 is comfortable for children, and LTS 4 is only
+/// tolerated by the strong and fearless.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TrafficStress {
+    LTS1,
+    LTS2,
+    LTS3,
+    LTS4,
+}
+
+impl TrafficStress {
+    pub fn all() -> Vec<TrafficStress> {
+        vec![
+            TrafficStress::LTS1,
+            TrafficStress::LTS2,
+            TrafficStress::LTS3,
+            TrafficStress::LTS4,
+        ]
+    }
+
+    fn worse(self) -> TrafficStress {
+        match self {
+            TrafficStress::LTS1 => TrafficStress::LTS2,
+            TrafficStress::LTS2 => TrafficStress::LTS3,
+            TrafficStress::LTS3 | TrafficStress::LTS4 => TrafficStress::LTS4,
+        }
+    }
+}
+
+impl fmt::Display for TrafficStress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrafficStress::LTS1 => write!(f, "LTS 1"),
+            TrafficStress::LTS2 => write!(f, "LTS 2"),
+            TrafficStress::LTS3 => write!(f, "LTS 3"),
+            TrafficStress::LTS4 => write!(f, "LTS 4"),
+        }
+    }
+}
+
+/// Configures how roads are classified into levels of traffic stress, and which levels count as
+/// high-stress.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StressThresholds {
+    /// Mixing with traffic at or below this speed limit is LTS 1
+    pub mixed_traffic_low_speed: Speed,
+    /// Mixing with traffic at or below this speed limit is LTS 2. Anything faster is LTS 4.
+    pub mixed_traffic_high_speed: Speed,
+    /// A bike lane on a road at or below this speed limit is LTS 1
+    pub bike_lane_low_speed: Speed,
+    /// A bike lane on a road at or below this speed limit is LTS 3. Anything faster is LTS 4.
+    pub bike_lane_high_speed: Speed,
+    /// Roads with more driving lanes than this in one direction are one level more stressful
+    pub max_lanes_per_direction: usize,
+    /// Bike lanes next to parked cars risk dooring, so treat them as one level more stressful
+    pub penalize_parking_adjacent_bike_lanes: bool,
+    /// Roads at this level or worse are considered high-stress
+    pub high_stress: TrafficStress,
+}
+
+impl Default for StressThresholds {
+    fn default() -> Self {
+        Self {
+            mixed_traffic_low_speed: Speed::miles_per_hour(25.0),
+            mixed_traffic_high_speed: Speed::miles_per_hour(30.0),
+            bike_lane_low_speed: Speed::miles_per_hour(30.0),
+            bike_lane_high_speed: Speed::miles_per_hour(35.0),
+            max_lanes_per_direction: 1,
+            penalize_parking_adjacent_bike_lanes: true,
+            high_stress: TrafficStress::LTS3,
+        }
+    }
+}
+
+impl Road {
+    /// Classify cycling in one direction of this road. Returns `None` if bikes can't use the road
+    /// at all.
+    pub fn traffic_stress(
+        &self,
+        map: &Map,
+        dir: Direction,
+        thresholds: &StressThresholds,
+    ) -> Option<TrafficStress> {
+        if !self
+            .lanes
+            .iter()
+            .any(|l| PathConstraints::Bike.can_use(l, map))
+        {
+            return None;
+        }
+        let driving_lanes = self
+            .lanes
+            .iter()
+            .filter(|l| l.dir == dir && l.lane_type == LaneType::Driving)
+            .count();
+        // Cycleways and other roads without any traffic
+        if !self.lanes.iter().any(|l| l.lane_type == LaneType::Driving) {
+            return Some(TrafficStress::LTS1);
+        }
+
+        let bike_lane = self
+            .lanes
+            .iter()
+            .position(|l| l.lane_type == LaneType::Biking && l.dir == dir);
+        let mut stress = if let Some(idx) = bike_lane {
+            let neighbors: Vec<LaneType> = [idx.checked_sub(1), Some(idx + 1)]
+                .into_iter()
+                .flatten()
+                .filter_map(|i| self.lanes.get(i))
+                .map(|l| l.lane_type)
+                .collect();
+            // Physical separation from traffic, not just paint
+            if neighbors
+                .iter()
+                .any(|lt| matches!(lt, LaneType::Buffer(buffer) if *buffer != BufferType::Stripes))
+            {
+                return Some(TrafficStress::LTS1);
+            }
+
+            let mut stress = if self.speed_limit <= thresholds.bike_lane_low_speed {
+                TrafficStress::LTS1
+            } else if self.speed_limit <= thresholds.bike_lane_high_speed {
+                TrafficStress::LTS3
+            } else {
+                TrafficStress::LTS4
+            };
+            if thresholds.penalize_parking_adjacent_bike_lanes
+                && neighbors.contains(&LaneType::Parking)
+            {
+                stress = stress.worse();
+            }
+            stress
+        } else {
+            self.mixed_traffic_stress(thresholds)
+        };
+        if driving_lanes > thresholds.max_lanes_per_direction {
+            stress = stress.worse();
+        }
+        Some(stress)
+    }
+
+    /// The stress of sharing this road with traffic, or crossing it, ignoring any bike lanes
+    pub(crate) fn mixed_traffic_stress(&self, thresholds: &StressThresholds) -> TrafficStress {
+        let mut stress = if self.speed_limit <= thresholds.mixed_traffic_low_speed {
+            TrafficStress::LTS1
+        } else if self.speed_limit <= thresholds.mixed_traffic_high_speed {
+            TrafficStress::LTS2
+        } else {
+            TrafficStress::LTS4
+        };
+        // Even where the official speed limit is low, arterial roads are designed for, and in
+        // practice carry, faster and heavier traffic
+        if self.get_rank() != osm::RoadRank::Local {
+            stress = stress.max(TrafficStress::LTS3);
+        }
+        stress
+    }
+
+    /// Is cycling in one direction of this road high-stress, according to the thresholds?
+    // TODO Should elevation matter or not? Flat high-speed roads are still terrifying, but there's
+    // something about slogging up (or flying down!) a pothole-filled road inches from cars.
+    pub fn high_stress_for_bikes(
+        &self,
+        map: &Map,
+        dir: Direction,
+        thresholds: &StressThresholds,
+    ) -> bool {
+        self.traffic_stress(map, dir, thresholds)
+            .map(|stress| stress >= thresholds.high_stress)
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,96 @@
+//! Rebuilding contraction hierarchies after applying edits is slow, and happens every time a
+//! proposal is loaded. Keep the results on disk, keyed by everything that affects them. Edits
+//! made interactively are usually never seen again, so they're not cached; see
+//! `Map::recalculate_pathfinding_after_loading_proposal`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use abstutil::Timer;
+
+use crate::{Map, Pathfinder};
+
+/// When the cache grows past this, the least recently used files are deleted.
+const MAX_CACHE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Returns the pathfinder previously prepared for the map's current edits and routing params.
+pub fn load(map: &Map, timer: &mut Timer) -> Option<Pathfinder> {
+    let path = abstio::path_pathfinder_cache(cache_key(map)?);
+    if !abstio::file_exists(&path) {
+        return None;
+    }
+    match abstio::maybe_read_binary::<Pathfinder>(path.clone(), timer) {
+        Ok(pathfinder) => {
+            // Remember this was used recently
+            let _ = filetime::set_file_mtime(
+                &path,
+                filetime::FileTime::from_system_time(SystemTime::now()),
+            );
+            Some(pathfinder)
+        }
+        Err(err) => {
+            warn!("Ignoring broken pathfinder cache {}: {}", path, err);
+            abstio::delete_file(path);
+            None
+        }
+    }
+}
+
+pub fn save(map: &Map, pathfinder: &Pathfinder) {
+    if let Some(key) = cache_key(map) {
+        abstio::write_binary(abstio::path_pathfinder_cache(key), pathfinder);
+        evict();
+    }
+}
+
+/// None if there's nothing worth caching, because the map isn't edited or didn't come from a file.
+fn cache_key(map: &Map) -> Option<u64> {
+    if map.get_edits().commands.is_empty() {
+        return None;
+    }
+    // Regenerating the map file changes this
+    let modified = std::fs::metadata(map.get_name().path())
+        .ok()?
+        .modified()
+        .ok()?;
+
+    let mut edits = map.get_edits().to_permanent(map);
+    // Renaming or describing a proposal doesn't affect pathfinding
+    edits.edits_name = String::new();
+    edits.proposal_description.clear();
+    edits.proposal_link = None;
+
+    // The hash isn't stable across builds. That's fine; a new build might change the pathfinder
+    // format anyway, so the old files just go unused until they're evicted.
+    let mut hasher = DefaultHasher::new();
+    map.get_name().path().hash(&mut hasher);
+    modified.hash(&mut hasher);
+    abstutil::to_json_terse(&edits).hash(&mut hasher);
+    abstutil::to_json_terse(map.routing_params()).hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+fn evict() {
+    let mut files = Vec::new();
+    let mut total_bytes = 0;
+    for path in abstio::list_dir(abstio::path_all_pathfinder_caches()) {
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            total_bytes += metadata.len();
+            files.push((
+                metadata.modified().unwrap_or(UNIX_EPOCH),
+                metadata.len(),
+                path,
+            ));
+        }
+    }
+    // Oldest first
+    files.sort();
+    for (_, bytes, path) in files {
+        if total_bytes <= MAX_CACHE_BYTES {
+            break;
+        }
+        abstio::delete_file(path);
+        total_bytes -= bytes;
+    }
+}
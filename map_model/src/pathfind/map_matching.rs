@@ -0,0 +1,47 @@
+//! Matches GPS traces to the roads somebody most likely followed, so observed routes can be
+//! compared with the ones the pathfinder picks.
+
+use geom::{Distance, FindClosest, Pt2D};
+
+use crate::{Map, PathConstraints, RoadID};
+
+/// Points further than this from any road are treated as noise.
+const MAX_DIST_FROM_ROAD: Distance = Distance::const_meters(20.0);
+
+/// Snaps GPS points to the nearest usable road.
+pub struct MapMatcher {
+    closest: FindClosest<RoadID>,
+}
+
+impl MapMatcher {
+    /// Only roads that somebody with these constraints can use will be matched.
+    pub fn new(map: &Map, constraints: PathConstraints) -> MapMatcher {
+        let mut closest = FindClosest::new(map.get_bounds());
+        for r in map.all_roads() {
+            if constraints.can_use_road(r, map) {
+                closest.add(r.id, r.center_pts.points());
+            }
+        }
+        MapMatcher { closest }
+    }
+
+    /// Returns the sequence of roads along a trace, in order. Points far from any road are
+    /// skipped. When the trace briefly jumps to another road and back, the detour is treated as
+    /// GPS noise. Sparse traces may skip over short roads entirely.
+    pub fn match_trace(&self, pts: &[Pt2D]) -> Vec<RoadID> {
+        let mut roads: Vec<RoadID> = Vec::new();
+        for pt in pts {
+            if let Some((r, _)) = self.closest.closest_pt(*pt, MAX_DIST_FROM_ROAD) {
+                if roads.last() == Some(&r) {
+                    continue;
+                }
+                if roads.len() >= 2 && roads[roads.len() - 2] == r {
+                    roads.pop();
+                    continue;
+                }
+                roads.push(r);
+            }
+        }
+        roads
+    }
+}
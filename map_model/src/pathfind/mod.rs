@@ -1,6 +1,6 @@
 //! Everything related to pathfinding through a map for different types of agents.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use enumset::EnumSetType;
 use serde::{Deserialize, Serialize};
@@ -8,14 +8,20 @@ use serde::{Deserialize, Serialize};
 use geom::Duration;
 
 pub use self::engine::CreateEngine;
+pub use self::map_matching::MapMatcher;
 pub use self::pathfinder::{Pathfinder, PathfinderCache, PathfinderCaching};
 pub use self::v1::{Path, PathRequest, PathStep};
 pub use self::v2::{PathStepV2, PathV2};
 pub use self::vehicles::vehicle_cost;
 pub use self::walking::WalkingNode;
-use crate::{osm, Lane, LaneID, LaneType, Map, MovementID, Road, RoadID, TurnType};
+use crate::{
+    osm, Lane, LaneID, LaneType, Map, MovementID, Road, RoadID, StressThresholds, TurnType,
+};
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod disk_cache;
 mod engine;
+mod map_matching;
 mod node_map;
 mod pathfinder;
 // TODO tmp
@@ -161,6 +167,23 @@ pub(crate) fn zone_cost(mvmnt: MovementID, constraints: PathConstraints, map: &M
     }
 }
 
+/// Penalize cars entering one of `RoutingParams::through_traffic_cells` from outside.
+pub(crate) fn through_traffic_cost(
+    mvmnt: MovementID,
+    constraints: PathConstraints,
+    params: &RoutingParams,
+) -> Duration {
+    if constraints != PathConstraints::Car {
+        return Duration::ZERO;
+    }
+    match params.through_traffic_cells.get(&mvmnt.to.road) {
+        Some(cell) if params.through_traffic_cells.get(&mvmnt.from.road) != Some(cell) => {
+            params.through_traffic_penalty
+        }
+        _ => Duration::ZERO,
+    }
+}
+
 /// Tuneable parameters for all types of routing.
 // These will maybe become part of the PathRequest later, but that's an extremely invasive and
 // space-expensive change right now.
@@ -181,8 +204,11 @@ pub struct RoutingParams {
     // further "delay" on top of that!)
     // TODO But even steeper roads matter more!
     pub avoid_steep_incline_penalty: f64,
-    // If the road is `high_stress_for_bikes`, multiply by the base cost.
+    // If the road is `high_stress_for_bikes`, multiply by the base cost. If only the intersection
+    // is, apply the penalty to the time spent crossing it.
     pub avoid_high_stress: f64,
+    /// Determines which roads and intersections are high-stress
+    pub bike_stress: StressThresholds,
 
     /// When crossing an arterial or highway road, multiply the base cost by this penalty. When
     /// greater than 1, this will encourage routes to use local roads more.
@@ -196,6 +222,18 @@ pub struct RoutingParams {
     /// Don't allow movements between these roads at all. Only affects vehicle routing, not
     /// pedestrian.
     pub avoid_movements_between: BTreeSet<(RoadID, RoadID)>,
+
+    /// Roads grouped into cells, like the interiors of low-traffic neighborhoods. When a car
+    /// enters a cell from outside, `through_traffic_penalty` is added. This approximates
+    /// restrictions enforced by cameras instead of physical filters -- somebody whose destination
+    /// is inside has to pay the penalty anyway, but drivers cutting through will avoid it when
+    /// they can.
+    pub through_traffic_cells: BTreeMap<RoadID, usize>,
+    pub through_traffic_penalty: Duration,
+
+    /// The roads inside charging cordons, and the price of entering each one from outside,
+    /// expressed as a delay. Only filled out by `Map::routing_params_at` while a cordon is active.
+    pub cordon_charges: Vec<(BTreeSet<RoadID>, Duration)>,
 }
 
 impl Default for RoutingParams {
@@ -211,11 +249,18 @@ impl Default for RoutingParams {
 
             avoid_steep_incline_penalty: 1.0,
             avoid_high_stress: 1.0,
+            bike_stress: StressThresholds::default(),
 
             main_road_penalty: 1.0,
 
             avoid_roads: BTreeSet::new(),
             avoid_movements_between: BTreeSet::new(),
+
+            through_traffic_cells: BTreeMap::new(),
+            // High enough that almost nobody risks a fine to save a few minutes
+            through_traffic_penalty: Duration::const_seconds(3600.0),
+
+            cordon_charges: Vec::new(),
         }
     }
 }
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use thread_local::ThreadLocal;
 
-use abstutil::{Timer, VecMap};
+use abstutil::{LazyDeserialize, Timer, VecMap};
 use geom::Duration;
 
 use crate::pathfind::engine::CreateEngine;
@@ -15,14 +15,17 @@ use crate::{
     TransitRouteID, TransitStopID,
 };
 
+/// The graphs are big and slow to deserialize, and many uses of a map don't pathfind for every
+/// mode, or at all. So each graph is only deserialized the first time it's used, which makes
+/// loading a map much faster.
 #[derive(Serialize, Deserialize)]
 pub struct Pathfinder {
-    car_graph: VehiclePathfinder,
-    bike_graph: VehiclePathfinder,
-    bus_graph: VehiclePathfinder,
-    train_graph: VehiclePathfinder,
-    walking_graph: SidewalkPathfinder,
-    walking_with_transit_graph: SidewalkPathfinder,
+    car_graph: LazyDeserialize<VehiclePathfinder>,
+    bike_graph: LazyDeserialize<VehiclePathfinder>,
+    bus_graph: LazyDeserialize<VehiclePathfinder>,
+    train_graph: LazyDeserialize<VehiclePathfinder>,
+    walking_graph: LazyDeserialize<SidewalkPathfinder>,
+    walking_with_transit_graph: LazyDeserialize<SidewalkPathfinder>,
 
     // These params cover the main graphs
     params: RoutingParams,
@@ -67,12 +70,12 @@ impl Pathfinder {
     /// work.
     pub(crate) fn empty() -> Pathfinder {
         Pathfinder {
-            car_graph: VehiclePathfinder::empty(),
-            bike_graph: VehiclePathfinder::empty(),
-            bus_graph: VehiclePathfinder::empty(),
-            train_graph: VehiclePathfinder::empty(),
-            walking_graph: SidewalkPathfinder::empty(),
-            walking_with_transit_graph: SidewalkPathfinder::empty(),
+            car_graph: LazyDeserialize::new(VehiclePathfinder::empty()),
+            bike_graph: LazyDeserialize::new(VehiclePathfinder::empty()),
+            bus_graph: LazyDeserialize::new(VehiclePathfinder::empty()),
+            train_graph: LazyDeserialize::new(VehiclePathfinder::empty()),
+            walking_graph: LazyDeserialize::new(SidewalkPathfinder::empty()),
+            walking_with_transit_graph: LazyDeserialize::new(SidewalkPathfinder::empty()),
             params: RoutingParams::default(),
             cached_alternatives: ThreadLocal::new(),
         }
@@ -122,12 +125,12 @@ impl Pathfinder {
         let walking_with_transit_graph = SidewalkPathfinder::empty();
 
         Pathfinder {
-            car_graph,
-            bike_graph,
-            bus_graph,
-            train_graph,
-            walking_graph,
-            walking_with_transit_graph,
+            car_graph: LazyDeserialize::new(car_graph),
+            bike_graph: LazyDeserialize::new(bike_graph),
+            bus_graph: LazyDeserialize::new(bus_graph),
+            train_graph: LazyDeserialize::new(train_graph),
+            walking_graph: LazyDeserialize::new(walking_graph),
+            walking_with_transit_graph: LazyDeserialize::new(walking_with_transit_graph),
 
             params,
             cached_alternatives: ThreadLocal::new(),
@@ -169,19 +172,40 @@ impl Pathfinder {
             timer.start(format!("prepare pathfinding for just {:?}", constraints));
             match constraints {
                 PathConstraints::Pedestrian => {
-                    p.walking_graph = SidewalkPathfinder::new(map, None, &engine);
+                    p.walking_graph =
+                        LazyDeserialize::new(SidewalkPathfinder::new(map, None, &engine));
                 }
                 PathConstraints::Car => {
-                    p.car_graph = VehiclePathfinder::new(map, constraints, &params, &engine);
+                    p.car_graph = LazyDeserialize::new(VehiclePathfinder::new(
+                        map,
+                        constraints,
+                        &params,
+                        &engine,
+                    ));
                 }
                 PathConstraints::Bike => {
-                    p.bike_graph = VehiclePathfinder::new(map, constraints, &params, &engine);
+                    p.bike_graph = LazyDeserialize::new(VehiclePathfinder::new(
+                        map,
+                        constraints,
+                        &params,
+                        &engine,
+                    ));
                 }
                 PathConstraints::Bus => {
-                    p.bus_graph = VehiclePathfinder::new(map, constraints, &params, &engine);
+                    p.bus_graph = LazyDeserialize::new(VehiclePathfinder::new(
+                        map,
+                        constraints,
+                        &params,
+                        &engine,
+                    ));
                 }
                 PathConstraints::Train => {
-                    p.train_graph = VehiclePathfinder::new(map, constraints, &params, &engine);
+                    p.train_graph = LazyDeserialize::new(VehiclePathfinder::new(
+                        map,
+                        constraints,
+                        &params,
+                        &engine,
+                    ));
                 }
             }
             timer.stop(format!("prepare pathfinding for just {:?}", constraints));
@@ -191,8 +215,45 @@ impl Pathfinder {
     }
 
     pub(crate) fn finalize_transit(&mut self, map: &Map, engine: &CreateEngine) {
-        self.walking_with_transit_graph =
-            SidewalkPathfinder::new(map, Some((&self.bus_graph, &self.train_graph)), engine);
+        self.walking_with_transit_graph = LazyDeserialize::new(SidewalkPathfinder::new(
+            map,
+            Some((
+                self.vehicle_graph(PathConstraints::Bus, map),
+                self.vehicle_graph(PathConstraints::Train, map),
+            )),
+            engine,
+        ));
+    }
+
+    // A graph that can't be deserialized, probably because it was saved by an older version, is
+    // rebuilt from the map. That's slow, but much better than failing mid-simulation.
+    fn vehicle_graph(&self, constraints: PathConstraints, map: &Map) -> &VehiclePathfinder {
+        let graph = match constraints {
+            PathConstraints::Car => &self.car_graph,
+            PathConstraints::Bike => &self.bike_graph,
+            PathConstraints::Bus => &self.bus_graph,
+            PathConstraints::Train => &self.train_graph,
+            PathConstraints::Pedestrian => unreachable!(),
+        };
+        graph.get_or_rebuild(|| rebuild_vehicle_graph(map, constraints, &self.params))
+    }
+
+    fn sidewalk_graph(&self, map: &Map) -> &SidewalkPathfinder {
+        self.walking_graph
+            .get_or_rebuild(|| SidewalkPathfinder::new(map, None, &CreateEngine::CH))
+    }
+
+    fn sidewalk_with_transit_graph(&self, map: &Map) -> &SidewalkPathfinder {
+        self.walking_with_transit_graph.get_or_rebuild(|| {
+            SidewalkPathfinder::new(
+                map,
+                Some((
+                    self.vehicle_graph(PathConstraints::Bus, map),
+                    self.vehicle_graph(PathConstraints::Train, map),
+                )),
+                &CreateEngine::CH,
+            )
+        })
     }
 
     /// Finds a path from a start to an end for a certain type of agent.
@@ -204,11 +265,8 @@ impl Pathfinder {
     /// built into this Pathfinder.
     pub fn pathfind_v2(&self, req: PathRequest, map: &Map) -> Option<PathV2> {
         match req.constraints {
-            PathConstraints::Pedestrian => self.walking_graph.pathfind(req, map),
-            PathConstraints::Car => self.car_graph.pathfind(req, map),
-            PathConstraints::Bike => self.bike_graph.pathfind(req, map),
-            PathConstraints::Bus => self.bus_graph.pathfind(req, map),
-            PathConstraints::Train => self.train_graph.pathfind(req, map),
+            PathConstraints::Pedestrian => self.sidewalk_graph(map).pathfind(req, map),
+            constraints => self.vehicle_graph(constraints, map).pathfind(req, map),
         }
     }
 
@@ -226,11 +284,8 @@ impl Pathfinder {
         let constraints = req.constraints;
         if params == &self.params {
             return match constraints {
-                PathConstraints::Pedestrian => self.walking_graph.pathfind(req, map),
-                PathConstraints::Car => self.car_graph.pathfind(req, map),
-                PathConstraints::Bike => self.bike_graph.pathfind(req, map),
-                PathConstraints::Bus => self.bus_graph.pathfind(req, map),
-                PathConstraints::Train => self.train_graph.pathfind(req, map),
+                PathConstraints::Pedestrian => self.sidewalk_graph(map).pathfind(req, map),
+                constraints => self.vehicle_graph(constraints, map).pathfind(req, map),
             };
         }
 
@@ -278,9 +333,10 @@ impl Pathfinder {
     ) -> Option<(Duration, HashMap<DirectedRoadID, Duration>)> {
         let req_cost = self.pathfind(req.clone(), map)?.get_cost();
         let all_costs = match req.constraints {
-            PathConstraints::Pedestrian => self.walking_graph.all_costs_from(req.start, map),
-            PathConstraints::Car => self.car_graph.all_costs_from(req.start, map),
-            PathConstraints::Bike => self.bike_graph.all_costs_from(req.start, map),
+            PathConstraints::Pedestrian => self.sidewalk_graph(map).all_costs_from(req.start, map),
+            PathConstraints::Car | PathConstraints::Bike => self
+                .vehicle_graph(req.constraints, map)
+                .all_costs_from(req.start, map),
             PathConstraints::Bus | PathConstraints::Train => unreachable!(),
         };
         Some((req_cost, all_costs))
@@ -293,38 +349,71 @@ impl Pathfinder {
         start: Position,
         end: Position,
     ) -> Option<(TransitStopID, Option<TransitStopID>, TransitRouteID)> {
-        self.walking_with_transit_graph
+        self.sidewalk_with_transit_graph(map)
             .should_use_transit(map, start, end)
     }
 
     pub(crate) fn apply_edits(&mut self, map: &Map, timer: &mut Timer) {
+        let params = &self.params;
+
         timer.start("apply edits to car pathfinding");
-        self.car_graph.apply_edits(map);
+        self.car_graph
+            .get_mut_or_rebuild(|| rebuild_vehicle_graph(map, PathConstraints::Car, params))
+            .apply_edits(map);
         timer.stop("apply edits to car pathfinding");
 
         timer.start("apply edits to bike pathfinding");
-        self.bike_graph.apply_edits(map);
+        self.bike_graph
+            .get_mut_or_rebuild(|| rebuild_vehicle_graph(map, PathConstraints::Bike, params))
+            .apply_edits(map);
         timer.stop("apply edits to bike pathfinding");
 
         timer.start("apply edits to bus pathfinding");
-        self.bus_graph.apply_edits(map);
+        self.bus_graph
+            .get_mut_or_rebuild(|| rebuild_vehicle_graph(map, PathConstraints::Bus, params))
+            .apply_edits(map);
         timer.stop("apply edits to bus pathfinding");
 
         timer.start("apply edits to train pathfinding");
-        self.train_graph.apply_edits(map);
+        self.train_graph
+            .get_mut_or_rebuild(|| rebuild_vehicle_graph(map, PathConstraints::Train, params))
+            .apply_edits(map);
         timer.stop("apply edits to train pathfinding");
 
         timer.start("apply edits to pedestrian pathfinding");
-        self.walking_graph.apply_edits(map, None);
+        self.walking_graph
+            .get_mut_or_rebuild(|| SidewalkPathfinder::new(map, None, &CreateEngine::CH))
+            .apply_edits(map, None);
         timer.stop("apply edits to pedestrian pathfinding");
 
+        // The transit graphs were just loaded above
         timer.start("apply edits to pedestrian using transit pathfinding");
+        let use_transit = Some((
+            self.bus_graph.get().unwrap(),
+            self.train_graph.get().unwrap(),
+        ));
         self.walking_with_transit_graph
-            .apply_edits(map, Some((&self.bus_graph, &self.train_graph)));
+            .get_mut_or_rebuild(|| SidewalkPathfinder::new(map, use_transit, &CreateEngine::CH))
+            .apply_edits(map, use_transit);
         timer.stop("apply edits to pedestrian using transit pathfinding");
     }
 }
 
+/// Build a graph from scratch the same way as for a new map, when the saved one is unusable.
+fn rebuild_vehicle_graph(
+    map: &Map,
+    constraints: PathConstraints,
+    params: &RoutingParams,
+) -> VehiclePathfinder {
+    // Light rail networks are tiny, so new maps don't use a contraction hierarchy for them
+    let engine = if constraints == PathConstraints::Train {
+        CreateEngine::Dijkstra
+    } else {
+        CreateEngine::CH
+    };
+    VehiclePathfinder::new(map, constraints, params, &engine)
+}
+
 /// For callers needing to request paths with a variety of RoutingParams. The caller is in charge
 /// of the lifetime, so they can clear it out when appropriate.
 pub struct PathfinderCache {
@@ -1,5 +1,6 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::sync::Arc;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -145,9 +146,15 @@ impl PathStep {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Cloning a path is cheap. The steps and uber-turns are shared between clones, and only copied
+/// when one of them is modified. Agents mostly just advance along their path, which doesn't count
+/// as a modification, so buses on the same route and events recording a path don't duplicate it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Path {
-    steps: VecDeque<PathStep>,
+    steps: Arc<Vec<PathStep>>,
+    // Everything before this has been shifted off already. The first remaining step is the
+    // current one.
+    step_idx: u32,
     // The original request used to produce this path. Calling shift(), add(), modify_step(), etc
     // will NOT affect this.
     orig_req: PathRequest,
@@ -158,7 +165,9 @@ pub struct Path {
 
     // A list of uber-turns encountered by this path, in order. The steps are flattened into the
     // sequence of turn->lane->...->turn.
-    uber_turns: VecDeque<UberTurn>,
+    uber_turns: Arc<Vec<UberTurn>>,
+    // Uber-turns before this have been entered already
+    uber_turn_idx: u32,
     // Is the current_step in the middle of an UberTurn?
     currently_inside_ut: Option<UberTurn>,
 
@@ -184,20 +193,27 @@ impl Path {
             validate_zones(map, &steps, &orig_req);
         }
         let mut path = Path {
-            steps: VecDeque::from(steps),
+            steps: Arc::new(steps),
+            step_idx: 0,
             orig_req,
             total_length: Distance::ZERO,
             crossed_so_far: Distance::ZERO,
-            uber_turns: uber_turns.into_iter().collect(),
+            uber_turns: Arc::new(uber_turns),
+            uber_turn_idx: 0,
             currently_inside_ut: None,
             blocked_starts,
         };
-        for step in &path.steps {
+        for step in path.get_steps() {
             path.total_length += path.dist_crossed_from_step(map, step);
         }
         path
     }
 
+    /// The uber-turns not yet entered
+    fn remaining_uber_turns(&self) -> &[UberTurn] {
+        &self.uber_turns[self.uber_turn_idx as usize..]
+    }
+
     /// Once we finish this PathStep, how much distance will be crossed? If the step is at the
     /// beginning or end of our path, then the full length may not be used.
     pub fn dist_crossed_from_step(&self, map: &Map, step: &PathStep) -> Distance {
@@ -260,34 +276,37 @@ impl Path {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.steps.is_empty()
+        self.get_steps().is_empty()
     }
 
     pub fn is_last_step(&self) -> bool {
-        self.steps.len() == 1
+        self.get_steps().len() == 1
     }
 
     pub fn isnt_last_step(&self) -> bool {
-        self.steps.len() > 1
+        self.get_steps().len() > 1
     }
 
     pub fn currently_inside_ut(&self) -> &Option<UberTurn> {
         &self.currently_inside_ut
     }
     pub fn about_to_start_ut(&self) -> Option<&UberTurn> {
-        if self.steps.len() < 2 || self.uber_turns.is_empty() {
+        let steps = self.get_steps();
+        let uber_turns = self.remaining_uber_turns();
+        if steps.len() < 2 || uber_turns.is_empty() {
             return None;
         }
-        if let PathStep::Turn(t) = self.steps[1] {
-            if self.uber_turns[0].path[0] == t {
-                return Some(&self.uber_turns[0]);
+        if let PathStep::Turn(t) = steps[1] {
+            if uber_turns[0].path[0] == t {
+                return Some(&uber_turns[0]);
             }
         }
         None
     }
 
     pub fn shift(&mut self, map: &Map) -> PathStep {
-        let step = self.steps.pop_front().unwrap();
+        let step = self.current_step();
+        self.step_idx += 1;
         self.crossed_so_far += self.dist_crossed_from_step(map, &step);
 
         #[allow(clippy::collapsible_if)] // better readability
@@ -295,15 +314,16 @@ impl Path {
             if step == PathStep::Turn(*ut.path.last().unwrap()) {
                 self.currently_inside_ut = None;
             }
-        } else if !self.steps.is_empty() && !self.uber_turns.is_empty() {
-            if self.steps[0] == PathStep::Turn(self.uber_turns[0].path[0]) {
-                self.currently_inside_ut = Some(self.uber_turns.pop_front().unwrap());
+        } else if !self.is_empty() && !self.remaining_uber_turns().is_empty() {
+            if self.current_step() == PathStep::Turn(self.remaining_uber_turns()[0].path[0]) {
+                self.currently_inside_ut = Some(self.remaining_uber_turns()[0].clone());
+                self.uber_turn_idx += 1;
             }
         }
 
-        if self.steps.len() == 1 {
+        if self.is_last_step() {
             // TODO When handle_uber_turns experiment is turned off, this will crash
-            assert!(self.uber_turns.is_empty());
+            assert!(self.remaining_uber_turns().is_empty());
             assert!(self.currently_inside_ut.is_none());
         }
 
@@ -311,7 +331,7 @@ impl Path {
     }
 
     pub fn add(&mut self, step: PathStep, map: &Map) {
-        if let Some(PathStep::Lane(l)) = self.steps.back() {
+        if let Some(PathStep::Lane(l)) = self.get_steps().last() {
             if *l == self.orig_req.end.lane() {
                 self.total_length += map.get_l(*l).length() - self.orig_req.end.dist_along();
             }
@@ -319,13 +339,31 @@ impl Path {
         // TODO We assume we'll be going along the full length of this new step
         self.total_length += step.as_traversable().get_polyline(map).length();
 
-        self.steps.push_back(step);
+        self.steps_mut().push(step);
         // TODO Maybe need to amend uber_turns?
     }
 
+    /// Replaces everything after the current step with a detour. The detour must start with the
+    /// current step.
+    pub fn reroute(&mut self, detour: Path, map: &Map) {
+        assert!(self.currently_inside_ut.is_none());
+        assert_eq!(self.current_step(), detour.current_step());
+        for step in &self.get_steps()[1..] {
+            self.total_length -= self.dist_crossed_from_step(map, step);
+        }
+        for step in &detour.get_steps()[1..] {
+            self.total_length += detour.dist_crossed_from_step(map, step);
+        }
+
+        self.steps = Arc::new(detour.get_steps().to_vec());
+        self.step_idx = 0;
+        self.uber_turns = Arc::new(detour.remaining_uber_turns().to_vec());
+        self.uber_turn_idx = 0;
+    }
+
     pub fn is_upcoming_uber_turn_component(&self, t: TurnID) -> bool {
-        self.uber_turns
-            .front()
+        self.remaining_uber_turns()
+            .first()
             .map(|ut| ut.path.contains(&t))
             .unwrap_or(false)
     }
@@ -335,11 +373,15 @@ impl Path {
         assert!(self.currently_inside_ut.is_none());
         // We're assuming this step was in the middle of the path, meaning we were planning to
         // travel its full length
-        self.total_length -= self.steps[idx].as_traversable().get_polyline(map).length();
+        self.total_length -= self.get_steps()[idx]
+            .as_traversable()
+            .get_polyline(map)
+            .length();
 
         // When replacing a turn, also update any references to it in uber_turns
-        if let PathStep::Turn(old_turn) = self.steps[idx] {
-            for uts in &mut self.uber_turns {
+        if let PathStep::Turn(old_turn) = self.get_steps()[idx] {
+            let first_ut = self.uber_turn_idx as usize;
+            for uts in &mut Arc::make_mut(&mut self.uber_turns)[first_ut..] {
                 if let Some(turn_idx) = uts.path.iter().position(|i| i == &old_turn) {
                     if let PathStep::Turn(new_turn) = step {
                         uts.path[turn_idx] = new_turn;
@@ -350,8 +392,8 @@ impl Path {
             }
         }
 
-        self.steps[idx] = step;
-        self.total_length += self.steps[idx].as_traversable().get_polyline(map).length();
+        self.steps_mut()[idx] = step;
+        self.total_length += step.as_traversable().get_polyline(map).length();
 
         if self.total_length < Distance::ZERO {
             panic!(
@@ -362,11 +404,11 @@ impl Path {
     }
 
     pub fn current_step(&self) -> PathStep {
-        self.steps[0]
+        self.get_steps()[0]
     }
 
     pub fn next_step(&self) -> PathStep {
-        self.steps[1]
+        self.get_steps()[1]
     }
     pub fn maybe_next_step(&self) -> Option<PathStep> {
         if self.is_last_step() {
@@ -377,7 +419,7 @@ impl Path {
     }
 
     pub fn last_step(&self) -> PathStep {
-        self.steps[self.steps.len() - 1]
+        *self.get_steps().last().unwrap()
     }
 
     /// Traces along the path from its originally requested start. This is only valid to call for
@@ -386,7 +428,7 @@ impl Path {
     /// It mostly seems the PolyLine's length will match `total_length`, but callers beware if
     /// you're relying on this -- check walking paths with the buggy sharp angles particularly.
     pub fn trace(&self, map: &Map) -> Option<PolyLine> {
-        let t1 = self.steps[0].as_traversable();
+        let t1 = self.current_step().as_traversable();
         let t2 = Traversable::Lane(self.orig_req.start.lane());
         if t1 != t2 {
             warn!(
@@ -401,8 +443,9 @@ impl Path {
     /// Traces along the path from a specified distance along the first step until the end.
     pub fn trace_from_start(&self, map: &Map, start_dist: Distance) -> Option<PolyLine> {
         let orig_end_dist = self.orig_req.end.dist_along();
+        let steps = self.get_steps();
 
-        if self.steps.len() == 1 {
+        if steps.len() == 1 {
             let dist_ahead = if start_dist < orig_end_dist {
                 orig_end_dist - start_dist
             } else {
@@ -412,23 +455,21 @@ impl Path {
             // Why might this fail? It's possible there are paths on their last step that're
             // effectively empty, because they're a 0-length turn, or something like a pedestrian
             // crossing a front path and immediately getting on a bike.
-            return self.steps[0]
-                .exact_slice(map, start_dist, Some(dist_ahead))
-                .ok();
+            return steps[0].exact_slice(map, start_dist, Some(dist_ahead)).ok();
         }
 
         let mut pts_so_far: Option<PolyLine> = None;
 
         // Special case the first step with start_dist.
-        if let Ok(pts) = self.steps[0].exact_slice(map, start_dist, None) {
+        if let Ok(pts) = steps[0].exact_slice(map, start_dist, None) {
             pts_so_far = Some(pts);
         }
 
         // Crunch through the intermediate steps, as long as we can.
-        for i in 1..self.steps.len() {
+        for i in 1..steps.len() {
             // Restrict the last step's slice
-            let dist_ahead = if i == self.steps.len() - 1 {
-                Some(match self.steps[i] {
+            let dist_ahead = if i == steps.len() - 1 {
+                Some(match steps[i] {
                     PathStep::ContraflowLane(l) => {
                         map.get_l(l).lane_center_pts.reversed().length() - orig_end_dist
                     }
@@ -441,14 +482,14 @@ impl Path {
                 None
             };
 
-            let start_dist_this_step = match self.steps[i] {
+            let start_dist_this_step = match steps[i] {
                 // TODO Length of a PolyLine can slightly change when points are reversed! That
                 // seems bad.
                 PathStep::ContraflowLane(l) => map.get_l(l).lane_center_pts.reversed().length(),
                 PathStep::ContraflowTurn(t) => map.get_t(t).geom.reversed().length(),
                 _ => Distance::ZERO,
             };
-            if let Ok(new_pts) = self.steps[i].exact_slice(map, start_dist_this_step, dist_ahead) {
+            if let Ok(new_pts) = steps[i].exact_slice(map, start_dist_this_step, dist_ahead) {
                 if pts_so_far.is_some() {
                     match pts_so_far.unwrap().extend(new_pts) {
                         Ok(new) => {
@@ -473,7 +514,7 @@ impl Path {
     pub fn trace_v2(&self, map: &Map) -> Result<Polygon> {
         let mut left_pts = Vec::new();
         let mut right_pts = Vec::new();
-        for step in &self.steps {
+        for step in self.get_steps() {
             match step {
                 PathStep::Lane(l) => {
                     let road = map.get_parent(*l);
@@ -500,8 +541,19 @@ impl Path {
         Ok(Ring::deduping_new(left_pts)?.into_polygon())
     }
 
-    pub fn get_steps(&self) -> &VecDeque<PathStep> {
-        &self.steps
+    /// The remaining steps, starting with the current one.
+    pub fn get_steps(&self) -> &[PathStep] {
+        &self.steps[self.step_idx as usize..]
+    }
+
+    /// Copies the steps if they're shared with another path. Steps already shifted off are dropped
+    /// at the same time, since nothing can look at them anymore.
+    fn steps_mut(&mut self) -> &mut Vec<PathStep> {
+        if self.step_idx > 0 {
+            self.steps = Arc::new(self.get_steps().to_vec());
+            self.step_idx = 0;
+        }
+        Arc::make_mut(&mut self.steps)
     }
 
     /// Estimate how long following the path will take in the best case, assuming no traffic or
@@ -509,7 +561,7 @@ impl Path {
     /// max_speed must be known.
     pub fn estimate_duration(&self, map: &Map, max_speed: Option<Speed>) -> Duration {
         let mut total = Duration::ZERO;
-        for step in &self.steps {
+        for step in self.get_steps() {
             let dist = self.dist_crossed_from_step(map, step);
             let speed = step.max_speed_along(max_speed, self.orig_req.constraints, map);
             total += dist / speed;
@@ -527,7 +579,7 @@ impl Path {
     pub fn get_total_elevation_change(&self, map: &Map) -> (Distance, Distance) {
         let mut gain = Distance::ZERO;
         let mut loss = Distance::ZERO;
-        for step in &self.steps {
+        for step in self.get_steps() {
             let (from, to) = match step {
                 PathStep::Lane(l) => {
                     let lane = map.get_l(*l);
@@ -557,7 +609,7 @@ impl Path {
     }
 
     pub fn get_step_at_dist_along(&self, map: &Map, mut dist_along: Distance) -> Result<PathStep> {
-        for step in &self.steps {
+        for step in self.get_steps() {
             let dist_here = self.dist_crossed_from_step(map, step);
             if dist_along <= dist_here {
                 return Ok(*step);
@@ -571,6 +623,19 @@ impl Path {
     }
 }
 
+// Paths that have shifted off a different number of steps can still be equal
+impl PartialEq for Path {
+    fn eq(&self, other: &Path) -> bool {
+        self.get_steps() == other.get_steps()
+            && self.orig_req == other.orig_req
+            && self.total_length == other.total_length
+            && self.crossed_so_far == other.crossed_so_far
+            && self.remaining_uber_turns() == other.remaining_uber_turns()
+            && self.currently_inside_ut == other.currently_inside_ut
+            && self.blocked_starts == other.blocked_starts
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct PathRequest {
     pub start: Position,
@@ -8,11 +8,12 @@ use serde::{Deserialize, Serialize};
 use abstutil::MultiMap;
 use geom::Duration;
 
+use crate::objects::cordon::cordon_cost;
 use crate::pathfind::engine::{CreateEngine, PathfindEngine};
 use crate::pathfind::node_map::{deserialize_nodemap, NodeMap};
 use crate::pathfind::uber_turns::{IntersectionCluster, UberTurnV2};
-use crate::pathfind::zone_cost;
 use crate::pathfind::{round, unround};
+use crate::pathfind::{through_traffic_cost, zone_cost};
 use crate::{
     osm, DirectedRoadID, Direction, LaneType, Map, MovementID, PathConstraints, PathRequest,
     PathV2, Position, RoutingParams, Traversable,
@@ -341,11 +342,18 @@ pub fn vehicle_cost(
         }
     }
 
-    if constraints == PathConstraints::Bike
-        && (params.avoid_high_stress - 1.0).abs() > f64::EPSILON
-        && road.high_stress_for_bikes(map, dr.dir)
+    let mut stress_penalty = Duration::ZERO;
+    if constraints == PathConstraints::Bike && (params.avoid_high_stress - 1.0).abs() > f64::EPSILON
     {
-        multiplier *= params.avoid_high_stress;
+        if road.high_stress_for_bikes(map, dr.dir, &params.bike_stress) {
+            multiplier *= params.avoid_high_stress;
+        } else if map
+            .get_i(mvmnt.parent)
+            .high_stress_for_bikes(map, &params.bike_stress)
+        {
+            // Only the time spent crossing the intersection is stressful
+            stress_penalty = (params.avoid_high_stress - 1.0) * t2;
+        }
     }
 
     if params.avoid_roads.contains(&dr.road)
@@ -356,7 +364,9 @@ pub fn vehicle_cost(
         return None;
     }
 
-    let mut extra = zone_cost(mvmnt, constraints, map);
+    let mut extra = zone_cost(mvmnt, constraints, map) + stress_penalty;
+    extra += cordon_cost(mvmnt, constraints, params);
+    extra += through_traffic_cost(mvmnt, constraints, params);
     // Penalize unprotected turns at a stop sign from smaller to larger roads.
     if map.is_unprotected_turn(dr.road, mvmnt.to.road, movement.turn_type) {
         extra += params.unprotected_turn_penalty
@@ -4,7 +4,10 @@ use serde::{Deserialize, Serialize};
 
 use geom::{Angle, Distance, PolyLine, Pt2D, Speed};
 
-use crate::{DirectedRoadID, Direction, LaneID, Map, MovementID, PathConstraints, TurnID};
+use crate::{
+    DirectedRoadID, Direction, DrivingSide, LaneID, Map, MovementID, PathConstraints, TurnID,
+    TurnType,
+};
 
 /// Represents a specific point some distance along a lane.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -234,19 +237,49 @@ impl Traversable {
     pub(crate) fn max_speed_along_movement(
         mvmnt: MovementID,
         max_speed_on_flat_ground: Option<Speed>,
-        _: PathConstraints,
+        constraints: PathConstraints,
         map: &Map,
     ) -> Speed {
         // TODO Ignore elevation on turns?
-        let base = map
+        let mut base = map
             .get_r(mvmnt.from.road)
             .speed_limit
             .min(map.get_r(mvmnt.to.road).speed_limit);
         if let Some(s) = max_speed_on_flat_ground {
-            base.min(s)
+            base = base.min(s);
+        }
+
+        // Corner radii only matter when they've been edited
+        let i = map.get_i(mvmnt.parent);
+        if i.corner_radius.is_none() {
+            return base;
+        }
+        let movement = match i.movements.get(&mvmnt) {
+            Some(m) => m,
+            None => {
+                return base;
+            }
+        };
+        if constraints == PathConstraints::Pedestrian {
+            // Rather than change the crosswalk geometry, make crossing it take as long as walking
+            // the adjusted distance would.
+            if movement.turn_type.pedestrian_crossing() {
+                let len = movement.geom.length();
+                let adjusted = (len + i.crossing_distance_adjustment()).max(len / 2.0);
+                base = base * (len / adjusted);
+            }
         } else {
-            base
+            // The turn hugging the curb
+            let curb_turn = if map.get_config().driving_side == DrivingSide::Right {
+                TurnType::Right
+            } else {
+                TurnType::Left
+            };
+            if movement.turn_type == curb_turn {
+                base = base.min(i.max_speed_around_corner().unwrap());
+            }
         }
+        base
     }
 }
 
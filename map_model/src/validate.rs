@@ -0,0 +1,163 @@
+//! Sanity checks over a finished map, so people importing new places can find geometry problems
+//! before they break the simulation or UI.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use geom::Distance;
+
+use crate::connectivity::find_scc;
+use crate::{IntersectionID, LaneID, Map, PathConstraints};
+
+/// Lanes shorter than this are almost certainly a geometry bug.
+const MIN_LANE_LENGTH: Distance = Distance::const_meters(0.1);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ValidationProblem {
+    ZeroLengthLane(LaneID),
+    /// Two intersections that aren't connected by any road have overlapping polygons
+    OverlappingIntersections(IntersectionID, IntersectionID),
+    /// The polygon of an intersection doubles back on itself
+    BadIntersectionPolygon(IntersectionID),
+    /// These lanes aren't part of the largest strongly-connected component of the graph for some
+    /// constraints. Trips starting or ending there will likely fail.
+    DisconnectedLanes(PathConstraints, Vec<LaneID>),
+}
+
+impl fmt::Display for ValidationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationProblem::ZeroLengthLane(l) => write!(f, "{} has nearly zero length", l),
+            ValidationProblem::OverlappingIntersections(i1, i2) => {
+                write!(f, "{} and {} overlap, but aren't connected", i1, i2)
+            }
+            ValidationProblem::BadIntersectionPolygon(i) => {
+                write!(f, "{} has a polygon that doubles back on itself", i)
+            }
+            ValidationProblem::DisconnectedLanes(constraints, lanes) => write!(
+                f,
+                "{} lanes are disconnected from the main graph for {:?}",
+                lanes.len(),
+                constraints
+            ),
+        }
+    }
+}
+
+/// The result of `Map::validate`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub problems: Vec<ValidationProblem>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// One line per problem
+    pub fn describe(&self) -> Vec<String> {
+        if self.problems.is_empty() {
+            return vec!["No problems found".to_string()];
+        }
+        self.problems.iter().map(|p| p.to_string()).collect()
+    }
+
+    /// All lanes involved in some problem
+    pub fn all_lanes(&self) -> Vec<LaneID> {
+        let mut lanes = Vec::new();
+        for problem in &self.problems {
+            match problem {
+                ValidationProblem::ZeroLengthLane(l) => {
+                    lanes.push(*l);
+                }
+                ValidationProblem::DisconnectedLanes(_, list) => {
+                    lanes.extend(list.iter().cloned());
+                }
+                _ => {}
+            }
+        }
+        lanes
+    }
+
+    /// All intersections involved in some problem
+    pub fn all_intersections(&self) -> Vec<IntersectionID> {
+        let mut intersections = Vec::new();
+        for problem in &self.problems {
+            match problem {
+                ValidationProblem::OverlappingIntersections(i1, i2) => {
+                    intersections.push(*i1);
+                    intersections.push(*i2);
+                }
+                ValidationProblem::BadIntersectionPolygon(i) => {
+                    intersections.push(*i);
+                }
+                _ => {}
+            }
+        }
+        intersections
+    }
+}
+
+impl Map {
+    /// Check the map for geometry and connectivity problems. This is somewhat expensive.
+    pub fn validate(&self) -> ValidationReport {
+        let mut problems = Vec::new();
+
+        for l in self.all_lanes() {
+            if l.length() < MIN_LANE_LENGTH {
+                problems.push(ValidationProblem::ZeroLengthLane(l.id));
+            }
+        }
+
+        for i in self.all_intersections() {
+            if let Some(ring) = i.polygon.get_outer_ring() {
+                if ring.doubles_back() {
+                    problems.push(ValidationProblem::BadIntersectionPolygon(i.id));
+                }
+            }
+        }
+
+        // Sweep over intersections sorted by their left edge, only comparing polygons when the
+        // bounding boxes overlap
+        let mut sorted: Vec<_> = self
+            .all_intersections()
+            .iter()
+            .map(|i| (i.polygon.get_bounds(), i))
+            .collect();
+        sorted.sort_by(|(b1, _), (b2, _)| b1.min_x.partial_cmp(&b2.min_x).unwrap());
+        for (idx, (b1, i1)) in sorted.iter().enumerate() {
+            for (b2, i2) in &sorted[idx + 1..] {
+                if b2.min_x > b1.max_x {
+                    break;
+                }
+                if b2.min_y > b1.max_y || b1.min_y > b2.max_y {
+                    continue;
+                }
+                if i1.roads.iter().any(|r| i2.roads.contains(r)) {
+                    continue;
+                }
+                if i1.polygon.intersects(&i2.polygon) {
+                    problems.push(ValidationProblem::OverlappingIntersections(i1.id, i2.id));
+                }
+            }
+        }
+
+        for constraints in [
+            PathConstraints::Pedestrian,
+            PathConstraints::Car,
+            PathConstraints::Bike,
+            PathConstraints::Bus,
+        ] {
+            let (_, disconnected) = find_scc(self, constraints);
+            if !disconnected.is_empty() {
+                let mut lanes: Vec<LaneID> = disconnected.into_iter().collect();
+                lanes.sort();
+                problems.push(ValidationProblem::DisconnectedLanes(constraints, lanes));
+            }
+        }
+
+        ValidationReport { problems }
+    }
+}
@@ -97,21 +97,8 @@ pub fn disaggregate(
         let home_zone = &zones[&desire.home_zone];
         let work_zone = &zones[&desire.work_zone];
 
-        // If both are remote, make sure the desire line intersects the map
-        if home_zone.is_remote() && work_zone.is_remote() {
-            if desire.home_zone == desire.work_zone {
-                continue;
-            }
-
-            if !map
-                .get_boundary_polygon()
-                .intersects_polyline(&PolyLine::must_new(vec![
-                    home_zone.center,
-                    work_zone.center,
-                ]))
-            {
-                continue;
-            }
+        if !crosses_map(map, home_zone, work_zone) {
+            continue;
         }
 
         for _ in 0..desire.number_commuters {
@@ -185,6 +172,93 @@ pub fn disaggregate(
     people
 }
 
+/// This describes some number of trips from one named zone to another (or the same zone), using
+/// some mode, and departing within one hour of the day.
+#[derive(Debug)]
+pub struct ZoneTrips {
+    pub origin_zone: String,
+    pub destination_zone: String,
+    pub mode: TripMode,
+    /// Trips depart sometime during this hour, from 0 to 23
+    pub hour: usize,
+    pub count: usize,
+}
+
+/// Generates a scenario from an origin/destination matrix broken down by mode and hour. Unlike
+/// `disaggregate`, each person takes just a single trip, departing at a random time within the
+/// hour. The origin and destination are sampled from all buildings in each zone, or snapped to a
+/// map border, following the same rules.
+pub fn disaggregate_trips(
+    map: &Map,
+    zones: HashMap<String, Polygon>,
+    counts: Vec<ZoneTrips>,
+    include_zones: IncludeZonePolicy,
+    rng: &mut XorShiftRng,
+    timer: &mut Timer,
+) -> Vec<PersonSpec> {
+    let zones = create_zones(map, zones, include_zones, timer);
+
+    let mut people = Vec::new();
+    timer.start_iter("create people per zone pair", counts.len());
+    for trips in counts {
+        timer.next();
+        let (origin_zone, destination_zone) = match (
+            zones.get(&trips.origin_zone),
+            zones.get(&trips.destination_zone),
+        ) {
+            (Some(a), Some(b)) => (a, b),
+            // We filtered out one of the zones
+            _ => continue,
+        };
+        if !crosses_map(map, origin_zone, destination_zone) {
+            continue;
+        }
+
+        for _ in 0..trips.count {
+            if let (Some((origin, _)), Some((_, destination))) = (
+                origin_zone.pick_building(trips.mode, map, rng),
+                destination_zone.pick_building(trips.mode, map, rng),
+            ) {
+                if origin == destination {
+                    continue;
+                }
+                let departure = Time::START_OF_DAY
+                    + Duration::hours(trips.hour)
+                    + Duration::seconds(rng.gen_range(0.0..3600.0));
+                people.push(PersonSpec {
+                    orig_id: None,
+                    trips: vec![IndividTrip::new(
+                        departure,
+                        TripPurpose::Work,
+                        origin,
+                        destination,
+                        trips.mode,
+                    )],
+                });
+            }
+        }
+    }
+    info!(
+        "Created {} people, each taking one trip",
+        prettyprint_usize(people.len())
+    );
+
+    people
+}
+
+/// Trips between two remote zones are only relevant if the straight line between them crosses
+/// the map.
+fn crosses_map(map: &Map, from: &Zone, to: &Zone) -> bool {
+    if !from.is_remote() || !to.is_remote() {
+        return true;
+    }
+    if from.center == to.center {
+        return false;
+    }
+    map.get_boundary_polygon()
+        .intersects_polyline(&PolyLine::must_new(vec![from.center, to.center]))
+}
+
 struct Zone {
     polygon: Polygon,
     center: Pt2D,
@@ -347,6 +421,24 @@ impl Zone {
         self.pick_borders(mode, map, rng)
     }
 
+    /// Returns endpoints to (leave, arrive at) any building in the zone, weighting homes and
+    /// workplaces the same way as `pick_home` and `pick_workplace`.
+    fn pick_building(
+        &self,
+        mode: TripMode,
+        map: &Map,
+        rng: &mut XorShiftRng,
+    ) -> Option<(TripEndpoint, TripEndpoint)> {
+        if rng.gen_bool(self.pct_overlap) {
+            let candidates: Vec<&(BuildingID, usize)> =
+                self.homes.iter().chain(self.workplaces.iter()).collect();
+            if let Ok((b, _)) = candidates.choose_weighted(rng, |(_, n)| *n) {
+                return Some((TripEndpoint::Building(*b), TripEndpoint::Building(*b)));
+            }
+        }
+        self.pick_borders(mode, map, rng)
+    }
+
     fn pick_borders(
         &self,
         mode: TripMode,
@@ -10,15 +10,17 @@ pub fn get_lane_specs_ltr(tags: &Tags, cfg: &MapConfig) -> Vec<LaneSpec> {
         lt,
         dir: Direction::Fwd,
         width: LaneSpec::typical_lane_widths(lt, tags)[0].0,
+        pocket: None,
     };
     let back = |lt: LaneType| LaneSpec {
         lt,
         dir: Direction::Back,
         width: LaneSpec::typical_lane_widths(lt, tags)[0].0,
+        pocket: None,
     };
 
     // Easy special cases first.
-    if tags.is_any("railway", vec!["light_rail", "rail"]) {
+    if tags.is_any("railway", vec!["light_rail", "rail", "tram"]) {
         return vec![fwd(LaneType::LightRail)];
     }
     if tags.is(osm::HIGHWAY, "steps") {
@@ -98,19 +100,10 @@ pub fn get_lane_specs_ltr(tags: &Tags, cfg: &MapConfig) -> Vec<LaneSpec> {
         1
     };
 
-    #[allow(clippy::if_same_then_else)] // better readability
     let driving_lane =
         if tags.is("access", "no") && (tags.is("bus", "yes") || tags.is("psv", "yes")) {
             // Sup West Seattle
             LaneType::Bus
-        } else if tags
-            .get("motor_vehicle:conditional")
-            .map(|x| x.starts_with("no"))
-            .unwrap_or(false)
-            && tags.is("bus", "yes")
-        {
-            // Example: 3rd Ave in downtown Seattle
-            LaneType::Bus
         } else if tags.is("access", "no") || tags.is("highway", "construction") {
             LaneType::Construction
         } else {
@@ -177,6 +170,19 @@ pub fn get_lane_specs_ltr(tags: &Tags, cfg: &MapConfig) -> Vec<LaneSpec> {
             }
         }
     }
+    // Contraflow bus lanes on one-way streets. oneway:bus=no alone only says buses may go both
+    // ways, not that there's a lane for it, so it's ignored.
+    let contraflow_busway_side = if cfg.driving_side == DrivingSide::Right {
+        "busway:left"
+    } else {
+        "busway:right"
+    };
+    if oneway
+        && !back_side.iter().any(|spec| spec.lt == LaneType::Bus)
+        && (tags.is("busway", "opposite_lane") || tags.is(contraflow_busway_side, "opposite_lane"))
+    {
+        back_side.push(back(LaneType::Bus));
+    }
 
     if tags.is_any("cycleway", vec!["lane", "track"]) {
         fwd_side.push(fwd(LaneType::Biking));
@@ -518,6 +524,40 @@ mod tests {
                 "SdddddS",
                 "vvv^^^^",
             ),
+            (
+                // I didn't look for a real example of this
+                "https://wiki.openstreetmap.org/wiki/Key:busway",
+                vec![
+                    "lanes=1",
+                    "oneway=yes",
+                    "sidewalk=both",
+                    "busway=opposite_lane",
+                ],
+                DrivingSide::Right,
+                "sBds",
+                "vv^^",
+            ),
+            (
+                // I didn't look for a real example of this
+                "https://wiki.openstreetmap.org/wiki/Key:busway",
+                vec![
+                    "lanes=1",
+                    "oneway=yes",
+                    "sidewalk=both",
+                    "busway:left=opposite_lane",
+                ],
+                DrivingSide::Right,
+                "sBds",
+                "vv^^",
+            ),
+            (
+                // Buses may go both ways, but there's no lane for them
+                "https://wiki.openstreetmap.org/wiki/Key:oneway:bus",
+                vec!["lanes=1", "oneway=yes", "sidewalk=both", "oneway:bus=no"],
+                DrivingSide::Right,
+                "sds",
+                "v^^",
+            ),
             (
                 "https://www.openstreetmap.org/way/335668924",
                 vec!["lanes=1", "sidewalk=none"],
@@ -531,6 +571,9 @@ mod tests {
                 bikes_can_use_bus_lanes: true,
                 inferred_sidewalks: true,
                 street_parking_spot_length: geom::Distance::meters(8.0),
+                turn_on_red: true,
+                default_speed_limits: crate::DefaultSpeedLimits::usa(),
+                roundabout_priority: crate::RoundaboutPriority::Circulating,
             };
             let actual = get_lane_specs_ltr(&tags(input.clone()), &cfg);
             let actual_lt: String = actual.iter().map(|s| s.lt.to_char()).collect();
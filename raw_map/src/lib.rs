@@ -21,8 +21,9 @@ use geom::{Angle, Distance, GPSBounds, PolyLine, Polygon, Pt2D};
 pub use self::geometry::intersection_polygon;
 pub use self::lane_specs::get_lane_specs_ltr;
 pub use self::types::{
-    Amenity, AmenityType, AreaType, BufferType, Direction, DrivingSide, IntersectionType, LaneSpec,
-    LaneType, MapConfig, NamePerLanguage, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
+    Amenity, AmenityType, AreaType, BufferType, DefaultSpeedLimits, Direction, DrivingSide,
+    IntersectionType, LaneSpec, LaneType, MapConfig, NamePerLanguage, RoundaboutPriority,
+    NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
 };
 
 mod geometry;
@@ -155,6 +156,8 @@ impl RawMap {
                 inferred_sidewalks: true,
                 street_parking_spot_length: Distance::meters(8.0),
                 turn_on_red: true,
+                default_speed_limits: DefaultSpeedLimits::usa(),
+                roundabout_priority: RoundaboutPriority::Circulating,
             },
         }
     }
@@ -374,6 +377,9 @@ pub struct RawRoad {
     pub turn_restrictions: Vec<(RestrictionType, OriginalRoad)>,
     /// (via, to). For turn restrictions where 'via' is an entire road. Only BanTurns.
     pub complicated_turn_restrictions: Vec<(OriginalRoad, OriginalRoad)>,
+    /// (to, condition). Turn restrictions that only apply during certain hours. The condition is
+    /// the raw OSM value, like `no_left_turn @ (07:00-09:00)`.
+    pub conditional_turn_restrictions: Vec<(OriginalRoad, String)>,
     pub percent_incline: f64,
     /// Is there a tagged crosswalk near each end of the road?
     pub crosswalk_forward: bool,
@@ -383,7 +389,8 @@ pub struct RawRoad {
 impl RawRoad {
     // TODO For the moment, treating all rail things as light rail
     pub fn is_light_rail(&self) -> bool {
-        self.osm_tags.is_any("railway", vec!["light_rail", "rail"])
+        self.osm_tags
+            .is_any("railway", vec!["light_rail", "rail", "tram"])
     }
 
     pub fn is_footway(&self) -> bool {
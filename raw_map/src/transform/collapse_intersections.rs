@@ -41,11 +41,13 @@ fn should_collapse(r1: OriginalRoad, r2: OriginalRoad, raw: &RawMap) -> Result<(
     let road2 = &raw.roads[&r2];
 
     // Don't attempt to merge roads with these.
-    if !road1.turn_restrictions.is_empty() || !road1.complicated_turn_restrictions.is_empty() {
-        bail!("one road has turn restrictions");
-    }
-    if !road2.turn_restrictions.is_empty() || !road2.complicated_turn_restrictions.is_empty() {
-        bail!("one road has turn restrictions");
+    for road in [road1, road2] {
+        if !road.turn_restrictions.is_empty()
+            || !road.complicated_turn_restrictions.is_empty()
+            || !road.conditional_turn_restrictions.is_empty()
+        {
+            bail!("one road has turn restrictions");
+        }
     }
 
     // Avoid two one-ways that point at each other. https://www.openstreetmap.org/node/440979339 is
@@ -230,6 +232,12 @@ pub fn collapse_intersection(raw: &mut RawMap, i: NodeID) {
                 *id2 = new_r1;
             }
         }
+
+        for (id, _) in &mut road.conditional_turn_restrictions {
+            if rewrite(id) {
+                *id = new_r1;
+            }
+        }
     }
 }
 
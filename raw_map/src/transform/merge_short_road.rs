@@ -179,6 +179,17 @@ impl RawMap {
             road.turn_restrictions.extend(add);
         }
 
+        // Conditional restrictions onto the deleted road are just dropped
+        for road in self.roads.values_mut() {
+            road.conditional_turn_restrictions
+                .retain(|(to, _)| *to != short);
+            for (to, _) in &mut road.conditional_turn_restrictions {
+                if let Some(new_id) = old_to_new.get(to) {
+                    *to = *new_id;
+                }
+            }
+        }
+
         Ok((i1, i2, deleted, created))
     }
 }
@@ -6,7 +6,7 @@ use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
 
 use abstutil::{deserialize_btreemap, serialize_btreemap, Tags};
-use geom::Distance;
+use geom::{Distance, Speed};
 
 use crate::osm;
 
@@ -286,6 +286,65 @@ pub struct MapConfig {
     pub street_parking_spot_length: Distance,
     /// If true, turns on red which do not conflict crossing traffic ('right on red') are allowed
     pub turn_on_red: bool,
+    /// When a road has no speed limit tagged in OSM, guess one based on its type. The defaults
+    /// vary by country.
+    pub default_speed_limits: DefaultSpeedLimits,
+    /// At roundabouts without any explicit signage, who yields?
+    pub roundabout_priority: RoundaboutPriority,
+}
+
+/// Speed limits used when a road isn't tagged with `maxspeed`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DefaultSpeedLimits {
+    /// Primary and secondary roads and motorway links
+    pub arterial: Speed,
+    pub living_street: Speed,
+    pub service: Speed,
+    /// Everything else
+    pub other: Speed,
+}
+
+impl DefaultSpeedLimits {
+    /// Half reasonable guesses for the US.
+    pub fn usa() -> DefaultSpeedLimits {
+        DefaultSpeedLimits {
+            arterial: Speed::miles_per_hour(40.0),
+            // about 12mph
+            living_street: Speed::km_per_hour(20.0),
+            service: Speed::miles_per_hour(10.0),
+            other: Speed::miles_per_hour(20.0),
+        }
+    }
+
+    /// The UK's default in built-up areas is 30mph, regardless of road type.
+    pub fn uk() -> DefaultSpeedLimits {
+        DefaultSpeedLimits {
+            arterial: Speed::miles_per_hour(30.0),
+            living_street: Speed::miles_per_hour(10.0),
+            service: Speed::miles_per_hour(10.0),
+            other: Speed::miles_per_hour(30.0),
+        }
+    }
+
+    /// Most of continental Europe defaults to 50km/h in urban areas.
+    pub fn europe() -> DefaultSpeedLimits {
+        DefaultSpeedLimits {
+            arterial: Speed::km_per_hour(50.0),
+            living_street: Speed::km_per_hour(20.0),
+            service: Speed::km_per_hour(20.0),
+            other: Speed::km_per_hour(50.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RoundaboutPriority {
+    /// Vehicles entering the roundabout yield to those already circulating. This is the rule
+    /// nearly everywhere.
+    Circulating,
+    /// Vehicles already in the roundabout yield to those entering. Some older French roundabouts
+    /// work this way.
+    Entering,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
@@ -464,6 +523,11 @@ pub struct LaneSpec {
     pub lt: LaneType,
     pub dir: Direction,
     pub width: Distance,
+    /// A turn lane may only open up partway along a road, as a pocket near the intersection. This
+    /// is the length of the full-width part, measured back from the end of the lane. None means
+    /// the lane runs the whole length of the road.
+    #[serde(default)]
+    pub pocket: Option<Distance>,
 }
 
 impl LaneSpec {
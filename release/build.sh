@@ -32,7 +32,7 @@ mkdir $output
 
 cp release/play_abstreet.$ext release/ungap_the_map.$ext release/INSTRUCTIONS.txt $output
 
-for name in game cli fifteen_min osm_viewer parking_mapper santa ltn; do
+for name in game cli fifteen_min osm_viewer map_viewer parking_mapper santa ltn; do
 	cp target/release/${name}${suffix} $output;
 done
 
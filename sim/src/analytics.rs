@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
 use std::io::Write;
 
 use anyhow::Result;
@@ -6,14 +7,17 @@ use fs_err::File;
 use serde::{Deserialize, Serialize};
 
 use abstutil::Counter;
-use geom::{Duration, Time};
+use geom::{Distance, Duration, Histogram, Time};
 use map_model::{
     CompressedMovementID, IntersectionID, LaneID, Map, MovementID, ParkingLotID, Path, PathRequest,
-    RoadID, TransitRouteID, TransitStopID, Traversable, TurnID,
+    RoadID, TransitRouteID, TransitStopID, Traversable, TurnID, TurnType,
 };
 use synthpop::TripMode;
 
-use crate::{AgentID, AgentType, AlertLocation, CarID, Event, ParkingSpot, TripID, TripPhaseType};
+use crate::{
+    AgentID, AgentType, AlertLocation, CarID, Event, MissedConnectionChoice, ParkingSpot, TripID,
+    TripPhaseType, VehicleType, MAX_CAR_LENGTH,
+};
 
 /// As a simulation runs, different pieces emit Events. The Analytics object listens to these,
 /// organizing and storing some information from them. The UI queries Analytics to draw time-series
@@ -41,6 +45,9 @@ pub struct Analytics {
     /// For each passenger boarding, how long did they wait at the stop?
     pub passengers_boarding: BTreeMap<TransitStopID, Vec<(Time, TransitRouteID, Duration)>>,
     pub passengers_alighting: BTreeMap<TransitStopID, Vec<(Time, TransitRouteID)>>,
+    /// Who reached a stop just after their vehicle left, and what did they do about it?
+    pub missed_connections:
+        BTreeMap<TransitStopID, Vec<(Time, TransitRouteID, MissedConnectionChoice)>>,
 
     pub started_trips: BTreeMap<TripID, Time>,
     /// Finish time, ID, mode, trip duration if successful (or None if cancelled)
@@ -59,11 +66,46 @@ pub struct Analytics {
     /// Per parking lane or lot, when does a spot become filled (true) or free (false)
     pub parking_lane_changes: BTreeMap<LaneID, Vec<(Time, bool)>>,
     pub parking_lot_changes: BTreeMap<ParkingLotID, Vec<(Time, bool)>>,
+    /// How far people walk between their parked car and the rest of their trip. The bool is true
+    /// when walking to the car, false when walking away after parking.
+    pub parking_walks: Vec<(Time, TripID, Distance, bool)>,
+
+    /// Every time a car enters a charging cordon while it's active, record the index of the
+    /// cordon in the map edits and the price paid.
+    pub cordon_charges: Vec<(Time, TripID, usize, f64)>,
+
+    /// For each intersection, the farthest upstream that stopped vehicles waiting to enter it have
+    /// ever reached, and when. When a queue fills an entire lane and spills back, this continues
+    /// along the upstream lanes.
+    pub max_queue_extent: BTreeMap<IntersectionID, (Time, Distance)>,
+
+    /// Turning movement counts. Per intersection, (15 minute interval, approaching road, movement,
+    /// agent type) -> count. Pedestrians are counted on the road their crosswalk crosses.
+    pub turning_movements:
+        BTreeMap<IntersectionID, BTreeMap<(usize, RoadID, TurnType, AgentType), usize>>,
+
+    /// For every car and pedestrian, when they entered each lane and turn, and how long they
+    /// waited at intersections. This is only used to replay one agent's progress in the UI, so
+    /// it's not saved, keeping prebaked results small. Only recorded with
+    /// `SimOptions::record_agent_progress`.
+    #[serde(skip_serializing, skip_deserializing)]
+    pub agent_progress: BTreeMap<AgentID, AgentProgress>,
 
     pub(crate) alerts: Vec<(Time, AlertLocation, String)>,
 
     /// For benchmarking, we may want to disable collecting data.
     record_anything: bool,
+    #[serde(default)]
+    record_agent_progress: bool,
+}
+
+/// Everywhere one agent has been, in order
+#[derive(Clone, Default)]
+pub struct AgentProgress {
+    /// When the agent entered each lane or turn
+    pub traversals: Vec<(Time, Traversable)>,
+    /// When the agent finished waiting to start a turn, and how long they waited
+    pub delays: Vec<(Time, TurnID, Duration)>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -79,7 +121,7 @@ pub enum Problem {
 }
 
 impl Analytics {
-    pub fn new(record_anything: bool) -> Analytics {
+    pub fn new(record_anything: bool, record_agent_progress: bool) -> Analytics {
         Analytics {
             road_thruput: TimeSeriesCount::new(),
             intersection_thruput: TimeSeriesCount::new(),
@@ -88,6 +130,7 @@ impl Analytics {
             bus_arrivals: Vec::new(),
             passengers_boarding: BTreeMap::new(),
             passengers_alighting: BTreeMap::new(),
+            missed_connections: BTreeMap::new(),
             started_trips: BTreeMap::new(),
             finished_trips: Vec::new(),
             problems_per_trip: BTreeMap::new(),
@@ -95,11 +138,65 @@ impl Analytics {
             intersection_delays: BTreeMap::new(),
             parking_lane_changes: BTreeMap::new(),
             parking_lot_changes: BTreeMap::new(),
+            parking_walks: Vec::new(),
+            cordon_charges: Vec::new(),
+            max_queue_extent: BTreeMap::new(),
+            turning_movements: BTreeMap::new(),
+            agent_progress: BTreeMap::new(),
             alerts: Vec::new(),
             record_anything,
+            record_agent_progress,
         }
     }
 
+    /// How many records each kind of metric has, to see at a glance what's available. (`demand`
+    /// is left out, since it only describes one moment.)
+    pub fn describe_contents(&self) -> Vec<(&'static str, usize)> {
+        fn total<K, V>(map: &BTreeMap<K, Vec<V>>) -> usize {
+            map.values().map(|list| list.len()).sum()
+        }
+
+        vec![
+            ("road throughput", self.road_thruput.counts.len()),
+            (
+                "intersection throughput",
+                self.intersection_thruput.counts.len(),
+            ),
+            (
+                "traffic signal throughput",
+                self.traffic_signal_thruput.counts.len(),
+            ),
+            ("bus arrivals", self.bus_arrivals.len()),
+            ("passengers boarding", total(&self.passengers_boarding)),
+            ("passengers alighting", total(&self.passengers_alighting)),
+            ("missed connections", total(&self.missed_connections)),
+            ("started trips", self.started_trips.len()),
+            ("finished trips", self.finished_trips.len()),
+            ("trip problems", total(&self.problems_per_trip)),
+            ("trip phases", self.trip_log.len()),
+            ("intersection delays", total(&self.intersection_delays)),
+            ("parking lane changes", total(&self.parking_lane_changes)),
+            ("parking lot changes", total(&self.parking_lot_changes)),
+            ("walks to and from parking", self.parking_walks.len()),
+            ("cordon charges", self.cordon_charges.len()),
+            ("queue spillback", self.max_queue_extent.len()),
+            (
+                "turning movement counts",
+                self.turning_movements
+                    .values()
+                    .map(|counts| counts.len())
+                    .sum(),
+            ),
+            (
+                "agent progress",
+                self.agent_progress
+                    .values()
+                    .map(|x| x.traversals.len())
+                    .sum(),
+            ),
+        ]
+    }
+
     pub fn event(&mut self, ev: Event, time: Time, map: &Map) {
         if !self.record_anything {
             return;
@@ -127,6 +224,21 @@ impl Analytics {
                         );
                     }
 
+                    let turn_type = map.get_t(t).turn_type;
+                    if turn_type != TurnType::SharedSidewalkCorner {
+                        *self
+                            .turning_movements
+                            .entry(t.parent)
+                            .or_insert_with(BTreeMap::new)
+                            .entry((
+                                ((time - Time::START_OF_DAY) / TURNING_MOVEMENT_INTERVAL) as usize,
+                                t.src.road,
+                                turn_type,
+                                a.to_type(),
+                            ))
+                            .or_insert(0) += 1;
+                    }
+
                     if let Some((id, compressed)) = map.get_movement_for_traffic_signal(t) {
                         *self.demand.entry(id).or_insert(0) -= 1;
                         self.traffic_signal_thruput
@@ -143,6 +255,26 @@ impl Analytics {
                 }
             };
         }
+        // Agent progress
+        if let Event::AgentEntersTraversable(a, _, on, _) = ev {
+            if self.record_agent_progress && !matches!(a, AgentID::BusPassenger(_, _)) {
+                self.agent_progress
+                    .entry(a)
+                    .or_insert_with(AgentProgress::default)
+                    .traversals
+                    .push((time, on));
+            }
+        }
+        if let Event::IntersectionDelayMeasured(_, turn, a, delay) = ev {
+            if self.record_agent_progress && !matches!(a, AgentID::BusPassenger(_, _)) {
+                self.agent_progress
+                    .entry(a)
+                    .or_insert_with(AgentProgress::default)
+                    .delays
+                    .push((time, turn, delay));
+            }
+        }
+
         match ev {
             Event::PersonLeavesMap(_, Some(a), i) => {
                 // Ignore cancelled trips
@@ -172,6 +304,12 @@ impl Analytics {
                 .or_insert_with(Vec::new)
                 .push((time, route));
         }
+        if let Event::MissedTransitConnection(_, route, stop, choice) = ev {
+            self.missed_connections
+                .entry(stop)
+                .or_insert_with(Vec::new)
+                .push((time, route, choice));
+        }
 
         // Started trips
         if let Event::TripPhaseStarting(id, _, _, _) = ev {
@@ -246,6 +384,15 @@ impl Analytics {
             }
         }
 
+        if let Event::ParkingWalk {
+            trip,
+            distance,
+            to_car,
+        } = ev
+        {
+            self.parking_walks.push((time, trip, distance, to_car));
+        }
+
         // Safety metrics
         if let Event::AgentEntersTraversable(a, Some(trip), Traversable::Turn(t), _) = ev {
             if a.to_type() == AgentType::Bike && map.get_i(t.parent).roads.len() > 4 {
@@ -269,6 +416,23 @@ impl Analytics {
             }
         }
 
+        // Congestion pricing
+        if let Event::AgentEntersTraversable(
+            AgentID::Car(car),
+            Some(trip),
+            Traversable::Turn(t),
+            _,
+        ) = ev
+        {
+            if car.vehicle_type == VehicleType::Car {
+                for (idx, cordon) in map.get_edits().charging_cordons.iter().enumerate() {
+                    if cordon.is_active(time) && cordon.is_entered_by(t.src.road, t.dst.road) {
+                        self.cordon_charges.push((time, trip, idx, cordon.price));
+                    }
+                }
+            }
+        }
+
         // TODO Kinda hacky, but these all consume the event, so kinda bundle em.
         match ev {
             Event::TripPhaseStarting(id, _, maybe_req, phase_type) => {
@@ -298,6 +462,32 @@ impl Analytics {
         }
     }
 
+    /// Given the current extent of queues on each lane, update the maximum upstream extent per
+    /// intersection.
+    pub fn record_queue_extents(
+        &mut self,
+        time: Time,
+        extents: &BTreeMap<LaneID, Distance>,
+        map: &Map,
+    ) {
+        if !self.record_anything {
+            return;
+        }
+
+        for l in extents.keys() {
+            let extent = upstream_queue_extent(*l, extents, map, &mut BTreeSet::new());
+            let i = map.get_l(*l).dst_i;
+            if self
+                .max_queue_extent
+                .get(&i)
+                .map(|(_, max)| extent > *max)
+                .unwrap_or(true)
+            {
+                self.max_queue_extent.insert(i, (time, extent));
+            }
+        }
+    }
+
     pub fn record_demand(&mut self, path: &Path, map: &Map) {
         for step in path.get_steps() {
             if let Traversable::Turn(t) = step.as_traversable() {
@@ -311,6 +501,68 @@ impl Analytics {
     // TODO If these ever need to be speeded up, just cache the histogram and index in the events
     // list.
 
+    /// For one charging cordon (by index into the map edits), returns the number of times a car
+    /// paid to enter and the total revenue.
+    pub fn cordon_revenue(&self, cordon: usize) -> (usize, f64) {
+        let mut entries = 0;
+        let mut revenue = 0.0;
+        for (_, _, idx, price) in &self.cordon_charges {
+            if *idx == cordon {
+                entries += 1;
+                revenue += price;
+            }
+        }
+        (entries, revenue)
+    }
+
+    /// Distributions of how far people walked to reach their parked car and how far they walked
+    /// after parking, up to some time.
+    pub fn parking_walk_distances(&self, now: Time) -> (Histogram<Distance>, Histogram<Distance>) {
+        let mut to_car = Histogram::new();
+        let mut from_car = Histogram::new();
+        for (t, _, dist, is_to_car) in &self.parking_walks {
+            if *t > now {
+                break;
+            }
+            if *is_to_car {
+                to_car.add(*dist);
+            } else {
+                from_car.add(*dist);
+            }
+        }
+        (to_car, from_car)
+    }
+
+    /// How many times have cars entered any of these roads, up to the current hour?
+    pub fn car_traffic_on_roads(&self, roads: &BTreeSet<RoadID>, now: Time) -> usize {
+        let mut cnt = 0;
+        for r in roads {
+            for hour in 0..=now.get_hours() {
+                cnt += self
+                    .road_thruput
+                    .counts
+                    .get(&(*r, AgentType::Car, hour))
+                    .cloned()
+                    .unwrap_or(0);
+            }
+        }
+        cnt
+    }
+
+    /// How many times have cars entered any of these roads during one hour block?
+    pub fn car_traffic_on_roads_during_hour(&self, roads: &BTreeSet<RoadID>, hour: usize) -> usize {
+        roads
+            .iter()
+            .map(|r| {
+                self.road_thruput
+                    .counts
+                    .get(&(*r, AgentType::Car, hour))
+                    .cloned()
+                    .unwrap_or(0)
+            })
+            .sum()
+    }
+
     /// Ignores the current time. Returns None for cancelled trips.
     pub fn finished_trip_time(&self, trip: TripID) -> Option<Duration> {
         // TODO This is so inefficient!
@@ -447,17 +699,48 @@ impl Analytics {
     }
 
     /// Returns the free spots over time
-    pub fn parking_lane_availability(
-        &self,
-        now: Time,
-        l: LaneID,
-        capacity: usize,
-    ) -> Vec<(Time, usize)> {
-        if let Some(changes) = self.parking_lane_changes.get(&l) {
+    /// While a time-of-day restriction closes the lane, nothing is available.
+    pub fn parking_lane_availability(&self, now: Time, l: LaneID, map: &Map) -> Vec<(Time, usize)> {
+        if !map.get_parent(l).curb_use(l).allows_parking() {
+            return vec![(Time::START_OF_DAY, 0), (now, 0)];
+        }
+        let capacity = map.get_l(l).number_parking_spots(map.get_config());
+        let pts = if let Some(changes) = self.parking_lane_changes.get(&l) {
             Analytics::parking_spot_availability(now, changes, capacity)
         } else {
             vec![(Time::START_OF_DAY, capacity), (now, capacity)]
+        };
+
+        let road = map.get_parent(l);
+        if road.parking_restrictions.is_empty() {
+            return pts;
         }
+        let mut times: Vec<Time> = pts.iter().map(|(t, _)| *t).collect();
+        times.extend(
+            road.parking_restrictions
+                .all_transitions()
+                .into_iter()
+                .filter(|t| *t <= now),
+        );
+        times.sort();
+        times.dedup();
+        times
+            .into_iter()
+            .map(|t| {
+                if road.parking_restriction_at(l, t).is_some() {
+                    (t, 0)
+                } else {
+                    // The step function's value at this time
+                    let cnt = pts
+                        .iter()
+                        .take_while(|(pt_time, _)| *pt_time <= t)
+                        .last()
+                        .map(|(_, cnt)| *cnt)
+                        .unwrap_or(capacity);
+                    (t, cnt)
+                }
+            })
+            .collect()
     }
     pub fn parking_lot_availability(
         &self,
@@ -505,11 +788,374 @@ impl Analytics {
         }
         pts
     }
+
+    /// Grades the average delay of vehicles through a traffic signal until some time, overall and
+    /// for each approaching road. Returns `None` if no vehicles have crossed yet.
+    pub fn level_of_service(
+        &self,
+        i: IntersectionID,
+        map: &Map,
+        until: Time,
+    ) -> Option<(GradedDelay, BTreeMap<RoadID, GradedDelay>)> {
+        let movements: Vec<&MovementID> = map.get_i(i).movements.keys().collect();
+        let mut overall = (Duration::ZERO, 0);
+        let mut approaches: BTreeMap<RoadID, (Duration, usize)> = BTreeMap::new();
+        for (idx, t, dt, agent_type) in self.intersection_delays.get(&i)? {
+            if *t > until {
+                break;
+            }
+            if *agent_type == AgentType::Pedestrian {
+                continue;
+            }
+            overall.0 += *dt;
+            overall.1 += 1;
+            if let Some(m) = movements.get(*idx as usize) {
+                let entry = approaches.entry(m.from.road).or_insert((Duration::ZERO, 0));
+                entry.0 += *dt;
+                entry.1 += 1;
+            }
+        }
+        if overall.1 == 0 {
+            return None;
+        }
+        Some((
+            GradedDelay::new(overall.0, overall.1),
+            approaches
+                .into_iter()
+                .map(|(r, (total, vehicles))| (r, GradedDelay::new(total, vehicles)))
+                .collect(),
+        ))
+    }
+}
+
+/// The Highway Capacity Manual's level of service grades for signalized intersections, based on
+/// the average control delay per vehicle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LevelOfService {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+}
+
+impl LevelOfService {
+    pub fn all() -> Vec<LevelOfService> {
+        vec![
+            LevelOfService::A,
+            LevelOfService::B,
+            LevelOfService::C,
+            LevelOfService::D,
+            LevelOfService::E,
+            LevelOfService::F,
+        ]
+    }
+
+    pub fn from_delay(delay: Duration) -> LevelOfService {
+        let secs = delay.inner_seconds();
+        if secs <= 10.0 {
+            LevelOfService::A
+        } else if secs <= 20.0 {
+            LevelOfService::B
+        } else if secs <= 35.0 {
+            LevelOfService::C
+        } else if secs <= 55.0 {
+            LevelOfService::D
+        } else if secs <= 80.0 {
+            LevelOfService::E
+        } else {
+            LevelOfService::F
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            LevelOfService::A => "up to 10s of delay",
+            LevelOfService::B => "10-20s of delay",
+            LevelOfService::C => "20-35s of delay",
+            LevelOfService::D => "35-55s of delay",
+            LevelOfService::E => "55-80s of delay",
+            LevelOfService::F => "more than 80s of delay",
+        }
+    }
+}
+
+impl fmt::Display for LevelOfService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// The average delay of some vehicles, and its level of service grade
+#[derive(Clone, Copy, Debug)]
+pub struct GradedDelay {
+    pub avg_delay: Duration,
+    pub vehicles: usize,
+    pub grade: LevelOfService,
+}
+
+impl GradedDelay {
+    fn new(total: Duration, vehicles: usize) -> GradedDelay {
+        let avg_delay = total / (vehicles as f64);
+        GradedDelay {
+            avg_delay,
+            vehicles,
+            grade: LevelOfService::from_delay(avg_delay),
+        }
+    }
+}
+
+/// How far back does the queue on this lane reach, continuing onto upstream lanes if it fills this
+/// one?
+fn upstream_queue_extent(
+    l: LaneID,
+    extents: &BTreeMap<LaneID, Distance>,
+    map: &Map,
+    visited: &mut BTreeSet<LaneID>,
+) -> Distance {
+    let extent = match extents.get(&l) {
+        Some(x) => *x,
+        None => {
+            return Distance::ZERO;
+        }
+    };
+    if !visited.insert(l) {
+        return Distance::ZERO;
+    }
+    // If there isn't room for another vehicle behind the queue, it's spilling back
+    if map.get_l(l).length() - extent > MAX_CAR_LENGTH {
+        return extent;
+    }
+    let mut upstream = Distance::ZERO;
+    for turn in map.get_turns_to_lane(l) {
+        upstream = upstream.max(upstream_queue_extent(turn.id.src, extents, map, visited));
+    }
+    extent + upstream
+}
+
+/// Turning movements are counted in intervals this long, the usual length for manual counts.
+pub const TURNING_MOVEMENT_INTERVAL: Duration = Duration::const_seconds(15.0 * 60.0);
+
+/// Turning movement counts at one intersection, arranged like a traffic engineer's count sheet.
+pub struct TurningMovementCounts {
+    /// Every road entering the intersection, labelled by the direction of travel, like "NB" for
+    /// northbound traffic coming from the south.
+    pub approaches: Vec<(RoadID, String)>,
+    /// The start of each interval with any activity, and the counts per approach, in the same
+    /// order as `approaches`
+    pub intervals: Vec<(Time, Vec<ApproachCounts>)>,
+}
+
+/// What happened on one approach during some period
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct ApproachCounts {
+    /// Motor vehicles by movement. Bikes aren't included.
+    pub left: usize,
+    pub through: usize,
+    pub right: usize,
+    pub u_turn: usize,
+    /// Bikes making any movement
+    pub bikes: usize,
+    /// Pedestrians crossing this approach
+    pub pedestrians: usize,
+}
+
+impl ApproachCounts {
+    pub fn vehicles(&self) -> usize {
+        self.left + self.through + self.right + self.u_turn
+    }
+
+    fn add(&mut self, other: &ApproachCounts) {
+        self.left += other.left;
+        self.through += other.through;
+        self.right += other.right;
+        self.u_turn += other.u_turn;
+        self.bikes += other.bikes;
+        self.pedestrians += other.pedestrians;
+    }
+}
+
+impl Analytics {
+    pub fn turning_movement_counts(&self, i: IntersectionID, map: &Map) -> TurningMovementCounts {
+        let mut approaches = Vec::new();
+        for r in &map.get_i(i).roads {
+            let road = map.get_r(*r);
+            // Direction of travel towards the intersection
+            let angle = if road.dst_i == i {
+                road.center_pts.last_line().angle()
+            } else {
+                road.center_pts.first_line().angle().opposite()
+            };
+            // Y points down, so north is 270 degrees
+            let dir = match angle.normalized_degrees() {
+                x if (45.0..135.0).contains(&x) => "SB",
+                x if (135.0..225.0).contains(&x) => "WB",
+                x if (225.0..315.0).contains(&x) => "NB",
+                _ => "EB",
+            };
+            approaches.push((*r, dir.to_string()));
+        }
+        // Count sheets conventionally list southbound, westbound, northbound, then eastbound
+        approaches.sort_by_key(|(r, dir)| {
+            (
+                ["SB", "WB", "NB", "EB"]
+                    .iter()
+                    .position(|x| *x == dir.as_str())
+                    .unwrap(),
+                *r,
+            )
+        });
+        // Disambiguate approaches from the same direction
+        let mut per_dir = Counter::new();
+        for (_, dir) in &approaches {
+            per_dir.inc(dir.clone());
+        }
+        for (r, dir) in &mut approaches {
+            if per_dir.get(dir.clone()) > 1 {
+                *dir = format!("{} {}", dir, map.get_r(*r).get_name(None));
+            }
+        }
+
+        let mut intervals: BTreeMap<usize, Vec<ApproachCounts>> = BTreeMap::new();
+        for ((interval, r, turn_type, agent_type), count) in self
+            .turning_movements
+            .get(&i)
+            .into_iter()
+            .flat_map(|counts| counts.iter())
+        {
+            let idx = approaches.iter().position(|(x, _)| x == r).unwrap();
+            let counts = &mut intervals
+                .entry(*interval)
+                .or_insert_with(|| vec![ApproachCounts::default(); approaches.len()])[idx];
+            match (agent_type, turn_type) {
+                (AgentType::Pedestrian, TurnType::Crosswalk | TurnType::UnmarkedCrossing) => {
+                    counts.pedestrians += count;
+                }
+                (AgentType::Pedestrian, _) => {}
+                (AgentType::Bike, _) => {
+                    counts.bikes += count;
+                }
+                (_, TurnType::Left) => {
+                    counts.left += count;
+                }
+                (_, TurnType::Straight) => {
+                    counts.through += count;
+                }
+                (_, TurnType::Right) => {
+                    counts.right += count;
+                }
+                (_, TurnType::UTurn) => {
+                    counts.u_turn += count;
+                }
+                _ => {}
+            }
+        }
+
+        TurningMovementCounts {
+            approaches,
+            intervals: intervals
+                .into_iter()
+                .map(|(interval, counts)| {
+                    (
+                        Time::START_OF_DAY + TURNING_MOVEMENT_INTERVAL * (interval as f64),
+                        counts,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TurningMovementCounts {
+    /// The hour starting at some interval with the most motor vehicles, and the total counts per
+    /// approach during it
+    pub fn peak_hour(&self) -> Option<(Time, Vec<ApproachCounts>)> {
+        let vehicles =
+            |counts: &[ApproachCounts]| -> usize { counts.iter().map(|c| c.vehicles()).sum() };
+        let mut best: Option<(Time, Vec<ApproachCounts>)> = None;
+        for (start, _) in &self.intervals {
+            let mut totals = vec![ApproachCounts::default(); self.approaches.len()];
+            for (time, counts) in &self.intervals {
+                if *time >= *start && *time < *start + Duration::hours(1) {
+                    for (total, count) in totals.iter_mut().zip(counts) {
+                        total.add(count);
+                    }
+                }
+            }
+            if best
+                .as_ref()
+                .map(|(_, x)| vehicles(&totals) > vehicles(x))
+                .unwrap_or(true)
+            {
+                best = Some((*start, totals));
+            }
+        }
+        best
+    }
+
+    /// The approach whose traffic comes from the opposite direction, if there's exactly one
+    pub fn opposing(&self, idx: usize) -> Option<usize> {
+        let dir = &self.approaches[idx].1[0..2];
+        let opposite = match dir {
+            "NB" => "SB",
+            "SB" => "NB",
+            "EB" => "WB",
+            _ => "EB",
+        };
+        let matches: Vec<usize> = self
+            .approaches
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, x))| x.starts_with(opposite))
+            .map(|(idx, _)| idx)
+            .collect();
+        if matches.len() == 1 {
+            Some(matches[0])
+        } else {
+            None
+        }
+    }
+
+    /// One row per 15 minute interval, with columns for each movement on each approach, like a
+    /// manual count sheet
+    pub fn export_csv(&self, path: &str) -> Result<()> {
+        let mut f = File::create(path)?;
+        let mut header = vec!["Start".to_string(), "End".to_string()];
+        for (_, dir) in &self.approaches {
+            for movement in ["Left", "Thru", "Right", "U-Turn", "Bikes", "Peds"] {
+                header.push(format!("{} {}", dir, movement));
+            }
+        }
+        header.push("Total vehicles".to_string());
+        writeln!(f, "{}", header.join(","))?;
+
+        for (start, counts) in &self.intervals {
+            let mut row = vec![
+                start.to_string(),
+                (*start + TURNING_MOVEMENT_INTERVAL).to_string(),
+            ];
+            for c in counts {
+                for x in [c.left, c.through, c.right, c.u_turn, c.bikes, c.pedestrians] {
+                    row.push(x.to_string());
+                }
+            }
+            row.push(
+                counts
+                    .iter()
+                    .map(|c| c.vehicles())
+                    .sum::<usize>()
+                    .to_string(),
+            );
+            writeln!(f, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for Analytics {
     fn default() -> Analytics {
-        Analytics::new(false)
+        Analytics::new(false, false)
     }
 }
 
@@ -0,0 +1,298 @@
+//! A bike share system lets people ride a shared bike between docking stations. In a
+//! free-floating system, riders can also leave a bike right at their destination, where the next
+//! person nearby can pick it up. The system is described separately from the map, so a proposal
+//! to expand it can be tested without map edits.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use abstutil::Counter;
+use geom::{Distance, Duration, Pt2D, Time};
+use map_model::{BuildingID, Map};
+
+use crate::{CarID, Command, Scheduler};
+
+/// The stations and policies of a bike share system.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BikeShareSystem {
+    pub stations: Vec<BikeShareStation>,
+    /// Riders can leave bikes at their destination, not just at a station with a free dock.
+    pub free_floating: bool,
+    /// How far somebody will go, as the crow flies, to pick up or leave a bike
+    pub max_walk: Distance,
+    /// How often crews even out the bikes between stations. This happens instantly; the trucks
+    /// aren't simulated.
+    pub rebalance_every: Option<Duration>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BikeShareStation {
+    /// The docks are next to this building
+    pub bldg: BuildingID,
+    pub capacity: usize,
+    /// How many bikes are docked here at the start of the day
+    pub initial_bikes: usize,
+}
+
+impl Default for BikeShareSystem {
+    fn default() -> BikeShareSystem {
+        BikeShareSystem {
+            stations: Vec::new(),
+            free_floating: false,
+            max_walk: Distance::meters(400.0),
+            rebalance_every: Some(Duration::hours(1)),
+        }
+    }
+}
+
+impl BikeShareSystem {
+    pub fn num_docks(&self) -> usize {
+        self.stations.iter().map(|s| s.capacity).sum()
+    }
+
+    pub fn num_bikes(&self) -> usize {
+        self.stations
+            .iter()
+            .map(|s| s.initial_bikes.min(s.capacity))
+            .sum()
+    }
+
+    pub fn get_station(&self, b: BuildingID) -> Option<&BikeShareStation> {
+        self.stations.iter().find(|s| s.bldg == b)
+    }
+}
+
+/// How a bike share system has been used so far.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BikeShareStats {
+    /// When each ride started, where the bike was picked up, and where it'll be left
+    pub rides: Vec<(Time, BuildingID, BuildingID)>,
+    /// Trip origins where somebody wanted a bike, but none were close enough, so they walked
+    pub no_bike: Counter<BuildingID>,
+    /// Trip destinations where somebody found a bike, but no free dock was close enough, so they
+    /// walked
+    pub no_dock: Counter<BuildingID>,
+    pub bikes_rebalanced: usize,
+}
+
+/// Tracks where every shared bike is. While somebody rides one, it's represented by their own
+/// bike vehicle, and a dock at the end of their ride is held for them.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct BikeShareState {
+    system: Option<BikeShareSystem>,
+    /// How many bikes are waiting at each station, or for free-floating systems, any building
+    available: BTreeMap<BuildingID, usize>,
+    /// Docks held for riders on their way
+    reserved: BTreeMap<BuildingID, usize>,
+    /// Where each bike being ridden will be left
+    riding: BTreeMap<CarID, BuildingID>,
+    stats: BikeShareStats,
+}
+
+impl BikeShareState {
+    pub fn new(system: Option<BikeShareSystem>, scheduler: &mut Scheduler) -> BikeShareState {
+        let mut available = BTreeMap::new();
+        if let Some(ref system) = system {
+            for station in &system.stations {
+                available.insert(station.bldg, station.initial_bikes.min(station.capacity));
+            }
+            if let Some(dt) = system.rebalance_every {
+                scheduler.push(Time::START_OF_DAY + dt, Command::RebalanceBikeShare);
+            }
+        }
+        BikeShareState {
+            system,
+            available,
+            reserved: BTreeMap::new(),
+            riding: BTreeMap::new(),
+            stats: BikeShareStats::default(),
+        }
+    }
+
+    pub fn get_system(&self) -> Option<&BikeShareSystem> {
+        self.system.as_ref()
+    }
+
+    pub fn get_available(&self) -> &BTreeMap<BuildingID, usize> {
+        &self.available
+    }
+
+    pub fn get_stats(&self) -> &BikeShareStats {
+        &self.stats
+    }
+
+    /// Finds a bike near the start and somewhere to leave it near the end, then takes the bike
+    /// and holds the dock. Returns where to pick up and leave the bike, or `None` if the person
+    /// should walk instead.
+    pub fn start_ride(
+        &mut self,
+        now: Time,
+        bike: CarID,
+        start: BuildingID,
+        end: BuildingID,
+        map: &Map,
+    ) -> Option<(BuildingID, BuildingID)> {
+        let system = if let Some(ref system) = self.system {
+            system
+        } else {
+            self.stats.no_bike.inc(start);
+            return None;
+        };
+
+        let start_pt = map.get_b(start).polygon.center();
+        let pickup = if let Some(b) = closest(
+            start_pt,
+            system.max_walk,
+            self.available
+                .iter()
+                .filter(|(_, n)| **n > 0)
+                .map(|(b, _)| *b),
+            map,
+        ) {
+            b
+        } else {
+            self.stats.no_bike.inc(start);
+            return None;
+        };
+
+        let end_pt = map.get_b(end).polygon.center();
+        let dropoff = if system.free_floating
+            && system.get_station(end).is_none()
+            && map.get_b(end).biking_connection(map).is_some()
+        {
+            end
+        } else if let Some(b) = closest(
+            end_pt,
+            system.max_walk,
+            system
+                .stations
+                .iter()
+                .filter(|s| {
+                    count(&self.available, s.bldg) + count(&self.reserved, s.bldg) < s.capacity
+                })
+                .map(|s| s.bldg),
+            map,
+        ) {
+            b
+        } else {
+            self.stats.no_dock.inc(end);
+            return None;
+        };
+
+        // Both ends must be reachable by bike, and riding to a different spot along the same road
+        // is silly.
+        match (
+            map.get_b(pickup).biking_connection(map),
+            map.get_b(dropoff).biking_connection(map),
+        ) {
+            (Some((pos1, _)), Some((pos2, _))) if pos1.lane() != pos2.lane() => {}
+            _ => {
+                return None;
+            }
+        }
+
+        *self.available.get_mut(&pickup).unwrap() -= 1;
+        if system.get_station(pickup).is_none() && self.available[&pickup] == 0 {
+            self.available.remove(&pickup);
+        }
+        if system.get_station(dropoff).is_some() {
+            *self.reserved.entry(dropoff).or_insert(0) += 1;
+        }
+        self.riding.insert(bike, dropoff);
+        self.stats.rides.push((now, pickup, dropoff));
+        Some((pickup, dropoff))
+    }
+
+    /// The bike was left where planned, or the ride was cancelled and the bike is warped there.
+    /// Does nothing for bikes that aren't shared.
+    pub fn end_ride(&mut self, bike: CarID) {
+        if let Some(dropoff) = self.riding.remove(&bike) {
+            if let Some(n) = self.reserved.get_mut(&dropoff) {
+                *n -= 1;
+                if *n == 0 {
+                    self.reserved.remove(&dropoff);
+                }
+            }
+            *self.available.entry(dropoff).or_insert(0) += 1;
+        }
+    }
+
+    /// Spread all parked bikes across the stations, in proportion to the free docks at each.
+    /// Bikes left outside stations are collected too.
+    pub fn rebalance(&mut self, now: Time, scheduler: &mut Scheduler) {
+        let system = self.system.as_ref().unwrap();
+        if let Some(dt) = system.rebalance_every {
+            scheduler.push(now + dt, Command::RebalanceBikeShare);
+        }
+        if system.stations.is_empty() {
+            return;
+        }
+
+        let total_bikes: usize = self.available.values().sum();
+        let room: Vec<usize> = system
+            .stations
+            .iter()
+            .map(|s| s.capacity.saturating_sub(count(&self.reserved, s.bldg)))
+            .collect();
+        let total_room: usize = room.iter().sum();
+        if total_room == 0 {
+            return;
+        }
+
+        let mut target: Vec<usize> = room
+            .iter()
+            .map(|r| (total_bikes * r / total_room).min(*r))
+            .collect();
+        // Hand out whatever's left after rounding down, one at a time
+        let mut leftover = total_bikes - target.iter().sum::<usize>();
+        for (idx, r) in room.iter().enumerate() {
+            if leftover == 0 {
+                break;
+            }
+            if target[idx] < *r {
+                target[idx] += 1;
+                leftover -= 1;
+            }
+        }
+
+        let mut moved = 0;
+        let mut new_available = BTreeMap::new();
+        for (station, n) in system.stations.iter().zip(target) {
+            moved += n.saturating_sub(count(&self.available, station.bldg));
+            new_available.insert(station.bldg, n);
+        }
+        if leftover > 0 {
+            // More bikes than docks, so some free-floating bikes stay where they are
+            for (b, n) in &self.available {
+                if system.get_station(*b).is_none() {
+                    let keep = leftover.min(*n);
+                    new_available.insert(*b, keep);
+                    leftover -= keep;
+                }
+            }
+        }
+        self.available = new_available;
+        self.available
+            .retain(|b, n| *n > 0 || system.get_station(*b).is_some());
+        self.stats.bikes_rebalanced += moved;
+    }
+}
+
+fn count(map: &BTreeMap<BuildingID, usize>, b: BuildingID) -> usize {
+    map.get(&b).cloned().unwrap_or(0)
+}
+
+/// The closest candidate within some straight-line distance
+fn closest<I: Iterator<Item = BuildingID>>(
+    pt: Pt2D,
+    max_dist: Distance,
+    candidates: I,
+    map: &Map,
+) -> Option<BuildingID> {
+    candidates
+        .map(|b| (b, pt.dist_to(map.get_b(b).polygon.center())))
+        .filter(|(_, dist)| *dist <= max_dist)
+        .min_by_key(|(_, dist)| *dist)
+        .map(|(b, _)| b)
+}
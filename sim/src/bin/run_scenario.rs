@@ -12,13 +12,20 @@ struct Args {
     /// How many hours to simulate.
     #[structopt(long)]
     hours: usize,
+    /// Print logs, progress, and summaries as JSON lines, for other programs to parse.
+    #[structopt(long)]
+    json_logs: bool,
     #[structopt(flatten)]
     flags: sim::SimFlags,
 }
 
 fn main() {
-    abstutil::logger::setup();
     let mut args = Args::from_args();
+    if args.json_logs {
+        abstutil::logger::setup_json();
+    } else {
+        abstutil::logger::setup();
+    }
     args.flags.initialize();
     let hours = geom::Duration::hours(args.hours);
     let (mut map, mut sim, _) = args
@@ -41,12 +48,7 @@ fn main() {
         let start = instant::Instant::now();
         let goal_time = geom::Time::START_OF_DAY + hours;
         while running.load(Ordering::SeqCst) {
-            println!(
-                "After {}, the sim is at {}. {} live agents",
-                geom::Duration::realtime_elapsed(start),
-                sim.time(),
-                abstutil::prettyprint_usize(sim.active_agents().len())
-            );
+            sim.print_progress(geom::Duration::realtime_elapsed(start), &map);
             sim.time_limited_step(
                 &map,
                 goal_time - sim.time(),
@@ -57,10 +59,10 @@ fn main() {
                 return;
             }
         }
-        println!("\n\nInterrupting at {}", sim.time());
+        log::info!("Interrupting at {}", sim.time());
         sim.save();
         for x in sim.describe_internal_stats() {
-            println!("{}", x);
+            log::info!("{}", x);
         }
     } else {
         sim.timed_step(
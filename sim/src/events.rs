@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use geom::Duration;
+use geom::{Distance, Duration};
 use map_model::{
     BuildingID, IntersectionID, LaneID, Map, Path, PathRequest, TransitRouteID, TransitStopID,
     Traversable, TurnID,
@@ -27,6 +27,13 @@ pub enum Event {
     /// How long waiting at the stop?
     PassengerBoardsTransit(PersonID, CarID, TransitRouteID, TransitStopID, Duration),
     PassengerAlightsTransit(PersonID, CarID, TransitRouteID, TransitStopID),
+    /// Someone reached a stop just after their vehicle left, and decided what to do instead.
+    MissedTransitConnection(
+        PersonID,
+        TransitRouteID,
+        TransitStopID,
+        MissedConnectionChoice,
+    ),
 
     PersonEntersBuilding(PersonID, BuildingID),
     PersonLeavesBuilding(PersonID, BuildingID),
@@ -35,6 +42,14 @@ pub enum Event {
     PersonEntersMap(PersonID, AgentID, IntersectionID),
 
     PedReachedParkingSpot(PedestrianID, ParkingSpot),
+    /// Someone starts walking to their parked car, or away from where they just parked. The
+    /// distance is the length of the walking path, or zero when they parked inside their
+    /// destination.
+    ParkingWalk {
+        trip: TripID,
+        distance: Distance,
+        to_car: bool,
+    },
 
     BikeStoppedAtSidewalk(CarID, LaneID),
 
@@ -70,6 +85,15 @@ pub enum AlertLocation {
     Building(BuildingID),
 }
 
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum MissedConnectionChoice {
+    WaitForNext,
+    /// Wait for a different route that also goes to the same stop
+    SwitchRoute(TransitRouteID),
+    /// Give up on transit and walk the rest of the way
+    Walk,
+}
+
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum TripPhaseType {
     Driving,
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use abstutil::prettyprint_usize;
+use geom::{Duration, Histogram, Statistic, Time};
+use map_model::Map;
+use synthpop::TripMode;
+
+use crate::{AgentType, Sim};
+
+/// https://www.epa.gov/greenvehicles/greenhouse-gas-emissions-typical-passenger-vehicle#driving
+/// says a typical passenger vehicle emits 404 grams of CO2 per mile.
+const CO2_KG_PER_METER_DRIVEN: f64 = 0.404 / 1609.344;
+
+/// A standard bundle of key performance indicators about a simulation, so that headless runs, the
+/// API, and the UI all report the same numbers.
+///
+/// Durations and distances are stored as f64 seconds and meters, since the serialized forms of
+/// `Duration` and `Distance` have a low cap.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimKpis {
+    pub time: Time,
+    pub finished_trips: usize,
+    pub cancelled_trips: usize,
+    /// Trips that haven't started yet or are still in progress
+    pub unfinished_trips: usize,
+    pub active_agents: usize,
+    pub total_trip_duration_seconds: f64,
+    /// Delay is the time a finished trip spent blocked -- waiting at intersections, stuck behind
+    /// other agents, or waiting for transit.
+    pub delay_per_mode: BTreeMap<TripMode, ModeDelay>,
+    /// Distance driven by cars, estimated from how many entered each road
+    pub vehicle_distance_meters: f64,
+    /// Only present when `SimOptions::estimate_emissions` is enabled
+    pub co2_emissions_kg: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModeDelay {
+    pub finished_trips: usize,
+    pub mean_delay_seconds: f64,
+    pub p95_delay_seconds: f64,
+}
+
+impl SimKpis {
+    /// One line per indicator
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("At {}", self.time),
+            format!(
+                "{} finished trips, {} cancelled, {} unfinished",
+                prettyprint_usize(self.finished_trips),
+                prettyprint_usize(self.cancelled_trips),
+                prettyprint_usize(self.unfinished_trips)
+            ),
+            format!("{} active agents", prettyprint_usize(self.active_agents)),
+        ];
+        for (mode, delay) in &self.delay_per_mode {
+            lines.push(format!(
+                "{} trips: mean delay {}, 95%ile delay {}, out of {} trips",
+                mode.noun(),
+                Duration::seconds(delay.mean_delay_seconds),
+                Duration::seconds(delay.p95_delay_seconds),
+                prettyprint_usize(delay.finished_trips)
+            ));
+        }
+        lines.push(format!(
+            "{:.1} km driven by cars",
+            self.vehicle_distance_meters / 1000.0
+        ));
+        if let Some(kg) = self.co2_emissions_kg {
+            lines.push(format!("{:.1} kg of CO2 emitted by cars", kg));
+        }
+        lines
+    }
+}
+
+impl Sim {
+    /// Summarize the simulation so far.
+    pub fn kpis(&self, map: &Map) -> SimKpis {
+        let (_, unfinished_trips) = self.num_trips();
+        let mut finished_trips = 0;
+        let mut cancelled_trips = 0;
+        let mut total_trip_duration_seconds = 0.0;
+        let mut delays: BTreeMap<TripMode, Histogram<Duration>> = BTreeMap::new();
+        for (_, id, mode, maybe_dt) in &self.get_analytics().finished_trips {
+            if let Some(dt) = maybe_dt {
+                finished_trips += 1;
+                total_trip_duration_seconds += dt.inner_seconds();
+                delays
+                    .entry(*mode)
+                    .or_insert_with(Histogram::new)
+                    .add(self.trip_blocked_time(*id));
+            } else {
+                cancelled_trips += 1;
+            }
+        }
+        let delay_per_mode = delays
+            .into_iter()
+            .map(|(mode, hgram)| {
+                let seconds = |x: Option<Duration>| x.unwrap_or(Duration::ZERO).inner_seconds();
+                (
+                    mode,
+                    ModeDelay {
+                        finished_trips: hgram.count(),
+                        mean_delay_seconds: seconds(hgram.select(Statistic::Mean)),
+                        p95_delay_seconds: seconds(hgram.percentile(95.0)),
+                    },
+                )
+            })
+            .collect();
+
+        let mut vehicle_distance_meters = 0.0;
+        for ((r, agent_type, _), count) in &self.get_analytics().road_thruput.counts {
+            if *agent_type == AgentType::Car {
+                vehicle_distance_meters += (*count as f64) * map.get_r(*r).length().inner_meters();
+            }
+        }
+
+        SimKpis {
+            time: self.time(),
+            finished_trips,
+            cancelled_trips,
+            unfinished_trips,
+            active_agents: self.num_active_agents(),
+            total_trip_duration_seconds,
+            delay_per_mode,
+            vehicle_distance_meters,
+            co2_emissions_kg: if self.estimate_emissions {
+                Some(vehicle_distance_meters * CO2_KG_PER_METER_DRIVEN)
+            } else {
+                None
+            },
+        }
+    }
+}
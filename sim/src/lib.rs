@@ -34,29 +34,43 @@ pub use crate::render::{
     UnzoomedAgent,
 };
 
-pub use self::analytics::{Analytics, Problem, SlidingWindow, TripPhase};
-pub(crate) use self::events::Event;
-pub use self::events::{AlertLocation, TripPhaseType};
-pub use self::make::{fork_rng, BorderSpawnOverTime, ScenarioGenerator, SimFlags, SpawnOverTime};
+pub use self::analytics::{
+    AgentProgress, Analytics, ApproachCounts, GradedDelay, LevelOfService, Problem, SlidingWindow,
+    TripPhase, TurningMovementCounts, TURNING_MOVEMENT_INTERVAL,
+};
+pub(crate) use self::bike_share::BikeShareState;
+pub use self::bike_share::{BikeShareStation, BikeShareStats, BikeShareSystem};
+pub use self::events::{AlertLocation, Event, MissedConnectionChoice, TripPhaseType};
+pub use self::kpis::{ModeDelay, SimKpis};
+pub use self::make::{
+    fork_rng, BorderSpawnOverTime, ReproBundle, ScenarioGenerator, SimFlags, SpawnOverTime,
+};
 pub(crate) use self::make::{StartTripArgs, TripSpec};
 pub(crate) use self::mechanics::{
     DrivingSimState, IntersectionSimState, ParkingSim, ParkingSimState, WalkingSimState,
 };
 pub(crate) use self::pandemic::PandemicModel;
-pub use self::prebake::PrebakeSummary;
+pub use self::prebake::{PrebakeMetadata, PrebakeSummary, Reliability, ReliabilityResults};
 pub(crate) use self::recorder::TrafficRecorder;
 pub(crate) use self::router::{ActionAtEnd, Router};
 pub(crate) use self::scheduler::{Command, Scheduler};
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::sim::BackgroundSim;
 pub use self::sim::{
-    count_parked_cars_per_bldg, rand_dist, AgentProperties, AlertHandler, DelayCause, Sim,
-    SimCallback, SimOptions,
+    count_parked_cars_per_bldg, rand_dist, AgentProperties, AlertHandler, DelayCause, EventFilter,
+    ProfileBucket, SavestateHistory, Sim, SimCallback, SimOptions, SimProfile, SubscriptionID,
+    Subsystem, Weather,
 };
 pub(crate) use self::transit::TransitSimState;
-pub use self::trips::{CommutersVehiclesCounts, Person, PersonState, TripInfo, TripResult};
+pub use self::trips::{
+    Activity, CommutersVehiclesCounts, Person, PersonState, TripInfo, TripResult,
+};
 pub(crate) use self::trips::{TripLeg, TripManager};
 
 mod analytics;
+mod bike_share;
 mod events;
+mod kpis;
 mod make;
 mod mechanics;
 mod pandemic;
@@ -73,6 +87,11 @@ mod trips;
 pub(crate) const BIKE_LENGTH: Distance = Distance::const_meters(1.8);
 pub(crate) const MIN_CAR_LENGTH: Distance = Distance::const_meters(4.5);
 pub(crate) const MAX_CAR_LENGTH: Distance = Distance::const_meters(6.5);
+// Box vans and pickups towing something. These still fit in a street parking spot.
+pub(crate) const MIN_TRUCK_LENGTH: Distance = Distance::const_meters(7.0);
+pub(crate) const MAX_TRUCK_LENGTH: Distance = Distance::const_meters(7.5);
+// The fraction of privately owned cars that're trucks
+pub(crate) const PCT_TRUCKS: f64 = 0.05;
 // Note this is more than MAX_CAR_LENGTH
 pub(crate) const BUS_LENGTH: Distance = Distance::const_meters(12.5);
 pub(crate) const LIGHT_RAIL_LENGTH: Distance = Distance::const_meters(60.0);
@@ -316,6 +335,43 @@ pub struct Vehicle {
     pub max_speed: Option<Speed>,
 }
 
+impl Vehicle {
+    /// Some privately owned vehicles are trucks, which take up more room and are more sluggish
+    /// than cars. They're told apart only by their length.
+    pub fn is_truck(&self) -> bool {
+        self.vehicle_type == VehicleType::Car && self.length >= MIN_TRUCK_LENGTH
+    }
+
+    /// How quickly the vehicle speeds up from a stop, in m/s^2. Longer cars are usually vans and
+    /// pickup trucks, which are slower than smaller cars.
+    pub(crate) fn acceleration(&self) -> f64 {
+        match self.vehicle_type {
+            VehicleType::Car if self.is_truck() => 1.2,
+            VehicleType::Car => {
+                let pct_long = ((self.length - MIN_CAR_LENGTH) / (MAX_CAR_LENGTH - MIN_CAR_LENGTH))
+                    .max(0.0)
+                    .min(1.0);
+                2.5 - pct_long
+            }
+            VehicleType::Bus => 1.0,
+            VehicleType::Train => 1.0,
+            VehicleType::Bike => 1.0,
+        }
+    }
+
+    /// How quickly the vehicle comfortably brakes for a stop, in m/s^2. Transit vehicles go
+    /// gently, since passengers may be standing.
+    pub(crate) fn deceleration(&self) -> f64 {
+        match self.vehicle_type {
+            VehicleType::Car if self.is_truck() => 2.0,
+            VehicleType::Car => 3.0,
+            VehicleType::Bus => 1.5,
+            VehicleType::Train => 1.2,
+            VehicleType::Bike => 2.0,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct VehicleSpec {
     pub vehicle_type: VehicleType,
@@ -355,6 +411,8 @@ pub struct ParkedCar {
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) enum DrivingGoal {
     ParkNear(BuildingID),
+    /// Only cars use this, for park-and-ride
+    ParkInLot(ParkingLotID),
     Border(IntersectionID, LaneID),
 }
 
@@ -376,6 +434,7 @@ impl DrivingGoal {
                     unreachable!()
                 }
             },
+            DrivingGoal::ParkInLot(pl) => Some(map.get_pl(*pl).driving_pos),
             DrivingGoal::Border(_, l) => Some(Position::end(*l, map)),
         }
     }
@@ -389,6 +448,7 @@ impl DrivingGoal {
                     Router::park_near(owner, path, *b)
                 }
             }
+            DrivingGoal::ParkInLot(pl) => Router::park_in_lot(owner, path, *pl),
             DrivingGoal::Border(i, last_lane) => {
                 Router::end_at_border(owner, path, map.get_l(*last_lane).length(), *i)
             }
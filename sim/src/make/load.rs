@@ -7,7 +7,7 @@ use abstio::MapName;
 use map_model::{Map, MapEdits};
 use synthpop::{Scenario, ScenarioModifier};
 
-use crate::{Sim, SimOptions};
+use crate::{ReproBundle, Sim, SimOptions};
 
 /// SimFlags specifies a simulation to setup. After parsing from structopt, you must call
 /// `initialize`.
@@ -18,6 +18,7 @@ pub struct SimFlags {
     /// - some kind of map: start an empty simulation on the map
     /// - a scenario
     /// - a savestate: restore the simulation exactly from some savestate
+    /// - a repro bundle: replay the simulation up to when a problem happened
     #[structopt()]
     load_path: Option<String>,
     /// The same as `load_path`, but with a default value filled out. Call `initialize` to set this
@@ -92,7 +93,7 @@ impl SimFlags {
             ) {
                 Ok(edits) => {
                     map.must_apply_edits(edits, timer);
-                    map.recalculate_pathfinding_after_edits(timer);
+                    map.recalculate_pathfinding_after_loading_proposal(timer);
                 }
                 Err(err) => {
                     panic!("Couldn't load edits \"{}\": {}", sim.edits_name, err);
@@ -100,6 +101,16 @@ impl SimFlags {
             }
 
             (map, sim, rng)
+        } else if self.load.starts_with(&abstio::path_player("repros/")) {
+            info!("Replaying {}", self.load);
+
+            let bundle: ReproBundle = abstio::must_read_object(self.load.clone(), timer);
+            match bundle.replay(timer) {
+                Ok(result) => result,
+                Err(err) => {
+                    panic!("Couldn't replay {}: {}", self.load, err);
+                }
+            }
         } else if self.load.contains("/scenarios/") {
             info!("Seeding the simulation from scenario {}", self.load);
 
@@ -107,7 +118,11 @@ impl SimFlags {
 
             let map = Map::load_synchronously(scenario.map_name.path(), timer);
 
-            for m in &self.scenario_modifiers {
+            for m in self
+                .scenario_modifiers
+                .iter()
+                .chain(opts.weather_modifiers().iter())
+            {
                 scenario = m.apply(&map, scenario);
             }
 
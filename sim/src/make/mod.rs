@@ -6,11 +6,13 @@ use rand_xorshift::XorShiftRng;
 
 pub use self::generator::{BorderSpawnOverTime, ScenarioGenerator, SpawnOverTime};
 pub use self::load::SimFlags;
+pub use self::repro::ReproBundle;
 pub(crate) use self::spawner::{StartTripArgs, TripSpec};
 
 mod activity_model;
 mod generator;
 mod load;
+mod repro;
 mod spawner;
 
 /// Need to explain this trick -- basically keeps consistency between two different simulations when
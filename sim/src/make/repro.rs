@@ -0,0 +1,115 @@
+use anyhow::Result;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+
+use abstio::MapName;
+use abstutil::Timer;
+use geom::Time;
+use map_model::{Map, PermanentMapEdits};
+use synthpop::{Scenario, ScenarioModifier};
+
+use crate::{Sim, SimOptions};
+
+/// Everything needed to run the same simulation on another machine and watch a problem happen
+/// again. Attach one of these to reports about gridlock or crashes.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReproBundle {
+    pub map_name: MapName,
+    /// The md5sum of the map file. If the map was imported differently on the other machine, the
+    /// problem may not reproduce. This can't be calculated on the web.
+    pub map_md5sum: Option<String>,
+    pub edits: PermanentMapEdits,
+    /// The scenario before any modifiers are applied
+    pub scenario: Scenario,
+    pub scenario_modifiers: Vec<ScenarioModifier>,
+    pub rng_seed: u64,
+    /// The pandemic model's RNG and the alert handler aren't preserved.
+    pub opts: SimOptions,
+    /// When the problem happened
+    pub time: Time,
+    /// Anything the reporter wants to say about the problem
+    pub notes: String,
+}
+
+impl ReproBundle {
+    pub fn new(
+        map: &Map,
+        scenario: Scenario,
+        scenario_modifiers: Vec<ScenarioModifier>,
+        rng_seed: u64,
+        opts: SimOptions,
+        time: Time,
+        notes: String,
+    ) -> ReproBundle {
+        #[cfg(not(target_arch = "wasm32"))]
+        let map_md5sum = abstio::md5sum_file(&map.get_name().path()).ok();
+        #[cfg(target_arch = "wasm32")]
+        let map_md5sum = None;
+
+        ReproBundle {
+            map_name: map.get_name().clone(),
+            map_md5sum,
+            edits: map.get_edits().to_permanent(map),
+            scenario,
+            scenario_modifiers,
+            rng_seed,
+            opts,
+            time,
+            notes,
+        }
+    }
+
+    /// Writes the bundle and returns its path.
+    pub fn save(&self) -> String {
+        let path = abstio::path_player(format!(
+            "repros/{}_{}_{}.json",
+            self.map_name.as_filename(),
+            self.scenario.scenario_name,
+            self.time.as_filename()
+        ));
+        abstio::write_json(path.clone(), self);
+        path
+    }
+
+    /// Loads the map and edits, and sets up the simulation at midnight, exactly as it was set up
+    /// originally. Not appropriate for use in the UI or on web.
+    pub fn load_synchronously(&self, timer: &mut Timer) -> Result<(Map, Sim, XorShiftRng)> {
+        let mut map = Map::load_synchronously(self.map_name.path(), timer);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ref expected) = self.map_md5sum {
+            if abstio::md5sum_file(&self.map_name.path())? != *expected {
+                warn!(
+                    "{} differs from the map the repro was made with; the problem may not happen \
+                     again",
+                    self.map_name.path()
+                );
+            }
+        }
+
+        let edits = self.edits.clone().into_edits(&map)?;
+        map.must_apply_edits(edits, timer);
+        map.recalculate_pathfinding_after_edits(timer);
+
+        let mut scenario = self.scenario.clone();
+        for m in self
+            .scenario_modifiers
+            .iter()
+            .chain(self.opts.weather_modifiers().iter())
+        {
+            scenario = m.apply(&map, scenario);
+        }
+
+        let mut rng = XorShiftRng::seed_from_u64(self.rng_seed);
+        let mut sim = Sim::new(&map, self.opts.clone());
+        sim.instantiate(&scenario, &map, &mut rng, timer);
+        Ok((map, sim, rng))
+    }
+
+    /// Like `load_synchronously`, but also runs the simulation up to when the problem happened.
+    pub fn replay(&self, timer: &mut Timer) -> Result<(Map, Sim, XorShiftRng)> {
+        let (map, mut sim, rng) = self.load_synchronously(timer)?;
+        sim.timed_step(&map, self.time - Time::START_OF_DAY, &mut None, timer);
+        Ok((map, sim, rng))
+    }
+}
@@ -4,10 +4,16 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-use map_model::{BuildingID, Map, PathConstraints, Position, TransitRouteID, TransitStopID};
+use geom::Time;
+use map_model::{
+    BuildingID, Map, ParkingLotID, PathConstraints, Position, TransitRouteID, TransitStopID,
+};
 use synthpop::{TripEndpoint, TripMode};
 
-use crate::{CarID, DrivingGoal, SidewalkSpot, TripLeg, VehicleType, SPAWN_DIST};
+use crate::{
+    BikeShareState, CarID, DrivingGoal, ParkingSim, ParkingSimState, ParkingSpot, SidewalkSpot,
+    TripLeg, VehicleType, SPAWN_DIST,
+};
 
 /// We need to remember a few things from scenario instantiation that're used for starting the
 /// trip.
@@ -15,6 +21,8 @@ use crate::{CarID, DrivingGoal, SidewalkSpot, TripLeg, VehicleType, SPAWN_DIST};
 pub(crate) struct StartTripArgs {
     pub retry_if_no_room: bool,
     pub use_vehicle: Option<CarID>,
+    /// For biking trips, try to use a shared bike instead of the person's own
+    pub bike_share: bool,
 }
 
 // TODO Some of these fields are unused now that we separately pass TripEndpoint
@@ -48,6 +56,15 @@ pub(crate) enum TripSpec {
         start: BuildingID,
         goal: DrivingGoal,
     },
+    /// Walk to a bike share station, ride to another one, and walk the rest of the way.
+    UsingSharedBike {
+        /// The person's own bike stands in for the shared one during the ride.
+        bike: CarID,
+        start_bldg: BuildingID,
+        pickup: BuildingID,
+        dropoff: BuildingID,
+        goal_bldg: BuildingID,
+    },
     UsingTransit {
         start: SidewalkSpot,
         goal: SidewalkSpot,
@@ -55,6 +72,27 @@ pub(crate) enum TripSpec {
         stop1: TransitStopID,
         maybe_stop2: Option<TransitStopID>,
     },
+    /// Park-and-ride, leaving home: drive to a lot, then continue by transit.
+    DrivingToTransit {
+        /// This must be a currently parked vehicle owned by the person.
+        car: CarID,
+        start_bldg: BuildingID,
+        lot: ParkingLotID,
+        goal: SidewalkSpot,
+        route: TransitRouteID,
+        stop1: TransitStopID,
+        maybe_stop2: Option<TransitStopID>,
+    },
+    /// Park-and-ride, heading back: take transit to the lot where the car was left, then drive.
+    TransitToDriving {
+        /// This must be a vehicle owned by the person, parked in a lot.
+        car: CarID,
+        start: SidewalkSpot,
+        route: TransitRouteID,
+        stop1: TransitStopID,
+        stop2: TransitStopID,
+        goal: DrivingGoal,
+    },
 }
 
 impl TripSpec {
@@ -114,7 +152,7 @@ impl TripSpec {
                     DrivingGoal::ParkNear(b) => {
                         legs.push(TripLeg::Walk(SidewalkSpot::building(*b, map)));
                     }
-                    DrivingGoal::Border(_, _) => {}
+                    DrivingGoal::ParkInLot(_) | DrivingGoal::Border(_, _) => {}
                 }
             }
             TripSpec::JustWalking { start, goal, .. } => {
@@ -139,6 +177,7 @@ impl TripSpec {
                             goal,
                         })
                     }
+                    DrivingGoal::ParkInLot(_) => unreachable!(),
                 };
 
                 if let Some(start_spot) = SidewalkSpot::bike_rack(*start, map) {
@@ -167,7 +206,7 @@ impl TripSpec {
                         DrivingGoal::ParkNear(b) => {
                             legs.push(TripLeg::Walk(SidewalkSpot::building(*b, map)));
                         }
-                        DrivingGoal::Border(_, _) => {}
+                        DrivingGoal::ParkInLot(_) | DrivingGoal::Border(_, _) => {}
                     }
                 } else if let Some(plan) = backup_plan {
                     info!("Can't start biking from {}. Walking instead", start);
@@ -183,6 +222,20 @@ impl TripSpec {
                     .into_plan(map);
                 }
             }
+            TripSpec::UsingSharedBike {
+                bike,
+                pickup,
+                dropoff,
+                goal_bldg,
+                ..
+            } => {
+                // BikeShareState::start_ride already checked both stations can be reached by bike
+                legs.push(TripLeg::Walk(
+                    SidewalkSpot::bike_rack(*pickup, map).unwrap(),
+                ));
+                legs.push(TripLeg::Drive(*bike, DrivingGoal::ParkNear(*dropoff)));
+                legs.push(TripLeg::Walk(SidewalkSpot::building(*goal_bldg, map)));
+            }
             TripSpec::UsingTransit {
                 route,
                 stop1,
@@ -201,6 +254,39 @@ impl TripSpec {
                     legs = vec![TripLeg::Walk(walk_to), TripLeg::RideBus(*route, None)];
                 }
             }
+            TripSpec::DrivingToTransit {
+                car,
+                lot,
+                goal,
+                route,
+                stop1,
+                maybe_stop2,
+                ..
+            } => {
+                legs.push(TripLeg::Walk(SidewalkSpot::deferred_parking_spot()));
+                legs.push(TripLeg::Drive(*car, DrivingGoal::ParkInLot(*lot)));
+                legs.push(TripLeg::Walk(SidewalkSpot::bus_stop(*stop1, map)));
+                legs.push(TripLeg::RideBus(*route, *maybe_stop2));
+                if maybe_stop2.is_some() {
+                    legs.push(TripLeg::Walk(goal.clone()));
+                }
+            }
+            TripSpec::TransitToDriving {
+                car,
+                route,
+                stop1,
+                stop2,
+                goal,
+                ..
+            } => {
+                legs.push(TripLeg::Walk(SidewalkSpot::bus_stop(*stop1, map)));
+                legs.push(TripLeg::RideBus(*route, Some(*stop2)));
+                legs.push(TripLeg::Walk(SidewalkSpot::deferred_parking_spot()));
+                legs.push(TripLeg::Drive(*car, goal.clone()));
+                if let DrivingGoal::ParkNear(b) = goal {
+                    legs.push(TripLeg::Walk(SidewalkSpot::building(*b, map)));
+                }
+            }
         };
 
         (self, legs)
@@ -290,6 +376,115 @@ impl TripSpec {
             }
         })
     }
+
+    /// Plan one half of a park-and-ride trip, depending on where the person's car is. If it was
+    /// left in a lot, ride transit back to it and drive the rest of the way. Otherwise, drive to
+    /// the nearest park-and-ride lot with space and transit to the destination. When no lot works
+    /// out, just drive the whole way.
+    pub fn park_and_ride(
+        from: TripEndpoint,
+        to: TripEndpoint,
+        car: CarID,
+        map: &Map,
+        parking: &ParkingSimState,
+    ) -> Result<TripSpec> {
+        let (start_bldg, parked_car) = match (from, parking.lookup_parked_car(car)) {
+            (TripEndpoint::Building(b), Some(p)) => (b, p),
+            // The car is stuck somewhere else, so don't bother with it
+            _ => return TripSpec::maybe_new(from, to, TripMode::Transit, None, true, map),
+        };
+        let start = start_sidewalk_spot(from, map)?;
+
+        if let ParkingSpot::Lot(pl, _) = parked_car.spot {
+            let goal = driving_goal(to, PathConstraints::Car, map)?;
+            if let Some((stop1, Some(stop2), route)) =
+                map.should_use_transit(start.sidewalk_pos, map.get_pl(pl).sidewalk_pos)
+            {
+                return Ok(TripSpec::TransitToDriving {
+                    car,
+                    start,
+                    route,
+                    stop1,
+                    stop2,
+                    goal,
+                });
+            }
+            return Ok(TripSpec::UsingParkedCar {
+                car,
+                start_bldg,
+                goal,
+            });
+        }
+
+        let goal = end_sidewalk_spot(to, map)?;
+        let start_pt = start.sidewalk_pos.pt(map);
+        let max_dist = start_pt.dist_to(goal.sidewalk_pos.pt(map));
+        let mut lots: Vec<_> = map
+            .park_and_ride_lots()
+            .into_iter()
+            .map(|pl| (pl, start_pt.dist_to(map.get_pl(pl).sidewalk_pos.pt(map))))
+            // Don't drive away from the destination
+            .filter(|(_, dist)| *dist < max_dist)
+            .collect();
+        lots.sort_by_key(|(_, dist)| *dist);
+        // Pathfinding for transit is expensive, so only consider a few of the closest lots
+        for (lot, _) in lots
+            .into_iter()
+            .filter(|(pl, _)| !parking.get_free_lot_spots(*pl).is_empty())
+            .take(3)
+        {
+            if let Some((stop1, maybe_stop2, route)) =
+                map.should_use_transit(map.get_pl(lot).sidewalk_pos, goal.sidewalk_pos)
+            {
+                return Ok(TripSpec::DrivingToTransit {
+                    car,
+                    start_bldg,
+                    lot,
+                    goal,
+                    route,
+                    stop1,
+                    maybe_stop2,
+                });
+            }
+        }
+        Ok(TripSpec::UsingParkedCar {
+            car,
+            start_bldg,
+            goal: driving_goal(to, PathConstraints::Car, map)?,
+        })
+    }
+
+    /// Plan a biking trip on a shared bike. If no bike or free dock is close enough, walk the
+    /// whole way instead. Trips starting or ending off-map just use the person's own bike.
+    pub fn bike_share(
+        now: Time,
+        from: TripEndpoint,
+        to: TripEndpoint,
+        bike: CarID,
+        map: &Map,
+        bike_share: &mut BikeShareState,
+    ) -> Result<TripSpec> {
+        let (start_bldg, goal_bldg) = match (from, to) {
+            (TripEndpoint::Building(b1), TripEndpoint::Building(b2)) => (b1, b2),
+            _ => return TripSpec::maybe_new(from, to, TripMode::Bike, Some(bike), true, map),
+        };
+        if let Some((pickup, dropoff)) =
+            bike_share.start_ride(now, bike, start_bldg, goal_bldg, map)
+        {
+            Ok(TripSpec::UsingSharedBike {
+                bike,
+                start_bldg,
+                pickup,
+                dropoff,
+                goal_bldg,
+            })
+        } else {
+            Ok(TripSpec::JustWalking {
+                start: SidewalkSpot::building(start_bldg, map),
+                goal: SidewalkSpot::building(goal_bldg, map),
+            })
+        }
+    }
 }
 
 fn start_sidewalk_spot(endpt: TripEndpoint, map: &Map) -> Result<SidewalkSpot> {
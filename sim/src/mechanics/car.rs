@@ -29,6 +29,11 @@ pub(crate) struct Car {
     /// Since lane over-taking isn't implemented yet, a vehicle tends to be stuck behind a slow
     /// leader for a while. Avoid duplicate events.
     pub wants_to_overtake: BTreeSet<CarID>,
+
+    /// Scales the speed the vehicle would otherwise travel at, depending on the weather
+    pub speed_factor: f64,
+    /// If false, the vehicle instantly reaches its speed after a stop
+    pub model_acceleration: bool,
 }
 
 impl Car {
@@ -45,6 +50,47 @@ impl Car {
         self.crossing_state_with_end_dist(dist_int, start_time, map)
     }
 
+    /// Like `crossing_state`, but the vehicle is starting from a stop, so it has to accelerate
+    /// first. If it braked for the stop, that lost time is charged here too, since until a
+    /// vehicle starts again, there's no way to know it'll have to stop.
+    pub fn crossing_state_from_rest(
+        &self,
+        start_dist: Distance,
+        start_time: Time,
+        braked: bool,
+        map: &Map,
+    ) -> CarState {
+        let mut state = self.crossing_state(start_dist, start_time, map);
+        if !self.model_acceleration {
+            return state;
+        }
+        if let CarState::Crossing {
+            ref mut time_int,
+            ref dist_int,
+            ..
+        } = state
+        {
+            let dist = (dist_int.end - dist_int.start).inner_meters();
+            let cruising = (time_int.end - time_int.start).inner_seconds();
+            if dist <= 0.0 || cruising <= 0.0 {
+                return state;
+            }
+            let speed = dist / cruising;
+            let accel = self.vehicle.acceleration();
+            // Short distances are covered before reaching full speed
+            let mut dt = if dist >= speed * speed / (2.0 * accel) {
+                cruising + speed / (2.0 * accel)
+            } else {
+                (2.0 * dist / accel).sqrt()
+            };
+            if braked {
+                dt += speed / (2.0 * self.vehicle.deceleration());
+            }
+            *time_int = TimeInterval::new(start_time, start_time + Duration::seconds(dt));
+        }
+        state
+    }
+
     pub fn crossing_state_with_end_dist(
         &self,
         dist_int: DistanceInterval,
@@ -60,7 +106,7 @@ impl Car {
                 self.vehicle.vehicle_type.to_constraints(),
                 map,
             );
-        let dt = (dist_int.end - dist_int.start) / speed;
+        let dt = (dist_int.end - dist_int.start) / (speed * self.speed_factor);
         CarState::Crossing {
             time_int: TimeInterval::new(start_time, start_time + dt),
             dist_int,
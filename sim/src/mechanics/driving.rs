@@ -4,7 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use abstutil::{deserialize_hashmap, serialize_hashmap, FixedMap, IndexableKey};
 use geom::{Distance, Duration, PolyLine, Time};
-use map_model::{DrivingSide, IntersectionID, LaneID, Map, Path, PathStep, Position, Traversable};
+use map_model::{
+    CurbUse, DrivingSide, IntersectionID, LaneID, Map, Path, PathStep, Position, Road, Traversable,
+};
 
 use crate::mechanics::car::{Car, CarState};
 use crate::mechanics::queue::{Queue, QueueEntry, Queued};
@@ -13,11 +15,18 @@ use crate::{
     ActionAtEnd, AgentID, AgentProperties, CarID, CarStatus, Command, CreateCar, DelayCause,
     DistanceInterval, DrawCarInput, Event, IntersectionSimState, ParkedCar, ParkingSim,
     ParkingSpot, PersonID, Problem, SimOptions, TimeInterval, TransitSimState, TripID, TripManager,
-    UnzoomedAgent, Vehicle, VehicleType, WalkingSimState, FOLLOWING_DISTANCE, MAX_CAR_LENGTH,
+    UnzoomedAgent, Vehicle, VehicleType, WalkingSimState, MAX_TRUCK_LENGTH,
 };
 
 const TIME_TO_WAIT_AT_BUS_STOP: Duration = Duration::const_seconds(10.0);
+/// When the curb at a stop is kept clear for buses, they don't have to maneuver around parked cars
+const TIME_TO_WAIT_AT_CURB_BUS_STOP: Duration = Duration::const_seconds(6.0);
+/// Light rail stations see more boarding per stop, through more doors
+const TIME_TO_WAIT_AT_STATION: Duration = Duration::const_seconds(30.0);
 const TIME_TO_CHANGE_LANES: Duration = Duration::const_seconds(1.0);
+/// Only change lanes mid-block if the adjacent lane has at least this many fewer vehicles than are
+/// ahead in the current lane. This avoids oscillating between lanes.
+const MIN_QUEUE_ADVANTAGE_TO_CHANGE_LANES: usize = 2;
 
 // TODO Do something else.
 pub const BLIND_RETRY_TO_CREEP_FORWARDS: Duration = Duration::const_seconds(0.1);
@@ -44,12 +53,18 @@ pub(crate) struct DrivingSimState {
     waiting_to_spawn: BTreeMap<CarID, (Position, Option<PersonID>)>,
 
     recalc_lanechanging: bool,
+    mid_block_lanechanging: bool,
     handle_uber_turns: bool,
 
     time_to_unpark_onstreet: Duration,
     time_to_park_onstreet: Duration,
     time_to_unpark_offstreet: Duration,
     time_to_park_offstreet: Duration,
+
+    /// Depends on the weather
+    speed_factor: f64,
+    following_distance: Distance,
+    model_acceleration: bool,
 }
 
 // Mutations
@@ -60,6 +75,7 @@ impl DrivingSimState {
             queues: HashMap::new(),
             events: Vec::new(),
             recalc_lanechanging: !opts.dont_recalc_lanechanging,
+            mid_block_lanechanging: opts.mid_block_lanechanging,
             handle_uber_turns: !opts.dont_handle_uber_turns,
             waiting_to_spawn: BTreeMap::new(),
 
@@ -67,6 +83,10 @@ impl DrivingSimState {
             time_to_park_onstreet: Duration::seconds(15.0),
             time_to_unpark_offstreet: Duration::seconds(5.0),
             time_to_park_offstreet: Duration::seconds(5.0),
+
+            speed_factor: opts.weather.speed_factor(),
+            following_distance: opts.weather.following_distance(),
+            model_acceleration: opts.model_acceleration,
         };
         if opts.infinite_parking {
             sim.time_to_unpark_offstreet = Duration::seconds(0.1);
@@ -75,13 +95,13 @@ impl DrivingSimState {
 
         for l in map.all_lanes() {
             if l.lane_type.is_for_moving_vehicles() {
-                let q = Queue::new(Traversable::Lane(l.id), map);
+                let q = Queue::new(Traversable::Lane(l.id), map, sim.following_distance);
                 sim.queues.insert(q.id, q);
             }
         }
         for t in map.all_turns() {
             if !t.between_sidewalks() {
-                let q = Queue::new(Traversable::Turn(t.id), map);
+                let q = Queue::new(Traversable::Turn(t.id), map, sim.following_distance);
                 sim.queues.insert(q.id, q);
             }
         }
@@ -130,7 +150,7 @@ impl DrivingSimState {
                 .get_req()
                 .start
                 .equiv_pos(lane, ctx.map)
-                .buffer_dist(MAX_CAR_LENGTH + FOLLOWING_DISTANCE, ctx.map)
+                .buffer_dist(MAX_TRUCK_LENGTH + self.following_distance, ctx.map)
             {
                 Some(pos) => pos,
                 None => {
@@ -191,6 +211,8 @@ impl DrivingSimState {
                 total_blocked_time: Duration::ZERO,
                 trip_and_person: params.trip_and_person,
                 wants_to_overtake: BTreeSet::new(),
+                speed_factor: self.speed_factor,
+                model_acceleration: self.model_acceleration,
             };
             if let Some(p) = params.maybe_parked_car {
                 let delay = match p.spot {
@@ -235,6 +257,7 @@ impl DrivingSimState {
                         start_dist,
                         &car.vehicle,
                         ctx.parking,
+                        now,
                         ctx.map,
                         car.trip_and_person,
                         &mut self.events,
@@ -418,6 +441,15 @@ impl DrivingSimState {
                         };
                         return true;
                     }
+                } else if self.mid_block_lanechanging {
+                    if let Some(target_lane) = self.pick_anticipatory_lane(car, ctx.map) {
+                        // Same as overtaking; try_start_lc checks for a gap in the target lane.
+                        car.state = CarState::Queued {
+                            blocked_since: now,
+                            want_to_change_lanes: Some(target_lane),
+                        };
+                        return true;
+                    }
                 }
             }
             CarState::Unparking {
@@ -453,17 +485,27 @@ impl DrivingSimState {
                         front,
                         &car.vehicle,
                         ctx.parking,
+                        now,
                         ctx.map,
                         car.trip_and_person,
                         &mut self.events,
                     );
                 }
-                car.state = car.crossing_state(front, now, ctx.map);
+                car.state = car.crossing_state_from_rest(front, now, false, ctx.map);
                 ctx.scheduler
                     .push(car.state.get_end_time(), Command::UpdateCar(car.vehicle.id));
             }
             CarState::WaitingToAdvance { blocked_since } => {
                 // 'car' is the leader.
+                if let Some(retry) =
+                    car.router
+                        .avoid_restricted_zones(&car.vehicle, now, ctx.map, &mut self.events)
+                {
+                    // Wait at the edge of the restricted zone until it opens
+                    ctx.scheduler
+                        .update(retry, Command::UpdateCar(car.vehicle.id));
+                    return false;
+                }
                 let from = car.router.head();
                 let goto = car.router.next();
                 assert!(from != goto);
@@ -506,12 +548,18 @@ impl DrivingSimState {
                 let last_step = car.router.advance(
                     &car.vehicle,
                     ctx.parking,
+                    now,
                     ctx.map,
                     car.trip_and_person,
                     &mut self.events,
                 );
                 car.total_blocked_time += now - blocked_since;
-                car.state = car.crossing_state(Distance::ZERO, now, ctx.map);
+                // If the turn could start right away, the vehicle never stopped
+                car.state = if blocked_since < now {
+                    car.crossing_state_from_rest(Distance::ZERO, now, true, ctx.map)
+                } else {
+                    car.crossing_state(Distance::ZERO, now, ctx.map)
+                };
                 ctx.scheduler
                     .push(car.state.get_end_time(), Command::UpdateCar(car.vehicle.id));
                 self.events.push(Event::AgentEntersTraversable(
@@ -535,7 +583,7 @@ impl DrivingSimState {
                     car.crossing_state_with_end_dist(
                         DistanceInterval::new_driving(
                             Distance::ZERO,
-                            car.vehicle.length + FOLLOWING_DISTANCE,
+                            car.vehicle.length + self.following_distance,
                         ),
                         now,
                         ctx.map,
@@ -621,6 +669,7 @@ impl DrivingSimState {
                     our_dist,
                     &car.vehicle,
                     ctx.parking,
+                    now,
                     ctx.map,
                     car.trip_and_person,
                     &mut self.events,
@@ -693,9 +742,18 @@ impl DrivingSimState {
                     Some(ActionAtEnd::BusAtStop) => {
                         car.total_blocked_time += now - blocked_since;
                         if transit.bus_arrived_at_stop(now, car.vehicle.id, trips, walking, ctx) {
+                            let dwell_time = if car.vehicle.vehicle_type == VehicleType::Train {
+                                TIME_TO_WAIT_AT_STATION
+                            } else if matches!(car.router.head(), Traversable::Lane(l)
+                                if ctx.map.get_parent(l).curb_use(l) == CurbUse::BusStop)
+                            {
+                                TIME_TO_WAIT_AT_CURB_BUS_STOP
+                            } else {
+                                TIME_TO_WAIT_AT_BUS_STOP
+                            };
                             car.state = CarState::IdlingAtStop(
                                 our_dist,
-                                TimeInterval::new(now, now + TIME_TO_WAIT_AT_BUS_STOP),
+                                TimeInterval::new(now, now + dwell_time),
                             );
                             ctx.scheduler
                                 .push(car.state.get_end_time(), Command::UpdateCar(car.vehicle.id));
@@ -751,10 +809,10 @@ impl DrivingSimState {
                 false
             }
             CarState::IdlingAtStop(dist, _) => {
-                car.router = transit.bus_departed_from_stop(car.vehicle.id, ctx.map);
+                car.router = transit.bus_departed_from_stop(now, car.vehicle.id, ctx.map);
                 self.events
                     .push(Event::PathAmended(car.router.get_path().clone()));
-                car.state = car.crossing_state(dist, now, ctx.map);
+                car.state = car.crossing_state_from_rest(dist, now, true, ctx.map);
                 ctx.scheduler
                     .push(car.state.get_end_time(), Command::UpdateCar(car.vehicle.id));
 
@@ -786,7 +844,7 @@ impl DrivingSimState {
             let queue = self.queues.get_mut(&car.router.head()).unwrap();
             // delete_car_internal will call free_reserved_space, so this is necessary to balance
             // that.
-            queue.reserved_length += car.vehicle.length + FOLLOWING_DISTANCE;
+            queue.reserved_length += car.vehicle.length + self.following_distance;
             ctx.intersections.agent_deleted_mid_turn(AgentID::Car(c), t);
 
             // Free any reserved space on the next step.
@@ -877,7 +935,11 @@ impl DrivingSimState {
 
                     // Prevent them from jumping forwards.
                     follower.total_blocked_time += now - blocked_since;
-                    follower.state = follower.crossing_state(follower_dist, now, ctx.map);
+                    follower.state = if blocked_since < now {
+                        follower.crossing_state_from_rest(follower_dist, now, true, ctx.map)
+                    } else {
+                        follower.crossing_state(follower_dist, now, ctx.map)
+                    };
                     ctx.scheduler.update(
                         follower.state.get_end_time(),
                         Command::UpdateCar(follower_id),
@@ -948,7 +1010,7 @@ impl DrivingSimState {
         };
 
         // Trim off as many of the oldest last_steps as we've made distance.
-        let mut dist_left_to_cleanup = self.cars[&id].vehicle.length + FOLLOWING_DISTANCE;
+        let mut dist_left_to_cleanup = self.cars[&id].vehicle.length + self.following_distance;
         dist_left_to_cleanup -= dist_along_last;
         let mut num_to_trim = None;
         for (idx, step) in self.cars[&id].last_steps.iter().enumerate() {
@@ -974,7 +1036,7 @@ impl DrivingSimState {
                     // fine for correctness.
                     DistanceInterval::new_driving(
                         dist_along_last,
-                        self.cars[&id].vehicle.length + FOLLOWING_DISTANCE,
+                        self.cars[&id].vehicle.length + self.following_distance,
                     ),
                     now,
                     ctx.map,
@@ -1128,6 +1190,44 @@ impl DrivingSimState {
         None
     }
 
+    /// When a car is queued behind others, is there an adjacent lane that also leads to the next
+    /// step of the path, but has a shorter queue? Changing before reaching the intersection
+    /// positions the car for its upcoming turn.
+    fn pick_anticipatory_lane(&self, car: &Car, map: &Map) -> Option<LaneID> {
+        let current_lane = map.get_l(car.router.head().maybe_lane()?);
+        let num_ahead = self.queues[&car.router.head()]
+            .get_active_cars()
+            .into_iter()
+            .position(|c| c == car.vehicle.id)?;
+        let road = map.get_parent(current_lane.id);
+        let idx = current_lane.id.offset;
+        let constraints = car.vehicle.vehicle_type.to_constraints();
+
+        let mut candidates = Vec::new();
+        if idx != 0 {
+            candidates.push(&road.lanes[idx - 1]);
+        }
+        if idx != road.lanes.len() - 1 {
+            candidates.push(&road.lanes[idx + 1]);
+        }
+        candidates
+            .into_iter()
+            .filter(|l| {
+                l.dir == current_lane.dir
+                    && constraints.can_use(l, map)
+                    && car.router.can_lanechange(current_lane.id, l.id, map)
+            })
+            .map(|l| {
+                let (num_vehicles, _) = self.queues[&Traversable::Lane(l.id)].target_lane_penalty();
+                (num_vehicles, l.id)
+            })
+            .filter(|(num_vehicles, _)| {
+                num_vehicles + MIN_QUEUE_ADVANTAGE_TO_CHANGE_LANES <= num_ahead
+            })
+            .min_by_key(|(num_vehicles, _)| *num_vehicles)
+            .map(|(_, l)| l)
+    }
+
     fn try_start_lc(
         &mut self,
         car: &mut Car,
@@ -1241,7 +1341,8 @@ impl DrivingSimState {
         // Delete any old queues.
         self.queues.retain(|k, v| {
             if new_queues.remove(k) {
-                // No changes
+                // No changes, besides maybe becoming a turn pocket
+                v.update_capacity(map);
                 true
             } else {
                 // Make sure it's empty!
@@ -1259,7 +1360,8 @@ impl DrivingSimState {
 
         // Create any new queues
         for key in new_queues {
-            self.queues.insert(key, Queue::new(key, map));
+            self.queues
+                .insert(key, Queue::new(key, map, self.following_distance));
         }
     }
 }
@@ -1561,11 +1663,51 @@ impl DrivingSimState {
         }
     }
 
+    /// How many vehicles are on some lane of this road right now?
+    pub fn num_vehicles_on_road(&self, road: &Road) -> usize {
+        road.lanes
+            .iter()
+            .filter_map(|l| self.queues.get(&Traversable::Lane(l.id)))
+            .map(|q| q.get_active_cars().len() + q.laggy_head.iter().count())
+            .sum()
+    }
+
     pub fn debug_queue_lengths(&self, l: LaneID) -> Option<(Distance, Distance)> {
         let queue = self.queues.get(&Traversable::Lane(l))?;
         Some((queue.reserved_length, queue.geom_len))
     }
 
+    /// For every lane with vehicles stopped at its end, how far back does the stopped queue
+    /// reach? This is measured from the end of the lane to the back of the last stopped vehicle.
+    /// Vehicles still moving to join the queue aren't counted.
+    pub fn get_queue_extents(&self, now: Time) -> BTreeMap<LaneID, Distance> {
+        let mut extents = BTreeMap::new();
+        for queue in self.queues.values() {
+            let l = match queue.id {
+                Traversable::Lane(l) => l,
+                Traversable::Turn(_) => continue,
+            };
+            let mut extent = Distance::ZERO;
+            for entry in queue.get_car_positions(now, &self.cars, &self.queues) {
+                let stopped = match entry.member {
+                    Queued::Vehicle(id) => matches!(
+                        self.cars[&id].state,
+                        CarState::Queued { .. } | CarState::WaitingToAdvance { .. }
+                    ),
+                    Queued::StaticBlockage { .. } | Queued::DynamicBlockage { .. } => false,
+                };
+                if !stopped {
+                    break;
+                }
+                extent = queue.geom_len - entry.back;
+            }
+            if extent > Distance::ZERO {
+                extents.insert(l, extent);
+            }
+        }
+        extents
+    }
+
     pub fn get_blocked_by_graph(
         &self,
         now: Time,
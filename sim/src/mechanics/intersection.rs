@@ -5,18 +5,25 @@ use serde::{Deserialize, Serialize};
 use abstutil::{deserialize_btreemap, prettyprint_usize, serialize_btreemap, FixedMap};
 use geom::{Duration, Time};
 use map_model::{
-    ControlStopSign, ControlTrafficSignal, Intersection, IntersectionID, LaneID, Map, StageType,
-    Traversable, TurnID, TurnPriority, TurnType, UberTurn,
+    osm, ControlStopSign, ControlTrafficSignal, DrivingSide, Intersection, IntersectionID, LaneID,
+    Map, RoadID, Stage, StageType, Traversable, TurnID, TurnPriority, TurnType, UberTurn,
 };
 
 use crate::mechanics::car::{Car, CarState};
 use crate::mechanics::Queue;
 use crate::{
     AgentID, AlertLocation, CarID, Command, DelayCause, Event, Scheduler, SimOptions, Speed,
+    VehicleType,
 };
 
 const WAIT_AT_STOP_SIGN: Duration = Duration::const_seconds(0.5);
 const WAIT_BEFORE_YIELD_AT_TRAFFIC_SIGNAL: Duration = Duration::const_seconds(0.2);
+/// At signals protected for biking or with a bike box, motor vehicles wait this long after their
+/// stage starts.
+const LEADING_BIKE_INTERVAL: Duration = Duration::const_seconds(5.0);
+/// When a train preempts a traffic signal, the current stage still stays green at least this long
+/// before its yellow and all-red intervals.
+const MIN_GREEN_BEFORE_PREEMPTION: Duration = Duration::const_seconds(7.0);
 
 /// Manages conflicts at intersections. When an agent has reached the end of a lane, they call
 /// maybe_start_turn to make a Request. Based on the intersection type (stop sign, traffic signal,
@@ -34,6 +41,8 @@ pub(crate) struct IntersectionSimState {
     break_turn_conflict_cycles: bool,
     handle_uber_turns: bool,
     disable_turn_conflicts: bool,
+    model_merges: bool,
+    prioritize_rail: bool,
     // (x, y) means x is blocked by y. It's a many-to-many relationship. TODO Better data
     // structure.
     blocked_by: BTreeSet<(CarID, CarID)>,
@@ -66,6 +75,9 @@ struct State {
     // In some cases, a turn completing at one intersection may affect agents waiting to start an
     // uber-turn at nearby intersections.
     uber_turn_neighbors: Vec<IntersectionID>,
+    // Where several roads merge into one highway lane, which road did the last vehicle to enter
+    // the lane come from?
+    last_merge_entry: BTreeMap<LaneID, RoadID>,
 
     signal: Option<SignalState>,
 }
@@ -78,6 +90,14 @@ struct SignalState {
     stage_ends_at: Time,
     // The number of times a variable signal has been extended during the current stage.
     extensions_count: usize,
+    // If true, the signal holds its current stage until something outside the simulation changes
+    // it.
+    #[serde(default)]
+    externally_controlled: bool,
+    // A train is waiting for this stage, so switch to it once the current stage ends, instead of
+    // continuing the normal cycle.
+    #[serde(default)]
+    preempt_to: Option<usize>,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Clone, Debug)]
@@ -96,6 +116,8 @@ impl IntersectionSimState {
             break_turn_conflict_cycles: !opts.dont_break_turn_conflict_cycles,
             handle_uber_turns: !opts.dont_handle_uber_turns,
             disable_turn_conflicts: opts.disable_turn_conflicts,
+            model_merges: opts.model_merges,
+            prioritize_rail: !opts.dont_prioritize_rail,
             blocked_by: BTreeSet::new(),
             events: Vec::new(),
 
@@ -114,6 +136,7 @@ impl IntersectionSimState {
                 waiting: BTreeMap::new(),
                 reserved: BTreeSet::new(),
                 uber_turn_neighbors: Vec::new(),
+                last_merge_entry: BTreeMap::new(),
                 signal: None,
             };
             if i.is_traffic_signal() {
@@ -317,6 +340,19 @@ impl IntersectionSimState {
         // Switch to a new stage?
         assert_eq!(now, signal_state.stage_ends_at);
         let old_stage = &signal.stages[signal_state.current_stage];
+        if signal_state.externally_controlled {
+            signal_state.stage_ends_at = now + old_stage.stage_type.simple_duration();
+            scheduler.push(signal_state.stage_ends_at, Command::UpdateIntersection(id));
+            return;
+        }
+        if let Some(stage) = signal_state.preempt_to.take() {
+            signal_state.current_stage = stage;
+            signal_state.extensions_count = 0;
+            signal_state.stage_ends_at = now + signal.stages[stage].stage_type.simple_duration();
+            scheduler.push(signal_state.stage_ends_at, Command::UpdateIntersection(id));
+            self.wakeup_waiting(now, id, scheduler, map);
+            return;
+        }
         match old_stage.stage_type {
             StageType::Fixed(_) => {
                 duration = advance(signal_state, signal, i, !ped_waiting);
@@ -445,6 +481,8 @@ impl IntersectionSimState {
             }
 
             true
+        } else if self.model_merges && !self.zip_merge_allows(&req, map) {
+            false
         } else if self.use_freeform_policy_everywhere {
             // If we made it this far, we don't conflict with an accepted turn
             true
@@ -560,6 +598,9 @@ impl IntersectionSimState {
         // for stop signs too.
         let state = self.state.get_mut(&turn.parent).unwrap();
         state.waiting.remove(&req).unwrap();
+        if self.model_merges && matches!(agent, AgentID::Car(_)) && is_merge(turn, map) {
+            state.last_merge_entry.insert(turn.dst, turn.src.road);
+        }
         state.accepted.insert(req);
         if self.break_turn_conflict_cycles {
             if let AgentID::Car(car) = agent {
@@ -573,6 +614,72 @@ impl IntersectionSimState {
         std::mem::take(&mut self.events)
     }
 
+    /// Immediately switch a traffic signal to a different stage. The stage lasts for its usual
+    /// duration, after which the signal either resumes its normal cycle or, if externally
+    /// controlled, holds the stage.
+    pub fn set_signal_stage(
+        &mut self,
+        now: Time,
+        id: IntersectionID,
+        stage: usize,
+        scheduler: &mut Scheduler,
+        map: &Map,
+    ) {
+        let signal_state = self.state.get_mut(&id).unwrap().signal.as_mut().unwrap();
+        signal_state.current_stage = stage;
+        signal_state.extensions_count = 0;
+        signal_state.preempt_to = None;
+        signal_state.stage_ends_at = now
+            + map.get_traffic_signal(id).stages[stage]
+                .stage_type
+                .simple_duration();
+        scheduler.update(signal_state.stage_ends_at, Command::UpdateIntersection(id));
+        self.wakeup_waiting(now, id, scheduler, map);
+    }
+
+    /// Cut the current stage of a traffic signal short to switch to another one, like when a
+    /// train approaches. The current stage still gets a minimum green, then its usual yellow and
+    /// all-red intervals.
+    fn preempt_signal(
+        &mut self,
+        now: Time,
+        id: IntersectionID,
+        stage: usize,
+        scheduler: &mut Scheduler,
+        map: &Map,
+    ) {
+        let signal = map.get_traffic_signal(id);
+        let signal_state = self.state.get_mut(&id).unwrap().signal.as_mut().unwrap();
+        if signal_state.preempt_to.is_some() {
+            return;
+        }
+        signal_state.preempt_to = Some(stage);
+
+        let current = &signal.stages[signal_state.current_stage];
+        let time_into_stage =
+            current.stage_type.simple_duration() - (signal_state.stage_ends_at - now);
+        let green_until = now
+            + std::cmp::max(
+                Duration::ZERO,
+                MIN_GREEN_BEFORE_PREEMPTION - time_into_stage,
+            );
+        let ends_at = green_until + current.yellow + current.all_red;
+        if ends_at < signal_state.stage_ends_at {
+            signal_state.stage_ends_at = ends_at;
+            scheduler.update(ends_at, Command::UpdateIntersection(id));
+        }
+    }
+
+    pub fn set_signal_external_control(&mut self, id: IntersectionID, enabled: bool) {
+        self.state
+            .get_mut(&id)
+            .unwrap()
+            .signal
+            .as_mut()
+            .unwrap()
+            .externally_controlled = enabled;
+    }
+
     pub fn handle_live_edited_traffic_signals(
         &mut self,
         now: Time,
@@ -815,7 +922,26 @@ impl IntersectionSimState {
         assert!(our_priority != TurnPriority::Banned);
         let (our_time, _) = self.state[&req.turn.parent].waiting[req];
 
-        if our_priority == TurnPriority::Yield && now < our_time + WAIT_AT_STOP_SIGN {
+        // Vehicles merging onto a highway use the ramp to speed up and don't stop first. Trains
+        // don't stop at street crossings; everybody else waits for them.
+        let merging = self.model_merges && is_merge(req.turn, map);
+        let rail = self.prioritize_rail && is_train(req.agent);
+        if self.prioritize_rail && !rail {
+            let turn = map.get_t(req.turn);
+            if self.state[&req.turn.parent]
+                .waiting
+                .keys()
+                .any(|other| is_train(other.agent) && map.get_t(other.turn).conflicts_with(turn))
+            {
+                // We'll be woken up after the train finishes its turn
+                return false;
+            }
+        }
+        if our_priority == TurnPriority::Yield
+            && !merging
+            && !rail
+            && now < our_time + WAIT_AT_STOP_SIGN
+        {
             // Since we have "ownership" of scheduling for req.agent, don't need to use
             // scheduler.update.
             scheduler.push(
@@ -844,6 +970,55 @@ impl IntersectionSimState {
         true
     }
 
+    /// When vehicles from different roads are waiting to merge into the same highway lane, they
+    /// take turns, like a zipper.
+    fn zip_merge_allows(&self, req: &Request, map: &Map) -> bool {
+        if !matches!(req.agent, AgentID::Car(_)) || !is_merge(req.turn, map) {
+            return true;
+        }
+        let state = &self.state[&req.turn.parent];
+        if state.last_merge_entry.get(&req.turn.dst) != Some(&req.turn.src.road) {
+            return true;
+        }
+        // The last vehicle in came from our road, so let somebody from a different road go first
+        !state
+            .waiting
+            .keys()
+            .any(|other| self.competes_for_merge(other, req, map))
+    }
+
+    /// Is `other` waiting to merge into the same lane as `req` from a different road, and could it
+    /// go right now if `req` held back? Vehicles stuck behind a red light or a conflicting turn
+    /// aren't taking their turn, so they shouldn't hold up the zipper.
+    fn competes_for_merge(&self, other: &Request, req: &Request, map: &Map) -> bool {
+        if !matches!(other.agent, AgentID::Car(_))
+            || other.turn.dst != req.turn.dst
+            || other.turn.src.road == req.turn.src.road
+        {
+            return false;
+        }
+        let state = &self.state[&req.turn.parent];
+        let turn = map.get_t(other.turn);
+        if state
+            .accepted
+            .iter()
+            .any(|accepted| map.get_t(accepted.turn).conflicts_with(turn))
+        {
+            return false;
+        }
+        if let Some(signal) = map.maybe_get_traffic_signal(req.turn.parent) {
+            if !self.use_freeform_policy_everywhere {
+                let stage = &signal.stages[state.signal.as_ref().unwrap().current_stage];
+                if stage.get_priority_of_turn(other.turn, map.get_i(state.id))
+                    == TurnPriority::Banned
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     fn traffic_signal_policy(
         &mut self,
         req: &Request,
@@ -863,8 +1038,71 @@ impl IntersectionSimState {
         let (our_time, _) = state.waiting[req];
 
         // Can't go at all this stage.
-        let our_priority = stage.get_priority_of_turn(req.turn, map.get_i(state.id));
+        let mut our_priority = stage.get_priority_of_turn(req.turn, map.get_i(state.id));
+        // The turn across oncoming traffic is a right turn where people drive on the left.
+        let turn_across_traffic = if map.get_config().driving_side == DrivingSide::Right {
+            TurnType::Left
+        } else {
+            TurnType::Right
+        };
+        if signal.two_stage_bike_left_turns
+            && is_bike(req.agent)
+            && turn.turn_type == turn_across_traffic
+            && our_priority != TurnPriority::Protected
+        {
+            our_priority = two_stage_left_turn_priority(stage, map.get_i(state.id), req.turn);
+        }
         if our_priority == TurnPriority::Banned {
+            // A train preempts the signal, switching to the next stage serving it once the current
+            // stage clears. The train will be woken up to try again.
+            if self.prioritize_rail && is_train(req.agent) && !signal_state.externally_controlled {
+                if let Some(scheduler) = scheduler {
+                    let i = map.get_i(state.id);
+                    let current = signal_state.current_stage;
+                    if let Some(next) = (1..signal.stages.len())
+                        .map(|offset| (current + offset) % signal.stages.len())
+                        .find(|idx| {
+                            signal.stages[*idx].get_priority_of_turn(req.turn, i)
+                                != TurnPriority::Banned
+                        })
+                    {
+                        self.preempt_signal(now, req.turn.parent, next, scheduler, map);
+                    }
+                }
+            }
+            return false;
+        }
+
+        // Nothing new starts a movement that's about to end during the yellow and all-red
+        // intervals. Variable and externally controlled stages don't know when they'll end,
+        // unless a train is preempting the signal.
+        let next_stage = signal_state
+            .preempt_to
+            .unwrap_or((signal_state.current_stage + 1) % signal.stages.len());
+        if (matches!(stage.stage_type, StageType::Fixed(_)) || signal_state.preempt_to.is_some())
+            && !signal_state.externally_controlled
+            && remaining_stage_time <= stage.yellow + stage.all_red
+            && signal.stages[next_stage].get_priority_of_turn(req.turn, map.get_i(state.id))
+                == TurnPriority::Banned
+        {
+            return false;
+        }
+
+        // Give people biking and walking a head start, so they're already in the intersection
+        // and visible before motor vehicles start turning across them. A bike box means bikes
+        // waiting at the red are in front of motor vehicles, so they also get to go first.
+        let time_into_stage = full_stage_duration - remaining_stage_time;
+        if time_into_stage >= Duration::ZERO
+            && time_into_stage < LEADING_BIKE_INTERVAL
+            && is_motor_vehicle(req.agent)
+            && (map.get_i(state.id).protected_for_bikes || signal.bike_box)
+        {
+            if let Some(s) = scheduler {
+                s.update(
+                    now + (LEADING_BIKE_INTERVAL - time_into_stage),
+                    Command::update_agent(req.agent),
+                );
+            }
             return false;
         }
 
@@ -1038,6 +1276,8 @@ impl SignalState {
             current_stage: 0,
             stage_ends_at: now,
             extensions_count: 0,
+            externally_controlled: false,
+            preempt_to: None,
         };
 
         let signal = map.get_traffic_signal(id);
@@ -1102,3 +1342,43 @@ fn allow_block_the_box(i: &Intersection) -> bool {
     }
     false
 }
+
+/// Does this turn join a highway lane that vehicles from some other road also turn into? This
+/// covers on-ramps and two highways combining.
+fn is_merge(turn: TurnID, map: &Map) -> bool {
+    if map.get_parent(turn.dst).get_rank() != osm::RoadRank::Highway {
+        return false;
+    }
+    map.get_turns_to_lane(turn.dst)
+        .into_iter()
+        .any(|t| t.id.src.road != turn.src.road)
+}
+
+fn is_train(agent: AgentID) -> bool {
+    matches!(agent, AgentID::Car(c) if c.vehicle_type == VehicleType::Train)
+}
+
+fn is_motor_vehicle(agent: AgentID) -> bool {
+    matches!(agent, AgentID::Car(c) if c.vehicle_type != VehicleType::Bike)
+}
+
+fn is_bike(agent: AgentID) -> bool {
+    matches!(agent, AgentID::Car(c) if c.vehicle_type == VehicleType::Bike)
+}
+
+/// A two-stage left turn first rides straight across with through traffic, then waits in a queue
+/// box on the far corner and crosses again with traffic heading into the destination road. Only
+/// the second leg is modeled: the bike goes whenever through traffic into its destination is
+/// protected, instead of yielding to oncoming traffic.
+fn two_stage_left_turn_priority(stage: &Stage, i: &Intersection, turn: TurnID) -> TurnPriority {
+    let to = i.turn_to_movement(turn).0.to;
+    if i.movements.values().any(|m| {
+        m.id.to == to
+            && m.turn_type == TurnType::Straight
+            && stage.get_priority_of_movement(m.id) == TurnPriority::Protected
+    }) {
+        TurnPriority::Protected
+    } else {
+        TurnPriority::Banned
+    }
+}
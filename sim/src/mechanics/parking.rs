@@ -9,10 +9,10 @@ use abstutil::{
     deserialize_btreemap, deserialize_multimap, serialize_btreemap, serialize_multimap, MultiMap,
     Timer,
 };
-use geom::{Distance, PolyLine, Pt2D};
+use geom::{Distance, PolyLine, Pt2D, Time};
 use map_model::{
     BuildingID, Lane, LaneID, LaneType, Map, OffstreetParking, ParkingLotID, PathConstraints,
-    PathStep, Position, Traversable, TurnID,
+    PathStep, Position, RoutingParams, Traversable, TurnID,
 };
 
 use crate::{CarID, CarStatus, DrawCarInput, Event, ParkedCar, ParkingSpot, PersonID, Vehicle};
@@ -26,10 +26,16 @@ pub trait ParkingSim {
     /// Returns any cars that got very abruptly evicted from existence, and also cars actively
     /// moving into a deleted spot.
     fn handle_live_edits(&mut self, map: &Map, timer: &mut Timer) -> (Vec<ParkedCar>, Vec<CarID>);
+    /// Closes or reopens onstreet parking based on time-of-day restrictions. Cars already parked
+    /// when a restriction begins stay put, but nobody new may park there. Returns the next time
+    /// that any restriction changes.
+    fn update_restrictions(&mut self, now: Time, map: &Map) -> Option<Time>;
     fn get_free_onstreet_spots(&self, l: LaneID) -> Vec<ParkingSpot>;
     fn get_free_offstreet_spots(&self, b: BuildingID) -> Vec<ParkingSpot>;
     fn get_free_lot_spots(&self, pl: ParkingLotID) -> Vec<ParkingSpot>;
     fn reserve_spot(&mut self, spot: ParkingSpot, car: CarID);
+    /// Remember which building a car belongs to, for resident permit parking.
+    fn set_home(&mut self, car: CarID, home: BuildingID);
     /// Needed when abruptly deleting a car, in case they're being deleted during their last step.
     fn unreserve_spot(&mut self, car: CarID);
     fn remove_parked_car(&mut self, p: ParkedCar);
@@ -57,7 +63,7 @@ pub trait ParkingSim {
     fn spot_to_sidewalk_pos(&self, spot: ParkingSpot, map: &Map) -> Position;
     fn get_owner_of_car(&self, id: CarID) -> Option<PersonID>;
     fn lookup_parked_car(&self, id: CarID) -> Option<&ParkedCar>;
-    /// (Filled, available)
+    /// (Filled, available). Empty spots closed by a time-of-day restriction are in neither.
     fn get_all_parking_spots(&self) -> (Vec<ParkingSpot>, Vec<ParkingSpot>);
     /// Unrealistically assumes the driver has knowledge of currently free parking spots, even if
     /// they're far away. Since they don't reserve the spot in advance, somebody else can still beat
@@ -70,6 +76,7 @@ pub trait ParkingSim {
         start: LaneID,
         vehicle: &Vehicle,
         target: BuildingID,
+        now: Time,
         map: &Map,
     ) -> Option<(Vec<PathStep>, ParkingSpot, Position)>;
     fn collect_events(&mut self) -> Vec<Event>;
@@ -129,6 +136,8 @@ pub struct NormalParkingSimState {
         deserialize_with = "deserialize_multimap"
     )]
     driving_to_parking_lanes: MultiMap<LaneID, LaneID>,
+    // Parking lanes currently closed by a time-of-day restriction
+    restricted_lanes: BTreeSet<LaneID>,
 
     // Off-street
     num_spots_per_offstreet: BTreeMap<BuildingID, usize>,
@@ -147,6 +156,13 @@ pub struct NormalParkingSimState {
     )]
     driving_to_lots: MultiMap<LaneID, ParkingLotID>,
 
+    // Where cars were initially parked, which is where their owner lives
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    homes: BTreeMap<CarID, BuildingID>,
+
     events: Vec<Event>,
 }
 
@@ -159,11 +175,14 @@ impl NormalParkingSimState {
 
             onstreet_lanes: BTreeMap::new(),
             driving_to_parking_lanes: MultiMap::new(),
+            restricted_lanes: BTreeSet::new(),
             num_spots_per_offstreet: BTreeMap::new(),
             driving_to_offstreet: MultiMap::new(),
             num_spots_per_lot: BTreeMap::new(),
             driving_to_lots: MultiMap::new(),
 
+            homes: BTreeMap::new(),
+
             events: Vec::new(),
         };
         for l in map.all_lanes() {
@@ -211,6 +230,21 @@ impl NormalParkingSimState {
 
         sim
     }
+
+    fn is_restricted(&self, spot: ParkingSpot) -> bool {
+        match spot {
+            ParkingSpot::Onstreet(l, _) => self.restricted_lanes.contains(&l),
+            ParkingSpot::Offstreet(_, _) | ParkingSpot::Lot(_, _) => false,
+        }
+    }
+
+    /// Onstreet parking in a resident permit zone is only for cars living there
+    fn has_permit(&self, l: LaneID, vehicle: &Vehicle, map: &Map) -> bool {
+        map.get_edits()
+            .permit_zone_for_road(l.road)
+            .map(|zone| zone.allows(self.homes.get(&vehicle.id).cloned()))
+            .unwrap_or(true)
+    }
 }
 
 impl ParkingSim for NormalParkingSimState {
@@ -254,6 +288,29 @@ impl ParkingSim for NormalParkingSimState {
         (evicted, moving_into_deleted_spot)
     }
 
+    fn update_restrictions(&mut self, now: Time, map: &Map) -> Option<Time> {
+        self.restricted_lanes.clear();
+        let mut next_change: Option<Time> = None;
+        for l in self.onstreet_lanes.keys() {
+            let road = map.get_parent(*l);
+            if road.parking_restrictions.is_empty() {
+                continue;
+            }
+            if road.parking_restriction_at(*l, now).is_some() {
+                self.restricted_lanes.insert(*l);
+            }
+            if let Some(t) = road
+                .parking_restrictions
+                .all_transitions()
+                .into_iter()
+                .find(|t| *t > now)
+            {
+                next_change = Some(next_change.map(|x| x.min(t)).unwrap_or(t));
+            }
+        }
+        next_change
+    }
+
     fn get_free_onstreet_spots(&self, l: LaneID) -> Vec<ParkingSpot> {
         let mut spots: Vec<ParkingSpot> = Vec::new();
         if let Some(lane) = self.onstreet_lanes.get(&l) {
@@ -310,6 +367,10 @@ impl ParkingSim for NormalParkingSimState {
         self.reserved_spots.retain(|_, c| car != *c);
     }
 
+    fn set_home(&mut self, car: CarID, home: BuildingID) {
+        self.homes.insert(car, home);
+    }
+
     fn remove_parked_car(&mut self, p: ParkedCar) {
         if self.parked_cars.remove(&p.vehicle.id).is_none() {
             panic!("remove_parked_car {:?} missing from parked_cars", p);
@@ -431,7 +492,9 @@ impl ParkingSim for NormalParkingSimState {
     }
 
     fn is_free(&self, spot: ParkingSpot) -> bool {
-        !self.occupants.contains_key(&spot) && !self.reserved_spots.contains_key(&spot)
+        !self.occupants.contains_key(&spot)
+            && !self.reserved_spots.contains_key(&spot)
+            && !self.is_restricted(spot)
     }
 
     fn get_car_at_spot(&self, spot: ParkingSpot) -> Option<&ParkedCar> {
@@ -451,6 +514,9 @@ impl ParkingSim for NormalParkingSimState {
         let mut candidates = Vec::new();
 
         for l in self.driving_to_parking_lanes.get(driving_pos.lane()) {
+            if !self.has_permit(*l, vehicle, map) {
+                continue;
+            }
             for spot in self.onstreet_lanes[l].spots() {
                 if self.is_free(spot)
                     && driving_pos.dist_along()
@@ -551,7 +617,8 @@ impl ParkingSim for NormalParkingSimState {
         for spot in spots {
             if self.is_free(spot) {
                 available.push(spot);
-            } else {
+            } else if self.occupants.contains_key(&spot) || self.reserved_spots.contains_key(&spot)
+            {
                 filled.push(spot);
             }
         }
@@ -563,6 +630,7 @@ impl ParkingSim for NormalParkingSimState {
         start: LaneID,
         vehicle: &Vehicle,
         target: BuildingID,
+        now: Time,
         map: &Map,
     ) -> Option<(Vec<PathStep>, ParkingSpot, Position)> {
         let mut backrefs: HashMap<LaneID, TurnID> = HashMap::new();
@@ -571,6 +639,7 @@ impl ParkingSim for NormalParkingSimState {
         // deterministic.
         let mut queue: BinaryHeap<(Distance, LaneID)> = BinaryHeap::new();
         queue.push((Distance::ZERO, start));
+        let restrictions = parking_search_restrictions(start, target, now, map);
 
         // We need a source of randomness between different cars, but it needs to be deterministic
         // across repeated runs of the exact same simulation. This also shouldn't be the same
@@ -608,6 +677,9 @@ impl ParkingSim for NormalParkingSimState {
                 }
             }
             for turn in map.get_turns_for(current, PathConstraints::Car) {
+                if !parking_search_allows(&restrictions, turn.id) {
+                    continue;
+                }
                 if let Entry::Vacant(e) = backrefs.entry(turn.id.dst) {
                     let dist_this_step = turn.geom.length() + map.get_l(current).length();
                     // When vehicles search away from the first lane for a spot, don't all go in
@@ -670,6 +742,10 @@ impl ParkingLane {
         if lane.lane_type != LaneType::Parking {
             return None;
         }
+        // The curb might be used for something else
+        if !map.get_parent(lane.id).curb_use(lane.id).allows_parking() {
+            return None;
+        }
 
         let driving_lane = if let Some(l) = map.get_parent(lane.id).parking_to_driving(lane.id) {
             l
@@ -842,6 +918,11 @@ impl ParkingSim for InfiniteParkingSimState {
         (Vec::new(), Vec::new())
     }
 
+    fn update_restrictions(&mut self, _: Time, _: &Map) -> Option<Time> {
+        // Onstreet parking is ignored entirely
+        None
+    }
+
     fn get_free_onstreet_spots(&self, _: LaneID) -> Vec<ParkingSpot> {
         Vec::new()
     }
@@ -864,6 +945,10 @@ impl ParkingSim for InfiniteParkingSimState {
         self.reserved_spots.retain(|_, c| car != *c);
     }
 
+    fn set_home(&mut self, _: CarID, _: BuildingID) {
+        // Onstreet parking is ignored, so permits don't matter
+    }
+
     fn remove_parked_car(&mut self, p: ParkedCar) {
         self.parked_cars
             .remove(&p.vehicle.id)
@@ -989,6 +1074,7 @@ impl ParkingSim for InfiniteParkingSimState {
         start: LaneID,
         vehicle: &Vehicle,
         target: BuildingID,
+        now: Time,
         map: &Map,
     ) -> Option<(Vec<PathStep>, ParkingSpot, Position)> {
         // TODO This impl is copied from NormalParkingSimState. Instead, we already know the
@@ -999,6 +1085,7 @@ impl ParkingSim for InfiniteParkingSimState {
         // deterministic.
         let mut queue: BinaryHeap<(Distance, LaneID)> = BinaryHeap::new();
         queue.push((Distance::ZERO, start));
+        let restrictions = parking_search_restrictions(start, target, now, map);
 
         while !queue.is_empty() {
             let (dist_so_far, current) = queue.pop().unwrap();
@@ -1029,6 +1116,9 @@ impl ParkingSim for InfiniteParkingSimState {
                 }
             }
             for turn in map.get_turns_for(current, PathConstraints::Car) {
+                if !parking_search_allows(&restrictions, turn.id) {
+                    continue;
+                }
                 if let Entry::Vacant(e) = backrefs.entry(turn.id.dst) {
                     let dist_this_step = turn.geom.length() + map.get_l(current).length();
                     e.insert(turn.id);
@@ -1070,3 +1160,30 @@ impl ParkingSim for InfiniteParkingSimState {
         cars
     }
 }
+
+/// Cars searching for parking respect time-of-day restrictions and restricted zones, except
+/// around where they are and where they're going, just like `Map::pathfind_at`.
+fn parking_search_restrictions(
+    start: LaneID,
+    target: BuildingID,
+    now: Time,
+    map: &Map,
+) -> Option<RoutingParams> {
+    map.routing_params_at(
+        PathConstraints::Car,
+        now,
+        &[start.road, map.get_b(target).sidewalk_pos.lane().road],
+    )
+}
+
+fn parking_search_allows(restrictions: &Option<RoutingParams>, turn: TurnID) -> bool {
+    match restrictions {
+        Some(params) => {
+            !params.avoid_roads.contains(&turn.dst.road)
+                && !params
+                    .avoid_movements_between
+                    .contains(&(turn.src.road, turn.dst.road))
+        }
+        None => true,
+    }
+}
@@ -7,7 +7,7 @@ use geom::{Distance, Time};
 use map_model::{Map, Position, Traversable};
 
 use crate::mechanics::car::{Car, CarState};
-use crate::{CarID, VehicleType, FOLLOWING_DISTANCE};
+use crate::{CarID, VehicleType};
 
 /// A Queue of vehicles on a single lane or turn. This is where
 /// https://a-b-street.github.io/docs/tech/trafficsim/discrete_event.html#exact-positions is
@@ -36,12 +36,19 @@ pub(crate) struct Queue {
 
     /// How long the lane or turn physically is.
     pub geom_len: Distance,
-    /// When a car's turn is accepted, reserve the vehicle length + FOLLOWING_DISTANCE for the
+    /// How much of the queue vehicles can wait in. This is usually geom_len, but a turn pocket
+    /// only opens partway along its lane. Once the pocket is full, more vehicles wait to turn into
+    /// it, instead of spilling back into the space beside the pocket.
+    capacity: Distance,
+    /// When a car's turn is accepted, reserve the vehicle length + following distance for the
     /// target lane. When the car completely leaves (stops being the laggy_head), free up that
     /// space. To prevent blocking the box for possibly scary amounts of time, allocate some of
     /// this length first. This is unused for turns themselves. This value can exceed geom_len
     /// (for the edge case of ONE long car on a short queue).
     pub reserved_length: Distance,
+    /// How far apart vehicles must be, measured from front of one to the back of the other. This
+    /// depends on the weather.
+    following_distance: Distance,
 }
 
 /// A member of a `Queue`.
@@ -69,21 +76,38 @@ pub enum Queued {
 pub struct QueueEntry {
     pub member: Queued,
     pub front: Distance,
-    /// Not incuding FOLLOWING_DISTANCE
+    /// Not incuding the following distance
     pub back: Distance,
 }
 
 impl Queue {
-    pub fn new(id: Traversable, map: &Map) -> Queue {
+    pub fn new(id: Traversable, map: &Map, following_distance: Distance) -> Queue {
+        let geom_len = id.get_polyline(map).length();
         Queue {
             id,
             members: VecDeque::new(),
             laggy_head: None,
-            geom_len: id.get_polyline(map).length(),
+            geom_len,
+            capacity: Queue::capacity(id, geom_len, map),
             reserved_length: Distance::ZERO,
+            following_distance,
         }
     }
 
+    fn capacity(id: Traversable, geom_len: Distance, map: &Map) -> Distance {
+        if let Traversable::Lane(l) = id {
+            if let Some(pocket) = map.get_l(l).pocket {
+                return pocket.min(geom_len);
+            }
+        }
+        geom_len
+    }
+
+    /// After live map edits, a lane may have become a turn pocket or stopped being one.
+    pub fn update_capacity(&mut self, map: &Map) {
+        self.capacity = Queue::capacity(self.id, self.geom_len, map);
+    }
+
     /// Get the front of the last car in the queue.
     pub fn get_last_car_position(
         &self,
@@ -130,7 +154,7 @@ impl Queue {
         let mut previous: Option<QueueEntry> = None;
         for queued in self.members.iter().cloned() {
             let bound = match previous {
-                Some(entry) => entry.back - FOLLOWING_DISTANCE,
+                Some(entry) => entry.back - self.following_distance,
                 None => match self.laggy_head {
                     Some(id) => {
                         // The simple but broken version:
@@ -181,11 +205,11 @@ impl Queue {
                             // They might actually be out of the way, but laggy_head hasn't been
                             // updated yet.
                             if dist_away_from_this_queue
-                                < leader.vehicle.length + FOLLOWING_DISTANCE
+                                < leader.vehicle.length + self.following_distance
                             {
                                 self.geom_len
                                     - (cars[&id].vehicle.length - dist_away_from_this_queue)
-                                    - FOLLOWING_DISTANCE
+                                    - self.following_distance
                             } else {
                                 self.geom_len
                             }
@@ -281,7 +305,13 @@ impl Queue {
         // Enable to detect possible bugs, but save time otherwise
         if false {
             if let Some(intermediate_results) = intermediate_results {
-                validate_positions(intermediate_results, cars, now, self.id)
+                validate_positions(
+                    intermediate_results,
+                    cars,
+                    now,
+                    self.id,
+                    self.following_distance,
+                )
             }
         }
 
@@ -325,18 +355,20 @@ impl Queue {
                 // TODO We can be more precise! We already call get_car_positions, and that
                 // calculates exactly where the laggy head is. We just need to plumb that bound
                 // back here.
-                if self.geom_len - cars[&c].vehicle.length - FOLLOWING_DISTANCE < start_dist {
+                if self.geom_len - cars[&c].vehicle.length - self.following_distance < start_dist {
                     return None;
                 }
             }
         }
 
         // Are we too close to the leader?
-        if idx != 0 && dists[idx - 1].back - FOLLOWING_DISTANCE < start_dist {
+        if idx != 0 && dists[idx - 1].back - self.following_distance < start_dist {
             return None;
         }
         // Or the follower?
-        if idx != dists.len() && start_dist - vehicle_len - FOLLOWING_DISTANCE < dists[idx].front {
+        if idx != dists.len()
+            && start_dist - vehicle_len - self.following_distance < dists[idx].front
+        {
             return None;
         }
 
@@ -347,7 +379,7 @@ impl Queue {
     /// -- the same index and immediately after passing that query.
     pub fn insert_car_at_idx(&mut self, idx: usize, car: &Car) {
         self.members.insert(idx, Queued::Vehicle(car.vehicle.id));
-        self.reserved_length += car.vehicle.length + FOLLOWING_DISTANCE;
+        self.reserved_length += car.vehicle.length + self.following_distance;
     }
 
     /// Record that a car has entered a queue at the end. It's assumed that try_to_reserve_entry
@@ -376,36 +408,36 @@ impl Queue {
     /// If true, there's room and the car must actually start the turn (because the space is
     /// reserved).
     pub fn try_to_reserve_entry(&mut self, car: &Car, force_entry: bool) -> bool {
-        // If self.reserved_length >= self.geom_len, then the lane is already full. Normally we
+        // If self.reserved_length >= self.capacity, then the lane is already full. Normally we
         // won't allow more cars to start a turn towards it, but if force_entry is true, then we'll
         // allow it.
 
-        // Sometimes a car + FOLLOWING_DISTANCE might be longer than the geom_len entirely. In that
-        // case, it just means the car won't totally fit on the queue at once, which is fine.
+        // Sometimes a car + the following distance might be longer than the geom_len entirely. In
+        // that case, it just means the car won't totally fit on the queue at once, which is fine.
         // Reserve the normal amount of space; the next car trying to enter will get rejected.
         // Also allow this don't-block-the-box prevention to be disabled.
         if self.room_for_car(car) || force_entry {
-            self.reserved_length += car.vehicle.length + FOLLOWING_DISTANCE;
+            self.reserved_length += car.vehicle.length + self.following_distance;
             return true;
         }
         false
     }
 
-    /// True if the reserved length exceeds the physical length. This means a vehicle is headed
-    /// towards the queue already and is expected to not fit entirely inside.
+    /// True if the reserved length exceeds the space available for waiting. This means a vehicle
+    /// is headed towards the queue already and is expected to not fit entirely inside.
     pub fn is_overflowing(&self) -> bool {
-        self.reserved_length >= self.geom_len
+        self.reserved_length >= self.capacity
     }
 
     /// Can a car start a turn for this queue?
     pub fn room_for_car(&self, car: &Car) -> bool {
         self.reserved_length == Distance::ZERO
-            || self.reserved_length + car.vehicle.length + FOLLOWING_DISTANCE < self.geom_len
+            || self.reserved_length + car.vehicle.length + self.following_distance < self.capacity
     }
 
     /// Once a car has fully exited a queue, free up the space it was reserving.
     pub fn free_reserved_space(&mut self, car: &Car) {
-        self.reserved_length -= car.vehicle.length + FOLLOWING_DISTANCE;
+        self.reserved_length -= car.vehicle.length + self.following_distance;
         assert!(
             self.reserved_length >= Distance::ZERO,
             "invalid reserved length: {:?}, car: {:?}",
@@ -470,11 +502,11 @@ impl Queue {
         idx: usize,
     ) {
         assert!(front > back);
-        assert!(back >= FOLLOWING_DISTANCE);
+        assert!(back >= self.following_distance);
         let vehicle_len = front - back;
         self.members
             .insert(idx, Queued::StaticBlockage { cause, front, back });
-        self.reserved_length += vehicle_len + FOLLOWING_DISTANCE;
+        self.reserved_length += vehicle_len + self.following_distance;
     }
 
     /// Record that a car is no longer blocking a static portion of the queue.
@@ -484,7 +516,7 @@ impl Queue {
             Queued::StaticBlockage { front, back, cause } => {
                 assert_eq!(caused_by, cause);
                 let vehicle_len = front - back;
-                self.reserved_length -= vehicle_len + FOLLOWING_DISTANCE;
+                self.reserved_length -= vehicle_len + self.following_distance;
             }
             _ => unreachable!(),
         }
@@ -500,7 +532,8 @@ impl Queue {
                 vehicle_len: car.vehicle.length,
             },
         );
-        // We don't need to touch reserved_length -- it's still vehicle_len + FOLLOWING_DISTANCE
+        // We don't need to touch reserved_length -- it's still vehicle_len + the following
+        // distance
     }
 
     /// Record that a car is no longer blocking a dynamic portion of the queue.
@@ -509,7 +542,7 @@ impl Queue {
         match blockage {
             Queued::DynamicBlockage { cause, vehicle_len } => {
                 assert_eq!(caused_by, cause);
-                self.reserved_length -= vehicle_len + FOLLOWING_DISTANCE;
+                self.reserved_length -= vehicle_len + self.following_distance;
             }
             _ => unreachable!(),
         }
@@ -561,9 +594,10 @@ fn validate_positions(
     cars: &FixedMap<CarID, Car>,
     now: Time,
     id: Traversable,
+    following_distance: Distance,
 ) {
     for pair in dists.windows(2) {
-        if pair[0].back - FOLLOWING_DISTANCE < pair[1].front {
+        if pair[0].back - following_distance < pair[1].front {
             dump_cars(dists, cars, id, now);
             panic!(
                 "get_car_positions wound up with bad positioning: {} then {}\n{:?}",
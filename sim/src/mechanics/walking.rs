@@ -5,21 +5,27 @@ use serde::{Deserialize, Serialize};
 use abstutil::{deserialize_multimap, serialize_multimap, FixedMap, IndexableKey, MultiMap};
 use geom::{Distance, Duration, Line, PolyLine, Speed, Time};
 use map_model::{
-    BuildingID, DrivingSide, Map, ParkingLotID, Path, PathConstraints, PathStep, TransitRouteID,
-    Traversable, SIDEWALK_THICKNESS,
+    osm, BuildingID, DrivingSide, Map, ParkingLotID, Path, PathConstraints, PathStep, Road,
+    TransitRouteID, Traversable, SIDEWALK_THICKNESS,
 };
 
 use crate::sim::Ctx;
 use crate::{
     AgentID, AgentProperties, Command, CommutersVehiclesCounts, CreatePedestrian, DistanceInterval,
-    DrawPedCrowdInput, DrawPedestrianInput, Event, Intent, IntersectionSimState, ParkedCar,
-    ParkingSpot, PedCrowdLocation, PedestrianID, PersonID, Scheduler, SidewalkPOI, SidewalkSpot,
-    TimeInterval, TransitSimState, TripID, TripManager, UnzoomedAgent,
+    DrawPedCrowdInput, DrawPedestrianInput, DrivingSimState, Event, Intent, IntersectionSimState,
+    ParkedCar, ParkingSpot, PedCrowdLocation, PedestrianID, PersonID, Scheduler, SidewalkPOI,
+    SidewalkSpot, SimOptions, TimeInterval, TransitSimState, TripID, TripManager, UnzoomedAgent,
 };
 
 const TIME_TO_START_BIKING: Duration = Duration::const_seconds(30.0);
 const TIME_TO_FINISH_BIKING: Duration = Duration::const_seconds(45.0);
 
+/// Nobody tries to jaywalk across roads wider than this.
+const MAX_JAYWALK_ROAD_WIDTH: Distance = Distance::const_meters(20.0);
+/// Pedestrians only jaywalk if fewer than this many vehicles are expected to pass by while they're
+/// in the road.
+const MAX_VEHICLES_WHILE_JAYWALKING: f64 = 0.5;
+
 /// Simulates pedestrians. Unlike vehicles, pedestrians can move bidirectionally on sidewalks and
 /// just "ghost" through each other. There's no queueing or slowdown when many people are
 /// overlapping. They're simply grouped together into a DrawPedCrowdInput for rendering.
@@ -32,14 +38,19 @@ pub(crate) struct WalkingSimState {
     )]
     peds_per_traversable: MultiMap<Traversable, PedestrianID>,
     events: Vec<Event>,
+    /// Scales everyone's walking speed, depending on the weather
+    speed_factor: f64,
+    allow_jaywalking: bool,
 }
 
 impl WalkingSimState {
-    pub fn new() -> WalkingSimState {
+    pub fn new(opts: &SimOptions) -> WalkingSimState {
         WalkingSimState {
             peds: FixedMap::new(),
             peds_per_traversable: MultiMap::new(),
             events: Vec::new(),
+            speed_factor: opts.weather.speed_factor(),
+            allow_jaywalking: opts.allow_jaywalking,
         }
     }
 
@@ -67,8 +78,9 @@ impl WalkingSimState {
                     Time::START_OF_DAY + Duration::seconds(1.0),
                 ),
                 steep_uphill: false,
+                jaywalk: false,
             },
-            speed: params.speed,
+            speed: params.speed * self.speed_factor,
             total_blocked_time: Duration::ZERO,
             started_at: now,
             path: params.path,
@@ -93,7 +105,12 @@ impl WalkingSimState {
                 Line::must_new(driving_pos.pt(map), params.start.sidewalk_pos.pt(map)),
                 TimeInterval::new(now, now + TIME_TO_FINISH_BIKING),
             ),
-            _ => ped.crossing_state(params.start.sidewalk_pos.dist_along(), now, map),
+            _ => ped.crossing_state(
+                params.start.sidewalk_pos.dist_along(),
+                now,
+                map,
+                self.allow_jaywalking,
+            ),
         };
 
         scheduler.push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
@@ -127,9 +144,59 @@ impl WalkingSimState {
         ctx: &mut Ctx,
         trips: &mut TripManager,
         transit: &mut TransitSimState,
+        driving: &DrivingSimState,
     ) {
+        let allow_jaywalking = self.allow_jaywalking;
         let mut ped = self.peds.get_mut(&id).unwrap();
         match ped.state {
+            PedState::Crossing {
+                ref dist_int,
+                jaywalk: true,
+                ..
+            } => {
+                let dist = dist_int.end;
+                let road = ctx.map.get_parent(ped.path.current_step().as_lane());
+                let pt1 = ped
+                    .path
+                    .current_step()
+                    .as_traversable()
+                    .get_polyline(ctx.map)
+                    .must_dist_along(dist)
+                    .0;
+                let pt2 = ped.goal.sidewalk_pos.pt(ctx.map);
+                match Line::new(pt1, pt2) {
+                    Ok(line) if gap_to_jaywalk(road, ped.speed, driving) => {
+                        let time_int = TimeInterval::new(now, now + line.length() / ped.speed);
+                        ped.state = PedState::Jaywalking(dist, line, time_int);
+                    }
+                    // Too much traffic right now, so keep walking to the corner
+                    _ => {
+                        ped.state = ped.crossing_state(dist, now, ctx.map, false);
+                    }
+                }
+                ctx.scheduler
+                    .push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
+            }
+            PedState::Jaywalking(_, _, _) => {
+                // Skip the crosswalk and arrive on the other side of the road
+                self.peds_per_traversable
+                    .remove(ped.path.current_step().as_traversable(), ped.id);
+                while !ped.path.is_last_step() {
+                    ped.path.shift(ctx.map);
+                }
+                self.peds_per_traversable
+                    .insert(ped.path.current_step().as_traversable(), ped.id);
+                self.events.push(Event::AgentEntersTraversable(
+                    AgentID::Pedestrian(ped.id),
+                    Some(ped.trip),
+                    ped.path.current_step().as_traversable(),
+                    None,
+                ));
+                ped.state =
+                    ped.crossing_state(ped.goal.sidewalk_pos.dist_along(), now, ctx.map, false);
+                ctx.scheduler
+                    .push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
+            }
             PedState::Crossing { ref dist_int, .. } => {
                 if ped.path.is_last_step() {
                     match ped.goal.connection {
@@ -174,6 +241,7 @@ impl WalkingSimState {
                                 now,
                                 ped.id,
                                 stop,
+                                ped.started_at,
                                 ped.total_blocked_time,
                                 ped.path.total_length(),
                                 ctx,
@@ -234,6 +302,7 @@ impl WalkingSimState {
                         &mut self.peds_per_traversable,
                         &mut self.events,
                         ctx.scheduler,
+                        allow_jaywalking,
                     ) {
                         ctx.scheduler
                             .push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
@@ -251,6 +320,7 @@ impl WalkingSimState {
                     &mut self.peds_per_traversable,
                     &mut self.events,
                     ctx.scheduler,
+                    allow_jaywalking,
                 ) {
                     ctx.scheduler
                         .push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
@@ -264,8 +334,12 @@ impl WalkingSimState {
                 }
             }
             PedState::LeavingBuilding(b, _) => {
-                ped.state =
-                    ped.crossing_state(ctx.map.get_b(b).sidewalk_pos.dist_along(), now, ctx.map);
+                ped.state = ped.crossing_state(
+                    ctx.map.get_b(b).sidewalk_pos.dist_along(),
+                    now,
+                    ctx.map,
+                    allow_jaywalking,
+                );
                 ctx.scheduler
                     .push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
             }
@@ -283,8 +357,12 @@ impl WalkingSimState {
                 self.peds.remove(&id);
             }
             PedState::LeavingParkingLot(pl, _) => {
-                ped.state =
-                    ped.crossing_state(ctx.map.get_pl(pl).sidewalk_pos.dist_along(), now, ctx.map);
+                ped.state = ped.crossing_state(
+                    ctx.map.get_pl(pl).sidewalk_pos.dist_along(),
+                    now,
+                    ctx.map,
+                    allow_jaywalking,
+                );
                 ctx.scheduler
                     .push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
             }
@@ -318,7 +396,12 @@ impl WalkingSimState {
                 self.peds.remove(&id);
             }
             PedState::FinishingBiking(ref spot, _, _) => {
-                ped.state = ped.crossing_state(spot.sidewalk_pos.dist_along(), now, ctx.map);
+                ped.state = ped.crossing_state(
+                    spot.sidewalk_pos.dist_along(),
+                    now,
+                    ctx.map,
+                    allow_jaywalking,
+                );
                 ctx.scheduler
                     .push(ped.state.get_end_time(), Command::UpdatePed(ped.id));
             }
@@ -387,6 +470,7 @@ impl WalkingSimState {
             | PedState::FinishingBiking(_, _, _) => Distance::ZERO,
             // In all of these cases, we haven't shifted the PathStep that led us to this state yet
             PedState::WaitingToTurn(_, _)
+            | PedState::Jaywalking(_, _, _)
             | PedState::EnteringBuilding(_, _)
             | PedState::EnteringParkingLot(_, _)
             | PedState::StartingToBike(_, _, _)
@@ -448,6 +532,8 @@ impl WalkingSimState {
         let mut backwards: Vec<(PedestrianID, Distance)> = Vec::new();
         let mut bldg_driveway: MultiMap<BuildingID, (PedestrianID, Distance)> = MultiMap::new();
         let mut lot_driveway: MultiMap<ParkingLotID, (PedestrianID, Distance)> = MultiMap::new();
+        // People in the middle of the road are never part of a crowd
+        let mut loners: Vec<DrawPedestrianInput> = Vec::new();
 
         for id in self.peds_per_traversable.get(on) {
             let ped = &self.peds[id];
@@ -490,11 +576,13 @@ impl WalkingSimState {
                     // The backwards half of the sidewalk is closer to the road.
                     backwards.push((*id, dist));
                 }
+                PedState::Jaywalking(_, _, _) => {
+                    loners.push(ped.get_draw_ped(now, map));
+                }
             }
         }
 
         let mut crowds: Vec<DrawPedCrowdInput> = Vec::new();
-        let mut loners: Vec<DrawPedestrianInput> = Vec::new();
 
         // For each group, sort by distance along. Attempt to bundle into intervals.
         for (mut group, location, on_len) in vec![
@@ -622,7 +710,33 @@ struct Pedestrian {
 }
 
 impl Pedestrian {
-    fn crossing_state(&self, start_dist: Distance, start_time: Time, map: &Map) -> PedState {
+    /// Start walking along the current step of the path. If `allow_jaywalking`, the pedestrian
+    /// might stop partway to cross the road instead.
+    fn crossing_state(
+        &self,
+        start_dist: Distance,
+        start_time: Time,
+        map: &Map,
+        allow_jaywalking: bool,
+    ) -> PedState {
+        if allow_jaywalking {
+            if let Some(end_dist) = self.plan_jaywalk(start_dist, map) {
+                let dist_int = DistanceInterval::new_walking(start_dist, end_dist);
+                let (speed, percent_incline) =
+                    self.path.current_step().max_speed_and_incline_along(
+                        Some(self.speed),
+                        PathConstraints::Pedestrian,
+                        map,
+                    );
+                return PedState::Crossing {
+                    time_int: TimeInterval::new(start_time, start_time + dist_int.length() / speed),
+                    dist_int,
+                    steep_uphill: percent_incline >= 0.08,
+                    jaywalk: true,
+                };
+            }
+        }
+
         let end_dist = if self.path.is_last_step() {
             self.goal.sidewalk_pos.dist_along()
         } else {
@@ -645,7 +759,47 @@ impl Pedestrian {
             dist_int,
             time_int,
             steep_uphill: percent_incline >= 0.08,
+            jaywalk: false,
+        }
+    }
+
+    /// If the rest of the path just walks to the corner, crosses the same road, and walks back on
+    /// the other side to the goal, then the pedestrian could instead cross directly, if the road
+    /// isn't too wide or major. Returns the distance along the current sidewalk to cross from.
+    fn plan_jaywalk(&self, start_dist: Distance, map: &Map) -> Option<Distance> {
+        let steps = self.path.get_steps();
+        let (first, last) = match (steps.first()?, steps.last()?) {
+            (
+                PathStep::Lane(l1) | PathStep::ContraflowLane(l1),
+                PathStep::Lane(l2) | PathStep::ContraflowLane(l2),
+            ) => (*l1, *l2),
+            _ => {
+                return None;
+            }
+        };
+        if first == last
+            || steps[1..steps.len() - 1]
+                .iter()
+                .any(|step| !matches!(step, PathStep::Turn(_) | PathStep::ContraflowTurn(_)))
+        {
+            return None;
+        }
+        let road = map.get_parent(first);
+        if road.id != last.road
+            || road.get_width() > MAX_JAYWALK_ROAD_WIDTH
+            || road.get_rank() == osm::RoadRank::Highway
+        {
+            return None;
         }
+
+        // Cross from the point on this sidewalk closest to the goal, but don't backtrack
+        let pl = self.path.current_step().as_traversable().get_polyline(map);
+        let goal_pt = pl.project_pt(self.goal.sidewalk_pos.pt(map));
+        let across = pl.dist_along_of_point(goal_pt)?.0;
+        Some(match steps[0] {
+            PathStep::Lane(_) => across.max(start_dist),
+            _ => across.min(start_dist),
+        })
     }
 
     fn get_dist_along(&self, now: Time, map: &Map) -> Distance {
@@ -656,6 +810,7 @@ impl Pedestrian {
                 ..
             } => dist_int.lerp(time_int.percent(now)),
             PedState::WaitingToTurn(dist, _) => dist,
+            PedState::Jaywalking(dist, _, _) => dist,
             PedState::LeavingBuilding(b, _) | PedState::EnteringBuilding(b, _) => {
                 map.get_b(b).sidewalk_pos.dist_along()
             }
@@ -682,6 +837,7 @@ impl Pedestrian {
                 ref dist_int,
                 ref time_int,
                 steep_uphill,
+                ..
             } => {
                 let percent = if now > time_int.end {
                     1.0
@@ -753,6 +909,11 @@ impl Pedestrian {
                     line.angle().opposite(),
                 )
             }
+            PedState::Jaywalking(_, ref line, ref time_int) => (
+                line.percent_along(time_int.percent(now))
+                    .unwrap_or_else(|_| line.pt1()),
+                line.angle(),
+            ),
             PedState::StartingToBike(_, ref line, ref time_int) => (
                 line.percent_along(time_int.percent(now))
                     .unwrap_or_else(|_| line.pt1()),
@@ -801,6 +962,7 @@ impl Pedestrian {
         peds_per_traversable: &mut MultiMap<Traversable, PedestrianID>,
         events: &mut Vec<Event>,
         scheduler: &mut Scheduler,
+        allow_jaywalking: bool,
     ) -> bool {
         if let PathStep::Turn(t) | PathStep::ContraflowTurn(t) = self.path.next_step() {
             if !intersections.maybe_start_turn(
@@ -828,7 +990,7 @@ impl Pedestrian {
             PathStep::Turn(_) => Distance::ZERO,
             PathStep::ContraflowTurn(t) => map.get_t(t).geom.length(),
         };
-        self.state = self.crossing_state(start_dist, now, map);
+        self.state = self.crossing_state(start_dist, now, map, allow_jaywalking);
         peds_per_traversable.insert(self.path.current_step().as_traversable(), self.id);
         events.push(Event::AgentEntersTraversable(
             AgentID::Pedestrian(self.id),
@@ -846,6 +1008,8 @@ enum PedState {
         dist_int: DistanceInterval,
         time_int: TimeInterval,
         steep_uphill: bool,
+        /// At the end of `dist_int`, cross the road directly, if there's a gap in traffic.
+        jaywalk: bool,
     },
     /// The Distance is either 0 or the current traversable's length. The Time is blocked_since.
     WaitingToTurn(Distance, Time),
@@ -856,6 +1020,9 @@ enum PedState {
     StartingToBike(SidewalkSpot, Line, TimeInterval),
     FinishingBiking(SidewalkSpot, Line, TimeInterval),
     WaitingForBus(TransitRouteID, Time),
+    /// Crossing the road mid-block, straight to the goal. The Distance is where the pedestrian
+    /// left the current sidewalk.
+    Jaywalking(Distance, Line, TimeInterval),
 }
 
 impl PedState {
@@ -870,6 +1037,7 @@ impl PedState {
             PedState::StartingToBike(_, _, ref time_int) => time_int.end,
             PedState::FinishingBiking(_, _, ref time_int) => time_int.end,
             PedState::WaitingForBus(_, _) => unreachable!(),
+            PedState::Jaywalking(_, _, ref time_int) => time_int.end,
         }
     }
 
@@ -882,6 +1050,15 @@ impl PedState {
     }
 }
 
+/// Is there likely a gap in traffic long enough to cross this road? The wider the road and the more
+/// vehicles on it, the less likely.
+fn gap_to_jaywalk(road: &Road, speed: Speed, driving: &DrivingSimState) -> bool {
+    let time_in_road = road.get_width() / speed;
+    let time_to_drive_road = road.length() / road.speed_limit;
+    let vehicles = driving.num_vehicles_on_road(road) as f64;
+    vehicles * (time_in_road / time_to_drive_road) < MAX_VEHICLES_WHILE_JAYWALKING
+}
+
 // The crowds returned here may have low/high values extending up to radius past the real geometry.
 fn find_crowds(
     input: Vec<(PedestrianID, Distance)>,
@@ -1,10 +1,15 @@
-use serde::Serialize;
+use std::collections::BTreeMap;
+
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
 
 use crate::{AlertHandler, Sim, SimFlags, SimOptions};
+use abstio::MapName;
 use abstutil::{prettyprint_usize, Timer};
 use geom::{Duration, Time};
 use map_model::Map;
-use synthpop::Scenario;
+use synthpop::{Scenario, TripEndpoint, TripMode};
 
 /// Simulate a curated list of scenarios to completion, and save the analytics as "prebaked
 /// results," to later compare simulation metrics against the baseline without map edits.
@@ -15,12 +20,66 @@ pub fn prebake(map: &Map, scenario: Scenario, timer: &mut Timer) -> PrebakeSumma
         scenario.scenario_name
     ));
 
+    // Bit of an abuse of this, but just need to fix the rng seed.
+    let mut rng = SimFlags::for_test("prebaked").make_rng();
+    let sim = run_full_day(map, &scenario, &mut rng, timer);
+    abstio::write_binary(
+        abstio::path_prebaked_results(&scenario.map_name, &scenario.scenario_name),
+        sim.get_analytics(),
+    );
+    abstio::write_json(
+        abstio::path_prebaked_metadata(&scenario.map_name, &scenario.scenario_name),
+        &PrebakeMetadata::new(&scenario),
+    );
+    timer.stop(format!(
+        "prebake for {} / {}",
+        scenario.map_name.describe(),
+        scenario.scenario_name
+    ));
+
+    PrebakeSummary::new(&sim, &scenario, map)
+}
+
+/// Simulate a scenario to completion once per random seed, and save how much travel times for the
+/// same trips vary between runs. Average travel times hide how unpredictable some trips are.
+pub fn prebake_reliability(
+    map: &Map,
+    scenario: Scenario,
+    num_seeds: u64,
+    timer: &mut Timer,
+) -> ReliabilityResults {
+    let mut results = ReliabilityResults {
+        num_runs: 0,
+        travel_times: BTreeMap::new(),
+    };
+    for seed in 0..num_seeds {
+        timer.start(format!("reliability run with seed {}", seed));
+        let sim = run_full_day(map, &scenario, &mut XorShiftRng::seed_from_u64(seed), timer);
+        results.num_runs += 1;
+        for (_, id, _, maybe_dt) in &sim.get_analytics().finished_trips {
+            if let Some(dt) = maybe_dt {
+                let info = sim.trip_info(*id);
+                results
+                    .travel_times
+                    .entry((info.start, info.end, info.mode))
+                    .or_insert_with(Vec::new)
+                    .push(*dt);
+            }
+        }
+        timer.stop(format!("reliability run with seed {}", seed));
+    }
+    abstio::write_binary(
+        abstio::path_prebaked_reliability(&scenario.map_name, &scenario.scenario_name),
+        &results,
+    );
+    results
+}
+
+fn run_full_day(map: &Map, scenario: &Scenario, rng: &mut XorShiftRng, timer: &mut Timer) -> Sim {
     let mut opts = SimOptions::new("prebaked");
     opts.alerts = AlertHandler::Silence;
     let mut sim = Sim::new(map, opts);
-    // Bit of an abuse of this, but just need to fix the rng seed.
-    let mut rng = SimFlags::for_test("prebaked").make_rng();
-    sim.instantiate(&scenario, map, &mut rng, timer);
+    sim.instantiate(scenario, map, rng, timer);
 
     // Run until a few hours after the end of the day. Some trips start close to midnight, and we
     // want prebaked data for them too.
@@ -30,10 +89,6 @@ pub fn prebake(map: &Map, scenario: Scenario, timer: &mut Timer) -> PrebakeSumma
         &mut None,
         timer,
     );
-    abstio::write_binary(
-        abstio::path_prebaked_results(&scenario.map_name, &scenario.scenario_name),
-        sim.get_analytics(),
-    );
     // TODO Remove the num_agents check once transit isn't broken. In Green Lake, 3 poor people are
     // waiting at a bus stop that'll never be served...
     if !sim.is_done() && sim.num_agents().sum() > 10 {
@@ -44,13 +99,7 @@ pub fn prebake(map: &Map, scenario: Scenario, timer: &mut Timer) -> PrebakeSumma
             scenario.map_name.describe()
         );
     }
-    timer.stop(format!(
-        "prebake for {} / {}",
-        scenario.map_name.describe(),
-        scenario.scenario_name
-    ));
-
-    PrebakeSummary::new(&sim, &scenario)
+    sim
 }
 
 #[derive(Debug, Serialize)]
@@ -63,25 +112,164 @@ pub struct PrebakeSummary {
 }
 
 impl PrebakeSummary {
-    pub fn new(sim: &Sim, scenario: &Scenario) -> Self {
-        let mut finished_trips = 0;
-        let mut cancelled_trips = 0;
-        // Use f64 seconds, since a serialized Duration has a low cap.
-        let mut total_trip_duration_seconds = 0.0;
-        for (_, _, _, maybe_duration) in &sim.get_analytics().finished_trips {
-            if let Some(dt) = maybe_duration {
-                finished_trips += 1;
-                total_trip_duration_seconds += dt.inner_seconds();
-            } else {
-                cancelled_trips += 1;
-            }
-        }
+    pub fn new(sim: &Sim, scenario: &Scenario, map: &Map) -> Self {
+        let kpis = sim.kpis(map);
         Self {
             map: scenario.map_name.describe(),
             scenario: scenario.scenario_name.clone(),
-            finished_trips,
-            cancelled_trips,
-            total_trip_duration_seconds,
+            finished_trips: kpis.finished_trips,
+            cancelled_trips: kpis.cancelled_trips,
+            total_trip_duration_seconds: kpis.total_trip_duration_seconds,
+        }
+    }
+}
+
+/// Records where a set of prebaked results came from, so it's possible to notice when they no
+/// longer match the map and scenario they're compared against.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PrebakeMetadata {
+    /// Seconds since the UNIX epoch
+    pub created: u64,
+    /// From `file_checksum`. None if the file couldn't be read, or the scenario was generated
+    /// on the fly.
+    pub map_checksum: Option<u64>,
+    pub scenario_checksum: Option<u64>,
+    pub num_trips: usize,
+}
+
+impl PrebakeMetadata {
+    /// Describe the map and scenario files currently on disk. This can only be called natively.
+    pub fn new(scenario: &Scenario) -> PrebakeMetadata {
+        PrebakeMetadata {
+            created: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|dt| dt.as_secs())
+                .unwrap_or(0),
+            map_checksum: file_checksum(scenario.map_name.path()),
+            scenario_checksum: file_checksum(abstio::path_scenario(
+                &scenario.map_name,
+                &scenario.scenario_name,
+            )),
+            num_trips: num_trips(scenario),
+        }
+    }
+
+    pub fn load(name: &MapName, scenario_name: &str) -> Option<PrebakeMetadata> {
+        abstio::maybe_read_json(
+            abstio::path_prebaked_metadata(name, scenario_name),
+            &mut Timer::throwaway(),
+        )
+        .ok()
+    }
+
+    /// Explains why these results might not match the current map and scenario. Checksums that
+    /// couldn't be calculated on either side aren't compared.
+    pub fn stale_reasons(&self, scenario: &Scenario) -> Vec<String> {
+        let mut reasons = Vec::new();
+        if let (Some(before), Some(now)) =
+            (self.map_checksum, file_checksum(scenario.map_name.path()))
+        {
+            if before != now {
+                reasons.push("The map file has changed".to_string());
+            }
         }
+        if let (Some(before), Some(now)) = (
+            self.scenario_checksum,
+            file_checksum(abstio::path_scenario(
+                &scenario.map_name,
+                &scenario.scenario_name,
+            )),
+        ) {
+            if before != now {
+                reasons.push("The scenario file has changed".to_string());
+            }
+        }
+        let trips = num_trips(scenario);
+        if self.num_trips != trips {
+            reasons.push(format!(
+                "The results have {} trips, but the scenario has {}",
+                prettyprint_usize(self.num_trips),
+                prettyprint_usize(trips)
+            ));
+        }
+        reasons
+    }
+}
+
+fn num_trips(scenario: &Scenario) -> usize {
+    scenario.people.iter().map(|p| p.trips.len()).sum()
+}
+
+/// A cheap checksum of a file's contents, stable across builds (unlike `DefaultHasher`). Returns
+/// None if the file can't be read.
+pub fn file_checksum(path: String) -> Option<u64> {
+    // FNV-1a
+    let bytes = abstio::slurp_file(path).ok()?;
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Some(hash)
+}
+
+/// Travel times from running the same scenario with different random seeds, grouped by origin,
+/// destination, and mode. Cancelled trips are left out.
+#[derive(Serialize, Deserialize)]
+pub struct ReliabilityResults {
+    pub num_runs: usize,
+    pub travel_times: BTreeMap<(TripEndpoint, TripEndpoint, TripMode), Vec<Duration>>,
+}
+
+/// How predictable travel times between one origin and destination are
+#[derive(Clone, Copy, Debug)]
+pub struct Reliability {
+    pub samples: usize,
+    pub p50: Duration,
+    pub p95: Duration,
+}
+
+impl Reliability {
+    /// None if there are no samples
+    pub fn new(mut samples: Vec<Duration>) -> Option<Reliability> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+        // Nearest-rank, since there are usually only a handful of samples
+        let rank = |pct: f64| {
+            let idx = ((pct / 100.0) * (samples.len() as f64)).ceil() as usize;
+            samples[idx.max(1) - 1]
+        };
+        Some(Reliability {
+            samples: samples.len(),
+            p50: rank(50.0),
+            p95: rank(95.0),
+        })
+    }
+
+    /// The 95th percentile travel time divided by the median. 1.0 means the trip always takes
+    /// about the same time; 2.0 means that once in a while, it takes twice as long as usual.
+    pub fn ratio(&self) -> f64 {
+        if self.p50 == Duration::ZERO {
+            return 1.0;
+        }
+        self.p95 / self.p50
+    }
+}
+
+impl ReliabilityResults {
+    /// Only origin/destination pairs with at least this many samples are included.
+    pub fn per_od(
+        &self,
+        min_samples: usize,
+    ) -> Vec<(TripEndpoint, TripEndpoint, TripMode, Reliability)> {
+        self.travel_times
+            .iter()
+            .filter(|(_, times)| times.len() >= min_samples)
+            .filter_map(|((from, to, mode), times)| {
+                Reliability::new(times.clone()).map(|r| (*from, *to, *mode, r))
+            })
+            .collect()
     }
 }
@@ -5,10 +5,10 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use geom::Distance;
+use geom::{Distance, Time};
 use map_model::{
-    BuildingID, IntersectionID, LaneID, Map, Path, PathConstraints, PathRequest, PathStep,
-    Position, Traversable, Turn, TurnID,
+    BuildingID, IntersectionID, LaneID, Map, ParkingLotID, Path, PathConstraints, PathRequest,
+    PathStep, Position, Traversable, Turn, TurnID,
 };
 
 use crate::mechanics::Queue;
@@ -49,6 +49,11 @@ enum Goal {
         end_dist: Distance,
         i: IntersectionID,
     },
+    /// Park in one particular lot, without searching anywhere else
+    ParkInLot {
+        lot: ParkingLotID,
+        end_dist: Distance,
+    },
     BikeThenStop {
         goal: SidewalkSpot,
     },
@@ -98,6 +103,17 @@ impl Router {
         }
     }
 
+    pub fn park_in_lot(owner: CarID, path: Path, lot: ParkingLotID) -> Router {
+        Router {
+            goal: Goal::ParkInLot {
+                lot,
+                end_dist: path.get_req().end.dist_along(),
+            },
+            path,
+            owner,
+        }
+    }
+
     pub fn bike_then_stop(owner: CarID, path: Path, goal: SidewalkSpot) -> Router {
         Router {
             goal: Goal::BikeThenStop { goal },
@@ -142,6 +158,7 @@ impl Router {
                 stuck_end_dist,
                 ..
             } => stuck_end_dist.unwrap_or_else(|| spot.unwrap().1),
+            Goal::ParkInLot { end_dist, .. } => end_dist,
             Goal::BikeThenStop { ref goal } => goal.sidewalk_pos.dist_along(),
             Goal::FollowTransitRoute { end_dist } => end_dist,
         }
@@ -156,6 +173,7 @@ impl Router {
         &mut self,
         vehicle: &Vehicle,
         parking: &ParkingSimState,
+        now: Time,
         map: &Map,
         trip_and_person: Option<(TripID, PersonID)>,
         events: &mut Vec<Event>,
@@ -167,6 +185,7 @@ impl Router {
                 Distance::ZERO,
                 vehicle,
                 parking,
+                now,
                 map,
                 trip_and_person,
                 events,
@@ -194,6 +213,7 @@ impl Router {
         front: Distance,
         vehicle: &Vehicle,
         parking: &ParkingSimState,
+        now: Time,
         map: &Map,
         // TODO Not so nice to plumb all of this here
         trip_and_person: Option<(TripID, PersonID)>,
@@ -273,8 +293,8 @@ impl Router {
                         assert!(new_pos.dist_along() >= front);
                         *spot = Some((new_spot, new_pos.dist_along()));
                     } else {
-                        if let Some((new_path_steps, new_spot, new_pos)) =
-                            parking.path_to_free_parking_spot(current_lane, vehicle, target, map)
+                        if let Some((new_path_steps, new_spot, new_pos)) = parking
+                            .path_to_free_parking_spot(current_lane, vehicle, target, now, map)
                         {
                             assert!(!new_path_steps.is_empty());
                             for step in new_path_steps {
@@ -318,6 +338,23 @@ impl Router {
                     None
                 }
             }
+            Goal::ParkInLot { lot, end_dist } => {
+                if end_dist != front {
+                    return None;
+                }
+                // The lot filled up while driving there
+                if let Some(spot) = parking.get_free_lot_spots(lot).into_iter().next() {
+                    Some(ActionAtEnd::StartParking(spot))
+                } else {
+                    if let Some((_, p)) = trip_and_person {
+                        events.push(Event::Alert(
+                            AlertLocation::Person(p),
+                            format!("{} arrived at {}, but it's full", vehicle.id, lot),
+                        ));
+                    }
+                    Some(ActionAtEnd::GiveUpOnParking)
+                }
+            }
             Goal::BikeThenStop { ref goal } => {
                 if goal.sidewalk_pos.dist_along() == front {
                     Some(ActionAtEnd::StopBiking(goal.clone()))
@@ -466,6 +503,75 @@ impl Router {
         }
     }
 
+    /// Called before starting the next turn. If it enters a restricted zone closed to this vehicle
+    /// right now, detour around it. Vehicles already inside a zone, or heading somewhere inside
+    /// it, may continue. If there's no detour, returns the time when the zone opens again, and
+    /// the vehicle should wait until then.
+    pub fn avoid_restricted_zones(
+        &mut self,
+        vehicle: &Vehicle,
+        now: Time,
+        map: &Map,
+        events: &mut Vec<Event>,
+    ) -> Option<Time> {
+        // Transit follows a fixed route, and parking searches already avoid zones
+        if vehicle.vehicle_type.is_transit()
+            || self.is_parking()
+            || self.path.currently_inside_ut().is_some()
+        {
+            return None;
+        }
+        let turn = match self.path.maybe_next_step() {
+            Some(PathStep::Turn(t)) => t,
+            _ => {
+                return None;
+            }
+        };
+        let constraints = vehicle.vehicle_type.to_constraints();
+        let end = self.path.get_req().end;
+        let zone = map.get_edits().restricted_zones.iter().find(|z| {
+            z.members.contains(&turn.dst.road)
+                && !z.members.contains(&turn.src.road)
+                && !z.members.contains(&end.lane().road)
+                && !z.allows(constraints, now)
+        })?;
+
+        let current_lane = turn.src;
+        let req = PathRequest::vehicle(
+            Position::new(current_lane, map.get_l(current_lane).length()),
+            end,
+            constraints,
+        );
+        if let Ok(detour) = map.pathfind_at(req, now) {
+            if detour.maybe_next_step() != Some(PathStep::Turn(turn)) {
+                self.path.reroute(detour, map);
+                events.push(Event::PathAmended(self.path.clone()));
+                return None;
+            }
+        }
+
+        match zone
+            .windows
+            .iter()
+            .find(|w| w.is_active(now))
+            .map(|w| w.end_time)
+        {
+            Some(time) => Some(time),
+            None => {
+                // The zone is always closed, so waiting won't help. The vehicle must've been
+                // routed before the zone existed.
+                events.push(Event::Alert(
+                    AlertLocation::Intersection(turn.parent),
+                    format!(
+                        "{} can't avoid {}, so it's driving through",
+                        vehicle.id, zone.name
+                    ),
+                ));
+                None
+            }
+        }
+    }
+
     pub fn can_lanechange(&self, from: LaneID, to: LaneID, map: &Map) -> bool {
         let steps = self.path.get_steps();
         if steps.len() < 3 {
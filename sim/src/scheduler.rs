@@ -27,6 +27,12 @@ pub(crate) enum Command {
     Pandemic(pandemic::Cmd),
     /// The Time is redundant, just used to dedupe commands
     StartBus(TransitRouteID, Time),
+    /// Periodically record how far queues extend, for analytics
+    MeasureQueues,
+    /// Some time-of-day parking restriction starts or ends
+    UpdateParkingRestrictions,
+    /// Periodically even out bike share bikes between stations
+    RebalanceBikeShare,
 }
 
 impl Command {
@@ -50,6 +56,9 @@ impl Command {
             Command::Callback(_) => CommandType::Callback,
             Command::Pandemic(ref p) => CommandType::Pandemic(p.clone()),
             Command::StartBus(r, t) => CommandType::StartBus(*r, *t),
+            Command::MeasureQueues => CommandType::MeasureQueues,
+            Command::UpdateParkingRestrictions => CommandType::UpdateParkingRestrictions,
+            Command::RebalanceBikeShare => CommandType::RebalanceBikeShare,
         }
     }
 
@@ -65,6 +74,9 @@ impl Command {
             Command::Callback(_) => SimpleCommandType::Callback,
             Command::Pandemic(_) => SimpleCommandType::Pandemic,
             Command::StartBus(_, _) => SimpleCommandType::StartBus,
+            Command::MeasureQueues => SimpleCommandType::MeasureQueues,
+            Command::UpdateParkingRestrictions => SimpleCommandType::UpdateParkingRestrictions,
+            Command::RebalanceBikeShare => SimpleCommandType::RebalanceBikeShare,
         }
     }
 }
@@ -81,6 +93,9 @@ enum CommandType {
     Callback,
     Pandemic(pandemic::Cmd),
     StartBus(TransitRouteID, Time),
+    MeasureQueues,
+    UpdateParkingRestrictions,
+    RebalanceBikeShare,
 }
 
 /// A more compressed form of CommandType, just used for keeping stats on event processing.
@@ -94,6 +109,9 @@ enum SimpleCommandType {
     Callback,
     Pandemic,
     StartBus,
+    MeasureQueues,
+    UpdateParkingRestrictions,
+    RebalanceBikeShare,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -0,0 +1,122 @@
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use instant::Instant;
+
+use geom::Duration;
+use map_model::Map;
+
+use crate::Sim;
+
+/// Advances a simulation on another thread, so a UI can stay responsive while the simulation runs
+/// quickly. While it's running, nothing else should touch the simulation. Instead, the caller
+/// periodically receives an immutable snapshot -- a copy of the whole `Sim` -- to draw and query.
+/// Stopping hands back the real simulation.
+///
+/// Callbacks aren't supported. Alerts raised while running move into the next snapshot, so each
+/// one is only seen once.
+pub struct BackgroundSim {
+    requests: Sender<Request>,
+    latest: Arc<Mutex<Option<Sim>>>,
+    thread: Option<JoinHandle<Sim>>,
+}
+
+enum Request {
+    SetSpeed(f64),
+    Stop,
+}
+
+impl BackgroundSim {
+    /// Start advancing `sim` at `speed` times realtime, producing a snapshot about every
+    /// `snapshot_interval`. The thread needs its own copy of the map, which is expensive to make,
+    /// so prefer changing the speed over restarting.
+    pub fn start(sim: Sim, map: Map, speed: f64, snapshot_interval: Duration) -> BackgroundSim {
+        let (requests, rx) = channel();
+        let latest = Arc::new(Mutex::new(None));
+        let latest_for_thread = latest.clone();
+        let thread = std::thread::spawn(move || {
+            run(sim, map, speed, snapshot_interval, rx, latest_for_thread)
+        });
+        BackgroundSim {
+            requests,
+            latest,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn set_speed(&self, speed: f64) {
+        // If the thread crashed, stop() will report it
+        let _ = self.requests.send(Request::SetSpeed(speed));
+    }
+
+    /// Returns the newest snapshot, if one has been made since the last call.
+    pub fn take_snapshot(&self) -> Option<Sim> {
+        self.latest.lock().unwrap().take()
+    }
+
+    /// Stop stepping and return the simulation. It's likely a bit ahead of the last snapshot.
+    pub fn stop(mut self) -> Sim {
+        let _ = self.requests.send(Request::Stop);
+        self.thread
+            .take()
+            .unwrap()
+            .join()
+            .expect("background simulation crashed")
+    }
+}
+
+fn run(
+    mut sim: Sim,
+    map: Map,
+    mut speed: f64,
+    snapshot_interval: Duration,
+    requests: Receiver<Request>,
+    latest: Arc<Mutex<Option<Sim>>>,
+) -> Sim {
+    let mut last_step = Instant::now();
+    let mut last_snapshot = Instant::now();
+    loop {
+        loop {
+            match requests.try_recv() {
+                Ok(Request::SetSpeed(x)) => {
+                    speed = x;
+                }
+                // If the BackgroundSim was dropped without stopping, nobody's waiting for the
+                // result
+                Ok(Request::Stop) | Err(TryRecvError::Disconnected) => {
+                    return sim;
+                }
+                Err(TryRecvError::Empty) => {
+                    break;
+                }
+            }
+        }
+
+        let end_time = sim.time() + speed * Duration::realtime_elapsed(last_step);
+        last_step = Instant::now();
+        sim.time_limited_step(&map, end_time - sim.time(), snapshot_interval, &mut None);
+
+        // Don't make the UI wait to hear about alerts
+        if !sim.analytics.alerts.is_empty()
+            || Duration::realtime_elapsed(last_snapshot) >= snapshot_interval
+        {
+            let mut snapshot = sim.clone();
+            sim.clear_alerts();
+            let mut latest = latest.lock().unwrap();
+            // If the UI hasn't taken the previous snapshot yet, its alerts haven't been seen
+            if let Some(old) = latest.take() {
+                let mut alerts = old.analytics.alerts;
+                alerts.append(&mut snapshot.analytics.alerts);
+                snapshot.analytics.alerts = alerts;
+            }
+            *latest = Some(snapshot);
+            last_snapshot = Instant::now();
+        }
+
+        // Caught up, so don't spin
+        if sim.time() >= end_time {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+}
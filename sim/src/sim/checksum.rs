@@ -0,0 +1,49 @@
+use abstutil::Timer;
+use geom::{Duration, Time};
+use map_model::Map;
+
+use crate::{Event, Sim};
+
+/// Incrementally hashes every event the simulation produces, in order. The simulation promises to
+/// be deterministic, so given the same map, scenario, and RNG seed, the final checksum must match
+/// across runs, platforms, and the number of threads used to instantiate the scenario.
+///
+/// This uses FNV-1a over the Debug representation of each event, rather than std's Hasher, whose
+/// algorithm isn't guaranteed to stay the same across Rust releases.
+#[derive(Clone)]
+pub(crate) struct EventChecksum {
+    hash: u64,
+    num_events: usize,
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+impl EventChecksum {
+    pub fn new() -> EventChecksum {
+        EventChecksum {
+            hash: FNV_OFFSET_BASIS,
+            num_events: 0,
+        }
+    }
+
+    pub fn record(&mut self, time: Time, ev: &Event) {
+        for byte in format!("{} {:?}\n", time.inner_seconds(), ev).bytes() {
+            self.hash ^= u64::from(byte);
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+        self.num_events += 1;
+    }
+}
+
+impl Sim {
+    /// Run the simulation for some duration, returning a canonical hash of every event that
+    /// happened in the meantime and the number of events. Use this to verify refactors of the
+    /// simulation don't change its behavior, and that the results are deterministic.
+    pub fn run_and_checksum(&mut self, map: &Map, dt: Duration, timer: &mut Timer) -> (u64, usize) {
+        self.event_checksum = Some(EventChecksum::new());
+        self.timed_step(map, dt, &mut None, timer);
+        let checksum = self.event_checksum.take().unwrap();
+        (checksum.hash, checksum.num_events)
+    }
+}
@@ -16,24 +16,43 @@ use map_model::{
     BuildingID, IntersectionID, LaneID, Map, ParkingLotID, Path, PathConstraints, PathRequest,
     Position, TransitRoute, Traversable,
 };
-use synthpop::OrigPersonID;
+use synthpop::{OrigPersonID, ScenarioModifier};
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::background::BackgroundSim;
+use self::checksum::EventChecksum;
+pub use self::profile::{ProfileBucket, SimProfile, Subsystem};
 pub use self::queries::{AgentProperties, DelayCause};
+pub use self::savestates::SavestateHistory;
 // TODO Super weird for both of these to wind up here
 pub use self::scenario::{count_parked_cars_per_bldg, rand_dist};
+use self::subscriptions::Subscriptions;
+pub use self::subscriptions::{EventFilter, SubscriptionID};
+use self::weather::parse_weather;
+pub use self::weather::Weather;
 use crate::{
-    AgentID, AlertLocation, Analytics, CarID, Command, CreateCar, DrivingSimState, Event,
-    IntersectionSimState, PandemicModel, ParkedCar, ParkingSim, ParkingSimState, ParkingSpot,
-    Person, PersonID, Router, Scheduler, SidewalkPOI, SidewalkSpot, StartTripArgs, TrafficRecorder,
-    TransitSimState, TripID, TripInfo, TripManager, TripPhaseType, Vehicle, VehicleSpec,
-    VehicleType, WalkingSimState, BUS_LENGTH, LIGHT_RAIL_LENGTH, MIN_CAR_LENGTH,
+    AgentID, AlertLocation, Analytics, BikeShareState, BikeShareSystem, CarID, Command, CreateCar,
+    DrivingSimState, Event, IntersectionSimState, PandemicModel, ParkedCar, ParkingSim,
+    ParkingSimState, ParkingSpot, Person, PersonID, Router, Scheduler, SidewalkPOI, SidewalkSpot,
+    StartTripArgs, TrafficRecorder, TransitSimState, TripID, TripInfo, TripManager, TripPhaseType,
+    Vehicle, VehicleSpec, VehicleType, WalkingSimState, BUS_LENGTH, LIGHT_RAIL_LENGTH,
+    MIN_CAR_LENGTH,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+mod background;
+mod checksum;
+mod profile;
 mod queries;
+mod savestates;
 mod scenario;
+mod subscriptions;
+mod weather;
 
 // TODO Do something else.
 const BLIND_RETRY_TO_SPAWN: Duration = Duration::const_seconds(5.0);
+/// How often to record queue extents for analytics
+const MEASURE_QUEUES_FREQUENCY: Duration = Duration::const_seconds(60.0);
 
 /// The Sim ties together all the pieces of the simulation. Its main property is the current time.
 #[derive(Serialize, Deserialize, Clone)]
@@ -44,6 +63,7 @@ pub struct Sim {
     intersections: IntersectionSimState,
     transit: TransitSimState,
     trips: TripManager,
+    bike_share: BikeShareState,
     #[serde(skip_serializing, skip_deserializing)]
     pandemic: Option<PandemicModel>,
     scheduler: Scheduler,
@@ -57,6 +77,7 @@ pub struct Sim {
     run_name: String,
     step_count: usize,
     highlighted_people: Option<BTreeSet<PersonID>>,
+    pub(crate) estimate_emissions: bool,
 
     analytics: Analytics,
     // This is created interactively, and there's no reason to preserve one for savestates.
@@ -65,12 +86,25 @@ pub struct Sim {
 
     #[serde(skip_serializing, skip_deserializing)]
     alerts: AlertHandler,
+
+    // Only present while verifying determinism
+    #[serde(skip_serializing, skip_deserializing)]
+    event_checksum: Option<EventChecksum>,
+
+    // Embedders subscribe again after loading
+    #[serde(skip_serializing, skip_deserializing)]
+    subscriptions: Subscriptions,
+
+    // Timing only matters for this run
+    #[serde(skip_serializing, skip_deserializing)]
+    profile: SimProfile,
 }
 
 pub(crate) struct Ctx<'a> {
     pub parking: &'a mut ParkingSimState,
     pub intersections: &'a mut IntersectionSimState,
     pub scheduler: &'a mut Scheduler,
+    pub bike_share: &'a mut BikeShareState,
     pub map: &'a Map,
     /// If present, live map edits are being processed, and the agents specified are in the process
     /// of being deleted. Some regular work should maybe be skipped.
@@ -78,7 +112,7 @@ pub(crate) struct Ctx<'a> {
 }
 
 /// Options controlling the traffic simulation.
-#[derive(Clone, StructOpt)]
+#[derive(Clone, StructOpt, Serialize, Deserialize)]
 pub struct SimOptions {
     /// Used to distinguish savestates for running the same scenario.
     #[structopt(long, default_value = "unnamed")]
@@ -96,6 +130,16 @@ pub struct SimOptions {
     /// based on some score of "least-loaded" lane. Disable this default behavior.
     #[structopt(long)]
     pub dont_recalc_lanechanging: bool,
+    /// Let vehicles stuck in a queue change to an adjacent lane in the middle of a road, when that
+    /// lane also leads to their next turn and has fewer vehicles waiting. This positions vehicles
+    /// before reaching the intersection, producing weaving on multi-lane roads.
+    #[structopt(long)]
+    pub mid_block_lanechanging: bool,
+    /// Let pedestrians heading to the other side of the same road cross mid-block, instead of
+    /// walking to the corner and back. They only do this on roads narrow and quiet enough for a
+    /// gap in traffic.
+    #[structopt(long)]
+    pub allow_jaywalking: bool,
     /// Normally if a cycle of vehicles depending on each other to turn is detected, temporarily allow
     /// "blocking the box" to try to break gridlock. Disable this default behavior.
     #[structopt(long)]
@@ -105,12 +149,28 @@ pub struct SimOptions {
     /// red lights after starting.
     #[structopt(long)]
     pub dont_handle_uber_turns: bool,
+    /// Enable experimental merge modeling. Vehicles on highway on-ramps merge without stopping,
+    /// and under congestion, vehicles from different roads take turns merging into the same
+    /// lane.
+    #[structopt(long)]
+    pub model_merges: bool,
+    /// Normally a light rail vehicle arriving at a red traffic signal switches the signal to a
+    /// stage serving it, and doesn't stop at stop signs. Disable this default behavior.
+    #[structopt(long)]
+    pub dont_prioritize_rail: bool,
+    /// Enable experimental acceleration modeling. Vehicles take time to speed up after stopping
+    /// and to brake for stops, with trucks, buses, and trains slower than cars. Otherwise,
+    /// vehicles instantly change speed.
+    #[structopt(long)]
+    pub model_acceleration: bool,
     /// Enable an experimental SEIR pandemic model. This requires an RNG seed, which can be the
     /// same or different from the one used for the rest of the simulation.
     #[structopt(long, parse(try_from_str = parse_rng))]
+    #[serde(skip)]
     pub enable_pandemic_model: Option<XorShiftRng>,
     /// When a warning is encountered during simulation, specifies how to respond.
     #[structopt(long, parse(try_from_str = parse_alert_handler), default_value = "print")]
+    #[serde(skip)]
     pub alerts: AlertHandler,
     /// Ignore parking data in the map and instead treat every building as if it has unlimited
     /// capacity for vehicles.
@@ -127,6 +187,25 @@ pub struct SimOptions {
     /// quickly.
     #[structopt(long)]
     pub skip_analytics: bool,
+    /// Record every lane and turn each agent enters, so the UI can chart an agent's progress.
+    /// This grows with every step of every trip, so it's off by default.
+    #[structopt(long)]
+    pub record_agent_progress: bool,
+    /// Simulate adverse conditions, which slow everybody down and make vehicles keep a larger
+    /// distance from each other. Must be clear|rain|snow|ice.
+    #[structopt(long, parse(try_from_str = parse_weather), default_value = "clear")]
+    pub weather: Weather,
+    /// When starting a scenario, change the mode of some walking and biking trips to transit,
+    /// depending on the weather.
+    #[structopt(long)]
+    pub weather_mode_shift: bool,
+    /// A JSON file describing a bike share system. Biking trips marked to use bike share pick up
+    /// bikes from its stations.
+    #[structopt(long, parse(try_from_str = parse_bike_share))]
+    pub bike_share: Option<BikeShareSystem>,
+    /// Estimate CO2 emissions from the distance cars drive, and include them in summaries.
+    #[structopt(long)]
+    pub estimate_emissions: bool,
 }
 
 impl SimOptions {
@@ -136,13 +215,33 @@ impl SimOptions {
             use_freeform_policy_everywhere: false,
             allow_block_the_box: false,
             dont_recalc_lanechanging: false,
+            mid_block_lanechanging: false,
+            allow_jaywalking: false,
             dont_break_turn_conflict_cycles: false,
             dont_handle_uber_turns: false,
+            model_merges: false,
+            dont_prioritize_rail: false,
+            model_acceleration: false,
             enable_pandemic_model: None,
             alerts: AlertHandler::Print,
             infinite_parking: false,
             disable_turn_conflicts: false,
             skip_analytics: false,
+            record_agent_progress: false,
+            weather: Weather::Clear,
+            weather_mode_shift: false,
+            bike_share: None,
+            estimate_emissions: false,
+        }
+    }
+
+    /// Scenario modifiers that should be applied before instantiating a scenario, to model the
+    /// weather's effect on mode choice.
+    pub fn weather_modifiers(&self) -> Vec<ScenarioModifier> {
+        if self.weather_mode_shift {
+            self.weather.mode_shift()
+        } else {
+            Vec::new()
         }
     }
 }
@@ -158,6 +257,10 @@ fn parse_rng(x: &str) -> Result<XorShiftRng> {
     Ok(XorShiftRng::seed_from_u64(seed))
 }
 
+fn parse_bike_share(path: &str) -> Result<BikeShareSystem> {
+    abstio::maybe_read_json(path.to_string(), &mut Timer::throwaway())
+}
+
 #[derive(Clone)]
 pub enum AlertHandler {
     /// Just print the alert to STDOUT
@@ -168,6 +271,17 @@ pub enum AlertHandler {
     Silence,
 }
 
+fn print_alert(time: Time, loc: &AlertLocation, msg: &str) {
+    if abstutil::logger::json_logs_enabled() {
+        let mut fields = serde_json::Map::new();
+        fields.insert("sim_time".to_string(), time.to_string().into());
+        fields.insert("location".to_string(), format!("{:?}", loc).into());
+        abstutil::logger::print_json_line("alert", msg, fields);
+    } else {
+        println!("Alert at {} ({:?}): {}", time, loc, msg);
+    }
+}
+
 impl Default for AlertHandler {
     fn default() -> AlertHandler {
         AlertHandler::Print
@@ -206,13 +320,28 @@ impl Sim {
             opts.allow_block_the_box = true;
         }
 
+        if !opts.skip_analytics {
+            scheduler.push(
+                Time::START_OF_DAY + MEASURE_QUEUES_FREQUENCY,
+                Command::MeasureQueues,
+            );
+        }
+
+        let mut parking = ParkingSimState::new(map, opts.infinite_parking, &mut timer);
+        if let Some(t) = parking.update_restrictions(Time::START_OF_DAY, map) {
+            scheduler.push(t, Command::UpdateParkingRestrictions);
+        }
+
+        let bike_share = BikeShareState::new(opts.bike_share.clone(), &mut scheduler);
+
         Sim {
             driving: DrivingSimState::new(map, &opts),
-            parking: ParkingSimState::new(map, opts.infinite_parking, &mut timer),
-            walking: WalkingSimState::new(),
+            parking,
+            walking: WalkingSimState::new(&opts),
             intersections: IntersectionSimState::new(map, &mut scheduler, &opts),
             transit: TransitSimState::new(map),
             trips: TripManager::new(),
+            bike_share,
             pandemic: opts.enable_pandemic_model.map(PandemicModel::new),
             scheduler,
             time: Time::START_OF_DAY,
@@ -222,10 +351,14 @@ impl Sim {
             run_name: opts.run_name,
             step_count: 0,
             highlighted_people: None,
+            estimate_emissions: opts.estimate_emissions,
             alerts: opts.alerts,
 
-            analytics: Analytics::new(!opts.skip_analytics),
+            analytics: Analytics::new(!opts.skip_analytics, opts.record_agent_progress),
             recorder: None,
+            event_checksum: None,
+            subscriptions: Subscriptions::default(),
+            profile: SimProfile::default(),
         }
     }
 
@@ -249,10 +382,6 @@ impl Sim {
             }
         }
 
-        if let Some(ref mut m) = self.pandemic {
-            m.initialize(self.trips.get_all_people(), &mut self.scheduler);
-        }
-
         self.dispatch_events(Vec::new(), map);
     }
 
@@ -268,7 +397,7 @@ impl Sim {
         self.parking.get_free_lot_spots(pl)
     }
 
-    /// (Filled, available)
+    /// (Filled, available). Empty spots closed by a time-of-day restriction are in neither.
     pub fn get_all_parking_spots(&self) -> (Vec<ParkingSpot>, Vec<ParkingSpot>) {
         self.parking.get_all_parking_spots()
     }
@@ -300,9 +429,13 @@ impl Sim {
         {
             *spot
         } else {
-            let (_, spot, _) =
-                self.parking
-                    .path_to_free_parking_spot(driving_lane, &vehicle, b, map)?;
+            let (_, spot, _) = self.parking.path_to_free_parking_spot(
+                driving_lane,
+                &vehicle,
+                b,
+                self.time,
+                map,
+            )?;
             spot
         };
 
@@ -319,7 +452,14 @@ impl Sim {
     ) -> &Person {
         self.trips.new_person(orig_id, ped_speed, vehicle_specs)
     }
-    pub(crate) fn seed_parked_car(&mut self, vehicle: Vehicle, spot: ParkingSpot) {
+    /// The car belongs to the home building, which matters for resident permit parking.
+    pub(crate) fn seed_parked_car(
+        &mut self,
+        vehicle: Vehicle,
+        spot: ParkingSpot,
+        home: BuildingID,
+    ) {
+        self.parking.set_home(vehicle.id, home);
         self.parking.reserve_spot(spot, vehicle.id);
         self.parking.add_parked_car(ParkedCar {
             vehicle,
@@ -439,10 +579,13 @@ impl Sim {
             parking: &mut self.parking,
             intersections: &mut self.intersections,
             scheduler: &mut self.scheduler,
+            bike_share: &mut self.bike_share,
             map,
             handling_live_edits: None,
         };
 
+        let started = Instant::now();
+        let subsystem = Subsystem::for_command(&cmd);
         match cmd {
             Command::StartTrip(id, args) => {
                 self.trips.start_trip(self.time, id, args, &mut ctx);
@@ -612,6 +755,7 @@ impl Sim {
                     &mut ctx,
                     &mut self.trips,
                     &mut self.transit,
+                    &self.driving,
                 );
             }
             Command::UpdateIntersection(i) => {
@@ -634,10 +778,29 @@ impl Sim {
             Command::StartBus(r, _) => {
                 self.start_bus(map.get_tr(r), map);
             }
+            Command::MeasureQueues => {
+                self.scheduler
+                    .push(self.time + MEASURE_QUEUES_FREQUENCY, Command::MeasureQueues);
+                let extents = self.driving.get_queue_extents(self.time);
+                self.analytics
+                    .record_queue_extents(self.time, &extents, map);
+            }
+            Command::UpdateParkingRestrictions => {
+                if let Some(t) = self.parking.update_restrictions(self.time, map) {
+                    self.scheduler.push(t, Command::UpdateParkingRestrictions);
+                }
+            }
+            Command::RebalanceBikeShare => {
+                self.bike_share.rebalance(self.time, &mut self.scheduler);
+            }
         }
 
+        self.profile.record(subsystem, started);
+
         // Record events at precisely the time they occur.
+        let started = Instant::now();
         self.dispatch_events(events, map);
+        self.profile.record(Subsystem::Analytics, started);
 
         halt
     }
@@ -656,6 +819,10 @@ impl Sim {
             if let Some(ref mut r) = self.recorder {
                 r.handle_event(self.time, &ev, map, &self.driving);
             }
+            if let Some(ref mut c) = self.event_checksum {
+                c.record(self.time, &ev);
+            }
+            self.subscriptions.handle_event(self.time, &ev);
 
             self.analytics.event(ev, self.time, map);
         }
@@ -681,12 +848,12 @@ impl Sim {
                 match self.alerts {
                     AlertHandler::Print => {
                         for (t, loc, msg) in self.analytics.alerts.drain(..) {
-                            println!("Alert at {} ({:?}): {}", t, loc, msg);
+                            print_alert(t, &loc, &msg);
                         }
                     }
                     AlertHandler::Block => {
                         for (t, loc, msg) in &self.analytics.alerts {
-                            println!("Alert at {} ({:?}): {}", t, loc, msg);
+                            print_alert(*t, loc, msg);
                         }
                         break;
                     }
@@ -697,17 +864,50 @@ impl Sim {
             }
             if Duration::realtime_elapsed(last_update) >= Duration::seconds(1.0) {
                 // TODO Not timer?
-                println!(
-                    "- After {}, the sim is at {}. {} live agents",
-                    Duration::realtime_elapsed(start),
-                    self.time,
-                    prettyprint_usize(self.num_active_agents()),
-                );
+                self.print_progress(Duration::realtime_elapsed(start), map);
                 last_update = Instant::now();
             }
         }
         timer.stop(format!("Advance sim to {}", end_time));
     }
+
+    /// Print a one-line summary of how far the simulation has gotten. When
+    /// `abstutil::logger::setup_json` is used, this is a JSON line in the "summary" category,
+    /// including the full `SimKpis` and `SimProfile`.
+    pub fn print_progress(&self, realtime_elapsed: Duration, map: &Map) {
+        if abstutil::logger::json_logs_enabled() {
+            let mut fields = serde_json::Map::new();
+            fields.insert("sim_time".to_string(), self.time.to_string().into());
+            fields.insert(
+                "sim_seconds".to_string(),
+                (self.time - Time::START_OF_DAY).inner_seconds().into(),
+            );
+            fields.insert(
+                "realtime_seconds".to_string(),
+                realtime_elapsed.inner_seconds().into(),
+            );
+            fields.insert(
+                "kpis".to_string(),
+                serde_json::to_value(self.kpis(map)).unwrap(),
+            );
+            fields.insert(
+                "profile".to_string(),
+                serde_json::to_value(&self.profile).unwrap(),
+            );
+            abstutil::logger::print_json_line(
+                "summary",
+                &format!("The sim is at {}", self.time),
+                fields,
+            );
+        } else {
+            println!(
+                "- After {}, the sim is at {}. {} live agents",
+                realtime_elapsed,
+                self.time,
+                prettyprint_usize(self.num_active_agents()),
+            );
+        }
+    }
     pub fn tiny_step(&mut self, map: &Map, maybe_cb: &mut Option<Box<dyn SimCallback>>) {
         self.timed_step(
             map,
@@ -735,12 +935,12 @@ impl Sim {
                 match self.alerts {
                     AlertHandler::Print => {
                         for (t, loc, msg) in self.analytics.alerts.drain(..) {
-                            println!("Alert at {} ({:?}): {}", t, loc, msg);
+                            print_alert(t, &loc, &msg);
                         }
                     }
                     AlertHandler::Block => {
                         for (t, loc, msg) in &self.analytics.alerts {
-                            println!("Alert at {} ({:?}): {}", t, loc, msg);
+                            print_alert(*t, loc, msg);
                         }
                         break;
                     }
@@ -829,6 +1029,42 @@ impl Sim {
     }
 }
 
+// External control of traffic signals
+impl Sim {
+    /// Immediately switch a traffic signal to a different stage.
+    pub fn set_traffic_signal_stage(
+        &mut self,
+        i: IntersectionID,
+        stage: usize,
+        map: &Map,
+    ) -> Result<()> {
+        let signal = map
+            .maybe_get_traffic_signal(i)
+            .ok_or_else(|| anyhow!("{} isn't a traffic signal", i))?;
+        if stage >= signal.stages.len() {
+            bail!("{} only has {} stages", i, signal.stages.len());
+        }
+        self.intersections
+            .set_signal_stage(self.time, i, stage, &mut self.scheduler, map);
+        Ok(())
+    }
+
+    /// When a traffic signal is externally controlled, it holds its current stage until
+    /// `set_traffic_signal_stage` is called, instead of following its timing plan.
+    pub fn set_traffic_signal_external_control(
+        &mut self,
+        i: IntersectionID,
+        enabled: bool,
+        map: &Map,
+    ) -> Result<()> {
+        if map.maybe_get_traffic_signal(i).is_none() {
+            bail!("{} isn't a traffic signal", i);
+        }
+        self.intersections.set_signal_external_control(i, enabled);
+        Ok(())
+    }
+}
+
 // Live edits
 impl Sim {
     pub fn handle_live_edited_traffic_signals(&mut self, map: &Map) {
@@ -852,6 +1088,7 @@ impl Sim {
             parking: &mut self.parking,
             intersections: &mut self.intersections,
             scheduler: &mut self.scheduler,
+            bike_share: &mut self.bike_share,
             map,
             handling_live_edits: Some(affected_agents),
         };
@@ -938,6 +1175,10 @@ impl Sim {
         let num_evicted = {
             let (evicted_cars, cars_parking_in_the_void) =
                 self.parking.handle_live_edits(map, timer);
+            // Edits may have changed restrictions, so recalculate them now and reschedule
+            if let Some(t) = self.parking.update_restrictions(self.time, map) {
+                self.scheduler.update(t, Command::UpdateParkingRestrictions);
+            }
             let num_evicted = evicted_cars.len();
             affected.extend(self.walking.find_trips_to_parking(evicted_cars));
             for car in cars_parking_in_the_void {
@@ -967,6 +1208,7 @@ impl Sim {
                 parking: &mut self.parking,
                 intersections: &mut self.intersections,
                 scheduler: &mut self.scheduler,
+                bike_share: &mut self.bike_share,
                 map,
                 handling_live_edits: None,
             };
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+
+use abstutil::prettyprint_usize;
+use geom::Duration;
+
+use crate::Command;
+
+/// The parts of the simulation that time is spent on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Subsystem {
+    /// Starting trips and placing new agents on the map
+    Spawning,
+    Driving,
+    Walking,
+    Intersections,
+    Transit,
+    Parking,
+    /// Collecting events and recording metrics
+    Analytics,
+    /// Callbacks, the pandemic model, and bike share rebalancing
+    Other,
+}
+
+impl Subsystem {
+    pub fn all() -> Vec<Subsystem> {
+        vec![
+            Subsystem::Spawning,
+            Subsystem::Driving,
+            Subsystem::Walking,
+            Subsystem::Intersections,
+            Subsystem::Transit,
+            Subsystem::Parking,
+            Subsystem::Analytics,
+            Subsystem::Other,
+        ]
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Subsystem::Spawning => "spawning",
+            Subsystem::Driving => "driving",
+            Subsystem::Walking => "walking",
+            Subsystem::Intersections => "intersections",
+            Subsystem::Transit => "transit",
+            Subsystem::Parking => "parking",
+            Subsystem::Analytics => "analytics",
+            Subsystem::Other => "other",
+        }
+    }
+
+    pub(crate) fn for_command(cmd: &Command) -> Subsystem {
+        match cmd {
+            Command::StartTrip(_, _) | Command::SpawnCar(_, _) | Command::SpawnPed(_) => {
+                Subsystem::Spawning
+            }
+            Command::UpdateCar(_) | Command::UpdateLaggyHead(_) => Subsystem::Driving,
+            Command::UpdatePed(_) => Subsystem::Walking,
+            Command::UpdateIntersection(_) => Subsystem::Intersections,
+            Command::StartBus(_, _) => Subsystem::Transit,
+            Command::UpdateParkingRestrictions => Subsystem::Parking,
+            Command::MeasureQueues => Subsystem::Analytics,
+            Command::Callback(_) | Command::Pandemic(_) | Command::RebalanceBikeShare => {
+                Subsystem::Other
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct ProfileBucket {
+    /// How many commands were handled
+    pub calls: usize,
+    /// Real time spent, not simulation time
+    pub realtime: Duration,
+}
+
+/// Measures the real time spent in each part of the simulation, so slowness can be pinned down.
+/// Timing is always on; it only costs a clock read per command.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SimProfile {
+    pub buckets: BTreeMap<Subsystem, ProfileBucket>,
+}
+
+impl SimProfile {
+    pub(crate) fn record(&mut self, subsystem: Subsystem, started: Instant) {
+        let bucket = self.buckets.entry(subsystem).or_default();
+        bucket.calls += 1;
+        bucket.realtime += Duration::realtime_elapsed(started);
+    }
+
+    pub fn total(&self) -> Duration {
+        self.buckets.values().map(|b| b.realtime).sum()
+    }
+
+    /// One line per subsystem, most expensive first
+    pub fn describe(&self) -> Vec<String> {
+        let total = self.total();
+        if total == Duration::ZERO {
+            return vec!["The simulation hasn't run yet".to_string()];
+        }
+        let mut buckets: Vec<(Subsystem, ProfileBucket)> =
+            self.buckets.iter().map(|(k, v)| (*k, *v)).collect();
+        buckets.sort_by_key(|(_, b)| std::cmp::Reverse(b.realtime));
+
+        let mut lines = vec![format!("{} spent simulating", total)];
+        for (subsystem, bucket) in buckets {
+            lines.push(format!(
+                "{}: {} ({:.1}%) over {} commands",
+                subsystem.describe(),
+                bucket.realtime,
+                100.0 * (bucket.realtime / total),
+                prettyprint_usize(bucket.calls)
+            ));
+        }
+        lines
+    }
+}
@@ -7,16 +7,17 @@ use std::collections::{BTreeMap, BTreeSet};
 use abstutil::Counter;
 use geom::{Distance, Duration, PolyLine, Pt2D, Time};
 use map_model::{
-    BuildingID, IntersectionID, Lane, LaneID, Map, Path, Position, TransitRouteID, TransitStopID,
-    Traversable, TurnID,
+    BuildingID, IntersectionID, Lane, LaneID, Map, ParkingLotID, Path, Position, TransitRouteID,
+    TransitStopID, Traversable, TurnID,
 };
 use synthpop::{OrigPersonID, Scenario, TripMode};
 
 use crate::analytics::SlidingWindow;
 use crate::{
-    AgentID, AgentType, Analytics, CarID, CommutersVehiclesCounts, DrawCarInput, DrawPedCrowdInput,
-    DrawPedestrianInput, PandemicModel, ParkedCar, ParkingSim, PedestrianID, Person, PersonID,
-    PersonState, Sim, TripEndpoint, TripID, TripInfo, TripResult, UnzoomedAgent, VehicleType,
+    AgentID, AgentType, Analytics, BikeShareStats, BikeShareSystem, CarID, CommutersVehiclesCounts,
+    DrawCarInput, DrawPedCrowdInput, DrawPedestrianInput, PandemicModel, ParkedCar, ParkingSim,
+    PedestrianID, Person, PersonID, PersonState, Sim, SimProfile, TripEndpoint, TripID, TripInfo,
+    TripResult, UnzoomedAgent, VehicleType,
 };
 
 // TODO Many of these just delegate to an inner piece. This is unorganized and hard to maintain.
@@ -394,6 +395,25 @@ impl Sim {
         self.parking.is_infinite()
     }
 
+    pub fn num_free_lot_spots(&self, pl: ParkingLotID) -> usize {
+        self.parking.get_free_lot_spots(pl).len()
+    }
+
+    /// The bike share system this simulation was started with, if any
+    pub fn get_bike_share_system(&self) -> Option<&BikeShareSystem> {
+        self.bike_share.get_system()
+    }
+
+    /// How many shared bikes are waiting at each station, and for free-floating systems, any
+    /// other building where somebody left one
+    pub fn get_bike_share_availability(&self) -> &BTreeMap<BuildingID, usize> {
+        self.bike_share.get_available()
+    }
+
+    pub fn get_bike_share_stats(&self) -> &BikeShareStats {
+        self.bike_share.get_stats()
+    }
+
     pub fn all_waiting_people(&self) -> BTreeMap<PersonID, Duration> {
         let mut delays = BTreeMap::new();
         self.walking.all_waiting_people(self.time, &mut delays);
@@ -401,6 +421,11 @@ impl Sim {
         delays
     }
 
+    /// How much real time each part of the simulation has taken so far
+    pub fn get_profile(&self) -> &SimProfile {
+        &self.profile
+    }
+
     pub fn describe_internal_stats(&self) -> Vec<String> {
         let mut stats = self.scheduler.describe_stats();
         stats.push(String::new());
@@ -412,6 +437,12 @@ impl Sim {
         self.driving.debug_queue_lengths(l)
     }
 
+    /// For every lane with vehicles stopped at its end, how far back from the end the stopped
+    /// queue currently reaches.
+    pub fn get_queue_extents(&self) -> BTreeMap<LaneID, Distance> {
+        self.driving.get_queue_extents(self.time)
+    }
+
     /// Returns the best-case time for a trip in a world with no traffic or intersection delays.
     /// Might fail in some cases where the real trip succeeds, but the single-mode path can't be
     /// found. Assumes the TripID exists.
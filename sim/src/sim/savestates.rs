@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use abstutil::prettyprint_usize;
+use geom::{Duration, Time};
+
+use crate::Sim;
+
+/// Keeps many snapshots of one simulation run in memory, so rewinding is cheap. Every
+/// `keyframe_every`, the entire serialized simulation is stored. In between, every `diff_every`,
+/// only the bytes that changed relative to the previous full snapshot are stored.
+///
+/// Serialized state shifts around as agents come and go, so the diffs are based on
+/// content-defined chunks: the bytes are split wherever a rolling hash hits a certain pattern, and
+/// a chunk that also appears in the full snapshot is stored as a reference to it.
+///
+/// Snapshots of a big simulation take lots of memory, so nothing is recorded until the history is
+/// enabled, and the oldest snapshots are dropped once `max_bytes` is exceeded.
+pub struct SavestateHistory {
+    keyframe_every: Duration,
+    diff_every: Duration,
+    max_bytes: usize,
+    enabled: bool,
+    /// Ordered by time
+    keyframes: Vec<Keyframe>,
+    /// Approximately how much memory all snapshots use
+    bytes_used: usize,
+}
+
+struct Keyframe {
+    time: Time,
+    bytes: Vec<u8>,
+    /// From the hash of a chunk to (start, length) in `bytes`
+    chunks: HashMap<u64, (usize, usize)>,
+    /// Ordered by time, all relative to this keyframe
+    diffs: Vec<Diff>,
+}
+
+struct Diff {
+    time: Time,
+    pieces: Vec<Piece>,
+}
+
+impl Keyframe {
+    fn bytes_used(&self) -> usize {
+        self.bytes.len()
+            + self.chunks.len() * std::mem::size_of::<(u64, (usize, usize))>()
+            + self.diffs.iter().map(|d| d.bytes_used()).sum::<usize>()
+    }
+}
+
+impl Diff {
+    fn bytes_used(&self) -> usize {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Copy(_, _) => std::mem::size_of::<Piece>(),
+                Piece::Literal(literal) => std::mem::size_of::<Piece>() + literal.len(),
+            })
+            .sum()
+    }
+}
+
+enum Piece {
+    /// (start, length) in the keyframe
+    Copy(usize, usize),
+    Literal(Vec<u8>),
+}
+
+// Chunks average about 4KB
+const CHUNK_MASK: u64 = (1 << 12) - 1;
+const MIN_CHUNK: usize = 512;
+const MAX_CHUNK: usize = 64 * 1024;
+
+impl SavestateHistory {
+    /// The history starts disabled.
+    pub fn new(
+        keyframe_every: Duration,
+        diff_every: Duration,
+        max_bytes: usize,
+    ) -> SavestateHistory {
+        assert!(diff_every > Duration::ZERO && keyframe_every >= diff_every);
+        SavestateHistory {
+            keyframe_every,
+            diff_every,
+            max_bytes,
+            enabled: false,
+            keyframes: Vec::new(),
+            bytes_used: 0,
+        }
+    }
+
+    /// Start or stop recording snapshots. Stopping forgets every snapshot.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Forgets every snapshot. Call this when the simulation is replaced by an unrelated one.
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+        self.bytes_used = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// The time of the most recent snapshot
+    pub fn latest_time(&self) -> Option<Time> {
+        let keyframe = self.keyframes.last()?;
+        Some(
+            keyframe
+                .diffs
+                .last()
+                .map(|d| d.time)
+                .unwrap_or(keyframe.time),
+        )
+    }
+
+    /// Call this regularly while the simulation runs. If the history is enabled, it records a
+    /// snapshot if enough time has passed since the last one, returning true if so. If the
+    /// simulation is earlier than the last snapshot, it's assumed to be a different run, and the
+    /// history starts over.
+    pub fn maybe_record(&mut self, sim: &Sim) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if let Some(latest) = self.latest_time() {
+            if sim.time() < latest {
+                self.clear();
+            } else if sim.time() - latest < self.diff_every {
+                return false;
+            }
+        }
+        self.record(sim);
+        true
+    }
+
+    /// Unconditionally record a snapshot of the simulation now, even if the history is disabled.
+    pub fn record(&mut self, sim: &Sim) {
+        self.record_snapshot(sim);
+        self.evict();
+    }
+
+    fn record_snapshot(&mut self, sim: &Sim) {
+        let bytes = abstutil::to_binary(sim);
+        let time = sim.time();
+        let need_keyframe = match self.keyframes.last() {
+            Some(keyframe) => time - keyframe.time >= self.keyframe_every,
+            None => true,
+        };
+        if need_keyframe {
+            let mut chunks = HashMap::new();
+            for (start, len) in split_chunks(&bytes) {
+                chunks
+                    .entry(hash(&bytes[start..start + len]))
+                    .or_insert((start, len));
+            }
+            let keyframe = Keyframe {
+                time,
+                bytes,
+                chunks,
+                diffs: Vec::new(),
+            };
+            self.bytes_used += keyframe.bytes_used();
+            self.keyframes.push(keyframe);
+            return;
+        }
+
+        let keyframe = self.keyframes.last_mut().unwrap();
+        let mut pieces: Vec<Piece> = Vec::new();
+        for (start, len) in split_chunks(&bytes) {
+            let chunk = &bytes[start..start + len];
+            let matching = keyframe
+                .chunks
+                .get(&hash(chunk))
+                .cloned()
+                .filter(|(s, l)| &keyframe.bytes[*s..*s + *l] == chunk);
+            match (matching, pieces.last_mut()) {
+                // Merge adjacent references, since unchanged regions usually span many chunks
+                (Some((s, l)), Some(Piece::Copy(prev_start, prev_len)))
+                    if *prev_start + *prev_len == s =>
+                {
+                    *prev_len += l;
+                }
+                (Some((s, l)), _) => {
+                    pieces.push(Piece::Copy(s, l));
+                }
+                (None, Some(Piece::Literal(prev))) => {
+                    prev.extend_from_slice(chunk);
+                }
+                (None, _) => {
+                    pieces.push(Piece::Literal(chunk.to_vec()));
+                }
+            }
+        }
+        let diff = Diff { time, pieces };
+        self.bytes_used += diff.bytes_used();
+        keyframe.diffs.push(diff);
+    }
+
+    /// Drop the oldest snapshots until they fit in `max_bytes`. The latest full snapshot is always
+    /// kept, but its oldest diffs may go.
+    fn evict(&mut self) {
+        while self.bytes_used > self.max_bytes && self.keyframes.len() > 1 {
+            let keyframe = self.keyframes.remove(0);
+            self.bytes_used -= keyframe.bytes_used();
+        }
+        if let Some(keyframe) = self.keyframes.last_mut() {
+            while self.bytes_used > self.max_bytes && !keyframe.diffs.is_empty() {
+                let diff = keyframe.diffs.remove(0);
+                self.bytes_used -= diff.bytes_used();
+            }
+        }
+    }
+
+    /// Restores the latest snapshot at or before some time, discarding every snapshot after it.
+    /// Returns `None` if there isn't one.
+    pub fn rewind_to(&mut self, time: Time) -> Option<Result<Sim>> {
+        self.keyframes.retain(|k| k.time <= time);
+        if let Some(keyframe) = self.keyframes.last_mut() {
+            keyframe.diffs.retain(|d| d.time <= time);
+        }
+        self.bytes_used = self.keyframes.iter().map(|k| k.bytes_used()).sum();
+        let keyframe = self.keyframes.last()?;
+
+        let bytes = match keyframe.diffs.last() {
+            Some(diff) => {
+                let mut bytes = Vec::new();
+                for piece in &diff.pieces {
+                    match piece {
+                        Piece::Copy(start, len) => {
+                            bytes.extend_from_slice(&keyframe.bytes[*start..*start + *len]);
+                        }
+                        Piece::Literal(literal) => {
+                            bytes.extend_from_slice(literal);
+                        }
+                    }
+                }
+                bytes
+            }
+            None => keyframe.bytes.clone(),
+        };
+        Some(abstutil::from_binary(&bytes))
+    }
+
+    /// Summarizes how many snapshots there are and how much memory they use.
+    pub fn describe(&self) -> String {
+        let mut num_diffs = 0;
+        let mut keyframe_bytes = 0;
+        let mut diff_bytes = 0;
+        for keyframe in &self.keyframes {
+            keyframe_bytes += keyframe.bytes.len();
+            num_diffs += keyframe.diffs.len();
+            for diff in &keyframe.diffs {
+                for piece in &diff.pieces {
+                    diff_bytes += match piece {
+                        Piece::Copy(_, _) => std::mem::size_of::<Piece>(),
+                        Piece::Literal(literal) => literal.len(),
+                    };
+                }
+            }
+        }
+        format!(
+            "{} full snapshots ({} bytes) and {} diffs ({} bytes), out of a {} byte limit",
+            prettyprint_usize(self.keyframes.len()),
+            prettyprint_usize(keyframe_bytes),
+            prettyprint_usize(num_diffs),
+            prettyprint_usize(diff_bytes),
+            prettyprint_usize(self.max_bytes)
+        )
+    }
+}
+
+/// Returns (start, length) of content-defined chunks covering all of the bytes.
+fn split_chunks(bytes: &[u8]) -> Vec<(usize, usize)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut rolling: u64 = 0;
+    for (idx, byte) in bytes.iter().enumerate() {
+        // A "gear" hash: older bytes shift out after 64 steps
+        rolling = (rolling << 1).wrapping_add(GEAR[*byte as usize]);
+        let len = idx + 1 - start;
+        if (len >= MIN_CHUNK && rolling & CHUNK_MASK == 0) || len >= MAX_CHUNK {
+            chunks.push((start, len));
+            start = idx + 1;
+        }
+    }
+    if start < bytes.len() {
+        chunks.push((start, bytes.len() - start));
+    }
+    chunks
+}
+
+// FNV-1a, like the event checksum. Matches are verified byte-by-byte, so collisions only cost
+// space.
+fn hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Pseudo-random values for each byte, generated with splitmix64
+const GEAR: [u64; 256] = make_gear_table();
+
+const fn make_gear_table() -> [u64; 256] {
+    let mut table = [0; 256];
+    let mut state: u64 = 0x9e3779b97f4a7c15;
+    let mut idx = 0;
+    while idx < 256 {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        table[idx] = z ^ (z >> 31);
+        idx += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_cover_everything() {
+        let bytes: Vec<u8> = (0..200_000)
+            .map(|x: usize| (x * 7919 % 251) as u8)
+            .collect();
+        let chunks = split_chunks(&bytes);
+        let mut expected_start = 0;
+        for (start, len) in chunks {
+            assert_eq!(start, expected_start);
+            assert!(len <= MAX_CHUNK);
+            expected_start += len;
+        }
+        assert_eq!(expected_start, bytes.len());
+    }
+}
@@ -1,21 +1,26 @@
 use std::collections::{BTreeMap, HashSet, VecDeque};
 
+use anyhow::Result;
 use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
 
 use abstutil::{prettyprint_usize, Counter, Timer};
-use geom::{Distance, Speed};
+use geom::{Distance, Speed, Time};
 use map_model::{BuildingID, Map, OffstreetParking, RoadID};
-use synthpop::{PersonSpec, Scenario, TripEndpoint, TripMode};
+use synthpop::{IndividTrip, PersonSpec, Scenario, TripEndpoint, TripMode, TripPurpose};
 
 use crate::make::fork_rng;
 use crate::{
-    ParkingSpot, Sim, StartTripArgs, TripInfo, Vehicle, VehicleSpec, VehicleType, BIKE_LENGTH,
-    MAX_CAR_LENGTH, MIN_CAR_LENGTH,
+    ParkingSpot, Sim, StartTripArgs, TripID, TripInfo, Vehicle, VehicleSpec, VehicleType,
+    BIKE_LENGTH, MAX_CAR_LENGTH, MAX_TRUCK_LENGTH, MIN_CAR_LENGTH, MIN_TRUCK_LENGTH, PCT_TRUCKS,
 };
 
 impl Sim {
+    /// Creates everybody in the scenario, parks their cars, and schedules their trips. No paths
+    /// are calculated here; each trip pathfinds when it departs, and each transit route does the
+    /// first time a vehicle starts along it. So the cost of pathfinding is spread through the
+    /// simulation, rather than delaying startup.
     pub fn instantiate(
         &mut self,
         scenario: &Scenario,
@@ -89,6 +94,7 @@ impl Sim {
                     StartTripArgs {
                         retry_if_no_room,
                         use_vehicle: maybe_idx.map(|idx| person.vehicles[idx].id),
+                        bike_share: trip.bike_share,
                     },
                 ));
             }
@@ -99,8 +105,81 @@ impl Sim {
         seed_parked_cars(parked_cars, self, map, rng, timer);
 
         self.spawn_trips(schedule_trips, map, timer);
+        // People scheduled later with schedule_trip aren't part of the pandemic model
+        if let Some(ref mut m) = self.pandemic {
+            m.initialize(self.trips.get_all_people(), &mut self.scheduler);
+        }
         timer.stop(format!("Instantiating {}", scenario.scenario_name));
     }
+
+    /// Create a new person taking one trip, in the middle of a simulation. A car for a driving
+    /// trip starting at a building is parked there first. The trip's progress can be queried like
+    /// any other.
+    pub fn schedule_trip(
+        &mut self,
+        at: Time,
+        origin: TripEndpoint,
+        destination: TripEndpoint,
+        mode: TripMode,
+        map: &Map,
+    ) -> Result<TripID> {
+        if at < self.time() {
+            bail!(
+                "It's {} now, so you can't start a trip at {}",
+                self.time(),
+                at
+            );
+        }
+        let spec = PersonSpec {
+            orig_id: None,
+            trips: vec![IndividTrip::new(
+                at,
+                // The purpose doesn't affect anything in the simulation
+                TripPurpose::PersonalBusiness,
+                origin,
+                destination,
+                mode,
+            )],
+        };
+        spec.check_schedule()?;
+
+        // Seed based on the number of people so far, so the same sequence of calls always gives
+        // the same vehicles.
+        let mut rng = XorShiftRng::seed_from_u64(self.get_all_people().len() as u64);
+        let (vehicle_specs, cars_initially_parked_at, vehicle_foreach_trip) =
+            get_vehicles(&spec, &mut rng);
+        let person = self.new_person(None, rand_ped_speed(&mut rng), vehicle_specs);
+        let person_id = person.id;
+        let use_vehicle = vehicle_foreach_trip[0].map(|idx| person.vehicles[idx].id);
+        let parked_cars = cars_initially_parked_at
+            .into_iter()
+            .map(|(idx, b)| (person.vehicles[idx].clone(), b))
+            .collect();
+        seed_parked_cars(parked_cars, self, map, &mut rng, &mut Timer::throwaway());
+
+        self.spawn_trips(
+            vec![(
+                person_id,
+                TripInfo {
+                    departure: at,
+                    mode,
+                    start: origin,
+                    end: destination,
+                    purpose: TripPurpose::PersonalBusiness,
+                    modified: false,
+                    cancellation_reason: None,
+                },
+                StartTripArgs {
+                    retry_if_no_room: true,
+                    use_vehicle,
+                    bike_share: false,
+                },
+            )],
+            map,
+            &mut Timer::throwaway(),
+        );
+        Ok(self.get_person(person_id).trips[0])
+    }
 }
 
 fn get_vehicles(
@@ -116,12 +195,26 @@ fn get_vehicles(
     let mut vehicle_foreach_trip = Vec::new();
 
     let mut bike_idx = None;
+    let mut park_and_ride_idx = None;
     // For each indexed car, is it parked somewhere, or off-map?
     let mut car_locations: Vec<(usize, Option<BuildingID>)> = Vec::new();
 
     // TODO If the trip is cancelled, this should be affected...
     for trip in &person.trips {
         let use_for_trip = match trip.mode {
+            // One car shared by all park-and-ride trips. It starts parked at the first origin and
+            // afterwards is wherever it was left, usually a park-and-ride lot.
+            TripMode::Transit if trip.park_and_ride => {
+                if park_and_ride_idx.is_none() {
+                    let idx = vehicle_specs.len();
+                    vehicle_specs.push(rand_car(rng));
+                    if let TripEndpoint::Building(b) = trip.origin {
+                        cars_initially_parked_at.push((idx, b));
+                    }
+                    park_and_ride_idx = Some(idx);
+                }
+                park_and_ride_idx
+            }
             TripMode::Walk | TripMode::Transit => None,
             TripMode::Bike => {
                 if bike_idx.is_none() {
@@ -189,7 +282,11 @@ fn get_vehicles(
 }
 
 fn rand_car(rng: &mut XorShiftRng) -> VehicleSpec {
-    let length = rand_dist(rng, MIN_CAR_LENGTH, MAX_CAR_LENGTH);
+    let length = if rng.gen_bool(PCT_TRUCKS) {
+        rand_dist(rng, MIN_TRUCK_LENGTH, MAX_TRUCK_LENGTH)
+    } else {
+        rand_dist(rng, MIN_CAR_LENGTH, MAX_CAR_LENGTH)
+    };
     VehicleSpec {
         vehicle_type: VehicleType::Car,
         length,
@@ -243,7 +340,7 @@ fn seed_parked_cars(
         for (vehicle, b) in parked_cars {
             timer.next();
             if let Some(spot) = sim.get_free_offstreet_spots(b).pop() {
-                sim.seed_parked_car(vehicle, spot);
+                sim.seed_parked_car(vehicle, spot, b);
             } else {
                 blackholed += 1;
             }
@@ -276,13 +373,26 @@ fn seed_parked_cars(
             .or_insert_with(Vec::new)
             .push((spot, restriction));
     }
-    // Changing parking on one road shouldn't affect far-off roads. Fork carefully.
+    // Changing parking on one road shouldn't affect far-off roads. Fork carefully. The RNGs are
+    // forked in order, so shuffling in parallel gives the same result.
+    let mut requests = Vec::new();
     for r in map.all_roads() {
-        let mut tmp_rng = fork_rng(base_rng);
-        if let Some(ref mut spots) = open_spots_per_road.get_mut(&r.id) {
-            spots.shuffle(&mut tmp_rng);
+        let tmp_rng = fork_rng(base_rng);
+        if let Some(spots) = open_spots_per_road.remove(&r.id) {
+            requests.push((r.id, spots, tmp_rng));
         }
     }
+    open_spots_per_road = timer
+        .parallelize(
+            "shuffle parking spots",
+            requests,
+            |(r, mut spots, mut tmp_rng)| {
+                spots.shuffle(&mut tmp_rng);
+                (r, spots)
+            },
+        )
+        .into_iter()
+        .collect();
 
     timer.start_iter("seed parked cars", parked_cars.len());
     let mut ok = true;
@@ -295,7 +405,7 @@ fn seed_parked_cars(
         }
         if let Some(spot) = find_spot_near_building(b, &mut open_spots_per_road, map) {
             seeded += 1;
-            sim.seed_parked_car(vehicle, spot);
+            sim.seed_parked_car(vehicle, spot, b);
         } else {
             warn!(
                 "Not enough room to seed parked cars. Only found spots for {} of {}",
@@ -334,10 +444,16 @@ fn find_spot_near_building(
             {
                 return Some(spots.remove(idx).0);
             }
-            if let Some(idx) = spots
-                .iter()
-                .position(|(_, restriction)| restriction.is_none())
-            {
+            // Onstreet spots in a resident permit zone are only for cars living there
+            let has_permit = map
+                .get_edits()
+                .permit_zone_for_road(r)
+                .map(|zone| zone.allows(Some(b)))
+                .unwrap_or(true);
+            if let Some(idx) = spots.iter().position(|(spot, restriction)| {
+                restriction.is_none()
+                    && (has_permit || !matches!(spot, ParkingSpot::Onstreet(_, _)))
+            }) {
                 return Some(spots.remove(idx).0);
             }
         }
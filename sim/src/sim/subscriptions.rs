@@ -0,0 +1,90 @@
+use geom::{Duration, Time};
+
+use crate::{Event, Sim};
+
+/// Which events a subscriber wants to hear about
+#[derive(Clone, Debug)]
+pub enum EventFilter {
+    /// Every event the simulation produces
+    All,
+    /// A trip starts a new phase, finishes, or is cancelled
+    TripPhases,
+    /// An agent waited at least this long to start a turn
+    IntersectionDelays(Duration),
+    /// A car reaches or leaves a parking spot, or a pedestrian reaches their parked car
+    Parking,
+}
+
+impl EventFilter {
+    pub fn matches(&self, ev: &Event) -> bool {
+        match self {
+            EventFilter::All => true,
+            EventFilter::TripPhases => matches!(
+                ev,
+                Event::TripPhaseStarting(_, _, _, _)
+                    | Event::TripFinished { .. }
+                    | Event::TripCancelled(_, _)
+            ),
+            EventFilter::IntersectionDelays(threshold) => {
+                matches!(ev, Event::IntersectionDelayMeasured(_, _, _, delay) if delay >= threshold)
+            }
+            EventFilter::Parking => matches!(
+                ev,
+                Event::CarReachedParkingSpot(_, _)
+                    | Event::CarLeftParkingSpot(_, _)
+                    | Event::PedReachedParkingSpot(_, _)
+            ),
+        }
+    }
+}
+
+/// Identifies one subscription to simulation events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubscriptionID(usize);
+
+/// Buffers events for code embedding the simulation, until they're drained. Subscriptions aren't
+/// part of savestates.
+#[derive(Clone, Default)]
+pub(crate) struct Subscriptions {
+    next_id: usize,
+    subscribers: Vec<(SubscriptionID, EventFilter, Vec<(Time, Event)>)>,
+}
+
+impl Subscriptions {
+    pub fn handle_event(&mut self, time: Time, ev: &Event) {
+        for (_, filter, queue) in &mut self.subscribers {
+            if filter.matches(ev) {
+                queue.push((time, ev.clone()));
+            }
+        }
+    }
+}
+
+impl Sim {
+    /// Start collecting every event matching the filter. Call `drain_events` regularly to consume
+    /// them, instead of polling `Analytics`.
+    pub fn subscribe(&mut self, filter: EventFilter) -> SubscriptionID {
+        let id = SubscriptionID(self.subscriptions.next_id);
+        self.subscriptions.next_id += 1;
+        self.subscriptions
+            .subscribers
+            .push((id, filter, Vec::new()));
+        id
+    }
+
+    /// Stop collecting events for a subscription, discarding any that weren't drained.
+    pub fn unsubscribe(&mut self, id: SubscriptionID) {
+        self.subscriptions.subscribers.retain(|(x, _, _)| *x != id);
+    }
+
+    /// Returns every event matching the subscription since the last call, in the order they
+    /// happened. Returns nothing for unknown subscriptions.
+    pub fn drain_events(&mut self, id: SubscriptionID) -> Vec<(Time, Event)> {
+        self.subscriptions
+            .subscribers
+            .iter_mut()
+            .find(|(x, _, _)| *x == id)
+            .map(|(_, _, queue)| std::mem::take(queue))
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use geom::{Distance, Duration, Time};
+use synthpop::{ScenarioModifier, TripMode};
+
+use crate::FOLLOWING_DISTANCE;
+
+/// Adverse conditions affecting how everybody moves around.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Weather {
+    Clear,
+    Rain,
+    Snow,
+    Ice,
+}
+
+impl Weather {
+    /// Multiplies the speed that vehicles and pedestrians would otherwise travel at.
+    pub fn speed_factor(self) -> f64 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 0.9,
+            Weather::Snow => 0.75,
+            Weather::Ice => 0.6,
+        }
+    }
+
+    /// How far apart vehicles must be, measured from front of one to the back of the other.
+    pub fn following_distance(self) -> Distance {
+        match self {
+            Weather::Clear => FOLLOWING_DISTANCE,
+            Weather::Rain => FOLLOWING_DISTANCE * 1.5,
+            Weather::Snow => FOLLOWING_DISTANCE * 2.0,
+            Weather::Ice => FOLLOWING_DISTANCE * 3.0,
+        }
+    }
+
+    /// Some people choose a different mode in bad weather. Returns the percent of people walking
+    /// and biking who instead take transit. These are rough guesses, not calibrated against any
+    /// survey data.
+    fn mode_elasticity(self) -> (usize, usize) {
+        match self {
+            Weather::Clear => (0, 0),
+            Weather::Rain => (5, 15),
+            Weather::Snow => (10, 40),
+            Weather::Ice => (20, 60),
+        }
+    }
+
+    /// Express the mode shift caused by this weather as scenario modifiers.
+    pub fn mode_shift(self) -> Vec<ScenarioModifier> {
+        let (pct_walk, pct_bike) = self.mode_elasticity();
+        let mut modifiers = Vec::new();
+        for (pct_ppl, mode) in [(pct_walk, TripMode::Walk), (pct_bike, TripMode::Bike)] {
+            if pct_ppl == 0 {
+                continue;
+            }
+            modifiers.push(ScenarioModifier::ChangeMode {
+                pct_ppl,
+                // Cover scenarios repeated over multiple days too
+                departure_filter: (
+                    Time::START_OF_DAY,
+                    Time::START_OF_DAY + Duration::hours(24 * 7),
+                ),
+                from_modes: vec![mode].into_iter().collect(),
+                to_mode: Some(TripMode::Transit),
+            });
+        }
+        modifiers
+    }
+}
+
+pub(crate) fn parse_weather(x: &str) -> Result<Weather> {
+    match x {
+        "clear" => Ok(Weather::Clear),
+        "rain" => Ok(Weather::Rain),
+        "snow" => Ok(Weather::Snow),
+        "ice" => Ok(Weather::Ice),
+        _ => bail!("Bad --weather={}. Must be clear|rain|snow|ice", x),
+    }
+}
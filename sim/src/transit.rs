@@ -3,7 +3,7 @@ use std::collections::{BTreeMap, BTreeSet};
 use serde::{Deserialize, Serialize};
 
 use abstutil::{deserialize_btreemap, serialize_btreemap};
-use geom::Time;
+use geom::{Distance, Duration, Speed, Time};
 use map_model::{Map, Path, PathRequest, Position, TransitRoute, TransitRouteID, TransitStopID};
 
 use crate::sim::Ctx;
@@ -15,11 +15,17 @@ use crate::{
 // These index stops along a route, not stops along a single sidewalk.
 type StopIdx = usize;
 
+// Only used to roughly compare options for people who miss a connection. Buses spend time at stops
+// and intersections, so this is much slower than the speed limit.
+const ESTIMATED_TRANSIT_SPEED: Speed = Speed::const_meters_per_second(5.0);
+
 #[derive(Serialize, Deserialize, Clone)]
 struct Stop {
     id: TransitStopID,
     driving_pos: Position,
     next_stop: Option<Path>,
+    /// When did a vehicle on this route most recently leave this stop?
+    last_departure: Option<Time>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -30,6 +36,16 @@ struct Route {
     active_vehicles: BTreeSet<CarID>,
 }
 
+impl Route {
+    /// Along the route from one stop to a later one
+    fn dist_between(&self, from: StopIdx, to: StopIdx) -> Distance {
+        self.stops[from..to]
+            .iter()
+            .map(|s| s.next_stop.as_ref().unwrap().total_length())
+            .sum()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 struct Bus {
     car: CarID,
@@ -88,7 +104,8 @@ impl TransitSimState {
         }
     }
 
-    /// Returns the path for the first leg.
+    /// Returns the path for the first leg. The paths between stops are only calculated when the
+    /// first vehicle on the route starts.
     pub fn create_empty_route(&mut self, bus_route: &TransitRoute, map: &Map) -> Path {
         self.routes.entry(bus_route.id).or_insert_with(|| {
             let mut stops = Vec::new();
@@ -99,6 +116,7 @@ impl TransitSimState {
                         id: stop1.id,
                         driving_pos: stop1.driving_pos,
                         next_stop: None,
+                        last_departure: None,
                     });
                     continue;
                 }
@@ -116,6 +134,7 @@ impl TransitSimState {
                             id: stop1.id,
                             driving_pos: stop1.driving_pos,
                             next_stop: Some(path),
+                            last_departure: None,
                         });
                     }
                     Err(err) => {
@@ -269,12 +288,13 @@ impl TransitSimState {
         }
     }
 
-    pub fn bus_departed_from_stop(&mut self, id: CarID, map: &Map) -> Router {
+    pub fn bus_departed_from_stop(&mut self, now: Time, id: CarID, map: &Map) -> Router {
         let mut bus = self.buses.get_mut(&id).unwrap();
         let route = self.routes.get_mut(&bus.route).unwrap();
         match bus.state {
             BusState::DrivingToStop(_) | BusState::DrivingOffMap | BusState::Done => unreachable!(),
             BusState::AtStop(stop_idx) => {
+                route.stops[stop_idx].last_departure = Some(now);
                 let stop = &route.stops[stop_idx];
                 self.events
                     .push(Event::BusDepartedFromStop(id, bus.route, stop.id));
@@ -354,6 +374,73 @@ impl TransitSimState {
         None
     }
 
+    /// Did a vehicle on this route leave the stop at or after some time?
+    pub fn departed_since(&self, route: TransitRouteID, stop: TransitStopID, since: Time) -> bool {
+        self.routes
+            .get(&route)
+            .and_then(|r| r.stops.iter().find(|s| s.id == stop))
+            .and_then(|s| s.last_departure)
+            .map(|t| t >= since)
+            .unwrap_or(false)
+    }
+
+    /// Roughly estimate how long it'd take to wait at stop1 for the next vehicle on this route and
+    /// ride it to stop2, or off the map if that's `None`. Returns `None` if no more vehicles will
+    /// come or the route doesn't go there.
+    pub fn estimate_wait_and_ride(
+        &self,
+        now: Time,
+        route_id: TransitRouteID,
+        stop1: TransitStopID,
+        maybe_stop2: Option<TransitStopID>,
+        map: &Map,
+    ) -> Option<Duration> {
+        let route = self.routes.get(&route_id)?;
+        let idx1 = route.stops.iter().position(|s| s.id == stop1)?;
+        let ride_dist = if let Some(stop2) = maybe_stop2 {
+            let idx2 = idx1 + 1 + route.stops[idx1 + 1..].iter().position(|s| s.id == stop2)?;
+            route.dist_between(idx1, idx2)
+        } else {
+            route.dist_between(idx1, route.stops.len() - 1)
+                + route.end_at_border.as_ref()?.total_length()
+        };
+
+        // The closest vehicle that hasn't passed the stop yet
+        let mut wait_dist: Option<Distance> = None;
+        for bus in &route.active_vehicles {
+            let dist = match self.buses[bus].state {
+                BusState::DrivingToStop(0) => {
+                    route.start.total_length() + route.dist_between(0, idx1)
+                }
+                BusState::DrivingToStop(idx) if idx <= idx1 => {
+                    route.stops[idx - 1]
+                        .next_stop
+                        .as_ref()
+                        .unwrap()
+                        .total_length()
+                        + route.dist_between(idx, idx1)
+                }
+                BusState::AtStop(idx) if idx <= idx1 => route.dist_between(idx, idx1),
+                _ => continue,
+            };
+            wait_dist = Some(wait_dist.map(|d| d.min(dist)).unwrap_or(dist));
+        }
+        let wait = if let Some(dist) = wait_dist {
+            dist / ESTIMATED_TRANSIT_SPEED
+        } else {
+            // Nothing is on the way yet, so wait for the next vehicle to start
+            let next_spawn = map
+                .get_tr(route_id)
+                .spawn_times
+                .iter()
+                .find(|t| **t >= now)?;
+            (*next_spawn - now)
+                + (route.start.total_length() + route.dist_between(0, idx1))
+                    / ESTIMATED_TRANSIT_SPEED
+        };
+        Some(wait + ride_dist / ESTIMATED_TRANSIT_SPEED)
+    }
+
     pub fn collect_events(&mut self) -> Vec<Event> {
         self.events.drain(..).collect()
     }
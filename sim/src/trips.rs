@@ -15,9 +15,9 @@ use synthpop::{
 use crate::sim::Ctx;
 use crate::{
     AgentID, AgentType, AlertLocation, CarID, Command, CreateCar, CreatePedestrian, DrivingGoal,
-    Event, ParkedCar, ParkingSim, ParkingSpot, PedestrianID, PersonID, SidewalkPOI, SidewalkSpot,
-    StartTripArgs, TransitSimState, TripID, TripPhaseType, TripSpec, Vehicle, VehicleSpec,
-    VehicleType, WalkingSimState,
+    Event, MissedConnectionChoice, ParkedCar, ParkingSim, ParkingSpot, PedestrianID, PersonID,
+    SidewalkPOI, SidewalkSpot, StartTripArgs, TransitSimState, TripID, TripPhaseType, TripSpec,
+    Vehicle, VehicleSpec, VehicleType, WalkingSimState,
 };
 
 /// Manages people, each of which executes some trips through the day. Each trip is further broken
@@ -85,6 +85,7 @@ impl TripManager {
             vehicles,
             delayed_trips: Vec::new(),
             on_bus: None,
+            activities: Vec::new(),
         });
         self.get_person(id).unwrap()
     }
@@ -114,6 +115,9 @@ impl TripManager {
                 TripEndpoint::Building(b) => {
                     self.events
                         .push(Event::PersonEntersBuilding(trip.person, b));
+                    // Nothing says what somebody was doing before their first trip, so assume
+                    // they start the day at home.
+                    person.start_activity(Time::START_OF_DAY, b, TripPurpose::Home);
                     PersonState::Inside(b)
                 }
                 TripEndpoint::Border(_) | TripEndpoint::SuddenlyAppear(_) => PersonState::OffMap,
@@ -158,16 +162,27 @@ impl TripManager {
             return;
         }
         self.trips[trip.0].started = true;
+        person.end_activity(now);
 
         let info = &self.trips[trip.0].info;
-        let spec = match TripSpec::maybe_new(
-            info.start,
-            info.end,
-            info.mode,
-            args.use_vehicle,
-            args.retry_if_no_room,
-            ctx.map,
-        ) {
+        let maybe_spec = match (info.mode, args.use_vehicle) {
+            // Only park-and-ride transit trips have a vehicle
+            (TripMode::Transit, Some(car)) => {
+                TripSpec::park_and_ride(info.start, info.end, car, ctx.map, ctx.parking)
+            }
+            (TripMode::Bike, Some(bike)) if args.bike_share => {
+                TripSpec::bike_share(now, info.start, info.end, bike, ctx.map, ctx.bike_share)
+            }
+            _ => TripSpec::maybe_new(
+                info.start,
+                info.end,
+                info.mode,
+                args.use_vehicle,
+                args.retry_if_no_room,
+                ctx.map,
+            ),
+        };
+        let spec = match maybe_spec {
             Ok(spec) => spec,
             Err(error) => TripSpec::SpawningFailure {
                 use_vehicle: args.use_vehicle,
@@ -208,7 +223,7 @@ impl TripManager {
                 );
                 let person = person.id;
 
-                match ctx.map.pathfind(req) {
+                match ctx.map.pathfind_at(req, now) {
                     Ok(path) => {
                         let router = goal.make_router(vehicle.id, path, ctx.map);
                         ctx.scheduler.push(
@@ -232,6 +247,9 @@ impl TripManager {
             }
             TripSpec::UsingParkedCar {
                 car, start_bldg, ..
+            }
+            | TripSpec::DrivingToTransit {
+                car, start_bldg, ..
             } => {
                 assert_eq!(person.state, PersonState::Inside(start_bldg));
                 person.state = PersonState::Trip(trip);
@@ -243,6 +261,11 @@ impl TripManager {
                     let req = PathRequest::walking(start.sidewalk_pos, walking_goal.sidewalk_pos);
                     match ctx.map.pathfind(req) {
                         Ok(path) => {
+                            self.events.push(Event::ParkingWalk {
+                                trip,
+                                distance: path.total_length(),
+                                to_car: true,
+                            });
                             ctx.scheduler.push(
                                 now,
                                 Command::SpawnPed(CreatePedestrian {
@@ -382,7 +405,37 @@ impl TripManager {
                     );
                 }
             }
-            TripSpec::UsingTransit { start, stop1, .. } => {
+            TripSpec::UsingSharedBike {
+                start_bldg, pickup, ..
+            } => {
+                assert_eq!(person.state, PersonState::Inside(start_bldg));
+                person.state = PersonState::Trip(trip);
+
+                let start = SidewalkSpot::building(start_bldg, ctx.map);
+                let walk_to = SidewalkSpot::bike_rack(pickup, ctx.map).unwrap();
+                let req = PathRequest::walking(start.sidewalk_pos, walk_to.sidewalk_pos);
+                match ctx.map.pathfind(req) {
+                    Ok(path) => {
+                        ctx.scheduler.push(
+                            now,
+                            Command::SpawnPed(CreatePedestrian {
+                                id: person.ped,
+                                speed: person.ped_speed,
+                                start,
+                                goal: walk_to,
+                                path,
+                                trip,
+                                person: person.id,
+                            }),
+                        );
+                    }
+                    Err(err) => {
+                        self.cancel_trip(now, trip, err.to_string(), None, ctx);
+                    }
+                }
+            }
+            TripSpec::UsingTransit { start, stop1, .. }
+            | TripSpec::TransitToDriving { start, stop1, .. } => {
                 assert_eq!(
                     person.state,
                     match start.connection {
@@ -466,7 +519,7 @@ impl TripManager {
         trip.total_distance += distance_crossed;
 
         match trip.legs.pop_front() {
-            Some(TripLeg::Drive(c, DrivingGoal::ParkNear(_))) => {
+            Some(TripLeg::Drive(c, DrivingGoal::ParkNear(_) | DrivingGoal::ParkInLot(_))) => {
                 assert_eq!(car, c);
             }
             _ => unreachable!(),
@@ -479,6 +532,11 @@ impl TripManager {
                     trip.legs.pop_front().unwrap();
 
                     self.people[trip.person.0].state = PersonState::Inside(b1);
+                    self.events.push(Event::ParkingWalk {
+                        trip: trip.id,
+                        distance: Distance::ZERO,
+                        to_car: false,
+                    });
                     self.events
                         .push(Event::PersonEntersBuilding(trip.person, b1));
                     let id = trip.id;
@@ -547,7 +605,7 @@ impl TripManager {
 
         let person = trip.person;
         let trip = trip.id;
-        match ctx.map.pathfind(req) {
+        match ctx.map.pathfind_at(req, now) {
             Ok(path) => {
                 let router = drive_to.make_router(parked_car.vehicle.id, path, ctx.map);
                 ctx.scheduler.push(
@@ -616,7 +674,7 @@ impl TripManager {
             ))
         } else {
             ctx.map
-                .pathfind(req)
+                .pathfind_at(req, now)
                 .map(|path| drive_to.make_router(bike, path, ctx.map))
         };
         match maybe_router {
@@ -664,6 +722,7 @@ impl TripManager {
             }
             _ => unreachable!(),
         };
+        ctx.bike_share.end_ride(bike);
 
         let id = trip.id;
         self.spawn_ped(now, id, bike_rack, ctx);
@@ -696,18 +755,21 @@ impl TripManager {
         self.trip_finished(now, id, ctx);
     }
 
-    /// If no route is returned, the pedestrian boarded a bus immediately.
+    /// If no route is returned, the pedestrian boarded a bus immediately, or gave up on transit
+    /// and is walking the rest of the way.
     pub fn ped_reached_bus_stop(
         &mut self,
         now: Time,
         ped: PedestrianID,
         stop: TransitStopID,
+        walking_since: Time,
         blocked_time: Duration,
         distance_crossed: Distance,
         ctx: &mut Ctx,
         transit: &mut TransitSimState,
     ) -> Option<TransitRouteID> {
-        let trip = &mut self.trips[self.active_trip_mode[&AgentID::Pedestrian(ped)].0];
+        let id = self.active_trip_mode[&AgentID::Pedestrian(ped)];
+        let trip = &mut self.trips[id.0];
         trip.total_blocked_time += blocked_time;
         trip.total_distance += distance_crossed;
 
@@ -717,40 +779,131 @@ impl TripManager {
             }
             _ => unreachable!(),
         }
-        match trip.legs[1] {
-            TripLeg::RideBus(route, maybe_stop2) => {
-                self.events.push(Event::TripPhaseStarting(
-                    trip.id,
-                    trip.person,
-                    None,
-                    TripPhaseType::WaitingForBus(route, stop),
-                ));
-                if let Some(bus) = transit.ped_waiting_for_bus(
-                    now,
-                    ped,
-                    trip.id,
-                    trip.person,
-                    stop,
-                    route,
-                    maybe_stop2,
-                    ctx.map,
-                ) {
+        let (mut route, maybe_stop2) = match trip.legs[1] {
+            TripLeg::RideBus(route, maybe_stop2) => (route, maybe_stop2),
+            _ => unreachable!(),
+        };
+
+        // Did the vehicle leave while they were walking here?
+        if transit.departed_since(route, stop, walking_since) {
+            let choice =
+                self.replan_missed_connection(now, id, stop, route, maybe_stop2, ctx.map, transit);
+            let trip = &mut self.trips[id.0];
+            self.events.push(Event::MissedTransitConnection(
+                trip.person,
+                route,
+                stop,
+                choice,
+            ));
+            match choice {
+                MissedConnectionChoice::WaitForNext => {}
+                MissedConnectionChoice::SwitchRoute(new_route) => {
+                    trip.legs[1] = TripLeg::RideBus(new_route, maybe_stop2);
+                    route = new_route;
+                }
+                MissedConnectionChoice::Walk => {
+                    // Skip the ride and head straight for the destination
+                    trip.legs.pop_front();
                     trip.legs.pop_front();
                     self.active_trip_mode
                         .remove(&AgentID::Pedestrian(ped))
                         .unwrap();
-                    self.active_trip_mode
-                        .insert(AgentID::BusPassenger(trip.person, bus), trip.id);
-                    self.people[trip.person.0].on_bus = Some(bus);
-                    None
-                } else {
-                    Some(route)
+                    self.spawn_ped(now, id, SidewalkSpot::bus_stop(stop, ctx.map), ctx);
+                    return None;
                 }
             }
-            _ => unreachable!(),
+        }
+
+        let trip = &mut self.trips[id.0];
+        self.events.push(Event::TripPhaseStarting(
+            trip.id,
+            trip.person,
+            None,
+            TripPhaseType::WaitingForBus(route, stop),
+        ));
+        if let Some(bus) = transit.ped_waiting_for_bus(
+            now,
+            ped,
+            trip.id,
+            trip.person,
+            stop,
+            route,
+            maybe_stop2,
+            ctx.map,
+        ) {
+            trip.legs.pop_front();
+            self.active_trip_mode
+                .remove(&AgentID::Pedestrian(ped))
+                .unwrap();
+            self.active_trip_mode
+                .insert(AgentID::BusPassenger(trip.person, bus), trip.id);
+            self.people[trip.person.0].on_bus = Some(bus);
+            None
+        } else {
+            Some(route)
         }
     }
 
+    /// Someone just missed a vehicle at a stop. Compare waiting for the next one, switching to
+    /// another route serving the same stops, and walking the rest of the way.
+    fn replan_missed_connection(
+        &self,
+        now: Time,
+        id: TripID,
+        stop: TransitStopID,
+        route: TransitRouteID,
+        maybe_stop2: Option<TransitStopID>,
+        map: &Map,
+        transit: &TransitSimState,
+    ) -> MissedConnectionChoice {
+        let trip = &self.trips[id.0];
+
+        // None means nothing else is coming
+        let mut best_ride = transit.estimate_wait_and_ride(now, route, stop, maybe_stop2, map);
+        let mut choice = MissedConnectionChoice::WaitForNext;
+        for other in map.get_routes_serving_stop(stop) {
+            if other.id == route {
+                continue;
+            }
+            if let Some(dt) = transit.estimate_wait_and_ride(now, other.id, stop, maybe_stop2, map)
+            {
+                if best_ride.map(|best| dt < best).unwrap_or(true) {
+                    best_ride = Some(dt);
+                    choice = MissedConnectionChoice::SwitchRoute(other.id);
+                }
+            }
+        }
+
+        // Walking only makes sense if the rest of the trip after the ride is on foot
+        if let (Some(stop2), 3, Some(TripLeg::Walk(goal))) =
+            (maybe_stop2, trip.legs.len(), trip.legs.get(2))
+        {
+            if goal.connection != SidewalkPOI::DeferredParkingSpot {
+                let speed = self.people[trip.person.0].ped_speed;
+                if let (Ok(direct), Ok(after_ride)) = (
+                    map.pathfind(PathRequest::walking(
+                        map.get_ts(stop).sidewalk_pos,
+                        goal.sidewalk_pos,
+                    )),
+                    map.pathfind(PathRequest::walking(
+                        map.get_ts(stop2).sidewalk_pos,
+                        goal.sidewalk_pos,
+                    )),
+                ) {
+                    let walk = direct.total_length() / speed;
+                    if best_ride
+                        .map(|dt| walk < dt + after_ride.total_length() / speed)
+                        .unwrap_or(true)
+                    {
+                        return MissedConnectionChoice::Walk;
+                    }
+                }
+            }
+        }
+
+        choice
+    }
+
     pub fn ped_boarded_bus(
         &mut self,
         now: Time,
@@ -907,7 +1060,13 @@ impl TripManager {
             blocked_time: trip.total_blocked_time,
         });
 
-        let person = trip.person;
+        let purpose = trip.info.purpose;
+        let person = &mut self.people[trip.person.0];
+        if let PersonState::Inside(b) = person.state {
+            person.start_activity(now, b, purpose);
+        }
+
+        let person = person.id;
         self.start_delayed_trip(now, person, ctx);
     }
 
@@ -932,6 +1091,25 @@ impl TripManager {
     fn spawn_ped(&mut self, now: Time, id: TripID, start: SidewalkSpot, ctx: &mut Ctx) {
         let trip = &self.trips[id.0];
         let walk_to = match trip.legs[0] {
+            // Heading back to a car parked for park-and-ride
+            TripLeg::Walk(ref to) if to.connection == SidewalkPOI::DeferredParkingSpot => {
+                let car = match trip.legs[1] {
+                    TripLeg::Drive(c, _) => c,
+                    _ => unreachable!(),
+                };
+                if let Some(spot) = ctx.parking.lookup_parked_car(car).map(|p| p.spot) {
+                    SidewalkSpot::parking_spot(spot, ctx.map, ctx.parking)
+                } else {
+                    self.cancel_trip(
+                        now,
+                        id,
+                        format!("should have {} parked somewhere, but it's unavailable", car),
+                        None,
+                        ctx,
+                    );
+                    return;
+                }
+            }
             TripLeg::Walk(ref to) => to.clone(),
             _ => unreachable!(),
         };
@@ -939,6 +1117,19 @@ impl TripManager {
         let req = PathRequest::walking(start.sidewalk_pos, walk_to.sidewalk_pos);
         match ctx.map.pathfind(req) {
             Ok(path) => {
+                if let SidewalkPOI::ParkingSpot(_) = start.connection {
+                    self.events.push(Event::ParkingWalk {
+                        trip: id,
+                        distance: path.total_length(),
+                        to_car: false,
+                    });
+                } else if let SidewalkPOI::ParkingSpot(_) = walk_to.connection {
+                    self.events.push(Event::ParkingWalk {
+                        trip: id,
+                        distance: path.total_length(),
+                        to_car: true,
+                    });
+                }
                 let person = &self.people[trip.person.0];
                 ctx.scheduler.push(
                     now,
@@ -992,10 +1183,18 @@ impl TripManager {
         if let PersonState::Inside(b) = self.people[person.0].state {
             self.events.push(Event::PersonLeavesBuilding(person, b));
         }
+        self.people[person.0].end_activity(now);
+        // If the person hasn't finished riding a shared bike, leave it where they were headed
+        for leg in &trip.legs {
+            if let TripLeg::Drive(c, _) = leg {
+                ctx.bike_share.end_ride(*c);
+            }
+        }
         // Warp to the destination
         self.people[person.0].state = match trip.info.end {
             TripEndpoint::Building(b) => {
                 self.events.push(Event::PersonEntersBuilding(person, b));
+                self.people[person.0].start_activity(now, b, trip.info.purpose);
                 PersonState::Inside(b)
             }
             TripEndpoint::Border(i) => {
@@ -1026,7 +1225,7 @@ impl TripManager {
                         .map(|(spot, _)| *spot)
                         .or_else(|| {
                             ctx.parking
-                                .path_to_free_parking_spot(driving_lane, &vehicle, b, ctx.map)
+                                .path_to_free_parking_spot(driving_lane, &vehicle, b, now, ctx.map)
                                 .map(|(_, spot, _)| spot)
                         })
                     {
@@ -1397,12 +1596,61 @@ pub struct Person {
 
     delayed_trips: Vec<(TripID, StartTripArgs)>,
     on_bus: Option<CarID>,
+
+    /// Everywhere this person has spent time between trips so far today, in order. Unlike
+    /// `state`, this remembers what they were doing and for how long.
+    #[serde(default)]
+    pub activities: Vec<Activity>,
 }
 
 impl Person {
     fn get_vehicle(&self, id: CarID) -> Vehicle {
         self.vehicles.iter().find(|v| v.id == id).unwrap().clone()
     }
+
+    /// What this person is doing inside a building right now, if anything.
+    pub fn current_activity(&self) -> Option<&Activity> {
+        if !matches!(self.state, PersonState::Inside(_)) {
+            return None;
+        }
+        self.activities.last().filter(|a| a.end.is_none())
+    }
+
+    fn start_activity(&mut self, now: Time, building: BuildingID, purpose: TripPurpose) {
+        self.end_activity(now);
+        self.activities.push(Activity {
+            building,
+            purpose,
+            start: now,
+            end: None,
+        });
+    }
+
+    fn end_activity(&mut self, now: Time) {
+        if let Some(activity) = self.activities.last_mut() {
+            if activity.end.is_none() {
+                activity.end = Some(now);
+            }
+        }
+    }
+}
+
+/// Time that a person spends inside one building, between two trips.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Activity {
+    pub building: BuildingID,
+    /// The purpose of the trip that brought the person here
+    pub purpose: TripPurpose,
+    pub start: Time,
+    /// `None` while the person is still here
+    pub end: Option<Time>,
+}
+
+impl Activity {
+    /// How long the person has been or was here
+    pub fn duration(&self, now: Time) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -0,0 +1,128 @@
+//! When a wildfire, flood, or chemical spill threatens an area, everybody inside has to leave for
+//! somewhere safe within a short time. How long it takes to clear the area depends on the road
+//! network, so this turns a normal day of trips into an evacuation.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use geom::{Duration, Time};
+use map_model::{BuildingID, Map};
+
+use crate::event::low_discrepancy;
+use crate::{IndividTrip, MapBorders, Scenario, TripEndpoint, TripMode, TripPurpose};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug, Serialize, Deserialize)]
+pub struct Evacuation {
+    /// Buildings inside the hazard zone
+    pub zone: BTreeSet<BuildingID>,
+    /// Buildings where evacuees can shelter. Any inside the zone are ignored.
+    pub shelters: BTreeSet<BuildingID>,
+    /// Can evacuees reach safety by leaving the map?
+    pub leave_map: bool,
+    /// When the evacuation is ordered
+    pub start_time: Time,
+    /// Everybody departs within this long after the order
+    pub departure_window: Duration,
+}
+
+impl Evacuation {
+    pub fn describe(&self) -> String {
+        format!(
+            "evacuate {} buildings at {}, everybody leaving within {}",
+            self.zone.len(),
+            self.start_time.ampm_tostring(),
+            self.departure_window
+        )
+    }
+
+    /// Everybody inside the zone when the order is given abandons the rest of their day and
+    /// travels to the closest safe destination, using the mode of their next trip. People
+    /// traveling at that time are assumed to have already reached their destination. Returns the
+    /// number of evacuees.
+    pub fn apply(&self, map: &Map, s: &mut Scenario) -> usize {
+        // (index into people, where they are, index of their first trip after the order)
+        let mut evacuees = Vec::new();
+        for (idx, person) in s.people.iter().enumerate() {
+            let mut location = match person.trips.get(0) {
+                Some(trip) => trip.origin,
+                None => continue,
+            };
+            let mut next_trip = person.trips.len();
+            for (trip_idx, trip) in person.trips.iter().enumerate() {
+                if trip.depart > self.start_time {
+                    next_trip = trip_idx;
+                    break;
+                }
+                location = trip.destination;
+            }
+            if let TripEndpoint::Building(b) = location {
+                if self.zone.contains(&b) {
+                    evacuees.push((idx, b, next_trip));
+                }
+            }
+        }
+
+        let borders = MapBorders::new(map);
+        let num_evacuees = evacuees.len();
+        let mut count = 0;
+        for (rank, (idx, b, next_trip)) in evacuees.into_iter().enumerate() {
+            let person = &mut s.people[idx];
+            let mode = match person.trips.get(next_trip).unwrap_or_else(|| {
+                // They stay put after the order, so use their last trip
+                person.trips.last().unwrap()
+            }) {
+                // Buses can't be counted on during an evacuation
+                trip if trip.mode == TripMode::Transit => TripMode::Walk,
+                trip => trip.mode,
+            };
+
+            let pt = map.get_b(b).polygon.center();
+            let mut destinations: Vec<TripEndpoint> = self
+                .shelters
+                .iter()
+                .filter(|shelter| !self.zone.contains(shelter))
+                .map(|shelter| TripEndpoint::Building(*shelter))
+                .collect();
+            if self.leave_map {
+                destinations.extend(
+                    borders
+                        .for_mode(mode)
+                        .1
+                        .iter()
+                        .map(|border| TripEndpoint::Border(border.i)),
+                );
+            }
+            let destination = if let Some(x) = destinations
+                .into_iter()
+                .min_by_key(|endpoint| endpoint.pt(map).dist_to(pt))
+            {
+                x
+            } else {
+                warn!("Nowhere safe for {} to evacuate to by {:?}", b, mode);
+                continue;
+            };
+
+            // Spread departures evenly through the window, in an order unrelated to where people
+            // are
+            let depart =
+                self.start_time + low_discrepancy(rank, 0.618_033_988_7) * self.departure_window;
+            let mut trip = IndividTrip::new(
+                depart,
+                TripPurpose::Evacuation,
+                TripEndpoint::Building(b),
+                destination,
+                mode,
+            );
+            trip.modified = true;
+            person.trips.truncate(next_trip);
+            person.trips.push(trip);
+            count += 1;
+        }
+        info!(
+            "{} of {} people in the hazard zone evacuate",
+            count, num_evacuees
+        );
+        count
+    }
+}
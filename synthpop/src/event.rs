@@ -0,0 +1,227 @@
+//! Big events like a stadium game or a concert bring a surge of people to one venue. They arrive
+//! in the hour or so before it starts and leave together when it ends, on top of the usual traffic
+//! for that time of day. Testing a traffic management plan for an event needs this demand.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use geom::{Distance, Duration, Time};
+use map_model::{BuildingID, BuildingType, IntersectionID, Map};
+
+use crate::schools::estimate_travel_time;
+use crate::{IndividTrip, MapBorder, MapBorders, PersonSpec, TripEndpoint, TripMode, TripPurpose};
+
+/// Attendees who walk only come from homes this close to the venue.
+const MAX_WALKING_DISTANCE: Distance = Distance::const_meters(2000.0);
+/// Attendees who cycle only come from homes this close to the venue.
+const MAX_BIKING_DISTANCE: Distance = Distance::const_meters(8000.0);
+
+/// Describes one event and how people travel to it.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug, Serialize, Deserialize)]
+pub struct EventSurge {
+    pub venue: BuildingID,
+    pub attendance: usize,
+    pub start_time: Time,
+    pub end_time: Time,
+    /// Attendees arrive during this long before the start
+    pub arrival_window: Duration,
+    /// Attendees leave during this long after the end
+    pub departure_window: Duration,
+    pub arrival_curve: SurgeCurve,
+    pub departure_curve: SurgeCurve,
+    /// The share of attendees using each mode. Shares are relative to their sum, so they don't
+    /// have to add up to 100.
+    pub mode_split: BTreeMap<TripMode, usize>,
+    /// The percent of attendees driving or cycling who come from beyond the map's boundary
+    pub pct_from_outside: usize,
+}
+
+/// How arrivals or departures are spread out over their window
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SurgeCurve {
+    /// The same number of people every minute
+    Even,
+    /// Most people arrive right before the event starts, or leave right after it ends
+    Peaked,
+}
+
+impl SurgeCurve {
+    /// How far into a window does somebody arrive, given their rank among all attendees, from 0 to
+    /// 1? `peak_at_end` is true for arrivals and false for departures.
+    fn offset(self, rank: f64, window: Duration, peak_at_end: bool) -> Duration {
+        let pct = match self {
+            SurgeCurve::Even => rank,
+            // The inverse CDF of a triangular distribution
+            SurgeCurve::Peaked => {
+                if peak_at_end {
+                    rank.sqrt()
+                } else {
+                    1.0 - (1.0 - rank).sqrt()
+                }
+            }
+        };
+        pct * window
+    }
+}
+
+impl EventSurge {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} people attend an event at {} from {} to {}",
+            abstutil::prettyprint_usize(self.attendance),
+            self.venue,
+            self.start_time.ampm_tostring(),
+            self.end_time.ampm_tostring()
+        )
+    }
+
+    /// Generate one person per attendee, traveling from home or the map's edge to the venue and
+    /// back again. This is deterministic, so that results before and after changing the map can
+    /// be compared.
+    pub fn generate(&self, map: &Map) -> Vec<PersonSpec> {
+        let total_share: usize = self.mode_split.values().sum();
+        if total_share == 0 {
+            warn!("Nobody has a way to get to the event at {}", self.venue);
+            return Vec::new();
+        }
+
+        let venue_pt = map.get_b(self.venue).polygon.center();
+        let mut homes: Vec<(Distance, BuildingID)> = map
+            .all_buildings()
+            .iter()
+            .filter(|b| {
+                b.id != self.venue
+                    && matches!(
+                        b.bldg_type,
+                        BuildingType::Residential { .. }
+                            | BuildingType::ResidentialCommercial(_, _)
+                    )
+            })
+            .map(|b| (b.polygon.center().dist_to(venue_pt), b.id))
+            .collect();
+        if homes.is_empty() {
+            warn!(
+                "No homes to send people to the event at {} from",
+                self.venue
+            );
+            return Vec::new();
+        }
+        homes.sort_by_key(|(dist, _)| *dist);
+        let borders = MapBorders::new(map);
+        // Nobody can arrive before midnight
+        let arrival_window = self
+            .arrival_window
+            .min(self.start_time - Time::START_OF_DAY);
+
+        let mut people = Vec::new();
+        for idx in 0..self.attendance {
+            // Spread out each choice evenly, without correlating them with each other or with
+            // arrival time
+            let mode = self.choose_mode(low_discrepancy(idx, 0.618_033_988_7), total_share);
+            let pick = low_discrepancy(idx, 0.414_213_562_4);
+            let from_outside = matches!(mode, TripMode::Drive | TripMode::Bike)
+                && low_discrepancy(idx, 0.754_877_666_2) * 100.0 < self.pct_from_outside as f64;
+
+            let (origin, destination) = match (from_outside, borders.for_mode(mode)) {
+                (true, (incoming, outgoing)) if !incoming.is_empty() && !outgoing.is_empty() => {
+                    let from = pick_border(incoming, pick);
+                    // Leave the same way if possible
+                    let to = if outgoing.iter().any(|b| b.i == from) {
+                        from
+                    } else {
+                        pick_border(outgoing, pick)
+                    };
+                    (TripEndpoint::Border(from), TripEndpoint::Border(to))
+                }
+                _ => {
+                    let max_dist = match mode {
+                        TripMode::Walk => Some(MAX_WALKING_DISTANCE),
+                        TripMode::Bike => Some(MAX_BIKING_DISTANCE),
+                        TripMode::Transit | TripMode::Drive => None,
+                    };
+                    // Homes are sorted by distance. Always allow the closest one, even if it's too
+                    // far.
+                    let num = match max_dist {
+                        Some(max) => homes.partition_point(|(dist, _)| *dist <= max).max(1),
+                        None => homes.len(),
+                    };
+                    let home = homes[((pick * num as f64) as usize).min(num - 1)].1;
+                    (TripEndpoint::Building(home), TripEndpoint::Building(home))
+                }
+            };
+
+            // Ranks evenly spaced between 0 and 1
+            let rank = (idx as f64 + 0.5) / self.attendance as f64;
+            let arrive = self.start_time - arrival_window
+                + self.arrival_curve.offset(rank, arrival_window, true);
+            let travel_time = estimate_travel_time(mode, origin.pt(map).dist_to(venue_pt));
+            let depart = if arrive - Time::START_OF_DAY > travel_time {
+                arrive - travel_time
+            } else {
+                Time::START_OF_DAY
+            };
+            let leave = self.end_time
+                + self
+                    .departure_curve
+                    .offset(rank, self.departure_window, false);
+
+            people.push(PersonSpec {
+                orig_id: None,
+                trips: vec![
+                    IndividTrip::new(
+                        depart,
+                        TripPurpose::Recreation,
+                        origin,
+                        TripEndpoint::Building(self.venue),
+                        mode,
+                    ),
+                    IndividTrip::new(
+                        leave,
+                        TripPurpose::Home,
+                        TripEndpoint::Building(self.venue),
+                        destination,
+                        mode,
+                    ),
+                ],
+            });
+        }
+        people
+    }
+
+    fn choose_mode(&self, pick: f64, total_share: usize) -> TripMode {
+        let mut remaining = pick * total_share as f64;
+        for (mode, share) in &self.mode_split {
+            if remaining < *share as f64 {
+                return *mode;
+            }
+            remaining -= *share as f64;
+        }
+        // Only reachable through floating point error
+        *self
+            .mode_split
+            .iter()
+            .rev()
+            .find(|(_, share)| **share > 0)
+            .unwrap()
+            .0
+    }
+}
+
+/// A sequence of numbers in [0, 1) that covers the range evenly, without repeating patterns
+pub(crate) fn low_discrepancy(idx: usize, step: f64) -> f64 {
+    ((idx as f64 + 1.0) * step).fract()
+}
+
+/// Picks a border, favoring the ones connected to bigger roads. `pick` is in [0, 1).
+fn pick_border(borders: &[MapBorder], pick: f64) -> IntersectionID {
+    let total: usize = borders.iter().map(|b| b.weight).sum();
+    let mut remaining = pick * total as f64;
+    for b in borders {
+        if remaining < b.weight as f64 {
+            return b.i;
+        }
+        remaining -= b.weight as f64;
+    }
+    borders.last().unwrap().i
+}
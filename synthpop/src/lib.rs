@@ -18,16 +18,28 @@ use map_model::PathConstraints;
 pub use self::borders::{MapBorder, MapBorders};
 pub use self::counts::TrafficCounts;
 pub use self::endpoint::TripEndpoint;
+pub use self::evacuation::Evacuation;
+pub use self::event::{EventSurge, SurgeCurve};
 pub use self::external::{ExternalPerson, ExternalTrip, ExternalTripEndpoint};
+pub use self::mode_choice::ModeChoice;
 pub use self::modifier::ScenarioModifier;
 pub use self::scenario::{IndividTrip, PersonSpec, Scenario, TripPurpose};
+pub use self::schools::{
+    find_schools, is_school_street, roads_fronting_schools, school_street_zones, SCHOOL_STREET_ZONE,
+};
+pub use self::through_traffic::{find_through_traffic, ThroughTraffic};
 
 mod borders;
 mod counts;
 mod endpoint;
+mod evacuation;
+mod event;
 mod external;
+mod mode_choice;
 mod modifier;
 mod scenario;
+mod schools;
+mod through_traffic;
 
 /// How does a trip primarily happen?
 ///
@@ -0,0 +1,279 @@
+//! Scenarios are usually generated with a fixed mode for every trip, so map edits that make
+//! cycling faster or driving slower don't change how anybody travels. This lets people pick a mode
+//! instead, based on how long each option takes on the current map, what it costs, and how
+//! comfortable it is.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use geom::{Distance, Duration};
+use map_model::{Map, MAX_BIKE_SPEED, MAX_WALKING_SPEED};
+
+use crate::event::low_discrepancy;
+use crate::{IndividTrip, Scenario, TripEndpoint, TripMode};
+
+/// A multinomial logit model over modes. Each option is scored as an equivalent amount of travel
+/// time: the estimated time, plus the cost converted by the value of time, plus a comfort penalty.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug, Serialize, Deserialize)]
+pub struct ModeChoice {
+    /// Only this percentage of people reconsider their mode. Stable as the percentage increases,
+    /// like `ScenarioModifier::ChangeMode`.
+    pub pct_ppl: usize,
+    /// How much an hour of travel is worth to people, in cents
+    pub value_of_time_cents_per_hour: usize,
+    /// Fuel and wear for driving, in cents per kilometer
+    pub driving_cents_per_km: usize,
+    /// Paid for every transit trip, in cents
+    pub transit_fare_cents: usize,
+    /// Transit isn't modelled in detail here. A transit trip is estimated to take as long as
+    /// driving, plus this much to walk to the stop and wait.
+    pub transit_overhead: Duration,
+    /// How much extra travel time people treat each trip by some mode as, to cover things like
+    /// finding parking or arriving sweaty. Modes not listed have no penalty.
+    pub discomfort: BTreeMap<TripMode, Duration>,
+    /// How random the choice is. When one option is this much worse than another, it's picked e
+    /// times less often. If zero, everybody picks their best option.
+    pub spread: Duration,
+}
+
+impl ModeChoice {
+    pub fn new(pct_ppl: usize) -> ModeChoice {
+        let mut discomfort = BTreeMap::new();
+        discomfort.insert(TripMode::Bike, Duration::minutes(5));
+        discomfort.insert(TripMode::Transit, Duration::minutes(5));
+        discomfort.insert(TripMode::Drive, Duration::minutes(5));
+        ModeChoice {
+            pct_ppl,
+            value_of_time_cents_per_hour: 1500,
+            driving_cents_per_km: 20,
+            transit_fare_cents: 275,
+            transit_overhead: Duration::minutes(10),
+            discomfort,
+            spread: Duration::minutes(10),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{}% of people choose how to travel based on time, cost, and comfort",
+            self.pct_ppl
+        )
+    }
+
+    /// Each chosen person picks one mode for their entire day, so nobody leaves a car or bike
+    /// somewhere and comes home another way. Only people traveling between buildings are
+    /// considered; trips that start or end off the map keep their mode. Returns the number of
+    /// people whose mode changed.
+    pub fn apply(&self, map: &Map, s: &mut Scenario) -> usize {
+        let mut changed = 0;
+        for (idx, person) in s.people.iter_mut().enumerate() {
+            if idx % 100 > self.pct_ppl {
+                continue;
+            }
+            if person.trips.is_empty()
+                || !person.trips.iter().all(|trip| {
+                    !trip.cancelled
+                        && !trip.park_and_ride
+                        && !trip.bike_share
+                        && matches!(trip.origin, TripEndpoint::Building(_))
+                        && matches!(trip.destination, TripEndpoint::Building(_))
+                })
+            {
+                continue;
+            }
+
+            let mode = match self.choose(map, &person.trips, low_discrepancy(idx, 0.618034)) {
+                Some(mode) => mode,
+                None => {
+                    continue;
+                }
+            };
+            if person.trips.iter().any(|trip| trip.mode != mode) {
+                changed += 1;
+                for trip in &mut person.trips {
+                    if trip.mode != mode {
+                        trip.mode = mode;
+                        trip.modified = true;
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    /// Picks one mode for all of these trips, or `None` if no mode works for all of them. `roll`
+    /// is in [0, 1).
+    pub(crate) fn choose(&self, map: &Map, trips: &[IndividTrip], roll: f64) -> Option<TripMode> {
+        // The total cost of doing the whole day by each mode
+        let mut options: Vec<(TripMode, Duration)> = Vec::new();
+        for mode in TripMode::all() {
+            if let Some(total) = self.total_cost(map, trips, mode) {
+                options.push((mode, total));
+            }
+        }
+        if options.is_empty() {
+            return None;
+        }
+        Some(self.pick(&options, roll))
+    }
+
+    /// `roll` is in [0, 1)
+    fn pick(&self, options: &[(TripMode, Duration)], roll: f64) -> TripMode {
+        let best = options.iter().min_by_key(|(_, cost)| *cost).unwrap();
+        if self.spread == Duration::ZERO {
+            return best.0;
+        }
+        let weights: Vec<f64> = options
+            .iter()
+            .map(|(_, cost)| (-((*cost - best.1) / self.spread)).exp())
+            .collect();
+        let mut remaining = roll * weights.iter().sum::<f64>();
+        for ((mode, _), weight) in options.iter().zip(weights) {
+            if remaining < weight {
+                return *mode;
+            }
+            remaining -= weight;
+        }
+        best.0
+    }
+
+    fn total_cost(&self, map: &Map, trips: &[IndividTrip], mode: TripMode) -> Option<Duration> {
+        let mut total = Duration::ZERO;
+        for trip in trips {
+            total += self.cost(map, trip, mode)?;
+        }
+        Some(total)
+    }
+
+    /// The equivalent travel time of making one trip by some mode, or `None` if it's impossible.
+    fn cost(&self, map: &Map, trip: &IndividTrip, mode: TripMode) -> Option<Duration> {
+        let (time, cents) = match mode {
+            TripMode::Walk | TripMode::Bike => {
+                let max_speed = if mode == TripMode::Walk {
+                    MAX_WALKING_SPEED
+                } else {
+                    MAX_BIKE_SPEED
+                };
+                let path = map
+                    .pathfind_at(
+                        TripEndpoint::path_req(trip.origin, trip.destination, mode, map)?,
+                        trip.depart,
+                    )
+                    .ok()?;
+                (path.estimate_duration(map, Some(max_speed)), 0.0)
+            }
+            TripMode::Drive => {
+                let (time, dist) = self.driving(map, trip)?;
+                (
+                    time,
+                    (self.driving_cents_per_km as f64) * dist.inner_meters() / 1000.0,
+                )
+            }
+            TripMode::Transit => {
+                let req =
+                    TripEndpoint::path_req(trip.origin, trip.destination, TripMode::Walk, map)?;
+                map.should_use_transit(req.start, req.end)?;
+                let (time, _) = self.driving(map, trip)?;
+                (time + self.transit_overhead, self.transit_fare_cents as f64)
+            }
+        };
+
+        let mut cost = time
+            + self
+                .discomfort
+                .get(&mode)
+                .cloned()
+                .unwrap_or(Duration::ZERO);
+        if self.value_of_time_cents_per_hour > 0 {
+            cost += Duration::seconds(3600.0 * cents / (self.value_of_time_cents_per_hour as f64));
+        }
+        Some(cost)
+    }
+
+    fn driving(&self, map: &Map, trip: &IndividTrip) -> Option<(Duration, Distance)> {
+        // Time-of-day restrictions and restricted zones may force a detour
+        let path = map
+            .pathfind_at(
+                TripEndpoint::path_req(trip.origin, trip.destination, TripMode::Drive, map)?,
+                trip.depart,
+            )
+            .ok()?;
+        Some((path.estimate_duration(map, None), path.total_length()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> Vec<(TripMode, Duration)> {
+        vec![
+            (TripMode::Walk, Duration::minutes(30)),
+            (TripMode::Bike, Duration::minutes(20)),
+            (TripMode::Drive, Duration::minutes(40)),
+        ]
+    }
+
+    // How often each mode is picked over many evenly spread rolls
+    fn frequencies(model: &ModeChoice, options: &[(TripMode, Duration)]) -> Vec<f64> {
+        let n = 10_000;
+        let mut counts = vec![0; options.len()];
+        for idx in 0..n {
+            let mode = model.pick(options, low_discrepancy(idx, 0.618034));
+            counts[options.iter().position(|(m, _)| *m == mode).unwrap()] += 1;
+        }
+        counts.into_iter().map(|x| x as f64 / n as f64).collect()
+    }
+
+    #[test]
+    fn test_pick_probabilities() {
+        let model = ModeChoice::new(100);
+        // With a spread of 10 minutes, an option 10 minutes worse than the best is e times less
+        // likely
+        let weights = [(-1.0_f64).exp(), 1.0, (-2.0_f64).exp()];
+        let total: f64 = weights.iter().sum();
+
+        let mut ok = true;
+        for (expected, actual) in weights.iter().zip(frequencies(&model, &options())) {
+            let expected = expected / total;
+            if (expected - actual).abs() > 0.01 {
+                println!("Expected probability {}, but got {}", expected, actual);
+                ok = false;
+            }
+        }
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_pick_fixed_rolls() {
+        let model = ModeChoice::new(100);
+        let options = options();
+        // The cumulative probabilities are about 0.245, 0.910, and 1.0
+        let mut ok = true;
+        for (roll, expected) in [
+            (0.0, TripMode::Walk),
+            (0.24, TripMode::Walk),
+            (0.25, TripMode::Bike),
+            (0.9, TripMode::Bike),
+            (0.92, TripMode::Drive),
+            (0.999, TripMode::Drive),
+        ] {
+            let actual = model.pick(&options, roll);
+            if actual != expected {
+                println!("Roll {} picked {:?}, expected {:?}", roll, actual, expected);
+                ok = false;
+            }
+        }
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_pick_without_spread() {
+        let mut model = ModeChoice::new(100);
+        model.spread = Duration::ZERO;
+        for roll in [0.0, 0.5, 0.999] {
+            assert_eq!(model.pick(&options(), roll), TripMode::Bike);
+        }
+    }
+}
@@ -3,10 +3,10 @@ use std::collections::BTreeSet;
 use serde::{Deserialize, Serialize};
 
 use abstutil::Timer;
-use geom::{Duration, Time};
+use geom::{Distance, Duration, Time};
 use map_model::Map;
 
-use crate::{Scenario, TripMode};
+use crate::{Evacuation, EventSurge, ModeChoice, Scenario, TripEndpoint, TripMode};
 
 /// Transforms an existing Scenario before instantiating it.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
@@ -21,6 +21,33 @@ pub enum ScenarioModifier {
     },
     /// Scenario name
     AddExtraTrips(String),
+    /// Some people who would drive into a charging cordon while it's active instead take transit.
+    RespondToCordonCharges {
+        pct_ppl: usize,
+    },
+    /// Some people who drive between buildings all day instead drive to a park-and-ride lot, leave
+    /// their car there, and continue by transit. They pick the car up again on the way home.
+    ParkAndRide {
+        pct_ppl: usize,
+    },
+    /// Some people walking or driving a medium distance between buildings try to ride a shared
+    /// bike instead.
+    UseBikeShare {
+        pct_ppl: usize,
+    },
+    /// Students travel to every school, arriving by the bell time. The bell must be at least an
+    /// hour after midnight.
+    AddSchoolRuns {
+        bell_time: Time,
+        students_per_school: usize,
+    },
+    /// Attendees travel to and from one big event, on top of everybody else's trips.
+    AddEventSurge(EventSurge),
+    /// Everybody inside a hazard zone leaves for somewhere safe.
+    Evacuate(Evacuation),
+    /// Some people pick their mode based on travel times on the current map, instead of keeping
+    /// the one they were generated with.
+    ChooseModes(ModeChoice),
 }
 
 impl ScenarioModifier {
@@ -84,6 +111,118 @@ impl ScenarioModifier {
                 }
                 s
             }
+            ScenarioModifier::RespondToCordonCharges { pct_ppl } => {
+                let cordons = &map.get_edits().charging_cordons;
+                for (idx, person) in s.people.iter_mut().enumerate() {
+                    // Stable as the percentage increases, like ChangeMode
+                    if idx % 100 > *pct_ppl {
+                        continue;
+                    }
+                    for trip in &mut person.trips {
+                        if trip.cancelled || trip.mode != TripMode::Drive {
+                            continue;
+                        }
+                        let (pt1, pt2) = (trip.origin.pt(map), trip.destination.pt(map));
+                        if cordons.iter().any(|c| {
+                            c.is_active(trip.depart)
+                                && !c.boundary.contains_pt(pt1)
+                                && c.boundary.contains_pt(pt2)
+                        }) {
+                            trip.mode = TripMode::Transit;
+                            trip.modified = true;
+                        }
+                    }
+                }
+                s
+            }
+            ScenarioModifier::ParkAndRide { pct_ppl } => {
+                for (idx, person) in s.people.iter_mut().enumerate() {
+                    // Stable as the percentage increases, like ChangeMode
+                    if idx % 100 > *pct_ppl {
+                        continue;
+                    }
+                    // The car has to be left somewhere and picked up again later, so only switch
+                    // people who make a round trip entirely by car between buildings.
+                    if person.trips.len() < 2
+                        || !person.trips.iter().all(|trip| {
+                            !trip.cancelled
+                                && trip.mode == TripMode::Drive
+                                && matches!(trip.origin, TripEndpoint::Building(_))
+                                && matches!(trip.destination, TripEndpoint::Building(_))
+                        })
+                    {
+                        continue;
+                    }
+                    for trip in &mut person.trips {
+                        trip.mode = TripMode::Transit;
+                        trip.park_and_ride = true;
+                        trip.modified = true;
+                    }
+                }
+                s
+            }
+            ScenarioModifier::UseBikeShare { pct_ppl } => {
+                for (idx, person) in s.people.iter_mut().enumerate() {
+                    // Stable as the percentage increases, like ChangeMode
+                    if idx % 100 > *pct_ppl {
+                        continue;
+                    }
+                    for trip in &mut person.trips {
+                        let (b1, b2) = match (trip.origin, trip.destination) {
+                            (TripEndpoint::Building(b1), TripEndpoint::Building(b2)) => (b1, b2),
+                            _ => continue,
+                        };
+                        let dist = map
+                            .get_b(b1)
+                            .polygon
+                            .center()
+                            .dist_to(map.get_b(b2).polygon.center());
+                        // Short walks aren't worth finding a bike for, and long drives aren't
+                        // likely to switch
+                        let switch = match trip.mode {
+                            TripMode::Walk => dist > Distance::meters(800.0),
+                            TripMode::Drive => dist < Distance::meters(5000.0),
+                            TripMode::Bike | TripMode::Transit => false,
+                        };
+                        if switch && !trip.cancelled {
+                            trip.mode = TripMode::Bike;
+                            trip.bike_share = true;
+                            trip.modified = true;
+                        }
+                    }
+                }
+                s
+            }
+            ScenarioModifier::AddSchoolRuns {
+                bell_time,
+                students_per_school,
+            } => {
+                for mut p in crate::schools::school_run_trips(map, *bell_time, *students_per_school)
+                {
+                    for trip in &mut p.trips {
+                        trip.modified = true;
+                    }
+                    s.people.push(p);
+                }
+                s
+            }
+            ScenarioModifier::AddEventSurge(event) => {
+                for mut p in event.generate(map) {
+                    for trip in &mut p.trips {
+                        trip.modified = true;
+                    }
+                    s.people.push(p);
+                }
+                s
+            }
+            ScenarioModifier::Evacuate(evacuation) => {
+                evacuation.apply(map, &mut s);
+                s
+            }
+            ScenarioModifier::ChooseModes(mode_choice) => {
+                mode_choice.apply(map, &mut s);
+                s
+            }
         }
     }
 
@@ -105,6 +244,29 @@ impl ScenarioModifier {
                 to_mode.map(|m| m.verb())
             ),
             ScenarioModifier::AddExtraTrips(name) => format!("Add extra trips from {}", name),
+            ScenarioModifier::RespondToCordonCharges { pct_ppl } => format!(
+                "{}% of people driving into a charging cordon take transit instead",
+                pct_ppl
+            ),
+            ScenarioModifier::ParkAndRide { pct_ppl } => format!(
+                "{}% of people who drive everywhere use park-and-ride lots instead",
+                pct_ppl
+            ),
+            ScenarioModifier::UseBikeShare { pct_ppl } => format!(
+                "{}% of people walking or driving a medium distance ride a shared bike instead",
+                pct_ppl
+            ),
+            ScenarioModifier::AddSchoolRuns {
+                bell_time,
+                students_per_school,
+            } => format!(
+                "{} students travel to every school, arriving by {}",
+                students_per_school,
+                bell_time.ampm_tostring()
+            ),
+            ScenarioModifier::AddEventSurge(event) => event.describe(),
+            ScenarioModifier::Evacuate(evacuation) => evacuation.describe(),
+            ScenarioModifier::ChooseModes(mode_choice) => mode_choice.describe(),
         }
     }
 }
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use abstio::{CityName, MapName};
 use abstutil::prettyprint_usize;
 use geom::Time;
-use map_model::Map;
+use map_model::{AmenityType, BuildingType, Map};
 
 use crate::{OrigPersonID, TripEndpoint, TripMode};
 
@@ -42,6 +42,15 @@ pub struct IndividTrip {
     pub cancelled: bool,
     /// Did a ScenarioModifier affect this?
     pub modified: bool,
+    /// For transit trips, use a car to reach or leave transit. When the person's car is at the
+    /// origin, they drive to a park-and-ride lot first. When the car was left at a lot, they ride
+    /// transit back to it and drive the rest of the way.
+    #[serde(default)]
+    pub park_and_ride: bool,
+    /// For biking trips, ride a shared bike between stations when one is available, instead of
+    /// using the person's own bike. When no bike or dock is close enough, the person walks.
+    #[serde(default)]
+    pub bike_share: bool,
 }
 
 impl IndividTrip {
@@ -60,12 +69,14 @@ impl IndividTrip {
             purpose,
             cancelled: false,
             modified: false,
+            park_and_ride: false,
+            bike_share: false,
         }
     }
 }
 
 /// Lifted from Seattle's Soundcast model, but seems general enough to use anyhere.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TripPurpose {
     Home,
     Work,
@@ -78,6 +89,8 @@ pub enum TripPurpose {
     Recreation,
     Medical,
     ParkAndRideTransfer,
+    /// Leaving a hazard zone for somewhere safe
+    Evacuation,
 }
 
 impl fmt::Display for TripPurpose {
@@ -98,11 +111,130 @@ impl fmt::Display for TripPurpose {
                 TripPurpose::Recreation => "recreation",
                 TripPurpose::Medical => "medical",
                 TripPurpose::ParkAndRideTransfer => "park-and-ride transfer",
+                TripPurpose::Evacuation => "evacuation",
             }
         )
     }
 }
 
+impl TripPurpose {
+    pub fn all() -> Vec<TripPurpose> {
+        vec![
+            TripPurpose::Home,
+            TripPurpose::Work,
+            TripPurpose::School,
+            TripPurpose::Escort,
+            TripPurpose::PersonalBusiness,
+            TripPurpose::Shopping,
+            TripPurpose::Meal,
+            TripPurpose::Social,
+            TripPurpose::Recreation,
+            TripPurpose::Medical,
+            TripPurpose::ParkAndRideTransfer,
+            TripPurpose::Evacuation,
+        ]
+    }
+
+    /// Guesses why somebody makes a trip, based on what's at the destination and the time of day.
+    /// Many scenarios don't come with meaningful purposes, so this helps make the synthetic
+    /// population easier to interpret. Returns None for trips leaving the map, since nothing is
+    /// known about where they're headed.
+    pub fn infer(
+        depart: Time,
+        origin: &TripEndpoint,
+        destination: &TripEndpoint,
+        map: &Map,
+    ) -> Option<TripPurpose> {
+        let bldg = match destination {
+            TripEndpoint::Building(b) => map.get_b(*b),
+            TripEndpoint::Border(_) | TripEndpoint::SuddenlyAppear(_) => {
+                return None;
+            }
+        };
+        let hour = depart.get_hours() % 24;
+        let morning_commute = (5..10).contains(&hour);
+        let from_home = match origin {
+            TripEndpoint::Building(b) => map.get_b(*b).bldg_type.has_residents(),
+            TripEndpoint::Border(_) | TripEndpoint::SuddenlyAppear(_) => false,
+        };
+        let has_any = |categories: &[AmenityType]| categories.iter().any(|a| bldg.has_amenity(*a));
+
+        match bldg.bldg_type {
+            BuildingType::Residential { .. } => {
+                // Visiting somebody else in the evening
+                if from_home && (17..23).contains(&hour) {
+                    return Some(TripPurpose::Social);
+                }
+                return Some(TripPurpose::Home);
+            }
+            BuildingType::ResidentialCommercial(_, _) => {
+                if !from_home && hour >= 15 {
+                    return Some(TripPurpose::Home);
+                }
+            }
+            BuildingType::Commercial(_) => {}
+            BuildingType::Empty => {
+                return Some(TripPurpose::PersonalBusiness);
+            }
+        }
+
+        if morning_commute && has_any(&[AmenityType::Childcare]) {
+            return Some(TripPurpose::Escort);
+        }
+        if morning_commute && has_any(&[AmenityType::School, AmenityType::University]) {
+            return Some(TripPurpose::School);
+        }
+        if has_any(&[AmenityType::Medical]) {
+            return Some(TripPurpose::Medical);
+        }
+        if morning_commute {
+            return Some(TripPurpose::Work);
+        }
+        let mealtime = (11..14).contains(&hour) || (17..22).contains(&hour);
+        if mealtime
+            && has_any(&[
+                AmenityType::Bar,
+                AmenityType::Cafe,
+                AmenityType::FastFood,
+                AmenityType::Food,
+            ])
+        {
+            return Some(TripPurpose::Meal);
+        }
+        if has_any(&[
+            AmenityType::Beauty,
+            AmenityType::Bike,
+            AmenityType::ConvenienceStore,
+            AmenityType::Laundry,
+            AmenityType::Pet,
+            AmenityType::Shopping,
+            AmenityType::Supermarket,
+        ]) {
+            return Some(TripPurpose::Shopping);
+        }
+        if has_any(&[
+            AmenityType::Culture,
+            AmenityType::Exercise,
+            AmenityType::GreenSpace,
+            AmenityType::Library,
+            AmenityType::Playground,
+            AmenityType::Pool,
+            AmenityType::Religious,
+            AmenityType::Tourism,
+        ]) {
+            return Some(TripPurpose::Recreation);
+        }
+        if has_any(&[
+            AmenityType::Bank,
+            AmenityType::CarRepair,
+            AmenityType::PostOffice,
+        ]) {
+            return Some(TripPurpose::PersonalBusiness);
+        }
+        Some(TripPurpose::Work)
+    }
+}
+
 impl Scenario {
     pub fn save(&self) {
         abstio::write_binary(
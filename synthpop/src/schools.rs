@@ -0,0 +1,171 @@
+//! A "school street" closes the road in front of a school to through-traffic around drop-off and
+//! pick-up times. To evaluate one, the population needs children traveling to school at the right
+//! time, which most scenarios don't capture well.
+
+use std::collections::BTreeSet;
+
+use geom::{Distance, Duration, Speed, Time};
+use map_model::{
+    BuildingID, BuildingType, Map, PathConstraints, RestrictedZone, RoadID, TimeWindow,
+};
+
+use crate::event::low_discrepancy;
+use crate::{IndividTrip, ModeChoice, PersonSpec, TripEndpoint, TripMode, TripPurpose};
+
+/// Students only come from homes this close to their school.
+const MAX_DISTANCE_FROM_SCHOOL: Distance = Distance::const_meters(3000.0);
+/// How long between the morning bell and afternoon dismissal
+const SCHOOL_DAY: Duration = Duration::const_seconds(6.5 * 3600.0);
+/// Restricted zones closing a school street are named starting with this
+pub const SCHOOL_STREET_ZONE: &str = "School street";
+
+/// Every building used as a school.
+pub fn find_schools(map: &Map) -> Vec<BuildingID> {
+    map.all_buildings()
+        .iter()
+        .filter(|b| {
+            b.amenities.iter().any(|a| a.amenity_type == "school")
+                || b.osm_tags.is("amenity", "school")
+                || b.osm_tags.is("building", "school")
+        })
+        .map(|b| b.id)
+        .collect()
+}
+
+/// The roads that schools are accessed from.
+pub fn roads_fronting_schools(map: &Map) -> BTreeSet<RoadID> {
+    find_schools(map)
+        .into_iter()
+        .map(|b| map.get_b(b).sidewalk_pos.lane().road)
+        .collect()
+}
+
+/// Is the road in front of this school closed to cars, at least some of the time?
+pub fn is_school_street(map: &Map, school: BuildingID) -> bool {
+    let r = map.get_b(school).sidewalk_pos.lane().road;
+    map.get_edits()
+        .restricted_zones
+        .iter()
+        .any(|z| z.members.contains(&r) && !z.exemptions.contains(PathConstraints::Car))
+        || !map
+            .get_r(r)
+            .access_restrictions
+            .allow_through_traffic
+            .contains(PathConstraints::Car)
+}
+
+/// Restricted zones closing the road in front of every school that isn't a school street yet.
+/// Only buses and bikes may pass during drop-off and pick-up around the bell and dismissal. Cars
+/// going to the school or homes along the street can still reach them.
+pub fn school_street_zones(map: &Map, bell_time: Time) -> Vec<RestrictedZone> {
+    let dismissal = bell_time + SCHOOL_DAY;
+    let windows = vec![
+        TimeWindow {
+            start_time: bell_time - Duration::minutes(45),
+            end_time: bell_time + Duration::minutes(15),
+        },
+        TimeWindow {
+            start_time: dismissal - Duration::minutes(15),
+            end_time: dismissal + Duration::minutes(45),
+        },
+    ];
+
+    let mut roads = BTreeSet::new();
+    for school in find_schools(map) {
+        if !is_school_street(map, school) {
+            roads.insert(map.get_b(school).sidewalk_pos.lane().road);
+        }
+    }
+    roads
+        .into_iter()
+        .map(|r| {
+            RestrictedZone::new(
+                map,
+                format!("{} on {}", SCHOOL_STREET_ZONE, map.get_r(r).get_name(None)),
+                map.get_r(r).get_thick_polygon(),
+                PathConstraints::Bus | PathConstraints::Bike,
+                windows.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Generate one person per student, traveling from home to arrive at each school by the bell and
+/// returning home at dismissal. Students pick a mode using the default `ModeChoice` model, so
+/// closing school streets only changes modes through its effect on travel times. This is
+/// deterministic, so that results before and after closing streets can be compared.
+pub fn school_run_trips(map: &Map, bell_time: Time, students_per_school: usize) -> Vec<PersonSpec> {
+    let homes: Vec<BuildingID> = map
+        .all_buildings()
+        .iter()
+        .filter(|b| {
+            matches!(
+                b.bldg_type,
+                BuildingType::Residential { .. } | BuildingType::ResidentialCommercial(_, _)
+            )
+        })
+        .map(|b| b.id)
+        .collect();
+
+    let mode_choice = ModeChoice::new(100);
+    let mut people = Vec::new();
+    for school in find_schools(map) {
+        let school_pt = map.get_b(school).polygon.center();
+        let mut candidates: Vec<(Distance, BuildingID)> = homes
+            .iter()
+            .map(|b| (map.get_b(*b).polygon.center().dist_to(school_pt), *b))
+            .filter(|(dist, _)| *dist <= MAX_DISTANCE_FROM_SCHOOL)
+            .collect();
+        if candidates.is_empty() {
+            warn!("No homes near {}, skipping it", school);
+            continue;
+        }
+        // Sample evenly by distance, so every school gets a mix of near and far students
+        candidates.sort_by_key(|(dist, _)| *dist);
+
+        for idx in 0..students_per_school {
+            let (dist, home) = candidates[idx * candidates.len() / students_per_school];
+            // Pick the mode assuming a typical departure, then leave early enough by that mode
+            let mut trips = vec![
+                IndividTrip::new(
+                    bell_time - Duration::minutes(30),
+                    TripPurpose::School,
+                    TripEndpoint::Building(home),
+                    TripEndpoint::Building(school),
+                    TripMode::Walk,
+                ),
+                IndividTrip::new(
+                    bell_time + SCHOOL_DAY,
+                    TripPurpose::Home,
+                    TripEndpoint::Building(school),
+                    TripEndpoint::Building(home),
+                    TripMode::Walk,
+                ),
+            ];
+            let mode = mode_choice
+                .choose(map, &trips, low_discrepancy(people.len(), 0.618034))
+                .unwrap_or(TripMode::Walk);
+            trips[0].depart = bell_time - estimate_travel_time(mode, dist) - Duration::minutes(5);
+            for trip in &mut trips {
+                trip.mode = mode;
+            }
+            people.push(PersonSpec {
+                orig_id: None,
+                trips,
+            });
+        }
+    }
+    people
+}
+
+pub(crate) fn estimate_travel_time(mode: TripMode, dist: Distance) -> Duration {
+    // Routes aren't straight lines
+    let dist = 1.3 * dist;
+    let speed = match mode {
+        TripMode::Walk => Speed::meters_per_second(1.34),
+        TripMode::Bike => Speed::meters_per_second(4.0),
+        TripMode::Transit => Speed::meters_per_second(5.0),
+        TripMode::Drive => Speed::meters_per_second(8.0),
+    };
+    dist / speed
+}
@@ -0,0 +1,114 @@
+//! A rat-run is a residential street that drivers use as a shortcut between two main roads.
+//! Before designing a low-traffic neighborhood, it helps to know which streets suffer from this
+//! today, using the routes people take in a scenario instead of anecdotes.
+
+use abstutil::{Counter, Timer};
+use map_model::osm::RoadRank;
+use map_model::{Map, PathRequest, PathStepV2, RoadID};
+
+use crate::{Scenario, TripEndpoint, TripMode};
+
+/// How much driving traffic uses one local street, and how much of it is just passing through.
+#[derive(Clone, Debug)]
+pub struct ThroughTraffic {
+    pub road: RoadID,
+    /// Every driving trip using the street
+    pub total: usize,
+    /// Driving trips reaching this street from a main road and continuing to another main road
+    pub through: usize,
+}
+
+impl ThroughTraffic {
+    pub fn pct_through(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            100.0 * self.through as f64 / self.total as f64
+        }
+    }
+}
+
+/// Routes every driving trip in the scenario and finds the local streets carrying through-traffic,
+/// ranked with the most through-traffic first. A trip passes through a stretch of local streets
+/// when it enters the stretch from a main road and leaves it onto another main road. Trips that
+/// start or end along the stretch are just accessing the area. This doesn't need any
+/// neighborhood boundaries, so it works for a whole city at once.
+pub fn find_through_traffic(
+    map: &Map,
+    scenario: &Scenario,
+    timer: &mut Timer,
+) -> Vec<ThroughTraffic> {
+    let requests: Vec<PathRequest> = scenario
+        .all_trips()
+        .filter(|trip| trip.mode == TripMode::Drive)
+        .filter_map(|trip| TripEndpoint::path_req(trip.origin, trip.destination, trip.mode, map))
+        .collect();
+    let requests = PathRequest::deduplicate(map, requests);
+
+    // For each request, every local street used and whether it was passing through
+    let results = timer.parallelize(
+        "find through-traffic on local streets",
+        requests,
+        |(req, count)| {
+            let mut roads: Vec<RoadID> = Vec::new();
+            if let Some(path) = map.get_pathfinder().pathfind_v2(req, map) {
+                for step in path.get_steps() {
+                    if let PathStepV2::Along(dr) | PathStepV2::Contraflow(dr) = step {
+                        if roads.last() != Some(&dr.road) {
+                            roads.push(dr.road);
+                        }
+                    }
+                }
+            }
+            (classify(map, &roads), count)
+        },
+    );
+
+    let mut total = Counter::new();
+    let mut through = Counter::new();
+    for (local_roads, count) in results {
+        for (r, is_through) in local_roads {
+            total.add(r, count);
+            if is_through {
+                through.add(r, count);
+            }
+        }
+    }
+
+    let mut ranking: Vec<ThroughTraffic> = through
+        .consume()
+        .into_iter()
+        .map(|(road, through)| ThroughTraffic {
+            road,
+            total: total.get(road),
+            through,
+        })
+        .collect();
+    ranking.sort_by_key(|x| (std::cmp::Reverse(x.through), x.road));
+    ranking
+}
+
+/// Given the roads along one route in order, returns each local street and whether the route
+/// passes through it.
+fn classify(map: &Map, roads: &[RoadID]) -> Vec<(RoadID, bool)> {
+    let is_local = |r: RoadID| map.get_r(r).get_rank() == RoadRank::Local;
+    let mut results = Vec::new();
+    let mut idx = 0;
+    while idx < roads.len() {
+        if !is_local(roads[idx]) {
+            idx += 1;
+            continue;
+        }
+        // Find the whole stretch of local streets
+        let start = idx;
+        while idx < roads.len() && is_local(roads[idx]) {
+            idx += 1;
+        }
+        // The route has to use a main road before and after
+        let is_through = start > 0 && idx < roads.len();
+        for r in &roads[start..idx] {
+            results.push((*r, is_through));
+        }
+    }
+    results
+}
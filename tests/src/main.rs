@@ -15,6 +15,8 @@ use synthpop::{IndividTrip, PersonSpec, Scenario, TripEndpoint, TripMode, TripPu
 
 fn main() -> Result<()> {
     abstutil::logger::setup();
+    // Pass this after intentionally changing simulation behavior
+    let update_goldenfiles = std::env::args().any(|arg| arg == "--update-goldenfiles");
     test_blockfinding()?;
     test_lane_changing(&import_map(abstio::path(
         "../tests/input/lane_selection.osm",
@@ -22,6 +24,7 @@ fn main() -> Result<()> {
     test_map_importer()?;
     check_proposals()?;
     ab_test_spurious_diff()?;
+    test_determinism(update_goldenfiles)?;
     smoke_test()?;
     Ok(())
 }
@@ -64,11 +67,14 @@ fn import_map(path: String) -> Map {
                 inferred_sidewalks: true,
                 street_parking_spot_length: Distance::meters(8.0),
                 turn_on_red: false,
+                default_speed_limits: map_model::DefaultSpeedLimits::usa(),
+                roundabout_priority: map_model::RoundaboutPriority::Circulating,
             },
             onstreet_parking: convert_osm::OnstreetParking::JustOSM,
             public_offstreet_parking: convert_osm::PublicOffstreetParking::None,
             private_offstreet_parking: convert_osm::PrivateOffstreetParking::FixedPerBldg(0),
             include_railroads: true,
+            include_trams: true,
             extra_buildings: None,
             skip_local_roads: false,
             filter_crosswalks: false,
@@ -366,5 +372,64 @@ fn run_sim(map: &Map, scenario: &Scenario, timer: &mut Timer) -> PrebakeSummary
         timer,
     );
 
-    PrebakeSummary::new(&sim, scenario)
+    PrebakeSummary::new(&sim, scenario, map)
+}
+
+/// The simulation should be deterministic. Run the same scenario a few times, varying the number
+/// of threads used to instantiate it, and verify the event stream is identical. Also compare the
+/// checksum against a goldenfile, so differences across platforms or after refactoring the
+/// simulation are caught. If the change is expected, `update_goldenfiles` records the new result.
+fn test_determinism(update_goldenfiles: bool) -> Result<()> {
+    let mut timer = Timer::new("test simulation determinism");
+    let map = map_model::Map::load_synchronously(MapName::seattle("montlake").path(), &mut timer);
+    let scenario: Scenario =
+        abstio::read_binary(abstio::path_scenario(map.get_name(), "weekday"), &mut timer);
+
+    let mut results = Vec::new();
+    for max_threads in [Some(1), Some(2), None] {
+        abstutil::set_max_threads(max_threads);
+        let mut opts = SimOptions::new("determinism");
+        opts.alerts = AlertHandler::Silence;
+        let mut sim = Sim::new(&map, opts);
+        let mut rng = SimFlags::for_test("determinism").make_rng();
+        sim.instantiate(&scenario, &map, &mut rng, &mut timer);
+        let (checksum, num_events) = sim.run_and_checksum(&map, Duration::hours(3), &mut timer);
+        results.push((max_threads, checksum, num_events));
+    }
+    abstutil::set_max_threads(None);
+
+    for (max_threads, checksum, num_events) in &results[1..] {
+        if (*checksum, *num_events) != (results[0].1, results[0].2) {
+            bail!(
+                "Simulation isn't deterministic: with {:?} threads, {} events with checksum {:x}, but with 1 thread, {} events with checksum {:x}",
+                max_threads,
+                num_events,
+                checksum,
+                results[0].2,
+                results[0].1
+            );
+        }
+    }
+
+    let path = abstio::path("../tests/goldenfiles/determinism.txt");
+    let actual = format!(
+        "{}: {} events, checksum {:x}\n",
+        map.get_name().path(),
+        results[0].2,
+        results[0].1
+    );
+    if update_goldenfiles {
+        File::create(path)?.write_all(actual.as_bytes())?;
+        return Ok(());
+    }
+    let expected = fs_err::read_to_string(&path)?;
+    if actual != expected {
+        bail!(
+            "Simulation results changed. {} has \"{}\", but this run got \"{}\". If this is expected, rerun with --update-goldenfiles",
+            path,
+            expected.trim(),
+            actual.trim()
+        );
+    }
+    Ok(())
 }
@@ -15,6 +15,14 @@ pub struct TrafficSignal {
     /// order of ascending `start_time_seconds`, the first plan must begin at `0` (midnight), and
     /// the last plan must not start after 24 hours.
     pub plans: Vec<Plan>,
+    /// Is there an advanced stop line for bikes, letting them wait in a box in front of motor
+    /// vehicles and start first when the light turns green?
+    #[serde(default)]
+    pub bike_box: bool,
+    /// Do bikes turn left in two stages, first crossing with through traffic, then waiting in a
+    /// queue box on the far corner to cross again?
+    #[serde(default)]
+    pub two_stage_bike_left_turns: bool,
 }
 
 /// A plan describes how a traffic signal is configured during some period of time. Multiple plans
@@ -46,6 +54,12 @@ pub struct Stage {
     pub permitted_turns: BTreeSet<Turn>,
     /// The stage lasts this long before moving to the next one.
     pub stage_type: StageType,
+    /// The last seconds of the stage are a yellow interval, warning that movements are ending...
+    #[serde(default)]
+    pub yellow_seconds: usize,
+    /// ...followed by an all-red interval, letting the intersection clear.
+    #[serde(default)]
+    pub all_red_seconds: usize,
 }
 
 /// How long a stage lasts before moving to the next one.
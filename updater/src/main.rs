@@ -1,5 +1,5 @@
 use std::collections::BTreeMap;
-use std::io::{BufReader, Read};
+use std::io::BufReader;
 use std::process::Command;
 
 use anyhow::Result;
@@ -10,8 +10,6 @@ use walkdir::WalkDir;
 use abstio::{DataPacks, Entry, Manifest};
 use abstutil::{must_run_cmd, prettyprint_usize, Timer};
 
-const MD5_BUF_READ_SIZE: usize = 4096;
-
 #[derive(StructOpt)]
 #[structopt(
     name = "updater",
@@ -77,7 +75,7 @@ async fn main() {
         }
         Task::DryRun { single_file } => {
             if let Some(path) = single_file {
-                let local = md5sum(&path);
+                let local = abstio::md5sum_file(&path).unwrap();
                 let truth = Manifest::load()
                     .entries
                     .remove(&path)
@@ -118,35 +116,56 @@ async fn download_updates(version: String, minimal: bool, delete_local: bool, dl
         }
     }
 
-    // Anything missing or needing updating?
-    let mut failed = Vec::new();
-    for (path, entry) in truth.entries {
-        if local.entries.get(&path).map(|x| &x.checksum) != Some(&entry.checksum) {
-            if minimal && !path.contains("montlake") && path != "data/system/us/seattle/city.bin" {
-                continue;
-            }
+    // Anything missing or needing updating? Only these files get downloaded.
+    let changed: Vec<String> = truth
+        .entries
+        .iter()
+        .filter(|(path, entry)| {
+            local.entries.get(*path).map(|x| &x.checksum) != Some(&entry.checksum)
+                && (!minimal
+                    || path.contains("montlake")
+                    || *path == "data/system/us/seattle/city.bin")
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+    if changed.is_empty() {
+        println!("Everything is up-to-date");
+    } else {
+        println!("Updating:");
+        for line in truth.describe_changes(&changed) {
+            println!("- {}", line);
+        }
+    }
 
-            fs_err::create_dir_all(std::path::Path::new(&path).parent().unwrap()).unwrap();
-            match download_file(&version, &path, dl_from_local).await {
-                Ok(bytes) => {
-                    println!(
-                        "> decompress {}, which is {} bytes compressed",
-                        path,
-                        prettyprint_usize(bytes.len())
-                    );
-                    let mut decoder = flate2::read::GzDecoder::new(&bytes[..]);
-                    let mut out = File::create(&path).unwrap();
-                    if let Err(err) = std::io::copy(&mut decoder, &mut out) {
-                        println!("{}, but continuing", err);
-                        failed.push(format!("{} failed: {}", path, err));
-                    }
-                }
-                Err(err) => {
+    let mut failed = Vec::new();
+    for path in changed {
+        let entry = &truth.entries[&path];
+        fs_err::create_dir_all(std::path::Path::new(&path).parent().unwrap()).unwrap();
+        match download_file(&version, &path, entry, dl_from_local).await {
+            Ok(bytes) => {
+                println!(
+                    "> decompress {}, which is {} bytes compressed",
+                    path,
+                    prettyprint_usize(bytes.len())
+                );
+                let mut decoder = flate2::read::GzDecoder::new(&bytes[..]);
+                let mut out = File::create(&path).unwrap();
+                if let Err(err) = std::io::copy(&mut decoder, &mut out) {
                     println!("{}, but continuing", err);
                     failed.push(format!("{} failed: {}", path, err));
+                    continue;
                 }
-            };
-        }
+                if let Err(err) = abstio::verify_file(&path, entry) {
+                    println!("{} has the wrong checksum, but continuing", path);
+                    rm(&path);
+                    failed.push(format!("{} failed: {}", path, err));
+                }
+            }
+            Err(err) => {
+                println!("{}, but continuing", err);
+                failed.push(format!("{} failed: {}", path, err));
+            }
+        };
     }
     if !failed.is_empty() {
         // Fail the build.
@@ -170,9 +189,11 @@ fn just_compare() {
     }
 
     // Anything missing or needing updating?
-    for (path, entry) in truth.entries {
-        if local.entries.get(&path).map(|x| &x.checksum) != Some(&entry.checksum) {
-            if let Some(old_bytes) = local.entries.get(&path).map(|x| x.uncompressed_size_bytes) {
+    let mut changed = Vec::new();
+    for (path, entry) in &truth.entries {
+        if local.entries.get(path).map(|x| &x.checksum) != Some(&entry.checksum) {
+            changed.push(path.clone());
+            if let Some(old_bytes) = local.entries.get(path).map(|x| x.uncompressed_size_bytes) {
                 if old_bytes == entry.uncompressed_size_bytes {
                     println!("- Update {}. Same size, md5sum changed", path);
                 } else {
@@ -187,6 +208,13 @@ fn just_compare() {
             }
         }
     }
+
+    if !changed.is_empty() {
+        println!("\nIn total, downloading:");
+        for line in truth.describe_changes(&changed) {
+            println!("- {}", line);
+        }
+    }
 }
 
 fn upload(version: &str) {
@@ -336,29 +364,38 @@ fn generate_manifest(truth: &Manifest) -> Manifest {
     let mut kv = BTreeMap::new();
     for (path, entry) in
         Timer::new("compute md5sums").parallelize("compute md5sums", paths, |(orig_path, path)| {
-            // If the file's modtime is newer than 12 hours or the uncompressed size has changed,
-            // calculate md5sum. Otherwise assume no change. This heuristic saves lots of time and
+            // If the file's modtime is newer than 12 hours, the uncompressed size has changed, or
+            // the old manifest is missing the SHA-256, calculate checksums. Otherwise assume no
+            // change. This heuristic saves lots of time and
             // doesn't stress my poor SSD as much.
             let metadata = fs_err::metadata(&orig_path).unwrap();
             let uncompressed_size_bytes = metadata.len();
             let recent_modtime = metadata.modified().unwrap().elapsed().unwrap()
                 < std::time::Duration::from_secs(60 * 60 * 12);
 
-            let checksum = if recent_modtime
+            let (checksum, sha256) = if recent_modtime
                 || truth
                     .entries
                     .get(&path)
-                    .map(|entry| entry.uncompressed_size_bytes != uncompressed_size_bytes)
+                    .map(|entry| {
+                        entry.uncompressed_size_bytes != uncompressed_size_bytes
+                            || entry.sha256.is_empty()
+                    })
                     .unwrap_or(true)
             {
-                md5sum(&orig_path)
+                (
+                    abstio::md5sum_file(&orig_path).unwrap(),
+                    abstio::sha256sum_file(&orig_path).unwrap(),
+                )
             } else {
-                truth.entries[&path].checksum.clone()
+                let entry = &truth.entries[&path];
+                (entry.checksum.clone(), entry.sha256.clone())
             };
             (
                 path,
                 Entry {
                     checksum,
+                    sha256,
                     uncompressed_size_bytes,
                     // Will calculate later
                     compressed_size_bytes: 0,
@@ -372,20 +409,6 @@ fn generate_manifest(truth: &Manifest) -> Manifest {
     Manifest { entries: kv }
 }
 
-fn md5sum(path: &str) -> String {
-    // since these files can be very large, computes the md5 hash in chunks
-    let mut file = File::open(path).unwrap();
-    let mut buffer = [0_u8; MD5_BUF_READ_SIZE];
-    let mut context = md5::Context::new();
-    while let Ok(n) = file.read(&mut buffer) {
-        if n == 0 {
-            break;
-        }
-        context.consume(&buffer[..n]);
-    }
-    format!("{:x}", context.compute())
-}
-
 fn rm(path: &str) {
     println!("> rm {}", path);
     match fs_err::remove_file(path) {
@@ -401,7 +424,12 @@ fn rm(path: &str) {
     }
 }
 
-async fn download_file(version: &str, path: &str, dl_from_local: bool) -> Result<Vec<u8>> {
+async fn download_file(
+    version: &str,
+    path: &str,
+    entry: &Entry,
+    dl_from_local: bool,
+) -> Result<Vec<u8>> {
     if dl_from_local {
         return abstio::slurp_file(format!(
             "/home/dabreegster/s3_abst_data/{}/{}.gz",
@@ -413,7 +441,7 @@ async fn download_file(version: &str, path: &str, dl_from_local: bool) -> Result
     println!("> download {}", url);
     let (mut tx, rx) = futures_channel::mpsc::channel(1000);
     abstio::print_download_progress(rx);
-    abstio::download_bytes(url, None, &mut tx).await
+    abstio::download_bytes_resumable(url, entry, &mut tx).await
 }
 
 // download() will remove stray files, but leave empty directories around. Since some runtime code
@@ -50,6 +50,9 @@ pub struct GfxCtx<'a> {
 
     screencap_mode: bool,
     pub(crate) naming_hint: Option<String>,
+    // Clipping regions can be nested, like a scrolling list inside a scrolling panel. The last
+    // entry is the effective one, already intersected with all of the outer regions.
+    clip_stack: Vec<ScreenRectangle>,
 
     // TODO Don't be pub. Delegate everything.
     pub canvas: &'a Canvas,
@@ -78,6 +81,7 @@ impl<'a> GfxCtx<'a> {
             num_forks: 0,
             screencap_mode,
             naming_hint: None,
+            clip_stack: Vec::new(),
         }
     }
 
@@ -146,14 +150,37 @@ impl<'a> GfxCtx<'a> {
     }
 
     // TODO Stateful API :(
+    /// Only draw inside this rectangle, until `disable_clipping` is called. If clipping is already
+    /// enabled, only the overlap of both rectangles is drawn.
     pub fn enable_clipping(&mut self, rect: ScreenRectangle) {
         let scale_factor = self.prerender.get_scale_factor();
-        self.inner.enable_clipping(rect, scale_factor, self.canvas);
+        let rect = if let Some(outer) = self.clip_stack.last() {
+            self.inner.disable_clipping(scale_factor, self.canvas);
+            let x1 = rect.x1.max(outer.x1);
+            let y1 = rect.y1.max(outer.y1);
+            ScreenRectangle {
+                x1,
+                y1,
+                x2: rect.x2.min(outer.x2).max(x1),
+                y2: rect.y2.min(outer.y2).max(y1),
+            }
+        } else {
+            rect
+        };
+        self.inner
+            .enable_clipping(rect.clone(), scale_factor, self.canvas);
+        self.clip_stack.push(rect);
     }
 
+    /// Undoes the most recent `enable_clipping`, restoring any outer clipping region.
     pub fn disable_clipping(&mut self) {
         let scale_factor = self.prerender.get_scale_factor();
         self.inner.disable_clipping(scale_factor, self.canvas);
+        self.clip_stack.pop();
+        if let Some(outer) = self.clip_stack.last() {
+            self.inner
+                .enable_clipping(outer.clone(), scale_factor, self.canvas);
+        }
     }
 
     // Canvas stuff.
@@ -13,7 +13,7 @@ use crate::{EventCtx, Line, ScreenDims, ScreenPt, TextSpan};
 // it's too easy to have false positives.
 const MAX_DOUBLE_CLICK_DURATION: instant::Duration = instant::Duration::from_millis(300);
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event {
     // Used to initialize the application and also to recalculate menu state when some other event
     // is used.
@@ -46,6 +46,8 @@ pub enum Event {
     WindowGainedCursor,
     MouseWheelScroll(f64, f64),
     WindowResized(ScreenDims),
+    /// A file was dragged from somewhere else and dropped onto the window. This is the path to it.
+    DroppedFile(String),
 }
 
 impl Event {
@@ -130,6 +132,7 @@ impl Event {
             } else {
                 Event::WindowLostCursor
             }),
+            WindowEvent::DroppedFile(path) => Some(Event::DroppedFile(path.display().to_string())),
             _ => None,
         }
     }
@@ -202,6 +205,10 @@ pub enum Key {
     RightArrow,
     UpArrow,
     DownArrow,
+    PageUp,
+    PageDown,
+    Home,
+    End,
     F1,
     F2,
     F3,
@@ -289,6 +296,10 @@ impl Key {
             | Key::RightArrow
             | Key::UpArrow
             | Key::DownArrow
+            | Key::PageUp
+            | Key::PageDown
+            | Key::Home
+            | Key::End
             | Key::F1
             | Key::F2
             | Key::F3
@@ -318,6 +329,10 @@ impl Key {
             Key::RightArrow => "→ arrow".to_string(),
             Key::UpArrow => "↑".to_string(),
             Key::DownArrow => "↓".to_string(),
+            Key::PageUp => "Page Up".to_string(),
+            Key::PageDown => "Page Down".to_string(),
+            Key::Home => "Home".to_string(),
+            Key::End => "End".to_string(),
             Key::F1 => "F1".to_string(),
             Key::F2 => "F2".to_string(),
             Key::F3 => "F3".to_string(),
@@ -397,6 +412,10 @@ impl Key {
             VirtualKeyCode::Right => Key::RightArrow,
             VirtualKeyCode::Up => Key::UpArrow,
             VirtualKeyCode::Down => Key::DownArrow,
+            VirtualKeyCode::PageUp => Key::PageUp,
+            VirtualKeyCode::PageDown => Key::PageDown,
+            VirtualKeyCode::Home => Key::Home,
+            VirtualKeyCode::End => Key::End,
             VirtualKeyCode::F1 => Key::F1,
             VirtualKeyCode::F2 => Key::F2,
             VirtualKeyCode::F3 => Key::F3,
@@ -105,6 +105,19 @@ impl UserInput {
         None
     }
 
+    /// If a file was just dropped onto the window, consume the event and return its path.
+    pub fn dropped_file(&mut self) -> Option<String> {
+        if self.event_consumed {
+            return None;
+        }
+        if let Event::DroppedFile(ref path) = self.event {
+            let path = path.clone();
+            self.consume_event();
+            return Some(path);
+        }
+        None
+    }
+
     pub fn is_window_resized(&self) -> bool {
         matches!(self.event, Event::WindowResized(_))
     }
@@ -51,6 +51,7 @@ pub use crate::text::{Font, Line, Text, TextExt, TextSpan};
 pub use crate::tools::warper::Warper;
 pub use crate::tools::Cached;
 pub use crate::widgets::autocomplete::Autocomplete;
+pub use crate::widgets::box_plot::BoxPlot;
 pub(crate) use crate::widgets::button::Button;
 pub use crate::widgets::button::ButtonBuilder;
 pub use crate::widgets::compare_times::CompareTimes;
@@ -58,6 +59,7 @@ pub use crate::widgets::drag_drop::DragDrop;
 pub(crate) use crate::widgets::dropdown::Dropdown;
 pub use crate::widgets::fan_chart::FanChart;
 pub use crate::widgets::filler::Filler;
+pub use crate::widgets::histogram::Histogram;
 pub use crate::widgets::image::{Image, ImageSource};
 pub use crate::widgets::just_draw::DrawWithTooltips;
 pub(crate) use crate::widgets::just_draw::{DeferDraw, JustDraw};
@@ -73,6 +75,7 @@ pub use crate::widgets::table;
 pub use crate::widgets::tabs::TabController;
 pub use crate::widgets::text_box::TextBox;
 pub use crate::widgets::toggle::Toggle;
+pub use crate::widgets::virtual_list::VirtualList;
 pub use crate::widgets::DEFAULT_CORNER_RADIUS;
 pub use crate::widgets::{
     ClickOutcome, CornerRounding, EdgeInsets, Outcome, Panel, PanelBuilder, PanelDims, Widget,
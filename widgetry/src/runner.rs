@@ -57,6 +57,8 @@ impl<A: SharedAppState> State<A> {
                 });
         }
 
+        // The event moves into the input, so remember this for later
+        let is_key_release = matches!(ev, Event::KeyRelease(_));
         // It's impossible / very unlikely we'll grab the cursor in map space before the very first
         // start_drawing call.
         let input = UserInput::new(ev, &self.canvas);
@@ -140,10 +142,7 @@ impl<A: SharedAppState> State<A> {
             // TODO We should always do has_been_consumed, but various hacks prevent this from being
             // true. For now, just avoid the specific annoying redraw case when a KeyRelease event
             // is unused.
-            let input_used = match ev {
-                Event::KeyRelease(_) => ctx.input.has_been_consumed(),
-                _ => true,
-            };
+            let input_used = !is_key_release || ctx.input.has_been_consumed();
             (ctx.updates_requested, input_used)
         })) {
             Ok(pair) => pair,
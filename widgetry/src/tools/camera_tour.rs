@@ -0,0 +1,431 @@
+//! Guided tours script the camera, so somebody can walk an audience through a proposal inside the
+//! app. A tour is a list of steps stored as JSON, with positions in GPS coordinates so the same
+//! tour works after the map is re-imported.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use geom::{GPSBounds, LonLat, Pt2D};
+
+use crate::tools::PopupMsg;
+use crate::{
+    EventCtx, GfxCtx, HorizontalAlignment, Key, Line, Outcome, Panel, RoundedF64, Spinner, State,
+    Text, TextBox, TextExt, Transition, UpdateType, VerticalAlignment, Widget,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraTour {
+    pub name: String,
+    pub steps: Vec<TourStep>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum TourStep {
+    /// Smoothly move the camera to be centered on a point, at some zoom
+    FlyTo { to: LonLat, zoom: f64, seconds: f64 },
+    /// Hold the camera still
+    Dwell { seconds: f64 },
+    /// Change the caption shown with the tour. An empty string clears it.
+    Annotate { text: String },
+}
+
+impl CameraTour {
+    pub fn load(path: String) -> Result<CameraTour> {
+        let bytes = abstio::slurp_file(path)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// How long the tour takes to play, in seconds
+    pub fn total_seconds(&self) -> f64 {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                TourStep::FlyTo { seconds, .. } | TourStep::Dwell { seconds } => *seconds,
+                TourStep::Annotate { .. } => 0.0,
+            })
+            .sum()
+    }
+
+    /// Each stop starts with a camera movement. The steps before the first one form a stop too.
+    fn stops(&self) -> Vec<usize> {
+        let mut stops = vec![0];
+        for (idx, step) in self.steps.iter().enumerate() {
+            if idx > 0 && matches!(step, TourStep::FlyTo { .. }) {
+                stops.push(idx);
+            }
+        }
+        stops
+    }
+}
+
+/// Plays a `CameraTour`, driving the camera and showing captions. The tour can be paused, and
+/// while paused, the camera can be moved freely.
+pub struct TourPlayer {
+    tour: CameraTour,
+    gps_bounds: GPSBounds,
+    panel: Panel,
+
+    /// The step currently playing. When this is past the end, the tour is finished.
+    step: usize,
+    /// Seconds spent on the current step
+    elapsed: f64,
+    /// Where the camera was when the current step started, and its zoom
+    started_from: (Pt2D, f64),
+    caption: String,
+    paused: bool,
+}
+
+impl TourPlayer {
+    pub fn new_state<A: 'static>(
+        ctx: &mut EventCtx,
+        tour: CameraTour,
+        gps_bounds: GPSBounds,
+    ) -> Box<dyn State<A>> {
+        let mut player = TourPlayer {
+            tour,
+            gps_bounds,
+            panel: Panel::empty(ctx),
+            step: 0,
+            elapsed: 0.0,
+            started_from: (ctx.canvas.center_to_map_pt(), ctx.canvas.cam_zoom),
+            caption: String::new(),
+            paused: false,
+        };
+        player.jump_to(ctx, 0);
+        Box::new(player)
+    }
+
+    fn is_done(&self) -> bool {
+        self.step >= self.tour.steps.len()
+    }
+
+    fn current_stop(&self) -> usize {
+        self.tour
+            .stops()
+            .into_iter()
+            .rposition(|idx| idx <= self.step)
+            .unwrap_or(0)
+    }
+
+    /// Instantly apply every step before `step`, then start playing it
+    fn jump_to(&mut self, ctx: &mut EventCtx, step: usize) {
+        self.caption.clear();
+        for prev in self.tour.steps.iter().take(step) {
+            match prev {
+                TourStep::FlyTo { to, zoom, .. } => {
+                    ctx.canvas.cam_zoom = *zoom;
+                    ctx.canvas.center_on_map_pt(to.to_pt(&self.gps_bounds));
+                }
+                TourStep::Dwell { .. } => {}
+                TourStep::Annotate { text } => {
+                    self.caption = text.clone();
+                }
+            }
+        }
+        self.start_step(ctx, step);
+    }
+
+    fn start_step(&mut self, ctx: &mut EventCtx, step: usize) {
+        self.step = step;
+        self.elapsed = 0.0;
+        self.started_from = (ctx.canvas.center_to_map_pt(), ctx.canvas.cam_zoom);
+        self.recreate_panel(ctx);
+        ctx.request_update(UpdateType::Game);
+    }
+
+    /// Advance the current step by some time. Returns true when the step is finished.
+    fn play_step(&mut self, ctx: &mut EventCtx, dt: f64) -> bool {
+        self.elapsed += dt;
+        match self.tour.steps[self.step] {
+            TourStep::FlyTo { to, zoom, seconds } => {
+                let target = to.to_pt(&self.gps_bounds);
+                let percent = if seconds > 0.0 {
+                    (self.elapsed / seconds).min(1.0)
+                } else {
+                    1.0
+                };
+                // Ease in and out, so the camera doesn't jolt
+                let t = percent * percent * (3.0 - 2.0 * percent);
+                let (from, from_zoom) = self.started_from;
+                ctx.canvas.cam_zoom = from_zoom + t * (zoom - from_zoom);
+                ctx.canvas.center_on_map_pt(Pt2D::new(
+                    from.x() + t * (target.x() - from.x()),
+                    from.y() + t * (target.y() - from.y()),
+                ));
+                percent >= 1.0
+            }
+            TourStep::Dwell { seconds } => self.elapsed >= seconds,
+            TourStep::Annotate { ref text } => {
+                self.caption = text.clone();
+                true
+            }
+        }
+    }
+
+    fn recreate_panel(&mut self, ctx: &mut EventCtx) {
+        let stops = self.tour.stops();
+        let mut col = vec![Widget::row(vec![
+            Line(&self.tour.name).small_heading().into_widget(ctx),
+            ctx.style().btn_close_widget(ctx),
+        ])];
+        if !self.caption.is_empty() {
+            col.push(
+                Text::from(Line(&self.caption))
+                    .wrap_to_pct(ctx, 40)
+                    .into_widget(ctx),
+            );
+        }
+        col.push(Widget::row(vec![
+            ctx.style()
+                .btn_prev()
+                .disabled(self.step == 0)
+                .hotkey(Key::LeftArrow)
+                .build_widget(ctx, "previous stop"),
+            if self.paused || self.is_done() {
+                ctx.style()
+                    .btn_plain
+                    .icon("system/assets/speed/triangle.svg")
+                    .hotkey(Key::Space)
+                    .build_widget(ctx, "play")
+            } else {
+                ctx.style()
+                    .btn_plain
+                    .icon("system/assets/speed/pause.svg")
+                    .hotkey(Key::Space)
+                    .build_widget(ctx, "pause")
+            },
+            ctx.style()
+                .btn_next()
+                .disabled(self.is_done())
+                .hotkey(Key::RightArrow)
+                .build_widget(ctx, "next stop"),
+            if self.is_done() {
+                "Tour finished".text_widget(ctx)
+            } else {
+                format!("Stop {} of {}", self.current_stop() + 1, stops.len()).text_widget(ctx)
+            }
+            .centered_vert(),
+        ]));
+
+        self.panel = Panel::new_builder(Widget::col(col))
+            .aligned(HorizontalAlignment::Center, VerticalAlignment::Bottom)
+            .build(ctx);
+    }
+}
+
+impl<A: 'static> State<A> for TourPlayer {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut A) -> Transition<A> {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "play" => {
+                    self.paused = false;
+                    if self.is_done() {
+                        self.jump_to(ctx, 0);
+                    } else {
+                        // The camera may have been moved while paused
+                        let elapsed = self.elapsed;
+                        self.start_step(ctx, self.step);
+                        self.elapsed = elapsed;
+                    }
+                }
+                "pause" => {
+                    self.paused = true;
+                    self.recreate_panel(ctx);
+                }
+                "previous stop" => {
+                    let stops = self.tour.stops();
+                    let current = self.current_stop();
+                    // Restart the current stop, unless it only just started
+                    let idx = if self.step == stops[current] && self.elapsed < 1.0 {
+                        stops[current.saturating_sub(1)]
+                    } else {
+                        stops[current]
+                    };
+                    self.jump_to(ctx, idx);
+                }
+                "next stop" => {
+                    let idx = self
+                        .tour
+                        .stops()
+                        .into_iter()
+                        .find(|idx| *idx > self.step)
+                        .unwrap_or(self.tour.steps.len());
+                    self.jump_to(ctx, idx);
+                }
+                _ => unreachable!(),
+            }
+            return Transition::Keep;
+        }
+
+        if self.paused || self.is_done() {
+            ctx.canvas_movement();
+            return Transition::Keep;
+        }
+
+        if let Some(dt) = ctx.input.nonblocking_is_update_event() {
+            ctx.input.use_update_event();
+            let mut dt = dt.inner_seconds();
+            // Play any instant steps in the same frame
+            while !self.is_done() && self.play_step(ctx, dt) {
+                dt = 0.0;
+                self.start_step(ctx, self.step + 1);
+            }
+        }
+        if !self.is_done() {
+            ctx.request_update(UpdateType::Game);
+        }
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &A) {
+        self.panel.draw(g);
+    }
+}
+
+/// Build a tour by moving the camera around and recording stops.
+pub struct TourRecorder {
+    tour: CameraTour,
+    gps_bounds: GPSBounds,
+    /// Where to save the tour
+    path: String,
+    panel: Panel,
+}
+
+impl TourRecorder {
+    pub fn new_state<A: 'static>(
+        ctx: &mut EventCtx,
+        name: String,
+        gps_bounds: GPSBounds,
+        path: String,
+    ) -> Box<dyn State<A>> {
+        let mut recorder = TourRecorder {
+            tour: CameraTour {
+                name,
+                steps: Vec::new(),
+            },
+            gps_bounds,
+            path,
+            panel: Panel::empty(ctx),
+        };
+        recorder.recreate_panel(ctx, String::new());
+        Box::new(recorder)
+    }
+
+    fn recreate_panel(&mut self, ctx: &mut EventCtx, caption: String) {
+        let stops = self
+            .tour
+            .steps
+            .iter()
+            .filter(|step| matches!(step, TourStep::FlyTo { .. }))
+            .count();
+        self.panel = Panel::new_builder(Widget::col(vec![
+            Widget::row(vec![
+                Line("Record a guided tour")
+                    .small_heading()
+                    .into_widget(ctx),
+                ctx.style().btn_close_widget(ctx),
+            ]),
+            Text::from(
+                Line("Move the camera to each place to show, describe it, then add a stop")
+                    .secondary(),
+            )
+            .wrap_to_pct(ctx, 30)
+            .into_widget(ctx),
+            Widget::row(vec![
+                "Caption:".text_widget(ctx).centered_vert(),
+                TextBox::default_widget(ctx, "caption", caption),
+            ]),
+            Widget::row(vec![
+                "Fly there over".text_widget(ctx).centered_vert(),
+                Spinner::f64_widget(ctx, "fly seconds", (0.0, 30.0), 3.0, 0.5),
+                "seconds, then stay for".text_widget(ctx).centered_vert(),
+                Spinner::f64_widget(ctx, "dwell seconds", (0.0, 120.0), 5.0, 0.5),
+                "seconds".text_widget(ctx).centered_vert(),
+            ]),
+            Widget::row(vec![
+                ctx.style()
+                    .btn_solid_primary
+                    .text("Add stop here")
+                    .build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Remove last stop")
+                    .disabled(stops == 0)
+                    .build_def(ctx),
+            ]),
+            format!("{} stops, {:.0} seconds", stops, self.tour.total_seconds()).text_widget(ctx),
+            Widget::row(vec![
+                ctx.style()
+                    .btn_outline
+                    .text("Preview")
+                    .disabled(stops == 0)
+                    .build_def(ctx),
+                ctx.style()
+                    .btn_outline
+                    .text("Save")
+                    .disabled(stops == 0)
+                    .build_def(ctx),
+            ]),
+        ]))
+        .aligned(HorizontalAlignment::Right, VerticalAlignment::Top)
+        .build(ctx);
+    }
+}
+
+impl<A: 'static> State<A> for TourRecorder {
+    fn event(&mut self, ctx: &mut EventCtx, _: &mut A) -> Transition<A> {
+        ctx.canvas_movement();
+
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "Add stop here" => {
+                    let caption = self.panel.text_box("caption");
+                    self.tour.steps.push(TourStep::FlyTo {
+                        to: ctx.canvas.center_to_map_pt().to_gps(&self.gps_bounds),
+                        zoom: ctx.canvas.cam_zoom,
+                        seconds: self.panel.spinner::<RoundedF64>("fly seconds").0,
+                    });
+                    self.tour.steps.push(TourStep::Annotate { text: caption });
+                    self.tour.steps.push(TourStep::Dwell {
+                        seconds: self.panel.spinner::<RoundedF64>("dwell seconds").0,
+                    });
+                    self.recreate_panel(ctx, String::new());
+                }
+                "Remove last stop" => {
+                    let idx = *self.tour.stops().last().unwrap();
+                    self.tour.steps.truncate(idx);
+                    let caption = self.panel.text_box("caption");
+                    self.recreate_panel(ctx, caption);
+                }
+                "Preview" => {
+                    return Transition::Push(TourPlayer::new_state(
+                        ctx,
+                        self.tour.clone(),
+                        self.gps_bounds.clone(),
+                    ));
+                }
+                "Save" => {
+                    abstio::write_json(self.path.clone(), &self.tour);
+                    return Transition::Push(PopupMsg::new_state(
+                        ctx,
+                        "Tour saved",
+                        vec![format!("Saved to {}", self.path)],
+                    ));
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &A) {
+        self.panel.draw(g);
+    }
+}
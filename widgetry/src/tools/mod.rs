@@ -1,14 +1,18 @@
+mod camera_tour;
 mod lasso;
 mod load;
 mod popup;
 pub(crate) mod screenshot;
 mod url;
 pub(crate) mod warper;
+mod wizard;
 
+pub use camera_tour::{CameraTour, TourPlayer, TourRecorder, TourStep};
 pub use lasso::{Lasso, PolyLineLasso};
 pub use load::{FileLoader, FutureLoader, RawBytes};
-pub use popup::PopupMsg;
+pub use popup::{Confirmation, PopupMsg};
 pub use url::URLManager;
+pub use wizard::{Wizard, WizardBuilder, WizardStep};
 
 /// Store a cached key/value pair, only recalculating when the key changes.
 pub struct Cached<K: PartialEq + Clone, V> {
@@ -66,3 +66,82 @@ impl<A> State<A> for PopupMsg {
         self.panel.draw(g);
     }
 }
+
+/// Ask the user to confirm an action before doing it.
+pub struct Confirmation<A> {
+    panel: Panel,
+    // Wrapped in an Option so that we can consume it once
+    on_confirm: Option<Box<dyn FnOnce(&mut EventCtx, &mut A) -> Transition<A>>>,
+}
+
+impl<A: 'static> Confirmation<A> {
+    /// If the user confirms, the transition returned by `on_confirm` is used instead of popping
+    /// this dialog, so it should pop or replace it. `destructive` styles the button as dangerous,
+    /// for actions that can't be undone.
+    pub fn new_state(
+        ctx: &mut EventCtx,
+        title: &str,
+        lines: Vec<impl AsRef<str>>,
+        confirm_label: &str,
+        destructive: bool,
+        on_confirm: Box<dyn FnOnce(&mut EventCtx, &mut A) -> Transition<A>>,
+    ) -> Box<dyn State<A>> {
+        let mut txt = Text::new();
+        txt.add_line(Line(title).small_heading());
+        for l in lines {
+            txt.add_line(l);
+        }
+        let confirm = if destructive {
+            &ctx.style().btn_solid_destructive
+        } else {
+            &ctx.style().btn_solid_primary
+        };
+        Box::new(Confirmation {
+            panel: Panel::new_builder(Widget::col(vec![
+                txt.into_widget(ctx),
+                Widget::row(vec![
+                    ctx.style()
+                        .btn_outline
+                        .text("Cancel")
+                        .hotkey(Key::Escape)
+                        .build_def(ctx),
+                    confirm
+                        .text(confirm_label)
+                        .hotkey(Key::Enter)
+                        .build_widget(ctx, "confirm"),
+                ])
+                .align_right(),
+            ]))
+            .build(ctx),
+            on_confirm: Some(on_confirm),
+        })
+    }
+}
+
+impl<A: 'static> State<A> for Confirmation<A> {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut A) -> Transition<A> {
+        match self.panel.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
+                "Cancel" => Transition::Pop,
+                "confirm" => (self.on_confirm.take().unwrap())(ctx, app),
+                _ => unreachable!(),
+            },
+            _ => Transition::Keep,
+        }
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &A) {
+        g.fork_screenspace();
+        g.draw_polygon(
+            Color::BLACK.alpha(0.6),
+            Polygon::rectangle(g.canvas.window_width, g.canvas.window_height),
+        );
+        g.unfork();
+
+        self.panel.draw(g);
+    }
+}
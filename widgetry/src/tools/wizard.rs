@@ -0,0 +1,266 @@
+use geom::Polygon;
+
+use crate::{
+    Color, DrawBaselayer, EventCtx, GfxCtx, Key, Line, Outcome, Panel, State, Text, Transition,
+    Widget,
+};
+
+/// One page of a `Wizard`. `S` is state shared by all of the steps.
+pub struct WizardStep<A, S> {
+    title: String,
+    make: Box<dyn Fn(&mut EventCtx, &A, &S) -> Widget>,
+    validate: Box<dyn Fn(&Panel, &A, &mut S) -> Result<(), String>>,
+    on_click: Option<Box<dyn Fn(&mut EventCtx, &mut A, &mut S, &str) -> Transition<A>>>,
+}
+
+impl<A, S> WizardStep<A, S> {
+    /// `make` builds the contents of this step. Since the panel is rebuilt when the user goes
+    /// back, the contents should be filled out from the shared state.
+    pub fn new<I: Into<String>>(
+        title: I,
+        make: Box<dyn Fn(&mut EventCtx, &A, &S) -> Widget>,
+    ) -> WizardStep<A, S> {
+        WizardStep {
+            title: title.into(),
+            make,
+            validate: Box::new(|_, _, _| Ok(())),
+            on_click: None,
+        }
+    }
+
+    /// Before moving to the next step, read the values of named widgets from the panel into the
+    /// shared state. If this returns an error, the message is shown and the user stays on this
+    /// step.
+    pub fn validate(
+        mut self,
+        validate: Box<dyn Fn(&Panel, &A, &mut S) -> Result<(), String>>,
+    ) -> WizardStep<A, S> {
+        self.validate = validate;
+        self
+    }
+
+    /// Handle clicks on any extra buttons that `make` created.
+    pub fn on_click(
+        mut self,
+        on_click: Box<dyn Fn(&mut EventCtx, &mut A, &mut S, &str) -> Transition<A>>,
+    ) -> WizardStep<A, S> {
+        self.on_click = Some(on_click);
+        self
+    }
+}
+
+/// Walks the user through a sequence of steps, with back and next buttons. Each step can check
+/// its input before the user moves on.
+pub struct Wizard<A, S> {
+    title: String,
+    steps: Vec<WizardStep<A, S>>,
+    idx: usize,
+    state: S,
+    panel: Panel,
+
+    finish_label: String,
+    skip_label: Option<String>,
+    on_cancel: Option<Box<dyn Fn(&mut EventCtx, &mut A) -> Transition<A>>>,
+    on_finish: Box<dyn Fn(&mut EventCtx, &mut A, &mut S) -> Transition<A>>,
+}
+
+pub struct WizardBuilder<A, S> {
+    title: String,
+    state: S,
+    steps: Vec<WizardStep<A, S>>,
+    finish_label: String,
+    skip_label: Option<String>,
+    on_cancel: Option<Box<dyn Fn(&mut EventCtx, &mut A) -> Transition<A>>>,
+}
+
+impl<A: 'static, S: 'static> Wizard<A, S> {
+    pub fn builder<I: Into<String>>(title: I, state: S) -> WizardBuilder<A, S> {
+        WizardBuilder {
+            title: title.into(),
+            state,
+            steps: Vec::new(),
+            finish_label: "Finish".to_string(),
+            skip_label: None,
+            on_cancel: None,
+        }
+    }
+
+    /// The shared state, for callers that need to take something out of it later, like through
+    /// `Transition::ConsumeState`.
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    fn recreate_panel(&mut self, ctx: &mut EventCtx, app: &A) {
+        let step = &self.steps[self.idx];
+        let last_step = self.idx == self.steps.len() - 1;
+
+        let mut col = vec![Widget::row(vec![
+            Line(&self.title).small_heading().into_widget(ctx),
+            if self.on_cancel.is_some() {
+                ctx.style().btn_close_widget(ctx)
+            } else {
+                Widget::nothing()
+            },
+        ])];
+        if self.steps.len() > 1 {
+            let mut progress = format!("Step {} of {}", self.idx + 1, self.steps.len());
+            if !step.title.is_empty() {
+                progress = format!("{}: {}", progress, step.title);
+            }
+            col.push(Line(progress).secondary().into_widget(ctx));
+        } else if !step.title.is_empty() {
+            col.push(Line(&step.title).secondary().into_widget(ctx));
+        }
+        col.push((step.make)(ctx, app, &self.state));
+        col.push(Text::new().into_widget(ctx).named("error"));
+
+        let mut buttons = Vec::new();
+        if self.steps.len() > 1 {
+            buttons.push(
+                ctx.style()
+                    .btn_outline
+                    .text("Back")
+                    .disabled(self.idx == 0)
+                    .build_def(ctx),
+            );
+        }
+        if let Some(ref label) = self.skip_label {
+            if !last_step {
+                buttons.push(ctx.style().btn_outline.text(label).build_def(ctx));
+            }
+        }
+        buttons.push(
+            ctx.style()
+                .btn_solid_primary
+                .text(if last_step {
+                    self.finish_label.as_str()
+                } else {
+                    "Next"
+                })
+                .hotkey(Key::Enter)
+                .build_widget(ctx, "next"),
+        );
+        col.push(Widget::row(buttons).align_right());
+
+        self.panel = Panel::new_builder(Widget::col(col)).build(ctx);
+    }
+}
+
+impl<A: 'static, S: 'static> WizardBuilder<A, S> {
+    pub fn step(mut self, step: WizardStep<A, S>) -> WizardBuilder<A, S> {
+        self.steps.push(step);
+        self
+    }
+
+    /// The label of the button on the last step. Defaults to "Finish".
+    pub fn finish_label<I: Into<String>>(mut self, label: I) -> WizardBuilder<A, S> {
+        self.finish_label = label.into();
+        self
+    }
+
+    /// Show a button to jump straight to the last step, skipping validation.
+    pub fn skippable<I: Into<String>>(mut self, label: I) -> WizardBuilder<A, S> {
+        self.skip_label = Some(label.into());
+        self
+    }
+
+    /// Show a close button. Without this, the user must finish the wizard.
+    pub fn cancellable(
+        mut self,
+        on_cancel: Box<dyn Fn(&mut EventCtx, &mut A) -> Transition<A>>,
+    ) -> WizardBuilder<A, S> {
+        self.on_cancel = Some(on_cancel);
+        self
+    }
+
+    /// `on_finish` is called after the last step validates. If the transition it returns doesn't
+    /// pop or replace the wizard, the user stays on the last step.
+    pub fn build(
+        self,
+        ctx: &mut EventCtx,
+        app: &A,
+        on_finish: Box<dyn Fn(&mut EventCtx, &mut A, &mut S) -> Transition<A>>,
+    ) -> Box<dyn State<A>> {
+        assert!(!self.steps.is_empty());
+        let mut wizard = Wizard {
+            title: self.title,
+            steps: self.steps,
+            idx: 0,
+            state: self.state,
+            panel: Panel::empty(ctx),
+
+            finish_label: self.finish_label,
+            skip_label: self.skip_label,
+            on_cancel: self.on_cancel,
+            on_finish,
+        };
+        wizard.recreate_panel(ctx, app);
+        Box::new(wizard)
+    }
+}
+
+impl<A: 'static, S: 'static> State<A> for Wizard<A, S> {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut A) -> Transition<A> {
+        if let Outcome::Clicked(x) = self.panel.event(ctx) {
+            match x.as_ref() {
+                "close" => {
+                    return (self.on_cancel.as_ref().unwrap())(ctx, app);
+                }
+                "Back" => {
+                    self.idx -= 1;
+                    self.recreate_panel(ctx, app);
+                }
+                "next" => {
+                    if let Err(err) =
+                        (self.steps[self.idx].validate)(&self.panel, app, &mut self.state)
+                    {
+                        let txt = Text::from(Line(err).fg(ctx.style().text_destructive_color))
+                            .wrap_to_pct(ctx, 30);
+                        self.panel.replace(ctx, "error", txt.into_widget(ctx));
+                    } else if self.idx == self.steps.len() - 1 {
+                        return (self.on_finish)(ctx, app, &mut self.state);
+                    } else {
+                        self.idx += 1;
+                        self.recreate_panel(ctx, app);
+                    }
+                }
+                x if Some(x) == self.skip_label.as_deref() => {
+                    self.idx = self.steps.len() - 1;
+                    self.recreate_panel(ctx, app);
+                }
+                x => {
+                    let x = x.to_string();
+                    return (self.steps[self.idx].on_click.as_ref().unwrap())(
+                        ctx,
+                        app,
+                        &mut self.state,
+                        &x,
+                    );
+                }
+            }
+        }
+        // TODO Should the Panel for text widgets with wrapping do this instead?
+        if ctx.input.is_window_resized() {
+            self.recreate_panel(ctx, app);
+        }
+
+        Transition::Keep
+    }
+
+    fn draw_baselayer(&self) -> DrawBaselayer {
+        DrawBaselayer::PreviousState
+    }
+
+    fn draw(&self, g: &mut GfxCtx, _: &A) {
+        // This is a copy of grey_out_map from map_gui, with no dependencies on App
+        g.fork_screenspace();
+        g.draw_polygon(
+            Color::BLACK.alpha(0.6),
+            Polygon::rectangle(g.canvas.window_width, g.canvas.window_height),
+        );
+        g.unfork();
+
+        self.panel.draw(g);
+    }
+}
@@ -0,0 +1,213 @@
+use geom::{Distance, PolyLine, Polygon, Pt2D, UnitFmt};
+
+use crate::widgets::plots::{Axis, Brush, PlotOptions};
+use crate::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, Line, Outcome, ScreenDims, ScreenPt,
+    ScreenRectangle, Text, TextExt, Widget, WidgetImpl, WidgetOutput,
+};
+
+const ROW_HEIGHT: f64 = 40.0;
+
+/// Summarizes a few distributions, each drawn as a horizontal box (from the 25th to 75th
+/// percentile, with a line at the median) and whiskers (from the min to max). The rows share the
+/// same X axis. The user can drag across the plot to select a range of values; the widget then
+/// emits `Outcome::Changed(label)`, and the caller should look up `selected_range`.
+pub struct BoxPlot<X: Axis<X>> {
+    label: String,
+    draw: Drawable,
+
+    max_x: X,
+    // For tooltips, per row
+    summaries: Vec<Text>,
+    brush: Brush,
+    tooltip: Option<Text>,
+
+    top_left: ScreenPt,
+    dims: ScreenDims,
+}
+
+impl<X: Axis<X>> BoxPlot<X> {
+    /// Each row is described by (name, color, values). `label` is used to name the actual BoxPlot
+    /// widget. The result of this call can't be usefully `named`, since the plot is wrapped in
+    /// some containers for formatting. Only `max_x` and the width from `opts.dims` are used.
+    pub fn new_widget(
+        ctx: &EventCtx,
+        label: &str,
+        rows: Vec<(String, Color, Vec<X>)>,
+        opts: PlotOptions<X, usize>,
+        unit_fmt: UnitFmt,
+    ) -> Widget {
+        // Assume min_x is X::zero()
+        let max_x = opts.max_x.unwrap_or_else(|| {
+            rows.iter()
+                .filter_map(|(_, _, values)| values.iter().max().cloned())
+                .max()
+                .unwrap_or_else(X::zero)
+        });
+
+        let width = opts
+            .dims
+            .map(|dims| dims.width)
+            .unwrap_or_else(|| 0.22 * ctx.canvas.window_width);
+        let height = ROW_HEIGHT * (rows.len().max(1) as f64);
+
+        let mut batch = GeomBatch::new();
+        batch.push(Color::hex("#7C7C7C"), Polygon::rectangle(width, height));
+        // Line up the name of each row with its box
+        let mut names = GeomBatch::new();
+        names.push(Color::CLEAR, Polygon::rectangle(1.0, height));
+        let mut summaries = Vec::new();
+        for (idx, (name, color, mut values)) in rows.into_iter().enumerate() {
+            let txt = Text::from(&name).render(ctx).autocrop();
+            let txt_height = txt.get_dims().height;
+            names.append(txt.translate(0.0, ROW_HEIGHT * (idx as f64 + 0.5) - txt_height / 2.0));
+            if values.is_empty() {
+                summaries.push(Text::from(format!("{}: no data", name)));
+                continue;
+            }
+            values.sort();
+            let quantile = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+            let (min, p25, median, p75, max) = (
+                quantile(0.0),
+                quantile(0.25),
+                quantile(0.5),
+                quantile(0.75),
+                quantile(1.0),
+            );
+            let x = |value: X| value.to_percent(max_x).min(1.0) * width;
+            let center_y = ROW_HEIGHT * (idx as f64 + 0.5);
+            let thickness = Distance::meters(2.0);
+
+            if let Ok(pl) = PolyLine::new(vec![
+                Pt2D::new(x(min), center_y),
+                Pt2D::new(x(max), center_y),
+            ]) {
+                batch.push(Color::WHITE, pl.make_polygons(thickness));
+            }
+            for end in [min, max] {
+                batch.push(
+                    Color::WHITE,
+                    Polygon::rectangle(2.0, ROW_HEIGHT * 0.4)
+                        .translate(x(end) - 1.0, center_y - ROW_HEIGHT * 0.2),
+                );
+            }
+            batch.push(
+                color,
+                Polygon::rectangle((x(p75) - x(p25)).max(2.0), ROW_HEIGHT * 0.6)
+                    .translate(x(p25), center_y - ROW_HEIGHT * 0.3),
+            );
+            batch.push(
+                Color::BLACK,
+                Polygon::rectangle(2.0, ROW_HEIGHT * 0.6)
+                    .translate(x(median) - 1.0, center_y - ROW_HEIGHT * 0.3),
+            );
+
+            summaries.push(Text::from_multiline(vec![
+                Line(name).small_heading(),
+                Line(format!(
+                    "Min {}, 25% {}, median {}",
+                    min.prettyprint(&unit_fmt),
+                    p25.prettyprint(&unit_fmt),
+                    median.prettyprint(&unit_fmt)
+                )),
+                Line(format!(
+                    "75% {}, max {}",
+                    p75.prettyprint(&unit_fmt),
+                    max.prettyprint(&unit_fmt)
+                )),
+            ]));
+        }
+
+        let plot = BoxPlot {
+            label: label.to_string(),
+            draw: ctx.upload(batch),
+
+            max_x,
+            summaries,
+            brush: Brush::default(),
+            tooltip: None,
+
+            top_left: ScreenPt::new(0.0, 0.0),
+            dims: ScreenDims::new(width, height),
+        };
+
+        let num_x_labels = 3;
+        let mut row = Vec::new();
+        for i in 0..num_x_labels {
+            let percent_x = (i as f64) / ((num_x_labels - 1) as f64);
+            row.push(
+                max_x
+                    .from_percent(percent_x)
+                    .prettyprint(&unit_fmt)
+                    .text_widget(ctx),
+            );
+        }
+        let x_axis = Widget::custom_row(row).padding(10).evenly_spaced();
+
+        // Don't let the x-axis fill the parent container
+        Widget::custom_row(vec![
+            names.into_widget(ctx).margin_right(10),
+            Widget::custom_col(vec![Widget::new(Box::new(plot)).named(label), x_axis]),
+        ])
+        .container()
+    }
+
+    /// The selected range of values, inclusive on both ends.
+    pub fn selected_range(&self) -> Option<(X, X)> {
+        let (low, high) = self.brush.selected()?;
+        Some((self.max_x.from_percent(low), self.max_x.from_percent(high)))
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.brush.clear();
+    }
+}
+
+impl<X: Axis<X>> WidgetImpl for BoxPlot<X> {
+    fn get_dims(&self) -> ScreenDims {
+        self.dims
+    }
+
+    fn set_pos(&mut self, top_left: ScreenPt) {
+        self.top_left = top_left;
+    }
+
+    fn event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        if self
+            .brush
+            .event(ctx, self.top_left, self.dims, &|low, high| (low, high))
+        {
+            output.outcome = Outcome::Changed(self.label.clone());
+        }
+
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(cursor) = ctx.canvas.get_cursor_in_screen_space() {
+                if ScreenRectangle::top_left(self.top_left, self.dims).contains(cursor) {
+                    let idx = ((cursor.y - self.top_left.y) / ROW_HEIGHT) as usize;
+                    self.tooltip = self.summaries.get(idx).cloned();
+                }
+            }
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx) {
+        g.redraw_at(self.top_left, &self.draw);
+        self.brush.draw(g, self.top_left, self.dims);
+
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+
+    fn can_restore(&self) -> bool {
+        true
+    }
+
+    fn restore(&mut self, _: &mut EventCtx, prev: &dyn WidgetImpl) {
+        let prev = prev.downcast_ref::<BoxPlot<X>>().unwrap();
+        if prev.max_x == self.max_x {
+            self.brush = prev.brush.clone();
+        }
+    }
+}
@@ -5,7 +5,9 @@ use geom::{
     UnitFmt,
 };
 
-use crate::widgets::plots::{make_legend, thick_lineseries, Axis, PlotOptions};
+use crate::widgets::plots::{
+    grid_lines, make_legend, round_up_for_axis, thick_lineseries, Axis, PlotOptions,
+};
 use crate::{
     Color, Drawable, EventCtx, GeomBatch, GfxCtx, ScreenDims, ScreenPt, Series, Text, TextExt,
     Widget, WidgetImpl, WidgetOutput,
@@ -58,24 +60,27 @@ impl FanChart {
                 .unwrap_or_else(Y::zero)
         });
 
+        // Unless the caller fixed the range, extend it to a round value
+        let max_x = if opts.max_x.is_some() {
+            max_x
+        } else {
+            round_up_for_axis(max_x, &unit_fmt)
+        };
+        let max_y = if opts.max_y.is_some() {
+            max_y
+        } else {
+            round_up_for_axis(max_y, &unit_fmt)
+        };
+
         // TODO Tuned to fit the info panel. Instead these should somehow stretch to fill their
         // container.
         let width = 0.22 * ctx.canvas.window_width;
         let height = 0.2 * ctx.canvas.window_height;
 
         let mut batch = GeomBatch::new();
-        // Grid lines for the Y scale. Draw up to 10 lines max to cover the order of magnitude of
-        // the range.
-        // TODO This caps correctly, but if the max is 105, then suddenly we just have 2 grid
-        // lines.
+        // Grid lines for the Y scale, spaced at round values in the axis's units
         {
-            let order_of_mag = 10.0_f64.powf(max_y.to_f64().log10().ceil());
-            for i in 0..10 {
-                let y = max_y.from_f64(order_of_mag / 10.0 * (i as f64));
-                let pct = y.to_percent(max_y);
-                if pct > 1.0 {
-                    break;
-                }
+            for pct in grid_lines(max_y, &unit_fmt) {
                 batch.push(
                     Color::hex("#7C7C7C"),
                     PolyLine::must_new(vec![
@@ -88,13 +93,7 @@ impl FanChart {
         }
         // X axis grid
         if max_x != Time::START_OF_DAY {
-            let order_of_mag = 10.0_f64.powf(max_x.inner_seconds().log10().ceil());
-            for i in 0..10 {
-                let x = Time::START_OF_DAY + Duration::seconds(order_of_mag / 10.0 * (i as f64));
-                let pct = x.to_percent(max_x);
-                if pct > 1.0 {
-                    break;
-                }
+            for pct in grid_lines(max_x, &unit_fmt) {
                 batch.push(
                     Color::hex("#7C7C7C"),
                     PolyLine::must_new(vec![
@@ -0,0 +1,204 @@
+use geom::{Angle, Polygon, UnitFmt};
+
+use crate::widgets::plots::{Axis, Brush, PlotOptions};
+use crate::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, Outcome, ScreenDims, ScreenPt, ScreenRectangle,
+    Text, TextExt, Widget, WidgetImpl, WidgetOutput,
+};
+
+/// Counts how many values fall into equally-sized buckets. The user can click or drag across
+/// buckets to select a range of values; the widget then emits `Outcome::Changed(label)`, and the
+/// caller should look up `selected_range`.
+pub struct Histogram<X: Axis<X>> {
+    label: String,
+    draw: Drawable,
+
+    max_x: X,
+    counts: Vec<usize>,
+    brush: Brush,
+    tooltip: Option<Text>,
+
+    top_left: ScreenPt,
+    dims: ScreenDims,
+    unit_fmt: UnitFmt,
+}
+
+impl<X: Axis<X>> Histogram<X> {
+    /// `label` is used to name the actual Histogram widget. The result of this call can't be
+    /// usefully `named`, since the plot is wrapped in some containers for formatting.
+    pub fn new_widget(
+        ctx: &EventCtx,
+        label: &str,
+        values: Vec<X>,
+        num_buckets: usize,
+        color: Color,
+        opts: PlotOptions<X, usize>,
+        unit_fmt: UnitFmt,
+    ) -> Widget {
+        assert!(num_buckets > 0);
+        // Assume min_x is X::zero()
+        let max_x = opts
+            .max_x
+            .unwrap_or_else(|| values.iter().max().cloned().unwrap_or_else(X::zero));
+        let mut counts = vec![0; num_buckets];
+        for x in values {
+            counts[bucket(x.to_percent(max_x), num_buckets)] += 1;
+        }
+        let max_y = opts
+            .max_y
+            .unwrap_or_else(|| counts.iter().max().cloned().unwrap());
+
+        let dims = opts.dims.unwrap_or_else(|| {
+            ScreenDims::new(
+                0.22 * ctx.canvas.window_width,
+                0.2 * ctx.canvas.window_height,
+            )
+        });
+        let (width, height) = (dims.width, dims.height);
+
+        let mut batch = GeomBatch::new();
+        batch.push(Color::hex("#7C7C7C"), Polygon::rectangle(width, height));
+        let bar_width = width / (num_buckets as f64);
+        for (idx, cnt) in counts.iter().enumerate() {
+            let bar_height = cnt.to_percent(max_y).min(1.0) * height;
+            if bar_height == 0.0 {
+                continue;
+            }
+            // Leave a little gap between bars
+            batch.push(
+                color,
+                Polygon::rectangle((bar_width - 2.0).max(1.0), bar_height)
+                    .translate((idx as f64) * bar_width + 1.0, height - bar_height),
+            );
+        }
+
+        let plot = Histogram {
+            label: label.to_string(),
+            draw: ctx.upload(batch),
+
+            max_x,
+            counts,
+            brush: Brush::default(),
+            tooltip: None,
+
+            top_left: ScreenPt::new(0.0, 0.0),
+            dims,
+            unit_fmt,
+        };
+
+        let num_x_labels = 3;
+        let mut row = Vec::new();
+        for i in 0..num_x_labels {
+            let percent_x = (i as f64) / ((num_x_labels - 1) as f64);
+            let x = max_x.from_percent(percent_x);
+            let batch = Text::from(x.prettyprint(&unit_fmt))
+                .render(ctx)
+                .rotate(Angle::degrees(-15.0))
+                .autocrop();
+            row.push(batch.into_widget(ctx));
+        }
+        let x_axis = Widget::custom_row(row).padding(10).evenly_spaced();
+
+        let num_y_labels = 3;
+        let mut col = Vec::new();
+        for i in 0..num_y_labels {
+            let percent_y = (i as f64) / ((num_y_labels - 1) as f64);
+            col.push(
+                max_y
+                    .from_percent(percent_y)
+                    .prettyprint(&unit_fmt)
+                    .text_widget(ctx),
+            );
+        }
+        col.reverse();
+        let y_axis = Widget::custom_col(col).padding(10).evenly_spaced();
+
+        // Don't let the x-axis fill the parent container
+        Widget::custom_col(vec![
+            Widget::custom_row(vec![y_axis, Widget::new(Box::new(plot)).named(label)]),
+            x_axis,
+        ])
+        .container()
+    }
+
+    /// The range of values in the selected buckets, inclusive on both ends.
+    pub fn selected_range(&self) -> Option<(X, X)> {
+        let (low, high) = self.brush.selected()?;
+        Some((self.max_x.from_percent(low), self.max_x.from_percent(high)))
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.brush.clear();
+    }
+
+    fn num_buckets(&self) -> usize {
+        self.counts.len()
+    }
+}
+
+impl<X: Axis<X>> WidgetImpl for Histogram<X> {
+    fn get_dims(&self) -> ScreenDims {
+        self.dims
+    }
+
+    fn set_pos(&mut self, top_left: ScreenPt) {
+        self.top_left = top_left;
+    }
+
+    fn event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        let n = self.num_buckets();
+        let snap = |low: f64, high: f64| {
+            let n_f64 = n as f64;
+            (
+                (bucket(low, n) as f64) / n_f64,
+                ((bucket(high, n) + 1) as f64) / n_f64,
+            )
+        };
+        if self.brush.event(ctx, self.top_left, self.dims, &snap) {
+            output.outcome = Outcome::Changed(self.label.clone());
+        }
+
+        if ctx.redo_mouseover() {
+            self.tooltip = None;
+            if let Some(cursor) = ctx.canvas.get_cursor_in_screen_space() {
+                if ScreenRectangle::top_left(self.top_left, self.dims).contains(cursor) {
+                    let idx = bucket((cursor.x - self.top_left.x) / self.dims.width, n);
+                    let low = self.max_x.from_percent((idx as f64) / (n as f64));
+                    let high = self.max_x.from_percent(((idx + 1) as f64) / (n as f64));
+                    self.tooltip = Some(Text::from(format!(
+                        "{} to {}: {}",
+                        low.prettyprint(&self.unit_fmt),
+                        high.prettyprint(&self.unit_fmt),
+                        self.counts[idx].prettyprint(&self.unit_fmt)
+                    )));
+                }
+            }
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx) {
+        g.redraw_at(self.top_left, &self.draw);
+        self.brush.draw(g, self.top_left, self.dims);
+
+        if let Some(ref txt) = self.tooltip {
+            g.draw_mouse_tooltip(txt.clone());
+        }
+    }
+
+    fn can_restore(&self) -> bool {
+        true
+    }
+
+    fn restore(&mut self, _: &mut EventCtx, prev: &dyn WidgetImpl) {
+        let prev = prev.downcast_ref::<Histogram<X>>().unwrap();
+        if prev.num_buckets() == self.num_buckets() && prev.max_x == self.max_x {
+            self.brush = prev.brush.clone();
+        }
+    }
+}
+
+/// Which bucket does a percentage [0, 1] of the max value fall into? The max value itself goes in
+/// the last bucket.
+fn bucket(pct: f64, num_buckets: usize) -> usize {
+    ((pct.max(0.0) * (num_buckets as f64)) as usize).min(num_buckets - 1)
+}
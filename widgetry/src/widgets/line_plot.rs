@@ -1,6 +1,8 @@
 use geom::{Angle, Bounds, Circle, Distance, FindClosest, PolyLine, Pt2D, UnitFmt};
 
-use crate::widgets::plots::{make_legend, thick_lineseries, Axis, PlotOptions, Series};
+use crate::widgets::plots::{
+    grid_lines, make_legend, round_up_for_axis, thick_lineseries, Axis, PlotOptions, Series,
+};
 use crate::{
     Color, Drawable, EdgeInsets, EventCtx, GeomBatch, GfxCtx, ScreenDims, ScreenPt,
     ScreenRectangle, Text, TextExt, Widget, WidgetImpl, WidgetOutput,
@@ -56,6 +58,18 @@ impl<X: Axis<X>, Y: Axis<Y>> LinePlot<X, Y> {
                 .unwrap_or_else(Y::zero)
         });
 
+        // Unless the caller fixed the range, extend it to a round value
+        let max_x = if opts.max_x.is_some() {
+            max_x
+        } else {
+            round_up_for_axis(max_x, &unit_fmt)
+        };
+        let max_y = if opts.max_y.is_some() {
+            max_y
+        } else {
+            round_up_for_axis(max_y, &unit_fmt)
+        };
+
         // TODO: somehow stretch to fill their container.
         let default_dims = {
             let width = 0.23 * ctx.canvas.window_width;
@@ -68,18 +82,9 @@ impl<X: Axis<X>, Y: Axis<Y>> LinePlot<X, Y> {
         let height = dims.height;
 
         let mut batch = GeomBatch::new();
-        // Grid lines for the Y scale. Draw up to 10 lines max to cover the order of magnitude of
-        // the range.
-        // TODO This caps correctly, but if the max is 105, then suddenly we just have 2 grid
-        // lines.
+        // Grid lines for the Y scale, spaced at round values in the axis's units
         {
-            let order_of_mag = 10.0_f64.powf(max_y.to_f64().log10().ceil());
-            for i in 0..10 {
-                let y = max_y.from_f64(order_of_mag / 10.0 * (i as f64));
-                let pct = y.to_percent(max_y);
-                if pct > 1.0 {
-                    break;
-                }
+            for pct in grid_lines(max_y, &unit_fmt) {
                 batch.push(
                     Color::hex("#7C7C7C"),
                     PolyLine::must_new(vec![
@@ -92,13 +97,7 @@ impl<X: Axis<X>, Y: Axis<Y>> LinePlot<X, Y> {
         }
         // X axis grid
         if max_x != X::zero() {
-            let order_of_mag = 10.0_f64.powf(max_x.to_f64().log10().ceil());
-            for i in 0..10 {
-                let x = max_x.from_f64(order_of_mag / 10.0 * (i as f64));
-                let pct = x.to_percent(max_x);
-                if pct > 1.0 {
-                    break;
-                }
+            for pct in grid_lines(max_x, &unit_fmt) {
                 batch.push(
                     Color::hex("#7C7C7C"),
                     PolyLine::must_new(vec![
@@ -18,6 +18,7 @@ use crate::{
 };
 
 pub mod autocomplete;
+pub mod box_plot;
 pub mod button;
 pub mod compare_times;
 pub mod containers;
@@ -25,6 +26,7 @@ pub mod drag_drop;
 pub mod dropdown;
 pub mod fan_chart;
 pub mod filler;
+pub mod histogram;
 pub mod image;
 pub mod just_draw;
 pub mod line_plot;
@@ -40,6 +42,7 @@ pub mod table;
 pub mod tabs;
 pub mod text_box;
 pub mod toggle;
+pub mod virtual_list;
 
 pub const DEFAULT_CORNER_RADIUS: f64 = 5.0;
 
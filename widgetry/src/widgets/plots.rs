@@ -1,9 +1,12 @@
 use std::collections::HashSet;
 
 use abstutil::prettyprint_usize;
-use geom::{Circle, Distance, Duration, Percent, Polygon, Pt2D, Time, UnitFmt};
+use geom::{Circle, Distance, Duration, Percent, Polygon, Pt2D, Speed, Time, UnitFmt};
 
-use crate::{Color, EventCtx, GeomBatch, ScreenDims, TextExt, Toggle, Widget};
+use crate::{
+    Color, EventCtx, GeomBatch, GfxCtx, ScreenDims, ScreenPt, ScreenRectangle, TextExt, Toggle,
+    Widget,
+};
 
 #[derive(Default)]
 pub struct PlotOptions<X: Axis<X>, Y: Axis<Y>> {
@@ -39,6 +42,55 @@ pub trait Axis<T>: 'static + Copy + std::cmp::Ord + Default {
     fn to_f64(self) -> f64;
     fn from_f64(&self, x: f64) -> T;
     fn zero() -> T;
+    /// The spacing between grid lines for an axis from zero to this maximum, in the same units as
+    /// `to_f64`. Override this to land on values that read naturally in the type's units.
+    fn grid_interval(self, _: &UnitFmt) -> f64 {
+        nice_interval(self.to_f64(), &ONE_TWO_FIVE)
+    }
+}
+
+const ONE_TWO_FIVE: [f64; 3] = [1.0, 2.0, 5.0];
+
+/// Picks the smallest step from `steps` (repeated at every power of 10 beyond the last one) that
+/// covers `max` with a handful of grid lines.
+fn nice_interval(max: f64, steps: &[f64]) -> f64 {
+    let max_lines = 6.0;
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let mut scale = 1.0;
+    loop {
+        for step in steps {
+            if max / (step * scale) <= max_lines {
+                return step * scale;
+            }
+        }
+        scale *= 10.0;
+    }
+}
+
+/// Where to draw grid lines, as percentages along an axis from zero to `max`
+pub fn grid_lines<T: Axis<T>>(max: T, unit_fmt: &UnitFmt) -> Vec<f64> {
+    let step = max.grid_interval(unit_fmt);
+    let max = max.to_f64();
+    if max <= 0.0 {
+        return Vec::new();
+    }
+    (0..)
+        .map(|i| (i as f64) * step / max)
+        .take_while(|pct| *pct <= 1.0)
+        .collect()
+}
+
+/// Rounds up the maximum of an axis to an even number of grid intervals, so labels placed at the
+/// start, middle, and end of the axis show round values.
+pub fn round_up_for_axis<T: Axis<T>>(max: T, unit_fmt: &UnitFmt) -> T {
+    let x = max.to_f64();
+    if x <= 0.0 {
+        return max;
+    }
+    let step = 2.0 * max.grid_interval(unit_fmt);
+    max.from_f64((x / step).ceil() * step)
 }
 
 impl Axis<usize> for usize {
@@ -92,6 +144,18 @@ impl Axis<Duration> for Duration {
     fn zero() -> Duration {
         Duration::ZERO
     }
+    fn grid_interval(self, _: &UnitFmt) -> f64 {
+        duration_interval(self.inner_seconds())
+    }
+}
+
+/// Steps of seconds, minutes, and hours, in seconds
+fn duration_interval(max_seconds: f64) -> f64 {
+    let steps = [
+        1.0, 5.0, 10.0, 15.0, 30.0, 60.0, 300.0, 600.0, 900.0, 1800.0, 3600.0, 7200.0, 10800.0,
+        21600.0, 43200.0, 86400.0,
+    ];
+    nice_interval(max_seconds, &steps)
 }
 
 impl Axis<Time> for Time {
@@ -117,6 +181,9 @@ impl Axis<Time> for Time {
     fn zero() -> Time {
         Time::START_OF_DAY
     }
+    fn grid_interval(self, _: &UnitFmt) -> f64 {
+        duration_interval(self.inner_seconds())
+    }
 }
 
 impl Axis<Distance> for Distance {
@@ -142,6 +209,55 @@ impl Axis<Distance> for Distance {
     fn zero() -> Distance {
         Distance::ZERO
     }
+    fn grid_interval(self, unit_fmt: &UnitFmt) -> f64 {
+        if unit_fmt.metric {
+            nice_interval(self.inner_meters(), &ONE_TWO_FIVE)
+        } else {
+            // Feet for short distances, then tenths of a mile and miles
+            let feet = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0];
+            let miles = [0.1, 0.2, 0.5];
+            let steps: Vec<f64> = feet
+                .iter()
+                .map(|ft| Distance::feet(*ft).inner_meters())
+                .chain(miles.iter().map(|mi| Distance::miles(*mi).inner_meters()))
+                .collect();
+            nice_interval(self.inner_meters(), &steps)
+        }
+    }
+}
+
+impl Axis<Speed> for Speed {
+    fn from_percent(&self, percent: f64) -> Speed {
+        *self * percent
+    }
+    fn to_percent(self, max: Speed) -> f64 {
+        if max == Speed::ZERO {
+            0.0
+        } else {
+            self / max
+        }
+    }
+    fn prettyprint(self, unit_fmt: &UnitFmt) -> String {
+        self.to_string(unit_fmt)
+    }
+    fn to_f64(self) -> f64 {
+        self.inner_meters_per_second()
+    }
+    fn from_f64(&self, x: f64) -> Speed {
+        Speed::meters_per_second(x)
+    }
+    fn zero() -> Speed {
+        Speed::ZERO
+    }
+    fn grid_interval(self, unit_fmt: &UnitFmt) -> f64 {
+        // Round numbers of km/h or mph
+        let unit = if unit_fmt.metric {
+            Speed::km_per_hour(1.0)
+        } else {
+            Speed::miles_per_hour(1.0)
+        };
+        unit.inner_meters_per_second() * nice_interval(self / unit, &ONE_TWO_FIVE)
+    }
 }
 
 pub struct Series<X, Y> {
@@ -188,6 +304,80 @@ pub fn make_legend<X: Axis<X>, Y: Axis<Y>>(
     }
 }
 
+/// Lets the user click and drag across a plot to select ("brush") a range of the X axis. Clicking
+/// without dragging, or re-selecting the same range, clears the selection.
+#[derive(Clone, Default)]
+pub struct Brush {
+    // Both are percentages [0, 1] of the plot's width. The selection is always (low, high).
+    dragging_from: Option<(f64, Option<(f64, f64)>)>,
+    selected: Option<(f64, f64)>,
+}
+
+impl Brush {
+    /// `snap` adjusts a (low, high) selection, like to the edges of histogram buckets. Returns
+    /// true when the user finishes changing the selection.
+    pub fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        top_left: ScreenPt,
+        dims: ScreenDims,
+        snap: &dyn Fn(f64, f64) -> (f64, f64),
+    ) -> bool {
+        let cursor = match ctx.canvas.get_cursor_in_screen_space() {
+            Some(pt) => pt,
+            None => return false,
+        };
+        let pct = ((cursor.x - top_left.x) / dims.width).max(0.0).min(1.0);
+
+        if let Some((from, prev)) = self.dragging_from {
+            if ctx.input.left_mouse_button_released() {
+                self.dragging_from = None;
+                if let Some((low, high)) = self.selected {
+                    if high - low < 0.01 || self.selected == prev {
+                        self.selected = None;
+                    }
+                }
+                return true;
+            }
+            if ctx.input.get_moved_mouse().is_some() {
+                self.selected = Some(snap(from.min(pct), from.max(pct)));
+            }
+            return false;
+        }
+
+        if ctx.input.left_mouse_button_pressed()
+            && ScreenRectangle::top_left(top_left, dims).contains(cursor)
+        {
+            self.dragging_from = Some((pct, self.selected));
+            self.selected = Some(snap(pct, pct));
+        }
+        false
+    }
+
+    /// The current (low, high) selection as percentages of the plot's width, also while the user
+    /// is still dragging.
+    pub fn selected(&self) -> Option<(f64, f64)> {
+        self.selected
+    }
+
+    pub fn clear(&mut self) {
+        self.dragging_from = None;
+        self.selected = None;
+    }
+
+    pub fn draw(&self, g: &mut GfxCtx, top_left: ScreenPt, dims: ScreenDims) {
+        if let Some((low, high)) = self.selected {
+            g.fork_screenspace();
+            g.draw_polygon(
+                Color::hex("#4CA7E9").alpha(0.4),
+                Polygon::rectangle((high - low) * dims.width, dims.height)
+                    .translate(top_left.x + low * dims.width, top_left.y),
+            );
+            g.unfork();
+        }
+    }
+}
+
 // TODO If this proves useful, lift to geom
 pub fn thick_lineseries(pts: Vec<Pt2D>, width: Distance) -> Polygon {
     use lyon::math::{point, Point};
@@ -1,6 +1,6 @@
 use std::ops;
 
-use geom::{trim_f64, CornerRadii, Distance, Polygon, Pt2D};
+use geom::{trim_f64, CornerRadii, Distance, Polygon, Pt2D, UnitFmt};
 
 use crate::{
     include_labeled_bytes, Button, Drawable, EdgeInsets, EventCtx, GeomBatch, GfxCtx, Outcome,
@@ -342,3 +342,29 @@ impl Spinner<RoundedF64> {
         )
     }
 }
+
+impl Spinner<Distance> {
+    /// A spinner for distances, rendered and stepped in the user's preferred units. The step size
+    /// is given for both metric and imperial units, so values stay round in either.
+    pub fn distance_widget(
+        ctx: &EventCtx,
+        label: impl Into<String>,
+        (low, high): (Distance, Distance),
+        current: Distance,
+        (metric_step, imperial_step): (Distance, Distance),
+        unit_fmt: UnitFmt,
+    ) -> Widget {
+        Spinner::widget_with_custom_rendering(
+            ctx,
+            label,
+            (low, high),
+            current,
+            if unit_fmt.metric {
+                metric_step
+            } else {
+                imperial_step
+            },
+            Box::new(move |x| x.to_string(&unit_fmt)),
+        )
+    }
+}
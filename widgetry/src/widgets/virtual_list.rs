@@ -0,0 +1,271 @@
+use geom::Polygon;
+
+use crate::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, Key, Outcome, ScreenDims, ScreenPt,
+    ScreenRectangle, Widget, WidgetImpl, WidgetOutput,
+};
+
+const SCROLLBAR_WIDTH: f64 = 8.0;
+
+/// A long list of rows, where only the rows currently scrolled into view are rendered. Every row
+/// has the same height. The user scrolls with the mouse wheel, selects a row by clicking it, and
+/// after clicking in the list, can use the arrow keys, Page Up/Down, and Home/End. When the
+/// selection changes, the widget emits `Outcome::Changed(label)`, and the caller should look up
+/// `selected`.
+///
+/// The list does its own clipping, so it shouldn't be placed in a Panel that also scrolls.
+pub struct VirtualList<T> {
+    label: String,
+    rows: Vec<T>,
+    render_row: Box<dyn Fn(&EventCtx, &T) -> GeomBatch>,
+    row_height: f64,
+
+    // In pixels, from the top of the first row
+    scroll_y: f64,
+    selected: Option<usize>,
+    hovering: Option<usize>,
+    has_focus: bool,
+    // Only the visible rows, positioned relative to the top of the list
+    draw: Drawable,
+
+    top_left: ScreenPt,
+    dims: ScreenDims,
+}
+
+impl<T: 'static> VirtualList<T> {
+    /// `render_row` is called whenever a row scrolls into view; it should produce something
+    /// `row_height` tall, starting at (0, 0). The list shows `num_visible_rows` at once and
+    /// shrinks if there are fewer rows.
+    pub fn new_widget<F: 'static + Fn(&EventCtx, &T) -> GeomBatch>(
+        ctx: &EventCtx,
+        label: &str,
+        rows: Vec<T>,
+        render_row: F,
+        row_height: f64,
+        width: f64,
+        num_visible_rows: usize,
+    ) -> Widget {
+        let height = row_height * (rows.len().min(num_visible_rows).max(1) as f64);
+        let mut list = VirtualList {
+            label: label.to_string(),
+            rows,
+            render_row: Box::new(render_row),
+            row_height,
+
+            scroll_y: 0.0,
+            selected: None,
+            hovering: None,
+            has_focus: false,
+            draw: Drawable::empty(ctx),
+
+            top_left: ScreenPt::new(0.0, 0.0),
+            dims: ScreenDims::new(width, height),
+        };
+        list.redraw(ctx);
+        Widget::new(Box::new(list)).named(label)
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.selected.map(|idx| &self.rows[idx])
+    }
+
+    pub fn selected_idx(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Select a row and scroll to it. This doesn't emit an `Outcome`.
+    pub fn select(&mut self, ctx: &EventCtx, idx: Option<usize>) {
+        self.selected = idx.filter(|idx| *idx < self.rows.len());
+        if let Some(idx) = self.selected {
+            self.scroll_to(ctx, idx);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn max_scroll(&self) -> f64 {
+        (self.row_height * (self.rows.len() as f64) - self.dims.height).max(0.0)
+    }
+
+    fn num_visible_rows(&self) -> usize {
+        ((self.dims.height / self.row_height).floor() as usize).max(1)
+    }
+
+    fn set_scroll(&mut self, ctx: &EventCtx, scroll_y: f64) {
+        let scroll_y = scroll_y.max(0.0).min(self.max_scroll());
+        if scroll_y != self.scroll_y {
+            self.scroll_y = scroll_y;
+            self.redraw(ctx);
+        }
+    }
+
+    /// Scroll the minimum amount needed to fully show one row.
+    fn scroll_to(&mut self, ctx: &EventCtx, idx: usize) {
+        let top = self.row_height * (idx as f64);
+        if top < self.scroll_y {
+            self.set_scroll(ctx, top);
+        } else if top + self.row_height > self.scroll_y + self.dims.height {
+            self.set_scroll(ctx, top + self.row_height - self.dims.height);
+        }
+    }
+
+    fn redraw(&mut self, ctx: &EventCtx) {
+        let mut batch = GeomBatch::new();
+        if !self.rows.is_empty() {
+            let first = (self.scroll_y / self.row_height).floor() as usize;
+            let last = (((self.scroll_y + self.dims.height) / self.row_height).ceil() as usize)
+                .min(self.rows.len());
+            for idx in first..last {
+                batch.append(
+                    (self.render_row)(ctx, &self.rows[idx])
+                        .translate(0.0, self.row_height * (idx as f64) - self.scroll_y),
+                );
+            }
+
+            // A scrollbar that just indicates position
+            let max_scroll = self.max_scroll();
+            if max_scroll > 0.0 {
+                let thumb_height = (self.dims.height * self.dims.height
+                    / (self.row_height * (self.rows.len() as f64)))
+                    .max(20.0);
+                batch.push(
+                    ctx.style().btn_outline.fg.alpha(0.5),
+                    Polygon::rectangle(SCROLLBAR_WIDTH, thumb_height).translate(
+                        self.dims.width - SCROLLBAR_WIDTH,
+                        (self.dims.height - thumb_height) * self.scroll_y / max_scroll,
+                    ),
+                );
+            }
+        }
+        self.draw = ctx.upload(batch);
+    }
+
+    fn row_at(&self, pt: ScreenPt) -> Option<usize> {
+        if !ScreenRectangle::top_left(self.top_left, self.dims).contains(pt) {
+            return None;
+        }
+        let idx = ((pt.y - self.top_left.y + self.scroll_y) / self.row_height).floor() as usize;
+        if idx < self.rows.len() {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+
+    fn highlight(&self, g: &mut GfxCtx, idx: usize, color: Color) {
+        let y = self.row_height * (idx as f64) - self.scroll_y;
+        g.draw_polygon(
+            color,
+            Polygon::rectangle(self.dims.width, self.row_height)
+                .translate(self.top_left.x, self.top_left.y + y),
+        );
+    }
+}
+
+impl<T: 'static> WidgetImpl for VirtualList<T> {
+    fn get_dims(&self) -> ScreenDims {
+        self.dims
+    }
+
+    fn set_pos(&mut self, top_left: ScreenPt) {
+        self.top_left = top_left;
+    }
+
+    fn event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        let cursor = ctx.canvas.get_cursor_in_screen_space();
+        let inside = cursor
+            .map(|pt| ScreenRectangle::top_left(self.top_left, self.dims).contains(pt))
+            .unwrap_or(false);
+
+        if inside {
+            if let Some((_, dy)) = ctx.input.get_mouse_scroll() {
+                self.set_scroll(
+                    ctx,
+                    self.scroll_y - dy * (ctx.canvas.settings.gui_scroll_speed as f64),
+                );
+                // The rows moved under the cursor
+                self.hovering = cursor.and_then(|pt| self.row_at(pt));
+            }
+        }
+        if ctx.redo_mouseover() {
+            self.hovering = cursor.and_then(|pt| self.row_at(pt));
+        }
+
+        if ctx.normal_left_click() {
+            // Let other widgets see this event too, so they can manage their own focus.
+            ctx.input.unconsume_event();
+            self.has_focus = inside;
+            if let Some(idx) = self.hovering {
+                if self.selected != Some(idx) {
+                    self.selected = Some(idx);
+                    self.scroll_to(ctx, idx);
+                    output.outcome = Outcome::Changed(self.label.clone());
+                }
+            }
+        }
+
+        if !self.has_focus || self.rows.is_empty() {
+            return;
+        }
+        let last = self.rows.len() - 1;
+        let page = self.num_visible_rows();
+        let current = self.selected;
+        let next = if ctx.input.pressed(Key::UpArrow) {
+            Some(current.map(|idx| idx.saturating_sub(1)).unwrap_or(0))
+        } else if ctx.input.pressed(Key::DownArrow) {
+            Some(current.map(|idx| (idx + 1).min(last)).unwrap_or(0))
+        } else if ctx.input.pressed(Key::PageUp) {
+            Some(current.unwrap_or(0).saturating_sub(page))
+        } else if ctx.input.pressed(Key::PageDown) {
+            Some((current.unwrap_or(0) + page).min(last))
+        } else if ctx.input.pressed(Key::Home) {
+            Some(0)
+        } else if ctx.input.pressed(Key::End) {
+            Some(last)
+        } else {
+            None
+        };
+        if let Some(idx) = next {
+            self.scroll_to(ctx, idx);
+            if current != Some(idx) {
+                self.selected = Some(idx);
+                output.outcome = Outcome::Changed(self.label.clone());
+            }
+        }
+    }
+
+    fn draw(&self, g: &mut GfxCtx) {
+        g.enable_clipping(ScreenRectangle::top_left(self.top_left, self.dims));
+        g.fork_screenspace();
+        if let Some(idx) = self.selected {
+            self.highlight(g, idx, Color::hex("#4CA7E9").alpha(0.5));
+        }
+        if let Some(idx) = self.hovering {
+            if self.hovering != self.selected {
+                self.highlight(g, idx, Color::WHITE.alpha(0.1));
+            }
+        }
+        g.unfork();
+        g.redraw_at(self.top_left, &self.draw);
+        g.disable_clipping();
+    }
+
+    fn can_restore(&self) -> bool {
+        true
+    }
+
+    fn restore(&mut self, ctx: &mut EventCtx, prev: &dyn WidgetImpl) {
+        let prev = prev.downcast_ref::<VirtualList<T>>().unwrap();
+        if prev.rows.len() == self.rows.len() {
+            self.selected = prev.selected;
+            self.has_focus = prev.has_focus;
+            self.set_scroll(ctx, prev.scroll_y);
+        }
+    }
+}
@@ -3,10 +3,11 @@ use rand_xorshift::XorShiftRng;
 
 use geom::{Angle, Duration, Polygon, Pt2D, Time, UnitFmt};
 use widgetry::{
-    lctrl, Choice, Color, ContentMode, DragDrop, Drawable, EventCtx, Fill, GeomBatch, GfxCtx,
-    HorizontalAlignment, Image, Key, Line, LinePlot, Outcome, Panel, PanelDims, PersistentSplit,
-    PlotOptions, ScreenDims, Series, Settings, SharedAppState, StackAxis, State, TabController,
-    Text, TextExt, Texture, Toggle, Transition, UpdateType, VerticalAlignment, Widget,
+    lctrl, BoxPlot, Choice, Color, ContentMode, DragDrop, Drawable, EventCtx, Fill, GeomBatch,
+    GfxCtx, Histogram, HorizontalAlignment, Image, Key, Line, LinePlot, Outcome, Panel, PanelDims,
+    PersistentSplit, PlotOptions, ScreenDims, Series, Settings, SharedAppState, StackAxis, State,
+    TabController, Text, TextExt, Texture, Toggle, Transition, UpdateType, VerticalAlignment,
+    VirtualList, Widget,
 };
 
 pub fn main() {
@@ -151,8 +152,8 @@ impl State<App> for Demo {
         ctx.canvas_movement();
 
         // This dispatches event handling to all of the widgets inside.
-        if let Outcome::Clicked(x) = self.controls.event(ctx) {
-            match x.as_ref() {
+        match self.controls.event(ctx) {
+            Outcome::Clicked(x) => match x.as_ref() {
                 // These outcomes should probably be a custom enum per Panel, to be more
                 // typesafe.
                 "reset the stopwatch" => {
@@ -182,7 +183,32 @@ impl State<App> for Demo {
                         unimplemented!("clicked: {:?}", x);
                     }
                 }
-            }
+            },
+            Outcome::Changed(x) => match x.as_ref() {
+                "trip durations" => {
+                    let range = self
+                        .controls
+                        .find::<Histogram<Duration>>("trip durations")
+                        .selected_range();
+                    log::info!("selected trip durations: {:?}", range);
+                }
+                "durations by mode" => {
+                    let range = self
+                        .controls
+                        .find::<BoxPlot<Duration>>("durations by mode")
+                        .selected_range();
+                    log::info!("selected durations by mode: {:?}", range);
+                }
+                "long list" => {
+                    let row = self
+                        .controls
+                        .find::<VirtualList<usize>>("long list")
+                        .selected();
+                    log::info!("selected row {:?}", row);
+                }
+                _ => {}
+            },
+            _ => {}
         }
 
         // An update event means that no keyboard/mouse input happened, but time has passed.
@@ -420,6 +446,24 @@ fn make_tabs(ctx: &mut EventCtx) -> TabController {
         widgetry::Spinner::widget(ctx, "spinner", (0, 11), 1, 1),
         Text::from(Line("Drag & Drop Cards").big_heading_styled().size(18)).into_widget(ctx),
         build_drag_drop(ctx, 5).into_widget(ctx),
+        Text::from(Line("Brushing").big_heading_styled().size(18)).into_widget(ctx),
+        "Click or drag across a plot to select a range".text_widget(ctx),
+        build_distributions(ctx),
+        Text::from(Line("Long lists").big_heading_styled().size(18)).into_widget(ctx),
+        "Only the visible rows are rendered. Click, then use the arrow keys".text_widget(ctx),
+        VirtualList::new_widget(
+            ctx,
+            "long list",
+            (1..=100_000).collect::<Vec<usize>>(),
+            |ctx, n| {
+                Text::from(format!("Row {}: {} squared is {}", n, n, n * n))
+                    .render(ctx)
+                    .translate(5.0, 0.0)
+            },
+            30.0,
+            400.0,
+            8,
+        ),
     ]);
     tabs.push_tab(gallery_bar_item, gallery_content);
 
@@ -604,6 +648,41 @@ fn make_controls(ctx: &mut EventCtx, tabs: &mut TabController) -> Panel {
     .build(ctx)
 }
 
+// Some made-up trip durations to show off brushing
+fn build_distributions(ctx: &EventCtx) -> Widget {
+    let durations = |seed: usize| -> Vec<Duration> {
+        (0..200)
+            .map(|i| Duration::minutes((i * i + seed) % 97 / (1 + i % 3)))
+            .collect()
+    };
+    let unit_fmt = UnitFmt {
+        round_durations: true,
+        metric: true,
+    };
+    Widget::col(vec![
+        Histogram::new_widget(
+            ctx,
+            "trip durations",
+            durations(0),
+            10,
+            Color::hex("#5D9630"),
+            PlotOptions::fixed(),
+            unit_fmt,
+        ),
+        BoxPlot::new_widget(
+            ctx,
+            "durations by mode",
+            vec![
+                ("Walk".to_string(), Color::hex("#DF8C3D"), durations(1)),
+                ("Bike".to_string(), Color::hex("#5D9630"), durations(7)),
+                ("Drive".to_string(), Color::hex("#4CA7E9"), durations(13)),
+            ],
+            PlotOptions::fixed(),
+            unit_fmt,
+        ),
+    ])
+}
+
 fn build_drag_drop(ctx: &EventCtx, num_cards: usize) -> DragDrop<usize> {
     fn build_card(ctx: &EventCtx, num: usize) -> (ScreenDims, GeomBatch, GeomBatch, GeomBatch) {
         let dims = ScreenDims::new(100.0 + (num % 2) as f64 * 50.0, 150.0);